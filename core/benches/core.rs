@@ -0,0 +1,15 @@
+//! `cargo bench -p minesweeper-core` prints the timings of the hot paths on
+//! every board size in `bench::SIZES`. An argument that isn't a flag keeps
+//! only the operations whose name contains it, e.g. `cargo bench -p
+//! minesweeper-core -- solver`.
+
+use minesweeper_core::bench;
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let mut measurements = bench::run(&bench::SIZES, bench::BUDGET);
+    if let Some(filter) = filter {
+        measurements.retain(|measurement| measurement.operation.contains(&filter));
+    }
+    print!("{}", bench::table(&measurements));
+}
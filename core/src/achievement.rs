@@ -0,0 +1,75 @@
+//! Achievements, unlocked by finished games and kept in the stats file.
+//!
+//! Each one is checked when a game is recorded, against that game and the
+//! history before it, and stays unlocked once it has been. Frontends show
+//! them by id: `achievement-ID` names one and `achievement-ID-about`
+//! describes it.
+
+use std::time::Duration;
+
+use crate::stats::{Record, Stats};
+
+const INTERMEDIATE: (usize, usize, usize) = (16, 16, 40);
+const EXPERT: (usize, usize, usize) = (30, 16, 99);
+
+pub struct Achievement {
+    pub id: &'static str,
+    /// Whether `record`, just added to `stats`, unlocks it.
+    pub unlocked_by: fn(&Stats, &Record) -> bool,
+}
+
+/// Every achievement, in the order the browser lists them.
+pub const ALL: &[Achievement] = &[
+    Achievement {
+        id: "first-win",
+        unlocked_by: |_, record| record.won,
+    },
+    Achievement {
+        id: "intermediate",
+        unlocked_by: |_, record| record.won && record.size() == INTERMEDIATE,
+    },
+    Achievement {
+        id: "expert",
+        unlocked_by: |_, record| record.won && record.size() == EXPERT,
+    },
+    Achievement {
+        id: "expert-100",
+        unlocked_by: |_, record| {
+            record.won
+                && !record.is_assisted()
+                && record.size() == EXPERT
+                && record.time < Duration::from_secs(100)
+        },
+    },
+    Achievement {
+        id: "no-flags",
+        unlocked_by: |_, record| record.won && record.flags == 0 && record.mines >= 10,
+    },
+    Achievement {
+        id: "efficient",
+        unlocked_by: |_, record| record.won && record.clicks > 0 && record.wasted == 0,
+    },
+    Achievement {
+        id: "daily-7",
+        unlocked_by: |stats, record| record.won && win_streak_days(stats, record.day()) >= 7,
+    },
+    Achievement {
+        id: "games-100",
+        unlocked_by: |stats, _| stats.records.len() >= 100,
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static Achievement> {
+    ALL.iter().find(|achievement| achievement.id == id)
+}
+
+/// Consecutive days up to and including `day` with at least one win.
+fn win_streak_days(stats: &Stats, day: u64) -> u64 {
+    let won_on = |day: u64| {
+        stats
+            .records
+            .iter()
+            .any(|record| record.won && record.day() == day)
+    };
+    (0..=day).take_while(|&back| won_on(day - back)).count() as u64
+}
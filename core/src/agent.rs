@@ -0,0 +1,267 @@
+//! Bots that play classic games through the same information a human has.
+
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::board::{Board, Pos};
+use crate::protocol::{self, CellView};
+use crate::rules::Ruleset;
+
+/// What an agent can see of the board: revealed cells only.
+#[derive(Clone, Debug)]
+pub struct View {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub cells: Vec<CellView>,
+    /// Which cells the numbers count. The solver proves nothing once they
+    /// stack more than one mine on a cell.
+    pub rules: Ruleset,
+}
+
+impl View {
+    pub fn of(board: &Board) -> Self {
+        Self {
+            width: board.width(),
+            height: board.height(),
+            mines: board.mines(),
+            cells: protocol::view(board),
+            rules: board.rules(),
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> CellView {
+        self.cells[row * self.width + col]
+    }
+
+    /// The up to eight cells the number at `(row, col)` counts.
+    pub fn neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.around(row, col).collect()
+    }
+
+    /// Like `neighbors`, in the same order, without allocating.
+    pub(crate) fn around(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.rules
+            .around(self.width, self.height, Pos::new(row, col))
+            .map(|pos| (pos.row, pos.col))
+    }
+
+    pub fn hidden(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.get(row, col) == CellView::Hidden)
+            .collect()
+    }
+
+    /// Whether the cell can be seen through the rules' fog, so played; see
+    /// `Board::in_sight`.
+    pub fn in_sight(&self, row: usize, col: usize) -> bool {
+        self.rules.fog == 0 || self.sight(Pos::new(row, col), self.anything_seen())
+    }
+
+    /// Hidden cells in sight, the ones a move can reveal.
+    pub fn playable(&self) -> Vec<(usize, usize)> {
+        let seen = self.anything_seen();
+        self.hidden()
+            .into_iter()
+            .filter(|&(row, col)| self.sight(Pos::new(row, col), seen))
+            .collect()
+    }
+
+    fn anything_seen(&self) -> bool {
+        self.cells
+            .iter()
+            .any(|cell| !matches!(cell, CellView::Hidden | CellView::Flagged))
+    }
+
+    fn sight(&self, pos: Pos, seen: bool) -> bool {
+        !seen
+            || self.rules.in_sight(self.width, self.height, pos, |near| {
+                !matches!(
+                    self.get(near.row, near.col),
+                    CellView::Hidden | CellView::Flagged
+                )
+            })
+    }
+
+    /// Whether `(row, col)` shows a mine: one that went off while the rules
+    /// had a life to spare, a dud, or one captured in a match.
+    pub fn shows_mine(&self, row: usize, col: usize) -> bool {
+        matches!(self.get(row, col), CellView::Mine | CellView::Captured(_))
+    }
+
+    /// Mines still to be found, leaving out the ones on show.
+    pub fn mines_unseen(&self) -> usize {
+        let shown = self
+            .cells
+            .iter()
+            .filter(|cell| matches!(cell, CellView::Mine | CellView::Captured(_)))
+            .count();
+        self.mines.saturating_sub(shown)
+    }
+
+    /// Hidden cells including flagged ones, which may be wrong.
+    pub fn hidden_or_flagged(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                matches!(self.get(row, col), CellView::Hidden | CellView::Flagged)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AgentMove {
+    pub row: usize,
+    pub col: usize,
+    /// Whether the agent could not prove the cell safe.
+    pub guess: bool,
+}
+
+pub trait Agent {
+    fn name(&self) -> &str;
+
+    /// Called before every game so agents can drop what they learned.
+    fn reset(&mut self, seed: u64);
+
+    /// Picks the next cell to reveal, or `None` to give up.
+    fn next_move(&mut self, view: &View) -> Option<AgentMove>;
+}
+
+/// Every agent that ships with the crate.
+pub fn registered() -> Vec<Box<dyn Agent>> {
+    vec![
+        Box::new(RandomAgent::new()),
+        Box::new(SinglePointAgent::new()),
+    ]
+}
+
+/// Reveals hidden cells uniformly at random.
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Default for RandomAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn next_move(&mut self, view: &View) -> Option<AgentMove> {
+        let &(row, col) = view.playable().choose(&mut self.rng)?;
+        Some(AgentMove {
+            row,
+            col,
+            guess: true,
+        })
+    }
+}
+
+/// Looks at one number at a time: if its known mines already match it, the
+/// rest of its hidden neighbours are safe; if its hidden neighbours are all
+/// needed to reach it, they are all mines. Guesses at random when stuck.
+pub struct SinglePointAgent {
+    rng: StdRng,
+    mines: HashSet<(usize, usize)>,
+}
+
+impl SinglePointAgent {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(0),
+            mines: HashSet::new(),
+        }
+    }
+
+    fn deduce(&mut self, view: &View) -> Option<(usize, usize)> {
+        loop {
+            let mut progress = false;
+            for row in 0..view.height {
+                for col in 0..view.width {
+                    let CellView::Number(n) = view.get(row, col) else {
+                        continue;
+                    };
+                    let hidden: Vec<_> = view
+                        .neighbors(row, col)
+                        .into_iter()
+                        .filter(|&(r, c)| view.get(r, c) == CellView::Hidden)
+                        .collect();
+                    let known = hidden.iter().filter(|pos| self.mines.contains(pos)).count();
+
+                    if known == n {
+                        if let Some(&safe) = hidden.iter().find(|pos| !self.mines.contains(pos)) {
+                            return Some(safe);
+                        }
+                    } else if hidden.len() == n {
+                        for pos in hidden {
+                            progress |= self.mines.insert(pos);
+                        }
+                    }
+                }
+            }
+            if !progress {
+                return None;
+            }
+        }
+    }
+}
+
+impl Default for SinglePointAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for SinglePointAgent {
+    fn name(&self) -> &str {
+        "single-point"
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.mines.clear();
+    }
+
+    fn next_move(&mut self, view: &View) -> Option<AgentMove> {
+        if let Some((row, col)) = self.deduce(view) {
+            return Some(AgentMove {
+                row,
+                col,
+                guess: false,
+            });
+        }
+
+        let candidates: Vec<_> = view
+            .playable()
+            .into_iter()
+            .filter(|pos| !self.mines.contains(pos))
+            .collect();
+        let &(row, col) = candidates.choose(&mut self.rng)?;
+        Some(AgentMove {
+            row,
+            col,
+            guess: true,
+        })
+    }
+}
@@ -0,0 +1,174 @@
+//! Timings of the hot paths, so a change that slows them down shows up in
+//! numbers rather than in a laggy game.
+//!
+//! Each operation runs on boards of several sizes: generating a board,
+//! checking every cell's number, flooding the largest opening, and the
+//! solver's single-point and full passes over the board that opening
+//! leaves, with one `Solver` kept across runs. An operation runs over and
+//! over for its time budget; the median run is the figure to compare,
+//! with the fastest and slowest beside it to show the noise. `cargo bench -p minesweeper-core` and the
+//! game's `--bench-report` both print the same table.
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::agent::View;
+use crate::board::{Board, CellState, CellType};
+use crate::solver::Solver;
+use crate::verify;
+
+/// Width, height and mines of the boards measured by default: beginner,
+/// expert, and two far larger than anyone plays by hand.
+pub const SIZES: [(usize, usize, usize); 4] = [
+    (9, 9, 10),
+    (30, 16, 99),
+    (100, 100, 1600),
+    (500, 500, 40_000),
+];
+
+/// Time each operation gets on each board by default.
+pub const BUDGET: Duration = Duration::from_millis(500);
+
+const SEED: u64 = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub operation: &'static str,
+    pub size: (usize, usize, usize),
+    pub runs: usize,
+    pub median: Duration,
+    pub fastest: Duration,
+    pub slowest: Duration,
+}
+
+/// Runs `routine` on a fresh value from `setup` until `budget` is spent,
+/// and at least once, timing only `routine`.
+pub fn measure<T, R>(
+    budget: Duration,
+    mut setup: impl FnMut() -> T,
+    mut routine: impl FnMut(T) -> R,
+) -> (usize, Duration, Duration, Duration) {
+    let mut times = Vec::new();
+    let started = Instant::now();
+    while times.is_empty() || started.elapsed() < budget {
+        let input = setup();
+        let run = Instant::now();
+        black_box(routine(black_box(input)));
+        times.push(run.elapsed());
+    }
+    times.sort_unstable();
+    (
+        times.len(),
+        times[times.len() / 2],
+        times[0],
+        times[times.len() - 1],
+    )
+}
+
+/// Every operation on boards of each of `sizes`.
+pub fn run(sizes: &[(usize, usize, usize)], budget: Duration) -> Vec<Measurement> {
+    let mut measurements = Vec::new();
+    for &size in sizes {
+        let (width, height, mines) = size;
+        let mut record = |operation, (runs, median, fastest, slowest)| {
+            measurements.push(Measurement {
+                operation,
+                size,
+                runs,
+                median,
+                fastest,
+                slowest,
+            });
+        };
+        let generated = || Board::with_seed(width, height, mines, SEED).expect("the size is valid");
+        let board = generated();
+        record("generate", measure(budget, || (), |()| generated()));
+        record(
+            "numbers",
+            measure(budget, || (), |()| verify::numbers(&board)),
+        );
+
+        let start = largest_opening(&board);
+        record(
+            "flood",
+            measure(
+                budget,
+                || board.clone(),
+                |mut board| {
+                    board.reveal_cells(start.0, start.1);
+                    board
+                },
+            ),
+        );
+
+        let mut opened = board.clone();
+        opened.reveal_cells(start.0, start.1);
+        let view = View::of(&opened);
+        // One solver for every run, as the game keeps one between moves
+        let mut solver = Solver::new();
+        record(
+            "single point",
+            measure(budget, || (), |()| solver.single_point(&view).mines.len()),
+        );
+        record(
+            "solver",
+            measure(budget, || (), |()| solver.solve(&view).mines.len()),
+        );
+    }
+    measurements
+}
+
+/// The empty cell whose opening uncovers the most, or the top left corner
+/// on a board without one.
+fn largest_opening(board: &Board) -> (usize, usize) {
+    // Each opening is revealed once, and its cells skipped after that
+    let mut opened = board.clone();
+    let mut best = ((0, 0), 0);
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let cell = opened.cell(row, col);
+            if cell.cell_type != CellType::Empty || cell.cell_state != CellState::Hidden {
+                continue;
+            }
+            let revealed = opened.reveal_cells(row, col).len();
+            if revealed > best.1 {
+                best = ((row, col), revealed);
+            }
+        }
+    }
+    best.0
+}
+
+/// The measurements as a table, one line each.
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<14} {:<16} {:>12} {:>12} {:>12} {:>8}",
+        "operation", "board", "median", "fastest", "slowest", "runs"
+    );
+    for measurement in measurements {
+        let (width, height, mines) = measurement.size;
+        let _ = writeln!(
+            out,
+            "{:<14} {:<16} {:>12} {:>12} {:>12} {:>8}",
+            measurement.operation,
+            format!("{}x{}x{}", width, height, mines),
+            time(measurement.median),
+            time(measurement.fastest),
+            time(measurement.slowest),
+            measurement.runs,
+        );
+    }
+    out
+}
+
+fn time(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    match nanos {
+        0..=9_999 => format!("{}ns", nanos),
+        10_000..=9_999_999 => format!("{:.1}µs", nanos as f64 / 1e3),
+        _ => format!("{:.2}ms", nanos as f64 / 1e6),
+    }
+}
@@ -0,0 +1,1131 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::placement::{Generator, Uniform};
+use crate::rules::{Adjacency, Reveal, Ruleset, Win};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellType {
+    Mine,
+    Number(usize),
+    Empty,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellState {
+    Visible,
+    Hidden,
+    Flagged,
+}
+
+/// What a mine does when it goes off; see `Ruleset::duds` and
+/// `Ruleset::bigs`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MineKind {
+    /// Costs a life, or the game.
+    #[default]
+    Normal,
+    /// Costs time instead.
+    Dud,
+    /// Costs a life and sets off the 3x3 area around it.
+    Big,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub cell_type: CellType,
+    pub cell_state: CellState,
+    pub owner: Option<usize>,
+}
+
+impl Cell {
+    pub fn new(cell_type: CellType, cell_state: CellState) -> Self {
+        Self {
+            cell_type,
+            cell_state,
+            owner: None,
+        }
+    }
+
+    pub fn update_state(&mut self, cell_state: CellState) {
+        self.cell_state = cell_state;
+    }
+}
+
+/// A cell packed into two bytes, so a million-cell board takes two
+/// megabytes.
+///
+/// The low five bits are the type: 0 for empty, the count for a number and
+/// `MINE` for a mine. Bits 5-6 hold the state, bits 7-8 the owner plus
+/// one, with 0 meaning no owner, bits 9-10 the mines in a mined cell
+/// beyond the first, bit 11 whether a liar's number lies low, bit 12
+/// whether the cell is locked, bit 13 whether a blind number was shown and
+/// bits 14-15 the kind of a mine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Packed(u16);
+
+impl Packed {
+    const MINE: u16 = 0x1f;
+    const TYPE: u16 = 0x1f;
+    const STATE_SHIFT: u16 = 5;
+    const OWNER_SHIFT: u16 = 7;
+    const LOAD_SHIFT: u16 = 9;
+    const LOW: u16 = 1 << 11;
+    const LOCKED: u16 = 1 << 12;
+    const SHOWN: u16 = 1 << 13;
+    const KIND_SHIFT: u16 = 14;
+
+    /// A hidden empty cell.
+    const EMPTY: Packed = Packed(1 << Self::STATE_SHIFT);
+
+    fn new(cell: &Cell) -> Self {
+        let mut packed = Packed(0);
+        packed.set_type(cell.cell_type);
+        packed.set_state(cell.cell_state);
+        packed.set_owner(cell.owner);
+        packed
+    }
+
+    fn unpack(self) -> Cell {
+        Cell {
+            cell_type: self.cell_type(),
+            cell_state: self.state(),
+            owner: self.owner(),
+        }
+    }
+
+    fn is_mine(self) -> bool {
+        self.0 & Self::TYPE == Self::MINE
+    }
+
+    fn cell_type(self) -> CellType {
+        match self.0 & Self::TYPE {
+            0 => CellType::Empty,
+            Self::MINE => CellType::Mine,
+            n => CellType::Number(n as usize),
+        }
+    }
+
+    /// Numbers above 30 don't fit and are stored as 30; no cell has more
+    /// than eight neighbours of three mines each.
+    fn set_type(&mut self, cell_type: CellType) {
+        let bits = match cell_type {
+            CellType::Empty => 0,
+            CellType::Mine => Self::MINE,
+            CellType::Number(n) => n.min(Self::MINE as usize - 1) as u16,
+        };
+        self.0 = (self.0 & !Self::TYPE) | bits;
+    }
+
+    fn state(self) -> CellState {
+        match (self.0 >> Self::STATE_SHIFT) & 0b11 {
+            0 => CellState::Visible,
+            1 => CellState::Hidden,
+            _ => CellState::Flagged,
+        }
+    }
+
+    fn set_state(&mut self, cell_state: CellState) {
+        let bits = match cell_state {
+            CellState::Visible => 0,
+            CellState::Hidden => 1,
+            CellState::Flagged => 2,
+        };
+        self.0 = (self.0 & !(0b11 << Self::STATE_SHIFT)) | (bits << Self::STATE_SHIFT);
+    }
+
+    fn owner(self) -> Option<usize> {
+        match (self.0 >> Self::OWNER_SHIFT) & 0b11 {
+            0 => None,
+            owner => Some(owner as usize - 1),
+        }
+    }
+
+    /// Owners beyond the third player don't fit and are dropped.
+    fn set_owner(&mut self, owner: Option<usize>) {
+        let bits = match owner {
+            Some(player) if player < 3 => player as u16 + 1,
+            _ => 0,
+        };
+        self.0 = (self.0 & !(0b11 << Self::OWNER_SHIFT)) | (bits << Self::OWNER_SHIFT);
+    }
+
+    /// How many mines the cell holds, 0 unless it is a mine.
+    fn load(self) -> usize {
+        match self.is_mine() {
+            true => 1 + ((self.0 >> Self::LOAD_SHIFT) & 0b11) as usize,
+            false => 0,
+        }
+    }
+
+    fn is_locked(self) -> bool {
+        self.0 & Self::LOCKED != 0
+    }
+
+    fn set_locked(&mut self, locked: bool) {
+        self.0 = (self.0 & !Self::LOCKED) | if locked { Self::LOCKED } else { 0 };
+    }
+
+    fn kind(self) -> MineKind {
+        match (self.0 >> Self::KIND_SHIFT) & 0b11 {
+            1 => MineKind::Dud,
+            2 => MineKind::Big,
+            _ => MineKind::Normal,
+        }
+    }
+
+    fn set_kind(&mut self, kind: MineKind) {
+        let bits = match kind {
+            MineKind::Normal => 0,
+            MineKind::Dud => 1,
+            MineKind::Big => 2,
+        };
+        self.0 = (self.0 & !(0b11 << Self::KIND_SHIFT)) | (bits << Self::KIND_SHIFT);
+    }
+
+    /// Whether a number under the blind rule was shown by a flag.
+    fn is_shown(self) -> bool {
+        self.0 & Self::SHOWN != 0
+    }
+
+    fn set_shown(&mut self) {
+        self.0 |= Self::SHOWN;
+    }
+
+    fn lies_low(self) -> bool {
+        self.0 & Self::LOW != 0
+    }
+
+    fn set_lies_low(&mut self, low: bool) {
+        self.0 = (self.0 & !Self::LOW) | if low { Self::LOW } else { 0 };
+    }
+
+    /// Loads beyond `Ruleset::MAX_MINES_PER_CELL` are stored as the most.
+    fn set_load(&mut self, load: usize) {
+        let bits = load.clamp(1, Ruleset::MAX_MINES_PER_CELL as usize) as u16 - 1;
+        self.0 = (self.0 & !(0b11 << Self::LOAD_SHIFT)) | (bits << Self::LOAD_SHIFT);
+    }
+}
+
+/// A cell position on the board. Rows count down from the top, columns
+/// across from the left, so `row < height` and `col < width`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self { row, col }
+    }
+}
+
+/// Every cell in row-major order, so `Pos { row, col }` lives at
+/// `row * width + col`. Only `Board::index` does that arithmetic.
+type State = Vec<Packed>;
+
+/// An opening being uncovered a few cells at a time, so revealing a huge
+/// one can be spread over several frames. Created by `Board::start_reveal`
+/// and advanced by `Board::spread`.
+///
+/// A finished flood can be handed to `Board::restart_reveal` to uncover
+/// the next opening with the memory it already has. The board's own cell
+/// states mark what has been visited, so nothing else is needed.
+#[derive(Clone, Debug, Default)]
+pub struct Flood {
+    queue: VecDeque<(usize, usize)>,
+    revealed: Vec<(usize, usize)>,
+}
+
+impl Flood {
+    pub fn is_done(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Every cell uncovered so far, starting with the one revealed first.
+    pub fn revealed(&self) -> &[(usize, usize)] {
+        &self.revealed
+    }
+}
+
+/// How many mines touch each cell of a `width` by `height` board, given
+/// where the mines are, both in row-major order. The 3x3 square around a
+/// cell is summed across each row first and then down each column, so
+/// every cell is read six times rather than nine, whatever the density.
+/// Off the board counts as no mine, the same at every edge. A mine's own
+/// count includes itself, so callers skip mines.
+pub(crate) fn mine_counts(width: usize, height: usize, mines: &[bool]) -> Vec<u8> {
+    let mut across = vec![0u8; width * height];
+    for (line, sums) in mines.chunks(width).zip(across.chunks_mut(width)) {
+        for (col, sum) in sums.iter_mut().enumerate() {
+            let cols = col.saturating_sub(1)..=(col + 1).min(width - 1);
+            *sum = line[cols].iter().map(|&mine| u8::from(mine)).sum();
+        }
+    }
+    let mut counts = vec![0u8; width * height];
+    for row in 0..height {
+        for r in row.saturating_sub(1)..=(row + 1).min(height - 1) {
+            let sums = &across[r * width..(r + 1) * width];
+            for (count, sum) in counts[row * width..(row + 1) * width].iter_mut().zip(sums) {
+                *count += sum;
+            }
+        }
+    }
+    counts
+}
+
+/// Why a board could not be generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// The board has no cells.
+    Empty,
+    /// The board has more than `Board::MAX_CELLS` cells.
+    TooLarge { width: usize, height: usize },
+    /// There must be at least one cell without a mine.
+    TooManyMines { mines: usize, cells: usize },
+    /// The mines do not fit outside the cells kept clear around the start.
+    CrowdedStart { mines: usize, free: usize },
+    /// No board that can be solved without guessing turned up in time.
+    NoGuessNotFound { attempts: u64 },
+    /// No board of the wanted difficulty turned up in time.
+    RatingNotFound { stars: u8, attempts: u64 },
+    /// The search for a board was called off, see `generate::Progress`.
+    Cancelled,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::Empty => f.write_str("the board must be at least 1x1"),
+            BoardError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} board is over the limit of {} cells",
+                width,
+                height,
+                Board::MAX_CELLS
+            ),
+            BoardError::TooManyMines { mines, cells } => write!(
+                f,
+                "{} mines do not fit on a board of {} cells",
+                mines, cells
+            ),
+            BoardError::CrowdedStart { mines, free } => write!(
+                f,
+                "{} mines do not fit in the {} cells away from the start",
+                mines, free
+            ),
+            BoardError::NoGuessNotFound { attempts } => {
+                write!(f, "no board without guesses found in {} attempts", attempts)
+            }
+            BoardError::RatingNotFound { stars, attempts } => write!(
+                f,
+                "no board rated {} stars found in {} attempts",
+                stars, attempts
+            ),
+            BoardError::Cancelled => f.write_str("the search for a board was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// Source of board revisions. Drawing from one global counter means two
+/// different boards never share a revision, even across restarts.
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    x_cells: usize,
+    y_cells: usize,
+    mines: usize,
+    state: State,
+    /// Running counts of visible and flagged cells, so huge boards don't
+    /// need a full scan to answer `revealed`, `flags` or `is_won`.
+    visible: usize,
+    flagged: usize,
+    revision: u64,
+    rules: Ruleset,
+}
+
+impl Board {
+    /// The most cells a board may have, well past anything playable but
+    /// small enough that its state always fits in memory.
+    pub const MAX_CELLS: usize = 1 << 22;
+
+    pub fn new(x_cells: usize, y_cells: usize, mines: usize) -> Result<Self, BoardError> {
+        Self::generate(
+            x_cells,
+            y_cells,
+            mines,
+            &mut rand::thread_rng(),
+            None,
+            &Uniform,
+            Ruleset::CLASSIC,
+        )
+    }
+
+    /// Generates the same board every time for a given seed.
+    pub fn with_seed(
+        x_cells: usize,
+        y_cells: usize,
+        mines: usize,
+        seed: u64,
+    ) -> Result<Self, BoardError> {
+        Self::generate(
+            x_cells,
+            y_cells,
+            mines,
+            &mut StdRng::seed_from_u64(seed),
+            None,
+            &Uniform,
+            Ruleset::CLASSIC,
+        )
+    }
+
+    /// Like `with_seed`, but keeps `start` and its neighbours free of mines
+    /// so revealing `start` always opens an area.
+    pub fn with_safe_start(
+        x_cells: usize,
+        y_cells: usize,
+        mines: usize,
+        seed: u64,
+        start: Pos,
+    ) -> Result<Self, BoardError> {
+        Self::generate(
+            x_cells,
+            y_cells,
+            mines,
+            &mut StdRng::seed_from_u64(seed),
+            Some(start),
+            &Uniform,
+            Ruleset::CLASSIC,
+        )
+    }
+
+    /// Like `with_seed`, with the mines placed by `generator` and, given a
+    /// `start`, kept off it and its neighbours.
+    pub fn placed(
+        x_cells: usize,
+        y_cells: usize,
+        mines: usize,
+        seed: u64,
+        start: Option<Pos>,
+        generator: &dyn Generator,
+    ) -> Result<Self, BoardError> {
+        Self::generate(
+            x_cells,
+            y_cells,
+            mines,
+            &mut StdRng::seed_from_u64(seed),
+            start,
+            generator,
+            Ruleset::CLASSIC,
+        )
+    }
+
+    /// Like `placed`, played by `rules` rather than the classic game. Each
+    /// of the `mines` mined cells gets between one and
+    /// `rules.mines_per_cell` mines, and `start` is kept clear of the
+    /// cells its number would count.
+    pub fn ruled(
+        x_cells: usize,
+        y_cells: usize,
+        mines: usize,
+        seed: u64,
+        start: Option<Pos>,
+        generator: &dyn Generator,
+        rules: Ruleset,
+    ) -> Result<Self, BoardError> {
+        Self::generate(
+            x_cells,
+            y_cells,
+            mines,
+            &mut StdRng::seed_from_u64(seed),
+            start,
+            generator,
+            rules,
+        )
+    }
+
+    /// Checks that a board of this size can hold `mines` mines.
+    pub fn validate(x_cells: usize, y_cells: usize, mines: usize) -> Result<(), BoardError> {
+        let cells = x_cells
+            .checked_mul(y_cells)
+            .filter(|&cells| cells <= Self::MAX_CELLS)
+            .ok_or(BoardError::TooLarge {
+                width: x_cells,
+                height: y_cells,
+            })?;
+        if cells == 0 {
+            return Err(BoardError::Empty);
+        }
+        if mines >= cells {
+            return Err(BoardError::TooManyMines { mines, cells });
+        }
+        Ok(())
+    }
+
+    fn generate<R: Rng>(
+        x_cells: usize,
+        y_cells: usize,
+        mines: usize,
+        rng: &mut R,
+        safe: Option<Pos>,
+        generator: &dyn Generator,
+        rules: Ruleset,
+    ) -> Result<Self, BoardError> {
+        Self::validate(x_cells, y_cells, mines)?;
+        let start = Instant::now();
+        let state = vec![Packed::EMPTY; x_cells * y_cells];
+        let mut board = Self::from_packed(x_cells, y_cells, mines, state);
+        board.rules = rules;
+
+        let mut kept: Vec<usize> = safe
+            .into_iter()
+            .flat_map(|pos| board.around(pos).chain([pos]))
+            .filter_map(|pos| board.index(pos))
+            .collect();
+        kept.sort_unstable();
+        let free = x_cells * y_cells - kept.len();
+        if mines > free {
+            return Err(BoardError::CrowdedStart { mines, free });
+        }
+
+        let mut mined = vec![false; x_cells * y_cells];
+        for i in generator.place(x_cells, y_cells, mines, &kept, rng) {
+            mined[i] = true;
+        }
+        // Put in the numbers
+        let plain = rules.adjacency == Adjacency::King && !rules.wrap;
+        if plain && rules.mines_per_cell == 1 && !rules.liar {
+            let counts = mine_counts(x_cells, y_cells, &mined);
+            for (i, cell) in board.state.iter_mut().enumerate() {
+                cell.set_type(match (mined[i], counts[i]) {
+                    (true, _) => CellType::Mine,
+                    (false, 0) => CellType::Empty,
+                    (false, n) => CellType::Number(n as usize),
+                });
+            }
+        } else {
+            let most = rules.mines_per_cell.max(1) as usize;
+            for (i, cell) in board.state.iter_mut().enumerate() {
+                if mined[i] {
+                    cell.set_type(CellType::Mine);
+                    cell.set_load(rng.gen_range(1..=most));
+                } else if rules.liar {
+                    cell.set_lies_low(rng.gen());
+                }
+            }
+            for i in (0..mined.len()).filter(|&i| !mined[i]) {
+                board.renumber(Pos::new(i / x_cells, i % x_cells));
+            }
+        }
+        // Drawn only when the rules have special mines, so other boards
+        // keep their seeds
+        if rules.duds > 0 || rules.bigs > 0 {
+            for i in (0..mined.len()).filter(|&i| mined[i]) {
+                let roll = rng.gen_range(0..100u16);
+                let (duds, bigs) = (u16::from(rules.duds), u16::from(rules.bigs));
+                board.state[i].set_kind(if roll < duds {
+                    MineKind::Dud
+                } else if roll < duds + bigs {
+                    MineKind::Big
+                } else {
+                    MineKind::Normal
+                });
+            }
+        }
+
+        crate::debug!(
+            "generated {}x{} board with {} mines ({}) in {:?}",
+            x_cells,
+            y_cells,
+            mines,
+            generator.name(),
+            start.elapsed()
+        );
+        Ok(board)
+    }
+
+    /// Builds a board from cells that were produced elsewhere, e.g. a
+    /// snapshot received from a server.
+    pub fn from_cells(x_cells: usize, y_cells: usize, mines: usize, state: Vec<Vec<Cell>>) -> Self {
+        let state = state.iter().flatten().map(Packed::new).collect();
+        Self::from_packed(x_cells, y_cells, mines, state)
+    }
+
+    fn from_packed(x_cells: usize, y_cells: usize, mines: usize, state: State) -> Self {
+        let count = |wanted: CellState| state.iter().filter(|cell| cell.state() == wanted).count();
+        Self {
+            x_cells,
+            y_cells,
+            mines,
+            visible: count(CellState::Visible),
+            flagged: count(CellState::Flagged),
+            state,
+            revision: next_revision(),
+            rules: Ruleset::CLASSIC,
+        }
+    }
+
+    /// The rules the board is played by.
+    pub fn rules(&self) -> Ruleset {
+        self.rules
+    }
+
+    /// Plays the board by `rules` from now on, leaving the cells as they
+    /// are, e.g. for one loaded with its numbers already worked out.
+    pub fn with_rules(mut self, rules: Ruleset) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.x_cells
+    }
+
+    pub fn height(&self) -> usize {
+        self.y_cells
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    /// Changes whenever any cell changes. Renderers compare it against the
+    /// revision they last drew to skip redrawing an unchanged board.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Number of visible cells.
+    pub fn revealed(&self) -> usize {
+        self.visible
+    }
+
+    /// Where `pos` lives in the flat storage, or `None` off the board.
+    fn index(&self, pos: Pos) -> Option<usize> {
+        self.contains(pos.row, pos.col)
+            .then_some(pos.row * self.x_cells + pos.col)
+    }
+
+    fn at(&self, pos: Pos) -> Packed {
+        match self.index(pos) {
+            Some(i) => self.state[i],
+            None => panic!(
+                "{:?} is outside a {}x{} board",
+                pos, self.x_cells, self.y_cells
+            ),
+        }
+    }
+
+    fn at_mut(&mut self, pos: Pos) -> &mut Packed {
+        match self.index(pos) {
+            Some(i) => &mut self.state[i],
+            None => panic!(
+                "{:?} is outside a {}x{} board",
+                pos, self.x_cells, self.y_cells
+            ),
+        }
+    }
+
+    /// The cell at `(row, col)`. Panics if it is outside the board; use
+    /// `get` for coordinates that have not been checked.
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        self.at(Pos::new(row, col)).unpack()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Cell> {
+        let i = self.index(Pos::new(row, col))?;
+        Some(self.state[i].unpack())
+    }
+
+    pub fn set_cell(&mut self, row: usize, col: usize, cell: Cell) {
+        self.set_state(row, col, cell.cell_state);
+        *self.at_mut(Pos::new(row, col)) = Packed::new(&cell);
+    }
+
+    /// The only place cell states change, keeping the counters and the
+    /// revision in step with the cells.
+    fn set_state(&mut self, row: usize, col: usize, cell_state: CellState) {
+        let previous = self.at(Pos::new(row, col)).state();
+        match previous {
+            CellState::Visible => self.visible -= 1,
+            CellState::Flagged => self.flagged -= 1,
+            CellState::Hidden => {}
+        }
+        match cell_state {
+            CellState::Visible => self.visible += 1,
+            CellState::Flagged => self.flagged += 1,
+            CellState::Hidden => {}
+        }
+        self.at_mut(Pos::new(row, col)).set_state(cell_state);
+        self.revision = next_revision();
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        row < self.y_cells && col < self.x_cells
+    }
+
+    /// Where an opening goes next from the empty cell at `(row, col)`:
+    /// the cells it counts that are neither mines, flagged nor locked.
+    fn neighbors(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let steps = self.rules.adjacency.offsets();
+        self.rules
+            .reach(self.x_cells, self.y_cells, Pos::new(row, col), steps)
+            .filter_map(move |pos| {
+                let cell = self.at(pos);
+                let valid =
+                    !cell.is_mine() && !cell.is_locked() && cell.state() != CellState::Flagged;
+                valid.then_some((pos.row, pos.col))
+            })
+    }
+
+    /// Continues revealing the opening `flood` is uncovering, expanding at
+    /// most `cells` more empty cells. Returns whether the opening is done.
+    pub fn spread(&mut self, flood: &mut Flood, cells: usize) -> bool {
+        // Use BFS to reveal empty. Visible cells count as visited, so each
+        // cell is queued at most once and nothing beyond the queue is kept.
+        for _ in 0..cells {
+            let Some((row, col)) = flood.queue.pop_front() else {
+                break;
+            };
+            let mut found = [(0, 0); 8];
+            let mut count = 0;
+            for neighbor in self.neighbors(row, col) {
+                found[count] = neighbor;
+                count += 1;
+            }
+
+            for &(row, col) in &found[..count] {
+                if self.at(Pos::new(row, col)).state() == CellState::Visible {
+                    continue;
+                }
+                self.set_state(row, col, CellState::Visible);
+                flood.revealed.push((row, col));
+                // `neighbors` never returns mines, and numbers stop the flood
+                if self.at(Pos::new(row, col)).cell_type() == CellType::Empty {
+                    flood.queue.push_back((row, col));
+                }
+            }
+        }
+        flood.is_done()
+    }
+
+    /// The up to eight cells the number at `pos` counts, without
+    /// allocating.
+    fn around(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.rules.around(self.x_cells, self.y_cells, pos)
+    }
+
+    /// Sets the cell at `pos`, which must not be a mine, to the mines
+    /// around it, or to the lie about them if the rules have liars.
+    fn renumber(&mut self, pos: Pos) {
+        let count: usize = self.around(pos).map(|near| self.at(near).load()).sum();
+        let liar = self.rules.liar;
+        let cell = self.at_mut(pos);
+        let shown = match (liar, cell.lies_low()) {
+            (false, _) => count,
+            (true, true) if count > 0 => count - 1,
+            (true, _) => count + 1,
+        };
+        cell.set_type(match shown {
+            0 => CellType::Empty,
+            n => CellType::Number(n),
+        });
+    }
+
+    /// How many mines the cell at `(row, col)` holds: 0 for a safe cell,
+    /// and more than 1 only if the rules allow it. Panics outside the
+    /// board.
+    pub fn mines_at(&self, row: usize, col: usize) -> usize {
+        self.at(Pos::new(row, col)).load()
+    }
+
+    /// Sets how many mines the mine at `(row, col)` holds, for boards
+    /// loaded from a save. Safe cells are left alone.
+    pub(crate) fn set_mines_at(&mut self, row: usize, col: usize, load: usize) {
+        let cell = self.at_mut(Pos::new(row, col));
+        if cell.is_mine() {
+            cell.set_load(load);
+        }
+    }
+
+    /// The up to eight cells the number at `(row, col)` counts.
+    pub fn adjacent(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut cells = Vec::with_capacity(8);
+        cells.extend(
+            self.around(Pos::new(row, col))
+                .map(|pos| (pos.row, pos.col)),
+        );
+        cells
+    }
+
+    pub fn reveal_all(&mut self) {
+        for row in 0..self.y_cells {
+            for col in 0..self.x_cells {
+                self.set_state(row, col, CellState::Visible);
+            }
+        }
+    }
+
+    /// Reveals a hidden cell and returns its type, or `None` if it was
+    /// already visible, is flagged or lies outside the board.
+    pub fn reveal(&mut self, row: usize, col: usize) -> Option<CellType> {
+        let (row, col) = *self.reveal_cells(row, col).first()?;
+        Some(self.at(Pos::new(row, col)).cell_type())
+    }
+
+    /// Like `reveal`, but returns every cell that was uncovered, starting
+    /// with `(row, col)` itself. Empty if nothing could be revealed.
+    pub fn reveal_cells(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut flood = Flood::default();
+        if self.restart_reveal(row, col, &mut flood) {
+            self.spread(&mut flood, usize::MAX);
+        }
+        flood.revealed
+    }
+
+    /// Reveals a hidden cell and returns the opening it starts, which is
+    /// already done unless the cell is empty; see `spread`. `None` if the
+    /// cell could not be revealed.
+    pub fn start_reveal(&mut self, row: usize, col: usize) -> Option<Flood> {
+        let mut flood = Flood::default();
+        self.restart_reveal(row, col, &mut flood).then_some(flood)
+    }
+
+    /// Like `start_reveal`, but uncovers the opening with `flood`, whatever
+    /// it held before, so its memory is reused. Returns whether the cell
+    /// could be revealed; `flood` is left empty if not.
+    pub fn restart_reveal(&mut self, row: usize, col: usize, flood: &mut Flood) -> bool {
+        flood.queue.clear();
+        flood.revealed.clear();
+        match self.index(Pos::new(row, col)).map(|i| self.state[i]) {
+            Some(cell) if cell.state() == CellState::Hidden && !cell.is_locked() => {}
+            _ => return false,
+        }
+        if !self.in_sight(row, col) {
+            return false;
+        }
+        self.set_state(row, col, CellState::Visible);
+
+        flood.revealed.push((row, col));
+        let opens = self.rules.reveal == Reveal::Flood;
+        if opens && self.at(Pos::new(row, col)).cell_type() == CellType::Empty {
+            // If empty reveal all empty nearby
+            flood.queue.push_back((row, col));
+        }
+        true
+    }
+
+    /// Shows every mine, leaving the rest of the board as the player left it.
+    pub fn reveal_mines(&mut self) {
+        for row in 0..self.y_cells {
+            for col in 0..self.x_cells {
+                if self.at(Pos::new(row, col)).is_mine() {
+                    self.set_state(row, col, CellState::Visible);
+                }
+            }
+        }
+    }
+
+    /// Moves the mine at `from` to `to`, with every mine it holds, and
+    /// renumbers the cells around both. Both must be on the board and not
+    /// revealed, `from` a mine and `to` not one; returns whether the mine
+    /// moved.
+    pub fn move_mine(&mut self, from: Pos, to: Pos) -> bool {
+        let hidden_mine = |pos: Pos, mine: bool| {
+            self.index(pos).is_some_and(|i| {
+                let cell = self.state[i];
+                cell.is_mine() == mine && cell.state() != CellState::Visible
+            })
+        };
+        if !hidden_mine(from, true) || !hidden_mine(to, false) {
+            return false;
+        }
+        let (load, kind) = (self.at(from).load(), self.at(from).kind());
+        let cell = self.at_mut(to);
+        cell.set_type(CellType::Mine);
+        cell.set_load(load);
+        cell.set_kind(kind);
+        let cell = self.at_mut(from);
+        cell.set_type(CellType::Empty);
+        cell.set_kind(MineKind::Normal);
+        // Only the numbers around the two cells can change
+        for pos in [from, to] {
+            for near in self.around(pos).chain([from]) {
+                if !self.at(near).is_mine() {
+                    self.renumber(near);
+                }
+            }
+        }
+        self.revision = next_revision();
+        true
+    }
+
+    /// Whether the number at `(row, col)`, if it has one, is on show: the
+    /// rules aren't blind, or a cell it counts was flagged at some point.
+    pub fn shows_number(&self, row: usize, col: usize) -> bool {
+        !self.rules.blind || self.at(Pos::new(row, col)).is_shown()
+    }
+
+    /// Marks the number at `(row, col)` as shown, for loading saves.
+    pub(crate) fn show_number(&mut self, row: usize, col: usize) {
+        self.at_mut(Pos::new(row, col)).set_shown();
+    }
+
+    /// Whether the player can see the cell at `(row, col)` through the
+    /// rules' fog: it is near an uncovered cell, or nothing is uncovered
+    /// yet. Cells out of sight can't be revealed or flagged.
+    pub fn in_sight(&self, row: usize, col: usize) -> bool {
+        self.visible == 0
+            || self
+                .rules
+                .in_sight(self.x_cells, self.y_cells, Pos::new(row, col), |near| {
+                    self.at(near).state() == CellState::Visible
+                })
+    }
+
+    /// Moves a random mine to a random safe cell, both hidden, unflagged
+    /// and unlocked, and neither counted by a revealed number, so nothing
+    /// the player can see changes. Returns where it moved from and to, or
+    /// `None` if no mine or no cell qualifies.
+    pub fn shift_mine(&mut self, rng: &mut impl Rng) -> Option<(Pos, Pos)> {
+        let unseen = |pos: Pos| {
+            let cell = self.at(pos);
+            cell.state() == CellState::Hidden
+                && !cell.is_locked()
+                && self
+                    .around(pos)
+                    .all(|near| self.at(near).state() != CellState::Visible)
+        };
+        let (mut mines, mut safe) = (Vec::new(), Vec::new());
+        for row in 0..self.y_cells {
+            for col in 0..self.x_cells {
+                let pos = Pos::new(row, col);
+                if unseen(pos) {
+                    match self.at(pos).is_mine() {
+                        true => mines.push(pos),
+                        false => safe.push(pos),
+                    }
+                }
+            }
+        }
+        if mines.is_empty() || safe.is_empty() {
+            return None;
+        }
+        let from = mines[rng.gen_range(0..mines.len())];
+        let to = safe[rng.gen_range(0..safe.len())];
+        self.move_mine(from, to).then_some((from, to))
+    }
+
+    /// Locks or unlocks the cell at `(row, col)`. A locked cell can't be
+    /// revealed or flagged, and openings stop at it; nothing else changes.
+    pub fn set_locked(&mut self, row: usize, col: usize, locked: bool) {
+        self.at_mut(Pos::new(row, col)).set_locked(locked);
+        self.revision = next_revision();
+    }
+
+    pub fn is_locked(&self, row: usize, col: usize) -> bool {
+        self.index(Pos::new(row, col))
+            .is_some_and(|i| self.state[i].is_locked())
+    }
+
+    /// Adds a mine to the hidden, unflagged, safe cell at `pos` and
+    /// renumbers the cells around it. Returns whether it was added.
+    pub fn plant(&mut self, pos: Pos) -> bool {
+        let fits = self.index(pos).is_some_and(|i| {
+            let cell = self.state[i];
+            !cell.is_mine() && cell.state() == CellState::Hidden
+        });
+        if !fits || self.mines + 1 >= self.x_cells * self.y_cells {
+            return false;
+        }
+        let cell = self.at_mut(pos);
+        cell.set_type(CellType::Mine);
+        cell.set_load(1);
+        self.mines += 1;
+        for near in self.around(pos) {
+            if !self.at(near).is_mine() {
+                self.renumber(near);
+            }
+        }
+        self.revision = next_revision();
+        true
+    }
+
+    /// Flags a hidden cell or unflags a flagged one. Returns whether the cell
+    /// is flagged afterwards. Locked cells and cells out of sight can't be
+    /// flagged.
+    pub fn toggle_flag(&mut self, row: usize, col: usize) -> bool {
+        let Some(cell) = self.get(row, col) else {
+            return false;
+        };
+        if self.is_locked(row, col) || !self.in_sight(row, col) {
+            return false;
+        }
+        match cell.cell_state {
+            CellState::Hidden => {
+                self.set_state(row, col, CellState::Flagged);
+                // Every number counting the flag shows from now on
+                let pos = Pos::new(row, col);
+                for near in self.around(pos) {
+                    self.at_mut(near).set_shown();
+                }
+            }
+            CellState::Flagged => self.set_state(row, col, CellState::Hidden),
+            CellState::Visible => {}
+        }
+        self.at(Pos::new(row, col)).state() == CellState::Flagged
+    }
+
+    pub fn flags(&self) -> usize {
+        self.flagged
+    }
+
+    /// Reveals the unflagged neighbours of a revealed number once it has as
+    /// many flags around it as its value, returning every cell uncovered.
+    pub fn chord(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let Some(Cell {
+            cell_type: CellType::Number(n),
+            cell_state,
+            ..
+        }) = self.get(row, col)
+        else {
+            return Vec::new();
+        };
+        if cell_state != CellState::Visible {
+            return Vec::new();
+        }
+
+        let adjacent = self.adjacent(row, col);
+        let flags = adjacent
+            .iter()
+            .filter(|&&(r, c)| self.at(Pos::new(r, c)).state() == CellState::Flagged)
+            .count();
+        if flags != n {
+            return Vec::new();
+        }
+
+        // One flood serves every neighbour, as each is done before the next
+        let mut flood = Flood::default();
+        let mut revealed = Vec::new();
+        for (r, c) in adjacent {
+            if self.restart_reveal(r, c, &mut flood) {
+                self.spread(&mut flood, usize::MAX);
+                revealed.extend_from_slice(flood.revealed());
+            }
+        }
+        revealed
+    }
+
+    /// True once the rules' win condition is met and no mine has gone
+    /// off: classically once every cell without a mine has been revealed.
+    pub fn is_won(&self) -> bool {
+        // The counters can't be short on a won board, so huge boards only
+        // get scanned near the end of a game
+        match self.rules.win {
+            Win::Reveal => {
+                self.visible + self.mines >= self.x_cells * self.y_cells
+                    && !self.is_lost()
+                    && self
+                        .state
+                        .iter()
+                        .all(|cell| cell.is_mine() || cell.state() == CellState::Visible)
+            }
+            Win::Flag => {
+                self.flagged <= self.mines
+                    && !self.is_lost()
+                    && self.state.iter().all(|cell| match cell.state() {
+                        CellState::Flagged => cell.is_mine(),
+                        // Mines that went off count as found
+                        CellState::Visible => true,
+                        CellState::Hidden => !cell.is_mine(),
+                    })
+            }
+        }
+    }
+
+    /// True once as many mines have gone off as the rules give lives:
+    /// classically the first one revealed without being captured.
+    pub fn is_lost(&self) -> bool {
+        self.hits() >= self.rules.lives.max(1) as usize
+    }
+
+    /// Mines revealed without being captured, duds aside.
+    pub fn hits(&self) -> usize {
+        self.state
+            .iter()
+            .filter(|cell| {
+                cell.is_mine()
+                    && cell.state() == CellState::Visible
+                    && cell.owner().is_none()
+                    && cell.kind() != MineKind::Dud
+            })
+            .count()
+    }
+
+    /// The kind of the mine at `(row, col)`, or `None` if there is none.
+    pub fn mine_kind(&self, row: usize, col: usize) -> Option<MineKind> {
+        let cell = self.state[self.index(Pos::new(row, col))?];
+        cell.is_mine().then(|| cell.kind())
+    }
+
+    pub(crate) fn set_mine_kind(&mut self, row: usize, col: usize, kind: MineKind) {
+        self.at_mut(Pos::new(row, col)).set_kind(kind);
+    }
+
+    /// Uncovers the eight cells around a big mine at `(row, col)` that
+    /// went off, flags and all, and returns them. Locked cells are spared.
+    pub fn blast(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let pos = Pos::new(row, col);
+        let area: Vec<Pos> = self
+            .rules
+            .reach(self.x_cells, self.y_cells, pos, Adjacency::King.offsets())
+            .collect();
+        let mut blown = Vec::new();
+        for near in area {
+            let cell = self.at(near);
+            if cell.state() != CellState::Visible && !cell.is_locked() {
+                self.set_state(near.row, near.col, CellState::Visible);
+                blown.push((near.row, near.col));
+            }
+        }
+        blown
+    }
+
+    /// Mines that can still go off before the game is lost.
+    pub fn lives_left(&self) -> usize {
+        (self.rules.lives.max(1) as usize).saturating_sub(self.hits())
+    }
+
+    pub fn capture(&mut self, row: usize, col: usize, player: usize) {
+        if let Some(i) = self.index(Pos::new(row, col)) {
+            self.state[i].set_owner(Some(player));
+            self.revision = next_revision();
+        }
+    }
+
+    /// Classic single-player click: hitting a mine reveals the whole board.
+    pub fn click(&mut self, row: usize, col: usize) {
+        if let Some(CellType::Mine) = self.reveal(row, col) {
+            self.reveal_all();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Commands for a developer console: a line such as `reveal 4 7` names a
+//! command and gives its arguments, separated by spaces.
+//!
+//! A `Registry` knows the commands, checks each line against them and
+//! hands back the command to run, so frontends decide what a command may
+//! touch. `H` is whatever the frontend runs, usually a function taking its
+//! own state and the arguments. Modules register their commands into the
+//! frontend's registry, and a later command of the same name replaces an
+//! earlier one.
+
+use std::fmt;
+use std::str::FromStr;
+
+pub struct Command<H> {
+    pub name: &'static str,
+    /// The arguments, `<required>` ones first and then `[optional]` ones,
+    /// e.g. `<row> <col>`.
+    pub usage: &'static str,
+    pub about: &'static str,
+    pub run: H,
+}
+
+impl<H> Command<H> {
+    /// How many arguments the command takes, at least and at most.
+    fn arity(&self) -> (usize, usize) {
+        let words = self.usage.split_whitespace();
+        let required = words.clone().filter(|word| word.starts_with('<')).count();
+        (required, words.count())
+    }
+}
+
+/// A line checked against its command.
+pub struct Call<'r, 'l, H> {
+    pub command: &'r Command<H>,
+    pub args: Vec<&'l str>,
+}
+
+/// Why a line can't be run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Unknown(String),
+    Usage {
+        name: &'static str,
+        usage: &'static str,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unknown(name) => write!(f, "unknown command `{}`, try `help`", name),
+            Error::Usage { name, usage } => write!(f, "usage: {} {}", name, usage),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub struct Registry<H> {
+    commands: Vec<Command<H>>,
+}
+
+impl<H> Registry<H> {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, command: Command<H>) {
+        self.commands.retain(|known| known.name != command.name);
+        self.commands.push(command);
+        self.commands.sort_by_key(|known| known.name);
+    }
+
+    /// Every command, by name.
+    pub fn commands(&self) -> &[Command<H>] {
+        &self.commands
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command<H>> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// The command `line` runs and its arguments, or `None` for a blank
+    /// line.
+    pub fn parse<'l>(&self, line: &'l str) -> Result<Option<Call<'_, 'l, H>>, Error> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(None);
+        };
+        let command = self
+            .get(name)
+            .ok_or_else(|| Error::Unknown(name.to_owned()))?;
+        let args: Vec<&str> = words.collect();
+        let (least, most) = command.arity();
+        if !(least..=most).contains(&args.len()) {
+            return Err(Error::Usage {
+                name: command.name,
+                usage: command.usage,
+            });
+        }
+        Ok(Some(Call { command, args }))
+    }
+
+    /// The names of the commands starting with `prefix`.
+    pub fn complete(&self, prefix: &str) -> Vec<&'static str> {
+        self.commands
+            .iter()
+            .map(|command| command.name)
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    /// One line per command: its name, arguments and what it does.
+    pub fn help(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .map(|command| {
+                let call = format!("{} {}", command.name, command.usage);
+                format!("{}  {}", call.trim_end(), command.about)
+            })
+            .collect()
+    }
+}
+
+impl<H> Default for Registry<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Argument `index`, called `name` in messages, read as a `T`; `None` when
+/// an optional argument was left out.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<Option<T>, String> {
+    args.get(index)
+        .map(|word| {
+            word.parse()
+                .map_err(|_| format!("{} can't be `{}`", name, word))
+        })
+        .transpose()
+}
@@ -0,0 +1,57 @@
+//! The moving-mines variant, where a mine moves to another cell every few
+//! seconds of play.
+//!
+//! A mine only moves between cells no revealed number counts, see
+//! `Board::shift_mine`, so every number on show stays true and the
+//! player's deductions hold; only the cells the numbers don't reach yet
+//! change under them.
+
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::board::{Board, Pos};
+
+/// When the mines move in a moving-mines game.
+#[derive(Clone, Debug)]
+pub struct Drift {
+    every: Duration,
+    /// How many times the mines were due to move so far.
+    moves: u32,
+    rng: StdRng,
+}
+
+impl Drift {
+    /// Time between moves in games of `Variant::MOVING`.
+    pub const EVERY: Duration = Duration::from_secs(10);
+    /// How long before a move the player is warned of it.
+    pub const WARNING: Duration = Duration::from_secs(3);
+
+    /// Moves a mine every `every`, picking it and where it goes from
+    /// `seed`.
+    pub fn new(every: Duration, seed: u64) -> Self {
+        Self {
+            every,
+            moves: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Moves a mine for every move due `played` after the first click and
+    /// returns the moves made. A move with no mine or cell to take is
+    /// skipped.
+    pub fn update(&mut self, board: &mut Board, played: Duration) -> Vec<(Pos, Pos)> {
+        let mut moved = Vec::new();
+        while self.every * (self.moves + 1) <= played {
+            self.moves += 1;
+            moved.extend(board.shift_mine(&mut self.rng));
+        }
+        moved
+    }
+
+    /// Time left until the next move, `played` after the first click.
+    pub fn until_next(&self, played: Duration) -> Duration {
+        (self.every * (self.moves + 1)).saturating_sub(played)
+    }
+}
@@ -0,0 +1,62 @@
+//! Structured notifications emitted by a classic `Game`.
+//!
+//! Renderers, audio, replay recorders and stats subscribe a `Listener` to a
+//! game and react to what happened, instead of the board code calling them.
+
+use crate::board::CellType;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A hidden cell was uncovered, either directly or by a flood or chord.
+    CellRevealed {
+        row: usize,
+        col: usize,
+        cell_type: CellType,
+    },
+    /// A flag was placed (`flagged`) or removed.
+    Flagged {
+        row: usize,
+        col: usize,
+        flagged: bool,
+    },
+    /// Revealing an empty cell at `(row, col)` uncovered `cells` cells.
+    OpeningFlooded {
+        row: usize,
+        col: usize,
+        cells: usize,
+    },
+    MineHit {
+        row: usize,
+        col: usize,
+    },
+    /// A mine went off at `(row, col)` but the rules had a life to spare,
+    /// leaving `lives` more.
+    LifeLost {
+        row: usize,
+        col: usize,
+        lives: usize,
+    },
+    /// A dud went off at `(row, col)`, costing time rather than a life.
+    DudHit {
+        row: usize,
+        col: usize,
+    },
+    /// A big mine went off at `(row, col)` and blew `cells` cells open
+    /// around it, setting off any mines among them.
+    Blast {
+        row: usize,
+        col: usize,
+        cells: usize,
+    },
+    GameWon,
+}
+
+pub trait Listener {
+    fn on_event(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Listener for F {
+    fn on_event(&mut self, event: &Event) {
+        self(event)
+    }
+}
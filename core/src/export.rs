@@ -0,0 +1,384 @@
+//! Renders boards to pixels without a GPU, and replays to animated GIFs
+//! that can be shared without screen recording, or to frames and a
+//! timing manifest for ffmpeg when making videos. Also writes screenshots
+//! that carry their game along as PNG text, so it can be set up again
+//! from the image alone.
+//!
+//! Tiles are flat squares with a one pixel grid, and numbers are drawn
+//! from a 3x5 pixel font scaled up with the tiles.
+
+use std::time::Duration;
+
+use crate::board::{Board, CellState, CellType};
+use crate::gif::Encoder;
+use crate::png::{self, PngError};
+use crate::replay::Replay;
+use crate::save::{self, Document, SaveError, Saved};
+use crate::stats::{Configuration, Variant};
+
+/// Grid, hidden, revealed, mine, flag, then the numbers 1 to 8 in their
+/// usual colours.
+const PALETTE: [[u8; 3]; 13] = [
+    [0x80, 0x80, 0x80],
+    [0xc0, 0xc0, 0xc0],
+    [0xe8, 0xe8, 0xe8],
+    [0x00, 0x00, 0x00],
+    [0xe0, 0x20, 0x20],
+    [0x00, 0x00, 0xff],
+    [0x00, 0x80, 0x00],
+    [0xff, 0x00, 0x00],
+    [0x00, 0x00, 0x80],
+    [0x80, 0x00, 0x00],
+    [0x00, 0x80, 0x80],
+    [0x00, 0x00, 0x00],
+    [0x80, 0x80, 0x80],
+];
+const GRID: u8 = 0;
+const HIDDEN: u8 = 1;
+const REVEALED: u8 = 2;
+const MINE: u8 = 3;
+const FLAG: u8 = 4;
+const NUMBER: u8 = 5;
+
+/// Rows of the digits 1 to 8, three bits each, high bit on the left.
+const DIGITS: [[u8; 5]; 8] = [
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b110, 0b001, 0b010, 0b100, 0b111],
+    [0b110, 0b001, 0b010, 0b001, 0b110],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b110, 0b001, 0b110],
+    [0b011, 0b100, 0b110, 0b101, 0b010],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b010, 0b101, 0b010, 0b101, 0b010],
+];
+
+/// How the last frame of a replay is held before it loops or ends.
+const HOLD: Duration = Duration::from_secs(3);
+/// Longest side of an exported image; tiles shrink to fit.
+const MAX_SIDE: usize = 4096;
+/// The end of the ffmpeg commands in video manifests. Common video codecs
+/// need even sizes, so odd ones are padded.
+const ENCODE: &str = "-vf 'pad=ceil(iw/2)*2:ceil(ih/2)*2' -pix_fmt yuv420p replay.mp4";
+/// The shortest frame most viewers show at its real length.
+const MIN_FRAME: Duration = Duration::from_millis(20);
+
+/// How a replay is sized and timed when it is exported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayOptions {
+    /// Pixels per tile, grid line included.
+    pub cell: usize,
+    /// Playback speed; 2 plays the game twice as fast as it was played.
+    pub speed: f64,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            cell: 16,
+            speed: 1.0,
+        }
+    }
+}
+
+/// The board as palette indices for `PALETTE`, row by row, `cell` pixels
+/// to a tile.
+pub fn render(board: &Board, cell: usize) -> Vec<u8> {
+    let (width, height) = (board.width() * cell, board.height() * cell);
+    let mut pixels = vec![GRID; width * height];
+    // The digits fill the middle of the tile, leaving a margin
+    let scale = (cell / 8).max(1);
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let cell_at = board.cell(row, col);
+            let mut fill = |x: usize, y: usize, w: usize, h: usize, color: u8| {
+                for py in y..(y + h).min(cell) {
+                    let start = (row * cell + py) * width + col * cell;
+                    for px in x..(x + w).min(cell) {
+                        pixels[start + px] = color;
+                    }
+                }
+            };
+            let inner = cell.saturating_sub(1);
+            let (background, mark) = match (cell_at.cell_state, cell_at.cell_type) {
+                (CellState::Hidden, _) => (HIDDEN, None),
+                (CellState::Flagged, _) => (HIDDEN, Some(FLAG)),
+                (CellState::Visible, CellType::Mine) => (REVEALED, Some(MINE)),
+                (CellState::Visible, _) => (REVEALED, None),
+            };
+            fill(0, 0, inner, inner, background);
+            if let Some(color) = mark {
+                let size = inner / 2;
+                let at = (inner - size) / 2;
+                fill(at, at, size, size, color);
+            }
+            if let (CellState::Visible, CellType::Number(n)) =
+                (cell_at.cell_state, cell_at.cell_type)
+            {
+                let glyph = DIGITS[n.clamp(1, 8) - 1];
+                let (x0, y0) = (
+                    inner.saturating_sub(3 * scale) / 2,
+                    inner.saturating_sub(5 * scale) / 2,
+                );
+                for (gy, bits) in glyph.iter().enumerate() {
+                    for gx in 0..3 {
+                        if bits & (0b100 >> gx) != 0 {
+                            let color = NUMBER + n.clamp(1, 8) as u8 - 1;
+                            fill(x0 + gx * scale, y0 + gy * scale, scale, scale, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// The boards a replay shows and how long each stays up: one per move
+/// until the next, then the end held for a few seconds. Moves closer
+/// together than a GIF can show are merged.
+pub fn timeline(replay: &Replay, speed: f64) -> Vec<(Board, Duration)> {
+    let speed = speed.max(0.01);
+    let mut timeline: Vec<(Board, Duration)> = Vec::new();
+    // When the last board in the timeline went up
+    let mut since = Duration::ZERO;
+    for (at, board) in replay.frames() {
+        let at = at.div_f64(speed);
+        match timeline.last_mut() {
+            Some((last, _)) if at.saturating_sub(since) < MIN_FRAME => *last = board,
+            Some((_, shown)) => {
+                *shown = at - since;
+                since = at;
+                timeline.push((board, HOLD));
+            }
+            None => {
+                since = at;
+                timeline.push((board, HOLD));
+            }
+        }
+    }
+    timeline
+}
+
+/// The replay as a looping GIF of its `timeline`. Large boards get smaller
+/// tiles, down to a pixel each, and are cut off beyond that.
+pub fn gif(replay: &Replay, options: ReplayOptions) -> Vec<u8> {
+    let frame = Frame::of(replay.start(), options.cell);
+    let mut encoder = Encoder::new(frame.width as u16, frame.height as u16, &PALETTE);
+    for (board, shown) in timeline(replay, options.speed) {
+        encoder.frame(&frame.render(&board), shown);
+    }
+    encoder.finish()
+}
+
+/// How a replay is written for ffmpeg.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VideoFormat {
+    /// A PNG for each board of the timeline, and an ffconcat manifest that
+    /// gives each one its time.
+    Png,
+    /// One raw RGBA file with every frame at a fixed rate, and a manifest
+    /// with its size and rate.
+    Raw { fps: u32 },
+}
+
+/// The replay as files for ffmpeg, named as they should be written into
+/// one directory. Each manifest starts with the ffmpeg command that turns
+/// the directory into a video.
+pub fn video(
+    replay: &Replay,
+    options: ReplayOptions,
+    format: VideoFormat,
+) -> Vec<(String, Vec<u8>)> {
+    let frame = Frame::of(replay.start(), options.cell);
+    let timeline = timeline(replay, options.speed);
+    let rgba = |board: &Board| -> Vec<u8> {
+        frame
+            .render(board)
+            .iter()
+            .flat_map(|&color| {
+                let [r, g, b] = PALETTE[usize::from(color)];
+                [r, g, b, 0xff]
+            })
+            .collect()
+    };
+    let (width, height) = (frame.width, frame.height);
+    let mut files = Vec::new();
+    let (name, manifest) = match format {
+        VideoFormat::Png => {
+            let mut manifest = String::from("ffconcat version 1.0\n");
+            manifest.push_str(&format!(
+                "# ffmpeg -f concat -i frames.ffconcat {}\n",
+                ENCODE
+            ));
+            for (i, (board, shown)) in timeline.iter().enumerate() {
+                let name = format!("frame-{:05}.png", i);
+                let png = png::encode(width as u32, height as u32, &rgba(board), &[]);
+                manifest.push_str(&format!(
+                    "file '{}'\nduration {:.3}\n",
+                    name,
+                    shown.as_secs_f64()
+                ));
+                files.push((name, png));
+            }
+            // ffmpeg only keeps the last duration if the file comes again
+            if let Some((name, _)) = files.last() {
+                manifest.push_str(&format!("file '{}'\n", name));
+            }
+            ("frames.ffconcat", manifest)
+        }
+        VideoFormat::Raw { fps } => {
+            let fps = fps.max(1);
+            let mut bytes = Vec::new();
+            // Frames are counted from the total so far, so rounding never
+            // adds up to drift
+            let (mut elapsed, mut written) = (Duration::ZERO, 0);
+            for (board, shown) in &timeline {
+                elapsed += *shown;
+                let until = (elapsed.as_secs_f64() * f64::from(fps)).round() as u64;
+                if until > written {
+                    let pixels = rgba(board);
+                    for _ in written..until {
+                        bytes.extend(&pixels);
+                    }
+                    written = until;
+                }
+            }
+            files.push(("frames.rgba".to_owned(), bytes));
+            let manifest = format!(
+                "# ffmpeg -f rawvideo -pixel_format rgba -video_size {w}x{h} -framerate {fps} \
+                 -i frames.rgba {encode}\n\
+                 width {w}\nheight {h}\nframerate {fps}\nframes {written}\n",
+                w = width,
+                h = height,
+                encode = ENCODE,
+            );
+            ("frames.txt", manifest)
+        }
+    };
+    files.push((name.to_owned(), manifest.into_bytes()));
+    files
+}
+
+/// The size exported frames of a board are drawn at.
+struct Frame {
+    cell: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Frame {
+    /// `cell` pixels to a tile, or fewer if that would be larger than
+    /// `MAX_SIDE`.
+    fn of(board: &Board, cell: usize) -> Self {
+        let longest = board.width().max(board.height());
+        let cell = cell.min(MAX_SIDE / longest).max(1);
+        Self {
+            cell,
+            width: (board.width() * cell).min(MAX_SIDE),
+            height: (board.height() * cell).min(MAX_SIDE),
+        }
+    }
+
+    /// `render`, cut down to the largest size if the board is beyond it.
+    fn render(&self, board: &Board) -> Vec<u8> {
+        let pixels = render(board, self.cell);
+        let full = board.width() * self.cell;
+        if full == self.width {
+            return pixels;
+        }
+        pixels
+            .chunks(full)
+            .take(self.height)
+            .flat_map(|row| row[..self.width].iter().copied())
+            .collect()
+    }
+}
+
+/// First PNG text key of a screenshot, holding the kind and version of
+/// the fields after it.
+const SCREENSHOT_KEY: &str = "minesweeper";
+
+/// A game as a screenshot records it.
+#[derive(Clone, Debug)]
+pub struct Screenshot {
+    /// What the board was generated from, if it came from a seed.
+    pub seed: Option<u64>,
+    pub configuration: Configuration,
+    /// Time played when the screenshot was taken.
+    pub elapsed: Duration,
+    /// The board before the first move.
+    pub start: Board,
+}
+
+impl Screenshot {
+    /// `rgba` as a PNG with this game's fields as text chunks, which image
+    /// viewers show as they are.
+    pub fn png(&self, width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+        let mut document = Document::new(Self::KIND, save::version::<Self>());
+        self.save(&mut document);
+        let header = format!("{} {}", document.kind(), document.version());
+        let mut text = vec![(SCREENSHOT_KEY, header.as_str())];
+        text.extend(document.fields());
+        png::encode(width, height, rgba, &text)
+    }
+
+    /// The game stored in a PNG written by `png`.
+    pub fn read(bytes: &[u8]) -> Result<Self, SaveError> {
+        let text = png::text(bytes).map_err(|err| match err {
+            PngError::NotAPng => SaveError::NotASave,
+            err => SaveError::Corrupt(err.to_string()),
+        })?;
+        let (_, header) = text
+            .iter()
+            .find(|(key, _)| key == SCREENSHOT_KEY)
+            .ok_or(SaveError::NotASave)?;
+        let (kind, version) = header.split_once(' ').ok_or(SaveError::NotASave)?;
+        let version = version.parse().map_err(|_| SaveError::NotASave)?;
+        let mut document = Document::new(kind, version);
+        for (key, value) in text.iter().filter(|(key, _)| key != SCREENSHOT_KEY) {
+            document.set(key, value);
+        }
+        save::migrate::<Self>(&mut document)?;
+        Self::load(&document)
+    }
+}
+
+/// The start board's own fields, with the rest of the configuration,
+/// the seed when there is one, and the time in milliseconds.
+impl Saved for Screenshot {
+    const KIND: &'static str = "screenshot";
+
+    fn save(&self, document: &mut Document) {
+        self.start.save(document);
+        document.set("variant", self.configuration.variant.encode());
+        document.set("assisted", self.configuration.assisted);
+        if let Some(seed) = self.seed {
+            document.set("seed", seed);
+        }
+        document.set("millis", self.elapsed.as_millis());
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let start = Board::load(document)?;
+        let variant = document.get("variant")?;
+        let variant = Variant::decode(variant).ok_or_else(|| SaveError::Invalid {
+            field: "variant".to_owned(),
+            value: variant.to_owned(),
+        })?;
+        Ok(Self {
+            seed: match document.get("seed") {
+                Ok(_) => Some(document.parse("seed")?),
+                Err(_) => None,
+            },
+            configuration: Configuration {
+                width: start.width(),
+                height: start.height(),
+                mines: start.mines(),
+                variant,
+                assisted: document.parse("assisted")?,
+            },
+            elapsed: Duration::from_millis(document.parse("millis")?),
+            start,
+        })
+    }
+}
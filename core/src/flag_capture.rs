@@ -0,0 +1,117 @@
+use std::fmt;
+
+use crate::board::{Board, CellType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Winner(usize),
+    Draw,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    NotYourTurn,
+    OutOfBounds,
+    AlreadyRevealed,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MoveError::GameOver => "the game is over",
+            MoveError::NotYourTurn => "it is not your turn",
+            MoveError::OutOfBounds => "the cell is outside the board",
+            MoveError::AlreadyRevealed => "the cell is already revealed",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Two players take turns revealing cells. Finding a mine captures it and
+/// earns another turn; anything else passes the turn to the opponent.
+#[derive(Clone, Debug)]
+pub struct FlagCapture {
+    mines: usize,
+    scores: [usize; 2],
+    turn: usize,
+}
+
+impl FlagCapture {
+    pub fn new(mines: usize) -> Self {
+        Self {
+            mines,
+            scores: [0; 2],
+            turn: 0,
+        }
+    }
+
+    /// Restores a match from scores and turn received from a server.
+    pub fn with_state(mines: usize, scores: [usize; 2], turn: usize) -> Self {
+        Self {
+            mines,
+            scores,
+            turn,
+        }
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn scores(&self) -> [usize; 2] {
+        self.scores
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn mines_left(&self) -> usize {
+        self.mines - self.scores.iter().sum::<usize>()
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        // A player holding more than half the mines can no longer be caught
+        if let Some(player) = self.scores.iter().position(|&s| s * 2 > self.mines) {
+            return Some(Outcome::Winner(player));
+        }
+        if self.mines_left() == 0 {
+            return Some(Outcome::Draw);
+        }
+        None
+    }
+
+    pub fn play(
+        &mut self,
+        board: &mut Board,
+        player: usize,
+        row: usize,
+        col: usize,
+    ) -> Result<(), MoveError> {
+        if self.outcome().is_some() {
+            return Err(MoveError::GameOver);
+        }
+        if player != self.turn {
+            return Err(MoveError::NotYourTurn);
+        }
+        if !board.contains(row, col) {
+            return Err(MoveError::OutOfBounds);
+        }
+
+        match board.reveal(row, col) {
+            Some(CellType::Mine) => {
+                board.capture(row, col, self.turn);
+                self.scores[self.turn] += 1;
+                if self.outcome().is_some() {
+                    board.reveal_all();
+                }
+            }
+            Some(_) => self.turn = 1 - self.turn,
+            None => return Err(MoveError::AlreadyRevealed),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, CellState, CellType, Flood, MineKind};
+use crate::event::{Event, Listener};
+use crate::flag_capture::MoveError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// A classic single-player game: reveal every safe cell without hitting a
+/// mine. Flags are the player's own marks and only protect cells from
+/// accidental clicks and chords.
+///
+/// Every move is reported to the subscribed listeners as `Event`s.
+pub struct Game {
+    board: Board,
+    status: Status,
+    listeners: Vec<Box<dyn Listener>>,
+    /// Opening started by `reveal_gradually` that is still being uncovered.
+    flood: Option<Flood>,
+    /// The last opening's flood, kept so the next one reuses its memory.
+    spare: Flood,
+    /// Duds set off so far.
+    duds: usize,
+}
+
+impl fmt::Debug for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Game")
+            .field("board", &self.board)
+            .field("status", &self.status)
+            .field("listeners", &self.listeners.len())
+            .field("flood", &self.flood)
+            .finish()
+    }
+}
+
+impl Game {
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            status: Status::Playing,
+            listeners: Vec::new(),
+            flood: None,
+            spare: Flood::default(),
+            duds: 0,
+        }
+    }
+
+    pub fn subscribe(&mut self, listener: Box<dyn Listener>) {
+        self.listeners.push(listener);
+    }
+
+    fn emit(&mut self, event: Event) {
+        for listener in &mut self.listeners {
+            listener.on_event(&event);
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status != Status::Playing
+    }
+
+    /// Duds set off so far, which frontends charge time for.
+    pub fn duds_hit(&self) -> usize {
+        self.duds
+    }
+
+    /// Mines minus flags; negative when the player has over-flagged.
+    pub fn mines_left(&self) -> isize {
+        self.board.mines() as isize - self.board.flags() as isize
+    }
+
+    fn check(&self, row: usize, col: usize) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if !self.board.contains(row, col) {
+            return Err(MoveError::OutOfBounds);
+        }
+        Ok(())
+    }
+
+    pub fn reveal(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        self.reveal_gradually(row, col)?;
+        self.finish();
+        Ok(())
+    }
+
+    /// Like `reveal`, but a large opening is only started and `advance`
+    /// uncovers the rest over the following frames. Every other move
+    /// finishes it first, so the game ends up exactly where `reveal` would
+    /// have left it.
+    pub fn reveal_gradually(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        self.finish();
+        self.check(row, col)?;
+        let mut flood = std::mem::take(&mut self.spare);
+        if !self.board.restart_reveal(row, col, &mut flood) {
+            self.spare = flood;
+            return Err(MoveError::AlreadyRevealed);
+        }
+        self.flood = Some(flood);
+        self.advance(Duration::ZERO);
+        Ok(())
+    }
+
+    /// Whether an opening started by `reveal_gradually` is still being
+    /// uncovered.
+    pub fn is_flooding(&self) -> bool {
+        self.flood.is_some()
+    }
+
+    /// Uncovers more of the current opening for roughly `budget`, and
+    /// reports the move once it is done. Returns whether it is done.
+    pub fn advance(&mut self, budget: Duration) -> bool {
+        // Cells expanded between clock checks; reading the clock for every
+        // cell would cost more than the cells
+        const STEP: usize = 4096;
+
+        let Some(flood) = self.flood.as_mut() else {
+            return true;
+        };
+        let start = Instant::now();
+        while !self.board.spread(flood, STEP) && start.elapsed() < budget {}
+        if !flood.is_done() {
+            return false;
+        }
+        if let Some(flood) = self.flood.take() {
+            self.conclude(flood);
+        }
+        true
+    }
+
+    /// Uncovers whatever is left of the current opening at once.
+    pub fn finish(&mut self) {
+        if let Some(mut flood) = self.flood.take() {
+            self.board.spread(&mut flood, usize::MAX);
+            self.conclude(flood);
+        }
+    }
+
+    fn conclude(&mut self, flood: Flood) {
+        let revealed = flood.revealed();
+        let (row, col) = revealed[0];
+        self.settle(revealed);
+        if self.board.cell(row, col).cell_type == CellType::Empty && revealed.len() > 1 {
+            self.emit(Event::OpeningFlooded {
+                row,
+                col,
+                cells: revealed.len(),
+            });
+        }
+        self.spare = flood;
+    }
+
+    pub fn toggle_flag(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        self.finish();
+        self.check(row, col)?;
+        if self.board.cell(row, col).cell_state == CellState::Visible {
+            return Err(MoveError::AlreadyRevealed);
+        }
+        let flagged = self.board.toggle_flag(row, col);
+        self.emit(Event::Flagged { row, col, flagged });
+        Ok(())
+    }
+
+    pub fn chord(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        self.finish();
+        self.check(row, col)?;
+        let revealed = self.board.chord(row, col);
+        self.settle(&revealed);
+        Ok(())
+    }
+
+    /// Reports the cells a move uncovered and ends the game if it was lost or
+    /// won.
+    fn settle(&mut self, revealed: &[(usize, usize)]) {
+        let mut hit = None;
+        // Cells big mines blew open, settled after the move's own
+        let mut blown = Vec::new();
+        for i in 0.. {
+            let Some((row, col)) = revealed
+                .get(i)
+                .or_else(|| blown.get(i - revealed.len()))
+                .copied()
+            else {
+                break;
+            };
+            let cell_type = self.board.cell(row, col).cell_type;
+            self.emit(Event::CellRevealed {
+                row,
+                col,
+                cell_type,
+            });
+            match self.board.mine_kind(row, col) {
+                Some(MineKind::Normal) => hit = Some((row, col)),
+                Some(MineKind::Dud) => {
+                    self.duds += 1;
+                    self.emit(Event::DudHit { row, col });
+                }
+                Some(MineKind::Big) => {
+                    hit = Some((row, col));
+                    let cells = self.board.blast(row, col);
+                    self.emit(Event::Blast {
+                        row,
+                        col,
+                        cells: cells.len(),
+                    });
+                    blown.extend(cells);
+                }
+                None => {}
+            }
+        }
+
+        crate::trace!("move uncovered {} cells", revealed.len());
+        if let Some((row, col)) = hit.filter(|_| self.board.is_lost()) {
+            crate::info!("game lost on a mine at ({}, {})", row, col);
+            self.status = Status::Lost;
+            self.board.reveal_mines();
+            self.emit(Event::MineHit { row, col });
+        } else if let Some((row, col)) = hit {
+            // The mine stays shown, as a number would
+            let lives = self.board.lives_left();
+            crate::info!("mine hit at ({}, {}), {} lives left", row, col, lives);
+            self.emit(Event::LifeLost { row, col, lives });
+        }
+        if !self.is_over() && self.board.is_won() {
+            crate::info!("game won");
+            self.status = Status::Won;
+            self.emit(Event::GameWon);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Boards that can be cleared without guessing.
+//!
+//! Generation is rejection sampling: seeded candidates are drawn until one
+//! can be solved by deduction alone from the start cell. Candidates are
+//! checked on every core at once, and the lowest passing attempt wins so the
+//! result depends only on the seed, never on the thread count.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Instant;
+
+use crate::agent::View;
+use crate::board::{Board, BoardError, CellState, CellType, Pos};
+use crate::placement::Generator;
+use crate::solver::Knowledge;
+
+/// Candidates tried before giving up. Expert boards need a few hundred.
+pub const MAX_ATTEMPTS: u64 = 100_000;
+
+/// How far a long search for a board got, for showing on another thread,
+/// and a way for that thread to call it off.
+#[derive(Debug, Default)]
+pub struct Progress {
+    tried: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Candidates dealt so far.
+    pub fn tried(&self) -> u64 {
+        self.tried.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count(&self) {
+        self.tried.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Stops the search at the next candidate, which then fails with
+    /// `BoardError::Cancelled`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Generates a board that can be cleared from `start` without a guess.
+/// `start` and its neighbours never hold a mine, and `generator` places
+/// the others.
+pub fn no_guess(
+    width: usize,
+    height: usize,
+    mines: usize,
+    start: Pos,
+    seed: u64,
+    generator: &dyn Generator,
+) -> Result<Board, BoardError> {
+    let progress = Progress::default();
+    no_guess_with(width, height, mines, start, seed, generator, &progress)
+}
+
+/// Like `no_guess`, counting the candidates in `progress` and giving up
+/// once it is cancelled.
+pub fn no_guess_with(
+    width: usize,
+    height: usize,
+    mines: usize,
+    start: Pos,
+    seed: u64,
+    generator: &dyn Generator,
+    progress: &Progress,
+) -> Result<Board, BoardError> {
+    let begin = Instant::now();
+    // The first candidate runs alone so bad settings are reported once
+    let first = Board::placed(width, height, mines, seed, Some(start), generator)?;
+    progress.count();
+    if solvable(&first, start) {
+        return Ok(first);
+    }
+
+    let next = AtomicU64::new(1);
+    let found = AtomicU64::new(u64::MAX);
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let best = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let board =
+                        |seed| Board::placed(width, height, mines, seed, Some(start), generator);
+                    search(board, start, seed, &next, &found, progress)
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().expect("generator thread panicked"))
+            .min_by_key(|&(attempt, _)| attempt)
+    });
+
+    match best {
+        Some((attempt, board)) => {
+            crate::debug!(
+                "found a no-guess {}x{} board after {} attempts in {:?} on {} threads",
+                width,
+                height,
+                attempt + 1,
+                begin.elapsed(),
+                threads
+            );
+            Ok(board)
+        }
+        None if progress.is_cancelled() => Err(BoardError::Cancelled),
+        None => Err(BoardError::NoGuessNotFound {
+            attempts: MAX_ATTEMPTS,
+        }),
+    }
+}
+
+/// One worker's share of the search. Attempts are handed out in order, so
+/// once some worker passes attempt `n`, every attempt below `n` is already
+/// taken and only those still need finishing.
+fn search(
+    board: impl Fn(u64) -> Result<Board, BoardError>,
+    start: Pos,
+    seed: u64,
+    next: &AtomicU64,
+    found: &AtomicU64,
+    progress: &Progress,
+) -> Option<(u64, Board)> {
+    loop {
+        let attempt = next.fetch_add(1, Ordering::Relaxed);
+        if attempt >= MAX_ATTEMPTS
+            || attempt > found.load(Ordering::Relaxed)
+            || progress.is_cancelled()
+        {
+            return None;
+        }
+        let seed = seed.wrapping_add(attempt);
+        let board = board(seed).ok()?;
+        progress.count();
+        if solvable(&board, start) {
+            found.fetch_min(attempt, Ordering::Relaxed);
+            return Some((attempt, board));
+        }
+    }
+}
+
+/// How far deduction alone gets from an opening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every safe cell gets revealed.
+    Solvable,
+    /// The opening is off the board or a mine.
+    BadStart,
+    /// Deduction stops after revealing `revealed` cells, and the player
+    /// has to guess; `at` is the first hidden cell next to the revealed
+    /// ones, row by row.
+    Guess { at: Pos, revealed: usize },
+}
+
+/// Whether revealing `start` and then only ever acting on certainties
+/// clears the board.
+pub fn solvable(board: &Board, start: Pos) -> bool {
+    verify(board, start) == Verdict::Solvable
+}
+
+/// Reveals `start` and then only what `solver::Knowledge` proves safe,
+/// until the board is cleared or nothing more is proven. Flags already on
+/// `board` are ignored.
+pub fn verify(board: &Board, start: Pos) -> Verdict {
+    if !board.contains(start.row, start.col)
+        || board.cell(start.row, start.col).cell_type == CellType::Mine
+    {
+        return Verdict::BadStart;
+    }
+    let mut board = board.clone();
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            if board.cell(row, col).cell_state == CellState::Flagged {
+                board.toggle_flag(row, col);
+            }
+        }
+    }
+    board.reveal(start.row, start.col);
+    let hidden = |board: &Board, knowledge: Knowledge| -> Vec<(usize, usize)> {
+        let safe = knowledge.safe.into_iter();
+        safe.filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Hidden)
+            .collect()
+    };
+    loop {
+        // The full solver only runs once single numbers are stuck
+        let view = View::of(&board);
+        let mut safe = hidden(&board, Knowledge::single_point(&view));
+        if safe.is_empty() {
+            safe = hidden(&board, Knowledge::of(&view));
+        }
+        if safe.is_empty() {
+            break;
+        }
+        for (row, col) in safe {
+            board.reveal(row, col);
+        }
+    }
+    if board.is_won() {
+        return Verdict::Solvable;
+    }
+
+    let hidden =
+        |&(row, col): &(usize, usize)| board.cell(row, col).cell_state == CellState::Hidden;
+    let cells = (0..board.height()).flat_map(|row| (0..board.width()).map(move |col| (row, col)));
+    let frontier = cells.clone().filter(hidden).find(|&(row, col)| {
+        board
+            .adjacent(row, col)
+            .into_iter()
+            .any(|(r, c)| board.cell(r, c).cell_state == CellState::Visible)
+    });
+    let (row, col) = frontier
+        .or_else(|| cells.clone().find(hidden))
+        .expect("a board that is not won has a hidden cell");
+    Verdict::Guess {
+        at: Pos::new(row, col),
+        revealed: board.revealed(),
+    }
+}
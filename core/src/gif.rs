@@ -0,0 +1,187 @@
+//! A small animated GIF encoder: one global palette of up to 256 colours,
+//! frames given as palette indices, looping forever.
+//!
+//! Each frame after the first only stores the rectangle that changed
+//! since the one before, which keeps replays of large boards small.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// GIF codes never grow past 12 bits.
+const MAX_CODES: u16 = 4096;
+
+pub struct Encoder {
+    width: u16,
+    height: u16,
+    /// Bits per palette index, at least 2 as the format requires.
+    depth: u8,
+    bytes: Vec<u8>,
+    /// The frame before, to find what changed.
+    previous: Option<Vec<u8>>,
+}
+
+impl Encoder {
+    /// Starts a `width` by `height` animation. The palette is padded to a
+    /// power of two; it must not have more than 256 colours.
+    pub fn new(width: u16, height: u16, palette: &[[u8; 3]]) -> Self {
+        assert!(
+            !palette.is_empty() && palette.len() <= 256,
+            "a GIF palette has 1 to 256 colours"
+        );
+        let mut depth = 2;
+        while 1 << depth < palette.len() {
+            depth += 1;
+        }
+        let mut bytes = b"GIF89a".to_vec();
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        // A global colour table of 2^depth entries, 8-bit colour
+        bytes.push(0x80 | 0x70 | (depth - 1));
+        bytes.extend([0, 0]);
+        for i in 0..1 << depth {
+            bytes.extend(palette.get(i).copied().unwrap_or_default());
+        }
+        // Loop forever
+        bytes.extend([0x21, 0xff, 0x0b]);
+        bytes.extend(b"NETSCAPE2.0");
+        bytes.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+        Self {
+            width,
+            height,
+            depth,
+            bytes,
+            previous: None,
+        }
+    }
+
+    /// Adds a frame of `width * height` palette indices, row by row, shown
+    /// for `delay` (rounded to the format's hundredths of a second).
+    pub fn frame(&mut self, pixels: &[u8], delay: Duration) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        assert_eq!(pixels.len(), width * height, "a frame covers the image");
+        let (x, y, w, h) = match &self.previous {
+            Some(previous) => changed(previous, pixels, width).unwrap_or((0, 0, 1, 1)),
+            None => (0, 0, width, height),
+        };
+        let centiseconds = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        self.bytes.extend([0x21, 0xf9, 0x04, 0x00]);
+        self.bytes.extend(centiseconds.to_le_bytes());
+        self.bytes.extend([0x00, 0x00]);
+
+        self.bytes.push(0x2c);
+        for value in [x, y, w, h] {
+            self.bytes.extend((value as u16).to_le_bytes());
+        }
+        self.bytes.push(0x00);
+        let region: Vec<u8> = (y..y + h)
+            .flat_map(|row| pixels[row * width + x..row * width + x + w].iter().copied())
+            .collect();
+        self.bytes.push(self.depth);
+        let data = compress(&region, self.depth);
+        for block in data.chunks(255) {
+            self.bytes.push(block.len() as u8);
+            self.bytes.extend(block);
+        }
+        self.bytes.push(0x00);
+        self.previous = Some(pixels.to_vec());
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.push(0x3b);
+        self.bytes
+    }
+}
+
+/// The smallest rectangle holding every pixel that differs, as x, y,
+/// width and height. `None` if the frames are the same.
+fn changed(before: &[u8], after: &[u8], width: usize) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (i, _) in before
+        .iter()
+        .zip(after)
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+    {
+        let (x, y) = (i % width, i / width);
+        bounds = Some(match bounds {
+            None => (x, y, x, y),
+            Some((left, top, right, bottom)) => {
+                (left.min(x), top.min(y), right.max(x), bottom.max(y))
+            }
+        });
+    }
+    bounds.map(|(left, top, right, bottom)| (left, top, right - left + 1, bottom - top + 1))
+}
+
+/// GIF's variable-width LZW, packed least significant bit first.
+fn compress(pixels: &[u8], depth: u8) -> Vec<u8> {
+    let clear = 1u16 << depth;
+    let end = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut width = depth + 1;
+    writer.write(clear, width);
+
+    let mut pixels = pixels.iter();
+    let Some(&first) = pixels.next() else {
+        writer.write(end, width);
+        return writer.finish();
+    };
+    let mut current = u16::from(first);
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(current, pixel)) {
+            current = code;
+            continue;
+        }
+        writer.write(current, width);
+        if next < MAX_CODES {
+            table.insert((current, pixel), next);
+            // The decoder widens its codes one step later than the table
+            // grows, once the next code no longer fits
+            if next == 1 << width && width < 12 {
+                width += 1;
+            }
+            next += 1;
+        } else {
+            writer.write(clear, width);
+            table.clear();
+            next = end + 1;
+            width = depth + 1;
+        }
+        current = u16::from(pixel);
+    }
+    writer.write(current, width);
+    // The decoder adds an entry for that code too, and may widen for it
+    if next == 1 << width && width < 12 {
+        width += 1;
+    }
+    writer.write(end, width);
+    writer.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
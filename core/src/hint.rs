@@ -0,0 +1,102 @@
+//! Hints: on request, point out a hidden cell the numbers prove safe.
+//!
+//! Each game has a budget of hints, and every hint used adds a time
+//! penalty to the finished game. Games that used any are recorded as
+//! assisted and left out of personal bests.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::agent::View;
+use crate::board::Board;
+use crate::protocol::CellView;
+use crate::solver::Knowledge;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HintError {
+    /// The game's budget is used up.
+    Exhausted,
+    /// Nothing can be proven safe from the numbers shown; the player has
+    /// to guess. Costs nothing.
+    NoSafeCell,
+    /// The board is played under rules the solver doesn't model, such as
+    /// lying numbers or spare lives, so it can't be trusted to prove
+    /// anything. Costs nothing.
+    Unsupported,
+}
+
+impl fmt::Display for HintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HintError::Exhausted => write!(f, "no hints left"),
+            HintError::NoSafeCell => write!(f, "no cell can be proven safe"),
+            HintError::Unsupported => write!(f, "no hints under custom rules"),
+        }
+    }
+}
+
+impl std::error::Error for HintError {}
+
+/// The hints of one game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hints {
+    budget: u32,
+    penalty: Duration,
+    used: u32,
+}
+
+impl Hints {
+    /// `budget` hints per game, each adding `penalty` to the game's time.
+    pub fn new(budget: u32, penalty: Duration) -> Self {
+        Self {
+            budget,
+            penalty,
+            used: 0,
+        }
+    }
+
+    /// No hints at all.
+    pub fn none() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    pub fn used(&self) -> u32 {
+        self.used
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.budget - self.used
+    }
+
+    /// Time added to the game for the hints used so far.
+    pub fn penalty(&self) -> Duration {
+        self.penalty * self.used
+    }
+
+    /// Finds a hidden cell that is provably safe and charges a hint for it.
+    /// Of several, the first row by row is given, so hints don't jump
+    /// around.
+    pub fn request(&mut self, board: &Board) -> Result<(usize, usize), HintError> {
+        if !board.rules().is_classic() {
+            return Err(HintError::Unsupported);
+        }
+        if self.remaining() == 0 {
+            return Err(HintError::Exhausted);
+        }
+        let view = View::of(board);
+        let safe = Knowledge::of(&view)
+            .safe
+            .into_iter()
+            .filter(|&(row, col)| view.get(row, col) == CellView::Hidden && view.in_sight(row, col))
+            .min()
+            .ok_or(HintError::NoSafeCell)?;
+        self.used += 1;
+        Ok(safe)
+    }
+}
+
+impl Default for Hints {
+    fn default() -> Self {
+        Self::none()
+    }
+}
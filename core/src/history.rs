@@ -0,0 +1,284 @@
+//! Every recorded game as a table for spreadsheets and scripts: one row
+//! per game, oldest first, as CSV with a header row or as a JSON array of
+//! objects with the same keys.
+//!
+//! Times are in milliseconds, and games end at both a Unix timestamp and
+//! the same moment in UTC as ISO 8601. A game without a seed leaves its
+//! cell empty in CSV and has a `null` one in JSON.
+//!
+//! `import` reads either export back, or a whole stats file, for merging
+//! into the games recorded here with `Stats::merge`.
+
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::save::{self, json, SaveError};
+use crate::stats::{Record, Stats, Variant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// File extension, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            other => Err(format!("unknown history format `{}`", other)),
+        }
+    }
+}
+
+/// One cell of the table.
+enum Value {
+    Text(String),
+    Number(u64),
+    Bool(bool),
+    Missing,
+}
+
+/// The table's columns, in order.
+pub const COLUMNS: [&str; 18] = [
+    "id",
+    "finished",
+    "finished_utc",
+    "width",
+    "height",
+    "mines",
+    "variant",
+    "assisted",
+    "seed",
+    "result",
+    "millis",
+    "bbbv",
+    "clicks",
+    "wasted",
+    "flags",
+    "hints",
+    "opening_clicks",
+    "number_clicks",
+];
+
+/// A game's cells, one for each of `COLUMNS`.
+fn row(record: &Record) -> [Value; 18] {
+    [
+        Value::Text(record.id()),
+        Value::Number(record.finished),
+        Value::Text(utc(record.finished)),
+        Value::Number(record.width as u64),
+        Value::Number(record.height as u64),
+        Value::Number(record.mines as u64),
+        Value::Text(record.variant.encode()),
+        Value::Bool(record.is_assisted()),
+        record.seed.map_or(Value::Missing, Value::Number),
+        Value::Text(if record.won { "won" } else { "lost" }.to_owned()),
+        Value::Number(record.time.as_millis() as u64),
+        Value::Number(record.bbbv.into()),
+        Value::Number(record.clicks.into()),
+        Value::Number(record.wasted.into()),
+        Value::Number(record.flags.into()),
+        Value::Number(record.hints.into()),
+        Value::Number(record.opening_clicks.into()),
+        Value::Number(record.number_clicks.into()),
+    ]
+}
+
+/// `records` as a file in `format`.
+pub fn export(records: &[Record], format: Format) -> String {
+    match format {
+        Format::Csv => csv(records),
+        Format::Json => json(records),
+    }
+}
+
+/// No cell holds a comma, quote or line break, so none is quoted.
+fn csv(records: &[Record]) -> String {
+    let mut text = COLUMNS.join(",");
+    text.push('\n');
+    for record in records {
+        let cells: Vec<String> = row(record)
+            .into_iter()
+            .map(|value| match value {
+                Value::Text(text) => text,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Missing => String::new(),
+            })
+            .collect();
+        text.push_str(&cells.join(","));
+        text.push('\n');
+    }
+    text
+}
+
+fn json(records: &[Record]) -> String {
+    let mut text = String::from("[");
+    for (i, record) in records.iter().enumerate() {
+        text.push_str(if i == 0 { "\n  {" } else { ",\n  {" });
+        for (j, (name, value)) in COLUMNS.iter().zip(row(record)).enumerate() {
+            let separator = if j == 0 { "" } else { ", " };
+            let value = match value {
+                Value::Text(text) => format!("\"{}\"", text),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Missing => "null".to_owned(),
+            };
+            let _ = write!(text, "{}\"{}\": {}", separator, name, value);
+        }
+        text.push('}');
+    }
+    if !records.is_empty() {
+        text.push('\n');
+    }
+    text.push_str("]\n");
+    text
+}
+
+/// Reads games exported as CSV or JSON, or a stats file in any save
+/// format. Exports carry no achievements or heatmaps.
+pub fn import(bytes: &[u8]) -> Result<Stats, SaveError> {
+    let text = std::str::from_utf8(bytes).map(str::trim_start);
+    let rows = match text {
+        Ok(text) if text.starts_with('[') => json::rows(text)?,
+        Ok(text) if text.starts_with(COLUMNS[0]) => csv_rows(text)?,
+        _ => return save::load(bytes),
+    };
+    let records = rows
+        .iter()
+        .enumerate()
+        .map(|(index, cells)| Row { index, cells }.record())
+        .collect::<Result<_, _>>()?;
+    Ok(Stats {
+        records,
+        ..Stats::default()
+    })
+}
+
+/// The rows under a CSV header, each cell named by its column.
+fn csv_rows(text: &str) -> Result<Vec<Vec<(String, String)>>, SaveError> {
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or("").split(',').collect();
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let cells: Vec<&str> = line.split(',').collect();
+            if cells.len() != header.len() {
+                return Err(SaveError::Corrupt(format!(
+                    "row {} has {} cells for {} columns",
+                    i,
+                    cells.len(),
+                    header.len()
+                )));
+            }
+            Ok(header
+                .iter()
+                .zip(cells)
+                .map(|(name, cell)| (name.trim().to_owned(), cell.trim().to_owned()))
+                .collect())
+        })
+        .collect()
+}
+
+/// One imported row, for reading its cells.
+struct Row<'a> {
+    /// Counted from 0, for errors.
+    index: usize,
+    cells: &'a [(String, String)],
+}
+
+impl<'a> Row<'a> {
+    fn get(&self, column: &str) -> Result<&'a str, SaveError> {
+        self.cells
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| SaveError::Missing(format!("{} of row {}", column, self.index)))
+    }
+
+    fn invalid(&self, column: &str, value: &str) -> SaveError {
+        SaveError::Invalid {
+            field: format!("{} of row {}", column, self.index),
+            value: value.to_owned(),
+        }
+    }
+
+    fn parse<T: FromStr>(&self, column: &str) -> Result<T, SaveError> {
+        let value = self.get(column)?;
+        value.parse().map_err(|_| self.invalid(column, value))
+    }
+
+    /// The game in the row. Its id, UTC time and assistance follow from
+    /// the other columns, so they aren't read.
+    fn record(&self) -> Result<Record, SaveError> {
+        let variant = self.get("variant")?;
+        Ok(Record {
+            width: self.parse("width")?,
+            height: self.parse("height")?,
+            mines: self.parse("mines")?,
+            won: match self.get("result")? {
+                "won" => true,
+                "lost" => false,
+                other => return Err(self.invalid("result", other)),
+            },
+            time: Duration::from_millis(self.parse("millis")?),
+            hints: self.parse("hints")?,
+            clicks: self.parse("clicks")?,
+            wasted: self.parse("wasted")?,
+            flags: self.parse("flags")?,
+            finished: self.parse("finished")?,
+            bbbv: self.parse("bbbv")?,
+            opening_clicks: self.parse("opening_clicks")?,
+            number_clicks: self.parse("number_clicks")?,
+            variant: Variant::decode(variant).ok_or_else(|| self.invalid("variant", variant))?,
+            seed: match self.get("seed")? {
+                "" => None,
+                _ => Some(self.parse("seed")?),
+            },
+        })
+    }
+}
+
+/// Seconds since the Unix epoch as an ISO 8601 time in UTC, from Howard
+/// Hinnant's days-to-civil algorithm.
+fn utc(seconds: u64) -> String {
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted + 2) / 5 + 1;
+    let month = if shifted < 10 {
+        shifted + 3
+    } else {
+        shifted - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
@@ -0,0 +1,42 @@
+pub mod achievement;
+pub mod agent;
+pub mod bench;
+pub mod board;
+pub mod console;
+pub mod drift;
+pub mod event;
+pub mod export;
+pub mod flag_capture;
+pub mod game;
+pub mod generate;
+pub mod gif;
+pub mod hint;
+pub mod history;
+pub mod log;
+pub mod metrics;
+pub mod placement;
+pub mod plugin;
+pub mod png;
+pub mod protocol;
+pub mod rating;
+pub mod replay;
+pub mod review;
+pub mod rules;
+pub mod save;
+pub mod scenario;
+pub mod script;
+pub mod share;
+pub mod sim;
+pub mod solver;
+pub mod sound;
+pub mod stats;
+pub mod sync;
+pub mod tournament;
+pub mod tutorial;
+pub mod verify;
+
+pub use board::{Board, BoardError, Cell, CellState, CellType, Flood, MineKind, Pos};
+pub use event::{Event, Listener};
+pub use flag_capture::{FlagCapture, MoveError, Outcome};
+pub use game::{Game, Status};
+pub use rules::Ruleset;
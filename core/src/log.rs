@@ -0,0 +1,139 @@
+//! Levelled diagnostics shared by every binary in the workspace.
+//!
+//! Records go to stderr, or to the file given with `--log-file`. Nothing
+//! below the level chosen with `--log-level` is formatted, so leaving
+//! `debug!` and `trace!` calls in hot paths costs one atomic load.
+//!
+//! ```text
+//! 0.482 DEBUG minesweeper_core::board: generated 16x16 board with 50 mines in 31µs
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Off => "OFF",
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Level::Off),
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!(
+                "unknown log level {} (expected off, error, warn, info, debug or trace)",
+                s
+            )),
+        }
+    }
+}
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+static FILE: Mutex<Option<File>> = Mutex::new(None);
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Sets the most verbose level that is recorded and where records go.
+pub fn init(level: Level, file: Option<&str>) -> io::Result<()> {
+    START.get_or_init(Instant::now);
+    let file = file.map(File::create).transpose()?;
+    *FILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = file;
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Reads `--log-level LEVEL` and `--log-file PATH` from the command line,
+/// falling back to `default` when no level is given.
+pub fn init_from_args(args: &[String], default: Level) -> Result<(), String> {
+    let value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+    };
+    let level = match value("--log-level") {
+        Some(level) => level.parse()?,
+        None => default,
+    };
+    let file = value("--log-file").map(String::as_str);
+    init(level, file).map_err(|err| format!("could not open log file: {}", err))
+}
+
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Writes one record. Use the `error!` .. `trace!` macros instead.
+pub fn write(level: Level, target: &str, args: fmt::Arguments) {
+    let elapsed = START.get_or_init(Instant::now).elapsed().as_secs_f64();
+    let line = format!("{:.3} {:<5} {}: {}\n", elapsed, level.name(), target, args);
+
+    let mut file = FILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _ = match file.as_mut() {
+        Some(file) => file.write_all(line.as_bytes()),
+        None => io::stderr().write_all(line.as_bytes()),
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            $crate::log::write($level, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::__log!($crate::log::Level::Trace, $($arg)+) };
+}
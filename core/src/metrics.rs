@@ -0,0 +1,355 @@
+//! Measures of how much work a board takes, as used by the speedrunning
+//! community to compare games on different boards.
+
+use std::collections::HashMap;
+
+use crate::board::{Board, CellState, CellType};
+use crate::game::Game;
+use crate::replay;
+use crate::sim::Move;
+
+/// The openings of a board: connected regions of empty cells, each
+/// uncovered by one click together with the numbers around it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Openings {
+    width: usize,
+    /// The opening each cell belongs to, for empty cells and the numbers
+    /// bordering an opening. A number between two openings keeps the
+    /// first.
+    region: Vec<Option<usize>>,
+    /// Cells each opening uncovers, its border included.
+    sizes: Vec<usize>,
+}
+
+impl Openings {
+    pub fn of(board: &Board) -> Self {
+        let (width, height) = (board.width(), board.height());
+        let mut region = vec![None; width * height];
+        let mut sizes = Vec::new();
+        for row in 0..height {
+            for col in 0..width {
+                if region[row * width + col].is_some()
+                    || board.cell(row, col).cell_type != CellType::Empty
+                {
+                    continue;
+                }
+                let id = sizes.len();
+                let mut size = 1;
+                region[row * width + col] = Some(id);
+                let mut stack = vec![(row, col)];
+                while let Some((r, c)) = stack.pop() {
+                    for (nr, nc) in board.adjacent(r, c) {
+                        let i = nr * width + nc;
+                        if region[i].is_some() {
+                            continue;
+                        }
+                        region[i] = Some(id);
+                        size += 1;
+                        if board.cell(nr, nc).cell_type == CellType::Empty {
+                            stack.push((nr, nc));
+                        }
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        Self {
+            width,
+            region,
+            sizes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// How many cells each opening uncovers, in the order they were found:
+    /// row by row from the top left.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// The opening that uncovers `(row, col)`, if one does.
+    pub fn at(&self, row: usize, col: usize) -> Option<usize> {
+        self.region[row * self.width + col]
+    }
+}
+
+/// Bechtel's Board Benchmark Value: the fewest left clicks that clear the
+/// board without chording. Every opening takes one click, and so does every
+/// number that no opening uncovers.
+pub fn bbbv(board: &Board) -> usize {
+    let openings = Openings::of(board);
+    let lone_numbers = (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+        .filter(|&(row, col)| {
+            openings.at(row, col).is_none()
+                && matches!(board.cell(row, col).cell_type, CellType::Number(_))
+        })
+        .count();
+    openings.len() + lone_numbers
+}
+
+/// Why a click did nothing towards clearing the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waste {
+    /// The board didn't change.
+    NoEffect,
+    /// A flag taken back off.
+    Unflag,
+    /// A flag that no chord went on to use, so clicking the cells around
+    /// it would have cost no more.
+    UnusedFlag,
+}
+
+/// The moves of a game that cost a click without helping to clear the
+/// board, by their index in `moves`, in order. A flag counts as used once
+/// a chord next to it uncovers something.
+pub fn waste(start: &Board, moves: &[Move]) -> Vec<(usize, Waste)> {
+    let mut game = Game::new(start.clone());
+    // Flags standing that no chord has used yet, by where they are
+    let mut flags: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut waste = Vec::new();
+    for (i, &step) in moves.iter().enumerate() {
+        if game.is_over() {
+            break;
+        }
+        let step = replay::resolve(game.board(), step);
+        let before = game.board().revision();
+        replay::play(&mut game, step);
+        if game.board().revision() == before {
+            waste.push((i, Waste::NoEffect));
+            continue;
+        }
+        match step {
+            Move::Flag(row, col)
+                if game.board().cell(row, col).cell_state == CellState::Flagged =>
+            {
+                flags.insert((row, col), i);
+            }
+            Move::Flag(row, col) => {
+                waste.push((i, Waste::Unflag));
+                if let Some(placed) = flags.remove(&(row, col)) {
+                    waste.push((placed, Waste::UnusedFlag));
+                }
+            }
+            Move::Chord(row, col) => {
+                for pos in game.board().adjacent(row, col) {
+                    flags.remove(&pos);
+                }
+            }
+            Move::Reveal(..) => {}
+        }
+    }
+    waste.extend(
+        flags
+            .into_values()
+            .map(|placed| (placed, Waste::UnusedFlag)),
+    );
+    waste.sort_unstable_by_key(|&(i, _)| i);
+    waste
+}
+
+/// The click-count benchmarks of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Benchmarks {
+    pub bbbv: usize,
+    pub zini: usize,
+    pub hzini: usize,
+}
+
+impl Benchmarks {
+    pub fn of(board: &Board) -> Self {
+        Self {
+            bbbv: bbbv(board),
+            zini: zini(board),
+            hzini: hzini(board),
+        }
+    }
+}
+
+/// Greedy ZiNi: a count of left clicks, flags and chords that clears the
+/// board, found by clicking every opening and then always making the
+/// chord that saves the most clicks on the whole board. Lower than 3BV
+/// wherever chording pays off; not always the true minimum.
+pub fn zini(board: &Board) -> usize {
+    Sweep::new(board).run(false)
+}
+
+/// Human ZiNi: like ZiNi, but chords are made in reading order, the
+/// first one that saves anything each time, the way a player works
+/// through a board rather than searching all of it.
+pub fn hzini(board: &Board) -> usize {
+    Sweep::new(board).run(true)
+}
+
+/// A board being cleared by one of the ZiNi algorithms.
+struct Sweep<'a> {
+    board: &'a Board,
+    openings: Openings,
+    open: Vec<bool>,
+    flagged: Vec<bool>,
+    clicks: usize,
+    /// Every cell's `premium`, kept up to date around the cells that
+    /// change so each step doesn't recount the whole board.
+    premiums: Vec<Option<isize>>,
+    /// Cells opened or flagged since the premiums were last updated.
+    changed: Vec<(usize, usize)>,
+}
+
+impl<'a> Sweep<'a> {
+    fn new(board: &'a Board) -> Self {
+        let cells = board.width() * board.height();
+        Self {
+            board,
+            openings: Openings::of(board),
+            open: vec![false; cells],
+            flagged: vec![false; cells],
+            clicks: 0,
+            premiums: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        row * self.board.width() + col
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.board.width();
+        (0..self.board.height()).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    fn is_closed_safe(&self, row: usize, col: usize) -> bool {
+        !self.open[self.index(row, col)] && self.board.cell(row, col).cell_type != CellType::Mine
+    }
+
+    fn run(mut self, reading_order: bool) -> usize {
+        for (row, col) in self.cells() {
+            if self.is_closed_safe(row, col)
+                && self.board.cell(row, col).cell_type == CellType::Empty
+            {
+                self.clicks += 1;
+                self.uncover(row, col);
+            }
+        }
+        self.premiums = self
+            .cells()
+            .map(|(row, col)| self.premium(row, col))
+            .collect();
+        self.changed.clear();
+        loop {
+            self.refresh();
+            let mut best: Option<((usize, usize), isize)> = None;
+            for (i, premium) in self.premiums.iter().enumerate() {
+                let Some(premium) = *premium else {
+                    continue;
+                };
+                let (row, col) = (i / self.board.width(), i % self.board.width());
+                if premium > 0 && best.is_none_or(|(_, most)| premium > most) {
+                    best = Some(((row, col), premium));
+                    if reading_order {
+                        break;
+                    }
+                }
+            }
+            match best {
+                Some(((row, col), _)) => self.chord(row, col),
+                None => {
+                    let Some((row, col)) = self.cells().find(|&(r, c)| self.is_closed_safe(r, c))
+                    else {
+                        return self.clicks;
+                    };
+                    self.clicks += 1;
+                    self.uncover(row, col);
+                }
+            }
+        }
+    }
+
+    /// Updates the premiums of the changed cells and their neighbours.
+    fn refresh(&mut self) {
+        let changed = std::mem::take(&mut self.changed);
+        for (row, col) in changed {
+            for (r, c) in std::iter::once((row, col)).chain(self.board.adjacent(row, col)) {
+                let i = self.index(r, c);
+                self.premiums[i] = self.premium(r, c);
+            }
+        }
+    }
+
+    /// Clicks saved by opening `(row, col)` if needed, flagging the mines
+    /// around it and chording it, against clicking each 3BV piece the
+    /// chord opens. `None` for cells that can't be chorded.
+    fn premium(&self, row: usize, col: usize) -> Option<isize> {
+        if !matches!(self.board.cell(row, col).cell_type, CellType::Number(_)) {
+            return None;
+        }
+        let closed = !self.open[self.index(row, col)];
+        let mut pieces = Vec::new();
+        let mut lone = 0;
+        let mut flags = 0;
+        let cells = std::iter::once((row, col))
+            .filter(|_| closed)
+            .chain(self.board.adjacent(row, col));
+        for (r, c) in cells {
+            let i = self.index(r, c);
+            if self.board.cell(r, c).cell_type == CellType::Mine {
+                flags += usize::from(!self.flagged[i]);
+            } else if !self.open[i] {
+                match self.openings.at(r, c) {
+                    Some(id) if self.board.cell(r, c).cell_type == CellType::Empty => {
+                        if !pieces.contains(&id) {
+                            pieces.push(id);
+                        }
+                    }
+                    Some(_) => {}
+                    None => lone += 1,
+                }
+            }
+        }
+        let saved = (pieces.len() + lone) as isize;
+        Some(saved - flags as isize - 1 - isize::from(closed))
+    }
+
+    fn chord(&mut self, row: usize, col: usize) {
+        if !self.open[self.index(row, col)] {
+            self.clicks += 1;
+            self.uncover(row, col);
+        }
+        for (r, c) in self.board.adjacent(row, col) {
+            let i = self.index(r, c);
+            if self.board.cell(r, c).cell_type == CellType::Mine {
+                if !self.flagged[i] {
+                    self.flagged[i] = true;
+                    self.changed.push((r, c));
+                    self.clicks += 1;
+                }
+            } else {
+                self.uncover(r, c);
+            }
+        }
+        self.clicks += 1;
+    }
+
+    /// Opens a safe cell, and the rest of its opening if it is empty.
+    fn uncover(&mut self, row: usize, col: usize) {
+        let mut stack = vec![(row, col)];
+        while let Some((r, c)) = stack.pop() {
+            let i = self.index(r, c);
+            if self.open[i] {
+                continue;
+            }
+            self.open[i] = true;
+            self.changed.push((r, c));
+            if self.board.cell(r, c).cell_type == CellType::Empty {
+                stack.extend(self.board.adjacent(r, c));
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Where a new board's mines go.
+//!
+//! A `Generator` picks the mine cells and `Board` fills in the numbers.
+//! `Uniform` is the classic game; the others change how a board feels
+//! rather than how many mines it holds. Every generator takes its
+//! randomness from the caller, so a seed gives the same board each time.
+
+use rand::seq::index;
+use rand::{Rng, RngCore};
+
+pub trait Generator: Sync {
+    /// Name in share codes, saves and on screen.
+    fn name(&self) -> &'static str;
+
+    /// Picks `mines` distinct cells of a `width` by `height` board as
+    /// row-major indices. `kept` lists the cells that must stay free,
+    /// sorted, and leaves room for all the mines.
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize>;
+}
+
+/// Every cell equally likely.
+pub struct Uniform;
+
+/// Mines attract each other: a cell is more likely the more mines are
+/// already next to it, leaving clumps and wide empty stretches.
+pub struct Clustered;
+
+/// Mines keep their distance: no two touch while there is still room
+/// elsewhere, so numbers stay low and openings small.
+pub struct Dispersed;
+
+/// Sparse at the top and dense at the bottom, where a cell is four times
+/// as likely to hold a mine.
+pub struct Gradient;
+
+/// Density rising row by row, from `from` percent at the top to `to` at
+/// the bottom when the board holds the average of the two; other mine
+/// counts scale both ends alike. Each row gets exactly its share, so one
+/// board ramps from easy to hard.
+pub struct Ramp {
+    pub from: u8,
+    pub to: u8,
+}
+
+/// Every generator, the classic one first.
+pub const ALL: [&dyn Generator; 5] = [&Uniform, &Clustered, &Dispersed, &Gradient, &Ramp::STEEP];
+
+pub fn named(name: &str) -> Option<&'static dyn Generator> {
+    ALL.into_iter().find(|generator| generator.name() == name)
+}
+
+impl Generator for Uniform {
+    fn name(&self) -> &'static str {
+        "uniform"
+    }
+
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        // Sample among the cells outside `kept`, then step each sampled
+        // index past the kept cells at or below it
+        let free = width * height - kept.len();
+        index::sample(rng, free, mines)
+            .into_iter()
+            .map(|mut i| {
+                for &k in kept {
+                    if k <= i {
+                        i += 1;
+                    }
+                }
+                i
+            })
+            .collect()
+    }
+}
+
+impl Generator for Clustered {
+    fn name(&self) -> &'static str {
+        "clustered"
+    }
+
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        weighted(width, height, mines, kept, rng, |_, near| {
+            1 + 6 * near as u64
+        })
+    }
+}
+
+impl Generator for Dispersed {
+    fn name(&self) -> &'static str {
+        "dispersed"
+    }
+
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        weighted(width, height, mines, kept, rng, |_, near| {
+            u64::from(near == 0)
+        })
+    }
+}
+
+impl Generator for Gradient {
+    fn name(&self) -> &'static str {
+        "gradient"
+    }
+
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        let rows = height.max(2) as u64 - 1;
+        weighted(width, height, mines, kept, rng, |i, _| {
+            rows + 3 * (i / width) as u64
+        })
+    }
+}
+
+impl Ramp {
+    /// The ramp in `ALL`, from 5% to 35%.
+    pub const STEEP: Ramp = Ramp { from: 5, to: 35 };
+
+    /// Mines a `width` by `height` board needs for the density to run
+    /// from `from` to `to`.
+    pub fn mines(&self, width: usize, height: usize) -> usize {
+        let percent = usize::from(self.from) + usize::from(self.to);
+        (width * height * percent + 100) / 200
+    }
+
+    /// The density at the top and bottom rows, in percent, with `mines`
+    /// on a `width` by `height` board.
+    pub fn ends(&self, width: usize, height: usize, mines: usize) -> (f64, f64) {
+        let average = 100.0 * mines as f64 / (width * height) as f64;
+        let middle = (f64::from(self.from) + f64::from(self.to)) / 2.0;
+        let scale = if middle > 0.0 { average / middle } else { 0.0 };
+        (f64::from(self.from) * scale, f64::from(self.to) * scale)
+    }
+
+    /// How much of the mines row `row` gets, relative to the others.
+    fn weight(&self, row: usize, height: usize) -> u64 {
+        let from = u64::from(self.from) * (height - 1 - row) as u64;
+        let to = u64::from(self.to) * row as u64;
+        match height {
+            1 => 1,
+            _ => from + to,
+        }
+    }
+}
+
+impl Generator for Ramp {
+    fn name(&self) -> &'static str {
+        "ramp"
+    }
+
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        kept: &[usize],
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        let free: Vec<Vec<usize>> = (0..height)
+            .map(|row| {
+                (row * width..(row + 1) * width)
+                    .filter(|i| kept.binary_search(i).is_err())
+                    .collect()
+            })
+            .collect();
+        let weights: Vec<u64> = (0..height)
+            .map(|row| self.weight(row, height).max(1))
+            .collect();
+        let total: u64 = weights.iter().sum();
+        // Each row's share rounded down, then the mines left over go one
+        // at a time to the rows furthest below their share
+        let mut counts: Vec<usize> = weights
+            .iter()
+            .zip(&free)
+            .map(|(&weight, free)| ((mines as u64 * weight / total) as usize).min(free.len()))
+            .collect();
+        let mut left = mines - counts.iter().sum::<usize>();
+        while left > 0 {
+            let shortfall = |row: usize| {
+                i128::from(mines as u64 * weights[row]) - (counts[row] as i128 * i128::from(total))
+            };
+            let Some(row) = (0..height)
+                .filter(|&row| counts[row] < free[row].len())
+                .max_by_key(|&row| shortfall(row))
+            else {
+                break;
+            };
+            counts[row] += 1;
+            left -= 1;
+        }
+        free.iter()
+            .zip(counts)
+            .flat_map(|(free, count)| {
+                index::sample(rng, free.len(), count)
+                    .into_iter()
+                    .map(|i| free[i])
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Picks mines one at a time, each cell in proportion to its weight given
+/// its index and the number of mines already next to it. Once every free
+/// cell weighs nothing, the rest are picked uniformly.
+fn weighted(
+    width: usize,
+    height: usize,
+    mines: usize,
+    kept: &[usize],
+    rng: &mut dyn RngCore,
+    weight: impl Fn(usize, usize) -> u64,
+) -> Vec<usize> {
+    let cells = width * height;
+    let mut taken = vec![false; cells];
+    for &k in kept {
+        taken[k] = true;
+    }
+    let mut near = vec![0; cells];
+    let mut tree = Fenwick::new(cells);
+    for i in (0..cells).filter(|&i| !taken[i]) {
+        tree.set(i, weight(i, 0));
+    }
+
+    let mut uniform = false;
+    let mut placed = Vec::with_capacity(mines);
+    for _ in 0..mines {
+        if tree.total() == 0 && !uniform {
+            uniform = true;
+            for i in (0..cells).filter(|&i| !taken[i]) {
+                tree.set(i, 1);
+            }
+        }
+        let i = tree.find(rng.gen_range(0..tree.total()));
+        taken[i] = true;
+        tree.set(i, 0);
+        placed.push(i);
+        if uniform {
+            continue;
+        }
+        let (row, col) = (i / width, i % width);
+        for r in row.saturating_sub(1)..(row + 2).min(height) {
+            for c in col.saturating_sub(1)..(col + 2).min(width) {
+                let j = r * width + c;
+                if !taken[j] {
+                    near[j] += 1;
+                    tree.set(j, weight(j, near[j]));
+                }
+            }
+        }
+    }
+    placed
+}
+
+/// Weights with prefix sums, so a weighted pick among many cells stays
+/// cheap as the weights change.
+struct Fenwick {
+    sums: Vec<u64>,
+    weights: Vec<u64>,
+    total: u64,
+}
+
+impl Fenwick {
+    fn new(len: usize) -> Self {
+        Self {
+            sums: vec![0; len + 1],
+            weights: vec![0; len],
+            total: 0,
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.total
+    }
+
+    fn set(&mut self, i: usize, weight: u64) {
+        // Wrapping arithmetic lets a lighter weight subtract
+        let delta = weight.wrapping_sub(self.weights[i]);
+        self.weights[i] = weight;
+        self.total = self.total.wrapping_add(delta);
+        let mut node = i + 1;
+        while node < self.sums.len() {
+            self.sums[node] = self.sums[node].wrapping_add(delta);
+            node += node & node.wrapping_neg();
+        }
+    }
+
+    /// The index whose weight covers `target`, counting from the first.
+    fn find(&self, mut target: u64) -> usize {
+        let mut node = 0;
+        let mut step = self.sums.len().next_power_of_two();
+        while step > 0 {
+            let next = node + step;
+            if next < self.sums.len() && self.sums[next] <= target {
+                node = next;
+                target -= self.sums[next];
+            }
+            step /= 2;
+        }
+        node
+    }
+}
@@ -0,0 +1,219 @@
+//! Extension points for variants and overlays that live outside the core.
+//!
+//! A `Plugin` registers any number of rule modifiers, HUD widgets and
+//! post-game analyzers into a `PluginRegistry`. The frontend owns the
+//! registry and consults it at the matching points of a game, so a plugin
+//! crate never needs to touch the board code itself.
+
+use std::fmt;
+
+use crate::board::{Board, CellState, CellType};
+
+/// Text a plugin shows the player. Frontends look `Localized` messages up
+/// in their translations and fill in the arguments; `Plain` text is shown
+/// as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Plain(String),
+    Localized {
+        id: &'static str,
+        args: Vec<(&'static str, String)>,
+    },
+}
+
+impl Message {
+    pub fn new(id: &'static str) -> Self {
+        Message::Localized {
+            id,
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds an argument to a localized message.
+    pub fn arg(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        if let Message::Localized { args, .. } = &mut self {
+            args.push((name, value.to_string()));
+        }
+        self
+    }
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Message::Plain(text)
+    }
+}
+
+/// Changes how a game plays out.
+pub trait RuleModifier {
+    fn name(&self) -> &str;
+
+    /// Called once on a freshly generated board.
+    fn on_generate(&self, _board: &mut Board) {}
+
+    /// Return false to ignore a click on `(row, col)`.
+    fn allow_reveal(&self, _board: &Board, _row: usize, _col: usize) -> bool {
+        true
+    }
+
+    /// Called after a click on `(row, col)` revealed something.
+    fn after_reveal(&self, _board: &mut Board, _row: usize, _col: usize) {}
+}
+
+/// Adds a line of text to the HUD.
+pub trait HudWidget {
+    fn name(&self) -> &str;
+
+    /// Text to show this frame, or `None` to hide the widget.
+    fn text(&self, board: &Board) -> Option<Message>;
+}
+
+/// Looks at a finished game and reports anything of interest.
+pub trait PostGameAnalyzer {
+    fn name(&self) -> &str;
+
+    fn analyze(&self, board: &Board, won: bool) -> Vec<Message>;
+}
+
+pub trait Plugin {
+    fn name(&self) -> &str;
+
+    fn register(&self, registry: &mut PluginRegistry);
+}
+
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: Vec<String>,
+    rules: Vec<Box<dyn RuleModifier>>,
+    widgets: Vec<Box<dyn HudWidget>>,
+    analyzers: Vec<Box<dyn PostGameAnalyzer>>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn install(&mut self, plugin: &dyn Plugin) {
+        self.plugins.push(plugin.name().to_owned());
+        plugin.register(self);
+    }
+
+    pub fn add_rule(&mut self, rule: Box<dyn RuleModifier>) {
+        self.rules.push(rule);
+    }
+
+    pub fn add_widget(&mut self, widget: Box<dyn HudWidget>) {
+        self.widgets.push(widget);
+    }
+
+    pub fn add_analyzer(&mut self, analyzer: Box<dyn PostGameAnalyzer>) {
+        self.analyzers.push(analyzer);
+    }
+
+    pub fn plugins(&self) -> &[String] {
+        &self.plugins
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn RuleModifier> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    pub fn widgets(&self) -> impl Iterator<Item = &dyn HudWidget> {
+        self.widgets.iter().map(|widget| widget.as_ref())
+    }
+
+    pub fn analyzers(&self) -> impl Iterator<Item = &dyn PostGameAnalyzer> {
+        self.analyzers.iter().map(|analyzer| analyzer.as_ref())
+    }
+
+    pub fn on_generate(&self, board: &mut Board) {
+        for rule in &self.rules {
+            rule.on_generate(board);
+        }
+    }
+
+    pub fn allow_reveal(&self, board: &Board, row: usize, col: usize) -> bool {
+        self.rules
+            .iter()
+            .all(|rule| rule.allow_reveal(board, row, col))
+    }
+
+    pub fn after_reveal(&self, board: &mut Board, row: usize, col: usize) {
+        for rule in &self.rules {
+            rule.after_reveal(board, row, col);
+        }
+    }
+
+    pub fn analyze(&self, board: &Board, won: bool) -> Vec<Message> {
+        self.analyzers
+            .iter()
+            .flat_map(|analyzer| analyzer.analyze(board, won))
+            .collect()
+    }
+}
+
+/// The plugin the game ships with: a progress widget and a game summary.
+pub struct Builtin;
+
+impl Plugin for Builtin {
+    fn name(&self) -> &str {
+        "builtin"
+    }
+
+    fn register(&self, registry: &mut PluginRegistry) {
+        registry.add_widget(Box::new(Progress));
+        registry.add_analyzer(Box::new(Summary));
+    }
+}
+
+fn safe_cells(board: &Board) -> (usize, usize) {
+    let mut revealed = 0;
+    let mut total = 0;
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let cell = board.cell(row, col);
+            if !matches!(cell.cell_type, CellType::Mine) {
+                total += 1;
+                revealed += usize::from(cell.cell_state == CellState::Visible);
+            }
+        }
+    }
+    (revealed, total)
+}
+
+struct Progress;
+
+impl HudWidget for Progress {
+    fn name(&self) -> &str {
+        "progress"
+    }
+
+    fn text(&self, board: &Board) -> Option<Message> {
+        // Runs every frame, so use the board's running count rather than a
+        // scan; no mine is visible while a classic game is in progress
+        let total = board.width() * board.height() - board.mines();
+        Some(
+            Message::new("progress")
+                .arg("cleared", board.revealed().min(total))
+                .arg("total", total),
+        )
+    }
+}
+
+struct Summary;
+
+impl PostGameAnalyzer for Summary {
+    fn name(&self) -> &str {
+        "summary"
+    }
+
+    fn analyze(&self, board: &Board, won: bool) -> Vec<Message> {
+        if won {
+            return vec![Message::new("board-cleared")];
+        }
+        let (revealed, total) = safe_cells(board);
+        let percent = revealed as f64 * 100.0 / total.max(1) as f64;
+        vec![Message::new("hit-mine").arg("percent", format!("{:.0}", percent))]
+    }
+}
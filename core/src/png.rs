@@ -0,0 +1,291 @@
+//! A small PNG writer for 8-bit RGBA images with text chunks, and a
+//! reader for those text chunks.
+//!
+//! Pixels are deflated with the fixed Huffman codes and greedy matches
+//! against the last place each three bytes were seen, which suits flat
+//! coloured screenshots well enough without a compression library.
+
+use std::fmt;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+/// How far back deflate matches may reach.
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+/// Lengths 3 to 258 and distances 1 to 32768 are sent as a symbol for a
+/// range and extra bits for the place in it.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PngError {
+    NotAPng,
+    /// The file ends inside a chunk or before `IEND`.
+    Truncated,
+    /// A chunk's checksum doesn't match what it holds.
+    Checksum(String),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PngError::NotAPng => write!(f, "not a PNG file"),
+            PngError::Truncated => write!(f, "the PNG file is cut short"),
+            PngError::Checksum(chunk) => write!(f, "the PNG {} chunk is damaged", chunk),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+/// Encodes `rgba`, four bytes per pixel row by row from the top, with a
+/// `tEXt` chunk for each key and value. Text is stored as Latin-1, so
+/// other characters become `?`.
+pub fn encode(width: u32, height: u32, rgba: &[u8], text: &[(&str, &str)]) -> Vec<u8> {
+    assert!(width > 0 && height > 0, "a PNG has at least one pixel");
+    assert_eq!(
+        rgba.len(),
+        width as usize * height as usize * 4,
+        "the pixels don't fill the image"
+    );
+    let mut bytes = SIGNATURE.to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace
+    header.extend([8, 6, 0, 0, 0]);
+    chunk(&mut bytes, b"IHDR", &header);
+    for (key, value) in text {
+        assert!(
+            (1..80).contains(&key.len()),
+            "PNG text keys are 1 to 79 bytes"
+        );
+        let mut data: Vec<u8> = latin1(key).collect();
+        data.push(0);
+        data.extend(latin1(value));
+        chunk(&mut bytes, b"tEXt", &data);
+    }
+    let stride = width as usize * 4;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgba.chunks(stride) {
+        // Filter type 0: the row as it is
+        raw.push(0);
+        raw.extend(row);
+    }
+    chunk(&mut bytes, b"IDAT", &zlib(&raw));
+    chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+/// Every `tEXt` key and value in the file, in order.
+pub fn text(bytes: &[u8]) -> Result<Vec<(String, String)>, PngError> {
+    let mut rest = bytes.strip_prefix(&SIGNATURE).ok_or(PngError::NotAPng)?;
+    let mut text = Vec::new();
+    loop {
+        if rest.len() < 12 {
+            return Err(PngError::Truncated);
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if rest.len() - 12 < length {
+            return Err(PngError::Truncated);
+        }
+        let (body, crc) = rest[4..12 + length].split_at(4 + length);
+        let (kind, data) = body.split_at(4);
+        if crc32(body).to_be_bytes() != crc {
+            return Err(PngError::Checksum(
+                String::from_utf8_lossy(kind).into_owned(),
+            ));
+        }
+        match kind {
+            b"IEND" => return Ok(text),
+            b"tEXt" => {
+                if let Some(end) = data.iter().position(|&byte| byte == 0) {
+                    let decode =
+                        |bytes: &[u8]| bytes.iter().map(|&byte| char::from(byte)).collect();
+                    text.push((decode(&data[..end]), decode(&data[end + 1..])));
+                }
+            }
+            _ => {}
+        }
+        rest = &rest[12 + length..];
+    }
+}
+
+fn latin1(text: &str) -> impl Iterator<Item = u8> + '_ {
+    text.chars().map(|c| u8::try_from(c).unwrap_or(b'?'))
+}
+
+fn chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    let start = bytes.len();
+    bytes.extend(kind);
+    bytes.extend(data);
+    let crc = crc32(&bytes[start..]);
+    bytes.extend(crc.to_be_bytes());
+}
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` could overflow
+    for block in bytes.chunks(5552) {
+        for &byte in block {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// `data` as a zlib stream: one final deflate block with the fixed codes.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter {
+        bytes: vec![0x78, 0x01],
+        ..BitWriter::default()
+    };
+    // Final block, fixed Huffman codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+    let hash = |i: usize| {
+        let key = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], 0]);
+        (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+    };
+    // One past the last position each hash was seen at, 0 for never
+    let mut last = vec![0usize; 1 << HASH_BITS];
+    let mut i = 0;
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            if let Some(from) = last[h].checked_sub(1).filter(|from| i - from <= WINDOW) {
+                let limit = MAX_MATCH.min(data.len() - i);
+                length = (0..limit)
+                    .take_while(|&k| data[from + k] == data[i + k])
+                    .count();
+                distance = i - from;
+            }
+            last[h] = i + 1;
+        }
+        if length < MIN_MATCH {
+            bits.literal(u16::from(data[i]));
+            i += 1;
+            continue;
+        }
+        bits.copy(length, distance);
+        for k in i + 1..(i + length).min(data.len().saturating_sub(MIN_MATCH - 1)) {
+            last[hash(k)] = k + 1;
+        }
+        i += length;
+    }
+    bits.literal(256);
+    let mut bytes = bits.finish();
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+/// Packs bits into bytes from the least significant end, as deflate
+/// does.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Writes the low `count` bits of `value`, least significant first.
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.bits;
+        self.bits += count;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which deflate sends most significant bit
+    /// first.
+    fn code(&mut self, code: u32, count: u32) {
+        let reversed = code.reverse_bits() >> (32 - count);
+        self.write(reversed, count);
+    }
+
+    /// A literal byte or length symbol from the fixed code.
+    fn literal(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn copy(&mut self, length: usize, distance: usize) {
+        let i = LENGTH_BASES.partition_point(|&base| usize::from(base) <= length) - 1;
+        self.literal(257 + i as u16);
+        self.write(
+            (length - usize::from(LENGTH_BASES[i])) as u32,
+            u32::from(LENGTH_EXTRA[i]),
+        );
+        let i = DISTANCE_BASES.partition_point(|&base| usize::from(base) <= distance) - 1;
+        self.code(i as u32, 5);
+        self.write(
+            (distance - usize::from(DISTANCE_BASES[i])) as u32,
+            u32::from(DISTANCE_EXTRA[i]),
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
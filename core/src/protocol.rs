@@ -0,0 +1,498 @@
+//! Messages exchanged between clients and the server.
+//!
+//! Every message is a single line of space-separated fields, tagged by its
+//! first word. A connection starts with the client sending `Hello` with the
+//! range of versions it understands; the server answers with the version both
+//! sides will speak, or `Incompatible` if there is none. Unknown tags decode to
+//! `None` so newer peers can add messages without breaking older ones.
+
+use crate::board::{Board, Cell, CellState, CellType};
+use crate::flag_capture::FlagCapture;
+
+/// Newest protocol version this build speaks.
+pub const VERSION: u32 = 1;
+/// Oldest protocol version this build still accepts.
+pub const MIN_VERSION: u32 = 1;
+
+/// Picks the newest version both this build and a peer supporting
+/// `min..=max` understand.
+pub fn negotiate(min: u32, max: u32) -> Option<u32> {
+    let version = max.min(VERSION);
+    (version >= min && version >= MIN_VERSION).then_some(version)
+}
+
+/// What a player is allowed to know about a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellView {
+    Hidden,
+    Flagged,
+    Empty,
+    Number(usize),
+    Mine,
+    Captured(usize),
+    /// A revealed number the blind rule doesn't show yet.
+    Unread,
+}
+
+impl CellView {
+    pub fn of(cell: &Cell) -> Self {
+        match (&cell.cell_state, &cell.cell_type, cell.owner) {
+            (CellState::Hidden, _, _) => CellView::Hidden,
+            (CellState::Flagged, _, _) => CellView::Flagged,
+            (CellState::Visible, CellType::Empty, _) => CellView::Empty,
+            (CellState::Visible, CellType::Number(n), _) => CellView::Number(*n),
+            (CellState::Visible, CellType::Mine, Some(player)) => CellView::Captured(player),
+            (CellState::Visible, CellType::Mine, None) => CellView::Mine,
+        }
+    }
+
+    pub fn to_cell(self) -> Cell {
+        match self {
+            CellView::Hidden => Cell::new(CellType::Empty, CellState::Hidden),
+            CellView::Flagged => Cell::new(CellType::Empty, CellState::Flagged),
+            CellView::Empty | CellView::Unread => Cell::new(CellType::Empty, CellState::Visible),
+            CellView::Number(n) => Cell::new(CellType::Number(n), CellState::Visible),
+            CellView::Mine => Cell::new(CellType::Mine, CellState::Visible),
+            CellView::Captured(player) => {
+                let mut cell = Cell::new(CellType::Mine, CellState::Visible);
+                cell.owner = Some(player);
+                cell
+            }
+        }
+    }
+
+    fn encode(self) -> char {
+        match self {
+            CellView::Hidden => '#',
+            CellView::Flagged => 'F',
+            CellView::Empty => '.',
+            CellView::Number(n @ 0..=9) => char::from_digit(n as u32, 10).unwrap_or('9'),
+            // Past 9 numbers carry on in letters after the two capture ones,
+            // which reach 33, more than any rules can count
+            CellView::Number(n) => (b'c' + (n - 10).min(23) as u8) as char,
+            CellView::Mine => '*',
+            CellView::Captured(0) => 'a',
+            CellView::Captured(_) => 'b',
+            CellView::Unread => '?',
+        }
+    }
+
+    fn decode(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(CellView::Hidden),
+            'F' => Some(CellView::Flagged),
+            '.' => Some(CellView::Empty),
+            '*' => Some(CellView::Mine),
+            'a' => Some(CellView::Captured(0)),
+            'b' => Some(CellView::Captured(1)),
+            '?' => Some(CellView::Unread),
+            'c'..='z' => Some(CellView::Number(c as usize - 'c' as usize + 10)),
+            c => Some(CellView::Number(c.to_digit(10)? as usize)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellChange {
+    pub row: usize,
+    pub col: usize,
+    pub view: CellView,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomInfo {
+    pub name: String,
+    pub players: usize,
+    pub spectators: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientMessage {
+    Hello {
+        min_version: u32,
+        max_version: u32,
+    },
+    ListRooms,
+    Join {
+        room: String,
+    },
+    Spectate {
+        room: String,
+    },
+    Leave,
+    Reveal {
+        row: usize,
+        col: usize,
+    },
+    /// Pointer position in board cells, e.g. `(2.5, 0.5)` is the middle of
+    /// the third cell of the first row.
+    Cursor {
+        x: f32,
+        y: f32,
+    },
+    Chat {
+        text: String,
+    },
+    Ping {
+        nonce: u64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerMessage {
+    Hello {
+        version: u32,
+    },
+    Incompatible {
+        min_version: u32,
+        max_version: u32,
+    },
+    Rooms(Vec<RoomInfo>),
+    Welcome {
+        player: usize,
+    },
+    Spectating,
+    /// Full snapshot, sent when joining a room.
+    State {
+        width: usize,
+        height: usize,
+        mines: usize,
+        scores: [usize; 2],
+        turn: usize,
+        cells: Vec<CellView>,
+    },
+    /// The cells that changed since the previous `State` or `Delta`.
+    Delta {
+        scores: [usize; 2],
+        turn: usize,
+        changes: Vec<CellChange>,
+    },
+    Cursor {
+        player: usize,
+        x: f32,
+        y: f32,
+    },
+    Chat {
+        from: String,
+        text: String,
+    },
+    Pong {
+        nonce: u64,
+    },
+    Error(String),
+}
+
+impl ClientMessage {
+    pub fn hello() -> Self {
+        ClientMessage::Hello {
+            min_version: MIN_VERSION,
+            max_version: VERSION,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        match self {
+            ClientMessage::Hello {
+                min_version,
+                max_version,
+            } => format!("HELLO {} {}", min_version, max_version),
+            ClientMessage::ListRooms => "ROOMS".to_owned(),
+            ClientMessage::Join { room } => format!("JOIN {}", room),
+            ClientMessage::Spectate { room } => format!("SPECTATE {}", room),
+            ClientMessage::Leave => "LEAVE".to_owned(),
+            ClientMessage::Reveal { row, col } => format!("REVEAL {} {}", row, col),
+            ClientMessage::Cursor { x, y } => format!("CURSOR {} {}", x, y),
+            ClientMessage::Chat { text } => format!("CHAT {}", single_line(text)),
+            ClientMessage::Ping { nonce } => format!("PING {}", nonce),
+        }
+    }
+
+    pub fn decode(line: &str) -> Option<Self> {
+        if let Some(text) = line.strip_prefix("CHAT ") {
+            return Some(ClientMessage::Chat {
+                text: text.to_owned(),
+            });
+        }
+
+        let mut parts = line.split_whitespace();
+        match parts.next()? {
+            "HELLO" => Some(ClientMessage::Hello {
+                min_version: parts.next()?.parse().ok()?,
+                max_version: parts.next()?.parse().ok()?,
+            }),
+            "ROOMS" => Some(ClientMessage::ListRooms),
+            "JOIN" => Some(ClientMessage::Join {
+                room: parts.next()?.to_owned(),
+            }),
+            "SPECTATE" => Some(ClientMessage::Spectate {
+                room: parts.next()?.to_owned(),
+            }),
+            "LEAVE" => Some(ClientMessage::Leave),
+            "REVEAL" => Some(ClientMessage::Reveal {
+                row: parts.next()?.parse().ok()?,
+                col: parts.next()?.parse().ok()?,
+            }),
+            "CURSOR" => Some(ClientMessage::Cursor {
+                x: parts.next()?.parse().ok()?,
+                y: parts.next()?.parse().ok()?,
+            }),
+            "PING" => Some(ClientMessage::Ping {
+                nonce: parts.next()?.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl ServerMessage {
+    /// Full snapshot of what players are allowed to see, so clients never
+    /// learn where the hidden mines are.
+    pub fn state(board: &Board, game: &FlagCapture) -> Self {
+        ServerMessage::State {
+            width: board.width(),
+            height: board.height(),
+            mines: game.mines(),
+            scores: game.scores(),
+            turn: game.turn(),
+            cells: view(board),
+        }
+    }
+
+    /// The cells of `board` that differ from `before`, a previous `view`.
+    pub fn delta(before: &[CellView], board: &Board, game: &FlagCapture) -> Self {
+        let changes = view(board)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, view)| before.get(*i) != Some(view))
+            .map(|(i, view)| CellChange {
+                row: i / board.width(),
+                col: i % board.width(),
+                view,
+            })
+            .collect();
+
+        ServerMessage::Delta {
+            scores: game.scores(),
+            turn: game.turn(),
+            changes,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        match self {
+            ServerMessage::Hello { version } => format!("HELLO {}", version),
+            ServerMessage::Incompatible {
+                min_version,
+                max_version,
+            } => format!("INCOMPATIBLE {} {}", min_version, max_version),
+            ServerMessage::Rooms(rooms) => {
+                let mut line = "ROOMS".to_owned();
+                for room in rooms {
+                    line += &format!(" {}:{}:{}", room.name, room.players, room.spectators);
+                }
+                line
+            }
+            ServerMessage::Welcome { player } => format!("WELCOME {}", player),
+            ServerMessage::Spectating => "SPECTATING".to_owned(),
+            ServerMessage::State {
+                width,
+                height,
+                mines,
+                scores,
+                turn,
+                cells,
+            } => format!(
+                "STATE {} {} {} {} {} {} {}",
+                width,
+                height,
+                mines,
+                scores[0],
+                scores[1],
+                turn,
+                cells.iter().map(|view| view.encode()).collect::<String>()
+            ),
+            ServerMessage::Delta {
+                scores,
+                turn,
+                changes,
+            } => {
+                let mut line = format!("DELTA {} {} {}", scores[0], scores[1], turn);
+                for change in changes {
+                    line += &format!(" {}:{}:{}", change.row, change.col, change.view.encode());
+                }
+                line
+            }
+            ServerMessage::Cursor { player, x, y } => format!("CURSOR {} {} {}", player, x, y),
+            ServerMessage::Chat { from, text } => format!("CHAT {} {}", from, single_line(text)),
+            ServerMessage::Pong { nonce } => format!("PONG {}", nonce),
+            ServerMessage::Error(message) => format!("ERROR {}", message),
+        }
+    }
+
+    pub fn decode(line: &str) -> Option<Self> {
+        let (tag, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let mut parts = rest.split_whitespace();
+        match tag {
+            "HELLO" => Some(ServerMessage::Hello {
+                version: parts.next()?.parse().ok()?,
+            }),
+            "INCOMPATIBLE" => Some(ServerMessage::Incompatible {
+                min_version: parts.next()?.parse().ok()?,
+                max_version: parts.next()?.parse().ok()?,
+            }),
+            "ROOMS" => {
+                let rooms = parts
+                    .map(|room| {
+                        let mut fields = room.split(':');
+                        Some(RoomInfo {
+                            name: fields.next()?.to_owned(),
+                            players: fields.next()?.parse().ok()?,
+                            spectators: fields.next()?.parse().ok()?,
+                        })
+                    })
+                    .collect::<Option<_>>()?;
+                Some(ServerMessage::Rooms(rooms))
+            }
+            "WELCOME" => Some(ServerMessage::Welcome {
+                player: parts.next()?.parse().ok()?,
+            }),
+            "SPECTATING" => Some(ServerMessage::Spectating),
+            "STATE" => {
+                let mut number = || -> Option<usize> { parts.next()?.parse().ok() };
+                let width = number()?;
+                let height = number()?;
+                let mines = number()?;
+                let scores = [number()?, number()?];
+                let turn = number()?;
+                let cells = parts
+                    .next()?
+                    .chars()
+                    .map(CellView::decode)
+                    .collect::<Option<Vec<_>>>()?;
+                if width.checked_mul(height) != Some(cells.len())
+                    || !scores_fit(scores, mines)
+                    || turn >= 2
+                {
+                    return None;
+                }
+                Some(ServerMessage::State {
+                    width,
+                    height,
+                    mines,
+                    scores,
+                    turn,
+                    cells,
+                })
+            }
+            "DELTA" => {
+                let mut number = || -> Option<usize> { parts.next()?.parse().ok() };
+                let scores = [number()?, number()?];
+                let turn = number()?;
+                if turn >= 2 {
+                    return None;
+                }
+                let changes = parts
+                    .map(|change| {
+                        let mut fields = change.split(':');
+                        Some(CellChange {
+                            row: fields.next()?.parse().ok()?,
+                            col: fields.next()?.parse().ok()?,
+                            view: CellView::decode(fields.next()?.chars().next()?)?,
+                        })
+                    })
+                    .collect::<Option<_>>()?;
+                Some(ServerMessage::Delta {
+                    scores,
+                    turn,
+                    changes,
+                })
+            }
+            "CURSOR" => Some(ServerMessage::Cursor {
+                player: parts.next()?.parse().ok()?,
+                x: parts.next()?.parse().ok()?,
+                y: parts.next()?.parse().ok()?,
+            }),
+            "CHAT" => {
+                let (from, text) = rest.split_once(' ')?;
+                Some(ServerMessage::Chat {
+                    from: from.to_owned(),
+                    text: text.to_owned(),
+                })
+            }
+            "PONG" => Some(ServerMessage::Pong {
+                nonce: parts.next()?.parse().ok()?,
+            }),
+            "ERROR" => Some(ServerMessage::Error(rest.to_owned())),
+            _ => None,
+        }
+    }
+}
+
+/// Every cell of `board` as the players see it, row by row.
+pub fn view(board: &Board) -> Vec<CellView> {
+    let mut cells = Vec::with_capacity(board.width() * board.height());
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let cell = match CellView::of(&board.cell(row, col)) {
+                CellView::Number(_) if !board.shows_number(row, col) => CellView::Unread,
+                cell => cell,
+            };
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+/// Rebuilds the board and match a `State` message describes.
+pub fn restore(
+    width: usize,
+    height: usize,
+    mines: usize,
+    scores: [usize; 2],
+    turn: usize,
+    cells: &[CellView],
+) -> (Board, FlagCapture) {
+    let state = cells
+        .chunks(width)
+        .take(height)
+        .map(|row| row.iter().map(|view| view.to_cell()).collect())
+        .collect();
+
+    (
+        Board::from_cells(width, height, mines, state),
+        FlagCapture::with_state(mines, scores, turn),
+    )
+}
+
+/// Applies a `Delta` message on top of a board built by `restore`. A delta
+/// scoring more mines than the match has is ignored, returning false.
+pub fn apply_delta(
+    board: &mut Board,
+    game: &mut FlagCapture,
+    scores: [usize; 2],
+    turn: usize,
+    changes: &[CellChange],
+) -> bool {
+    if !scores_fit(scores, game.mines()) {
+        return false;
+    }
+    for change in changes {
+        if board.contains(change.row, change.col) {
+            board.set_cell(change.row, change.col, change.view.to_cell());
+        }
+    }
+    *game = FlagCapture::with_state(game.mines(), scores, turn);
+    true
+}
+
+/// Whether two players can have captured `scores` mines out of `mines`.
+fn scores_fit(scores: [usize; 2], mines: usize) -> bool {
+    scores[0]
+        .checked_add(scores[1])
+        .is_some_and(|sum| sum <= mines)
+}
+
+/// Messages are newline-delimited, so free text must stay on one line.
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
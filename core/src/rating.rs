@@ -0,0 +1,165 @@
+//! A difficulty estimate for a board, worked out before it is played.
+//!
+//! A standard solver plays the board from what a player would see. It
+//! reveals every cell single numbers prove safe, and falls back to the full
+//! solver only when those run out. When nothing is proven, it guesses the
+//! cell least likely to be a mine. A guess that finds a mine is set aside
+//! as if the player had survived it, so the board is always played to the
+//! end. Three measures come out of it, and the rating weighs them together:
+//! how likely the guesses were to go wrong, how often the logic needed
+//! more than single numbers, and how much clicking the board takes.
+//!
+//! `find` turns the rating around: it deals boards until one has the
+//! difficulty asked for.
+
+use crate::agent::View;
+use crate::board::{Board, BoardError, CellState, CellType};
+use crate::generate::Progress;
+use crate::metrics;
+use crate::protocol::CellView;
+use crate::solver::{Knowledge, Probabilities};
+
+/// Samples the guesses' chances are estimated from where numbers tangle
+/// too much to work them out, fewer than the overlay uses, as a rating
+/// only needs them roughly.
+const SAMPLES: usize = 100;
+
+/// Boards `find` deals before giving up. Every star rating is common
+/// enough at the usual sizes to turn up well within this.
+pub const MAX_ATTEMPTS: u64 = 200;
+
+/// How hard a board is to clear.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rating {
+    /// The chance of getting through every guess the solver had to make,
+    /// the first click included unless the board starts opened.
+    pub survival: f64,
+    pub guesses: usize,
+    /// The share of the solver's steps that needed more than single
+    /// numbers, from 0 to 1.
+    pub complexity: f64,
+    /// 3BV per cell.
+    pub bbbv_density: f64,
+}
+
+impl Rating {
+    /// Plays `board` from its current state, which it leaves alone. Flags
+    /// are the player's opinion and are ignored.
+    pub fn of(board: &Board) -> Self {
+        let cells = board.width() * board.height();
+        let bbbv = metrics::bbbv(board);
+        let mut board = board.clone();
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                if board.cell(row, col).cell_state == CellState::Flagged {
+                    board.toggle_flag(row, col);
+                }
+            }
+        }
+        // Mines the solver found by guessing
+        let mut aside = Vec::new();
+        let (mut survival, mut guesses) = (1.0, 0);
+        let (mut steps, mut hard) = (0, 0);
+        while board.revealed() + board.mines() < cells {
+            steps += 1;
+            let view = view_without(&board, &aside);
+            let mut safe = Knowledge::single_point(&view).safe;
+            if safe.is_empty() {
+                safe = Knowledge::of(&view).safe;
+                hard += usize::from(!safe.is_empty());
+            }
+            let mut safe: Vec<_> = safe
+                .into_iter()
+                .filter(|&(row, col)| view.get(row, col) == CellView::Hidden)
+                .collect();
+            if safe.is_empty() {
+                let Some(((row, col), mine)) = safest(&view) else {
+                    break;
+                };
+                survival *= 1.0 - mine;
+                guesses += 1;
+                if board.cell(row, col).cell_type == CellType::Mine {
+                    aside.push((row, col));
+                    continue;
+                }
+                safe.push((row, col));
+            }
+            for (row, col) in safe {
+                board.reveal(row, col);
+            }
+        }
+        Self {
+            survival,
+            guesses,
+            complexity: hard as f64 / steps.max(1) as f64,
+            bbbv_density: bbbv as f64 / cells.max(1) as f64,
+        }
+    }
+
+    /// The measures weighed together, from 0 for a board that clears
+    /// itself to 1. Guessing weighs most, as it is what loses games.
+    pub fn score(&self) -> f64 {
+        let luck = 1.0 - self.survival;
+        // Expert boards come to about a third of their cells as 3BV
+        let work = (self.bbbv_density * 2.5).min(1.0);
+        (0.5 * luck + 0.3 * (self.complexity * 2.0).min(1.0) + 0.2 * work).clamp(0.0, 1.0)
+    }
+
+    /// The score as one to five stars.
+    pub fn stars(&self) -> u8 {
+        ((self.score() * 5.0).ceil() as u8).clamp(1, 5)
+    }
+}
+
+/// Deals boards from consecutive seeds, starting at `seed`, until one
+/// rates `stars` stars, and returns it with the seed that dealt it so the
+/// game can be shared. `progress` counts the boards dealt so far, for
+/// showing on another thread, and cancelling it stops the search.
+pub fn find(
+    deal: impl Fn(u64) -> Result<Board, BoardError>,
+    stars: u8,
+    seed: u64,
+    progress: &Progress,
+) -> Result<(u64, Board), BoardError> {
+    for attempt in 0..MAX_ATTEMPTS {
+        if progress.is_cancelled() {
+            return Err(BoardError::Cancelled);
+        }
+        let seed = seed.wrapping_add(attempt);
+        let board = deal(seed)?;
+        progress.count();
+        if Rating::of(&board).stars() == stars {
+            return Ok((seed, board));
+        }
+    }
+    Err(BoardError::RatingNotFound {
+        stars,
+        attempts: MAX_ATTEMPTS,
+    })
+}
+
+/// What the player sees, with the mines in `aside` shown, which the
+/// solver counts as found.
+fn view_without(board: &Board, aside: &[(usize, usize)]) -> View {
+    let mut view = View::of(board);
+    for &(row, col) in aside {
+        view.cells[row * view.width + col] = CellView::Mine;
+    }
+    view
+}
+
+/// The hidden cell least likely to be a mine, first in reading order
+/// among equals, with its chance.
+fn safest(view: &View) -> Option<((usize, usize), f64)> {
+    let chances = Probabilities::with_budget(view, SAMPLES);
+    view.hidden()
+        .into_iter()
+        .filter_map(|(row, col)| Some(((row, col), chances.get(row, col)?.mine)))
+        .fold(
+            None,
+            |best: Option<((usize, usize), f64)>, (pos, mine)| match best {
+                Some((_, least)) if least <= mine => best,
+                _ => Some((pos, mine)),
+            },
+        )
+}
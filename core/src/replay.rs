@@ -0,0 +1,375 @@
+//! Recorded classic games: the board the game started on and every move,
+//! with when it was made.
+//!
+//! A finished replay also records how the game ended, the board it ended
+//! on and its time, as the game was played. `verify` plays the moves again
+//! and refuses a file whose record they don't reproduce, so an edited or
+//! mismatched replay can't pass for a real game.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::board::{Board, CellState};
+use crate::game::{Game, Status};
+use crate::save::{self, Document, Migration, SaveError, Saved};
+use crate::sim::Move;
+
+/// Version of the rules replays are played by. Raise it whenever a change
+/// to the engine would play a recorded move differently, so that replays
+/// recorded before are refused rather than misjudged.
+pub const RULES: u32 = 2;
+
+/// What a recorded move turned out to be, for drawing a game's timeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    /// A reveal that opened a single cell.
+    Click,
+    /// A reveal that opened an empty cell and everything around it.
+    Opening,
+    /// A flag placed or taken off.
+    Flag,
+    Chord,
+}
+
+/// How a game ended: its status, each cell's state row by row, and when
+/// the last move was made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Ending {
+    status: Status,
+    states: Vec<CellState>,
+    at: Duration,
+}
+
+impl Ending {
+    fn new(board: &Board, status: Status, at: Duration) -> Self {
+        let states = (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .map(|(row, col)| board.cell(row, col).cell_state)
+            .collect();
+        Self { status, states, at }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Replay {
+    start: Board,
+    /// Each move and its time since the first.
+    moves: Vec<(Duration, Move)>,
+    /// How the game really ended, once `finish` has been told.
+    ending: Option<Ending>,
+}
+
+impl Replay {
+    pub fn new(start: Board) -> Self {
+        Self {
+            start,
+            moves: Vec::new(),
+            ending: None,
+        }
+    }
+
+    pub fn push(&mut self, at: Duration, step: Move) {
+        self.moves.push((at, step));
+    }
+
+    /// Records how `game`, the one the moves were pushed from, ended, for
+    /// `verify` to hold the moves to.
+    pub fn finish(&mut self, game: &Game) {
+        let at = self.moves.last().map_or(Duration::ZERO, |&(at, _)| at);
+        self.ending = Some(Ending::new(game.board(), game.status(), at));
+    }
+
+    /// The board before the first move.
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn moves(&self) -> &[(Duration, Move)] {
+        &self.moves
+    }
+
+    /// The moves without their times.
+    pub fn steps(&self) -> Vec<Move> {
+        self.moves.iter().map(|&(_, step)| step).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The start, then the board after each move with when it was made.
+    /// Moves the game refused leave the board as it was; moves after the
+    /// game ended are dropped.
+    pub fn frames(&self) -> Vec<(Duration, Board)> {
+        let mut game = Game::new(self.start.clone());
+        let mut frames = vec![(Duration::ZERO, game.board().clone())];
+        for &(at, step) in &self.moves {
+            if game.is_over() {
+                break;
+            }
+            play(&mut game, step);
+            frames.push((at, game.board().clone()));
+        }
+        frames
+    }
+
+    /// How playing the moves again ends.
+    fn end(&self) -> Ending {
+        let mut game = Game::new(self.start.clone());
+        let mut last = Duration::ZERO;
+        for &(at, step) in &self.moves {
+            if game.is_over() {
+                break;
+            }
+            play(&mut game, step);
+            last = at;
+        }
+        Ending::new(game.board(), game.status(), last)
+    }
+
+    /// Each move with when it was made and what it did, dropping moves
+    /// after the game ended as `frames` does.
+    pub fn marks(&self) -> Vec<(Duration, Mark)> {
+        let mut game = Game::new(self.start.clone());
+        let mut marks = Vec::with_capacity(self.moves.len());
+        for &(at, step) in &self.moves {
+            if game.is_over() {
+                break;
+            }
+            let step = resolve(game.board(), step);
+            let revealed = game.board().revealed();
+            play(&mut game, step);
+            let mark = match step {
+                Move::Reveal(..) if game.board().revealed() > revealed + 1 => Mark::Opening,
+                Move::Reveal(..) => Mark::Click,
+                Move::Flag(..) => Mark::Flag,
+                Move::Chord(..) => Mark::Chord,
+            };
+            marks.push((at, mark));
+        }
+        marks
+    }
+}
+
+/// Why a replay failed verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The file could not be read as a replay.
+    Save(SaveError),
+    /// Recorded under other rules than the ones this build plays by.
+    Rules { recorded: u32, supported: u32 },
+    /// Playing the moves again does not give the recorded field.
+    Mismatch { field: &'static str },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Save(err) => err.fmt(f),
+            VerifyError::Rules {
+                recorded,
+                supported,
+            } => write!(
+                f,
+                "recorded under rules {}, this build plays by rules {}",
+                recorded, supported
+            ),
+            VerifyError::Mismatch { field } => {
+                let what = match *field {
+                    "end" => "end board",
+                    "millis" => "time",
+                    field => field,
+                };
+                write!(f, "the moves do not play out to the recorded {}", what)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<SaveError> for VerifyError {
+    fn from(err: SaveError) -> Self {
+        VerifyError::Save(err)
+    }
+}
+
+/// Reads a saved replay and plays it through again, returning it only if
+/// it ends the way the file says the game did.
+pub fn verify(bytes: &[u8]) -> Result<Replay, VerifyError> {
+    let mut document = Document::decode(bytes)?;
+    save::migrate::<Replay>(&mut document)?;
+    let rules = document.parse("rules")?;
+    if rules != RULES {
+        return Err(VerifyError::Rules {
+            recorded: rules,
+            supported: RULES,
+        });
+    }
+    let replay = Replay::load(&document)?;
+    let recorded = replay
+        .ending
+        .as_ref()
+        .ok_or_else(|| SaveError::Missing("result".to_owned()))?;
+    let replayed = replay.end();
+    if replayed.status != recorded.status {
+        return Err(VerifyError::Mismatch { field: "result" });
+    }
+    if replayed.states != recorded.states {
+        return Err(VerifyError::Mismatch { field: "end" });
+    }
+    if replayed.at != recorded.at {
+        return Err(VerifyError::Mismatch { field: "millis" });
+    }
+    Ok(replay)
+}
+
+/// The start board's own fields, then the rules, the moves as
+/// `MILLIS MOVE ROW COL` separated by `;` with the move as `r`, `f` or
+/// `c`, and for a finished game the record: `result` (`won`, `lost` or
+/// `playing`), the end board's cell states as in a board save, and the
+/// time of the last move in milliseconds.
+impl Saved for Replay {
+    const KIND: &'static str = "replay";
+    /// The start board's fields change with the board's own schema.
+    const MIGRATIONS: &'static [Migration] = &[save::add_ruleset, save::add_shown, save::add_kinds];
+
+    fn save(&self, document: &mut Document) {
+        self.start.save(document);
+        document.set("rules", RULES);
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|&(at, step)| {
+                let name = match step {
+                    Move::Reveal(..) => "r",
+                    Move::Flag(..) => "f",
+                    Move::Chord(..) => "c",
+                };
+                let (row, col) = step.position();
+                format!("{} {} {} {}", at.as_millis(), name, row, col)
+            })
+            .collect();
+        document.set("moves", moves.join(";"));
+
+        let Some(ending) = &self.ending else {
+            return;
+        };
+        let result = match ending.status {
+            Status::Won => "won",
+            Status::Lost => "lost",
+            Status::Playing => "playing",
+        };
+        document.set("result", result);
+        let states: String = ending
+            .states
+            .iter()
+            .map(|state| match state {
+                CellState::Hidden => 'h',
+                CellState::Flagged => 'f',
+                CellState::Visible => 'v',
+            })
+            .collect();
+        document.set("end", states);
+        document.set("millis", ending.at.as_millis());
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let mut replay = Replay::new(Board::load(document)?);
+        let moves = document.get("moves")?;
+        let invalid = || SaveError::Invalid {
+            field: "moves".to_owned(),
+            value: moves.to_owned(),
+        };
+        for step in moves.split(';').filter(|step| !step.trim().is_empty()) {
+            let words: Vec<&str> = step.split_whitespace().collect();
+            let [at, name, row, col] = words[..] else {
+                return Err(invalid());
+            };
+            let at = Duration::from_millis(at.parse().map_err(|_| invalid())?);
+            let (row, col) = (
+                row.parse().map_err(|_| invalid())?,
+                col.parse().map_err(|_| invalid())?,
+            );
+            if !replay.start.contains(row, col)
+                || replay.moves.last().is_some_and(|&(last, _)| last > at)
+            {
+                return Err(invalid());
+            }
+            replay.push(
+                at,
+                match name {
+                    "r" => Move::Reveal(row, col),
+                    "f" => Move::Flag(row, col),
+                    "c" => Move::Chord(row, col),
+                    _ => return Err(invalid()),
+                },
+            );
+        }
+        if let Ok(result) = document.get("result") {
+            replay.ending = Some(load_ending(
+                document,
+                result,
+                replay.start.width() * replay.start.height(),
+            )?);
+        }
+        Ok(replay)
+    }
+}
+
+/// The record of a finished game, whose `result` field is `result`.
+fn load_ending(document: &Document, result: &str, cells: usize) -> Result<Ending, SaveError> {
+    let invalid = |field: &str, value: &str| SaveError::Invalid {
+        field: field.to_owned(),
+        value: value.to_owned(),
+    };
+    let status = match result {
+        "won" => Status::Won,
+        "lost" => Status::Lost,
+        "playing" => Status::Playing,
+        _ => return Err(invalid("result", result)),
+    };
+    let end = document.get("end")?;
+    let states = end
+        .chars()
+        .map(|c| match c {
+            'h' => Ok(CellState::Hidden),
+            'f' => Ok(CellState::Flagged),
+            'v' => Ok(CellState::Visible),
+            _ => Err(invalid("end", end)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if states.len() != cells {
+        return Err(invalid("end", end));
+    }
+    let millis = document.parse("millis")?;
+    Ok(Ending {
+        status,
+        states,
+        at: Duration::from_millis(millis),
+    })
+}
+
+/// The move as the frontends play it: clicking a revealed number chords
+/// it.
+pub fn resolve(board: &Board, step: Move) -> Move {
+    match step {
+        Move::Reveal(row, col) if board.cell(row, col).cell_state == CellState::Visible => {
+            Move::Chord(row, col)
+        }
+        step => step,
+    }
+}
+
+/// Plays a recorded move. Moves the game refuses change nothing.
+pub fn play(game: &mut Game, step: Move) {
+    let _ = match resolve(game.board(), step) {
+        Move::Reveal(row, col) => game.reveal(row, col),
+        Move::Flag(row, col) => game.toggle_flag(row, col),
+        Move::Chord(row, col) => game.chord(row, col),
+    };
+}
@@ -0,0 +1,177 @@
+//! Post-game review: replays a finished classic game one move at a time
+//! and judges each move from what the player could see when making it,
+//! the way a chess engine annotates a game.
+//!
+//! A move is judged against what the numbers prove, as found by
+//! `solver::Knowledge`. The risk of a guess is its chance of hitting a
+//! mine from `solver::Probabilities`, exact unless the numbers around it
+//! form too large a tangle.
+
+use crate::agent::View;
+use crate::board::{Board, CellState};
+use crate::game::Game;
+use crate::plugin::Message;
+use crate::protocol::CellView;
+use crate::replay;
+use crate::sim::Move;
+use crate::solver::{Knowledge, Probabilities};
+
+/// How good a move was.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    /// The first click, before any number was shown.
+    Opening,
+    /// Every cell it opened was proven safe.
+    Forced,
+    /// A guess at `risk` while some other cell was proven safe.
+    Unforced {
+        risk: f64,
+    },
+    /// A guess at `risk` with nothing proven safe anywhere.
+    Guess {
+        risk: f64,
+    },
+    /// It opened a cell that was proven to be a mine.
+    Blunder,
+    /// A flag, on a proven mine or not.
+    Flag {
+        proven: bool,
+    },
+    Unflag,
+    /// The board didn't change.
+    Wasted,
+    /// A move under custom rules, which the solver doesn't model well
+    /// enough to judge.
+    Unjudged,
+}
+
+/// One move of the game and what the review makes of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    pub step: Move,
+    pub verdict: Verdict,
+}
+
+impl Note {
+    /// The verdict in words, for the frontends to translate.
+    pub fn message(&self) -> Message {
+        let percent = |risk: f64| format!("{:.0}", risk * 100.0);
+        match self.verdict {
+            Verdict::Opening => Message::new("review-opening"),
+            Verdict::Forced => Message::new("review-forced"),
+            Verdict::Unforced { risk } => {
+                Message::new("review-unforced").arg("risk", percent(risk))
+            }
+            Verdict::Guess { risk } => Message::new("review-guess").arg("risk", percent(risk)),
+            Verdict::Blunder => Message::new("review-blunder"),
+            Verdict::Flag { proven: true } => Message::new("review-flag"),
+            Verdict::Flag { proven: false } => Message::new("review-flag-unproven"),
+            Verdict::Unflag => Message::new("review-unflag"),
+            Verdict::Wasted => Message::new("review-wasted"),
+            Verdict::Unjudged => Message::new("review-unjudged"),
+        }
+    }
+}
+
+/// A finished game, move by move.
+pub struct Review {
+    /// The board before each move, then the board at the end.
+    positions: Vec<Board>,
+    notes: Vec<Note>,
+}
+
+impl Review {
+    /// Replays `moves` from `start`, the board before the first of them.
+    /// Moves after the game ended are dropped.
+    pub fn new(start: Board, moves: &[Move]) -> Self {
+        let mut game = Game::new(start);
+        let mut positions = vec![game.board().clone()];
+        let mut notes = Vec::with_capacity(moves.len());
+        for &step in moves {
+            if game.is_over() {
+                break;
+            }
+            let board = game.board();
+            let (row, col) = step.position();
+            let step = replay::resolve(board, step);
+            let verdict = judge(board, step);
+            let before = board.revision();
+            replay::play(&mut game, step);
+            let verdict = if game.board().revision() == before {
+                Verdict::Wasted
+            } else if game.board().cell(row, col).cell_state == CellState::Hidden
+                && matches!(step, Move::Flag(..))
+            {
+                Verdict::Unflag
+            } else {
+                verdict
+            };
+            notes.push(Note { step, verdict });
+            positions.push(game.board().clone());
+        }
+        Self { positions, notes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// The board before move `i`, or at the end of the game for `len()`.
+    pub fn position(&self, i: usize) -> &Board {
+        &self.positions[i]
+    }
+}
+
+/// Judges `step` on `board` before it is played. A move that turns out to
+/// change nothing is caught afterwards.
+fn judge(board: &Board, step: Move) -> Verdict {
+    if !board.rules().is_classic() {
+        return Verdict::Unjudged;
+    }
+    let view = View::of(board);
+    let knowledge = Knowledge::of(&view);
+    let opened = match step {
+        Move::Flag(row, col) => {
+            return Verdict::Flag {
+                proven: knowledge.mines.contains(&(row, col)),
+            }
+        }
+        Move::Reveal(row, col) => vec![(row, col)],
+        Move::Chord(row, col) => view
+            .neighbors(row, col)
+            .into_iter()
+            .filter(|&(r, c)| view.get(r, c) == CellView::Hidden)
+            .collect(),
+    };
+    if board.revealed() == 0 {
+        return Verdict::Opening;
+    }
+    if opened.iter().any(|pos| knowledge.mines.contains(pos)) {
+        return Verdict::Blunder;
+    }
+    if opened.iter().all(|pos| knowledge.safe.contains(pos)) {
+        return Verdict::Forced;
+    }
+    // The cells' risks are treated as independent, which is close enough
+    // for the few cells one chord opens
+    let chances = Probabilities::of(&view);
+    let survive: f64 = opened
+        .iter()
+        .filter_map(|&(row, col)| chances.get(row, col))
+        .map(|chance| 1.0 - chance.mine)
+        .product();
+    let risk = 1.0 - survive;
+    if knowledge.safe.is_empty() {
+        Verdict::Guess { risk }
+    } else {
+        Verdict::Unforced { risk }
+    }
+}
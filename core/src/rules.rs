@@ -0,0 +1,300 @@
+//! How a board is wired and when a game on it is over.
+//!
+//! A `Ruleset` is a handful of independent choices the `Board` consults
+//! instead of assuming the classic game, so variants compose: a torus
+//! with knight's-move numbers and up to three mines a cell is just three
+//! fields set at once. `Ruleset::default()` is the classic game.
+//!
+//! A ruleset other than the classic one is named in share codes by the
+//! words for what it changes, such as `wrap+knight+lives3`, so a custom
+//! game can be dealt again from its code.
+
+use crate::board::Pos;
+
+/// Which cells a number counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Adjacency {
+    /// The eight cells around, as in the classic game.
+    #[default]
+    King,
+    /// Only the four cells sharing an edge.
+    Cross,
+    /// The eight cells a knight's move away.
+    Knight,
+}
+
+impl Adjacency {
+    pub const ALL: [Adjacency; 3] = [Adjacency::King, Adjacency::Cross, Adjacency::Knight];
+
+    /// Name in share codes and saves.
+    pub fn name(self) -> &'static str {
+        match self {
+            Adjacency::King => "king",
+            Adjacency::Cross => "cross",
+            Adjacency::Knight => "knight",
+        }
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|adjacency| adjacency.name() == name)
+    }
+
+    /// Row and column steps to every counted cell.
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Adjacency::King => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Adjacency::Cross => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Adjacency::Knight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+        }
+    }
+}
+
+/// When a game is won. Either way, setting off a mine loses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Win {
+    /// Every safe cell revealed.
+    #[default]
+    Reveal,
+    /// Every mine flagged and no safe cell flagged.
+    Flag,
+}
+
+/// What revealing an empty cell does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Reveal {
+    /// Opens everything connected to it up to the numbers around.
+    #[default]
+    Flood,
+    /// Only ever uncovers the cell clicked.
+    Single,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ruleset {
+    pub adjacency: Adjacency,
+    /// Whether the edges join up, making the board a torus.
+    pub wrap: bool,
+    /// The most mines one cell can hold, from 1 to `MAX_MINES_PER_CELL`.
+    /// Numbers count mines, not mined cells.
+    pub mines_per_cell: u8,
+    pub win: Win,
+    pub reveal: Reveal,
+    /// Mines that can go off before the game is lost, from 1 to
+    /// `MAX_LIVES`.
+    pub lives: u8,
+    /// Every number is off by one, too high or too low; an empty cell
+    /// always shows 1.
+    pub liar: bool,
+    /// How far from an uncovered cell, in rows and columns, the player can
+    /// see; 0 for no fog. Cells further out can't be revealed or flagged.
+    pub fog: u8,
+    /// Revealed numbers stay blank until a cell they count is flagged.
+    pub blind: bool,
+    /// Percent of the mines that are duds, which cost time rather than a
+    /// life; see `board::MineKind`.
+    pub duds: u8,
+    /// Percent of the mines that are big, setting off the 3x3 area around
+    /// them.
+    pub bigs: u8,
+}
+
+impl Ruleset {
+    pub const CLASSIC: Ruleset = Ruleset {
+        adjacency: Adjacency::King,
+        wrap: false,
+        mines_per_cell: 1,
+        win: Win::Reveal,
+        reveal: Reveal::Flood,
+        lives: 1,
+        liar: false,
+        fog: 0,
+        blind: false,
+        duds: 0,
+        bigs: 0,
+    };
+
+    pub const MAX_MINES_PER_CELL: u8 = 3;
+    pub const MAX_LIVES: u8 = 9;
+    pub const MAX_FOG: u8 = 5;
+
+    pub fn is_classic(&self) -> bool {
+        *self == Self::CLASSIC
+    }
+
+    /// The words naming what these rules change from the classic game, in
+    /// a fixed order; none for the classic game.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if self.adjacency != Adjacency::King {
+            names.push(self.adjacency.name().to_owned());
+        }
+        if self.wrap {
+            names.push("wrap".to_owned());
+        }
+        if self.mines_per_cell > 1 {
+            names.push(format!("mines{}", self.mines_per_cell));
+        }
+        if self.win == Win::Flag {
+            names.push("flag-win".to_owned());
+        }
+        if self.reveal == Reveal::Single {
+            names.push("no-flood".to_owned());
+        }
+        if self.lives > 1 {
+            names.push(format!("lives{}", self.lives));
+        }
+        if self.liar {
+            names.push("liar".to_owned());
+        }
+        if self.fog > 0 {
+            names.push(format!("fog{}", self.fog));
+        }
+        if self.blind {
+            names.push("blind".to_owned());
+        }
+        if self.duds > 0 {
+            names.push(format!("duds{}", self.duds));
+        }
+        if self.bigs > 0 {
+            names.push(format!("bigs{}", self.bigs));
+        }
+        names
+    }
+
+    /// These rules changed as the word `name` from `names` says, or `None`
+    /// if it names no rule.
+    pub fn with_name(self, name: &str) -> Option<Self> {
+        let count = |prefix: &str, least: u8, most: u8| {
+            let count: u8 = name.strip_prefix(prefix)?.parse().ok()?;
+            (least..=most).contains(&count).then_some(count)
+        };
+        Some(match name {
+            "wrap" => Self { wrap: true, ..self },
+            "flag-win" => Self {
+                win: Win::Flag,
+                ..self
+            },
+            "no-flood" => Self {
+                reveal: Reveal::Single,
+                ..self
+            },
+            "liar" => Self { liar: true, ..self },
+            "blind" => Self {
+                blind: true,
+                ..self
+            },
+            _ => {
+                let mut rules = self;
+                if let Some(adjacency) = Adjacency::named(name) {
+                    rules.adjacency = adjacency;
+                } else if let Some(most) = count("mines", 2, Self::MAX_MINES_PER_CELL) {
+                    rules.mines_per_cell = most;
+                } else if let Some(lives) = count("lives", 2, Self::MAX_LIVES) {
+                    rules.lives = lives;
+                } else if let Some(fog) = count("fog", 1, Self::MAX_FOG) {
+                    rules.fog = fog;
+                } else if let Some(duds) = count("duds", 1, 100) {
+                    rules.duds = duds;
+                } else {
+                    rules.bigs = count("bigs", 1, 100)?;
+                }
+                rules
+            }
+        })
+    }
+
+    /// The distinct cells `steps` away from `pos` on a `width` by `height`
+    /// board, wrapping around the edges if the rules say so, without
+    /// allocating. `pos` itself never counts, even on a torus small enough
+    /// to step back onto it.
+    pub fn reach(
+        &self,
+        width: usize,
+        height: usize,
+        pos: Pos,
+        steps: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> {
+        let rules = *self;
+        steps.iter().enumerate().filter_map(move |(i, &step)| {
+            let near = rules.step(width, height, pos, step)?;
+            // Only a torus can step back onto a cell, or onto `pos`
+            let repeat = rules.wrap
+                && (near == pos
+                    || steps[..i]
+                        .iter()
+                        .any(|&earlier| rules.step(width, height, pos, earlier) == Some(near)));
+            (!repeat).then_some(near)
+        })
+    }
+
+    fn step(&self, width: usize, height: usize, pos: Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        if self.wrap {
+            return Some(Pos::new(
+                wrapped(pos.row, dr, height),
+                wrapped(pos.col, dc, width),
+            ));
+        }
+        let row = pos.row.checked_add_signed(dr).filter(|&row| row < height)?;
+        let col = pos.col.checked_add_signed(dc).filter(|&col| col < width)?;
+        Some(Pos::new(row, col))
+    }
+
+    /// Whether a cell `seen` accepts is within `fog` rows and columns of
+    /// `pos`, across the edges on a torus. Always true without fog.
+    pub fn in_sight(
+        &self,
+        width: usize,
+        height: usize,
+        pos: Pos,
+        seen: impl Fn(Pos) -> bool,
+    ) -> bool {
+        let reach = self.fog as isize;
+        self.fog == 0
+            || (-reach..=reach).any(|dr| {
+                (-reach..=reach)
+                    .any(|dc| self.step(width, height, pos, (dr, dc)).is_some_and(&seen))
+            })
+    }
+
+    /// The cells a number at `pos` counts.
+    pub fn around(&self, width: usize, height: usize, pos: Pos) -> impl Iterator<Item = Pos> {
+        // Gathered up front, as numbers are read far more often than not
+        let mut found = [pos; 8];
+        let mut count = 0;
+        for near in self.reach(width, height, pos, self.adjacency.offsets()) {
+            found[count] = near;
+            count += 1;
+        }
+        found.into_iter().take(count)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+fn wrapped(at: usize, step: isize, size: usize) -> usize {
+    (at as isize + step).rem_euclid(size as isize) as usize
+}
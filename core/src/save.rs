@@ -0,0 +1,510 @@
+//! Versioned save files.
+//!
+//! A save names what it holds and the version of its schema, followed by
+//! its fields. It can be written in three formats, and `load` tells them
+//! apart by their first bytes:
+//!
+//! - `Format::Text`, one `key value` field per line after a header:
+//!
+//!   ```text
+//!   minesweeper board 1
+//!   width 3
+//!   height 2
+//!   ```
+//!
+//! - `Format::Json`, for sharing and debugging:
+//!   `{"minesweeper": "board", "version": 1, "fields": {"width": 3, ...}}`
+//! - `Format::Binary`, length-prefixed and compact, for autosaves.
+//!
+//! A type that can be saved lists its migrations, oldest first; migration
+//! `i` upgrades a version `i + 1` document to version `i + 2`, so the
+//! current version is always one more than the number of migrations.
+//! Loading an older file runs the missing migrations before the fields are
+//! read, and files written by a newer release are refused rather than
+//! misread.
+
+mod binary;
+pub(crate) mod json;
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, Cell, CellState, CellType, MineKind};
+use crate::rules::Ruleset;
+
+/// First word of every save file.
+const MAGIC: &str = "minesweeper";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaveError {
+    /// The header is missing or malformed.
+    NotASave,
+    /// The header is fine but the rest of the file is not.
+    Corrupt(String),
+    WrongKind {
+        expected: String,
+        found: String,
+    },
+    /// Written by a newer release than this one.
+    TooNew {
+        version: u32,
+        supported: u32,
+    },
+    Missing(String),
+    Invalid {
+        field: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::NotASave => write!(f, "not a minesweeper save file"),
+            SaveError::Corrupt(reason) => write!(f, "corrupt save file: {}", reason),
+            SaveError::WrongKind { expected, found } => {
+                write!(f, "expected a {} save, found a {} save", expected, found)
+            }
+            SaveError::TooNew { version, supported } => write!(
+                f,
+                "saved by a newer version (format {}, this build reads up to {})",
+                version, supported
+            ),
+            SaveError::Missing(field) => write!(f, "missing field `{}`", field),
+            SaveError::Invalid { field, value } => {
+                write!(f, "field `{}` has invalid value `{}`", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Binary,
+}
+
+impl Format {
+    /// Guesses the format a save was written in from its first bytes.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(binary::MAGIC) {
+            Format::Binary
+        } else if bytes.trim_ascii_start().starts_with(b"{") {
+            Format::Json
+        } else {
+            Format::Text
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "binary" => Ok(Format::Binary),
+            other => Err(format!("unknown save format `{}`", other)),
+        }
+    }
+}
+
+/// A save file's contents before they are turned back into a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    kind: String,
+    version: u32,
+    fields: Vec<(String, String)>,
+}
+
+impl Document {
+    pub fn new(kind: &str, version: u32) -> Self {
+        Self {
+            kind: kind.to_owned(),
+            version,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Sets a field, replacing any earlier value. Line breaks in the value
+    /// are replaced by spaces.
+    pub fn set(&mut self, key: &str, value: impl fmt::Display) {
+        let value = value.to_string().replace(['\r', '\n'], " ");
+        match self.fields.iter_mut().find(|(k, _)| k == key) {
+            Some((_, old)) => *old = value,
+            None => self.fields.push((key.to_owned(), value)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<&str, SaveError> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| SaveError::Missing(key.to_owned()))
+    }
+
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T, SaveError> {
+        let value = self.get(key)?;
+        value.parse().map_err(|_| SaveError::Invalid {
+            field: key.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+
+    /// Every field in the order it was set.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let i = self.fields.iter().position(|(k, _)| k == key)?;
+        Some(self.fields.remove(i).1)
+    }
+
+    /// Renames a field, for migrations. Does nothing if it is missing.
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if let Some(value) = self.remove(from) {
+            self.set(to, value);
+        }
+    }
+
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Text => self.to_text().into_bytes(),
+            Format::Json => json::encode(self).into_bytes(),
+            Format::Binary => binary::encode(self),
+        }
+    }
+
+    /// Reads a document as it was written, in whichever format, without
+    /// migrating it.
+    pub fn decode(bytes: &[u8]) -> Result<Self, SaveError> {
+        match Format::detect(bytes) {
+            Format::Binary => binary::decode(bytes),
+            format => {
+                let text = std::str::from_utf8(bytes).map_err(|_| SaveError::NotASave)?;
+                match format {
+                    Format::Json => json::decode(text),
+                    _ => Self::from_text(text),
+                }
+            }
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("{} {} {}\n", MAGIC, self.kind, self.version);
+        for (key, value) in &self.fields {
+            text.push_str(key);
+            text.push(' ');
+            text.push_str(value);
+            text.push('\n');
+        }
+        text
+    }
+
+    fn from_text(text: &str) -> Result<Self, SaveError> {
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        let [MAGIC, kind, version] = header[..] else {
+            return Err(SaveError::NotASave);
+        };
+        let version = version.parse().map_err(|_| SaveError::NotASave)?;
+
+        let mut document = Self::new(kind, version);
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            document.set(key, value);
+        }
+        Ok(document)
+    }
+}
+
+/// Upgrades a document by one version.
+pub type Migration = fn(&mut Document) -> Result<(), SaveError>;
+
+pub trait Saved: Sized {
+    /// Second word of the header, telling save files apart.
+    const KIND: &'static str;
+    /// Every schema change so far, oldest first.
+    const MIGRATIONS: &'static [Migration] = &[];
+
+    fn save(&self, document: &mut Document);
+    fn load(document: &Document) -> Result<Self, SaveError>;
+}
+
+/// The version `T` is currently saved with.
+pub fn version<T: Saved>() -> u32 {
+    T::MIGRATIONS.len() as u32 + 1
+}
+
+pub fn save<T: Saved>(value: &T, format: Format) -> Vec<u8> {
+    let mut document = Document::new(T::KIND, version::<T>());
+    value.save(&mut document);
+    document.encode(format)
+}
+
+/// Loads a save written in any format by this or an earlier release.
+pub fn load<T: Saved>(bytes: &[u8]) -> Result<T, SaveError> {
+    let mut document = Document::decode(bytes)?;
+    migrate::<T>(&mut document)?;
+    T::load(&document)
+}
+
+/// Brings a document written by any earlier release up to the current
+/// version.
+pub fn migrate<T: Saved>(document: &mut Document) -> Result<(), SaveError> {
+    if document.kind != T::KIND {
+        return Err(SaveError::WrongKind {
+            expected: T::KIND.to_owned(),
+            found: document.kind.clone(),
+        });
+    }
+    let current = version::<T>();
+    if document.version > current {
+        return Err(SaveError::TooNew {
+            version: document.version,
+            supported: current,
+        });
+    }
+    if document.version == 0 {
+        return Err(SaveError::NotASave);
+    }
+    for migration in &T::MIGRATIONS[document.version as usize - 1..] {
+        migration(document)?;
+        document.version += 1;
+    }
+    Ok(())
+}
+
+/// Version 1 boards were all played by the classic rules, which a board
+/// without a `ruleset` still is, and no cell held more than one mine.
+/// Nothing needs filling in; the version only keeps older builds from
+/// loading a custom board as a classic one.
+pub(crate) fn add_ruleset(_: &mut Document) -> Result<(), SaveError> {
+    Ok(())
+}
+
+/// Version 2 boards predate blind numbers, so none hides its numbers and
+/// there is no `shown` field to fill in; the version keeps older builds
+/// from loading a blind board with every number on show.
+pub(crate) fn add_shown(_: &mut Document) -> Result<(), SaveError> {
+    Ok(())
+}
+
+/// Version 3 boards predate duds and big mines, so every mine is a plain
+/// one and there is no `kinds` field to fill in; the version keeps older
+/// builds from loading duds and big mines as plain mines.
+pub(crate) fn add_kinds(_: &mut Document) -> Result<(), SaveError> {
+    Ok(())
+}
+
+/// Cells are saved as three strings with one character per cell, row by
+/// row: what the cell is (`.` empty, a number as a base 36 digit, `*`
+/// mine), whether it is hidden, flagged or shown (`h`, `f`, `v`), and
+/// which player captured it (`-` for nobody). A board with custom rules
+/// also saves their names, as in share codes, the mines in each cell if a
+/// cell can hold more than one, under blind numbers which revealed
+/// numbers are on show (`1`) or not (`0`), and with duds or big mines
+/// what each mine is (`d` dud, `b` big, `-` plain or no mine).
+impl Saved for Board {
+    const KIND: &'static str = "board";
+    const MIGRATIONS: &'static [Migration] = &[add_ruleset, add_shown, add_kinds];
+
+    fn save(&self, document: &mut Document) {
+        let cells = || {
+            (0..self.height())
+                .flat_map(move |row| (0..self.width()).map(move |col| self.cell(row, col)))
+        };
+        document.set("width", self.width());
+        document.set("height", self.height());
+        document.set("mines", self.mines());
+        document.set(
+            "cells",
+            cells()
+                .map(|cell| match cell.cell_type {
+                    CellType::Empty => '.',
+                    CellType::Number(n) => char::from_digit(n as u32, 36).unwrap_or('?'),
+                    CellType::Mine => '*',
+                })
+                .collect::<String>(),
+        );
+        document.set(
+            "states",
+            cells()
+                .map(|cell| match cell.cell_state {
+                    CellState::Hidden => 'h',
+                    CellState::Flagged => 'f',
+                    CellState::Visible => 'v',
+                })
+                .collect::<String>(),
+        );
+        document.set(
+            "owners",
+            cells()
+                .map(|cell| {
+                    cell.owner
+                        .and_then(|player| char::from_digit(player as u32, 10))
+                        .unwrap_or('-')
+                })
+                .collect::<String>(),
+        );
+        let rules = self.rules();
+        if !rules.is_classic() {
+            document.set("ruleset", rules.names().join("+"));
+        }
+        if rules.mines_per_cell > 1 {
+            document.set(
+                "loads",
+                (0..self.height())
+                    .flat_map(|row| (0..self.width()).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        char::from_digit(self.mines_at(row, col) as u32, 10).unwrap_or('?')
+                    })
+                    .collect::<String>(),
+            );
+        }
+        if rules.duds > 0 || rules.bigs > 0 {
+            document.set(
+                "kinds",
+                (0..self.height())
+                    .flat_map(|row| (0..self.width()).map(move |col| (row, col)))
+                    .map(|(row, col)| match self.mine_kind(row, col) {
+                        Some(MineKind::Dud) => 'd',
+                        Some(MineKind::Big) => 'b',
+                        _ => '-',
+                    })
+                    .collect::<String>(),
+            );
+        }
+        if rules.blind {
+            document.set(
+                "shown",
+                (0..self.height())
+                    .flat_map(|row| (0..self.width()).map(move |col| (row, col)))
+                    .map(|(row, col)| match self.shows_number(row, col) {
+                        true => '1',
+                        false => '0',
+                    })
+                    .collect::<String>(),
+            );
+        }
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let width: usize = document.parse("width")?;
+        let height: usize = document.parse("height")?;
+        let mines: usize = document.parse("mines")?;
+        let size = width.checked_mul(height).filter(|&size| size > 0);
+        let field = |key: &str| -> Result<Vec<char>, SaveError> {
+            let value = document.get(key)?;
+            let chars: Vec<char> = value.chars().collect();
+            if Some(chars.len()) != size {
+                return Err(SaveError::Invalid {
+                    field: key.to_owned(),
+                    value: value.to_owned(),
+                });
+            }
+            Ok(chars)
+        };
+        let (types, states, owners) = (field("cells")?, field("states")?, field("owners")?);
+
+        let invalid = |key: &str, c: char| SaveError::Invalid {
+            field: key.to_owned(),
+            value: c.to_string(),
+        };
+        let mut cells = Vec::with_capacity(height);
+        for row in 0..height {
+            let mut line = Vec::with_capacity(width);
+            for i in row * width..(row + 1) * width {
+                let cell_type = match types[i] {
+                    '.' => CellType::Empty,
+                    '*' => CellType::Mine,
+                    c @ ('1'..='9' | 'a'..='u') => {
+                        CellType::Number(c.to_digit(36).unwrap_or(0) as usize)
+                    }
+                    c => return Err(invalid("cells", c)),
+                };
+                let cell_state = match states[i] {
+                    'h' => CellState::Hidden,
+                    'f' => CellState::Flagged,
+                    'v' => CellState::Visible,
+                    c => return Err(invalid("states", c)),
+                };
+                let mut cell = Cell::new(cell_type, cell_state);
+                cell.owner = match owners[i] {
+                    '-' => None,
+                    c @ ('0' | '1') => Some(c as usize - '0' as usize),
+                    c => return Err(invalid("owners", c)),
+                };
+                line.push(cell);
+            }
+            cells.push(line);
+        }
+        // The count drives the mine counter and the win check, so it must
+        // agree with the cells rather than be taken on trust
+        let mined = cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.cell_type == CellType::Mine)
+            .count();
+        if mines != mined {
+            return Err(SaveError::Invalid {
+                field: "mines".to_owned(),
+                value: mines.to_string(),
+            });
+        }
+        let rules = match document.get("ruleset") {
+            Ok(names) => names.split('+').try_fold(Ruleset::CLASSIC, |rules, name| {
+                rules.with_name(name).ok_or_else(|| SaveError::Invalid {
+                    field: "ruleset".to_owned(),
+                    value: names.to_owned(),
+                })
+            })?,
+            Err(_) => Ruleset::CLASSIC,
+        };
+        let mut board = Board::from_cells(width, height, mines, cells).with_rules(rules);
+        if rules.mines_per_cell > 1 {
+            for (i, c) in field("loads")?.into_iter().enumerate() {
+                let load = c.to_digit(10).ok_or_else(|| invalid("loads", c))? as usize;
+                board.set_mines_at(i / width, i % width, load);
+            }
+        }
+        if rules.duds > 0 || rules.bigs > 0 {
+            for (i, c) in field("kinds")?.into_iter().enumerate() {
+                let kind = match c {
+                    '-' => MineKind::Normal,
+                    'd' => MineKind::Dud,
+                    'b' => MineKind::Big,
+                    c => return Err(invalid("kinds", c)),
+                };
+                board.set_mine_kind(i / width, i % width, kind);
+            }
+        }
+        if rules.blind {
+            for (i, c) in field("shown")?.into_iter().enumerate() {
+                match c {
+                    '1' => board.show_number(i / width, i % width),
+                    '0' => {}
+                    c => return Err(invalid("shown", c)),
+                }
+            }
+        }
+        Ok(board)
+    }
+}
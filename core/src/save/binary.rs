@@ -0,0 +1,121 @@
+//! The compact format: a magic number, then the kind, version and fields.
+//! Strings are a length and their UTF-8 bytes; lengths, the version and
+//! integer values are LEB128 varints. Each value starts with a tag saying
+//! whether it is an integer or a string, so numbers take a byte or two.
+
+use super::{Document, SaveError};
+
+pub(super) const MAGIC: &[u8] = b"\x00MSW";
+
+const STRING: u8 = 0;
+const INTEGER: u8 = 1;
+
+pub(super) fn encode(document: &Document) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    string(&mut bytes, &document.kind);
+    varint(&mut bytes, document.version.into());
+    varint(&mut bytes, document.fields.len() as u64);
+    for (key, value) in &document.fields {
+        string(&mut bytes, key);
+        match integer(value) {
+            Some(n) => {
+                bytes.push(INTEGER);
+                varint(&mut bytes, n);
+            }
+            None => {
+                bytes.push(STRING);
+                string(&mut bytes, value);
+            }
+        }
+    }
+    bytes
+}
+
+pub(super) fn decode(bytes: &[u8]) -> Result<Document, SaveError> {
+    let mut reader = Reader {
+        bytes: bytes.strip_prefix(MAGIC).ok_or(SaveError::NotASave)?,
+    };
+    let kind = reader.string().map_err(|_| SaveError::NotASave)?;
+    let version = reader
+        .varint()
+        .ok()
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or(SaveError::NotASave)?;
+
+    let mut document = Document::new(&kind, version);
+    for _ in 0..reader.varint()? {
+        let key = reader.string()?;
+        let value = match reader.byte()? {
+            STRING => reader.string()?,
+            INTEGER => reader.varint()?.to_string(),
+            tag => return Err(corrupt(format!("unknown value tag {}", tag))),
+        };
+        document.set(&key, value);
+    }
+    if !reader.bytes.is_empty() {
+        return Err(corrupt(format!(
+            "{} bytes after the last field",
+            reader.bytes.len()
+        )));
+    }
+    Ok(document)
+}
+
+/// Values saved as varints are the ones that read back as the same text.
+fn integer(value: &str) -> Option<u64> {
+    value.parse::<u64>().ok().filter(|n| n.to_string() == value)
+}
+
+fn varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn string(bytes: &mut Vec<u8>, s: &str) {
+    varint(bytes, s.len() as u64);
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+fn corrupt(reason: String) -> SaveError {
+    SaveError::Corrupt(reason)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, SaveError> {
+        let (&first, rest) = self
+            .bytes
+            .split_first()
+            .ok_or_else(|| corrupt("file ends early".to_owned()))?;
+        self.bytes = rest;
+        Ok(first)
+    }
+
+    fn varint(&mut self) -> Result<u64, SaveError> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(corrupt("varint longer than 64 bits".to_owned()))
+    }
+
+    fn string(&mut self) -> Result<String, SaveError> {
+        let len = self.varint()?;
+        if len > self.bytes.len() as u64 {
+            return Err(corrupt("string runs past the end of the file".to_owned()));
+        }
+        let (text, rest) = self.bytes.split_at(len as usize);
+        self.bytes = rest;
+        String::from_utf8(text.to_vec()).map_err(|_| corrupt("string is not UTF-8".to_owned()))
+    }
+}
@@ -0,0 +1,289 @@
+//! The JSON format. Only what saves need is supported: objects, strings and
+//! numbers. Fields that read back as the same integer are written as
+//! numbers, everything else as strings.
+//!
+//! `rows` also reads the history export, an array of flat objects whose
+//! values may be `true`, `false` or `null` as well.
+
+use std::fmt::Write;
+
+use super::{Document, SaveError, MAGIC};
+
+pub(super) fn encode(document: &Document) -> String {
+    let mut text = String::from("{\n");
+    let _ = writeln!(text, "  {}: {},", quote(MAGIC), quote(&document.kind));
+    let _ = writeln!(text, "  \"version\": {},", document.version);
+    text.push_str("  \"fields\": {");
+    for (i, (key, value)) in document.fields.iter().enumerate() {
+        let separator = if i == 0 { "\n" } else { ",\n" };
+        let value = match value.parse::<u64>() {
+            Ok(n) if n.to_string() == *value => value.clone(),
+            _ => quote(value),
+        };
+        let _ = write!(text, "{}    {}: {}", separator, quote(key), value);
+    }
+    if !document.fields.is_empty() {
+        text.push_str("\n  ");
+    }
+    text.push_str("}\n}\n");
+    text
+}
+
+pub(super) fn decode(text: &str) -> Result<Document, SaveError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let Value::Object(top) = parser.document().map_err(SaveError::Corrupt)? else {
+        return Err(SaveError::NotASave);
+    };
+    let field = |name: &str| top.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+    let (Some(Value::String(kind)), Some(Value::Number(version))) =
+        (field(MAGIC), field("version"))
+    else {
+        return Err(SaveError::NotASave);
+    };
+    let version = version.parse().map_err(|_| SaveError::NotASave)?;
+
+    let mut document = Document::new(kind, version);
+    match field("fields") {
+        Some(Value::Object(fields)) => {
+            for (key, value) in fields {
+                match value {
+                    Value::String(value) | Value::Number(value) => document.set(key, value),
+                    _ => {
+                        return Err(SaveError::Corrupt(format!(
+                            "field `{}` is not a string or number",
+                            key
+                        )))
+                    }
+                }
+            }
+        }
+        None => {}
+        Some(_) => return Err(SaveError::Corrupt("`fields` is not an object".to_owned())),
+    }
+    Ok(document)
+}
+
+/// An array of flat objects, each as its keys and values in order, with
+/// every value as text: `true` and `false` as they are and `null` as
+/// empty.
+pub(crate) fn rows(text: &str) -> Result<Vec<Vec<(String, String)>>, SaveError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let Value::Array(items) = parser.document().map_err(SaveError::Corrupt)? else {
+        return Err(SaveError::Corrupt("expected an array".to_owned()));
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let Value::Object(members) = item else {
+                return Err(SaveError::Corrupt(format!("item {} is not an object", i)));
+            };
+            members
+                .into_iter()
+                .map(|(key, value)| match value {
+                    Value::String(text) | Value::Number(text) | Value::Literal(text) => {
+                        Ok((key, text))
+                    }
+                    _ => Err(SaveError::Corrupt(format!(
+                        "`{}` of item {} is not a plain value",
+                        key, i
+                    ))),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+enum Value {
+    String(String),
+    /// Kept as written, since document fields are text anyway.
+    Number(String),
+    Object(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    /// `true` or `false`, or `null` as empty.
+    Literal(String),
+}
+
+/// Saves nest one level deep; anything deeper is not a save.
+const MAX_DEPTH: usize = 4;
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn document(&mut self) -> Result<Value, String> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        if self.pos != self.chars.len() {
+            return Err("trailing characters".to_owned());
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied();
+        self.pos += 1;
+        c
+    }
+
+    fn expect(&mut self, wanted: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == wanted => Ok(()),
+            other => Err(format!("expected `{}`, found {:?}", wanted, other)),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos) {
+            Some('{') if depth < MAX_DEPTH => self.object(depth + 1).map(Value::Object),
+            Some('[') if depth < MAX_DEPTH => self.array(depth + 1).map(Value::Array),
+            Some('"') => self.string().map(Value::String),
+            Some(c) if *c == '-' || c.is_ascii_digit() => Ok(Value::Number(self.number())),
+            Some(c) => {
+                let ahead: String = self.chars[self.pos..].iter().take(5).collect();
+                let (word, text) = [("true", "true"), ("false", "false"), ("null", "")]
+                    .into_iter()
+                    .find(|(word, _)| ahead.starts_with(word))
+                    .ok_or_else(|| format!("unexpected {:?}", c))?;
+                self.pos += word.len();
+                Ok(Value::Literal(text.to_owned()))
+            }
+            None => Err("unexpected end".to_owned()),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Vec<Value>, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&']') {
+            self.pos += 1;
+            return Ok(items);
+        }
+        loop {
+            items.push(self.value(depth)?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(items),
+                other => return Err(format!("expected `,` or `]`, found {:?}", other)),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Vec<(String, Value)>, String> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&'}') {
+            self.pos += 1;
+            return Ok(members);
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value(depth)?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(members),
+                other => return Err(format!("expected `,` or `}}`, found {:?}", other)),
+            }
+        }
+    }
+
+    fn number(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.next() != Some('"') {
+            return Err("expected a string".to_owned());
+        }
+        let mut s = String::new();
+        loop {
+            match self.next().ok_or("unterminated string")? {
+                '"' => return Ok(s),
+                '\\' => match self.next().ok_or("unterminated escape")? {
+                    '"' => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/' => s.push('/'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'u' => {
+                        let high = self.hex()?;
+                        let code = if (0xd800..0xdc00).contains(&high) {
+                            // A surrogate pair spells one character
+                            if self.next() != Some('\\') || self.next() != Some('u') {
+                                return Err("unpaired surrogate".to_owned());
+                            }
+                            let low = self.hex()?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return Err("unpaired surrogate".to_owned());
+                            }
+                            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                        } else {
+                            high
+                        };
+                        s.push(char::from_u32(code).ok_or("invalid \\u escape")?);
+                    }
+                    other => return Err(format!("unknown escape `\\{}`", other)),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("invalid \\u escape `{}`", digits))
+    }
+}
@@ -0,0 +1,355 @@
+//! Campaign levels: a board and events that change the game while it is
+//! played.
+//!
+//! A level is a list of lines. `board`, first, names the board by its share
+//! code, `lock` closes a region of it until an event opens it again, and
+//! `<trigger> => <action>` lines fire once each, in order, the first time
+//! their trigger holds. `#` starts a comment.
+//!
+//! ```text
+//! board 16x16x30-classic-7
+//! lock 0,8 15,15
+//! at 30s => mines 4 in 0,0 15,7
+//! revealed 60 => bonus 20s
+//! cleared 0,0 15,7 => unlock 0,8 15,15
+//! ```
+//!
+//! Regions are two corners, `row,col row,col`, both inside. Triggers are
+//! `at <seconds>s` after the first move, `revealed <cells>` safe cells
+//! uncovered, and `cleared <region>` once every safe cell in the region is
+//! uncovered. Actions add mines to a region, `unlock` one, or start a
+//! `bonus` timer that stops the clock while it runs.
+//!
+//! Mines only land on hidden cells whose whole neighbourhood is hidden, so
+//! no number the player has seen changes under them; if a region has too
+//! few such cells, fewer mines are added.
+
+use std::fmt;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::board::{Board, BoardError, CellState, CellType, Pos};
+use crate::share::ShareCode;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+/// A rectangle of cells, corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl Region {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.top..=self.bottom).contains(&row) && (self.left..=self.right).contains(&col)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let cols = self.left..=self.right;
+        (self.top..=self.bottom).flat_map(move |row| cols.clone().map(move |col| (row, col)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// This long after the first move, not counting bonus time.
+    At(Duration),
+    /// Once this many cells are uncovered.
+    Revealed(usize),
+    /// Once every safe cell in the region is uncovered.
+    Cleared(Region),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Adds up to `count` mines to hidden cells of `region`.
+    Mines {
+        count: usize,
+        region: Region,
+    },
+    Unlock(Region),
+    /// Stops the clock for this long.
+    Bonus(Duration),
+}
+
+/// What an event did, for the frontend to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Happened {
+    /// This many mines were added.
+    Mines(usize),
+    Unlocked(Region),
+    Bonus(Duration),
+}
+
+impl Happened {
+    /// Whether the board changed in a way a replay of its moves can't
+    /// reproduce.
+    pub fn reshapes(&self) -> bool {
+        match *self {
+            Happened::Mines(count) => count > 0,
+            Happened::Unlocked(_) => true,
+            Happened::Bonus(_) => false,
+        }
+    }
+}
+
+/// A parsed level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub code: ShareCode,
+    pub locked: Vec<Region>,
+    pub events: Vec<(Trigger, Action)>,
+}
+
+impl Scenario {
+    pub fn parse(source: &str) -> Result<Self, ScenarioError> {
+        let mut code = None;
+        let mut locked = Vec::new();
+        let mut events = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let error = |message: String| ScenarioError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(text) = line.strip_prefix("board ") {
+                code = Some(text.parse::<ShareCode>().map_err(error)?);
+                continue;
+            }
+            let Some(board) = &code else {
+                return Err(error("expected the `board` line first".to_owned()));
+            };
+            if let Some(region) = line.strip_prefix("lock ") {
+                locked.push(parse_region(board, region).map_err(error)?);
+            } else if let Some((trigger, action)) = line.split_once("=>") {
+                events.push((
+                    parse_trigger(board, trigger.trim()).map_err(error)?,
+                    parse_action(board, action.trim()).map_err(error)?,
+                ));
+            } else {
+                return Err(error(format!(
+                    "expected `board`, `lock` or `<trigger> => <action>`, got `{}`",
+                    line
+                )));
+            }
+        }
+        let code = code.ok_or_else(|| ScenarioError {
+            line: source.lines().count(),
+            message: "no `board` line".to_owned(),
+        })?;
+        Ok(Self {
+            code,
+            locked,
+            events,
+        })
+    }
+
+    /// Deals the level's board with its regions locked, and the run that
+    /// fires its events.
+    pub fn start(&self) -> Result<(Board, Run), BoardError> {
+        let mut board = self.code.board()?;
+        for region in &self.locked {
+            for (row, col) in region.cells() {
+                board.set_locked(row, col, true);
+            }
+        }
+        let run = Run {
+            events: self.events.clone(),
+            fired: vec![false; self.events.len()],
+            bonuses: Vec::new(),
+            rng: StdRng::seed_from_u64(self.code.seed),
+        };
+        Ok((board, run))
+    }
+}
+
+fn parse_cell(text: &str) -> Result<(usize, usize), String> {
+    let (row, col) = text
+        .split_once(',')
+        .ok_or_else(|| format!("expected `row,col`, got `{}`", text))?;
+    let number = |part: &str| {
+        part.trim()
+            .parse()
+            .map_err(|_| format!("expected `row,col`, got `{}`", text))
+    };
+    Ok((number(row)?, number(col)?))
+}
+
+fn parse_region(board: &ShareCode, text: &str) -> Result<Region, String> {
+    let corners: Vec<&str> = text.split_whitespace().collect();
+    let [first, second] = corners[..] else {
+        return Err(format!("expected `row,col row,col`, got `{}`", text));
+    };
+    let (a, b) = (parse_cell(first)?, parse_cell(second)?);
+    if a.0.max(b.0) >= board.height || a.1.max(b.1) >= board.width {
+        return Err(format!(
+            "`{}` reaches off the {}x{} board",
+            text.trim(),
+            board.width,
+            board.height
+        ));
+    }
+    Ok(Region {
+        top: a.0.min(b.0),
+        left: a.1.min(b.1),
+        bottom: a.0.max(b.0),
+        right: a.1.max(b.1),
+    })
+}
+
+fn parse_seconds(text: &str) -> Result<Duration, String> {
+    text.strip_suffix('s')
+        .and_then(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("expected seconds such as `30s`, got `{}`", text))
+}
+
+fn parse_trigger(board: &ShareCode, text: &str) -> Result<Trigger, String> {
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    match word {
+        "at" => Ok(Trigger::At(parse_seconds(rest.trim())?)),
+        "revealed" => rest
+            .trim()
+            .parse()
+            .map(Trigger::Revealed)
+            .map_err(|_| format!("expected a cell count, got `{}`", rest.trim())),
+        "cleared" => Ok(Trigger::Cleared(parse_region(board, rest)?)),
+        other => Err(format!("unknown trigger `{}`", other)),
+    }
+}
+
+fn parse_action(board: &ShareCode, text: &str) -> Result<Action, String> {
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    match word {
+        "mines" => {
+            let (count, region) = rest
+                .split_once(" in ")
+                .ok_or_else(|| "expected `mines <count> in <region>`".to_owned())?;
+            Ok(Action::Mines {
+                count: count
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("expected a mine count, got `{}`", count.trim()))?,
+                region: parse_region(board, region)?,
+            })
+        }
+        "unlock" => Ok(Action::Unlock(parse_region(board, rest)?)),
+        "bonus" => Ok(Action::Bonus(parse_seconds(rest.trim())?)),
+        other => Err(format!("unknown action `{}`", other)),
+    }
+}
+
+/// A level being played: which events have fired and the bonus timers
+/// they started.
+#[derive(Clone, Debug)]
+pub struct Run {
+    events: Vec<(Trigger, Action)>,
+    fired: Vec<bool>,
+    /// When each bonus started, in played time, and how long it runs.
+    bonuses: Vec<(Duration, Duration)>,
+    /// Where added mines go, seeded by the level so a replay matches.
+    rng: StdRng,
+}
+
+impl Run {
+    /// Fires every event whose trigger holds, `played` after the first
+    /// move, and returns what they did. Call it every frame and after
+    /// every move.
+    pub fn update(&mut self, board: &mut Board, played: Duration) -> Vec<Happened> {
+        let clock = played.saturating_sub(self.credit(played));
+        let mut happened = Vec::new();
+        for i in 0..self.events.len() {
+            let (trigger, action) = self.events[i];
+            let holds = match trigger {
+                Trigger::At(time) => clock >= time,
+                Trigger::Revealed(cells) => board.revealed() >= cells,
+                Trigger::Cleared(region) => region.cells().all(|(row, col)| {
+                    let cell = board.cell(row, col);
+                    cell.cell_type == CellType::Mine || cell.cell_state == CellState::Visible
+                }),
+            };
+            if self.fired[i] || !holds {
+                continue;
+            }
+            self.fired[i] = true;
+            happened.push(match action {
+                Action::Mines { count, region } => {
+                    Happened::Mines(self.plant(board, count, region))
+                }
+                Action::Unlock(region) => {
+                    for (row, col) in region.cells() {
+                        board.set_locked(row, col, false);
+                    }
+                    Happened::Unlocked(region)
+                }
+                Action::Bonus(length) => {
+                    self.bonuses.push((played, length));
+                    Happened::Bonus(length)
+                }
+            });
+        }
+        happened
+    }
+
+    fn plant(&mut self, board: &mut Board, count: usize, region: Region) -> usize {
+        let mut spots: Vec<Pos> = region
+            .cells()
+            .filter(|&(row, col)| {
+                let cell = board.cell(row, col);
+                cell.cell_type != CellType::Mine
+                    && board
+                        .adjacent(row, col)
+                        .into_iter()
+                        .chain([(row, col)])
+                        .all(|(r, c)| board.cell(r, c).cell_state != CellState::Visible)
+            })
+            .map(|(row, col)| Pos::new(row, col))
+            .collect();
+        spots.shuffle(&mut self.rng);
+        spots
+            .into_iter()
+            .filter(|&pos| board.plant(pos))
+            .take(count)
+            .count()
+    }
+
+    /// Bonus time used up `played` after the first move, which the clock
+    /// doesn't count.
+    pub fn credit(&self, played: Duration) -> Duration {
+        self.bonuses
+            .iter()
+            .map(|&(start, length)| played.saturating_sub(start).min(length))
+            .sum()
+    }
+
+    /// What is left of the bonus timers running `played` after the first
+    /// move, if any is.
+    pub fn bonus_left(&self, played: Duration) -> Option<Duration> {
+        self.bonuses
+            .iter()
+            .map(|&(start, length)| (start + length).saturating_sub(played))
+            .filter(|left| !left.is_zero())
+            .max()
+    }
+}
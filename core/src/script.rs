@@ -0,0 +1,781 @@
+//! A tiny rule language for writing bots and game mods without recompiling
+//! the crate.
+//!
+//! A script is a list of lines. `rule <condition> => <action>` lines are
+//! tried in order against every revealed number on the board, and the first
+//! rule whose condition holds decides the move. A `fallback random` or
+//! `fallback first` line picks what to do when no rule applies. `#` starts a
+//! comment.
+//!
+//! ```text
+//! # Flag around numbers whose hidden neighbours must all be mines
+//! rule unknown > 0 && unknown == number - mines => mine
+//! # Reveal around numbers that already have all their mines
+//! rule unknown > 0 && mines == number => reveal
+//! fallback random
+//! ```
+//!
+//! Conditions are integer expressions with `+ - * /`, comparisons, `&&`,
+//! `||`, `!` and parentheses, over these variables:
+//!
+//! - `number`: the value of the revealed cell
+//! - `hidden`: hidden neighbours
+//! - `mines`: hidden neighbours the bot has marked as mines
+//! - `unknown`: hidden neighbours not marked as mines
+//! - `row`, `col`, `width`, `height`
+//! - `remaining`: mines on the board minus the ones the bot has marked
+//!
+//! Scripts cannot loop or touch anything but the view they are given, so a
+//! loaded script always terminates and can't cheat.
+//!
+//! A mod is a script of hooks that change the game itself, installed as a
+//! `Plugin`:
+//!
+//! ```text
+//! # No clicks on the edge until a tenth of the board is clear
+//! block (row == 0 || col == 0 || row == height - 1 || col == width - 1) && revealed * 10 < width * height
+//! # Uncovering a 3 or more flags the mines around it
+//! after number >= 3 => flag
+//! hud left < 10 => Only {left} cells to go!
+//! end won && flags == 0 => Cleared without a single flag
+//! ```
+//!
+//! `block <condition>` refuses clicks on cells where it holds. `after
+//! <condition> => reveal` or `=> flag` uncovers the safe cells or flags the
+//! mines around a clicked cell that uncovered something. `hud` shows the
+//! text of the first line whose condition holds, and `end` adds its text to
+//! the summary of a finished game. Text can name variables in braces. The
+//! variables are:
+//!
+//! - `row`, `col`: the clicked cell, 0 for `hud` and `end`
+//! - `number`: its value, -1 for a mine
+//! - `hidden`: its hidden neighbours
+//! - `width`, `height`, `mines`, `flags`
+//! - `revealed`: cells uncovered, and `left`: safe cells still hidden
+//! - `won`: 1 in an `end` line of a won game
+
+use std::collections::HashSet;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::agent::{Agent, AgentMove, View};
+use crate::board::{Board, CellState, CellType};
+use crate::plugin::{HudWidget, Message, Plugin, PluginRegistry, PostGameAnalyzer, RuleModifier};
+use crate::protocol::CellView;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Reveal,
+    Mine,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Fallback {
+    Random,
+    First,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Var {
+    Number,
+    Hidden,
+    Mines,
+    Unknown,
+    Row,
+    Col,
+    Width,
+    Height,
+    Remaining,
+    Total,
+    Flags,
+    Revealed,
+    Left,
+    Won,
+}
+
+/// The names bot conditions can use.
+const BOT_VARS: &[(&str, Var)] = &[
+    ("number", Var::Number),
+    ("hidden", Var::Hidden),
+    ("mines", Var::Mines),
+    ("unknown", Var::Unknown),
+    ("row", Var::Row),
+    ("col", Var::Col),
+    ("width", Var::Width),
+    ("height", Var::Height),
+    ("remaining", Var::Remaining),
+];
+
+/// The names mod conditions and text can use.
+const MOD_VARS: &[(&str, Var)] = &[
+    ("number", Var::Number),
+    ("hidden", Var::Hidden),
+    ("row", Var::Row),
+    ("col", Var::Col),
+    ("width", Var::Width),
+    ("height", Var::Height),
+    ("mines", Var::Total),
+    ("flags", Var::Flags),
+    ("revealed", Var::Revealed),
+    ("left", Var::Left),
+    ("won", Var::Won),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    Int(i64),
+    Var(Var),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, vars: &dyn Fn(Var) -> i64) -> i64 {
+        match self {
+            Expr::Int(n) => *n,
+            Expr::Var(var) => vars(*var),
+            Expr::Neg(expr) => expr.eval(vars).wrapping_neg(),
+            Expr::Not(expr) => i64::from(expr.eval(vars) == 0),
+            Expr::Bin(op, lhs, rhs) => {
+                let (a, b) = (lhs.eval(vars), rhs.eval(vars));
+                match op {
+                    BinOp::Add => a.wrapping_add(b),
+                    BinOp::Sub => a.wrapping_sub(b),
+                    BinOp::Mul => a.wrapping_mul(b),
+                    BinOp::Div => a.checked_div(b).unwrap_or(0),
+                    BinOp::Eq => i64::from(a == b),
+                    BinOp::Ne => i64::from(a != b),
+                    BinOp::Lt => i64::from(a < b),
+                    BinOp::Le => i64::from(a <= b),
+                    BinOp::Gt => i64::from(a > b),
+                    BinOp::Ge => i64::from(a >= b),
+                    BinOp::And => i64::from(a != 0 && b != 0),
+                    BinOp::Or => i64::from(a != 0 || b != 0),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Int(i64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    const OPS: [&str; 14] = [
+        "&&", "||", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "!", "=",
+    ];
+
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..end]
+                .parse()
+                .map_err(|_| format!("number too large: {}", &rest[..end]))?;
+            tokens.push(Token::Int(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_owned()));
+            rest = &rest[end..];
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            rest = &rest[1..];
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            rest = &rest[1..];
+        } else if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+            if *op == "=" {
+                return Err("use `==` to compare".to_owned());
+            }
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected character `{}`", c));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser, loosest binding first: `||`, `&&`,
+/// comparisons, `+ -`, `* /`, then unary operators and atoms.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    vars: &'static [(&'static str, Var)],
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[(&str, BinOp)],
+        next: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut lhs = next(self)?;
+        while let Some(&(_, op)) = self
+            .peek_op()
+            .and_then(|token| ops.iter().find(|(name, _)| *name == token))
+        {
+            self.pos += 1;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(next(self)?));
+        }
+        Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary(&[("||", BinOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary(&[("&&", BinOp::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+                ("<=", BinOp::Le),
+                (">=", BinOp::Ge),
+                ("<", BinOp::Lt),
+                (">", BinOp::Gt),
+            ],
+            Self::sum,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        self.binary(&[("+", BinOp::Add), ("-", BinOp::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        self.binary(&[("*", BinOp::Mul), ("/", BinOp::Div)], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek_op() {
+            Some("-") => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Some("!") => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Int(n)) => Ok(Expr::Int(n)),
+            Some(Token::Ident(name)) => variable(self.vars, &name).map(Expr::Var),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::RParen) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("missing `)`".to_owned()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("expression ends early".to_owned()),
+        }
+    }
+}
+
+/// Parsing and evaluating both recurse once per nesting level, so a
+/// condition is capped well below what would overflow the stack.
+const MAX_TOKENS: usize = 256;
+
+fn variable(vars: &[(&str, Var)], name: &str) -> Result<Var, String> {
+    vars.iter()
+        .find(|(known, _)| *known == name)
+        .map(|&(_, var)| var)
+        .ok_or_else(|| format!("unknown variable `{}`", name))
+}
+
+fn parse_expr(source: &str, vars: &'static [(&'static str, Var)]) -> Result<Expr, String> {
+    let tokens = tokenize(source)?;
+    if tokens.len() > MAX_TOKENS {
+        return Err(format!(
+            "condition has {} tokens, at most {} allowed",
+            tokens.len(),
+            MAX_TOKENS
+        ));
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        vars,
+    };
+    let expr = parser.or()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(format!("unexpected {:?}", parser.tokens[parser.pos]));
+    }
+    Ok(expr)
+}
+
+/// A parsed script, ready to be played as an `Agent`.
+#[derive(Clone, Debug)]
+pub struct Script {
+    name: String,
+    rules: Vec<(Expr, Action)>,
+    fallback: Option<Fallback>,
+}
+
+impl Script {
+    pub fn parse(name: &str, source: &str) -> Result<Self, ScriptError> {
+        let mut script = Self {
+            name: name.to_owned(),
+            rules: Vec::new(),
+            fallback: None,
+        };
+
+        for (i, line) in source.lines().enumerate() {
+            let error = |message: String| ScriptError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(rule) = line.strip_prefix("rule ") {
+                let (condition, action) = rule
+                    .split_once("=>")
+                    .ok_or_else(|| error("expected `rule <condition> => <action>`".to_owned()))?;
+                let action = match action.trim() {
+                    "reveal" => Action::Reveal,
+                    "mine" => Action::Mine,
+                    other => return Err(error(format!("unknown action `{}`", other))),
+                };
+                script
+                    .rules
+                    .push((parse_expr(condition, BOT_VARS).map_err(error)?, action));
+            } else if let Some(fallback) = line.strip_prefix("fallback ") {
+                script.fallback = Some(match fallback.trim() {
+                    "random" => Fallback::Random,
+                    "first" => Fallback::First,
+                    other => return Err(error(format!("unknown fallback `{}`", other))),
+                });
+            } else {
+                return Err(error(format!(
+                    "expected `rule` or `fallback`, got `{}`",
+                    line
+                )));
+            }
+        }
+        Ok(script)
+    }
+}
+
+/// Plays a `Script`, remembering the cells its rules marked as mines.
+pub struct ScriptAgent {
+    script: Script,
+    rng: StdRng,
+    mines: HashSet<(usize, usize)>,
+}
+
+impl ScriptAgent {
+    pub fn new(script: Script) -> Self {
+        Self {
+            script,
+            rng: StdRng::seed_from_u64(0),
+            mines: HashSet::new(),
+        }
+    }
+
+    /// Applies rules until one reveals a cell or none makes progress.
+    fn apply_rules(&mut self, view: &View) -> Option<(usize, usize)> {
+        loop {
+            let mut progress = false;
+            for row in 0..view.height {
+                for col in 0..view.width {
+                    let CellView::Number(number) = view.get(row, col) else {
+                        continue;
+                    };
+                    let hidden: Vec<_> = view
+                        .neighbors(row, col)
+                        .into_iter()
+                        .filter(|&(r, c)| view.get(r, c) == CellView::Hidden)
+                        .collect();
+                    let unknown: Vec<_> = hidden
+                        .iter()
+                        .copied()
+                        .filter(|pos| !self.mines.contains(pos))
+                        .collect();
+                    let mines = hidden.len() - unknown.len();
+                    let remaining = view.mines as i64 - self.mines.len() as i64;
+
+                    let vars = |var: Var| match var {
+                        Var::Number => number as i64,
+                        Var::Hidden => hidden.len() as i64,
+                        Var::Mines => mines as i64,
+                        Var::Unknown => unknown.len() as i64,
+                        Var::Row => row as i64,
+                        Var::Col => col as i64,
+                        Var::Width => view.width as i64,
+                        Var::Height => view.height as i64,
+                        Var::Remaining => remaining,
+                        // Only mods can name these
+                        Var::Total | Var::Flags | Var::Revealed | Var::Left | Var::Won => 0,
+                    };
+                    let Some(action) = self
+                        .script
+                        .rules
+                        .iter()
+                        .find(|(condition, _)| condition.eval(&vars) != 0)
+                        .map(|(_, action)| *action)
+                    else {
+                        continue;
+                    };
+
+                    match action {
+                        Action::Reveal => {
+                            if let Some(&pos) = unknown.first() {
+                                return Some(pos);
+                            }
+                        }
+                        Action::Mine => {
+                            for pos in unknown {
+                                progress |= self.mines.insert(pos);
+                            }
+                        }
+                    }
+                }
+            }
+            if !progress {
+                return None;
+            }
+        }
+    }
+}
+
+impl Agent for ScriptAgent {
+    fn name(&self) -> &str {
+        &self.script.name
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.mines.clear();
+    }
+
+    fn next_move(&mut self, view: &View) -> Option<AgentMove> {
+        if let Some((row, col)) = self.apply_rules(view) {
+            return Some(AgentMove {
+                row,
+                col,
+                guess: false,
+            });
+        }
+
+        let candidates: Vec<_> = view
+            .playable()
+            .into_iter()
+            .filter(|pos| !self.mines.contains(pos))
+            .collect();
+        let &(row, col) = match self.script.fallback? {
+            Fallback::Random => candidates.choose(&mut self.rng)?,
+            Fallback::First => candidates.first()?,
+        };
+        Some(AgentMove {
+            row,
+            col,
+            guess: true,
+        })
+    }
+}
+
+/// What an `after` hook does around the clicked cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Gift {
+    Reveal,
+    Flag,
+}
+
+/// Text with variables filled in when it is shown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Var(Var),
+}
+
+fn parse_text(source: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = source.trim();
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| "missing `}` in text".to_owned())?;
+        if open > 0 {
+            pieces.push(Piece::Text(rest[..open].to_owned()));
+        }
+        let name = rest[open + 1..open + close].trim();
+        pieces.push(Piece::Var(variable(MOD_VARS, name)?));
+        rest = &rest[open + close + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest.to_owned()));
+    }
+    Ok(pieces)
+}
+
+fn fill(pieces: &[Piece], vars: &dyn Fn(Var) -> i64) -> String {
+    pieces
+        .iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => text.clone(),
+            Piece::Var(var) => vars(*var).to_string(),
+        })
+        .collect()
+}
+
+/// The mod variables for `board`, about the clicked cell if there is one.
+fn mod_vars(board: &Board, cell: Option<(usize, usize)>, won: bool) -> impl Fn(Var) -> i64 + '_ {
+    let (number, hidden) = cell.map_or((0, 0), |(row, col)| {
+        let number = match board.cell(row, col).cell_type {
+            CellType::Empty => 0,
+            CellType::Number(n) => n as i64,
+            CellType::Mine => -1,
+        };
+        let hidden = board
+            .adjacent(row, col)
+            .into_iter()
+            .filter(|&(r, c)| board.cell(r, c).cell_state == CellState::Hidden)
+            .count();
+        (number, hidden as i64)
+    });
+    let (row, col) = cell.unwrap_or_default();
+    let safe = board.width() * board.height() - board.mines();
+    move |var| match var {
+        Var::Number => number,
+        Var::Hidden => hidden,
+        Var::Row => row as i64,
+        Var::Col => col as i64,
+        Var::Width => board.width() as i64,
+        Var::Height => board.height() as i64,
+        Var::Total => board.mines() as i64,
+        Var::Flags => board.flags() as i64,
+        Var::Revealed => board.revealed() as i64,
+        Var::Left => safe.saturating_sub(board.revealed()) as i64,
+        Var::Won => i64::from(won),
+        // Only bots can name these
+        Var::Mines | Var::Unknown | Var::Remaining => 0,
+    }
+}
+
+/// A parsed mod, installed into a game's `PluginRegistry` like any other
+/// plugin.
+#[derive(Clone, Debug)]
+pub struct ScriptMod {
+    name: String,
+    blocks: Vec<Expr>,
+    gifts: Vec<(Expr, Gift)>,
+    hud: Vec<(Expr, Vec<Piece>)>,
+    ends: Vec<(Expr, Vec<Piece>)>,
+}
+
+impl ScriptMod {
+    pub fn parse(name: &str, source: &str) -> Result<Self, ScriptError> {
+        let mut script = Self {
+            name: name.to_owned(),
+            blocks: Vec::new(),
+            gifts: Vec::new(),
+            hud: Vec::new(),
+            ends: Vec::new(),
+        };
+
+        for (i, line) in source.lines().enumerate() {
+            let error = |message: String| ScriptError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (hook, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+            if hook == "block" {
+                script
+                    .blocks
+                    .push(parse_expr(rest, MOD_VARS).map_err(error)?);
+                continue;
+            }
+            let (condition, result) = rest
+                .split_once("=>")
+                .ok_or_else(|| error(format!("expected `{} <condition> => ...`", hook)))?;
+            let condition = parse_expr(condition, MOD_VARS).map_err(error)?;
+            match hook {
+                "after" => {
+                    let gift = match result.trim() {
+                        "reveal" => Gift::Reveal,
+                        "flag" => Gift::Flag,
+                        other => return Err(error(format!("unknown action `{}`", other))),
+                    };
+                    script.gifts.push((condition, gift));
+                }
+                "hud" => script
+                    .hud
+                    .push((condition, parse_text(result).map_err(error)?)),
+                "end" => script
+                    .ends
+                    .push((condition, parse_text(result).map_err(error)?)),
+                _ => {
+                    return Err(error(format!(
+                        "expected `block`, `after`, `hud` or `end`, got `{}`",
+                        line
+                    )))
+                }
+            }
+        }
+        Ok(script)
+    }
+}
+
+impl Plugin for ScriptMod {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn register(&self, registry: &mut PluginRegistry) {
+        if !self.blocks.is_empty() || !self.gifts.is_empty() {
+            registry.add_rule(Box::new(self.clone()));
+        }
+        if !self.hud.is_empty() {
+            registry.add_widget(Box::new(self.clone()));
+        }
+        if !self.ends.is_empty() {
+            registry.add_analyzer(Box::new(self.clone()));
+        }
+    }
+}
+
+impl RuleModifier for ScriptMod {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn allow_reveal(&self, board: &Board, row: usize, col: usize) -> bool {
+        let vars = mod_vars(board, Some((row, col)), false);
+        !self.blocks.iter().any(|block| block.eval(&vars) != 0)
+    }
+
+    fn after_reveal(&self, board: &mut Board, row: usize, col: usize) {
+        let gifts: Vec<Gift> = {
+            let vars = mod_vars(board, Some((row, col)), false);
+            self.gifts
+                .iter()
+                .filter(|(condition, _)| condition.eval(&vars) != 0)
+                .map(|&(_, gift)| gift)
+                .collect()
+        };
+        for gift in gifts {
+            for (r, c) in board.adjacent(row, col) {
+                let cell = board.cell(r, c);
+                if cell.cell_state != CellState::Hidden {
+                    continue;
+                }
+                match (gift, cell.cell_type) {
+                    (Gift::Flag, CellType::Mine) => {
+                        board.toggle_flag(r, c);
+                    }
+                    (Gift::Reveal, CellType::Empty | CellType::Number(_)) => {
+                        board.reveal(r, c);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl HudWidget for ScriptMod {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn text(&self, board: &Board) -> Option<Message> {
+        let vars = mod_vars(board, None, false);
+        let (_, text) = self
+            .hud
+            .iter()
+            .find(|(condition, _)| condition.eval(&vars) != 0)?;
+        Some(Message::Plain(fill(text, &vars)))
+    }
+}
+
+impl PostGameAnalyzer for ScriptMod {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn analyze(&self, board: &Board, won: bool) -> Vec<Message> {
+        let vars = mod_vars(board, None, won);
+        self.ends
+            .iter()
+            .filter(|(condition, _)| condition.eval(&vars) != 0)
+            .map(|(_, text)| Message::Plain(fill(text, &vars)))
+            .collect()
+    }
+}
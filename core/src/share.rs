@@ -0,0 +1,120 @@
+//! Share codes: the size, mine count, variant and seed of a game in one
+//! line such as `16x16x50-classic-1234`, so anyone can play the same
+//! board again. Custom rules are named among the variant's flags, as in
+//! `16x16x50-custom+knight+wrap-1234`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, BoardError, Pos};
+use crate::generate::{self, Progress};
+use crate::rules::Ruleset;
+use crate::stats::Variant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShareCode {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub variant: Variant,
+    pub seed: u64,
+    /// The classic rules unless the variant is `Variant::CUSTOM`.
+    pub rules: Ruleset,
+}
+
+impl ShareCode {
+    /// Generates the game's board, with the mines placed as its variant
+    /// says. No-guess boards are solvable from the centre, which is
+    /// revealed already.
+    pub fn board(&self) -> Result<Board, BoardError> {
+        self.board_with(&Progress::default())
+    }
+
+    /// Like `board`, reporting the candidates a no-guess board took to
+    /// `progress` and giving up once it is cancelled.
+    pub fn board_with(&self, progress: &Progress) -> Result<Board, BoardError> {
+        let generator = self.variant.generator();
+        if !self.variant.contains(Variant::NO_GUESS) {
+            return Board::ruled(
+                self.width,
+                self.height,
+                self.mines,
+                self.seed,
+                None,
+                generator,
+                self.rules,
+            );
+        }
+        let start = Pos::new(self.height / 2, self.width / 2);
+        let mut board = generate::no_guess_with(
+            self.width,
+            self.height,
+            self.mines,
+            start,
+            self.seed,
+            generator,
+            progress,
+        )?;
+        board.reveal(start.row, start.col);
+        Ok(board)
+    }
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<String> = self.variant.names().map(str::to_owned).collect();
+        names.extend(self.rules.names());
+        let variant = match names.is_empty() {
+            true => "classic".to_owned(),
+            false => names.join("+"),
+        };
+        write!(
+            f,
+            "{}x{}x{}-{}-{}",
+            self.width, self.height, self.mines, variant, self.seed
+        )
+    }
+}
+
+/// Surrounding whitespace is ignored, as codes are often pasted.
+impl FromStr for ShareCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a share code", s.trim());
+        // Variant names can hold dashes themselves
+        let (rest, seed) = s.trim().rsplit_once('-').ok_or_else(invalid)?;
+        let (size, variant) = rest.split_once('-').ok_or_else(invalid)?;
+        let numbers: Vec<usize> = size
+            .split('x')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid())?;
+        let [width, height, mines] = numbers[..] else {
+            return Err(invalid());
+        };
+        Board::validate(width, height, mines).map_err(|err| err.to_string())?;
+        let (mut flags, mut rules) = (Variant::CLASSIC, Ruleset::CLASSIC);
+        for name in variant.split('+') {
+            match (Variant::decode(name), rules.with_name(name)) {
+                (Some(flag), _) => flags = flags.with(flag),
+                (None, Some(changed)) => rules = changed,
+                (None, None) => return Err(format!("unknown variant `{}`", name)),
+            }
+        }
+        if !rules.is_classic() {
+            if flags.contains(Variant::NO_GUESS) {
+                return Err("no-guess boards are only dealt by the classic rules".to_owned());
+            }
+            flags = flags.with(Variant::CUSTOM);
+        }
+        Ok(Self {
+            width,
+            height,
+            mines,
+            variant: flags,
+            seed: seed.parse().map_err(|_| invalid())?,
+            rules,
+        })
+    }
+}
@@ -0,0 +1,237 @@
+//! Deterministic games for tests: build a board from a picture, play a
+//! scripted list of moves and compare what the player would see.
+//!
+//! A layout has one line per row; spaces are ignored and numbers are worked
+//! out from the mines.
+//!
+//! ```text
+//! .  hidden safe cell     *  hidden mine
+//! f  flagged safe cell    F  flagged mine
+//! o  revealed safe cell
+//! ```
+//!
+//! Move scripts have one move per line or `;`-separated:
+//! `reveal ROW COL`, `flag ROW COL` or `chord ROW COL`, optionally
+//! shortened to `r`, `f` and `c`.
+//!
+//! `Sim::view` draws the board as the player sees it: `#` hidden, `F`
+//! flagged, `*` a shown mine, `_` a revealed empty cell and `1`-`8` numbers.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::board::{mine_counts, Board, Cell, CellState, CellType};
+use crate::event::Event;
+use crate::flag_capture::MoveError;
+use crate::game::{Game, Status};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Builds a board from a layout picture.
+pub fn board(layout: &str) -> Result<Board, ParseError> {
+    let mut mines = Vec::new();
+    let mut states = Vec::new();
+    for (i, line) in layout.lines().enumerate() {
+        let error = |message: String| ParseError {
+            line: i + 1,
+            message,
+        };
+        let row: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if row.is_empty() {
+            continue;
+        }
+        if let Some(first) = states.first().map(Vec::len) {
+            if row.len() != first {
+                return Err(error(format!(
+                    "row has {} cells, expected {}",
+                    row.len(),
+                    first
+                )));
+            }
+        }
+
+        let mut mine_row = Vec::with_capacity(row.len());
+        let mut state_row = Vec::with_capacity(row.len());
+        for c in row {
+            let (mine, state) = match c {
+                '.' => (false, CellState::Hidden),
+                '*' => (true, CellState::Hidden),
+                'f' => (false, CellState::Flagged),
+                'F' => (true, CellState::Flagged),
+                'o' => (false, CellState::Visible),
+                other => return Err(error(format!("unknown cell `{}`", other))),
+            };
+            mine_row.push(mine);
+            state_row.push(state);
+        }
+        mines.push(mine_row);
+        states.push(state_row);
+    }
+    if states.is_empty() {
+        return Err(ParseError {
+            line: 1,
+            message: "the layout has no cells".to_owned(),
+        });
+    }
+
+    let height = mines.len();
+    let width = mines[0].len();
+    let mines: Vec<bool> = mines.into_iter().flatten().collect();
+    let counts = mine_counts(width, height, &mines);
+    let cells = (0..height)
+        .map(|row| {
+            (0..width)
+                .map(|col| {
+                    let i = row * width + col;
+                    let cell_type = match (mines[i], counts[i]) {
+                        (true, _) => CellType::Mine,
+                        (false, 0) => CellType::Empty,
+                        (false, n) => CellType::Number(n as usize),
+                    };
+                    Cell::new(cell_type, states[row][col])
+                })
+                .collect()
+        })
+        .collect();
+    let count = mines.iter().filter(|&&mine| mine).count();
+    Ok(Board::from_cells(width, height, count, cells))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
+}
+
+impl Move {
+    /// The cell the move was made on.
+    pub fn position(self) -> (usize, usize) {
+        match self {
+            Move::Reveal(row, col) | Move::Flag(row, col) | Move::Chord(row, col) => (row, col),
+        }
+    }
+}
+
+/// Parses a move script.
+pub fn moves(script: &str) -> Result<Vec<Move>, ParseError> {
+    let mut moves = Vec::new();
+    for (i, line) in script.lines().enumerate() {
+        let error = |message: String| ParseError {
+            line: i + 1,
+            message,
+        };
+        for step in line.split(';') {
+            let words: Vec<&str> = step.split_whitespace().collect();
+            let [name, row, col] = words[..] else {
+                if words.is_empty() {
+                    continue;
+                }
+                return Err(error(format!(
+                    "expected `MOVE ROW COL`, got `{}`",
+                    step.trim()
+                )));
+            };
+            let number = |word: &str| {
+                word.parse::<usize>()
+                    .map_err(|_| error(format!("`{}` is not a cell index", word)))
+            };
+            let (row, col) = (number(row)?, number(col)?);
+            moves.push(match name {
+                "reveal" | "r" => Move::Reveal(row, col),
+                "flag" | "f" => Move::Flag(row, col),
+                "chord" | "c" => Move::Chord(row, col),
+                other => return Err(error(format!("unknown move `{}`", other))),
+            });
+        }
+    }
+    Ok(moves)
+}
+
+/// A classic game that records every event it emits.
+pub struct Sim {
+    game: Game,
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+impl Sim {
+    pub fn new(layout: &str) -> Result<Self, ParseError> {
+        let mut game = Game::new(board(layout)?);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&events);
+        game.subscribe(Box::new(move |event: &Event| {
+            log.borrow_mut().push(event.clone())
+        }));
+        Ok(Self { game, events })
+    }
+
+    pub fn play(&mut self, step: Move) -> Result<(), MoveError> {
+        match step {
+            Move::Reveal(row, col) => self.game.reveal(row, col),
+            Move::Flag(row, col) => self.game.toggle_flag(row, col),
+            Move::Chord(row, col) => self.game.chord(row, col),
+        }
+    }
+
+    /// Plays a move script and returns each move's result in order.
+    pub fn run(&mut self, script: &str) -> Result<Vec<Result<(), MoveError>>, ParseError> {
+        Ok(moves(script)?
+            .into_iter()
+            .map(|step| self.play(step))
+            .collect())
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn board(&self) -> &Board {
+        self.game.board()
+    }
+
+    pub fn status(&self) -> Status {
+        self.game.status()
+    }
+
+    /// Every event emitted so far, oldest first.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// The board as the player sees it, one line per row.
+    pub fn view(&self) -> String {
+        let board = self.board();
+        (0..board.height())
+            .map(|row| {
+                (0..board.width())
+                    .map(|col| {
+                        let cell = board.cell(row, col);
+                        match (cell.cell_state, cell.cell_type) {
+                            (CellState::Hidden, _) => '#',
+                            (CellState::Flagged, _) => 'F',
+                            (CellState::Visible, CellType::Mine) => '*',
+                            (CellState::Visible, CellType::Empty) => '_',
+                            (CellState::Visible, CellType::Number(n)) => {
+                                char::from_digit(n as u32, 10).unwrap_or('?')
+                            }
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
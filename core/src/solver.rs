@@ -0,0 +1,1360 @@
+//! What the numbers on show prove about the hidden cells.
+//!
+//! The solver sees what a player sees. Every revealed number with unknown
+//! neighbours is a constraint: so many of these cells are mines. A quick
+//! pass over single numbers, `Knowledge::single_point`, runs first; then
+//! pairs of overlapping numbers are settled, which finds most of what
+//! there is to find cheaply. What is left is split into
+//! components, groups of cells tied together by shared numbers, and every
+//! arrangement of mines in each component is enumerated: a cell that is a
+//! mine in all of them, or in none, is proven. The mine count ties the
+//! components together, along with the cells away from every number: only
+//! arrangements that leave a number of mines the rest can hold count, and
+//! once the components account for every mine, the cells away from them
+//! are safe. Flags are the player's opinion, so they prove nothing, but
+//! mines on show, ones that went off without ending the game, count
+//! towards the numbers around them and the mine count.
+//!
+//! Every cell is taken to hold one mine or none. Rules that stack more
+//! mines on a cell are beyond it, so on such boards it proves nothing and
+//! gives no chances.
+//!
+//! `Solver` does the same work with buffers it keeps between calls, for
+//! callers that solve again after every move.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::View;
+use crate::protocol::CellView;
+
+/// Components with more cells than this are left to the cheaper rules, as
+/// enumerating them can take seconds.
+const MAX_COMPONENT: usize = 40;
+
+/// Samples drawn from each component too large to enumerate, unless the
+/// caller gives a budget of its own.
+pub const DEFAULT_SAMPLES: usize = 1000;
+
+/// Steps allowed when looking for an arrangement to start sampling from.
+const SEARCH_BUDGET: usize = 1_000_000;
+
+/// A number's unknown neighbours, `Constraints::cells[start..end]` in
+/// reading order, and how many of them are mines.
+#[derive(Clone, Copy, Debug)]
+struct Constraint {
+    number: (usize, usize),
+    start: usize,
+    end: usize,
+    mines: usize,
+}
+
+impl Constraint {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// The constraint of every revealed number with unknown neighbours, in
+/// reading order, their cells sharing one list. Rebuilt in place, so the
+/// memory is reused from one round of the solver to the next.
+#[derive(Clone, Debug, Default)]
+struct Constraints {
+    width: usize,
+    list: Vec<Constraint>,
+    cells: Vec<(usize, usize)>,
+    /// One more than the index into `list` of each cell's constraint, or 0
+    /// where the cell has none.
+    at: Vec<usize>,
+}
+
+impl Constraints {
+    fn build(&mut self, view: &View, known: &Known) {
+        self.width = view.width;
+        self.list.clear();
+        self.cells.clear();
+        self.at.clear();
+        self.at.resize(view.width * view.height, 0);
+        for row in 0..view.height {
+            for col in 0..view.width {
+                let CellView::Number(n) = view.get(row, col) else {
+                    continue;
+                };
+                let start = self.cells.len();
+                let mut found = 0;
+                for pos in view.around(row, col) {
+                    if view.shows_mine(pos.0, pos.1) {
+                        found += 1;
+                        continue;
+                    }
+                    if !matches!(view.get(pos.0, pos.1), CellView::Hidden | CellView::Flagged) {
+                        continue;
+                    }
+                    match known.get(pos) {
+                        Some(true) => found += 1,
+                        Some(false) => {}
+                        None => self.cells.push(pos),
+                    }
+                }
+                if self.cells.len() > start {
+                    self.list.push(Constraint {
+                        number: (row, col),
+                        start,
+                        end: self.cells.len(),
+                        mines: n.saturating_sub(found),
+                    });
+                    self.at[row * view.width + col] = self.list.len();
+                }
+            }
+        }
+    }
+
+    /// The constraint of the number at `(row, col)`, if it has one.
+    fn get(&self, (row, col): (usize, usize)) -> Option<&Constraint> {
+        if col >= self.width {
+            return None;
+        }
+        match self.at.get(row * self.width + col) {
+            Some(&i) if i > 0 => Some(&self.list[i - 1]),
+            _ => None,
+        }
+    }
+
+    fn cells(&self, constraint: &Constraint) -> &[(usize, usize)] {
+        &self.cells[constraint.start..constraint.end]
+    }
+}
+
+/// What is proven about each cell, by index: `Some(true)` for a mine.
+#[derive(Clone, Debug, Default)]
+struct Known {
+    width: usize,
+    cells: Vec<Option<bool>>,
+    /// How many cells are proven mines.
+    mines: usize,
+}
+
+impl Known {
+    fn of(view: &View, knowledge: &Knowledge) -> Self {
+        let mut known = Self::default();
+        known.reset(view);
+        for &pos in &knowledge.safe {
+            known.learn(pos, false);
+        }
+        for &pos in &knowledge.mines {
+            known.learn(pos, true);
+        }
+        known
+    }
+
+    fn reset(&mut self, view: &View) {
+        self.width = view.width;
+        self.cells.clear();
+        self.cells.resize(view.width * view.height, None);
+        self.mines = 0;
+    }
+
+    fn get(&self, (row, col): (usize, usize)) -> Option<bool> {
+        self.cells[row * self.width + col]
+    }
+
+    /// Records what `(row, col)` is. Returns whether it wasn't known yet.
+    fn learn(&mut self, (row, col): (usize, usize), mine: bool) -> bool {
+        let cell = &mut self.cells[row * self.width + col];
+        if cell.is_some() {
+            return false;
+        }
+        *cell = Some(mine);
+        self.mines += usize::from(mine);
+        true
+    }
+}
+
+/// Everything the numbers prove.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Knowledge {
+    pub safe: HashSet<(usize, usize)>,
+    pub mines: HashSet<(usize, usize)>,
+}
+
+impl Knowledge {
+    pub fn of(view: &View) -> Self {
+        let mut solver = Solver::new();
+        solver.solve(view);
+        solver.knowledge
+    }
+
+    /// Only what single numbers prove: a number with all its mines known
+    /// has only safe cells left, and one with as many unknown cells as
+    /// missing mines has only mines. Cheap enough to run after every
+    /// move, and finds most of what `of` does on an ordinary board.
+    pub fn single_point(view: &View) -> Self {
+        let mut solver = Solver::new();
+        solver.single_point(view);
+        solver.knowledge
+    }
+
+    fn is_known(&self, pos: &(usize, usize)) -> bool {
+        self.safe.contains(pos) || self.mines.contains(pos)
+    }
+
+    /// The numbers whose constraints `(row, col)` is in, by position. A
+    /// proven cell is in none, as the constraints only hold unknown cells.
+    pub fn constraints_on(&self, view: &View, (row, col): (usize, usize)) -> Vec<Membership> {
+        let constraints = self.constraints(view);
+        let mut memberships: Vec<Membership> = view
+            .neighbors(row, col)
+            .into_iter()
+            .filter_map(|number| {
+                let constraint = constraints.get(number)?;
+                constraints
+                    .cells(constraint)
+                    .contains(&(row, col))
+                    .then_some(Membership {
+                        number,
+                        mines: constraint.mines,
+                        cells: constraint.len(),
+                    })
+            })
+            .collect();
+        memberships.sort_by_key(|membership| membership.number);
+        memberships
+    }
+
+    /// The constraint of every revealed number with unknown neighbours.
+    fn constraints(&self, view: &View) -> Constraints {
+        let mut constraints = Constraints::default();
+        constraints.build(view, &Known::of(view, self));
+        constraints
+    }
+
+    /// The chance that a hidden cell is a mine: the worst of the numbers
+    /// touching it, or the density of the unknown cells away from them.
+    pub fn risk(&self, view: &View, (row, col): (usize, usize)) -> f64 {
+        if self.mines.contains(&(row, col)) {
+            return 1.0;
+        }
+        if self.safe.contains(&(row, col)) {
+            return 0.0;
+        }
+        local_risk(view, &self.constraints(view), (row, col)).unwrap_or_else(|| {
+            let unknown = view
+                .hidden_or_flagged()
+                .into_iter()
+                .filter(|pos| !self.is_known(pos))
+                .count();
+            let left = view.mines_unseen().saturating_sub(self.mines.len());
+            left as f64 / unknown.max(1) as f64
+        })
+    }
+}
+
+/// The worst chance the numbers touching `(row, col)` give it, if any do.
+fn local_risk(view: &View, constraints: &Constraints, (row, col): (usize, usize)) -> Option<f64> {
+    view.around(row, col)
+        .filter_map(|pos| constraints.get(pos))
+        .map(|constraint| constraint.mines as f64 / constraint.len() as f64)
+        .fold(None, |worst: Option<f64>, risk| {
+            Some(worst.map_or(risk, |worst| worst.max(risk)))
+        })
+}
+
+/// Proves what `Knowledge` does, keeping its buffers from one view to the
+/// next. Once they have grown to the board, solving again allocates next
+/// to nothing, so calling it after every move costs the same on the last
+/// move of a large board as on the first.
+#[derive(Clone, Debug, Default)]
+pub struct Solver {
+    knowledge: Knowledge,
+    known: Known,
+    /// Numbers that might still prove something, as `(row, col, n)`.
+    numbers: Vec<(usize, usize, usize)>,
+    /// The unknown neighbours of the number at hand.
+    unknown: Vec<(usize, usize)>,
+    constraints: Constraints,
+    /// The cells of one number that another doesn't touch.
+    only: Vec<(usize, usize)>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What `Knowledge::single_point` proves about `view`.
+    pub fn single_point(&mut self, view: &View) -> &Knowledge {
+        self.known.reset(view);
+        if models(view) {
+            self.points(view);
+        }
+        self.publish()
+    }
+
+    /// What `Knowledge::of` proves about `view`.
+    pub fn solve(&mut self, view: &View) -> &Knowledge {
+        self.known.reset(view);
+        if !models(view) {
+            return self.publish();
+        }
+        self.points(view);
+        loop {
+            self.constraints.build(view, &self.known);
+            if reduce(&mut self.known, &self.constraints, &mut self.only) {
+                continue;
+            }
+            if !enumerate(&mut self.known, view, &self.constraints) {
+                break;
+            }
+        }
+        self.publish()
+    }
+
+    /// What single numbers and pairs of them prove, without enumerating
+    /// any component. `constraints` is left as the proven cells leave it.
+    fn pairs(&mut self, view: &View) -> &Knowledge {
+        self.known.reset(view);
+        self.points(view);
+        loop {
+            self.constraints.build(view, &self.known);
+            if !reduce(&mut self.known, &self.constraints, &mut self.only) {
+                break;
+            }
+        }
+        self.publish()
+    }
+
+    /// Settles single numbers until none of them proves anything more.
+    fn points(&mut self, view: &View) {
+        let Self {
+            known,
+            numbers,
+            unknown,
+            ..
+        } = self;
+        numbers.clear();
+        numbers.extend(
+            (0..view.height)
+                .flat_map(|row| (0..view.width).map(move |col| (row, col)))
+                .filter_map(|(row, col)| match view.get(row, col) {
+                    CellView::Number(n) => Some((row, col, n)),
+                    _ => None,
+                }),
+        );
+        loop {
+            let mut progress = false;
+            // Numbers with nothing left to say are dropped as they go
+            numbers.retain(|&(row, col, n)| {
+                let mut found = 0;
+                unknown.clear();
+                for pos in view.around(row, col) {
+                    if view.shows_mine(pos.0, pos.1) {
+                        found += 1;
+                        continue;
+                    }
+                    if !matches!(view.get(pos.0, pos.1), CellView::Hidden | CellView::Flagged) {
+                        continue;
+                    }
+                    match known.get(pos) {
+                        Some(true) => found += 1,
+                        Some(false) => {}
+                        None => unknown.push(pos),
+                    }
+                }
+                let mine = if found == n {
+                    false
+                } else if found + unknown.len() == n {
+                    true
+                } else {
+                    return !unknown.is_empty();
+                };
+                for &pos in unknown.iter() {
+                    known.learn(pos, mine);
+                }
+                progress |= !unknown.is_empty();
+                false
+            });
+            if !progress {
+                break;
+            }
+        }
+    }
+
+    /// Turns what is known into `Knowledge`, reusing its sets.
+    fn publish(&mut self) -> &Knowledge {
+        let knowledge = &mut self.knowledge;
+        knowledge.safe.clear();
+        knowledge.mines.clear();
+        for (i, &known) in self.known.cells.iter().enumerate() {
+            let pos = (i / self.known.width, i % self.known.width);
+            match known {
+                Some(true) => knowledge.mines.insert(pos),
+                Some(false) => knowledge.safe.insert(pos),
+                None => false,
+            };
+        }
+        knowledge
+    }
+}
+
+/// Whether the solver can reason about `view` at all, which it can't once
+/// the rules stack mines.
+fn models(view: &View) -> bool {
+    view.rules.mines_per_cell <= 1
+}
+
+/// Settles single numbers, then pairs of numbers close enough to share
+/// cells. Returns whether anything new was learned.
+fn reduce(known: &mut Known, constraints: &Constraints, only: &mut Vec<(usize, usize)>) -> bool {
+    let mut progress = false;
+    for constraint in &constraints.list {
+        let cells = constraints.cells(constraint);
+        progress |= settle(known, cells, constraint.mines, constraint.mines);
+    }
+    for a in &constraints.list {
+        let (row, col) = a.number;
+        for r in row.saturating_sub(2)..=row + 2 {
+            for c in col.saturating_sub(2)..=col + 2 {
+                match constraints.get((r, c)) {
+                    Some(b) if (r, c) != (row, col) => {
+                        progress |= couple(known, constraints, a, b, only);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    progress
+}
+
+/// What `b` says about the cells of `a` it doesn't touch. The shared
+/// cells hold at least what either number can't fit elsewhere and at
+/// most what both allow, which bounds the mines in the rest of `a`.
+fn couple(
+    known: &mut Known,
+    constraints: &Constraints,
+    a: &Constraint,
+    b: &Constraint,
+    only: &mut Vec<(usize, usize)>,
+) -> bool {
+    // Both lists are in reading order, so one pass splits them
+    let theirs = constraints.cells(b);
+    let mut next = 0;
+    let mut common = 0;
+    only.clear();
+    for &pos in constraints.cells(a) {
+        while theirs.get(next).is_some_and(|&other| other < pos) {
+            next += 1;
+        }
+        if theirs.get(next) == Some(&pos) {
+            common += 1;
+        } else {
+            only.push(pos);
+        }
+    }
+    if common == 0 {
+        return false;
+    }
+    let only_b = b.len() - common;
+    let least = a
+        .mines
+        .saturating_sub(only.len())
+        .max(b.mines.saturating_sub(only_b));
+    let most = common.min(a.mines).min(b.mines);
+    settle(
+        known,
+        only,
+        a.mines.saturating_sub(most),
+        a.mines.saturating_sub(least),
+    )
+}
+
+/// Marks `cells` safe when at most none of them can be mines, and
+/// mines when at least all of them must be. Returns whether anything
+/// new was learned.
+fn settle(known: &mut Known, cells: &[(usize, usize)], least: usize, most: usize) -> bool {
+    let mine = if most == 0 {
+        false
+    } else if least == cells.len() {
+        true
+    } else {
+        return false;
+    };
+    let mut progress = false;
+    for &pos in cells {
+        progress |= known.learn(pos, mine);
+    }
+    progress
+}
+
+/// Enumerates every component small enough and learns the cells that
+/// are the same in every arrangement whose mines leave a number the
+/// other components and the cells away from them can hold. The cells
+/// away are learned the same way: they are all safe if they can only
+/// hold none of the mines left, and all mines if they must hold one
+/// each. Returns whether anything new was learned.
+fn enumerate(known: &mut Known, view: &View, constraints: &Constraints) -> bool {
+    let never = AtomicBool::new(false);
+    let components = components(constraints);
+    let tallies: Vec<Option<Tally>> = components
+        .iter()
+        .map(|component| (component.cells.len() <= MAX_COMPONENT).then(|| component.tally(&never)))
+        .collect();
+    let frontier: HashSet<_> = components
+        .iter()
+        .flat_map(|component| component.cells.iter().copied())
+        .collect();
+    let away: Vec<_> = view
+        .hidden_or_flagged()
+        .into_iter()
+        .filter(|&pos| known.get(pos).is_none() && !frontier.contains(&pos))
+        .collect();
+    let left = view.mines_unseen().saturating_sub(known.mines);
+
+    // The numbers of mines each part can hold on its own, the cells away
+    // last; a component too large to enumerate might hold any
+    let mut holds: Vec<Vec<bool>> = components
+        .iter()
+        .zip(&tallies)
+        .map(|(component, tally)| match tally {
+            Some(tally) => tally.arrangements.iter().map(|&n| n > 0).collect(),
+            None => vec![true; component.cells.len() + 1],
+        })
+        .collect();
+    holds.push(vec![true; away.len() + 1]);
+    // ...and those it can hold while the others hold the rest. None at
+    // all means the numbers contradict each other or the mine count,
+    // and then nothing can be proven
+    let counts = |part: usize| -> Vec<usize> {
+        let others = reachable(
+            holds
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != part)
+                .map(|(_, holds)| holds),
+            left,
+        );
+        (0..holds[part].len().min(left + 1))
+            .filter(|&k| holds[part][k] && others.get(left - k) == Some(&true))
+            .collect()
+    };
+
+    let mut progress = false;
+    for (part, (component, tally)) in components.iter().zip(&tallies).enumerate() {
+        let Some(tally) = tally else {
+            continue;
+        };
+        let counts = counts(part);
+        if counts.is_empty() {
+            continue;
+        }
+        for (i, &pos) in component.cells.iter().enumerate() {
+            if counts.iter().all(|&k| tally.mines[k][i] == 0) {
+                progress |= known.learn(pos, false);
+            } else if counts
+                .iter()
+                .all(|&k| tally.mines[k][i] == tally.arrangements[k])
+            {
+                progress |= known.learn(pos, true);
+            }
+        }
+    }
+    let counts = counts(components.len());
+    let mine = if counts == [0] {
+        false
+    } else if counts == [away.len()] {
+        true
+    } else {
+        return progress;
+    };
+    for pos in away {
+        progress |= known.learn(pos, mine);
+    }
+    progress
+}
+
+/// A revealed number's say over a cell: `mines` of the `cells` unknown
+/// cells around it are mines, leaving out the ones already proven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Membership {
+    pub number: (usize, usize),
+    pub mines: usize,
+    pub cells: usize,
+}
+
+/// The chance that a hidden cell is a mine.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Chance {
+    pub mine: f64,
+    /// Worked out over every arrangement the numbers and the mine count
+    /// allow, rather than estimated.
+    pub exact: bool,
+    /// For a chance estimated by sampling, half the width of its 95%
+    /// confidence band. Other estimates have none.
+    pub margin: Option<f64>,
+}
+
+impl Chance {
+    fn exact(mine: f64) -> Self {
+        Self {
+            mine,
+            exact: true,
+            margin: None,
+        }
+    }
+
+    fn rough(mine: f64) -> Self {
+        Self {
+            mine,
+            exact: false,
+            margin: None,
+        }
+    }
+}
+
+/// The chance of a mine under every hidden cell, as the overlay and the
+/// review show it.
+#[derive(Clone, Debug)]
+pub struct Probabilities {
+    width: usize,
+    chances: Vec<Option<Chance>>,
+}
+
+impl Probabilities {
+    /// Every arrangement of the remaining mines that fits the numbers is
+    /// taken as equally likely. Components are enumerated on their own and
+    /// combined by how many mines each holds, each combination weighed by
+    /// the ways to place the rest away from the numbers. Components too
+    /// large to enumerate are sampled instead, `DEFAULT_SAMPLES` times,
+    /// and count as away from the numbers for the others, whose chances
+    /// are then no longer exact.
+    pub fn of(view: &View) -> Self {
+        Self::with_budget(view, DEFAULT_SAMPLES)
+    }
+
+    /// Like `of`, drawing `samples` samples from each component too large
+    /// to enumerate. The samples are seeded, so a view always gets the
+    /// same chances.
+    pub fn with_budget(view: &View, samples: usize) -> Self {
+        Self::analyse(view, samples, 1, &AtomicBool::new(false), &|_| {})
+            .expect("nothing cancels the analysis")
+    }
+
+    /// `with_budget` with the components spread over `threads` threads,
+    /// which gives the same chances whatever the count. `report` gets the
+    /// chances so far whenever a component is done: rough ones from the
+    /// numbers alone at first, each component's own once it is counted.
+    /// `None` once `cancel` is set, which stops the work soon after.
+    fn analyse(
+        view: &View,
+        samples: usize,
+        threads: usize,
+        cancel: &AtomicBool,
+        report: &(dyn Fn(&Self) + Sync),
+    ) -> Option<Self> {
+        if !models(view) {
+            return Some(Self {
+                width: view.width,
+                chances: vec![None; view.width * view.height],
+            });
+        }
+        // Single numbers and pairs are cheap; what enumeration would prove
+        // comes out of the tallies below as chances of exactly 0 or 1
+        let mut solver = Solver::new();
+        let knowledge = solver.pairs(view).clone();
+        let constraints = &solver.constraints;
+        let mut chances = vec![None; view.width * view.height];
+        for (proven, mine) in [(&knowledge.safe, 0.0), (&knowledge.mines, 1.0)] {
+            for &(row, col) in proven {
+                chances[row * view.width + col] = Some(Chance::exact(mine));
+            }
+        }
+
+        let (exact, large): (Vec<_>, Vec<_>) = components(constraints)
+            .into_iter()
+            .partition(|component| component.cells.len() <= MAX_COMPONENT);
+        let frontier: usize = exact.iter().map(|component| component.cells.len()).sum();
+        let unknown: Vec<_> = view
+            .hidden_or_flagged()
+            .into_iter()
+            .filter(|pos| !knowledge.is_known(pos))
+            .collect();
+        let away = unknown.len() - frontier;
+        let left = view.mines_unseen().saturating_sub(knowledge.mines.len());
+
+        // What `Knowledge::risk` says, until the components are counted
+        let density = left as f64 / unknown.len().max(1) as f64;
+        let mut rough = chances.clone();
+        for &(row, col) in &unknown {
+            let risk = local_risk(view, constraints, (row, col)).unwrap_or(density);
+            rough[row * view.width + col] = Some(Chance::rough(risk));
+        }
+        let partial = Mutex::new(Self {
+            width: view.width,
+            chances: rough,
+        });
+        report(&partial.lock().unwrap());
+        let mut set = |(row, col): (usize, usize), chance: Chance| {
+            chances[row * view.width + col] = Some(chance);
+        };
+
+        let tallies: Mutex<Vec<Option<Tally>>> =
+            Mutex::new((0..exact.len()).map(|_| None).collect());
+        spread(threads, exact.len(), cancel, |c| {
+            let component = &exact[c];
+            let tally = component.tally(cancel);
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            // Until the others are counted, the rest of the board is taken
+            // to hold the mines this component doesn't
+            let size = component.cells.len();
+            let rest = ways(unknown.len() - size, left, size + 1);
+            let total: f64 = (0..=size)
+                .map(|k| tally.arrangements[k] as f64 * rest[k])
+                .sum();
+            if total > 0.0 {
+                let mut partial = partial.lock().unwrap();
+                for (i, &(row, col)) in component.cells.iter().enumerate() {
+                    let weight: f64 = (0..=size).map(|k| tally.mines[k][i] as f64 * rest[k]).sum();
+                    partial.chances[row * view.width + col] = Some(Chance::rough(weight / total));
+                }
+                report(&partial);
+            }
+            tallies.lock().unwrap()[c] = Some(tally);
+        });
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let tallies: Vec<Tally> = tallies
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|tally| tally.expect("every component is counted"))
+            .collect();
+
+        // Each component's counts are scaled to at most 1, which leaves the
+        // chances as they are and keeps the products in range
+        let scales: Vec<f64> = tallies
+            .iter()
+            .map(|tally| tally.arrangements.iter().copied().max().unwrap_or(0).max(1) as f64)
+            .collect();
+        let weights: Vec<Vec<f64>> = tallies
+            .iter()
+            .zip(&scales)
+            .map(|(tally, scale)| {
+                tally
+                    .arrangements
+                    .iter()
+                    .map(|&n| n as f64 / scale)
+                    .collect()
+            })
+            .collect();
+        let rest = ways(away, left, frontier + 1);
+        let all = convolve(weights.iter());
+        let total: f64 = all.iter().zip(&rest).map(|(a, b)| a * b).sum();
+
+        let settled = |mine| {
+            if large.is_empty() {
+                Chance::exact(mine)
+            } else {
+                Chance::rough(mine)
+            }
+        };
+        let sampled: HashSet<_> = large
+            .iter()
+            .flat_map(|component| &component.cells)
+            .collect();
+        if total <= 0.0 {
+            // The numbers contradict each other or the mine count
+            for &pos in &unknown {
+                set(pos, Chance::rough(knowledge.risk(view, pos)));
+            }
+            return Some(Self {
+                width: view.width,
+                chances,
+            });
+        }
+        for (c, (component, tally)) in exact.iter().zip(&tallies).enumerate() {
+            let others = convolve(
+                weights
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != c)
+                    .map(|(_, weights)| weights),
+            );
+            // The weight of this component holding `k` mines, over all the
+            // ways the others and the rest of the board can go
+            let outside: Vec<f64> = (0..tally.arrangements.len())
+                .map(|k| {
+                    others
+                        .iter()
+                        .enumerate()
+                        .map(|(j, w)| w * rest[k + j])
+                        .sum()
+                })
+                .collect();
+            let possible: Vec<usize> = (0..outside.len())
+                .filter(|&k| outside[k] > 0.0 && tally.arrangements[k] > 0)
+                .collect();
+            for (i, &pos) in component.cells.iter().enumerate() {
+                // A cell that is the same in every arrangement that can
+                // happen is proven, and is given exactly that
+                let mine = if possible.iter().all(|&k| tally.mines[k][i] == 0) {
+                    0.0
+                } else if possible
+                    .iter()
+                    .all(|&k| tally.mines[k][i] == tally.arrangements[k])
+                {
+                    1.0
+                } else {
+                    (0..outside.len())
+                        .map(|k| tally.mines[k][i] as f64 / scales[c] * outside[k])
+                        .sum::<f64>()
+                        / total
+                };
+                set(pos, settled(mine));
+            }
+        }
+        // Mines the sampled components are expected to hold, which the
+        // cells away from every number then do not. Each is seeded on its
+        // own, so the threads can take them in any order
+        let estimates = Mutex::new(vec![None; large.len()]);
+        spread(threads, large.len(), cancel, |c| {
+            let component = &large[c];
+            let size = component.cells.len();
+            let rest = ways(away - size, left, frontier + size + 1);
+            let outside: Vec<f64> = (0..=size)
+                .map(|k| all.iter().enumerate().map(|(j, w)| w * rest[k + j]).sum())
+                .collect();
+            let mut rng = StdRng::seed_from_u64(c as u64);
+            let found = component.sample(&outside, samples, &mut rng, cancel);
+            if let Some(found) = &found {
+                let mut partial = partial.lock().unwrap();
+                for (&(row, col), &(mine, margin)) in component.cells.iter().zip(found) {
+                    partial.chances[row * view.width + col] = Some(Chance {
+                        mine,
+                        exact: false,
+                        margin: Some(margin),
+                    });
+                }
+                report(&partial);
+            }
+            estimates.lock().unwrap()[c] = found;
+        });
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut held = 0.0;
+        for (component, found) in large.iter().zip(estimates.into_inner().unwrap()) {
+            match found {
+                Some(estimates) => {
+                    for (&pos, (mine, margin)) in component.cells.iter().zip(estimates) {
+                        held += mine;
+                        set(
+                            pos,
+                            Chance {
+                                mine,
+                                exact: false,
+                                margin: Some(margin),
+                            },
+                        );
+                    }
+                }
+                None => {
+                    for &pos in &component.cells {
+                        let mine = knowledge.risk(view, pos);
+                        held += mine;
+                        set(pos, Chance::rough(mine));
+                    }
+                }
+            }
+        }
+        let possible: Vec<usize> = (0..all.len()).filter(|&k| all[k] * rest[k] > 0.0).collect();
+        let expected = if possible.iter().all(|&k| k == left) {
+            0.0
+        } else if large.is_empty() && possible.iter().all(|&k| left.checked_sub(k) == Some(away)) {
+            1.0
+        } else {
+            let mines_away = (0..all.len())
+                .map(|k| all[k] * rest[k] * left.saturating_sub(k) as f64)
+                .sum::<f64>()
+                / total;
+            ((mines_away - held).max(0.0) / (away - sampled.len()).max(1) as f64).min(1.0)
+        };
+        let on_frontier: HashSet<_> = exact
+            .iter()
+            .flat_map(|component| &component.cells)
+            .collect();
+        for &pos in &unknown {
+            if !on_frontier.contains(&pos) && !sampled.contains(&pos) {
+                set(pos, settled(expected));
+            }
+        }
+        Some(Self {
+            width: view.width,
+            chances,
+        })
+    }
+
+    /// `None` for revealed cells.
+    pub fn get(&self, row: usize, col: usize) -> Option<Chance> {
+        self.chances[row * self.width + col]
+    }
+}
+
+/// `Probabilities` worked out on threads of their own, so the window keeps
+/// drawing while a large frontier is counted. The chances so far can be
+/// had at any time; see `Probabilities::analyse` for how they fill in.
+/// Dropping it stops the work.
+pub struct Analysis {
+    cancel: Arc<AtomicBool>,
+    /// The newest chances not yet taken by `poll`.
+    latest: Arc<Mutex<Option<Probabilities>>>,
+    done: Arc<AtomicBool>,
+}
+
+impl Analysis {
+    /// Starts on `view` with `samples` as in `Probabilities::with_budget`,
+    /// on as many threads as the machine has cores.
+    pub fn start(view: View, samples: usize) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(None));
+        let done = Arc::new(AtomicBool::new(false));
+        let (stop, slot, finished) = (Arc::clone(&cancel), Arc::clone(&latest), Arc::clone(&done));
+        thread::spawn(move || {
+            let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+            let report = |chances: &Probabilities| {
+                *slot.lock().unwrap() = Some(chances.clone());
+            };
+            if let Some(chances) = Probabilities::analyse(&view, samples, threads, &stop, &report) {
+                *slot.lock().unwrap() = Some(chances);
+                finished.store(true, Ordering::Release);
+            }
+        });
+        Self {
+            cancel,
+            latest,
+            done,
+        }
+    }
+
+    /// The chances as they stand, if they changed since the last call.
+    pub fn poll(&self) -> Option<Probabilities> {
+        self.latest.lock().unwrap().take()
+    }
+
+    /// Whether the final chances are in, whether or not `poll` took them.
+    pub fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs `work` on every index below `count`, on up to `threads` threads
+/// that each take the next index no one has taken yet, until `cancel` is
+/// set. A single thread is the caller's own.
+fn spread(threads: usize, count: usize, cancel: &AtomicBool, work: impl Fn(usize) + Sync) {
+    let next = AtomicUsize::new(0);
+    let worker = || loop {
+        let i = next.fetch_add(1, Ordering::Relaxed);
+        if i >= count || cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        work(i);
+    };
+    if threads <= 1 || count <= 1 {
+        worker();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..threads.min(count) {
+                scope.spawn(worker);
+            }
+        });
+    }
+}
+
+/// The number of ways to place `left - k` mines on `cells` cells, for each
+/// `k` below `len`, relative to the largest of them. They can be far too
+/// large for a float, so they are worked out as logarithms.
+fn ways(cells: usize, left: usize, len: usize) -> Vec<f64> {
+    let mut logs = vec![f64::NEG_INFINITY; len];
+    // C(n, m - 1) = C(n, m) * m / (n - m + 1), from the most mines that fit
+    let first = left.saturating_sub(cells);
+    let mut log = 0.0;
+    for (k, slot) in logs.iter_mut().enumerate().take(left + 1).skip(first) {
+        if k > first {
+            let mines = left - k;
+            log += ((mines + 1) as f64).ln() - ((cells - mines) as f64).ln();
+        }
+        *slot = log;
+    }
+    let max = logs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return vec![0.0; len];
+    }
+    logs.into_iter().map(|log| (log - max).exp()).collect()
+}
+
+/// The weights of each total number of mines over several components,
+/// given each one's weights by its own number of mines.
+fn convolve<'a>(components: impl Iterator<Item = &'a Vec<f64>>) -> Vec<f64> {
+    let mut total = vec![1.0];
+    for weights in components {
+        let mut next = vec![0.0; total.len() + weights.len() - 1];
+        for (i, a) in total.iter().enumerate() {
+            for (j, b) in weights.iter().enumerate() {
+                next[i + j] += a * b;
+            }
+        }
+        total = next;
+    }
+    total
+}
+
+/// The totals up to `most` that several parts can add up to, given which
+/// numbers each part can hold on its own.
+fn reachable<'a>(parts: impl Iterator<Item = &'a Vec<bool>>, most: usize) -> Vec<bool> {
+    let mut total = vec![true];
+    for holds in parts {
+        let mut next = vec![false; (total.len() + holds.len() - 1).min(most + 1)];
+        for i in (0..total.len()).filter(|&i| total[i]) {
+            for j in (0..holds.len()).filter(|&j| holds[j]) {
+                if let Some(slot) = next.get_mut(i + j) {
+                    *slot = true;
+                }
+            }
+        }
+        total = next;
+    }
+    total
+}
+
+/// Cells tied together by numbers, with those numbers over the cells'
+/// indices in `cells`.
+struct Component {
+    cells: Vec<(usize, usize)>,
+    constraints: Vec<(Vec<usize>, usize)>,
+}
+
+/// How many arrangements of a component fit its numbers, and in how many
+/// of them each cell is a mine, by the number of mines they hold.
+struct Tally {
+    /// Arrangements with `k` mines at index `k`.
+    arrangements: Vec<u64>,
+    /// For each number of mines, how often each cell is one of them.
+    mines: Vec<Vec<u64>>,
+}
+
+/// Splits the cells under `constraints` into components. Cells are listed
+/// in the order a breadth-first walk through the numbers reaches them, so
+/// the enumeration completes numbers early and prunes soon. The numbers are
+/// taken in reading order, so the same view always splits the same way.
+fn components(constraints: &Constraints) -> Vec<Component> {
+    let list = &constraints.list;
+    let mut owners: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, constraint) in list.iter().enumerate() {
+        for &pos in constraints.cells(constraint) {
+            owners.entry(pos).or_default().push(i);
+        }
+    }
+
+    let mut seen = vec![false; list.len()];
+    let mut queue = VecDeque::new();
+    let mut components = Vec::new();
+    for first in 0..list.len() {
+        if seen[first] {
+            continue;
+        }
+        seen[first] = true;
+        queue.push_back(first);
+        let mut index: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut cells = Vec::new();
+        let mut members = Vec::new();
+        while let Some(i) = queue.pop_front() {
+            members.push(i);
+            for &pos in constraints.cells(&list[i]) {
+                if index.contains_key(&pos) {
+                    continue;
+                }
+                index.insert(pos, cells.len());
+                cells.push(pos);
+                for &j in &owners[&pos] {
+                    if !seen[j] {
+                        seen[j] = true;
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        let tied = members
+            .into_iter()
+            .map(|i| {
+                let cells = constraints.cells(&list[i]);
+                (cells.iter().map(|pos| index[pos]).collect(), list[i].mines)
+            })
+            .collect();
+        components.push(Component {
+            cells,
+            constraints: tied,
+        });
+    }
+    components
+}
+
+impl Component {
+    /// Counts every arrangement that fits. Cut short, and then wrong, if
+    /// `cancel` is set meanwhile.
+    fn tally(&self, cancel: &AtomicBool) -> Tally {
+        let size = self.cells.len();
+        let mut search = self.search();
+        search.tally = Tally {
+            arrangements: vec![0; size + 1],
+            mines: vec![vec![0; size]; size + 1],
+        };
+        search.run(0, cancel);
+        search.tally
+    }
+
+    /// Estimates each cell's chance of a mine, with its 95% margin, from
+    /// `samples` arrangements drawn by block Gibbs sampling. It starts from
+    /// any arrangement that fits, then again and again picks a cell and
+    /// redraws everything within two cells of it among the ways that fit
+    /// the numbers around, each weighed by `outside` for the mines the
+    /// whole arrangement then holds. The draws are correlated, so the
+    /// margins come from the spread between batches of them. `None` if no
+    /// arrangement that fits turns up quickly, or once `cancel` is set.
+    fn sample(
+        &self,
+        outside: &[f64],
+        samples: usize,
+        rng: &mut StdRng,
+        cancel: &AtomicBool,
+    ) -> Option<Vec<(f64, f64)>> {
+        let mut search = self.search();
+        let mut budget = SEARCH_BUDGET;
+        if !search.first(0, &mut budget) {
+            return None;
+        }
+        let index: HashMap<(usize, usize), usize> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| (pos, i))
+            .collect();
+        let blocks: Vec<Vec<usize>> = self
+            .cells
+            .iter()
+            .map(|&(row, col)| {
+                (row.saturating_sub(2)..=row + 2)
+                    .flat_map(|r| (col.saturating_sub(2)..=col + 2).map(move |c| (r, c)))
+                    .filter_map(|pos| index.get(&pos).copied())
+                    .collect()
+            })
+            .collect();
+        let step = |search: &mut Search, rng: &mut StdRng| {
+            let block = &blocks[rng.gen_range(0..blocks.len())];
+            for &cell in block {
+                search.unassign(cell);
+            }
+            let mut found = Vec::new();
+            search.complete(block, 0, &mut found);
+            let weights: Vec<f64> = found
+                .iter()
+                .map(|(_, placed)| outside.get(*placed).copied().unwrap_or(0.0))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            let chosen = if total > 0.0 {
+                let mut target = rng.gen::<f64>() * total;
+                weights
+                    .iter()
+                    .position(|&weight| {
+                        target -= weight;
+                        target < 0.0
+                    })
+                    .unwrap_or(found.len() - 1)
+            } else {
+                rng.gen_range(0..found.len())
+            };
+            for (&cell, &mine) in block.iter().zip(&found[chosen].0) {
+                search.assign(cell, mine);
+            }
+        };
+
+        // About one sweep of the component between samples
+        let sweep = self.cells.len() / 8 + 1;
+        for _ in 0..sweep * (samples / 10 + 1) {
+            step(&mut search, rng);
+        }
+        let batches = samples.clamp(2, 20);
+        let size = (samples / batches).max(1);
+        let mut means = vec![vec![0.0; self.cells.len()]; batches];
+        for batch in &mut means {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            for _ in 0..size {
+                for _ in 0..sweep {
+                    step(&mut search, rng);
+                }
+                for (sum, &mine) in batch.iter_mut().zip(&search.assignment) {
+                    *sum += f64::from(u8::from(mine)) / size as f64;
+                }
+            }
+        }
+        Some(
+            (0..self.cells.len())
+                .map(|i| {
+                    let chance = means.iter().map(|batch| batch[i]).sum::<f64>() / batches as f64;
+                    let spread = means
+                        .iter()
+                        .map(|batch| (batch[i] - chance).powi(2))
+                        .sum::<f64>()
+                        / (batches - 1) as f64;
+                    // Never narrower than independent draws would give
+                    let least = chance * (1.0 - chance) / (batches * size) as f64;
+                    (chance, 1.96 * (spread / batches as f64).max(least).sqrt())
+                })
+                .collect(),
+        )
+    }
+
+    fn search(&self) -> Search {
+        let mut owners = vec![Vec::new(); self.cells.len()];
+        for (k, (cells, _)) in self.constraints.iter().enumerate() {
+            for &i in cells {
+                owners[i].push(k);
+            }
+        }
+        Search {
+            owners,
+            need: self.constraints.iter().map(|(_, mines)| *mines).collect(),
+            open: self
+                .constraints
+                .iter()
+                .map(|(cells, _)| cells.len())
+                .collect(),
+            assignment: vec![false; self.cells.len()],
+            placed: 0,
+            tally: Tally {
+                arrangements: Vec::new(),
+                mines: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Backtracking over one component, cell by cell.
+struct Search {
+    /// The constraints each cell is under.
+    owners: Vec<Vec<usize>>,
+    /// Mines each constraint still needs among its open cells.
+    need: Vec<usize>,
+    /// Cells of each constraint not assigned yet.
+    open: Vec<usize>,
+    assignment: Vec<bool>,
+    /// Mines in the assignment so far.
+    placed: usize,
+    tally: Tally,
+}
+
+impl Search {
+    fn run(&mut self, next: usize, cancel: &AtomicBool) {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        if next == self.assignment.len() {
+            self.tally.arrangements[self.placed] += 1;
+            let mines = &mut self.tally.mines[self.placed];
+            for (count, &mine) in mines.iter_mut().zip(&self.assignment) {
+                *count += u64::from(mine);
+            }
+            return;
+        }
+        for mine in [false, true] {
+            if self.fits(next, mine) {
+                self.assign(next, mine);
+                self.run(next + 1, cancel);
+                self.unassign(next);
+            }
+        }
+    }
+
+    /// Whether every constraint on `cell` can still be met with it set so.
+    fn fits(&self, cell: usize, mine: bool) -> bool {
+        self.owners[cell].iter().all(|&k| {
+            if mine {
+                self.need[k] > 0
+            } else {
+                self.need[k] < self.open[k]
+            }
+        })
+    }
+
+    fn assign(&mut self, cell: usize, mine: bool) {
+        for &k in &self.owners[cell] {
+            self.open[k] -= 1;
+            self.need[k] -= usize::from(mine);
+        }
+        self.assignment[cell] = mine;
+        self.placed += usize::from(mine);
+    }
+
+    fn unassign(&mut self, cell: usize) {
+        let mine = self.assignment[cell];
+        for &k in &self.owners[cell] {
+            self.open[k] += 1;
+            self.need[k] += usize::from(mine);
+        }
+        self.placed -= usize::from(mine);
+    }
+
+    /// Looks for an arrangement that fits, in at most `budget` steps, and
+    /// leaves it in place if there is one.
+    fn first(&mut self, next: usize, budget: &mut usize) -> bool {
+        if next == self.assignment.len() {
+            return true;
+        }
+        for mine in [false, true] {
+            if *budget == 0 {
+                return false;
+            }
+            *budget -= 1;
+            if self.fits(next, mine) {
+                self.assign(next, mine);
+                if self.first(next + 1, budget) {
+                    return true;
+                }
+                self.unassign(next);
+            }
+        }
+        false
+    }
+
+    /// Every way to set the unassigned cells of `block` from `at` on that
+    /// fits, with the mines the whole assignment then holds.
+    fn complete(&mut self, block: &[usize], at: usize, found: &mut Vec<(Vec<bool>, usize)>) {
+        let Some(&cell) = block.get(at) else {
+            let values = block.iter().map(|&cell| self.assignment[cell]).collect();
+            found.push((values, self.placed));
+            return;
+        };
+        for mine in [false, true] {
+            if self.fits(cell, mine) {
+                self.assign(cell, mine);
+                self.complete(block, at + 1, found);
+                self.unassign(cell);
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Sound effects synthesised as WAV files, so the game needs no audio
+//! assets. Frontends hand the file to whatever plays sound on the system.
+
+/// Samples per second.
+pub const RATE: u32 = 22050;
+
+/// A short rising arpeggio for a win: C, E and G, then the octave held.
+pub fn fanfare() -> Vec<u8> {
+    const NOTES: [(f32, f32); 4] = [
+        (523.25, 0.12),
+        (659.25, 0.12),
+        (783.99, 0.12),
+        (1046.5, 0.5),
+    ];
+    let mut samples = Vec::new();
+    for (frequency, seconds) in NOTES {
+        let count = (seconds * RATE as f32) as usize;
+        samples.extend((0..count).map(|i| {
+            let t = i as f32 / RATE as f32;
+            // A quick attack and a linear release keep the notes from
+            // clicking where they meet
+            let envelope = (t / 0.01).min(1.0) * (1.0 - i as f32 / count as f32);
+            let wave = (t * frequency * std::f32::consts::TAU).sin();
+            (wave * envelope * 0.4 * f32::from(i16::MAX)) as i16
+        }));
+    }
+    wav(&samples)
+}
+
+/// A mono 16-bit PCM WAV file holding `samples` at `RATE`.
+pub fn wav(samples: &[i16]) -> Vec<u8> {
+    let data = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&RATE.to_le_bytes());
+    bytes.extend_from_slice(&(RATE * 2).to_le_bytes());
+    // Two bytes a frame, sixteen bits a sample
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
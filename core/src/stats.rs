@@ -0,0 +1,794 @@
+//! Finished games, kept for personal bests and the statistics screens,
+//! along with the achievements they unlocked.
+//!
+//! Games that used hints are recorded like any other but marked as
+//! assisted, and `Stats::best` leaves them out. Best times are kept per
+//! `Configuration`: board, variant and assistance together, so custom
+//! boards and variants get records of their own.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::achievement::{self, Achievement};
+use crate::placement::{self, Generator};
+use crate::save::{Document, Migration, SaveError, Saved};
+
+/// The rule variants a game was played under, as a set of flags. Games
+/// under different variants are different challenges, so each set keeps
+/// its own best times.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Variant(u32);
+
+impl Variant {
+    pub const CLASSIC: Variant = Variant(0);
+    /// A board generated to be solvable without guessing, with its first
+    /// opening revealed.
+    pub const NO_GUESS: Variant = Variant(1);
+    /// Mines placed by one of the non-uniform generators, named after it.
+    pub const CLUSTERED: Variant = Variant(2);
+    pub const DISPERSED: Variant = Variant(4);
+    pub const GRADIENT: Variant = Variant(8);
+    /// Played with the auto-chord assist, which chords numbers as soon as
+    /// their flags are placed.
+    pub const AUTO_CHORD: Variant = Variant(16);
+    /// The efficiency trainer, which scores a game on its clicks rather
+    /// than its time.
+    pub const TRAINER: Variant = Variant(32);
+    /// Played by rules other than the classic ones, which the share code
+    /// names; see `rules::Ruleset`.
+    pub const CUSTOM: Variant = Variant(64);
+    /// A mine moves to another unseen cell every few seconds; see
+    /// `drift::Drift`.
+    pub const MOVING: Variant = Variant(128);
+    pub const RAMP: Variant = Variant(256);
+
+    /// Every flag with its name in saves and on screen.
+    const NAMES: &'static [(Variant, &'static str)] = &[
+        (Variant::NO_GUESS, "no-guess"),
+        (Variant::CLUSTERED, "clustered"),
+        (Variant::DISPERSED, "dispersed"),
+        (Variant::GRADIENT, "gradient"),
+        (Variant::RAMP, "ramp"),
+        (Variant::AUTO_CHORD, "auto-chord"),
+        (Variant::TRAINER, "trainer"),
+        (Variant::CUSTOM, "custom"),
+        (Variant::MOVING, "moving"),
+    ];
+
+    pub fn contains(self, flags: Variant) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn with(self, flags: Variant) -> Self {
+        Variant(self.0 | flags.0)
+    }
+
+    /// Names of the flags set, none for the classic game.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .iter()
+            .filter(move |(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+    }
+
+    /// The flag for boards placed by the generator called `name`, none for
+    /// uniform ones.
+    pub fn placement(name: &str) -> Self {
+        Self::NAMES
+            .iter()
+            .find(|(_, known)| *known == name)
+            .map_or(Variant::CLASSIC, |(flag, _)| *flag)
+    }
+
+    /// The generator that placed this variant's mines.
+    pub fn generator(self) -> &'static dyn Generator {
+        self.names()
+            .find_map(placement::named)
+            .unwrap_or(&placement::Uniform)
+    }
+
+    /// `classic`, or the flags' names joined with `+`.
+    pub fn encode(self) -> String {
+        if self == Variant::CLASSIC {
+            return "classic".to_owned();
+        }
+        self.names().collect::<Vec<_>>().join("+")
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        if value == "classic" {
+            return Some(Variant::CLASSIC);
+        }
+        value
+            .split('+')
+            .try_fold(Variant::CLASSIC, |variant, name| {
+                let (flag, _) = Self::NAMES.iter().find(|(_, known)| *known == name)?;
+                Some(variant.with(*flag))
+            })
+    }
+}
+
+/// Everything that makes one game comparable with another: the board,
+/// the variant and whether it was assisted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Configuration {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub variant: Variant,
+    pub assisted: bool,
+}
+
+impl Configuration {
+    /// An unassisted game of the classic rules.
+    pub fn classic(width: usize, height: usize, mines: usize) -> Self {
+        Self {
+            width,
+            height,
+            mines,
+            variant: Variant::CLASSIC,
+            assisted: false,
+        }
+    }
+
+    /// Mines per cell, as a percentage.
+    pub fn density(&self) -> f64 {
+        self.mines as f64 * 100.0 / (self.width * self.height).max(1) as f64
+    }
+}
+
+/// One finished classic game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub won: bool,
+    /// Time from the first move to the last, hint penalties included.
+    pub time: Duration,
+    pub hints: u32,
+    /// Reveals, chords and flags the player made.
+    pub clicks: u32,
+    /// Clicks that changed nothing.
+    pub wasted: u32,
+    /// Flags placed, counting ones later removed.
+    pub flags: u32,
+    /// When the game ended, in seconds since the Unix epoch.
+    pub finished: u64,
+    /// The board's 3BV, see `metrics::bbbv`.
+    pub bbbv: u32,
+    /// Reveals of hidden empty cells, each starting an opening.
+    pub opening_clicks: u32,
+    /// Reveals of hidden numbers.
+    pub number_clicks: u32,
+    pub variant: Variant,
+    /// What the board was generated from, if it came from a seed.
+    pub seed: Option<u64>,
+}
+
+impl Record {
+    /// Whether hints helped, which keeps the game out of personal bests.
+    pub fn is_assisted(&self) -> bool {
+        self.hints > 0
+    }
+
+    /// 3BV cleared per second, for a won game.
+    pub fn bbbv_per_second(&self) -> f64 {
+        self.bbbv as f64 / self.time.as_secs_f64().max(0.001)
+    }
+
+    /// Width, height and mines, which together make a difficulty.
+    pub fn size(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.mines)
+    }
+
+    pub fn configuration(&self) -> Configuration {
+        Configuration {
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            variant: self.variant,
+            assisted: self.is_assisted(),
+        }
+    }
+
+    /// A name for the game that stays the same wherever the history is
+    /// exported to, so copies of it can be told apart from other games.
+    pub fn id(&self) -> String {
+        format!(
+            "{}-{}x{}x{}-{}",
+            self.finished,
+            self.width,
+            self.height,
+            self.mines,
+            self.time.as_millis()
+        )
+    }
+
+    /// Days since the Unix epoch, in UTC, the game ended on.
+    pub fn day(&self) -> u64 {
+        self.finished / 86_400
+    }
+
+    /// How efficiently a won game was played. `None` for losses, and for
+    /// games recorded before clicks and 3BV were.
+    pub fn efficiency(&self) -> Option<Efficiency> {
+        if !self.won || self.clicks == 0 || self.bbbv == 0 {
+            return None;
+        }
+        let bbbv = self.bbbv as f64;
+        let seconds = self.time.as_secs_f64().max(0.001);
+        Some(Efficiency {
+            ioe: bbbv / self.clicks as f64,
+            throughput: bbbv / self.clicks.saturating_sub(self.wasted).max(1) as f64,
+            rqp: seconds * seconds / bbbv,
+        })
+    }
+
+    fn encode(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            self.width,
+            self.height,
+            self.mines,
+            if self.won { "won" } else { "lost" },
+            self.time.as_millis(),
+            self.hints,
+            self.clicks,
+            self.wasted,
+            self.flags,
+            self.finished,
+            self.bbbv,
+            self.opening_clicks,
+            self.number_clicks,
+            self.variant.encode(),
+            self.seed
+                .map_or_else(|| "-".to_owned(), |seed| seed.to_string())
+        )
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        let [width, height, mines, result, millis, hints, clicks, wasted, flags, finished, bbbv, opening_clicks, number_clicks, variant, seed] =
+            words[..]
+        else {
+            return None;
+        };
+        Some(Self {
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+            mines: mines.parse().ok()?,
+            won: match result {
+                "won" => true,
+                "lost" => false,
+                _ => return None,
+            },
+            time: Duration::from_millis(millis.parse().ok()?),
+            hints: hints.parse().ok()?,
+            clicks: clicks.parse().ok()?,
+            wasted: wasted.parse().ok()?,
+            flags: flags.parse().ok()?,
+            finished: finished.parse().ok()?,
+            bbbv: bbbv.parse().ok()?,
+            opening_clicks: opening_clicks.parse().ok()?,
+            number_clicks: number_clicks.parse().ok()?,
+            variant: Variant::decode(variant)?,
+            seed: match seed {
+                "-" => None,
+                seed => Some(seed.parse().ok()?),
+            },
+        })
+    }
+}
+
+/// The community's measures of how well a board was cleared, all from
+/// the time, the clicks and the 3BV.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Efficiency {
+    /// Index of Efficiency: 3BV per click. Above 1 means chording paid off.
+    pub ioe: f64,
+    /// 3BV per click that changed the board, so wasted clicks don't count.
+    pub throughput: f64,
+    /// Time over 3BV/s; rewards speed and small 3BV alike. Lower is better.
+    pub rqp: f64,
+}
+
+impl Efficiency {
+    /// The mean of each measure, or `None` if there are none.
+    pub fn average(all: impl IntoIterator<Item = Efficiency>) -> Option<Self> {
+        let mut count = 0;
+        let mut sum = Efficiency {
+            ioe: 0.0,
+            throughput: 0.0,
+            rqp: 0.0,
+        };
+        for efficiency in all {
+            count += 1;
+            sum.ioe += efficiency.ioe;
+            sum.throughput += efficiency.throughput;
+            sum.rqp += efficiency.rqp;
+        }
+        (count > 0).then(|| Efficiency {
+            ioe: sum.ioe / count as f64,
+            throughput: sum.throughput / count as f64,
+            rqp: sum.rqp / count as f64,
+        })
+    }
+}
+
+/// Where a player's efficiency in the trainer is heading: the mean IOE of
+/// their latest trainer wins, and of as many wins before those.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Wins in the latest stretch, up to `WINDOW`.
+    pub games: usize,
+    pub recent: f64,
+    /// `None` until there are more wins than one stretch holds.
+    pub before: Option<f64>,
+}
+
+impl Progress {
+    /// Wins in each stretch compared.
+    pub const WINDOW: usize = 10;
+
+    /// From the IOE of every trainer win, oldest first. `None` if there
+    /// are none.
+    pub fn of(ioes: &[f64]) -> Option<Self> {
+        let mean = |values: &[f64]| {
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        };
+        let (earlier, recent) = ioes.split_at(ioes.len().saturating_sub(Self::WINDOW));
+        Some(Self {
+            games: recent.len(),
+            recent: mean(recent)?,
+            before: mean(&earlier[earlier.len().saturating_sub(Self::WINDOW)..]),
+        })
+    }
+}
+
+/// Wins in a row on one difficulty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Streak {
+    /// Wins since the last loss, which the next loss resets.
+    pub current: u32,
+    pub best: u32,
+}
+
+impl Streak {
+    /// The streak after another game.
+    pub fn after(self, won: bool) -> Self {
+        if won {
+            Self {
+                current: self.current + 1,
+                best: self.best.max(self.current + 1),
+            }
+        } else {
+            Self {
+                current: 0,
+                best: self.best,
+            }
+        }
+    }
+}
+
+/// How often each cell of one board size was clicked, across every game
+/// on that size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    counts: Vec<u32>,
+}
+
+impl Heatmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            counts: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Counts a click. Clicks outside the board are ignored.
+    pub fn add(&mut self, row: usize, col: usize) {
+        if row < self.height && col < self.width {
+            let count = &mut self.counts[row * self.width + col];
+            *count = count.saturating_add(1);
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> u32 {
+        self.counts[row * self.width + col]
+    }
+
+    /// The count of the most clicked cell.
+    pub fn max(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Counts separated by spaces, with runs of unclicked cells written as
+    /// `zN` so that big, mostly unclicked boards stay small.
+    fn encode(&self) -> String {
+        let mut words = Vec::new();
+        let mut zeros = 0;
+        for &count in &self.counts {
+            if count == 0 {
+                zeros += 1;
+                continue;
+            }
+            if zeros > 0 {
+                words.push(format!("z{}", zeros));
+                zeros = 0;
+            }
+            words.push(count.to_string());
+        }
+        if zeros > 0 {
+            words.push(format!("z{}", zeros));
+        }
+        words.join(" ")
+    }
+
+    fn decode(width: usize, height: usize, value: &str) -> Option<Self> {
+        let size = width.checked_mul(height)?;
+        let mut counts = Vec::with_capacity(size.min(value.len()));
+        for word in value.split_whitespace() {
+            match word.strip_prefix('z') {
+                Some(zeros) => {
+                    let zeros: usize = zeros.parse().ok()?;
+                    if zeros > size - counts.len() {
+                        return None;
+                    }
+                    counts.resize(counts.len() + zeros, 0);
+                }
+                None => counts.push(word.parse().ok()?),
+            }
+            if counts.len() > size {
+                return None;
+            }
+        }
+        (counts.len() == size).then_some(Self {
+            width,
+            height,
+            counts,
+        })
+    }
+}
+
+/// An achievement and when it was unlocked, in seconds since the Unix
+/// epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unlock {
+    pub id: String,
+    pub at: u64,
+}
+
+/// Every game recorded so far, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub records: Vec<Record>,
+    pub unlocked: Vec<Unlock>,
+    pub heatmaps: Vec<Heatmap>,
+}
+
+impl Stats {
+    /// Records a finished game and returns the achievements it unlocked.
+    pub fn push(&mut self, record: Record) -> Vec<&'static Achievement> {
+        self.records.push(record);
+        let new: Vec<&'static Achievement> = achievement::ALL
+            .iter()
+            .filter(|achievement| !self.is_unlocked(achievement.id))
+            .filter(|achievement| (achievement.unlocked_by)(self, &record))
+            .collect();
+        for achievement in &new {
+            self.unlocked.push(Unlock {
+                id: achievement.id.to_owned(),
+                at: record.finished,
+            });
+        }
+        new
+    }
+
+    /// Adds the games of `other` that aren't here yet, told apart by
+    /// `Record::id`, keeping every game in the order it ended. Returns how
+    /// many were added.
+    ///
+    /// Achievements unlocked on either side stay unlocked, at the earlier
+    /// time. Clicks can't be told apart by game, so each heatmap cell keeps
+    /// the larger of the two counts; merging the same stats twice changes
+    /// nothing.
+    pub fn merge(&mut self, other: Stats) -> usize {
+        let known: HashSet<String> = self.records.iter().map(Record::id).collect();
+        let before = self.records.len();
+        self.records.extend(
+            other
+                .records
+                .into_iter()
+                .filter(|record| !known.contains(&record.id())),
+        );
+        self.records.sort_by_key(|record| record.finished);
+        for unlock in other.unlocked {
+            match self.unlocked.iter_mut().find(|mine| mine.id == unlock.id) {
+                Some(mine) => mine.at = mine.at.min(unlock.at),
+                None => self.unlocked.push(unlock),
+            }
+        }
+        for map in other.heatmaps {
+            let size = (map.width, map.height);
+            match self
+                .heatmaps
+                .iter_mut()
+                .find(|mine| (mine.width, mine.height) == size)
+            {
+                Some(mine) => {
+                    for (count, theirs) in mine.counts.iter_mut().zip(map.counts) {
+                        *count = (*count).max(theirs);
+                    }
+                }
+                None => self.heatmaps.push(map),
+            }
+        }
+        self.records.len() - before
+    }
+
+    /// Counts the cells clicked in a game on a `width` by `height` board.
+    pub fn add_clicks(&mut self, width: usize, height: usize, clicks: &[(usize, usize)]) {
+        let i = match self
+            .heatmaps
+            .iter()
+            .position(|map| (map.width, map.height) == (width, height))
+        {
+            Some(i) => i,
+            None => {
+                self.heatmaps.push(Heatmap::new(width, height));
+                self.heatmaps.len() - 1
+            }
+        };
+        for &(row, col) in clicks {
+            self.heatmaps[i].add(row, col);
+        }
+    }
+
+    pub fn heatmap(&self, width: usize, height: usize) -> Option<&Heatmap> {
+        self.heatmaps
+            .iter()
+            .find(|map| (map.width, map.height) == (width, height))
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.iter().any(|unlock| unlock.id == id)
+    }
+
+    /// The fastest unassisted win of the classic game on a board of this
+    /// size.
+    pub fn best(&self, width: usize, height: usize, mines: usize) -> Option<&Record> {
+        self.best_in(Configuration::classic(width, height, mines))
+    }
+
+    /// The fastest win in exactly this configuration.
+    pub fn best_in(&self, configuration: Configuration) -> Option<&Record> {
+        self.records
+            .iter()
+            .filter(|record| record.won && record.configuration() == configuration)
+            .min_by_key(|record| record.time)
+    }
+
+    /// Every configuration played, in order of size, mines, variant and
+    /// then assistance.
+    pub fn configurations(&self) -> Vec<Configuration> {
+        let mut configurations: Vec<Configuration> =
+            self.records.iter().map(Record::configuration).collect();
+        configurations.sort();
+        configurations.dedup();
+        configurations
+    }
+
+    /// Every game on a board of this size, oldest first.
+    pub fn games(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+    ) -> impl Iterator<Item = &Record> + '_ {
+        self.records
+            .iter()
+            .filter(move |record| record.size() == (width, height, mines))
+    }
+
+    /// Every difficulty played, in the order each was first played.
+    pub fn sizes(&self) -> Vec<(usize, usize, usize)> {
+        let mut sizes = Vec::new();
+        for record in &self.records {
+            if !sizes.contains(&record.size()) {
+                sizes.push(record.size());
+            }
+        }
+        sizes
+    }
+
+    /// Average efficiency of the wins on a board of this size.
+    pub fn efficiency(&self, width: usize, height: usize, mines: usize) -> Option<Efficiency> {
+        Efficiency::average(
+            self.games(width, height, mines)
+                .filter_map(Record::efficiency),
+        )
+    }
+
+    /// The IOE of every trainer win, oldest first, for `Progress`.
+    pub fn trainer_ioes(&self) -> Vec<f64> {
+        self.records
+            .iter()
+            .filter(|record| record.variant.contains(Variant::TRAINER))
+            .filter_map(Record::efficiency)
+            .map(|efficiency| efficiency.ioe)
+            .collect()
+    }
+
+    /// Current and best win streaks on a board of this size.
+    pub fn streak(&self, width: usize, height: usize, mines: usize) -> Streak {
+        self.games(width, height, mines)
+            .fold(Streak::default(), |streak, record| streak.after(record.won))
+    }
+}
+
+/// Each game is a `game.N` field holding `width height mines won|lost
+/// milliseconds hints clicks wasted flags finished 3bv opening-clicks
+/// number-clicks variant`, each achievement an `achievement.ID` field
+/// holding when it was unlocked, and each heatmap a
+/// `heatmap.WIDTHxHEIGHT` field.
+impl Saved for Stats {
+    const KIND: &'static str = "stats";
+    const MIGRATIONS: &'static [Migration] = &[
+        add_click_counts,
+        add_bbbv,
+        add_click_origins,
+        add_variant,
+        add_seed,
+    ];
+
+    fn save(&self, document: &mut Document) {
+        for (i, record) in self.records.iter().enumerate() {
+            document.set(&format!("game.{}", i), record.encode());
+        }
+        for unlock in &self.unlocked {
+            document.set(&format!("achievement.{}", unlock.id), unlock.at);
+        }
+        for map in &self.heatmaps {
+            document.set(
+                &format!("heatmap.{}x{}", map.width, map.height),
+                map.encode(),
+            );
+        }
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let records = document
+            .fields()
+            .filter(|(key, _)| key.starts_with("game."))
+            .map(|(key, value)| {
+                Record::decode(value).ok_or_else(|| SaveError::Invalid {
+                    field: key.to_owned(),
+                    value: value.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let unlocked = document
+            .fields()
+            .filter_map(|(key, value)| Some((key.strip_prefix("achievement.")?, value)))
+            .map(|(id, value)| {
+                Ok(Unlock {
+                    id: id.to_owned(),
+                    at: value.parse().map_err(|_| SaveError::Invalid {
+                        field: format!("achievement.{}", id),
+                        value: value.to_owned(),
+                    })?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let heatmaps = document
+            .fields()
+            .filter_map(|(key, value)| Some((key, key.strip_prefix("heatmap.")?, value)))
+            .map(|(key, size, value)| {
+                size.split_once('x')
+                    .and_then(|(width, height)| {
+                        Heatmap::decode(width.parse().ok()?, height.parse().ok()?, value)
+                    })
+                    .ok_or_else(|| SaveError::Invalid {
+                        field: key.to_owned(),
+                        value: value.to_owned(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            records,
+            unlocked,
+            heatmaps,
+        })
+    }
+}
+
+/// Adds `extra` to the end of every game.
+fn extend_games(document: &mut Document, extra: &str) {
+    let games: Vec<(String, String)> = document
+        .fields()
+        .filter(|(key, _)| key.starts_with("game."))
+        .map(|(key, value)| (key.to_owned(), format!("{} {}", value, extra)))
+        .collect();
+    for (key, value) in games {
+        document.set(&key, value);
+    }
+}
+
+/// Version 1 games had no clicks, flags or end time. They are filled in
+/// as zeros; achievements only look at those of the game just played.
+fn add_click_counts(document: &mut Document) -> Result<(), SaveError> {
+    extend_games(document, "0 0 0 0");
+    Ok(())
+}
+
+/// Version 2 games had no 3BV, and the board is gone, so it is recorded
+/// as zero for unknown.
+fn add_bbbv(document: &mut Document) -> Result<(), SaveError> {
+    extend_games(document, "0");
+    Ok(())
+}
+
+/// Version 3 games didn't say where their reveals landed; recorded as
+/// zeros.
+fn add_click_origins(document: &mut Document) -> Result<(), SaveError> {
+    extend_games(document, "0 0");
+    Ok(())
+}
+
+/// Version 4 games didn't record their variant. No-guess boards were
+/// already offered, but there is no telling them apart now, so they all
+/// count as classic.
+fn add_variant(document: &mut Document) -> Result<(), SaveError> {
+    extend_games(document, "classic");
+    Ok(())
+}
+
+/// Version 5 games didn't record their seed.
+fn add_seed(document: &mut Document) -> Result<(), SaveError> {
+    extend_games(document, "-");
+    Ok(())
+}
+
+/// Games finished since the game was started, for grinding sessions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    pub games: usize,
+    pub wins: usize,
+    /// Total time of the wins.
+    won_time: Duration,
+    /// Sum of the 3BV/s of the wins.
+    speed: f64,
+}
+
+impl Session {
+    pub fn add(&mut self, record: &Record) {
+        self.games += 1;
+        if record.won {
+            self.wins += 1;
+            self.won_time += record.time;
+            self.speed += record.bbbv_per_second();
+        }
+    }
+
+    /// Average time of the games won.
+    pub fn average_time(&self) -> Option<Duration> {
+        (self.wins > 0).then(|| self.won_time / self.wins as u32)
+    }
+
+    /// Average 3BV/s of the games won.
+    pub fn average_speed(&self) -> Option<f64> {
+        (self.wins > 0).then(|| self.speed / self.wins as f64)
+    }
+}
@@ -0,0 +1,226 @@
+//! Which copy of a synced file wins, and the plain HTTP/1.1 spoken to the
+//! WebDAV folder files are synced through. Files that can't be merged,
+//! such as the config, are resolved by when they last changed: the newer
+//! copy replaces the older one on either side. The remote side keeps those
+//! times in a manifest, since its own file times depend on the server.
+//!
+//! Only the bytes are handled here; the frontend opens the connections.
+
+use crate::save::{Document, SaveError, Saved};
+
+/// When each synced file last changed, in seconds since the Unix epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    files: Vec<(String, u64)>,
+}
+
+impl Manifest {
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.files
+            .iter()
+            .find(|(file, _)| file == name)
+            .map(|&(_, at)| at)
+    }
+
+    pub fn set(&mut self, name: &str, at: u64) {
+        match self.files.iter_mut().find(|(file, _)| file == name) {
+            Some((_, time)) => *time = at,
+            None => self.files.push((name.to_owned(), at)),
+        }
+    }
+
+    /// Every file the server has, in the order they were first synced.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+/// Whether `name` is a theme file in the `themes/` folder, such as
+/// `themes/night.txt`. Names from the server's manifest are only trusted
+/// as paths once they pass, so a manifest can't write outside the folder.
+pub fn is_theme(name: &str) -> bool {
+    let Some(stem) = name
+        .strip_prefix("themes/")
+        .and_then(|file| file.strip_suffix(".txt"))
+    else {
+        return false;
+    };
+    !stem.is_empty()
+        && !stem.starts_with('.')
+        && !stem.contains(['/', '\\', ':'])
+        && !stem.chars().any(char::is_control)
+}
+
+/// One `file.NAME SECONDS` field per file.
+impl Saved for Manifest {
+    const KIND: &'static str = "manifest";
+
+    fn save(&self, document: &mut Document) {
+        for (name, at) in &self.files {
+            document.set(&format!("file.{}", name), at);
+        }
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let files = document
+            .fields()
+            .filter_map(|(key, value)| Some((key, key.strip_prefix("file.")?, value)))
+            .map(|(key, name, value)| {
+                let at = value.parse().map_err(|_| SaveError::Invalid {
+                    field: key.to_owned(),
+                    value: value.to_owned(),
+                })?;
+                Ok((name.to_owned(), at))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { files })
+    }
+}
+
+/// What to do with one file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Upload,
+    Download,
+    /// Both sides agree, or neither has the file.
+    Keep,
+}
+
+/// Settles a file changed `local` seconds after the epoch here and
+/// `remote` on the server, either missing if that side has no copy.
+pub fn resolve(local: Option<u64>, remote: Option<u64>) -> Action {
+    match (local, remote) {
+        (Some(local), Some(remote)) if local > remote => Action::Upload,
+        (Some(local), Some(remote)) if local < remote => Action::Download,
+        (Some(_), None) => Action::Upload,
+        (None, Some(_)) => Action::Download,
+        _ => Action::Keep,
+    }
+}
+
+/// A WebDAV folder, spoken to in plain HTTP/1.1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Folder {
+    host: String,
+    port: u16,
+    /// Path of the folder on the server, ending with `/`.
+    path: String,
+    /// The `Authorization` header's value, if there is a user.
+    auth: Option<String>,
+}
+
+impl Folder {
+    /// The folder at `url`, logged into as `user` unless that is empty.
+    pub fn new(url: &str, user: &str, password: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("{} is not an http:// URL; this build can't speak TLS", url))?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("invalid port in {}", url))?,
+            ),
+            None => (authority, 80),
+        };
+        let path = format!("/{}", path.trim_end_matches('/'));
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            path: if path == "/" { path } else { path + "/" },
+            auth: (!user.is_empty()).then(|| {
+                let credentials = format!("{}:{}", user, password);
+                format!("Basic {}", base64(credentials.as_bytes()))
+            }),
+        })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Path of the folder on the server, ending with `/`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The head of a request for the file `name` in the folder, with a
+    /// body of `length` bytes to follow.
+    pub fn head(&self, method: &str, name: &str, length: usize) -> String {
+        // Servers told a host without its port would build their links to
+        // the default one
+        let host = match self.port {
+            80 => self.host.clone(),
+            port => format!("{}:{}", self.host, port),
+        };
+        let mut head = format!(
+            "{} {}{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            method, self.path, name, host, length
+        );
+        if let Some(auth) = &self.auth {
+            head.push_str(&format!("Authorization: {}\r\n", auth));
+        }
+        head.push_str("\r\n");
+        head
+    }
+}
+
+/// The status and body of an HTTP/1.1 reply read to the end.
+pub fn parse_reply(reply: &[u8]) -> Option<(u16, Vec<u8>)> {
+    let end = reply.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&reply[..end]).ok()?;
+    let body = &reply[end + 4..];
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Some((status, body))
+}
+
+/// Joins a chunked body back together.
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut joined = Vec::new();
+    loop {
+        let line = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line + 2..];
+        if size == 0 {
+            return Some(joined);
+        }
+        joined.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// `bytes` in standard base64, padded.
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, &byte)| {
+            word | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(word >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
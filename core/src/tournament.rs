@@ -0,0 +1,139 @@
+//! Plays several agents on the same seeded boards and ranks them.
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::agent::{Agent, View};
+use crate::board::{Board, BoardError};
+
+#[derive(Clone, Copy, Debug)]
+pub struct TournamentConfig {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub boards: usize,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    pub guesses: usize,
+    pub time: Duration,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn average_time(&self) -> Duration {
+        self.time / self.games.max(1) as u32
+    }
+
+    pub fn average_guesses(&self) -> f64 {
+        self.guesses as f64 / self.games.max(1) as f64
+    }
+}
+
+/// Plays every agent on `config.boards` boards seeded from `config.seed`
+/// and returns the standings, best win rate first.
+pub fn run(
+    agents: &mut [Box<dyn Agent>],
+    config: TournamentConfig,
+) -> Result<Vec<Standing>, BoardError> {
+    let mut standings = Vec::with_capacity(agents.len());
+    for agent in agents.iter_mut() {
+        let mut standing = Standing {
+            name: agent.name().to_owned(),
+            games: 0,
+            wins: 0,
+            guesses: 0,
+            time: Duration::ZERO,
+        };
+        for game in 0..config.boards {
+            let seed = config.seed.wrapping_add(game as u64);
+            let board = Board::with_seed(config.width, config.height, config.mines, seed)?;
+            let (won, guesses, time) = play(agent.as_mut(), board, seed);
+            crate::debug!(
+                "{} {} board {} after {} guesses in {:?}",
+                standing.name,
+                if won { "won" } else { "lost" },
+                seed,
+                guesses,
+                time
+            );
+            standing.games += 1;
+            standing.wins += usize::from(won);
+            standing.guesses += guesses;
+            standing.time += time;
+        }
+        crate::info!(
+            "{} finished with a {:.1}% win rate",
+            standing.name,
+            standing.win_rate() * 100.0
+        );
+        standings.push(standing);
+    }
+
+    standings.sort_by(|a, b| {
+        b.win_rate()
+            .total_cmp(&a.win_rate())
+            .then(a.average_time().cmp(&b.average_time()))
+    });
+    Ok(standings)
+}
+
+/// Returns whether the agent won, how many guesses it made and how long it took.
+fn play(agent: &mut dyn Agent, mut board: Board, seed: u64) -> (bool, usize, Duration) {
+    // Seeding the agent with the board's own seed would let its first random
+    // pick replay the generator's first mine
+    agent.reset(!seed);
+    let mut guesses = 0;
+    let start = Instant::now();
+
+    while !board.is_won() && !board.is_lost() {
+        let Some(step) = agent.next_move(&View::of(&board)) else {
+            break;
+        };
+        crate::trace!(
+            "{} reveals ({}, {}){}",
+            agent.name(),
+            step.row,
+            step.col,
+            if step.guess { " as a guess" } else { "" }
+        );
+        // Revealing an already visible cell would loop forever
+        if !board.contains(step.row, step.col) || board.reveal(step.row, step.col).is_none() {
+            break;
+        }
+        guesses += usize::from(step.guess);
+    }
+
+    (board.is_won(), guesses, start.elapsed())
+}
+
+/// Formats standings as a ranked plain-text table.
+pub fn table(standings: &[Standing]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<4} {:<16} {:>6} {:>8} {:>12} {:>10}",
+        "rank", "agent", "games", "win %", "avg time", "avg guess"
+    );
+    for (rank, standing) in standings.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{:<4} {:<16} {:>6} {:>7.1}% {:>10.2}ms {:>10.2}",
+            rank + 1,
+            standing.name,
+            standing.games,
+            standing.win_rate() * 100.0,
+            standing.average_time().as_secs_f64() * 1000.0,
+            standing.average_guesses(),
+        );
+    }
+    out
+}
@@ -0,0 +1,163 @@
+//! A guided first game on a small fixed board.
+//!
+//! The lesson is a list of steps. Each one explains something, highlights
+//! the cells it is about, and waits either for the player to read it or
+//! for one particular move; any other move is blocked and answered with a
+//! hint. The last step lets the player finish the board on their own but
+//! still blocks moves that would lose it.
+//!
+//! Frontends check every move with `Tutorial::attempt` before playing it,
+//! and show the current step's text in their own language.
+
+use crate::board::{Board, CellState, CellType};
+use crate::plugin::Message;
+use crate::sim::{self, Move};
+
+/// The lesson board. Revealing the top left corner opens everything down
+/// to the numbers around the two mines, the 1 at (1, 2) then has a single
+/// hidden neighbour, and the rest of the board follows by logic.
+const LAYOUT: &str = "
+    . . . . .
+    . . . . .
+    . . . * .
+    . . . . .
+    * . . . .
+";
+
+/// What a step waits for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// Any click moves on.
+    Read,
+    /// Exactly this move.
+    Play(Move),
+    /// Clearing the board, with mistakes blocked.
+    Clear,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub text: Message,
+    pub highlight: Vec<(usize, usize)>,
+    pub goal: Goal,
+}
+
+impl Step {
+    fn new(id: &'static str, goal: Goal, highlight: &[(usize, usize)]) -> Self {
+        Self {
+            text: Message::new(id),
+            highlight: highlight.to_vec(),
+            goal,
+        }
+    }
+}
+
+pub struct Tutorial {
+    steps: Vec<Step>,
+    current: usize,
+    /// Why the last move was blocked, until the next one is allowed.
+    hint: Option<Message>,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            steps: vec![
+                Step::new("tutorial-welcome", Goal::Read, &[]),
+                Step::new("tutorial-reveal", Goal::Play(Move::Reveal(0, 0)), &[(0, 0)]),
+                Step::new("tutorial-numbers", Goal::Read, &[(1, 2), (2, 3)]),
+                Step::new("tutorial-flag", Goal::Play(Move::Flag(2, 3)), &[(2, 3)]),
+                Step::new("tutorial-chord", Goal::Play(Move::Chord(1, 3)), &[(1, 3)]),
+                Step::new("tutorial-clear", Goal::Clear, &[]),
+            ],
+            current: 0,
+            hint: None,
+        }
+    }
+
+    /// A fresh copy of the lesson board.
+    pub fn board() -> Board {
+        sim::board(LAYOUT).expect("the tutorial layout is valid")
+    }
+
+    pub fn step(&self) -> &Step {
+        &self.steps[self.current]
+    }
+
+    /// Number of the current step, from 0.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn hint(&self) -> Option<&Message> {
+        self.hint.as_ref()
+    }
+
+    /// Checks a move the player is about to make on `board` and returns
+    /// whether to play it. A move that completes the step moves the lesson
+    /// on; a click during a reading step moves it on without being played.
+    ///
+    /// Clicking a revealed number chords it, so that counts as a chord.
+    pub fn attempt(&mut self, board: &Board, mut step: Move) -> bool {
+        if let Move::Reveal(row, col) = step {
+            if board.cell(row, col).cell_state == CellState::Visible {
+                step = Move::Chord(row, col);
+            }
+        }
+        let blocked = match self.step().goal {
+            Goal::Read => {
+                self.advance();
+                return false;
+            }
+            Goal::Play(expected) if expected == step => {
+                self.advance();
+                return true;
+            }
+            Goal::Play(_) => Message::new("tutorial-not-that"),
+            Goal::Clear => match mistake(board, step) {
+                Some(hint) => hint,
+                None => {
+                    self.hint = None;
+                    return true;
+                }
+            },
+        };
+        self.hint = Some(blocked);
+        false
+    }
+
+    fn advance(&mut self) {
+        self.hint = None;
+        self.current = (self.current + 1).min(self.steps.len() - 1);
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Why `step` would be a mistake on `board`, if it would.
+fn mistake(board: &Board, step: Move) -> Option<Message> {
+    let is_mine = |(row, col): (usize, usize)| board.cell(row, col).cell_type == CellType::Mine;
+    match step {
+        Move::Reveal(row, col) if is_mine((row, col)) => Some(Message::new("tutorial-mine")),
+        Move::Flag(row, col)
+            if board.cell(row, col).cell_state == CellState::Hidden && !is_mine((row, col)) =>
+        {
+            Some(Message::new("tutorial-safe"))
+        }
+        // A chord only goes wrong when a flag is misplaced, and misplaced
+        // flags are already blocked
+        _ => None,
+    }
+}
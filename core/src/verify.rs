@@ -0,0 +1,184 @@
+//! Invariants every generated board must satisfy, and a runner that checks
+//! them over many random sizes, densities and seeds.
+//!
+//! Cases are drawn from a seeded generator, so a failure names the case
+//! that reproduces it: `Board::with_seed(width, height, mines, seed)`.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, BoardError, CellState, CellType};
+
+/// A broken invariant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    Generation(BoardError),
+    MineCount {
+        expected: usize,
+        found: usize,
+    },
+    WrongNumber {
+        row: usize,
+        col: usize,
+        expected: usize,
+        found: CellType,
+    },
+    FloodRevealedMine {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Generation(err) => write!(f, "generation failed: {}", err),
+            Violation::MineCount { expected, found } => {
+                write!(f, "expected {} mines, found {}", expected, found)
+            }
+            Violation::WrongNumber {
+                row,
+                col,
+                expected,
+                found,
+            } => write!(
+                f,
+                "({}, {}) touches {} mines but is {:?}",
+                row, col, expected, found
+            ),
+            Violation::FloodRevealedMine { row, col } => {
+                write!(f, "a flood revealed the mine at ({}, {})", row, col)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// The board holds exactly as many mines as it claims.
+pub fn mine_count(board: &Board) -> Result<(), Violation> {
+    let found = cells(board)
+        .filter(|&(row, col)| board.cell(row, col).cell_type == CellType::Mine)
+        .count();
+    if found != board.mines() {
+        return Err(Violation::MineCount {
+            expected: board.mines(),
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Every safe cell shows how many mines its neighbours hold, give or take
+/// the one a liar's number is off by.
+pub fn numbers(board: &Board) -> Result<(), Violation> {
+    for (row, col) in cells(board) {
+        let found = board.cell(row, col).cell_type;
+        let expected = board
+            .adjacent(row, col)
+            .into_iter()
+            .map(|(r, c)| board.mines_at(r, c))
+            .sum();
+        let shown = match found {
+            CellType::Empty => 0,
+            CellType::Number(n) => n,
+            CellType::Mine => continue,
+        };
+        // A liar's number is off by one either way
+        let matches = match board.rules().liar {
+            true => shown.abs_diff(expected) == 1,
+            false => shown == expected,
+        };
+        if !matches {
+            return Err(Violation::WrongNumber {
+                row,
+                col,
+                expected,
+                found,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Revealing the safe cell at `(row, col)` on a copy of the board never
+/// uncovers a mine, however far the opening spreads.
+pub fn flood(board: &Board, row: usize, col: usize) -> Result<(), Violation> {
+    let mut board = board.clone();
+    if board.cell(row, col).cell_type == CellType::Mine {
+        return Ok(());
+    }
+    board.reveal(row, col);
+    match cells(&board).find(|&(r, c)| {
+        let cell = board.cell(r, c);
+        cell.cell_type == CellType::Mine && cell.cell_state == CellState::Visible
+    }) {
+        Some((row, col)) => Err(Violation::FloodRevealedMine { row, col }),
+        None => Ok(()),
+    }
+}
+
+/// Every invariant, with floods started from the first empty cell and the
+/// cell in each corner.
+pub fn board(board: &Board) -> Result<(), Violation> {
+    mine_count(board)?;
+    numbers(board)?;
+    let (last_row, last_col) = (board.height() - 1, board.width() - 1);
+    let empty = cells(board).find(|&(row, col)| board.cell(row, col).cell_type == CellType::Empty);
+    for (row, col) in
+        empty
+            .into_iter()
+            .chain([(0, 0), (0, last_col), (last_row, 0), (last_row, last_col)])
+    {
+        flood(board, row, col)?;
+    }
+    Ok(())
+}
+
+fn cells(board: &Board) -> impl Iterator<Item = (usize, usize)> {
+    let width = board.width();
+    (0..board.height()).flat_map(move |row| (0..width).map(move |col| (row, col)))
+}
+
+/// One random board to check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Case {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub seed: u64,
+}
+
+impl Case {
+    /// A board up to `max_size` cells wide and tall, with any density that
+    /// leaves at least one safe cell.
+    pub fn random<R: Rng>(rng: &mut R, max_size: usize) -> Self {
+        let width = rng.gen_range(1..=max_size);
+        let height = rng.gen_range(1..=max_size);
+        Self {
+            width,
+            height,
+            mines: rng.gen_range(0..width * height),
+            seed: rng.gen(),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), Violation> {
+        let generated = Board::with_seed(self.width, self.height, self.mines, self.seed)
+            .map_err(Violation::Generation)?;
+        board(&generated)
+    }
+}
+
+/// Checks `cases` random boards drawn from `seed`, stopping at the first
+/// one that breaks an invariant.
+pub fn run(seed: u64, cases: usize, max_size: usize) -> Result<(), (Case, Violation)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..cases {
+        let case = Case::random(&mut rng, max_size);
+        case.check().map_err(|violation| (case, violation))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use minesweeper_core::bench::{self, Measurement};
+
+#[test]
+fn every_operation_is_measured_on_every_size() {
+    let sizes = [(9, 9, 10), (16, 16, 40)];
+    let measurements = bench::run(&sizes, Duration::ZERO);
+    let operations = ["generate", "numbers", "flood", "single point", "solver"];
+
+    assert_eq!(measurements.len(), sizes.len() * operations.len());
+    for (measurement, operation) in measurements.iter().zip(operations.iter().cycle()) {
+        assert_eq!(measurement.operation, *operation);
+        // A zero budget still runs each operation once
+        assert_eq!(measurement.runs, 1);
+        assert!(measurement.fastest <= measurement.median);
+        assert!(measurement.median <= measurement.slowest);
+    }
+}
+
+#[test]
+fn the_table_lines_up_one_row_per_measurement() {
+    let measurement = |operation, micros| Measurement {
+        operation,
+        size: (30, 16, 99),
+        runs: 12,
+        median: Duration::from_micros(micros),
+        fastest: Duration::from_nanos(900),
+        slowest: Duration::from_millis(15),
+    };
+    let table = bench::table(&[measurement("generate", 42), measurement("solver", 3)]);
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("operation"));
+    assert!(lines[1].starts_with("generate       30x16x99"));
+    assert!(lines[1].contains("42.0µs") && lines[1].contains("900ns"));
+    assert!(lines[1].contains("15.00ms"));
+    assert!(lines[2].contains("3000ns"));
+    assert!(lines
+        .iter()
+        .all(|line| line.chars().count() == lines[0].chars().count()));
+}
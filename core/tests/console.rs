@@ -0,0 +1,109 @@
+use minesweeper_core::console::{arg, Command, Error, Registry};
+
+type Handler = fn(&mut Vec<i64>, &[&str]) -> Result<String, String>;
+
+fn push(stack: &mut Vec<i64>, args: &[&str]) -> Result<String, String> {
+    let value: i64 = arg(args, 0, "value")?.unwrap_or_default();
+    stack.push(value);
+    Ok(format!("pushed {}", value))
+}
+
+fn add(stack: &mut Vec<i64>, args: &[&str]) -> Result<String, String> {
+    let (a, b) = (arg::<i64>(args, 0, "a")?, arg::<i64>(args, 1, "b")?);
+    stack.push(a.unwrap() + b.unwrap());
+    Ok(String::new())
+}
+
+fn registry() -> Registry<Handler> {
+    let mut registry = Registry::new();
+    registry.register(Command {
+        name: "push",
+        usage: "[value]",
+        about: "Pushes a value, 0 if none is given.",
+        run: push as Handler,
+    });
+    registry.register(Command {
+        name: "add",
+        usage: "<a> <b>",
+        about: "Pushes the sum.",
+        run: add as Handler,
+    });
+    registry
+}
+
+fn run(registry: &Registry<Handler>, stack: &mut Vec<i64>, line: &str) -> Result<String, String> {
+    match registry.parse(line).map_err(|err| err.to_string())? {
+        Some(call) => (call.command.run)(stack, &call.args),
+        None => Ok(String::new()),
+    }
+}
+
+#[test]
+fn lines_run_the_command_they_name() {
+    let registry = registry();
+    let mut stack = Vec::new();
+
+    assert_eq!(
+        run(&registry, &mut stack, "  push   12 "),
+        Ok("pushed 12".into())
+    );
+    assert_eq!(run(&registry, &mut stack, "push"), Ok("pushed 0".into()));
+    assert_eq!(run(&registry, &mut stack, "add 2 3"), Ok(String::new()));
+    assert_eq!(run(&registry, &mut stack, ""), Ok(String::new()));
+    assert_eq!(stack, [12, 0, 5]);
+}
+
+#[test]
+fn unknown_commands_and_wrong_arguments_are_refused() {
+    let registry = registry();
+    let mut stack = Vec::new();
+
+    assert!(matches!(registry.parse("pop"), Err(Error::Unknown(name)) if name == "pop"));
+    assert!(matches!(
+        registry.parse("add 1"),
+        Err(Error::Usage {
+            name: "add",
+            usage: "<a> <b>"
+        })
+    ));
+    assert!(registry.parse("push 1 2").is_err());
+    assert_eq!(
+        run(&registry, &mut stack, "push x"),
+        Err("value can't be `x`".into())
+    );
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn later_commands_replace_earlier_ones_of_the_same_name() {
+    let mut registry = registry();
+    registry.register(Command {
+        name: "push",
+        usage: "",
+        about: "Pushes 7.",
+        run: (|stack: &mut Vec<i64>, _: &[&str]| {
+            stack.push(7);
+            Ok(String::new())
+        }) as Handler,
+    });
+    let mut stack = Vec::new();
+
+    assert_eq!(registry.commands().len(), 2);
+    run(&registry, &mut stack, "push").unwrap();
+    assert_eq!(stack, [7]);
+}
+
+#[test]
+fn names_complete_and_help_lists_every_command() {
+    let registry = registry();
+
+    assert_eq!(registry.complete("p"), ["push"]);
+    assert_eq!(registry.complete(""), ["add", "push"]);
+    assert_eq!(
+        registry.help(),
+        [
+            "add <a> <b>  Pushes the sum.",
+            "push [value]  Pushes a value, 0 if none is given."
+        ]
+    );
+}
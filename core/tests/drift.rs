@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use minesweeper_core::drift::Drift;
+use minesweeper_core::{Board, CellState, CellType};
+
+fn cells(board: &Board) -> Vec<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+        .collect()
+}
+
+fn check_numbers(board: &Board) {
+    for (row, col) in cells(board) {
+        if board.cell(row, col).cell_type == CellType::Mine {
+            continue;
+        }
+        let count = board
+            .adjacent(row, col)
+            .into_iter()
+            .filter(|&(r, c)| board.cell(r, c).cell_type == CellType::Mine)
+            .count();
+        let number = match count {
+            0 => CellType::Empty,
+            count => CellType::Number(count),
+        };
+        assert_eq!(board.cell(row, col).cell_type, number, "at {},{}", row, col);
+    }
+}
+
+/// Reveals a safe cell with a number, so part of the board is seen.
+fn open_some(board: &mut Board) {
+    let (row, col) = cells(board)
+        .into_iter()
+        .find(|&(row, col)| board.cell(row, col).cell_type == CellType::Empty)
+        .unwrap();
+    board.reveal(row, col);
+}
+
+#[test]
+fn shifted_mines_keep_the_numbers_right_and_seen_cells_alone() {
+    let mut board = Board::new(16, 16, 40).unwrap();
+    open_some(&mut board);
+    let seen: Vec<_> = cells(&board)
+        .into_iter()
+        .filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Visible)
+        .collect();
+    // Whether each cell a number counts is a mine
+    let counted: Vec<_> = seen
+        .iter()
+        .flat_map(|&(row, col)| board.adjacent(row, col))
+        .map(|(row, col)| ((row, col), board.cell(row, col).cell_type == CellType::Mine))
+        .collect();
+    let seen: Vec<_> = seen
+        .into_iter()
+        .map(|(row, col)| ((row, col), board.cell(row, col)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..50 {
+        let (from, to) = board.shift_mine(&mut rng).unwrap();
+        assert_ne!(from, to);
+        assert_eq!(board.cell(to.row, to.col).cell_type, CellType::Mine);
+        assert_ne!(board.cell(from.row, from.col).cell_type, CellType::Mine);
+        assert_eq!(board.mines(), 40);
+        check_numbers(&board);
+        for &((row, col), cell) in &seen {
+            assert_eq!(board.cell(row, col), cell);
+        }
+        for &((row, col), mine) in &counted {
+            assert_eq!(board.cell(row, col).cell_type == CellType::Mine, mine);
+        }
+    }
+}
+
+#[test]
+fn flagged_cells_never_move() {
+    let mut board = Board::new(8, 8, 10).unwrap();
+    let flagged: Vec<_> = cells(&board)
+        .into_iter()
+        .filter(|&(row, col)| (row + col) % 2 == 0)
+        .collect();
+    for &(row, col) in &flagged {
+        board.toggle_flag(row, col);
+    }
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..100 {
+        let (from, to) = board.shift_mine(&mut rng).unwrap();
+        assert!(!flagged.contains(&(from.row, from.col)));
+        assert!(!flagged.contains(&(to.row, to.col)));
+    }
+}
+
+#[test]
+fn mines_move_on_time() {
+    let mut board = Board::new(16, 16, 40).unwrap();
+    let mut drift = Drift::new(Duration::from_secs(10), 3);
+    let start = board.clone();
+    assert!(drift.update(&mut board, Duration::from_secs(9)).is_empty());
+    assert_eq!(
+        drift.until_next(Duration::from_secs(9)),
+        Duration::from_secs(1)
+    );
+    assert_eq!(drift.update(&mut board, Duration::from_secs(10)).len(), 1);
+    assert_eq!(
+        drift.until_next(Duration::from_secs(10)),
+        Duration::from_secs(10)
+    );
+    // A frame that comes late makes up the moves it missed
+    assert_eq!(drift.update(&mut board, Duration::from_secs(45)).len(), 3);
+    assert_ne!(
+        cells(&board)
+            .into_iter()
+            .filter(|&(row, col)| board.cell(row, col) != start.cell(row, col))
+            .count(),
+        0
+    );
+    check_numbers(&board);
+}
@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use minesweeper_core::export::{self, ReplayOptions, Screenshot, VideoFormat};
+use minesweeper_core::gif::Encoder;
+use minesweeper_core::png;
+use minesweeper_core::replay::Replay;
+use minesweeper_core::save::SaveError;
+use minesweeper_core::sim::{self, Move};
+use minesweeper_core::stats::{Configuration, Variant};
+use minesweeper_core::CellType;
+
+/// One decoded GIF frame: its delay in hundredths of a second, where it
+/// goes and its palette indices.
+#[derive(Debug)]
+struct Frame {
+    delay: u16,
+    rect: [usize; 4],
+    pixels: Vec<u8>,
+}
+
+/// Reads back what `Encoder` writes: a global palette, then frames with
+/// a graphic control extension each.
+fn decode(bytes: &[u8]) -> (usize, usize, Vec<Frame>) {
+    assert_eq!(&bytes[..6], b"GIF89a");
+    let word = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let (width, height) = (word(6) as usize, word(8) as usize);
+    let mut i = 13 + 3 * (2 << (bytes[10] & 7));
+    let mut frames = Vec::new();
+    let mut delay = 0;
+    loop {
+        match bytes[i] {
+            0x3b => return (width, height, frames),
+            0x21 => {
+                if bytes[i + 1] == 0xf9 {
+                    delay = word(i + 4);
+                }
+                i += 2;
+                while bytes[i] != 0 {
+                    i += bytes[i] as usize + 1;
+                }
+                i += 1;
+            }
+            0x2c => {
+                let rect = [word(i + 1), word(i + 3), word(i + 5), word(i + 7)].map(usize::from);
+                let depth = bytes[i + 10];
+                i += 11;
+                let mut data = Vec::new();
+                while bytes[i] != 0 {
+                    data.extend(&bytes[i + 1..i + 1 + bytes[i] as usize]);
+                    i += bytes[i] as usize + 1;
+                }
+                i += 1;
+                let pixels = decompress(&data, depth);
+                assert_eq!(pixels.len(), rect[2] * rect[3]);
+                frames.push(Frame {
+                    delay,
+                    rect,
+                    pixels,
+                });
+            }
+            other => panic!("unexpected block {:#x}", other),
+        }
+    }
+}
+
+fn decompress(data: &[u8], depth: u8) -> Vec<u8> {
+    let clear = 1usize << depth;
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let mut width = depth + 1;
+    let mut previous: Option<usize> = None;
+    let mut output = Vec::new();
+    let (mut buffer, mut bits, mut bytes) = (0u32, 0u8, data.iter());
+    loop {
+        while bits < width {
+            buffer |= u32::from(*bytes.next().expect("end code")) << bits;
+            bits += 8;
+        }
+        let code = (buffer & ((1 << width) - 1)) as usize;
+        buffer >>= width;
+        bits -= width;
+        if code == clear {
+            table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.extend([Vec::new(), Vec::new()]);
+            width = depth + 1;
+            previous = None;
+            continue;
+        }
+        if code == clear + 1 {
+            return output;
+        }
+        let entry = match previous {
+            None => table[code].clone(),
+            Some(previous) => {
+                let entry = match table.get(code) {
+                    Some(entry) => entry.clone(),
+                    None => {
+                        let mut entry = table[previous].clone();
+                        entry.push(table[previous][0]);
+                        entry
+                    }
+                };
+                if table.len() < 4096 {
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << width && width < 12 {
+                        width += 1;
+                    }
+                }
+                entry
+            }
+        };
+        output.extend(&entry);
+        previous = Some(code);
+    }
+}
+
+#[test]
+fn frames_round_trip_through_the_encoder() {
+    // Enough varied pixels to grow the codes to 12 bits and clear the table
+    let (width, height) = (200u16, 150u16);
+    let first: Vec<u8> = (0..width as usize * height as usize)
+        .map(|i| (((i * 7919) % 13) ^ ((i / 200) % 3)) as u8 % 13)
+        .collect();
+    let mut second = first.clone();
+    second[150 * 200 / 2 + 10] = 12;
+    second[150 * 200 / 2 + 30] = 0;
+
+    let mut encoder = Encoder::new(width, height, &[[0, 0, 0]; 13]);
+    encoder.frame(&first, Duration::from_millis(500));
+    encoder.frame(&second, Duration::from_secs(2));
+    let (w, h, frames) = decode(&encoder.finish());
+
+    assert_eq!((w, h), (200, 150));
+    assert_eq!(frames[0].pixels, first);
+    assert_eq!(frames[0].delay, 50);
+    // Only the changed run of one row is stored again
+    assert_eq!(frames[1].rect, [10, 75, 21, 1]);
+    assert_eq!(frames[1].pixels, second[75 * 200 + 10..75 * 200 + 31]);
+    assert_eq!(frames[1].delay, 200);
+}
+
+/// A flag placed and taken back at once, then the winning click two
+/// seconds later, played back at double speed with 8 pixel tiles.
+fn replay() -> (Replay, ReplayOptions) {
+    let layout = "
+        . . .
+        . . .
+        . . *
+    ";
+    let mut replay = Replay::new(sim::board(layout).unwrap());
+    replay.push(Duration::ZERO, Move::Flag(2, 2));
+    replay.push(Duration::from_millis(5), Move::Flag(2, 2));
+    replay.push(Duration::from_secs(2), Move::Reveal(0, 0));
+    let options = ReplayOptions {
+        cell: 8,
+        speed: 2.0,
+    };
+    (replay, options)
+}
+
+#[test]
+fn replays_export_one_frame_per_move_at_their_speed() {
+    let (replay, options) = replay();
+    let (width, height, frames) = decode(&export::gif(&replay, options));
+
+    assert_eq!((width, height), (24, 24));
+    // The start and both flag toggles are too close together to show
+    // apart, so the first frame is already the unflagged board
+    let delays: Vec<u16> = frames.iter().map(|frame| frame.delay).collect();
+    assert_eq!(delays, [100, 300]);
+    assert_eq!(frames[0].pixels, export::render(replay.start(), 8));
+}
+
+#[test]
+fn videos_come_with_a_timing_manifest() {
+    let (replay, options) = replay();
+
+    let files = export::video(&replay, options, VideoFormat::Png);
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["frame-00000.png", "frame-00001.png", "frames.ffconcat"]
+    );
+    let manifest = String::from_utf8(files[2].1.clone()).unwrap();
+    let lines: Vec<&str> = manifest
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "ffconcat version 1.0",
+            "file 'frame-00000.png'",
+            "duration 1.000",
+            "file 'frame-00001.png'",
+            "duration 3.000",
+            "file 'frame-00001.png'",
+        ]
+    );
+
+    // Four seconds at 10 frames a second
+    let files = export::video(&replay, options, VideoFormat::Raw { fps: 10 });
+    assert_eq!(files[0].0, "frames.rgba");
+    assert_eq!(files[0].1.len(), 24 * 24 * 4 * 40);
+    let manifest = String::from_utf8(files[1].1.clone()).unwrap();
+    assert!(manifest.contains("-video_size 24x24 -framerate 10"));
+    assert!(manifest.ends_with("width 24\nheight 24\nframerate 10\nframes 40\n"));
+}
+
+#[test]
+fn screenshots_carry_their_game() {
+    let start = sim::board(
+        "
+        . * .
+        . . .
+    ",
+    )
+    .unwrap();
+    let screenshot = Screenshot {
+        seed: Some(1234),
+        configuration: Configuration {
+            variant: Variant::CLASSIC.with(Variant::NO_GUESS),
+            assisted: true,
+            ..Configuration::classic(3, 2, 1)
+        },
+        elapsed: Duration::from_millis(8250),
+        start,
+    };
+    let bytes = screenshot.png(2, 1, &[0; 8]);
+
+    let read = Screenshot::read(&bytes).unwrap();
+    assert_eq!(read.seed, Some(1234));
+    assert_eq!(read.configuration, screenshot.configuration);
+    assert_eq!(read.elapsed, Duration::from_millis(8250));
+    assert_eq!(read.start.cell(0, 1).cell_type, CellType::Mine);
+    assert!(matches!(
+        Screenshot::read(&png::encode(1, 1, &[0; 4], &[])),
+        Err(SaveError::NotASave)
+    ));
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use minesweeper_core::history::{self, Format};
+use minesweeper_core::save::{self, SaveError};
+use minesweeper_core::stats::{Record, Stats, Variant};
+
+fn record(finished: u64, won: bool, seed: Option<u64>) -> Record {
+    Record {
+        width: 9,
+        height: 9,
+        mines: 10,
+        won,
+        time: Duration::from_millis(12_345),
+        hints: 0,
+        clicks: 20,
+        wasted: 1,
+        flags: 10,
+        finished,
+        bbbv: 25,
+        opening_clicks: 3,
+        number_clicks: 5,
+        variant: Variant::NO_GUESS,
+        seed,
+    }
+}
+
+#[test]
+fn csv_has_a_header_and_a_row_per_game() {
+    let records = [
+        record(1_700_000_000, true, Some(42)),
+        // The leap day of a century divisible by 400
+        record(951_782_400, false, None),
+    ];
+    let csv = history::export(&records, Format::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], history::COLUMNS.join(","));
+    assert_eq!(
+        lines[1],
+        "1700000000-9x9x10-12345,1700000000,2023-11-14T22:13:20Z,9,9,10,no-guess,false,42,won,12345,25,20,1,10,0,3,5"
+    );
+    assert!(lines[2].starts_with("951782400-9x9x10-12345,951782400,2000-02-29T00:00:00Z,"));
+    assert!(lines[2].contains(",false,,lost,"));
+}
+
+#[test]
+fn json_is_an_array_of_objects_with_the_csv_columns() {
+    assert_eq!(history::export(&[], Format::Json), "[]\n");
+    let json = history::export(&[record(0, true, None)], Format::Json);
+    assert!(json.starts_with("[\n  {\"id\": \"0-9x9x10-12345\", \"finished\": 0, "));
+    assert!(json.contains("\"finished_utc\": \"1970-01-01T00:00:00Z\""));
+    assert!(json.contains("\"assisted\": false, \"seed\": null, \"result\": \"won\""));
+    assert!(json.ends_with("\"number_clicks\": 5}\n]\n"));
+    for column in history::COLUMNS {
+        assert!(json.contains(&format!("\"{}\": ", column)), "{}", column);
+    }
+}
+
+#[test]
+fn exports_import_back_as_the_same_games() {
+    let records = vec![
+        record(1_700_000_000, true, Some(42)),
+        Record {
+            hints: 2,
+            variant: Variant::CLUSTERED.with(Variant::AUTO_CHORD),
+            ..record(1_700_000_100, false, None)
+        },
+    ];
+    for format in [Format::Csv, Format::Json] {
+        let exported = history::export(&records, format);
+        let imported = history::import(exported.as_bytes()).unwrap();
+        assert_eq!(imported.records, records, "{:?}", format);
+        assert!(imported.unlocked.is_empty());
+    }
+}
+
+#[test]
+fn stats_files_import_whole() {
+    let mut stats = Stats::default();
+    stats.push(record(1_700_000_000, true, Some(7)));
+    stats.add_clicks(9, 9, &[(0, 0)]);
+    for format in [save::Format::Text, save::Format::Json, save::Format::Binary] {
+        let imported = history::import(&save::save(&stats, format)).unwrap();
+        assert_eq!(imported, stats, "{:?}", format);
+    }
+}
+
+#[test]
+fn broken_rows_name_their_cell() {
+    let csv = history::export(&[record(0, true, None)], Format::Csv);
+    let broken = csv.replace(",won,", ",drawn,");
+    assert_eq!(
+        history::import(broken.as_bytes()).unwrap_err(),
+        SaveError::Invalid {
+            field: "result of row 0".to_owned(),
+            value: "drawn".to_owned()
+        }
+    );
+    let short = csv.replace(",no-guess,", ",");
+    assert!(matches!(
+        history::import(short.as_bytes()),
+        Err(SaveError::Corrupt(_))
+    ));
+    let json = history::export(&[record(0, true, None)], Format::Json);
+    let missing = json.replace("\"bbbv\": 25, ", "");
+    assert_eq!(
+        history::import(missing.as_bytes()).unwrap_err(),
+        SaveError::Missing("bbbv of row 0".to_owned())
+    );
+}
@@ -0,0 +1,24 @@
+//! Inputs the fuzz targets in `fuzz/` turned up, kept so they stay fixed.
+
+use minesweeper_core::protocol::ServerMessage;
+use minesweeper_core::script::Script;
+
+#[test]
+fn states_whose_size_overflows_are_rejected() {
+    let line = format!("STATE {} 2 0 0 0 0 HH", usize::MAX);
+    assert_eq!(ServerMessage::decode(&line), None);
+}
+
+#[test]
+fn deeply_nested_conditions_are_rejected() {
+    let nested = format!(
+        "rule {}1{} => reveal",
+        "(".repeat(50_000),
+        ")".repeat(50_000)
+    );
+    assert!(Script::parse("nested", &nested).is_err());
+    let chained = format!("rule {}1 => reveal", "-".repeat(50_000));
+    assert!(Script::parse("chained", &chained).is_err());
+    let long = format!("rule 1{} => reveal", " + 1".repeat(50_000));
+    assert!(Script::parse("long", &long).is_err());
+}
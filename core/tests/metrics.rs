@@ -0,0 +1,85 @@
+use minesweeper_core::metrics::{self, Benchmarks, Openings, Waste};
+use minesweeper_core::sim;
+
+fn bbbv(layout: &str) -> usize {
+    metrics::bbbv(&sim::board(layout).expect("valid layout"))
+}
+
+#[test]
+fn an_opening_takes_one_click() {
+    assert_eq!(bbbv(". . .\n. . .\n. . ."), 1);
+}
+
+#[test]
+fn openings_uncover_the_numbers_around_them() {
+    // One opening on each side of the wall of mines
+    let layout = "
+        . . * . .
+        . . * . .
+        . . * . .
+    ";
+    assert_eq!(bbbv(layout), 2);
+}
+
+#[test]
+fn numbers_outside_openings_take_a_click_each() {
+    assert_eq!(bbbv(". * .\n* . *\n. * ."), 5);
+    assert_eq!(bbbv("*"), 0);
+}
+
+#[test]
+fn openings_are_counted_with_their_borders() {
+    let layout = "
+        . . * . .
+        . . * . .
+        . . * * *
+        . . . . .
+    ";
+    let board = sim::board(layout).unwrap();
+    let openings = Openings::of(&board);
+    // The left column with its numbers, and the top right corner; the
+    // numbers along the bottom right are in neither
+    assert_eq!(openings.sizes(), [8, 4]);
+    assert_eq!(openings.at(1, 1), Some(0));
+    assert_eq!(openings.at(1, 3), Some(1));
+    assert_eq!(openings.at(3, 3), None);
+    assert_eq!(metrics::bbbv(&board), 5);
+}
+
+#[test]
+fn zini_chords_where_it_saves_clicks() {
+    // Eight lone numbers around one mine: opening an edge, flagging and
+    // chording clears most of them in three clicks
+    let board = sim::board(". . .\n. * .\n. . .").unwrap();
+    assert_eq!(
+        Benchmarks::of(&board),
+        Benchmarks {
+            bbbv: 8,
+            zini: 5,
+            hzini: 5,
+        }
+    );
+    // Nothing to gain by chording an opening
+    let board = sim::board(". . .\n. . .\n. . .").unwrap();
+    assert_eq!((metrics::zini(&board), metrics::hzini(&board)), (1, 1));
+    let board = sim::board(". * .\n* . *\n. * .").unwrap();
+    assert_eq!(metrics::zini(&board), 5);
+}
+
+#[test]
+fn waste_counts_flags_no_chord_used() {
+    let board = sim::board(". . .\n. * .\n. . .").unwrap();
+    let moves = sim::moves("r 0 0; f 2 2; f 1 1; c 0 0; f 0 0; f 2 2; r 2 2").unwrap();
+    // The flag on the mine pays off in the chord; the other is taken back
+    assert_eq!(
+        metrics::waste(&board, &moves),
+        [
+            (1, Waste::UnusedFlag),
+            (4, Waste::NoEffect),
+            (5, Waste::Unflag)
+        ]
+    );
+    // A flag left standing to the end is wasted as well
+    let moves = sim::moves("r 0 0; f 1 1; r 0 1").unwrap();
+    assert_eq!(metrics::waste(&board, &moves), [(1, Waste::UnusedFlag)]);
+}
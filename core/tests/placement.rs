@@ -0,0 +1,141 @@
+use minesweeper_core::placement::{self, Clustered, Dispersed, Gradient, Ramp, Uniform};
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::stats::Variant;
+use minesweeper_core::{Board, CellType, Pos};
+
+fn mines(board: &Board) -> Vec<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+        .filter(|&(row, col)| board.cell(row, col).cell_type == CellType::Mine)
+        .collect()
+}
+
+/// Pairs of mines side by side or diagonal, each counted once.
+fn touching(board: &Board) -> usize {
+    let mines = mines(board);
+    mines
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| mines[i + 1..].iter().map(move |b| (a, b)))
+        .filter(|(a, b)| a.0.abs_diff(b.0) <= 1 && a.1.abs_diff(b.1) <= 1)
+        .count()
+}
+
+#[test]
+fn every_generator_places_all_mines_off_the_start() {
+    let start = Pos::new(8, 15);
+    for generator in placement::ALL {
+        for seed in 0..20 {
+            let board = Board::placed(30, 16, 99, seed, Some(start), generator).unwrap();
+            assert_eq!(mines(&board).len(), 99, "{}", generator.name());
+            for row in 7..=9 {
+                for col in 14..=16 {
+                    assert_ne!(board.cell(row, col).cell_type, CellType::Mine);
+                }
+            }
+            let again = Board::placed(30, 16, 99, seed, Some(start), generator).unwrap();
+            assert_eq!(mines(&board), mines(&again));
+        }
+    }
+}
+
+#[test]
+fn uniform_placement_keeps_old_seeds() {
+    for seed in 0..10 {
+        let placed = Board::placed(16, 16, 40, seed, None, &Uniform).unwrap();
+        assert_eq!(
+            mines(&placed),
+            mines(&Board::with_seed(16, 16, 40, seed).unwrap())
+        );
+    }
+}
+
+#[test]
+fn dispersed_mines_never_touch_while_there_is_room() {
+    for seed in 0..20 {
+        let board = Board::placed(16, 16, 20, seed, None, &Dispersed).unwrap();
+        assert_eq!(touching(&board), 0);
+    }
+    // A board too full to space out still gets every mine
+    let board = Board::placed(5, 5, 20, 1, None, &Dispersed).unwrap();
+    assert_eq!(mines(&board).len(), 20);
+}
+
+#[test]
+fn clustered_mines_touch_more_than_uniform_ones() {
+    let total = |generator| {
+        (0..20)
+            .map(|seed| touching(&Board::placed(30, 16, 60, seed, None, generator).unwrap()))
+            .sum::<usize>()
+    };
+    assert!(total(&Clustered) > 2 * total(&Uniform));
+}
+
+#[test]
+fn gradient_mines_gather_at_the_bottom() {
+    let (mut top, mut bottom) = (0, 0);
+    for seed in 0..20 {
+        let board = Board::placed(20, 20, 60, seed, None, &Gradient).unwrap();
+        for (row, _) in mines(&board) {
+            if row < 10 {
+                top += 1;
+            } else {
+                bottom += 1;
+            }
+        }
+    }
+    assert!(bottom > top * 3 / 2, "{} above, {} below", top, bottom);
+}
+
+#[test]
+fn share_codes_carry_the_placement() {
+    let code: ShareCode = "16x16x40-no-guess+clustered-9".parse().unwrap();
+    assert_eq!(
+        code.variant,
+        Variant::NO_GUESS.with(Variant::placement("clustered"))
+    );
+    assert_eq!(code.variant.generator().name(), "clustered");
+    assert_eq!(Variant::CLASSIC.generator().name(), "uniform");
+    assert_eq!(mines(&code.board().unwrap()).len(), 40);
+}
+
+#[test]
+fn ramp_rows_follow_the_densities() {
+    let ramp = Ramp::STEEP;
+    let (width, height) = (40, 20);
+    let mines_count = ramp.mines(width, height);
+    assert_eq!(mines_count, 160);
+    assert_eq!(ramp.ends(width, height, mines_count), (5.0, 35.0));
+    for seed in 0..5 {
+        let board = Board::placed(width, height, mines_count, seed, None, &ramp).unwrap();
+        let mut rows = vec![0; height];
+        for (row, _) in mines(&board) {
+            rows[row] += 1;
+        }
+        assert_eq!(rows.iter().sum::<usize>(), mines_count);
+        for (row, &count) in rows.iter().enumerate() {
+            let density = 5.0 + 30.0 * row as f64 / (height - 1) as f64;
+            let expected = density * width as f64 / 100.0;
+            assert!(
+                (count as f64 - expected).abs() <= 1.0,
+                "row {} has {} mines, not about {}",
+                row,
+                count,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn a_ramp_spills_over_rows_with_no_room() {
+    // The start's opening fills most of the short board's dense rows
+    let start = Pos::new(4, 2);
+    for seed in 0..5 {
+        let board = Board::placed(5, 6, 18, seed, Some(start), &Ramp::STEEP).unwrap();
+        assert_eq!(mines(&board).len(), 18);
+    }
+    let code: ShareCode = "16x16x51-ramp-3".parse().unwrap();
+    assert_eq!(code.variant.generator().name(), "ramp");
+    assert_eq!(mines(&code.board().unwrap()).len(), 51);
+}
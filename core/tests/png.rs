@@ -0,0 +1,179 @@
+use minesweeper_core::png::{self, PngError};
+
+/// Reads the bits of a deflate stream, least significant first.
+struct Bits<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Bits<'_> {
+    fn read(&mut self, count: u32) -> u32 {
+        (0..count).fold(0, |value, i| {
+            let bit = self.bytes[self.position / 8] >> (self.position % 8) & 1;
+            self.position += 1;
+            value | u32::from(bit) << i
+        })
+    }
+
+    /// Reads a Huffman code, most significant bit first.
+    fn code(&mut self, count: u32) -> u32 {
+        (0..count).fold(0, |value, _| value << 1 | self.read(1))
+    }
+}
+
+/// Inflates a zlib stream made of fixed Huffman blocks, which is all the
+/// encoder writes.
+fn inflate(zlib: &[u8]) -> Vec<u8> {
+    const LENGTHS: [(usize, u32); 29] = [
+        (3, 0),
+        (4, 0),
+        (5, 0),
+        (6, 0),
+        (7, 0),
+        (8, 0),
+        (9, 0),
+        (10, 0),
+        (11, 1),
+        (13, 1),
+        (15, 1),
+        (17, 1),
+        (19, 2),
+        (23, 2),
+        (27, 2),
+        (31, 2),
+        (35, 3),
+        (43, 3),
+        (51, 3),
+        (59, 3),
+        (67, 4),
+        (83, 4),
+        (99, 4),
+        (115, 4),
+        (131, 5),
+        (163, 5),
+        (195, 5),
+        (227, 5),
+        (258, 0),
+    ];
+    assert_eq!(zlib[0] & 0x0f, 8, "deflate");
+    let mut bits = Bits {
+        bytes: &zlib[2..],
+        position: 0,
+    };
+    let mut output: Vec<u8> = Vec::new();
+    loop {
+        let last = bits.read(1) == 1;
+        assert_eq!(bits.read(2), 1, "fixed Huffman block");
+        loop {
+            let mut code = bits.code(7);
+            let symbol = if code < 0x18 {
+                code + 256
+            } else {
+                code = code << 1 | bits.code(1);
+                match code {
+                    0x30..=0xbf => code - 0x30,
+                    0xc0..=0xc7 => code - 0xc0 + 280,
+                    _ => (code << 1 | bits.code(1)) - 0x190 + 144,
+                }
+            };
+            match symbol {
+                0..=255 => output.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let (base, extra) = LENGTHS[symbol as usize - 257];
+                    let length = base + bits.read(extra) as usize;
+                    let code = bits.code(5) as usize;
+                    let distance = if code < 4 {
+                        code + 1
+                    } else {
+                        let extra = code as u32 / 2 - 1;
+                        ((2 + code % 2) << extra) + 1 + bits.read(extra) as usize
+                    };
+                    for _ in 0..length {
+                        output.push(output[output.len() - distance]);
+                    }
+                }
+            }
+        }
+        if last {
+            return output;
+        }
+    }
+}
+
+/// The concatenated data of every chunk of `kind`.
+fn chunks(bytes: &[u8], kind: &[u8]) -> Vec<u8> {
+    let mut rest = &bytes[8..];
+    let mut data = Vec::new();
+    while !rest.is_empty() {
+        let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        if &rest[4..8] == kind {
+            data.extend(&rest[8..8 + length]);
+        }
+        rest = &rest[12 + length..];
+    }
+    data
+}
+
+#[test]
+fn pixels_survive_compression() {
+    // Flat areas for long matches, and noise for literals
+    let (width, height) = (120u32, 90u32);
+    let mut rgba = vec![0u8; width as usize * height as usize * 4];
+    for (i, pixel) in rgba.chunks_mut(4).enumerate() {
+        let (x, y) = (i % width as usize, i / width as usize);
+        let value = if x < 60 {
+            200
+        } else {
+            (i.wrapping_mul(2_654_435_761) >> 13) as u8
+        };
+        pixel.copy_from_slice(&[value, (y * 2) as u8, 30, 255]);
+    }
+    let bytes = png::encode(width, height, &rgba, &[]);
+
+    let header = chunks(&bytes, b"IHDR");
+    assert_eq!(header[..8], [0, 0, 0, 120, 0, 0, 0, 90]);
+    let raw = inflate(&chunks(&bytes, b"IDAT"));
+    let rows: Vec<&[u8]> = raw.chunks(width as usize * 4 + 1).collect();
+    assert_eq!(rows.len(), 90);
+    for (row, pixels) in rows.iter().zip(rgba.chunks(width as usize * 4)) {
+        assert_eq!(row[0], 0);
+        assert_eq!(&row[1..], pixels);
+    }
+    assert!(bytes.len() < rgba.len() * 3 / 4);
+}
+
+#[test]
+fn text_chunks_read_back_in_order() {
+    let text = [
+        ("Software", "minesweeper"),
+        ("Comment", "two\nlines, ünïcode ✓"),
+    ];
+    let bytes = png::encode(1, 1, &[1, 2, 3, 4], &text);
+
+    let read = png::text(&bytes).unwrap();
+    assert_eq!(read[0], ("Software".to_owned(), "minesweeper".to_owned()));
+    // Latin-1 has no check mark
+    assert_eq!(read[1].1, "two\nlines, ünïcode ?");
+}
+
+#[test]
+fn damaged_files_are_refused() {
+    let bytes = png::encode(1, 1, &[1, 2, 3, 4], &[("Title", "board")]);
+
+    assert_eq!(png::text(b"GIF89a"), Err(PngError::NotAPng));
+    assert_eq!(
+        png::text(&bytes[..bytes.len() - 4]),
+        Err(PngError::Truncated)
+    );
+    let mut damaged = bytes.clone();
+    let at = damaged
+        .windows(5)
+        .position(|window| window == b"board")
+        .unwrap();
+    damaged[at] = b'B';
+    assert_eq!(
+        png::text(&damaged),
+        Err(PngError::Checksum("tEXt".to_owned()))
+    );
+}
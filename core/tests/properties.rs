@@ -0,0 +1,187 @@
+use minesweeper_core::verify::{self, Case};
+use minesweeper_core::{Board, Pos};
+
+fn check(case: Case) {
+    if let Err(violation) = case.check() {
+        panic!("{:?}: {}", case, violation);
+    }
+}
+
+#[test]
+fn random_boards_keep_every_invariant() {
+    for seed in 0..4 {
+        if let Err((case, violation)) = verify::run(seed, 250, 40) {
+            panic!("{:?}: {}", case, violation);
+        }
+    }
+}
+
+#[test]
+fn thin_boards_keep_every_invariant() {
+    for seed in 0..50 {
+        for (width, height) in [(1, 30), (30, 1), (2, 17), (17, 2)] {
+            let mines = seed as usize % (width * height);
+            check(Case {
+                width,
+                height,
+                mines,
+                seed,
+            });
+        }
+    }
+}
+
+#[test]
+fn extreme_densities_keep_every_invariant() {
+    for seed in 0..20 {
+        for (width, height) in [(1, 1), (3, 3), (9, 9), (16, 30)] {
+            let cells = width * height;
+            for mines in [0, cells / 2, cells - 1] {
+                check(Case {
+                    width,
+                    height,
+                    mines,
+                    seed,
+                });
+            }
+        }
+    }
+}
+
+#[test]
+fn safe_starts_keep_every_invariant() {
+    for seed in 0..100 {
+        let board = Board::with_safe_start(30, 16, 99, seed, Pos::new(seed as usize % 16, 7))
+            .expect("99 mines fit");
+        if let Err(violation) = verify::board(&board) {
+            panic!("seed {}: {}", seed, violation);
+        }
+    }
+}
+
+#[test]
+fn broken_boards_are_caught() {
+    use minesweeper_core::sim;
+    use minesweeper_core::verify::Violation;
+    use minesweeper_core::{Cell, CellState, CellType};
+
+    let mut board = sim::board("* . .\n. . .").unwrap();
+    board.set_cell(0, 2, Cell::new(CellType::Number(1), CellState::Hidden));
+    assert_eq!(
+        verify::numbers(&board),
+        Err(Violation::WrongNumber {
+            row: 0,
+            col: 2,
+            expected: 0,
+            found: CellType::Number(1)
+        })
+    );
+
+    let mut board = sim::board(". . .\n. . .").unwrap();
+    board.set_cell(1, 2, Cell::new(CellType::Mine, CellState::Hidden));
+    assert_eq!(
+        verify::mine_count(&board),
+        Err(Violation::MineCount {
+            expected: 0,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn moved_mines_keep_the_numbers_right() {
+    use minesweeper_core::{sim, CellState, CellType};
+
+    let mut board = sim::board("* . .\n. . .\n. . *").unwrap();
+    // Next to where it was, onto a number counting it
+    assert!(board.move_mine(Pos::new(0, 0), Pos::new(1, 1)));
+    assert_eq!(verify::numbers(&board), Ok(()));
+    assert_eq!(board.cell(0, 0).cell_type, CellType::Number(1));
+    assert_eq!(board.cell(1, 2).cell_type, CellType::Number(2));
+    assert_eq!(verify::mine_count(&board), Ok(()));
+
+    // Far away
+    assert!(board.move_mine(Pos::new(2, 2), Pos::new(0, 2)));
+    assert_eq!(verify::numbers(&board), Ok(()));
+    assert_eq!(board.cell(2, 2).cell_type, CellType::Number(1));
+
+    // Only hidden mines move, and only onto hidden cells without one
+    assert!(!board.move_mine(Pos::new(0, 0), Pos::new(2, 0)));
+    assert!(!board.move_mine(Pos::new(1, 1), Pos::new(0, 2)));
+    assert!(!board.move_mine(Pos::new(1, 1), Pos::new(5, 5)));
+    board.reveal(2, 0);
+    assert_eq!(board.cell(2, 0).cell_state, CellState::Visible);
+    let revision = board.revision();
+    assert!(!board.move_mine(Pos::new(1, 1), Pos::new(2, 0)));
+    assert_eq!(board.revision(), revision);
+}
+
+#[test]
+fn reused_floods_uncover_what_fresh_ones_do() {
+    use minesweeper_core::{CellState, CellType, Flood};
+
+    let mut board = Board::with_seed(30, 16, 40, 7).unwrap();
+    let mut fresh = board.clone();
+    let mut flood = Flood::default();
+    let mut opened = Vec::new();
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            if board.cell(row, col).cell_type != CellType::Empty
+                || board.cell(row, col).cell_state != CellState::Hidden
+            {
+                continue;
+            }
+            assert!(board.restart_reveal(row, col, &mut flood));
+            board.spread(&mut flood, usize::MAX);
+            assert_eq!(flood.revealed(), fresh.reveal_cells(row, col));
+            opened.push((row, col));
+        }
+    }
+    // Nothing is left over from the last opening when a reveal is refused
+    let (row, col) = opened[0];
+    assert!(!board.restart_reveal(row, col, &mut flood));
+    assert!(flood.revealed().is_empty() && flood.is_done());
+}
+
+#[test]
+fn numbers_are_right_at_every_edge() {
+    use minesweeper_core::{sim, CellType};
+
+    let types = |layout: &str| -> Vec<CellType> {
+        let board = sim::board(layout).unwrap();
+        assert_eq!(verify::numbers(&board), Ok(()));
+        (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .map(|(row, col)| board.cell(row, col).cell_type)
+            .collect()
+    };
+    use CellType::{Empty, Mine, Number};
+    assert_eq!(types("*"), [Mine]);
+    assert_eq!(types("."), [Empty]);
+    assert_eq!(types("* . * *"), [Mine, Number(2), Mine, Mine]);
+    assert_eq!(types("*\n.\n.\n*"), [Mine, Number(1), Number(1), Mine]);
+    assert_eq!(
+        types("* * *\n* . *\n* * *"),
+        [Mine, Mine, Mine, Mine, Number(8), Mine, Mine, Mine, Mine]
+    );
+    assert_eq!(
+        types(". . *\n. . .\n* . ."),
+        [
+            Empty,
+            Number(1),
+            Mine,
+            Number(1),
+            Number(2),
+            Number(1),
+            Mine,
+            Number(1),
+            Empty
+        ]
+    );
+
+    // Full boards but for one cell, where every number is as high as it gets
+    for (width, height) in [(1, 9), (9, 1), (2, 2), (17, 5)] {
+        let board = Board::with_seed(width, height, width * height - 1, 11).unwrap();
+        assert_eq!(verify::numbers(&board), Ok(()));
+    }
+}
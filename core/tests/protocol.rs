@@ -0,0 +1,74 @@
+use minesweeper_core::protocol::{self, CellChange, CellView, ServerMessage};
+
+#[test]
+fn numbers_past_nine_round_trip() {
+    let cells: Vec<CellView> = (0..=25)
+        .map(CellView::Number)
+        .chain([
+            CellView::Hidden,
+            CellView::Flagged,
+            CellView::Empty,
+            CellView::Mine,
+            CellView::Captured(0),
+            CellView::Captured(1),
+            CellView::Unread,
+        ])
+        .collect();
+    let state = ServerMessage::State {
+        width: cells.len(),
+        height: 1,
+        mines: 3,
+        scores: [1, 2],
+        turn: 1,
+        cells: cells.clone(),
+    };
+    assert_eq!(ServerMessage::decode(&state.encode()), Some(state));
+
+    let delta = ServerMessage::Delta {
+        scores: [0, 0],
+        turn: 0,
+        changes: cells
+            .iter()
+            .enumerate()
+            .map(|(col, &view)| CellChange { row: 0, col, view })
+            .collect(),
+    };
+    assert_eq!(ServerMessage::decode(&delta.encode()), Some(delta));
+}
+
+#[test]
+fn impossible_scores_and_turns_are_rejected() {
+    for line in [
+        "STATE 2 1 3 2 2 0 ##",
+        "STATE 2 1 3 0 0 2 ##",
+        &format!("STATE 2 1 3 {} 1 0 ##", usize::MAX),
+        "DELTA 0 0 2",
+    ] {
+        assert_eq!(ServerMessage::decode(line), None, "{}", line);
+    }
+
+    let (mut board, mut game) =
+        protocol::restore(2, 1, 3, [1, 0], 0, &[CellView::Hidden, CellView::Hidden]);
+    let change = CellChange {
+        row: 0,
+        col: 0,
+        view: CellView::Captured(1),
+    };
+    assert!(!protocol::apply_delta(
+        &mut board,
+        &mut game,
+        [2, 2],
+        1,
+        &[change]
+    ));
+    assert_eq!(game.scores(), [1, 0]);
+    assert_eq!(board.cell(0, 0), CellView::Hidden.to_cell());
+    assert!(protocol::apply_delta(
+        &mut board,
+        &mut game,
+        [1, 1],
+        1,
+        &[change]
+    ));
+    assert_eq!(game.mines_left(), 1);
+}
@@ -0,0 +1,94 @@
+use minesweeper_core::generate::{self, Progress};
+use minesweeper_core::placement::Uniform;
+use minesweeper_core::rating::{self, Rating};
+use minesweeper_core::{sim, Board, BoardError, CellType, Pos};
+
+#[test]
+fn a_fifty_fifty_halves_the_survival() {
+    let board = sim::board("o o\no o\n* .").unwrap();
+    let rating = Rating::of(&board);
+    assert_eq!(rating.guesses, 1);
+    assert_eq!(rating.survival, 0.5);
+    // Once the mine is set aside, the last cell follows from single numbers
+    assert_eq!(rating.complexity, 0.0);
+}
+
+#[test]
+fn boards_without_guesses_are_survived() {
+    let start = Pos::new(8, 15);
+    for seed in 0..3 {
+        let mut board = generate::no_guess(30, 16, 99, start, seed, &Uniform).unwrap();
+        board.reveal(start.row, start.col);
+        let rating = Rating::of(&board);
+        assert_eq!((rating.guesses, rating.survival), (0, 1.0), "seed {}", seed);
+        assert!(rating.complexity < 1.0);
+    }
+}
+
+#[test]
+fn the_first_click_is_a_guess() {
+    let board = sim::board(". . .\n. . .\n. . *").unwrap();
+    let rating = Rating::of(&board);
+    assert!(rating.guesses >= 1);
+    assert!(rating.survival <= 1.0 - 1.0 / 9.0 + 1e-9);
+    // Untouched boards are played as dealt
+    assert_eq!(board.revealed(), 0);
+}
+
+#[test]
+fn expert_boards_rate_above_beginner_ones() {
+    let mean = |width, height, mines| {
+        (0..10)
+            .map(|seed| {
+                let board = Board::with_seed(width, height, mines, seed).unwrap();
+                Rating::of(&board).score()
+            })
+            .sum::<f64>()
+            / 10.0
+    };
+    assert!(mean(30, 16, 99) > mean(9, 9, 10));
+    for seed in 0..10 {
+        let stars = Rating::of(&Board::with_seed(9, 9, 10, seed).unwrap()).stars();
+        assert!((1..=5).contains(&stars));
+    }
+}
+
+#[test]
+fn found_boards_have_the_rating_asked_for() {
+    let deal = |seed| Board::with_seed(16, 16, 40, seed);
+    let progress = Progress::default();
+    let (seed, board) = rating::find(deal, 3, 7, &progress).unwrap();
+    assert_eq!(Rating::of(&board).stars(), 3);
+    // The seed deals the same board again, so the game can be shared
+    let mines = |board: &Board| {
+        (0..16)
+            .flat_map(|row| (0..16).map(move |col| (row, col)))
+            .filter(|&(row, col)| board.cell(row, col).cell_type == CellType::Mine)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(mines(&deal(seed).unwrap()), mines(&board));
+    assert_eq!(progress.tried(), seed - 7 + 1);
+}
+
+#[test]
+fn the_search_gives_up_on_ratings_that_never_turn_up() {
+    let progress = Progress::default();
+    let found = rating::find(|seed| Board::with_seed(3, 3, 1, seed), 5, 0, &progress);
+    assert_eq!(
+        found.unwrap_err(),
+        BoardError::RatingNotFound {
+            stars: 5,
+            attempts: rating::MAX_ATTEMPTS
+        }
+    );
+    assert_eq!(progress.tried(), rating::MAX_ATTEMPTS);
+}
+
+#[test]
+fn a_cancelled_search_stops() {
+    let progress = Progress::default();
+    progress.cancel();
+    let found = rating::find(|seed| Board::with_seed(16, 16, 40, seed), 3, 0, &progress);
+    assert_eq!(found.unwrap_err(), BoardError::Cancelled);
+    assert_eq!(progress.tried(), 0);
+}
@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use minesweeper_core::replay::{self, Mark, Replay, VerifyError};
+use minesweeper_core::save::{self, Format, SaveError};
+use minesweeper_core::sim::{self, Move};
+use minesweeper_core::Game;
+
+#[test]
+fn marks_tell_what_each_move_did() {
+    let layout = "
+        . . *
+        . . .
+        . . *
+    ";
+    let mut replay = Replay::new(sim::board(layout).unwrap());
+    let moves = [
+        Move::Reveal(0, 0),
+        Move::Flag(0, 2),
+        // Clicking a revealed number chords it, even when that does nothing
+        Move::Reveal(1, 1),
+        Move::Reveal(1, 2),
+        // The game is won by now
+        Move::Reveal(2, 2),
+    ];
+    for (i, &step) in moves.iter().enumerate() {
+        replay.push(Duration::from_secs(i as u64), step);
+    }
+    let marks = replay.marks();
+    assert_eq!(
+        marks.iter().map(|&(_, mark)| mark).collect::<Vec<_>>(),
+        [Mark::Opening, Mark::Flag, Mark::Chord, Mark::Click]
+    );
+    assert_eq!(marks[3].0, Duration::from_secs(3));
+}
+
+/// Records `moves` as they are played on `live`, a game started on
+/// `start` or on a board that has since been changed.
+fn recorded(start: &str, mut live: Game, moves: &[(u64, Move)]) -> Replay {
+    let mut replay = Replay::new(sim::board(start).unwrap());
+    for &(millis, step) in moves {
+        replay.push(Duration::from_millis(millis), step);
+        replay::play(&mut live, step);
+    }
+    replay.finish(&live);
+    replay
+}
+
+const MOVES: [(u64, Move); 3] = [
+    (0, Move::Reveal(0, 0)),
+    (1500, Move::Flag(0, 2)),
+    (2750, Move::Reveal(1, 2)),
+];
+
+/// A won game on the board from `marks_tell_what_each_move_did`, saved as
+/// text so tests can edit it.
+fn saved() -> String {
+    let layout = ". . *\n. . .\n. . *";
+    let live = Game::new(sim::board(layout).unwrap());
+    let replay = recorded(layout, live, &MOVES);
+    String::from_utf8(save::save(&replay, Format::Text)).unwrap()
+}
+
+#[test]
+fn saved_replays_verify_in_every_format() {
+    let text = saved();
+    assert!(text.contains("result won\n"));
+    assert!(text.contains("millis 2750\n"));
+    let replay = replay::verify(text.as_bytes()).unwrap();
+    assert_eq!(replay.len(), 3);
+    for format in [Format::Json, Format::Binary] {
+        let bytes = save::save(&replay, format);
+        assert_eq!(replay::verify(&bytes).unwrap().moves(), replay.moves());
+    }
+}
+
+#[test]
+fn edited_replays_are_refused() {
+    let text = saved();
+    let edits = [
+        ("result won", "result lost", "result"),
+        ("millis 2750", "millis 1999", "millis"),
+        // Dropping the last move leaves the game unfinished
+        (";2750 r 1 2", "", "result"),
+        // Revealing a mine instead ends it lost
+        ("2750 r 1 2", "2750 r 2 2", "result"),
+    ];
+    for (from, to, field) in edits {
+        let edited = text.replace(from, to);
+        assert_ne!(edited, text);
+        assert_eq!(
+            replay::verify(edited.as_bytes()).unwrap_err(),
+            VerifyError::Mismatch { field },
+            "{} -> {}",
+            from,
+            to
+        );
+    }
+}
+
+#[test]
+fn replays_that_differ_from_the_game_played_are_refused() {
+    // The live game's mine at the bottom right had moved up one, so the
+    // last click lost it; the moves alone would have won
+    let layout = ". . *\n. . .\n. . *";
+    let live = Game::new(sim::board(". . *\n. . *\n. . .").unwrap());
+    let replay = recorded(layout, live, &MOVES);
+    let bytes = save::save(&replay, Format::Text);
+    assert!(String::from_utf8_lossy(&bytes).contains("result lost\n"));
+    assert_eq!(
+        replay::verify(&bytes).unwrap_err(),
+        VerifyError::Mismatch { field: "result" }
+    );
+
+    // A replay nobody finished has no record to hold the moves to
+    let mut unfinished = Replay::new(sim::board(layout).unwrap());
+    unfinished.push(Duration::ZERO, Move::Reveal(0, 0));
+    assert_eq!(
+        replay::verify(&save::save(&unfinished, Format::Text)).unwrap_err(),
+        VerifyError::Save(SaveError::Missing("result".to_owned()))
+    );
+}
+
+#[test]
+fn replays_under_other_rules_or_with_bad_moves_are_refused() {
+    let text = saved();
+    // Recorded before openings spread diagonally
+    let other = text.replace(&format!("rules {}\n", replay::RULES), "rules 1\n");
+    assert_ne!(other, text);
+    assert_eq!(
+        replay::verify(other.as_bytes()).unwrap_err(),
+        VerifyError::Rules {
+            recorded: 1,
+            supported: replay::RULES
+        }
+    );
+    for moves in ["0 r 0 0;1500 x 0 2", "0 r 0 9", "1500 r 0 0;0 f 0 2"] {
+        let edited = text.replace("0 r 0 0;1500 f 0 2;2750 r 1 2", moves);
+        assert!(matches!(
+            replay::verify(edited.as_bytes()),
+            Err(VerifyError::Save(SaveError::Invalid { .. }))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use minesweeper_core::review::{Review, Verdict};
+use minesweeper_core::rules::Ruleset;
+use minesweeper_core::sim::{self, Move};
+use minesweeper_core::CellState;
+
+/// The numbers along the middle row prove (2, 1) is the mine and its
+/// neighbours are safe; the bottom rows are out of their reach.
+const FORCED: &str = "
+    o o o
+    o o o
+    . * .
+    . . .
+    . . *
+";
+
+fn verdicts(layout: &str, script: &str) -> Vec<Verdict> {
+    let review = Review::new(sim::board(layout).unwrap(), &sim::moves(script).unwrap());
+    review.notes().iter().map(|note| note.verdict).collect()
+}
+
+#[test]
+fn proven_moves_are_forced_and_proven_mines_are_blunders() {
+    assert_eq!(
+        verdicts(FORCED, "r 2 0; f 2 1; r 2 2"),
+        [
+            Verdict::Forced,
+            Verdict::Flag { proven: true },
+            Verdict::Forced
+        ]
+    );
+    assert_eq!(verdicts(FORCED, "r 2 1"), [Verdict::Blunder]);
+}
+
+#[test]
+fn guessing_while_a_safe_cell_is_known_is_unforced() {
+    // One mine left among the six cells nothing proves anything about
+    let [Verdict::Unforced { risk }] = verdicts(FORCED, "r 4 0")[..] else {
+        panic!("expected an unforced guess");
+    };
+    assert!((risk - 1.0 / 6.0).abs() < 1e-9, "risk {}", risk);
+}
+
+#[test]
+fn a_fifty_fifty_is_a_guess() {
+    let layout = "
+        o o
+        o o
+        * .
+    ";
+    assert_eq!(verdicts(layout, "r 2 1"), [Verdict::Guess { risk: 0.5 }]);
+}
+
+#[test]
+fn the_first_click_and_moves_that_change_nothing() {
+    let layout = "
+        . .
+        . *
+    ";
+    assert_eq!(
+        verdicts(layout, "f 0 0; r 0 0; f 0 0; r 0 0"),
+        [
+            Verdict::Flag { proven: false },
+            Verdict::Wasted,
+            Verdict::Unflag,
+            Verdict::Opening,
+        ]
+    );
+}
+
+#[test]
+fn moves_under_custom_rules_are_not_judged() {
+    let start = sim::board(FORCED).unwrap().with_rules(Ruleset {
+        lives: 2,
+        ..Ruleset::CLASSIC
+    });
+    let review = Review::new(start, &sim::moves("r 2 0; r 2 1; f 2 0").unwrap());
+    let verdicts: Vec<_> = review.notes().iter().map(|note| note.verdict).collect();
+    assert_eq!(
+        verdicts,
+        [Verdict::Unjudged, Verdict::Unjudged, Verdict::Wasted]
+    );
+}
+
+#[test]
+fn positions_follow_the_game_and_stop_when_it_ends() {
+    let start = sim::board(FORCED).unwrap();
+    let review = Review::new(start.clone(), &sim::moves("r 2 0; r 2 1; r 2 2").unwrap());
+    assert_eq!(review.len(), 2);
+    assert_eq!(review.position(0).cell(2, 0).cell_state, CellState::Hidden);
+    assert_eq!(review.position(1).cell(2, 0).cell_state, CellState::Visible);
+    assert_eq!(review.position(2).cell(2, 1).cell_state, CellState::Visible);
+    assert_eq!(review.notes()[1].step, Move::Reveal(2, 1));
+}
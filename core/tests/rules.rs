@@ -0,0 +1,545 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use minesweeper_core::agent::View;
+use minesweeper_core::placement::{Clustered, Uniform};
+use minesweeper_core::protocol::CellView;
+use minesweeper_core::rules::{Adjacency, Reveal, Ruleset, Win};
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::stats::Variant;
+use minesweeper_core::{verify, Board, CellState, CellType, Event, Game, MineKind, Pos, Status};
+
+fn cells(board: &Board) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..board.height()).flat_map(move |row| (0..board.width()).map(move |col| (row, col)))
+}
+
+/// The mines a number at `(row, col)` should count, worked out from the
+/// rules by hand rather than through the board.
+fn expected(board: &Board, row: usize, col: usize) -> usize {
+    let rules = board.rules();
+    let (width, height) = (board.width() as isize, board.height() as isize);
+    let mut seen = Vec::new();
+    for &(dr, dc) in rules.adjacency.offsets() {
+        let (mut r, mut c) = (row as isize + dr, col as isize + dc);
+        if rules.wrap {
+            r = r.rem_euclid(height);
+            c = c.rem_euclid(width);
+        }
+        let inside = (0..height).contains(&r) && (0..width).contains(&c);
+        let near = (r as usize, c as usize);
+        if inside && near != (row, col) && !seen.contains(&near) {
+            seen.push(near);
+        }
+    }
+    seen.into_iter().map(|(r, c)| board.mines_at(r, c)).sum()
+}
+
+fn check_numbers(board: &Board) {
+    for (row, col) in cells(board) {
+        let want = expected(board, row, col);
+        let found = match board.cell(row, col).cell_type {
+            CellType::Mine => continue,
+            CellType::Empty => 0,
+            CellType::Number(n) => n,
+        };
+        assert_eq!(found, want, "({}, {}) under {:?}", row, col, board.rules());
+    }
+}
+
+fn rules(adjacency: Adjacency, wrap: bool, mines_per_cell: u8) -> Ruleset {
+    Ruleset {
+        adjacency,
+        wrap,
+        mines_per_cell,
+        ..Ruleset::CLASSIC
+    }
+}
+
+#[test]
+fn classic_rules_deal_the_classic_board() {
+    for seed in 0..20 {
+        let start = Some(Pos::new(4, 4));
+        let placed = Board::placed(16, 16, 40, seed, start, &Clustered).unwrap();
+        let ruled = Board::ruled(16, 16, 40, seed, start, &Clustered, Ruleset::CLASSIC).unwrap();
+        for (row, col) in cells(&placed) {
+            assert_eq!(placed.cell(row, col), ruled.cell(row, col));
+        }
+    }
+}
+
+#[test]
+fn every_combination_of_wiring_numbers_the_board_right() {
+    for adjacency in Adjacency::ALL {
+        for wrap in [false, true] {
+            for mines_per_cell in 1..=Ruleset::MAX_MINES_PER_CELL {
+                let rules = rules(adjacency, wrap, mines_per_cell);
+                for (width, height) in [(1, 7), (3, 3), (4, 5), (16, 9)] {
+                    for seed in 0..5 {
+                        let mines = (width * height / 4).max(1);
+                        let board = Board::ruled(width, height, mines, seed, None, &Uniform, rules)
+                            .unwrap();
+                        check_numbers(&board);
+                        assert!(verify::numbers(&board).is_ok());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn numbers_reach_across_the_edges_of_a_torus() {
+    let rules = rules(Adjacency::King, true, 1);
+    let board = (0..)
+        .map(|seed| Board::ruled(9, 9, 1, seed, None, &Uniform, rules).unwrap())
+        .find(|board| board.cell(0, 0).cell_type == CellType::Mine)
+        .unwrap();
+    for (row, col) in [(8, 8), (0, 8), (8, 0), (1, 8), (8, 1)] {
+        assert_eq!(board.cell(row, col).cell_type, CellType::Number(1));
+    }
+    assert_eq!(board.adjacent(0, 0).len(), 8);
+}
+
+#[test]
+fn mined_cells_hold_up_to_the_most_the_rules_allow() {
+    let rules = rules(Adjacency::King, false, 3);
+    let mut loads = [0; 4];
+    for seed in 0..20 {
+        let board = Board::ruled(16, 16, 40, seed, None, &Uniform, rules).unwrap();
+        for (row, col) in cells(&board) {
+            let load = board.mines_at(row, col);
+            let mine = board.cell(row, col).cell_type == CellType::Mine;
+            assert_eq!(load > 0, mine);
+            loads[load] += 1;
+        }
+    }
+    assert_eq!(loads[1] + loads[2] + loads[3], 20 * 40);
+    assert!(loads[1..].iter().all(|&count| count > 0), "{:?}", loads);
+}
+
+#[test]
+fn a_safe_start_stays_clear_of_what_its_number_counts() {
+    let rules = rules(Adjacency::Knight, true, 2);
+    for seed in 0..20 {
+        let start = Pos::new(0, 0);
+        let board = Board::ruled(8, 8, 30, seed, Some(start), &Uniform, rules).unwrap();
+        assert_eq!(board.cell(0, 0).cell_type, CellType::Empty);
+        for (row, col) in board.adjacent(0, 0) {
+            assert_ne!(board.cell(row, col).cell_type, CellType::Mine);
+        }
+    }
+}
+
+#[test]
+fn moved_mines_keep_the_numbers_right_whatever_the_rules() {
+    let rules = rules(Adjacency::Knight, true, 3);
+    for seed in 0..10 {
+        let mut board = Board::ruled(7, 6, 12, seed, None, &Uniform, rules).unwrap();
+        let mine = cells(&board)
+            .find(|&(r, c)| board.mines_at(r, c) > 0)
+            .unwrap();
+        let safe = cells(&board)
+            .find(|&(r, c)| board.mines_at(r, c) == 0)
+            .unwrap();
+        let load = board.mines_at(mine.0, mine.1);
+        assert!(board.move_mine(mine.into(), safe.into()));
+        assert_eq!(board.mines_at(safe.0, safe.1), load);
+        check_numbers(&board);
+    }
+}
+
+#[test]
+fn single_reveals_never_open_an_area() {
+    let rules = Ruleset {
+        reveal: Reveal::Single,
+        ..Ruleset::CLASSIC
+    };
+    let start = Pos::new(5, 5);
+    let mut board = Board::ruled(12, 12, 10, 3, Some(start), &Uniform, rules).unwrap();
+    assert_eq!(board.reveal_cells(5, 5), vec![(5, 5)]);
+    assert_eq!(board.revealed(), 1);
+}
+
+#[test]
+fn flag_wins_need_every_mine_flagged_and_nothing_else() {
+    let rules = Ruleset {
+        win: Win::Flag,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(9, 9, 10, 7, None, &Uniform, rules).unwrap();
+    let safe = cells(&board)
+        .find(|&(r, c)| board.mines_at(r, c) == 0)
+        .unwrap();
+    board.toggle_flag(safe.0, safe.1);
+    let mines: Vec<_> = cells(&board)
+        .filter(|&(r, c)| board.mines_at(r, c) > 0)
+        .collect();
+    for &(row, col) in &mines[1..] {
+        board.toggle_flag(row, col);
+    }
+    assert!(!board.is_won(), "a wrong flag can't win");
+    board.toggle_flag(safe.0, safe.1);
+    assert!(!board.is_won(), "a mine is still unflagged");
+    board.toggle_flag(mines[0].0, mines[0].1);
+    assert!(board.is_won());
+    assert_eq!(board.cell(safe.0, safe.1).cell_state, CellState::Hidden);
+}
+
+#[test]
+fn the_view_counts_what_the_board_counts() {
+    let rules = rules(Adjacency::Cross, true, 1);
+    let board = Board::ruled(5, 4, 4, 1, None, &Uniform, rules).unwrap();
+    let view = View::of(&board);
+    for (row, col) in cells(&board) {
+        let mut seen = view.neighbors(row, col);
+        let mut counted = board.adjacent(row, col);
+        seen.sort_unstable();
+        counted.sort_unstable();
+        assert_eq!(seen, counted);
+    }
+}
+
+#[test]
+fn spare_lives_keep_the_game_going() {
+    let rules = Ruleset {
+        lives: 3,
+        ..Ruleset::CLASSIC
+    };
+    let board = Board::ruled(9, 9, 10, 2, None, &Uniform, rules).unwrap();
+    let mines: Vec<_> = cells(&board)
+        .filter(|&(r, c)| board.mines_at(r, c) > 0)
+        .collect();
+    let mut game = Game::new(board);
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let heard = Rc::clone(&events);
+    game.subscribe(Box::new(move |event: &Event| {
+        heard.borrow_mut().push(event.clone())
+    }));
+
+    for (i, &(row, col)) in mines[..2].iter().enumerate() {
+        game.reveal(row, col).unwrap();
+        assert_eq!(game.status(), Status::Playing);
+        assert_eq!(game.board().lives_left(), 2 - i);
+    }
+    game.reveal(mines[2].0, mines[2].1).unwrap();
+    assert_eq!(game.status(), Status::Lost);
+    let events = events.borrow();
+    let spared = events
+        .iter()
+        .filter(|event| matches!(event, Event::LifeLost { .. }))
+        .count();
+    assert_eq!(spared, 2);
+    assert!(matches!(events.last(), Some(Event::MineHit { .. })));
+}
+
+#[test]
+fn a_game_with_lives_to_spare_can_still_be_won() {
+    let rules = Ruleset {
+        lives: 2,
+        ..Ruleset::CLASSIC
+    };
+    let board = Board::ruled(6, 6, 5, 4, None, &Uniform, rules).unwrap();
+    let mine = cells(&board)
+        .find(|&(r, c)| board.mines_at(r, c) > 0)
+        .unwrap();
+    let safe: Vec<_> = cells(&board)
+        .filter(|&(r, c)| board.mines_at(r, c) == 0)
+        .collect();
+    let mut game = Game::new(board);
+    game.reveal(mine.0, mine.1).unwrap();
+    for (row, col) in safe {
+        let _ = game.reveal(row, col);
+    }
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn liars_are_off_by_exactly_one() {
+    let rules = Ruleset {
+        liar: true,
+        ..Ruleset::CLASSIC
+    };
+    let (mut high, mut low) = (0, 0);
+    for seed in 0..10 {
+        let board = Board::ruled(12, 12, 30, seed, None, &Uniform, rules).unwrap();
+        assert!(verify::numbers(&board).is_ok());
+        for (row, col) in cells(&board) {
+            let shown = match board.cell(row, col).cell_type {
+                CellType::Mine => continue,
+                CellType::Empty => 0,
+                CellType::Number(n) => n,
+            };
+            match shown > expected(&board, row, col) {
+                true => high += 1,
+                false => low += 1,
+            }
+        }
+    }
+    assert!(high > 0 && low > 0, "{} high, {} low", high, low);
+}
+
+#[test]
+fn custom_rules_travel_in_the_share_code() {
+    let text =
+        "16x16x40-gradient+custom+knight+wrap+mines2+flag-win+no-flood+lives3+liar+fog2+blind+duds10+bigs5-99";
+    let code: ShareCode = text.parse().unwrap();
+    assert_eq!(code.to_string(), text);
+    assert!(code.variant.contains(Variant::CUSTOM));
+    assert!(code.variant.contains(Variant::GRADIENT));
+    assert_eq!(
+        code.rules,
+        Ruleset {
+            adjacency: Adjacency::Knight,
+            wrap: true,
+            mines_per_cell: 2,
+            win: Win::Flag,
+            reveal: Reveal::Single,
+            lives: 3,
+            liar: true,
+            fog: 2,
+            blind: true,
+            duds: 10,
+            bigs: 5,
+        }
+    );
+    let (first, second) = (code.board().unwrap(), code.board().unwrap());
+    assert_eq!(first.rules(), code.rules);
+    for (row, col) in cells(&first) {
+        assert_eq!(first.cell(row, col), second.cell(row, col));
+    }
+
+    let implied: ShareCode = "9x9x10-wrap-1".parse().unwrap();
+    assert_eq!(implied.to_string(), "9x9x10-custom+wrap-1");
+    for broken in [
+        "9x9x10-lives1-1",
+        "9x9x10-mines4-1",
+        "9x9x10-fog0-1",
+        "9x9x10-fog6-1",
+        "9x9x10-duds0-1",
+        "9x9x10-bigs101-1",
+        "9x9x10-no-guess+wrap-1",
+    ] {
+        assert!(broken.parse::<ShareCode>().is_err(), "{}", broken);
+    }
+}
+
+#[test]
+fn ruled_boards_survive_a_save() {
+    let rules = Ruleset {
+        adjacency: Adjacency::King,
+        wrap: true,
+        mines_per_cell: 3,
+        lives: 2,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(10, 10, 40, 5, None, &Uniform, rules).unwrap();
+    board.reveal(0, 0);
+    for format in [Format::Text, Format::Json, Format::Binary] {
+        let loaded: Board = save::load(&save::save(&board, format)).unwrap();
+        assert_eq!(loaded.rules(), rules);
+        for (row, col) in cells(&board) {
+            assert_eq!(loaded.cell(row, col), board.cell(row, col));
+            assert_eq!(loaded.mines_at(row, col), board.mines_at(row, col));
+        }
+    }
+}
+
+#[test]
+fn fog_hides_cells_far_from_uncovered_ones() {
+    let rules = Ruleset {
+        fog: 2,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(16, 16, 40, 4, Some(Pos::new(8, 8)), &Uniform, rules).unwrap();
+    // Nothing uncovered yet, so nothing is hidden
+    assert!(cells(&board).all(|(row, col)| board.in_sight(row, col)));
+    board.reveal(8, 8);
+    let seen: Vec<_> = cells(&board)
+        .filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Visible)
+        .collect();
+    for (row, col) in cells(&board) {
+        let near = seen
+            .iter()
+            .any(|&(r, c)| r.abs_diff(row) <= 2 && c.abs_diff(col) <= 2);
+        assert_eq!(board.in_sight(row, col), near, "at {},{}", row, col);
+    }
+
+    let view = View::of(&board);
+    let (row, col) = cells(&board)
+        .find(|&(row, col)| !board.in_sight(row, col))
+        .expect("a cell under the fog");
+    assert!(!view.in_sight(row, col));
+    assert!(!view.playable().contains(&(row, col)));
+    assert!(!board.toggle_flag(row, col));
+    assert!(board.reveal(row, col).is_none());
+    assert_eq!(board.cell(row, col).cell_state, CellState::Hidden);
+    for (row, col) in view.playable() {
+        assert!(board.in_sight(row, col));
+    }
+}
+
+#[test]
+fn fog_reaches_across_a_torus() {
+    let rules = Ruleset {
+        fog: 1,
+        wrap: true,
+        reveal: Reveal::Single,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(9, 9, 10, 2, Some(Pos::new(0, 0)), &Uniform, rules).unwrap();
+    board.reveal(0, 0);
+    for (row, col) in [(8, 8), (0, 8), (8, 1), (1, 1)] {
+        assert!(board.in_sight(row, col), "at {},{}", row, col);
+    }
+    for (row, col) in [(2, 0), (7, 7), (4, 4)] {
+        assert!(!board.in_sight(row, col), "at {},{}", row, col);
+    }
+}
+
+#[test]
+fn blind_numbers_show_once_a_counted_cell_is_flagged() {
+    let rules = Ruleset {
+        blind: true,
+        reveal: Reveal::Single,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(9, 9, 20, 8, Some(Pos::new(4, 4)), &Uniform, rules).unwrap();
+    let (row, col) = cells(&board)
+        .find(|&(row, col)| matches!(board.cell(row, col).cell_type, CellType::Number(_)))
+        .unwrap();
+    let CellType::Number(n) = board.reveal(row, col).unwrap() else {
+        unreachable!();
+    };
+    assert!(!board.shows_number(row, col));
+    assert_eq!(View::of(&board).get(row, col), CellView::Unread);
+
+    // Flagging a cell the number doesn't count leaves it blank
+    let far = cells(&board)
+        .find(|&(r, c)| r.abs_diff(row) > 1 || c.abs_diff(col) > 1)
+        .unwrap();
+    board.toggle_flag(far.0, far.1);
+    assert!(!board.shows_number(row, col));
+
+    let (r, c) = board
+        .adjacent(row, col)
+        .into_iter()
+        .find(|&(r, c)| board.cell(r, c).cell_state == CellState::Hidden)
+        .unwrap();
+    board.toggle_flag(r, c);
+    assert!(board.shows_number(row, col));
+    assert_eq!(View::of(&board).get(row, col), CellView::Number(n));
+    // Taking the flag off again doesn't hide what was seen
+    board.toggle_flag(r, c);
+    assert!(board.shows_number(row, col));
+
+    for format in [Format::Text, Format::Json, Format::Binary] {
+        let loaded: Board = save::load(&save::save(&board, format)).unwrap();
+        for (row, col) in cells(&board) {
+            assert_eq!(loaded.shows_number(row, col), board.shows_number(row, col));
+        }
+    }
+}
+
+#[test]
+fn mine_kinds_are_dealt_by_share() {
+    let rules = Ruleset {
+        duds: 30,
+        bigs: 20,
+        ..Ruleset::CLASSIC
+    };
+    let board = Board::ruled(30, 30, 400, 3, None, &Uniform, rules).unwrap();
+    let count = |kind| {
+        cells(&board)
+            .filter(|&(r, c)| board.mine_kind(r, c) == Some(kind))
+            .count()
+    };
+    let (duds, bigs) = (count(MineKind::Dud), count(MineKind::Big));
+    assert!((80..160).contains(&duds), "{} duds", duds);
+    assert!((40..120).contains(&bigs), "{} big mines", bigs);
+    assert_eq!(duds + bigs + count(MineKind::Normal), 400);
+
+    let classic = Board::ruled(30, 30, 400, 3, None, &Uniform, Ruleset::CLASSIC).unwrap();
+    assert!(cells(&classic)
+        .all(|(r, c)| matches!(classic.mine_kind(r, c), None | Some(MineKind::Normal))));
+    for format in [Format::Text, Format::Json, Format::Binary] {
+        let loaded: Board = save::load(&save::save(&board, format)).unwrap();
+        assert_eq!(loaded.rules(), rules);
+        for (row, col) in cells(&board) {
+            assert_eq!(loaded.mine_kind(row, col), board.mine_kind(row, col));
+        }
+    }
+}
+
+#[test]
+fn duds_cost_no_life() {
+    let rules = Ruleset {
+        duds: 100,
+        ..Ruleset::CLASSIC
+    };
+    let board = Board::ruled(9, 9, 10, 6, None, &Uniform, rules).unwrap();
+    let mines: Vec<_> = cells(&board)
+        .filter(|&(r, c)| board.mines_at(r, c) > 0)
+        .collect();
+    let mut game = Game::new(board);
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let heard = Rc::clone(&events);
+    game.subscribe(Box::new(move |event: &Event| {
+        heard.borrow_mut().push(event.clone())
+    }));
+
+    for &(row, col) in &mines[..3] {
+        game.reveal(row, col).unwrap();
+    }
+    assert_eq!(game.status(), Status::Playing);
+    assert_eq!(game.duds_hit(), 3);
+    assert_eq!(game.board().lives_left(), 1);
+    let duds = events
+        .borrow()
+        .iter()
+        .filter(|event| matches!(event, Event::DudHit { .. }))
+        .count();
+    assert_eq!(duds, 3);
+}
+
+#[test]
+fn big_mines_blow_open_the_cells_around_them() {
+    let rules = Ruleset {
+        bigs: 100,
+        lives: 9,
+        ..Ruleset::CLASSIC
+    };
+    let board = Board::ruled(12, 12, 8, 2, None, &Uniform, rules).unwrap();
+    // A mine away from the edges, so all eight cells around it exist
+    let (row, col) = cells(&board)
+        .find(|&(r, c)| board.mines_at(r, c) > 0 && (1..11).contains(&r) && (1..11).contains(&c))
+        .unwrap();
+    let mut game = Game::new(board);
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let heard = Rc::clone(&events);
+    game.subscribe(Box::new(move |event: &Event| {
+        heard.borrow_mut().push(event.clone())
+    }));
+
+    game.reveal(row, col).unwrap();
+    for (r, c) in game.board().adjacent(row, col) {
+        assert_eq!(game.board().cell(r, c).cell_state, CellState::Visible);
+    }
+    let events = events.borrow();
+    assert!(events
+        .iter()
+        .any(|event| matches!(*event, Event::Blast { row: r, col: c, cells } if (r, c) == (row, col) && cells > 0)));
+    // Mines caught in the blast go off too and each cost a life
+    let blasts = events
+        .iter()
+        .filter(|event| matches!(event, Event::Blast { .. }))
+        .count();
+    let board = game.board();
+    let gone = cells(board)
+        .filter(|&(r, c)| {
+            board.mines_at(r, c) > 0 && board.cell(r, c).cell_state == CellState::Visible
+        })
+        .count();
+    assert_eq!(blasts, gone);
+    assert_eq!(board.lives_left(), 9usize.saturating_sub(gone));
+}
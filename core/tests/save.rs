@@ -0,0 +1,227 @@
+use minesweeper_core::save::{self, Document, Format, Migration, SaveError, Saved};
+use minesweeper_core::{sim, Board};
+
+#[test]
+fn boards_survive_a_round_trip() {
+    let mut board = sim::board(
+        "
+        * . . f
+        . o . .
+        . . F *
+        ",
+    )
+    .unwrap();
+    board.reveal_cells(0, 3);
+    board.capture(0, 0, 1);
+
+    let text = save::save(&board, Format::Text);
+    assert!(text.starts_with(b"minesweeper board 4\n"));
+    let loaded: Board = save::load(&text).unwrap();
+    for row in 0..3 {
+        for col in 0..4 {
+            assert_eq!(loaded.cell(row, col), board.cell(row, col));
+        }
+    }
+    assert_eq!(loaded.mines(), board.mines());
+    assert_eq!(loaded.flags(), board.flags());
+}
+
+#[test]
+fn broken_boards_are_refused() {
+    let text = String::from_utf8(save::save(
+        &Board::with_seed(4, 4, 3, 7).unwrap(),
+        Format::Text,
+    ))
+    .unwrap();
+    assert_eq!(save::load::<Board>(b"").err(), Some(SaveError::NotASave));
+    let short = text.replace("width 4", "width 5");
+    assert!(matches!(
+        save::load::<Board>(short.as_bytes()),
+        Err(SaveError::Invalid { .. })
+    ));
+    for mines in [
+        "mines 4",
+        "mines 2",
+        "mines 17",
+        "mines 18446744073709551615",
+    ] {
+        let miscounted = text.replace("mines 3", mines);
+        assert_eq!(
+            save::load::<Board>(miscounted.as_bytes()).err(),
+            Some(SaveError::Invalid {
+                field: "mines".to_owned(),
+                value: mines[6..].to_owned(),
+            }),
+        );
+    }
+    let missing = text.replace("states", "statez");
+    assert_eq!(
+        save::load::<Board>(missing.as_bytes()).err(),
+        Some(SaveError::Missing("states".to_owned()))
+    );
+}
+
+#[test]
+fn boards_from_before_custom_rules_load_as_classic() {
+    let board = Board::with_seed(4, 4, 3, 7).unwrap();
+    let text = String::from_utf8(save::save(&board, Format::Text)).unwrap();
+    let old = text.replacen(
+        &format!("board {}\n", save::version::<Board>()),
+        "board 1\n",
+        1,
+    );
+    assert_ne!(old, text);
+    let loaded: Board = save::load(old.as_bytes()).unwrap();
+    assert!(loaded.rules().is_classic());
+    assert_eq!(loaded.mines(), board.mines());
+}
+
+/// A record whose schema has changed twice: version 1 called `score`
+/// `points`, and version 2 had no `name`.
+#[derive(Clone, Debug, PartialEq)]
+struct Record {
+    name: String,
+    score: u32,
+}
+
+fn rename_points(document: &mut Document) -> Result<(), SaveError> {
+    document.rename("points", "score");
+    Ok(())
+}
+
+fn add_name(document: &mut Document) -> Result<(), SaveError> {
+    document.set("name", "anonymous");
+    Ok(())
+}
+
+impl Saved for Record {
+    const KIND: &'static str = "record";
+    const MIGRATIONS: &'static [Migration] = &[rename_points, add_name];
+
+    fn save(&self, document: &mut Document) {
+        document.set("name", &self.name);
+        document.set("score", self.score);
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        Ok(Self {
+            name: document.get("name")?.to_owned(),
+            score: document.parse("score")?,
+        })
+    }
+}
+
+#[test]
+fn older_saves_are_migrated() {
+    assert_eq!(save::version::<Record>(), 3);
+    let expected = Record {
+        name: "anonymous".to_owned(),
+        score: 12,
+    };
+    assert_eq!(
+        save::load(b"minesweeper record 1\npoints 12\n"),
+        Ok(expected)
+    );
+    assert_eq!(
+        save::load(b"minesweeper record 2\nscore 5\n"),
+        Ok(Record {
+            name: "anonymous".to_owned(),
+            score: 5,
+        })
+    );
+    let current = Record {
+        name: "ada\nlovelace".to_owned(),
+        score: 1,
+    };
+    assert_eq!(
+        save::load::<Record>(&save::save(&current, Format::Text))
+            .unwrap()
+            .name,
+        "ada lovelace"
+    );
+}
+
+#[test]
+fn newer_or_foreign_saves_are_refused() {
+    assert_eq!(
+        save::load::<Record>(b"minesweeper record 4\nscore 1\n"),
+        Err(SaveError::TooNew {
+            version: 4,
+            supported: 3,
+        })
+    );
+    assert_eq!(
+        save::load::<Record>(b"minesweeper board 1\n"),
+        Err(SaveError::WrongKind {
+            expected: "record".to_owned(),
+            found: "board".to_owned(),
+        })
+    );
+    assert_eq!(
+        save::load::<Record>(b"minesweeper record 0\n"),
+        Err(SaveError::NotASave)
+    );
+}
+
+#[test]
+fn every_format_loads_back() {
+    let board = Board::with_seed(9, 9, 10, 3).unwrap();
+    let record = Record {
+        name: "\"quoted\" \\ tabbed\t ünïcode 🙂 007".to_owned(),
+        score: 42,
+    };
+    for format in [Format::Text, Format::Json, Format::Binary] {
+        let bytes = save::save(&record, format);
+        assert_eq!(Format::detect(&bytes), format);
+        assert_eq!(save::load(&bytes), Ok(record.clone()));
+
+        let loaded: Board = save::load(&save::save(&board, format)).unwrap();
+        assert_eq!(save::save(&loaded, format), save::save(&board, format));
+    }
+    let size = |format| save::save(&board, format).len();
+    assert!(size(Format::Binary) < size(Format::Text));
+    assert!(size(Format::Text) < size(Format::Json));
+}
+
+#[test]
+fn json_saves_read_like_json() {
+    let text = String::from_utf8(save::save(
+        &Record {
+            name: "a\"b".to_owned(),
+            score: 7,
+        },
+        Format::Json,
+    ))
+    .unwrap();
+    assert_eq!(
+        text,
+        "{\n  \"minesweeper\": \"record\",\n  \"version\": 3,\n  \"fields\": {\n    \"name\": \"a\\\"b\",\n    \"score\": 7\n  }\n}\n"
+    );
+    // Other tools may reorder keys and reformat
+    let compact = r#"{"fields":{"score":"7","name":"\u0061\ud83d\ude42"},"version":3,"minesweeper":"record"}"#;
+    assert_eq!(
+        save::load(compact.as_bytes()),
+        Ok(Record {
+            name: "a🙂".to_owned(),
+            score: 7,
+        })
+    );
+}
+
+#[test]
+fn truncated_saves_are_corrupt() {
+    for format in [Format::Json, Format::Binary] {
+        let bytes = save::save(&Board::with_seed(5, 5, 5, 1).unwrap(), format);
+        for end in [bytes.len() - 3, bytes.len() / 2] {
+            assert!(
+                matches!(
+                    save::load::<Board>(&bytes[..end]),
+                    Err(SaveError::Corrupt(_))
+                ),
+                "{:?} cut at {}",
+                format,
+                end
+            );
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use minesweeper_core::scenario::{Action, Happened, Region, Scenario, Trigger};
+use minesweeper_core::{Board, CellState, CellType};
+
+const LEVEL: &str = "\
+# A test level
+board 10x10x15-classic-3
+lock 0,5 9,9
+at 30s => mines 4 in 0,0 9,4
+revealed 1 => bonus 20s
+cleared 0,0 9,4 => unlock 9,9 0,5
+";
+
+fn left() -> Region {
+    Region {
+        top: 0,
+        left: 0,
+        bottom: 9,
+        right: 4,
+    }
+}
+
+fn safe_zero(board: &Board, region: Region) -> (usize, usize) {
+    region
+        .cells()
+        .find(|&(row, col)| board.cell(row, col).cell_type == CellType::Empty)
+        .expect("a cell with no mines around it")
+}
+
+#[test]
+fn parses_a_level() {
+    let scenario = Scenario::parse(LEVEL).unwrap();
+    assert_eq!(scenario.code.to_string(), "10x10x15-classic-3");
+    let right = Region {
+        top: 0,
+        left: 5,
+        bottom: 9,
+        right: 9,
+    };
+    assert_eq!(scenario.locked, vec![right]);
+    assert_eq!(
+        scenario.events,
+        vec![
+            (
+                Trigger::At(Duration::from_secs(30)),
+                Action::Mines {
+                    count: 4,
+                    region: left()
+                }
+            ),
+            (Trigger::Revealed(1), Action::Bonus(Duration::from_secs(20))),
+            (Trigger::Cleared(left()), Action::Unlock(right)),
+        ]
+    );
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let error = Scenario::parse("board 10x10x1-classic-3\n\nat soon => bonus 5s").unwrap_err();
+    assert_eq!(error.line, 3);
+    assert!(error.to_string().starts_with("line 3: "));
+    assert_eq!(
+        Scenario::parse("board 10x10x1-classic-3\nexplode")
+            .unwrap_err()
+            .line,
+        2
+    );
+    assert_eq!(
+        Scenario::parse("board 10x10x1-classic-3\nlock 0,0 3")
+            .unwrap_err()
+            .line,
+        2
+    );
+    assert_eq!(Scenario::parse("lock 0,0 1,1").unwrap_err().line, 1);
+    assert_eq!(
+        Scenario::parse("board 10x10x1-classic-3\nlock 0,0 10,10")
+            .unwrap_err()
+            .line,
+        2
+    );
+}
+
+#[test]
+fn locked_cells_stop_openings_and_flags() {
+    let scenario = Scenario::parse("board 10x10x1-classic-3\nlock 0,5 9,9").unwrap();
+    let (mut board, _) = scenario.start().unwrap();
+    assert!(board.is_locked(0, 5) && board.is_locked(9, 9) && !board.is_locked(0, 4));
+    let (row, col) = safe_zero(&board, left());
+    board.reveal(row, col);
+    for (row, col) in scenario.locked[0].cells() {
+        assert_eq!(board.cell(row, col).cell_state, CellState::Hidden);
+    }
+    assert!(!board.toggle_flag(0, 9));
+    assert!(board.reveal(0, 9).is_none());
+}
+
+#[test]
+fn events_fire_once_when_due() {
+    let scenario = Scenario::parse(LEVEL).unwrap();
+    let (mut board, mut run) = scenario.start().unwrap();
+    assert!(run.update(&mut board, Duration::from_secs(1)).is_empty());
+
+    let number = left()
+        .cells()
+        .find(|&(row, col)| matches!(board.cell(row, col).cell_type, CellType::Number(_)))
+        .unwrap();
+    board.reveal(number.0, number.1);
+    let happened = run.update(&mut board, Duration::from_secs(2));
+    assert_eq!(happened, vec![Happened::Bonus(Duration::from_secs(20))]);
+    assert!(!happened[0].reshapes());
+    assert!(run.update(&mut board, Duration::from_secs(3)).is_empty());
+
+    // The bonus stops the clock, so 30 seconds on it come 20 later
+    assert_eq!(run.credit(Duration::from_secs(12)), Duration::from_secs(10));
+    assert_eq!(
+        run.bonus_left(Duration::from_secs(12)),
+        Some(Duration::from_secs(10))
+    );
+    assert!(run.update(&mut board, Duration::from_secs(49)).is_empty());
+    assert_eq!(run.bonus_left(Duration::from_secs(49)), None);
+    let mines = board.mines();
+    let happened = run.update(&mut board, Duration::from_secs(50));
+    let [Happened::Mines(added)] = happened[..] else {
+        panic!("expected mines, got {:?}", happened);
+    };
+    assert!(added > 0 && added <= 4);
+    assert!(happened[0].reshapes());
+    assert!(!Happened::Mines(0).reshapes());
+    assert_eq!(board.mines(), mines + added);
+}
+
+#[test]
+fn added_mines_leave_seen_numbers_alone() {
+    let scenario =
+        Scenario::parse("board 10x10x1-classic-3\nlock 0,5 9,9\nat 1s => mines 20 in 0,0 9,9")
+            .unwrap();
+    let (mut board, mut run) = scenario.start().unwrap();
+    let (row, col) = safe_zero(&board, left());
+    board.reveal(row, col);
+    let seen: Vec<_> = (0..10)
+        .flat_map(|row| (0..10).map(move |col| (row, col)))
+        .filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Visible)
+        .map(|(row, col)| ((row, col), board.cell(row, col)))
+        .collect();
+    let happened = run.update(&mut board, Duration::from_secs(1));
+    assert_eq!(happened, vec![Happened::Mines(20)]);
+    for ((row, col), cell) in seen {
+        assert_eq!(board.cell(row, col), cell);
+    }
+    for row in 0..10 {
+        for col in 0..10 {
+            if board.cell(row, col).cell_type != CellType::Mine {
+                let count = board
+                    .adjacent(row, col)
+                    .into_iter()
+                    .filter(|&(r, c)| board.cell(r, c).cell_type == CellType::Mine)
+                    .count();
+                let number = match count {
+                    0 => CellType::Empty,
+                    count => CellType::Number(count),
+                };
+                assert_eq!(board.cell(row, col).cell_type, number);
+            }
+        }
+    }
+}
+
+#[test]
+fn clearing_a_region_unlocks_another() {
+    let scenario =
+        Scenario::parse("board 10x10x1-classic-3\nlock 0,5 9,9\ncleared 0,0 9,4 => unlock 0,5 9,9")
+            .unwrap();
+    let (mut board, mut run) = scenario.start().unwrap();
+    for (row, col) in left().cells() {
+        if board.cell(row, col).cell_type != CellType::Mine {
+            board.reveal(row, col);
+        }
+    }
+    assert!(!board.is_won());
+    let happened = run.update(&mut board, Duration::ZERO);
+    assert_eq!(happened, vec![Happened::Unlocked(scenario.locked[0])]);
+    assert!(happened[0].reshapes());
+    assert!(!board.is_locked(0, 9));
+    let (row, col) = safe_zero(&board, scenario.locked[0]);
+    board.reveal(row, col);
+    assert_eq!(board.cell(row, col).cell_state, CellState::Visible);
+}
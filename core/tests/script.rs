@@ -0,0 +1,85 @@
+use minesweeper_core::plugin::{Message, PluginRegistry};
+use minesweeper_core::script::ScriptMod;
+use minesweeper_core::{sim, CellState};
+
+fn registry(source: &str) -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry.install(&ScriptMod::parse("test", source).unwrap());
+    registry
+}
+
+#[test]
+fn mods_block_clicks_and_flag_around_numbers() {
+    let registry = registry(
+        "
+        # Nothing on the top row
+        block row == 0
+        after number >= 2 => flag
+        ",
+    );
+    let mut board = sim::board(
+        "
+        * . *
+        . . .
+        . . .
+        ",
+    )
+    .unwrap();
+    assert!(!registry.allow_reveal(&board, 0, 1));
+    assert!(registry.allow_reveal(&board, 1, 1));
+
+    board.reveal(1, 1);
+    registry.after_reveal(&mut board, 1, 1);
+    assert_eq!(board.cell(0, 0).cell_state, CellState::Flagged);
+    assert_eq!(board.cell(0, 2).cell_state, CellState::Flagged);
+    assert_eq!(board.cell(0, 1).cell_state, CellState::Hidden);
+}
+
+#[test]
+fn mods_reveal_safe_neighbours_and_fill_in_text() {
+    let registry = registry(
+        "
+        after number == 1 => reveal
+        hud left > 0 => {left} of {revealed} to go
+        end won => Won with {flags} flags
+        ",
+    );
+    let mut board = sim::board(
+        "
+        * . .
+        . . .
+        ",
+    )
+    .unwrap();
+    board.reveal(1, 0);
+    registry.after_reveal(&mut board, 1, 0);
+    assert_eq!(board.cell(0, 1).cell_state, CellState::Visible);
+    assert_eq!(board.cell(1, 1).cell_state, CellState::Visible);
+    assert_eq!(board.cell(0, 0).cell_state, CellState::Hidden);
+
+    let hud: Vec<_> = registry
+        .widgets()
+        .filter_map(|widget| widget.text(&board))
+        .collect();
+    assert_eq!(hud, [Message::Plain("2 of 3 to go".to_owned())]);
+    assert_eq!(registry.analyze(&board, false), []);
+    assert_eq!(
+        registry.analyze(&board, true),
+        [Message::Plain("Won with 0 flags".to_owned())]
+    );
+}
+
+#[test]
+fn mod_errors_name_the_line() {
+    for (source, line) in [
+        ("block", 1),
+        ("\nafter number => explode", 2),
+        ("hud 1 => {unknown}", 1),
+        ("hud 1 => {left", 1),
+        ("# fine\nblock remaining > 0", 2),
+        ("rule 1 => reveal", 1),
+    ] {
+        let err = ScriptMod::parse("broken", source).unwrap_err();
+        assert_eq!(err.line, line, "{}: {}", source, err);
+    }
+}
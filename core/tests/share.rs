@@ -0,0 +1,70 @@
+use minesweeper_core::generate::Progress;
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::stats::Variant;
+use minesweeper_core::{BoardError, CellState, Ruleset};
+
+#[test]
+fn codes_round_trip_through_text() {
+    let code = ShareCode {
+        width: 30,
+        height: 16,
+        mines: 99,
+        variant: Variant::CLASSIC.with(Variant::NO_GUESS),
+        seed: 18_446_744_073_709_551_615,
+        rules: Ruleset::CLASSIC,
+    };
+    let text = code.to_string();
+
+    assert_eq!(text, "30x16x99-no-guess-18446744073709551615");
+    assert_eq!(format!(" {}\n", text).parse::<ShareCode>(), Ok(code));
+}
+
+#[test]
+fn the_same_code_deals_the_same_board() {
+    for text in ["9x9x10-classic-42", "12x10x20-no-guess-7"] {
+        let code: ShareCode = text.parse().unwrap();
+        let (first, second) = (code.board().unwrap(), code.board().unwrap());
+        for row in 0..code.height {
+            for col in 0..code.width {
+                assert_eq!(first.cell(row, col), second.cell(row, col));
+            }
+        }
+    }
+    let no_guess: ShareCode = "12x10x20-no-guess-7".parse().unwrap();
+    let board = no_guess.board().unwrap();
+    assert_eq!(board.cell(5, 6).cell_state, CellState::Visible);
+}
+
+#[test]
+fn broken_codes_are_refused() {
+    for text in [
+        "",
+        "42",
+        "9x9-classic-42",
+        "9x9x10-classic-",
+        "9x9x10-fog-42",
+        "9x9x81-classic-42",
+        "18446744073709551615x2x0-classic-1",
+        "4096x4096x10-classic-1",
+    ] {
+        assert!(text.parse::<ShareCode>().is_err(), "{:?}", text);
+    }
+}
+
+#[test]
+fn no_guess_deals_report_their_candidates_and_can_be_cancelled() {
+    let code: ShareCode = "30x16x99-no-guess-3".parse().unwrap();
+    let progress = Progress::default();
+    let board = code.board_with(&progress).unwrap();
+    assert!(progress.tried() >= 1);
+    assert_eq!(board.cell(8, 15), code.board().unwrap().cell(8, 15));
+
+    let cancelled = Progress::default();
+    cancelled.cancel();
+    // The first candidate is always dealt; expert ones rarely pass
+    assert_eq!(
+        code.board_with(&cancelled).unwrap_err(),
+        BoardError::Cancelled
+    );
+    assert_eq!(cancelled.tried(), 1);
+}
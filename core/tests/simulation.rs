@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use minesweeper_core::sim::{self, Move, Sim};
+use minesweeper_core::{CellType, Event, Game, MoveError, Status};
+
+fn rows(lines: &[&str]) -> String {
+    lines.join("\n")
+}
+
+fn sim(layout: &str) -> Sim {
+    Sim::new(layout).expect("valid layout")
+}
+
+#[test]
+fn layout_numbers_come_from_the_mines() {
+    let board = sim::board(
+        "
+        * . .
+        . . .
+        . . *
+        ",
+    )
+    .unwrap();
+    assert_eq!((board.width(), board.height(), board.mines()), (3, 3, 2));
+    assert_eq!(board.cell(0, 1).cell_type, CellType::Number(1));
+    assert_eq!(board.cell(1, 1).cell_type, CellType::Number(2));
+    assert_eq!(board.cell(2, 0).cell_type, CellType::Empty);
+}
+
+#[test]
+fn layout_errors_name_the_line() {
+    let err = sim::board(". .\n. . .").err().unwrap();
+    assert_eq!(err.line, 2);
+    let err = sim::board(". x").err().unwrap();
+    assert_eq!(err.message, "unknown cell `x`");
+    assert!(sim::board("\n  \n").is_err());
+}
+
+#[test]
+fn move_scripts_accept_short_names_and_separators() {
+    let moves = sim::moves("r 0 1; flag 2 3\nc 1 1").unwrap();
+    assert_eq!(
+        moves,
+        [Move::Reveal(0, 1), Move::Flag(2, 3), Move::Chord(1, 1)]
+    );
+    assert_eq!(sim::moves("r 0\n").err().unwrap().line, 1);
+    assert!(sim::moves("jump 0 0").is_err());
+}
+
+#[test]
+fn flood_reveals_the_opening_and_its_border() {
+    let mut game = sim("
+        . . . . *
+        . . . . .
+        . . . . .
+        * . . . .
+        ");
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.view(), rows(&["___1#", "___11", "11___", "#1___"]));
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn flood_reaches_numbers_only_touching_it_diagonally() {
+    // The 3 in the middle borders the opening at the top left only by its
+    // corner, as every cell between them is a number
+    let mut game = sim("
+        . . *
+        . . .
+        * . *
+        ");
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.view(), rows(&["_1#", "13#", "###"]));
+}
+
+#[test]
+fn flood_stops_at_flags() {
+    let mut game = sim("
+        . f . *
+        . f . .
+        ");
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.view(), rows(&["_F##", "_F##"]));
+    assert_eq!(game.status(), Status::Playing);
+}
+
+#[test]
+fn number_reveals_only_itself() {
+    let mut game = sim("
+        * . .
+        . . .
+        ");
+    game.run("r 0 1").unwrap();
+    assert_eq!(game.view(), rows(&["#1#", "###"]));
+}
+
+#[test]
+fn revealing_a_mine_loses_and_shows_every_mine() {
+    let mut game = sim("
+        * . *
+        . . .
+        ");
+    assert_eq!(game.run("r 0 0").unwrap(), [Ok(())]);
+    assert_eq!(game.status(), Status::Lost);
+    assert_eq!(game.view(), rows(&["*#*", "###"]));
+    assert_eq!(
+        game.events().last(),
+        Some(&Event::MineHit { row: 0, col: 0 })
+    );
+}
+
+#[test]
+fn chord_reveals_the_unflagged_neighbours() {
+    let mut game = sim("
+        * . .
+        . . .
+        . . *
+        ");
+    game.run("r 1 1; f 0 0; f 2 2; c 1 1").unwrap();
+    assert_eq!(game.view(), rows(&["F1_", "121", "_1F"]));
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn chord_needs_as_many_flags_as_the_number() {
+    let mut game = sim("
+        * . .
+        . . .
+        . . *
+        ");
+    game.run("r 1 1; f 0 0; c 1 1").unwrap();
+    assert_eq!(game.view(), rows(&["F##", "#2#", "###"]));
+}
+
+#[test]
+fn chord_with_a_wrong_flag_hits_the_mine() {
+    let mut game = sim("
+        * . .
+        . . .
+        ");
+    game.run("r 0 1; f 1 0; c 0 1").unwrap();
+    assert_eq!(game.status(), Status::Lost);
+    assert_eq!(game.board().cell(0, 0).cell_type, CellType::Mine);
+}
+
+#[test]
+fn winning_needs_every_safe_cell_but_no_flags() {
+    let mut game = sim("
+        * . .
+        ");
+    game.run("r 0 1").unwrap();
+    assert_eq!(game.status(), Status::Playing);
+    game.run("r 0 2").unwrap();
+    assert_eq!(game.status(), Status::Won);
+    assert_eq!(game.events().last(), Some(&Event::GameWon));
+}
+
+#[test]
+fn edge_cells_only_count_neighbours_on_the_board() {
+    let mut game = sim("* . . .");
+    game.run("r 0 3").unwrap();
+    assert_eq!(game.view(), "#1__");
+    assert_eq!(game.status(), Status::Won);
+
+    let mut game = sim("
+        .
+        .
+        *
+        ");
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.view(), rows(&["_", "1", "#"]));
+}
+
+#[test]
+fn single_cell_board_is_won_by_one_reveal() {
+    let mut game = sim(".");
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn invalid_moves_are_rejected() {
+    let mut game = sim("
+        * . .
+        . . .
+        ");
+    let results = game.run("r 0 2; r 0 2; r 5 5; f 0 2").unwrap();
+    assert_eq!(
+        results,
+        [
+            Ok(()),
+            Err(MoveError::AlreadyRevealed),
+            Err(MoveError::OutOfBounds),
+            Err(MoveError::AlreadyRevealed),
+        ]
+    );
+    game.run("r 0 0").unwrap();
+    assert_eq!(game.play(Move::Reveal(1, 2)), Err(MoveError::GameOver));
+}
+
+#[test]
+fn flags_toggle_and_are_reported() {
+    let mut game = sim("* .");
+    game.run("f 0 0; f 0 0").unwrap();
+    assert_eq!(game.view(), "##");
+    assert_eq!(
+        game.events(),
+        [
+            Event::Flagged {
+                row: 0,
+                col: 0,
+                flagged: true
+            },
+            Event::Flagged {
+                row: 0,
+                col: 0,
+                flagged: false
+            },
+        ]
+    );
+}
+
+#[test]
+fn openings_report_every_cell_then_the_flood() {
+    let mut game = sim(". . *");
+    game.run("r 0 0").unwrap();
+    let events = game.events();
+    assert_eq!(
+        events[..2],
+        [
+            Event::CellRevealed {
+                row: 0,
+                col: 0,
+                cell_type: CellType::Empty
+            },
+            Event::CellRevealed {
+                row: 0,
+                col: 1,
+                cell_type: CellType::Number(1)
+            },
+        ]
+    );
+    assert_eq!(
+        events[2..],
+        [
+            Event::GameWon,
+            Event::OpeningFlooded {
+                row: 0,
+                col: 0,
+                cells: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn gradual_reveal_ends_where_reveal_does() {
+    // Large enough that the opening takes several steps
+    let layout = format!("{}*\n", ".".repeat(99)).repeat(100);
+    let mut instant = Game::new(sim::board(&layout).unwrap());
+    let mut gradual = Game::new(sim::board(&layout).unwrap());
+
+    instant.reveal(50, 0).unwrap();
+    gradual.reveal_gradually(50, 0).unwrap();
+    assert!(gradual.is_flooding());
+    while !gradual.advance(Duration::ZERO) {}
+
+    assert!(!gradual.is_flooding());
+    assert_eq!(gradual.status(), instant.status());
+    for row in 0..100 {
+        for col in 0..100 {
+            assert_eq!(
+                gradual.board().cell(row, col),
+                instant.board().cell(row, col)
+            );
+        }
+    }
+}
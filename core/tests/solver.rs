@@ -0,0 +1,536 @@
+use std::collections::HashSet;
+
+use minesweeper_core::agent::View;
+use minesweeper_core::generate::{self, Verdict};
+use minesweeper_core::placement::Uniform;
+use minesweeper_core::protocol::CellView;
+use minesweeper_core::rules::Ruleset;
+use minesweeper_core::solver::{Analysis, Chance, Knowledge, Membership, Probabilities, Solver};
+use minesweeper_core::{sim, Board, CellState, CellType, Game, Pos, Status};
+
+#[test]
+fn logic_clears_a_board_without_guesses() {
+    let board = sim::board(
+        "
+        . . . . .
+        . . . . .
+        . . . * .
+        . . . . .
+        * . . . .
+        ",
+    )
+    .unwrap();
+    assert_eq!(generate::verify(&board, Pos::new(0, 0)), Verdict::Solvable);
+    assert!(generate::solvable(&board, Pos::new(0, 0)));
+}
+
+#[test]
+fn the_first_forced_guess_is_reported() {
+    // Both 1s see the same two cells, one of them the mine
+    let board = sim::board(". .\n. .\n* .").unwrap();
+    assert_eq!(
+        generate::verify(&board, Pos::new(0, 0)),
+        Verdict::Guess {
+            at: Pos::new(2, 0),
+            revealed: 4
+        }
+    );
+}
+
+#[test]
+fn openings_on_mines_or_off_the_board_are_refused() {
+    let board = sim::board(". *\n. .").unwrap();
+    assert_eq!(generate::verify(&board, Pos::new(0, 1)), Verdict::BadStart);
+    assert_eq!(generate::verify(&board, Pos::new(2, 0)), Verdict::BadStart);
+}
+
+#[test]
+fn single_numbers_settle_their_neighbours() {
+    let board = sim::board(
+        "
+        o * .
+        o o .
+        ",
+    )
+    .unwrap();
+    let knowledge = Knowledge::single_point(&View::of(&board));
+    assert_eq!(knowledge.mines, HashSet::from([(0, 1)]));
+    assert_eq!(knowledge.safe, HashSet::from([(0, 2), (1, 2)]));
+}
+
+#[test]
+fn boards_that_stack_mines_prove_nothing() {
+    let rules = Ruleset {
+        mines_per_cell: 3,
+        ..Ruleset::CLASSIC
+    };
+    let mut board = Board::ruled(9, 9, 30, 1, Some(Pos::new(4, 4)), &Uniform, rules).unwrap();
+    board.reveal_cells(4, 4);
+    let view = View::of(&board);
+    assert!(view
+        .cells
+        .iter()
+        .any(|cell| matches!(cell, CellView::Number(_))));
+    assert_eq!(Knowledge::single_point(&view), Knowledge::default());
+    assert_eq!(Knowledge::of(&view), Knowledge::default());
+    let chances = Probabilities::of(&view);
+    for (row, col) in view.hidden() {
+        assert_eq!(chances.get(row, col), None);
+    }
+}
+
+/// Checks what `Knowledge::of` proves about `board` against where the
+/// mines really are, returning how many cells it proved.
+fn assert_sound(board: &Board) -> usize {
+    let knowledge = Knowledge::of(&View::of(board));
+    for &(row, col) in &knowledge.mines {
+        assert_eq!(board.mines_at(row, col), 1, "({row}, {col}) is no mine");
+    }
+    for &(row, col) in &knowledge.safe {
+        assert_eq!(board.mines_at(row, col), 0, "({row}, {col}) is a mine");
+    }
+    knowledge.mines.len() + knowledge.safe.len()
+}
+
+/// Plays `seed`'s board under `rules` from the middle, then sets off a
+/// hidden mine next to the opening. `None` if the game didn't go on.
+fn after_a_mine(rules: Ruleset, seed: u64) -> Option<Game> {
+    let board = Board::ruled(9, 9, 15, seed, Some(Pos::new(4, 4)), &Uniform, rules).unwrap();
+    let mut game = Game::new(board);
+    game.reveal(4, 4).unwrap();
+    let board = game.board();
+    let (row, col) = cells(board).find(|&(row, col)| {
+        board.mines_at(row, col) > 0
+            && board
+                .adjacent(row, col)
+                .iter()
+                .any(|&(r, c)| board.cell(r, c).cell_state == CellState::Visible)
+    })?;
+    game.reveal(row, col).unwrap();
+    (game.status() == Status::Playing).then_some(game)
+}
+
+fn cells(board: &Board) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..board.height()).flat_map(move |row| (0..board.width()).map(move |col| (row, col)))
+}
+
+#[test]
+fn mines_that_went_off_count_towards_their_numbers() {
+    let rules = Ruleset {
+        lives: 3,
+        ..Ruleset::CLASSIC
+    };
+    let proven: usize = (0..40)
+        .filter_map(|seed| after_a_mine(rules, seed))
+        .map(|game| assert_sound(game.board()))
+        .sum();
+    assert!(proven > 0);
+}
+
+#[test]
+fn duds_that_went_off_count_towards_their_numbers() {
+    let rules = Ruleset {
+        duds: 100,
+        ..Ruleset::CLASSIC
+    };
+    let mut proven = 0;
+    for seed in 0..40 {
+        let game = after_a_mine(rules, seed).expect("duds cost no life");
+        assert_eq!(game.duds_hit(), 1);
+        let view = View::of(game.board());
+        assert_eq!(view.mines_unseen(), 14);
+        proven += assert_sound(game.board());
+    }
+    assert!(proven > 0);
+}
+
+#[test]
+fn overlapping_numbers_settle_each_other() {
+    let board = sim::board(
+        "
+        o o o o o
+        . * . * .
+        . . . . *
+        ",
+    )
+    .unwrap();
+    // No number settles on its own
+    assert_eq!(
+        Knowledge::single_point(&View::of(&board)),
+        Knowledge::default()
+    );
+    let knowledge = Knowledge::of(&View::of(&board));
+    assert_eq!(knowledge.safe, HashSet::from([(1, 0), (1, 2), (1, 4)]));
+    assert_eq!(knowledge.mines, HashSet::from([(1, 1), (1, 3)]));
+}
+
+#[test]
+fn cells_know_the_numbers_they_count_towards() {
+    let board = sim::board(
+        "
+        o o o
+        o o .
+        * . .
+        ",
+    )
+    .unwrap();
+    let view = View::of(&board);
+    let knowledge = Knowledge::of(&view);
+    // Both 1s see the same two cells, so which one is the mine is a guess
+    let fifty_fifty = |number| Membership {
+        number,
+        mines: 1,
+        cells: 2,
+    };
+    assert_eq!(
+        knowledge.constraints_on(&view, (2, 0)),
+        [fifty_fifty((1, 0)), fifty_fifty((1, 1))]
+    );
+    // Proven safe by the 0 above it, so in no constraint
+    assert!(knowledge.constraints_on(&view, (1, 2)).is_empty());
+    assert!(knowledge.constraints_on(&view, (0, 0)).is_empty());
+}
+
+/// The cells next to numbers that are safe or mines in every arrangement
+/// the numbers allow, found by trying them all; `None` if there are too
+/// many to try.
+fn brute_force(view: &View) -> Option<Knowledge> {
+    let is_number = |(row, col): (usize, usize)| matches!(view.get(row, col), CellView::Number(_));
+    let frontier: Vec<_> = view
+        .hidden()
+        .into_iter()
+        .filter(|&(row, col)| view.neighbors(row, col).into_iter().any(is_number))
+        .collect();
+    if frontier.len() > 16 {
+        return None;
+    }
+    let (mut ever_mine, mut ever_safe) = (0u32, 0u32);
+    for bits in 0u32..1 << frontier.len() {
+        let mine = |pos: &(usize, usize)| {
+            let i = frontier.iter().position(|cell| cell == pos);
+            i.is_some_and(|i| bits & (1 << i) != 0)
+        };
+        let fits = (0..view.height)
+            .flat_map(|row| (0..view.width).map(move |col| (row, col)))
+            .all(|(row, col)| match view.get(row, col) {
+                CellView::Number(n) => {
+                    view.neighbors(row, col)
+                        .iter()
+                        .filter(|pos| mine(pos))
+                        .count()
+                        == n
+                }
+                _ => true,
+            });
+        if fits {
+            ever_mine |= bits;
+            ever_safe |= !bits;
+        }
+    }
+    let never = |ever: u32| {
+        frontier
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| ever & (1 << i) == 0)
+            .map(|(_, &pos)| pos)
+            .collect()
+    };
+    Some(Knowledge {
+        safe: never(ever_mine),
+        mines: never(ever_safe),
+    })
+}
+
+#[test]
+fn the_solver_finds_everything_the_numbers_prove() {
+    let mut checked = 0;
+    for seed in 0..300 {
+        let mut board = Board::with_seed(6, 6, 9, seed).unwrap();
+        for i in 0..3 {
+            let (row, col) = ((seed as usize + 7 * i) % 6, (seed as usize / 6 + 5 * i) % 6);
+            if board.cell(row, col).cell_type != CellType::Mine {
+                board.reveal(row, col);
+            }
+        }
+        let view = View::of(&board);
+        let Some(expected) = brute_force(&view) else {
+            continue;
+        };
+        let knowledge = Knowledge::of(&view);
+        // The mine count can prove more than the numbers alone
+        assert!(expected.mines.is_subset(&knowledge.mines), "seed {}", seed);
+        assert!(expected.safe.is_subset(&knowledge.safe), "seed {}", seed);
+        checked += 1;
+    }
+    assert!(checked > 50, "only {} positions checked", checked);
+}
+
+#[test]
+fn the_mine_count_clears_the_cells_away_from_a_fifty_fifty() {
+    let board = sim::board(
+        "
+        . . * o
+        . . . o
+        ",
+    )
+    .unwrap();
+    let knowledge = Knowledge::of(&View::of(&board));
+    // The one mine is next to the numbers, so the cells away are safe
+    assert_eq!(
+        knowledge.safe,
+        HashSet::from([(0, 0), (0, 1), (1, 0), (1, 1)])
+    );
+    assert!(knowledge.mines.is_empty());
+}
+
+#[test]
+fn the_mine_count_fills_the_cells_away_from_a_fifty_fifty() {
+    let board = sim::board(
+        "
+        * * o
+        * . o
+        ",
+    )
+    .unwrap();
+    let knowledge = Knowledge::of(&View::of(&board));
+    // The numbers hold one mine, leaving two for the two cells away
+    assert_eq!(knowledge.mines, HashSet::from([(0, 0), (1, 0)]));
+    assert!(knowledge.safe.is_empty());
+}
+
+#[test]
+fn the_solver_finds_everything_the_numbers_and_mine_count_prove() {
+    let mut checked = 0;
+    for seed in 0..200 {
+        let mut board = Board::with_seed(5, 5, 5, seed).unwrap();
+        for i in 0..2 {
+            let (row, col) = ((seed as usize + 3 * i) % 5, (seed as usize / 5 + 2 * i) % 5);
+            if board.cell(row, col).cell_type != CellType::Mine {
+                board.reveal(row, col);
+            }
+        }
+        let view = View::of(&board);
+        if view.hidden().len() > 14 || board.is_won() {
+            continue;
+        }
+        let hidden = view.hidden();
+        let chances = enumerated(&view);
+        let proven = |chance: f64| -> HashSet<(usize, usize)> {
+            hidden
+                .iter()
+                .zip(&chances)
+                .filter(|&(_, &mine)| mine == chance)
+                .map(|(&pos, _)| pos)
+                .collect()
+        };
+        let knowledge = Knowledge::of(&view);
+        assert_eq!(knowledge.safe, proven(0.0), "seed {}", seed);
+        assert_eq!(knowledge.mines, proven(1.0), "seed {}", seed);
+        checked += 1;
+    }
+    assert!(checked > 30, "only {} positions checked", checked);
+}
+
+#[test]
+fn a_fifty_fifty_is_even() {
+    let board = sim::board("o o\no o\n* .").unwrap();
+    let chances = Probabilities::of(&View::of(&board));
+    let even = Some(Chance {
+        mine: 0.5,
+        exact: true,
+        margin: None,
+    });
+    assert_eq!(chances.get(2, 0), even);
+    assert_eq!(chances.get(2, 1), even);
+    assert_eq!(chances.get(0, 0), None);
+}
+
+/// Each hidden cell's chance of a mine over every way to place all the
+/// mines that fits the numbers.
+fn enumerated(view: &View) -> Vec<f64> {
+    let hidden = view.hidden();
+    let (mut fitting, mut mines) = (0u64, vec![0u64; hidden.len()]);
+    for bits in 0u32..1 << hidden.len() {
+        if bits.count_ones() as usize != view.mines {
+            continue;
+        }
+        let mine = |pos: &(usize, usize)| {
+            let i = hidden.iter().position(|cell| cell == pos);
+            i.is_some_and(|i| bits & (1 << i) != 0)
+        };
+        let fits = (0..view.height)
+            .flat_map(|row| (0..view.width).map(move |col| (row, col)))
+            .all(|(row, col)| match view.get(row, col) {
+                CellView::Number(n) => {
+                    view.neighbors(row, col)
+                        .iter()
+                        .filter(|pos| mine(pos))
+                        .count()
+                        == n
+                }
+                _ => true,
+            });
+        if fits {
+            fitting += 1;
+            for (i, count) in mines.iter_mut().enumerate() {
+                *count += u64::from(bits & (1 << i) != 0);
+            }
+        }
+    }
+    mines
+        .into_iter()
+        .map(|count| count as f64 / fitting as f64)
+        .collect()
+}
+
+#[test]
+fn chances_weigh_in_the_mines_left() {
+    let mut checked = 0;
+    for seed in 0..200 {
+        let mut board = Board::with_seed(5, 5, 5, seed).unwrap();
+        for i in 0..2 {
+            let (row, col) = ((seed as usize + 3 * i) % 5, (seed as usize / 5 + 2 * i) % 5);
+            if board.cell(row, col).cell_type != CellType::Mine {
+                board.reveal(row, col);
+            }
+        }
+        let view = View::of(&board);
+        if view.hidden().len() > 14 || board.is_won() {
+            continue;
+        }
+        let chances = Probabilities::of(&view);
+        for (&(row, col), expected) in view.hidden().iter().zip(enumerated(&view)) {
+            let chance = chances.get(row, col).unwrap();
+            assert!(chance.exact);
+            assert!(
+                (chance.mine - expected).abs() < 1e-9,
+                "seed {}: ({}, {}) is {} rather than {}",
+                seed,
+                row,
+                col,
+                chance.mine,
+                expected
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > 30, "only {} positions checked", checked);
+}
+
+/// A 20x20 board with every other cell of every other row revealed, so
+/// the numbers overlap into one component far too large to enumerate.
+fn web(seed: u64) -> Board {
+    let mines = Board::with_seed(20, 20, 50, seed).unwrap();
+    let layout: String = (0..20)
+        .map(|row| {
+            (0..20)
+                .map(|col| match mines.cell(row, col).cell_type {
+                    CellType::Mine => "* ",
+                    _ if row % 2 == 1 && col % 2 == 1 => "o ",
+                    _ => ". ",
+                })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect();
+    sim::board(&layout).unwrap()
+}
+
+#[test]
+fn huge_components_are_sampled_with_a_margin() {
+    for seed in 0..3 {
+        let view = View::of(&web(seed));
+        let chances = Probabilities::with_budget(&view, 200);
+        let hidden: Vec<Chance> = view
+            .hidden()
+            .into_iter()
+            .filter_map(|(row, col)| chances.get(row, col))
+            .collect();
+        let sampled: Vec<&Chance> = hidden
+            .iter()
+            .filter(|chance| chance.margin.is_some())
+            .collect();
+        assert!(
+            sampled.len() > 40,
+            "seed {}: {} cells sampled",
+            seed,
+            sampled.len()
+        );
+        assert!(sampled.iter().all(|chance| !chance.exact));
+        assert!(sampled
+            .iter()
+            .all(|chance| (0.0..=1.0).contains(&chance.mine)));
+
+        // Whatever the arrangement, all the mines are under hidden cells
+        let expected: f64 = hidden.iter().map(|chance| chance.mine).sum();
+        assert!(
+            (expected - view.mines as f64).abs() < view.mines as f64 * 0.1,
+            "seed {}: {} mines expected rather than {}",
+            seed,
+            expected,
+            view.mines
+        );
+    }
+}
+
+#[test]
+fn more_samples_narrow_the_margins() {
+    let view = View::of(&web(1));
+    let mean = |samples| {
+        let chances = Probabilities::with_budget(&view, samples);
+        let margins: Vec<f64> = view
+            .hidden()
+            .into_iter()
+            .filter_map(|(row, col)| chances.get(row, col)?.margin)
+            .collect();
+        margins.iter().sum::<f64>() / margins.len() as f64
+    };
+    assert!(mean(1000) < mean(100));
+}
+
+#[test]
+fn a_reused_solver_proves_what_a_fresh_one_does() {
+    let mut board = Board::with_seed(16, 16, 40, 3).unwrap();
+    let mut solver = Solver::new();
+    board.reveal_cells(8, 8);
+    // Smaller views after larger ones must not see leftovers either
+    for view in [View::of(&board), View::of(&sim::board(". *\n. .").unwrap())] {
+        assert_eq!(*solver.single_point(&view), Knowledge::single_point(&view));
+        assert_eq!(*solver.solve(&view), Knowledge::of(&view));
+    }
+    while !board.is_won() && !board.is_lost() {
+        let view = View::of(&board);
+        let knowledge = solver.solve(&view).clone();
+        assert_eq!(knowledge, Knowledge::of(&view));
+        let Some(&(row, col)) = knowledge
+            .safe
+            .iter()
+            .find(|&&(row, col)| view.get(row, col) == CellView::Hidden)
+        else {
+            break;
+        };
+        board.reveal_cells(row, col);
+    }
+}
+
+#[test]
+fn a_background_analysis_ends_where_the_chances_are() {
+    let mut expert = Board::with_seed(30, 16, 99, 5).unwrap();
+    expert.reveal_cells(8, 15);
+    for board in [web(2), expert] {
+        let view = View::of(&board);
+        let analysis = Analysis::start(view.clone(), 200);
+        let mut last = None;
+        while !analysis.is_done() {
+            last = analysis.poll().or(last);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        // Whatever came before, the final chances are the last ones
+        let last = analysis.poll().or(last).unwrap();
+
+        let expected = Probabilities::with_budget(&view, 200);
+        for (row, col) in view.hidden() {
+            assert_eq!(last.get(row, col), expected.get(row, col), "({row}, {col})");
+        }
+    }
+}
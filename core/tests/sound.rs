@@ -0,0 +1,30 @@
+use minesweeper_core::sound::{self, RATE};
+
+fn word(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[test]
+fn wav_headers_describe_the_samples() {
+    let bytes = sound::wav(&[0, 1, -1, i16::MAX]);
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(word(&bytes, 4) as usize, bytes.len() - 8);
+    assert_eq!(&bytes[8..16], b"WAVEfmt ");
+    assert_eq!(word(&bytes, 24), RATE);
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(word(&bytes, 40), 8);
+    assert_eq!(&bytes[44..], &[0, 0, 1, 0, 0xff, 0xff, 0xff, 0x7f]);
+}
+
+#[test]
+fn the_fanfare_is_under_a_second_and_not_silent() {
+    let bytes = sound::fanfare();
+    let samples: Vec<i16> = bytes[44..]
+        .chunks(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    assert!(samples.len() < RATE as usize);
+    assert!(samples.iter().any(|&sample| sample.unsigned_abs() > 1000));
+    // Each note fades out, so the file ends quietly
+    assert!(samples.last().unwrap().unsigned_abs() < 100);
+}
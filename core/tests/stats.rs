@@ -0,0 +1,460 @@
+use std::time::Duration;
+
+use minesweeper_core::achievement;
+use minesweeper_core::hint::{HintError, Hints};
+use minesweeper_core::rules::Ruleset;
+use minesweeper_core::save::{self, Format, SaveError};
+use minesweeper_core::sim;
+use minesweeper_core::stats::{
+    Configuration, Efficiency, Progress, Record, Session, Stats, Streak, Variant,
+};
+use minesweeper_core::CellType;
+
+fn record(time: u64, won: bool, hints: u32) -> Record {
+    Record {
+        width: 9,
+        height: 9,
+        mines: 10,
+        won,
+        time: Duration::from_secs(time),
+        hints,
+        clicks: 20,
+        wasted: 1,
+        flags: 10,
+        finished: 1_700_000_000,
+        bbbv: 25,
+        opening_clicks: 3,
+        number_clicks: 5,
+        variant: Variant::CLASSIC,
+        seed: None,
+    }
+}
+
+fn unlocked(stats: &mut Stats, record: Record) -> Vec<&'static str> {
+    stats
+        .push(record)
+        .iter()
+        .map(|achievement| achievement.id)
+        .collect()
+}
+
+#[test]
+fn hints_point_at_proven_safe_cells_until_the_budget_runs_out() {
+    let board = sim::board(
+        "
+        o * o
+        o o o
+        . . .
+        ",
+    )
+    .unwrap();
+    let mut hints = Hints::new(2, Duration::from_secs(10));
+    for _ in 0..2 {
+        let (row, col) = hints.request(&board).unwrap();
+        assert_ne!(board.cell(row, col).cell_type, CellType::Mine);
+    }
+    assert_eq!(hints.request(&board), Err(HintError::Exhausted));
+    assert_eq!(hints.used(), 2);
+    assert_eq!(hints.penalty(), Duration::from_secs(20));
+}
+
+#[test]
+fn hints_that_find_nothing_are_free() {
+    let board = sim::board(". *\n* .").unwrap();
+    let mut hints = Hints::new(1, Duration::from_secs(10));
+    assert_eq!(hints.request(&board), Err(HintError::NoSafeCell));
+    assert_eq!(hints.remaining(), 1);
+    assert_eq!(hints.penalty(), Duration::ZERO);
+    assert_eq!(Hints::none().request(&board), Err(HintError::Exhausted));
+
+    // Liars would make the solver prove cells wrong
+    let liar = sim::board("o * o\no o o\n. . .")
+        .unwrap()
+        .with_rules(Ruleset {
+            liar: true,
+            ..Ruleset::CLASSIC
+        });
+    assert_eq!(hints.request(&liar), Err(HintError::Unsupported));
+    assert_eq!(hints.remaining(), 1);
+}
+
+#[test]
+fn assisted_games_are_left_out_of_personal_bests() {
+    let mut stats = Stats::default();
+    stats.push(record(90, true, 0));
+    stats.push(record(40, true, 2));
+    stats.push(record(30, false, 0));
+    stats.push(record(70, true, 0));
+    assert_eq!(stats.best(9, 9, 10), Some(&record(70, true, 0)));
+    assert_eq!(stats.best(16, 16, 40), None);
+}
+
+#[test]
+fn stats_round_trip_in_every_format() {
+    let mut stats = Stats::default();
+    stats.push(record(90, true, 0));
+    stats.push(Record {
+        time: Duration::from_millis(1234),
+        ..record(0, false, 3)
+    });
+    for format in [Format::Text, Format::Json, Format::Binary] {
+        let loaded: Stats = save::load(&save::save(&stats, format)).unwrap();
+        assert_eq!(loaded, stats);
+    }
+}
+
+#[test]
+fn broken_records_are_reported() {
+    let text = "minesweeper stats 6\ngame.0 9 9 10 won 1000 0 5 0 0 0 7 1 2 classic -\ngame.1 9 9 10 won 1000 0 5 0 0 0 7 1 2 fog 12\n";
+    assert_eq!(
+        save::load::<Stats>(text.as_bytes()),
+        Err(SaveError::Invalid {
+            field: "game.1".to_owned(),
+            value: "9 9 10 won 1000 0 5 0 0 0 7 1 2 fog 12".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn older_stats_get_empty_click_counts_3bv_and_origins() {
+    let text = "minesweeper stats 1\ngame.0 9 9 10 won 61500 2\n";
+    let stats: Stats = save::load(text.as_bytes()).unwrap();
+    assert_eq!(
+        stats.records,
+        vec![Record {
+            time: Duration::from_millis(61500),
+            clicks: 0,
+            wasted: 0,
+            flags: 0,
+            finished: 0,
+            bbbv: 0,
+            opening_clicks: 0,
+            number_clicks: 0,
+            ..record(0, true, 2)
+        }]
+    );
+}
+
+#[test]
+fn seeds_are_kept_and_older_games_have_none() {
+    let text = "minesweeper stats 5\ngame.0 9 9 10 won 1000 0 5 0 0 0 7 1 2 classic\n";
+    let stats: Stats = save::load(text.as_bytes()).unwrap();
+    assert_eq!(stats.records[0].seed, None);
+
+    let seeded = Stats {
+        records: vec![Record {
+            seed: Some(u64::MAX),
+            ..record(10, true, 0)
+        }],
+        ..Stats::default()
+    };
+    let loaded: Stats = save::load(&save::save(&seeded, Format::Text)).unwrap();
+    assert_eq!(loaded, seeded);
+}
+
+#[test]
+fn merging_adds_only_games_not_already_there() {
+    let mut here = Stats::default();
+    here.push(Record {
+        finished: 100,
+        ..record(50, true, 0)
+    });
+    here.push(Record {
+        finished: 300,
+        ..record(60, false, 0)
+    });
+    here.add_clicks(9, 9, &[(0, 0), (0, 0), (1, 1)]);
+
+    let mut there = Stats::default();
+    there.push(Record {
+        finished: 200,
+        ..record(70, true, 0)
+    });
+    there.push(Record {
+        finished: 300,
+        ..record(60, false, 0)
+    });
+    there.add_clicks(9, 9, &[(0, 0), (2, 2)]);
+    there.add_clicks(16, 16, &[(3, 3)]);
+
+    assert_eq!(here.merge(there.clone()), 1);
+    let finished: Vec<u64> = here.records.iter().map(|record| record.finished).collect();
+    assert_eq!(finished, [100, 200, 300]);
+    let ids: Vec<&str> = here
+        .unlocked
+        .iter()
+        .map(|unlock| unlock.id.as_str())
+        .collect();
+    assert_eq!(ids, ["first-win"]);
+    assert_eq!(here.unlocked[0].at, 100);
+    let map = here.heatmap(9, 9).unwrap();
+    assert_eq!((map.get(0, 0), map.get(1, 1), map.get(2, 2)), (2, 1, 1));
+    assert!(here.heatmap(16, 16).is_some());
+
+    let merged = here.clone();
+    assert_eq!(here.merge(there), 0);
+    assert_eq!(here, merged);
+}
+
+#[test]
+fn achievements_unlock_once() {
+    let mut stats = Stats::default();
+    assert_eq!(
+        unlocked(&mut stats, record(50, false, 0)),
+        Vec::<&str>::new()
+    );
+    assert_eq!(unlocked(&mut stats, record(50, true, 0)), vec!["first-win"]);
+    assert_eq!(
+        unlocked(&mut stats, record(50, true, 0)),
+        Vec::<&str>::new()
+    );
+    assert!(stats.is_unlocked("first-win"));
+
+    let clean = Record {
+        wasted: 0,
+        flags: 0,
+        ..record(50, true, 0)
+    };
+    assert_eq!(unlocked(&mut stats, clean), vec!["no-flags", "efficient"]);
+
+    let loaded: Stats = save::load(&save::save(&stats, Format::Text)).unwrap();
+    assert_eq!(loaded, stats);
+}
+
+#[test]
+fn fast_expert_wins_only_count_without_hints() {
+    let expert = |time, hints| Record {
+        width: 30,
+        height: 16,
+        mines: 99,
+        ..record(time, true, hints)
+    };
+    let mut stats = Stats::default();
+    stats.push(record(50, true, 0));
+    assert_eq!(unlocked(&mut stats, expert(90, 1)), vec!["expert"]);
+    assert_eq!(unlocked(&mut stats, expert(120, 0)), Vec::<&str>::new());
+    assert_eq!(unlocked(&mut stats, expert(99, 0)), vec!["expert-100"]);
+}
+
+#[test]
+fn a_week_of_daily_wins_unlocks_the_streak() {
+    let mut stats = Stats::default();
+    let on_day = |day: u64, won| Record {
+        finished: day * 86_400 + 3600,
+        ..record(50, won, 0)
+    };
+    for day in [10, 11, 12, 14, 15, 16, 17, 18] {
+        stats.push(on_day(day, true));
+    }
+    // Day 13 is missing, so the streak is only five days long
+    assert!(!stats.is_unlocked("daily-7"));
+    // A loss doesn't break the streak as long as the day has a win
+    stats.push(on_day(19, false));
+    stats.push(on_day(19, true));
+    assert!(!stats.is_unlocked("daily-7"));
+    assert_eq!(unlocked(&mut stats, on_day(20, true)), vec!["daily-7"]);
+    assert!(achievement::find("daily-7").is_some());
+}
+
+#[test]
+fn streaks_are_kept_per_difficulty() {
+    let mut stats = Stats::default();
+    let expert = |won| Record {
+        width: 30,
+        height: 16,
+        mines: 99,
+        ..record(200, won, 0)
+    };
+    for won in [true, true, true, false, true] {
+        stats.push(record(50, won, 0));
+        stats.push(expert(!won));
+    }
+    assert_eq!(
+        stats.streak(9, 9, 10),
+        Streak {
+            current: 1,
+            best: 3
+        }
+    );
+    assert_eq!(
+        stats.streak(30, 16, 99),
+        Streak {
+            current: 0,
+            best: 1
+        }
+    );
+    assert_eq!(stats.streak(16, 16, 40), Streak::default());
+    assert_eq!(stats.sizes(), vec![(9, 9, 10), (30, 16, 99)]);
+    assert_eq!(stats.games(9, 9, 10).count(), 5);
+}
+
+#[test]
+fn sessions_average_over_their_wins() {
+    let mut session = Session::default();
+    assert_eq!(session.average_time(), None);
+    session.add(&record(50, true, 0));
+    session.add(&record(10, false, 0));
+    session.add(&Record {
+        bbbv: 100,
+        ..record(100, true, 0)
+    });
+    assert_eq!((session.games, session.wins), (3, 2));
+    assert_eq!(session.average_time(), Some(Duration::from_secs(75)));
+    // 25 3BV in 50s and 100 in 100s
+    assert_eq!(session.average_speed(), Some(0.75));
+}
+
+#[test]
+fn efficiency_comes_from_clicks_3bv_and_time() {
+    // 25 3BV in 20 clicks, one of them wasted
+    let efficiency = record(50, true, 0).efficiency().unwrap();
+    assert_eq!(efficiency.ioe, 1.25);
+    assert_eq!(efficiency.throughput, 25.0 / 19.0);
+    assert_eq!(efficiency.rqp, 100.0);
+    assert_eq!(record(50, false, 0).efficiency(), None);
+    let old = Record {
+        clicks: 0,
+        ..record(50, true, 0)
+    };
+    assert_eq!(old.efficiency(), None);
+
+    let mut stats = Stats::default();
+    for game in [
+        record(50, true, 0),
+        record(10, true, 0),
+        record(5, false, 0),
+        old,
+    ] {
+        stats.push(game);
+    }
+    let Some(Efficiency { ioe, rqp, .. }) = stats.efficiency(9, 9, 10) else {
+        panic!("expected an average");
+    };
+    assert_eq!((ioe, rqp), (1.25, 52.0));
+    assert_eq!(stats.efficiency(30, 16, 99), None);
+}
+
+#[test]
+fn best_times_are_kept_per_configuration() {
+    let no_guess = Record {
+        variant: Variant::CLASSIC.with(Variant::NO_GUESS),
+        ..record(20, true, 0)
+    };
+    let custom = Record {
+        width: 12,
+        mines: 30,
+        ..record(90, true, 0)
+    };
+    let mut stats = Stats::default();
+    for game in [record(60, true, 0), record(40, true, 2), no_guess, custom] {
+        stats.push(game);
+    }
+    // The classic best ignores the faster no-guess and assisted games
+    assert_eq!(stats.best(9, 9, 10).unwrap().time, Duration::from_secs(60));
+    let assisted = Configuration {
+        assisted: true,
+        ..Configuration::classic(9, 9, 10)
+    };
+    assert_eq!(
+        stats.best_in(assisted).unwrap().time,
+        Duration::from_secs(40)
+    );
+    assert_eq!(
+        stats.best_in(no_guess.configuration()).unwrap().time,
+        Duration::from_secs(20)
+    );
+    assert_eq!(
+        stats.configurations(),
+        vec![
+            Configuration::classic(9, 9, 10),
+            assisted,
+            no_guess.configuration(),
+            custom.configuration(),
+        ]
+    );
+    let round_trip: Stats = save::load(&save::save(&stats, Format::Text)).unwrap();
+    assert_eq!(
+        round_trip.records[2].variant.names().collect::<Vec<_>>(),
+        ["no-guess"]
+    );
+}
+
+#[test]
+fn auto_chord_games_are_a_variant_of_their_own() {
+    let variant = Variant::NO_GUESS.with(Variant::AUTO_CHORD);
+    assert_eq!(variant.encode(), "no-guess+auto-chord");
+    assert_eq!(Variant::decode("no-guess+auto-chord"), Some(variant));
+    // The assist doesn't change how the mines were placed
+    assert_eq!(variant.generator().name(), "uniform");
+}
+
+#[test]
+fn trainer_progress_compares_the_latest_wins_with_those_before() {
+    let mut stats = Stats::default();
+    for clicks in (1..=25).rev() {
+        let trainer = Record {
+            clicks,
+            variant: Variant::TRAINER,
+            ..record(60, true, 0)
+        };
+        stats.push(trainer);
+        // Losses and other games have no say
+        stats.push(Record {
+            variant: Variant::TRAINER,
+            ..record(60, false, 0)
+        });
+        stats.push(record(60, true, 0));
+    }
+    let ioes = stats.trainer_ioes();
+    assert_eq!(ioes.len(), 25);
+    assert_eq!(ioes[0], 1.0);
+    assert_eq!(ioes[24], 25.0);
+
+    let progress = Progress::of(&ioes).unwrap();
+    assert_eq!(progress.games, 10);
+    let mean = |clicks: std::ops::RangeInclusive<u32>| {
+        clicks
+            .clone()
+            .map(|clicks| 25.0 / clicks as f64)
+            .sum::<f64>()
+            / clicks.count() as f64
+    };
+    assert!((progress.recent - mean(1..=10)).abs() < 1e-9);
+    assert!((progress.before.unwrap() - mean(11..=20)).abs() < 1e-9);
+
+    let first = Progress::of(&ioes[..3]).unwrap();
+    assert_eq!((first.games, first.before), (3, None));
+    assert_eq!(Progress::of(&[]), None);
+}
+
+#[test]
+fn heatmaps_count_clicks_per_board_size() {
+    let mut stats = Stats::default();
+    stats.add_clicks(3, 2, &[(0, 0), (1, 2), (0, 0), (9, 9)]);
+    stats.add_clicks(3, 2, &[(0, 0)]);
+    stats.add_clicks(1000, 1000, &[(500, 500)]);
+    let map = stats.heatmap(3, 2).unwrap();
+    assert_eq!((map.get(0, 0), map.get(1, 2), map.get(0, 1)), (3, 1, 0));
+    assert_eq!(map.max(), 3);
+    assert_eq!(stats.heatmap(2, 3), None);
+
+    let text = save::save(&stats, Format::Text);
+    // A million cells with one click stays a few bytes
+    assert!(text.len() < 200, "{}", String::from_utf8_lossy(&text));
+    let loaded: Stats = save::load(&text).unwrap();
+    assert_eq!(loaded, stats);
+}
+
+#[test]
+fn heatmaps_of_the_wrong_size_are_rejected() {
+    for value in ["1 2 3", "z7", "z5 1 1", "z99999999999999999999"] {
+        let text = format!("minesweeper stats 3\nheatmap.3x2 {}\n", value);
+        assert!(
+            save::load::<Stats>(text.as_bytes()).is_err(),
+            "{} loaded",
+            value
+        );
+    }
+    let text = "minesweeper stats 3\nheatmap.3x2 z2 1 z3\n";
+    assert!(save::load::<Stats>(text.as_bytes()).is_ok());
+}
@@ -0,0 +1,132 @@
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::sync::{self, Action, Folder, Manifest};
+
+#[test]
+fn the_newer_copy_wins() {
+    assert_eq!(sync::resolve(Some(20), Some(10)), Action::Upload);
+    assert_eq!(sync::resolve(Some(10), Some(20)), Action::Download);
+    assert_eq!(sync::resolve(Some(10), Some(10)), Action::Keep);
+    assert_eq!(sync::resolve(Some(10), None), Action::Upload);
+    assert_eq!(sync::resolve(None, Some(10)), Action::Download);
+    assert_eq!(sync::resolve(None, None), Action::Keep);
+}
+
+#[test]
+fn manifests_round_trip() {
+    let mut manifest = Manifest::default();
+    manifest.set("config.txt", 1_700_000_000);
+    manifest.set("theme.txt", 5);
+    manifest.set("config.txt", 1_700_000_100);
+    assert_eq!(manifest.get("config.txt"), Some(1_700_000_100));
+    assert_eq!(manifest.get("stats.txt"), None);
+    assert!(manifest.names().eq(["config.txt", "theme.txt"]));
+    let text = save::save(&manifest, Format::Text);
+    assert_eq!(
+        String::from_utf8(text.clone()).unwrap(),
+        "minesweeper manifest 1\nfile.config.txt 1700000100\nfile.theme.txt 5\n"
+    );
+    assert_eq!(save::load::<Manifest>(&text).unwrap(), manifest);
+}
+
+#[test]
+fn only_plain_theme_files_are_synced_as_themes() {
+    for name in [
+        "themes/night.txt",
+        "themes/high contrast.txt",
+        "themes/a.b.txt",
+    ] {
+        assert!(sync::is_theme(name), "{}", name);
+    }
+    for name in [
+        "config.txt",
+        "themes/",
+        "themes/.txt",
+        "themes/night.toml",
+        "themes/../config.txt",
+        "themes/sub/night.txt",
+        "themes/..\\stats.txt",
+        "themes/C:night.txt",
+        "/themes/night.txt",
+    ] {
+        assert!(!sync::is_theme(name), "{}", name);
+    }
+}
+
+#[test]
+fn base64_pads_to_whole_quads() {
+    assert_eq!(sync::base64(b""), "");
+    assert_eq!(sync::base64(b"f"), "Zg==");
+    assert_eq!(sync::base64(b"fo"), "Zm8=");
+    assert_eq!(sync::base64(b"foo"), "Zm9v");
+    assert_eq!(sync::base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(sync::base64(&[0xff, 0xfe, 0x00]), "//4A");
+}
+
+#[test]
+fn folders_are_read_from_their_url() {
+    let folder = Folder::new("http://example.com/dav/minesweeper", "me", "secret").unwrap();
+    assert_eq!((folder.host(), folder.port()), ("example.com", 80));
+    assert_eq!(folder.path(), "/dav/minesweeper/");
+    assert_eq!(
+        folder.head("PUT", "config.txt", 12),
+        "PUT /dav/minesweeper/config.txt HTTP/1.1\r\n\
+         Host: example.com\r\n\
+         Connection: close\r\n\
+         Content-Length: 12\r\n\
+         Authorization: Basic bWU6c2VjcmV0\r\n\r\n"
+    );
+
+    let anonymous = Folder::new("http://10.0.0.2:8080", "", "").unwrap();
+    assert_eq!((anonymous.host(), anonymous.port()), ("10.0.0.2", 8080));
+    assert_eq!(anonymous.path(), "/");
+    let head = anonymous.head("GET", "", 0);
+    assert!(head.contains("\r\nHost: 10.0.0.2:8080\r\n"));
+    assert!(!head.contains("Authorization"));
+    let default = Folder::new("http://example.com:80/", "", "").unwrap();
+    assert!(default
+        .head("GET", "", 0)
+        .contains("\r\nHost: example.com\r\n"));
+
+    assert!(Folder::new("https://example.com/", "", "").is_err());
+    assert!(Folder::new("http://example.com:http/", "", "").is_err());
+}
+
+#[test]
+fn replies_are_split_into_status_and_body() {
+    let reply = b"HTTP/1.1 404 Not Found\r\nContent-Length: 5\r\n\r\ngone!";
+    assert_eq!(sync::parse_reply(reply), Some((404, b"gone!".to_vec())));
+    let empty = b"HTTP/1.1 201 Created\r\n\r\n";
+    assert_eq!(sync::parse_reply(empty), Some((201, Vec::new())));
+
+    for broken in [
+        &b""[..],
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nok",
+        b"HTTP/1.1 OK\r\n\r\n",
+        b"\r\n\r\n",
+    ] {
+        assert_eq!(sync::parse_reply(broken), None, "{:?}", broken);
+    }
+}
+
+#[test]
+fn chunked_replies_are_joined() {
+    let reply = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+        4\r\nmine\r\n7;note=x\r\nsweeper\r\n0\r\n\r\n";
+    assert_eq!(
+        sync::parse_reply(reply),
+        Some((200, b"minesweeper".to_vec()))
+    );
+    let header = b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n0\r\n\r\n";
+    assert_eq!(sync::parse_reply(header), Some((200, Vec::new())));
+
+    for broken in [
+        // The last chunk is missing
+        &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nmine\r\n"[..],
+        // A chunk shorter than its size
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nff\r\nmine\r\n0\r\n\r\n",
+        // A size that isn't hexadecimal
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\nmine\r\n0\r\n\r\n",
+    ] {
+        assert_eq!(sync::parse_reply(broken), None, "{:?}", broken);
+    }
+}
@@ -0,0 +1,112 @@
+use minesweeper_core::plugin::Message;
+use minesweeper_core::sim::Move;
+use minesweeper_core::tutorial::{Goal, Tutorial};
+use minesweeper_core::{CellState, Game, Status};
+
+/// Plays `step` if the tutorial allows it, and returns whether it did.
+fn play(tutorial: &mut Tutorial, game: &mut Game, step: Move) -> bool {
+    if !tutorial.attempt(game.board(), step) {
+        return false;
+    }
+    let result = match step {
+        Move::Reveal(row, col) => game.reveal(row, col),
+        Move::Flag(row, col) => game.toggle_flag(row, col),
+        Move::Chord(row, col) => game.chord(row, col),
+    };
+    assert_eq!(result, Ok(()), "{:?} was allowed but failed", step);
+    true
+}
+
+#[test]
+fn the_lesson_can_be_followed_to_a_cleared_board() {
+    let mut tutorial = Tutorial::new();
+    let mut game = Game::new(Tutorial::board());
+
+    while tutorial.step().goal != Goal::Clear {
+        let step = match tutorial.step().goal {
+            Goal::Read => Move::Reveal(4, 4),
+            Goal::Play(step) => step,
+            Goal::Clear => unreachable!(),
+        };
+        let before = tutorial.position();
+        play(&mut tutorial, &mut game, step);
+        assert_eq!(tutorial.position(), before + 1);
+    }
+    assert_eq!(tutorial.position(), tutorial.len() - 1);
+    // The chord opened the cell next to the flag
+    assert_eq!(game.board().cell(2, 4).cell_state, CellState::Visible);
+
+    for step in [Move::Chord(2, 2), Move::Chord(3, 2)] {
+        assert!(
+            play(&mut tutorial, &mut game, step),
+            "{:?} was blocked",
+            step
+        );
+    }
+    assert_eq!(game.status(), Status::Won);
+}
+
+#[test]
+fn reading_steps_move_on_without_playing() {
+    let mut tutorial = Tutorial::new();
+    let mut game = Game::new(Tutorial::board());
+    assert!(!play(&mut tutorial, &mut game, Move::Reveal(2, 3)));
+    assert_eq!(tutorial.position(), 1);
+    assert_eq!(game.board().revealed(), 0);
+}
+
+#[test]
+fn moves_other_than_the_one_asked_for_are_blocked() {
+    let mut tutorial = Tutorial::new();
+    let mut game = Game::new(Tutorial::board());
+    play(&mut tutorial, &mut game, Move::Reveal(0, 0));
+    assert_eq!(tutorial.step().highlight, vec![(0, 0)]);
+
+    assert!(!play(&mut tutorial, &mut game, Move::Reveal(0, 1)));
+    assert!(!play(&mut tutorial, &mut game, Move::Flag(0, 0)));
+    assert_eq!(tutorial.position(), 1);
+    assert_eq!(tutorial.hint(), Some(&Message::new("tutorial-not-that")));
+
+    assert!(play(&mut tutorial, &mut game, Move::Reveal(0, 0)));
+    assert_eq!(tutorial.hint(), None);
+}
+
+#[test]
+fn clicking_a_revealed_number_counts_as_a_chord() {
+    let mut tutorial = Tutorial::new();
+    let mut game = Game::new(Tutorial::board());
+    for step in [
+        Move::Reveal(0, 0),
+        Move::Reveal(0, 0),
+        Move::Reveal(0, 0),
+        Move::Flag(2, 3),
+    ] {
+        play(&mut tutorial, &mut game, step);
+    }
+    assert_eq!(tutorial.step().goal, Goal::Play(Move::Chord(1, 3)));
+    assert!(tutorial.attempt(game.board(), Move::Reveal(1, 3)));
+}
+
+#[test]
+fn mistakes_are_blocked_while_clearing() {
+    let mut tutorial = Tutorial::new();
+    let mut game = Game::new(Tutorial::board());
+    for step in [
+        Move::Reveal(0, 0),
+        Move::Reveal(0, 0),
+        Move::Reveal(0, 0),
+        Move::Flag(2, 3),
+        Move::Chord(1, 3),
+    ] {
+        play(&mut tutorial, &mut game, step);
+    }
+    assert_eq!(tutorial.step().goal, Goal::Clear);
+
+    assert!(!play(&mut tutorial, &mut game, Move::Reveal(4, 0)));
+    assert_eq!(tutorial.hint(), Some(&Message::new("tutorial-mine")));
+    assert!(!play(&mut tutorial, &mut game, Move::Flag(4, 1)));
+    assert_eq!(tutorial.hint(), Some(&Message::new("tutorial-safe")));
+    assert!(play(&mut tutorial, &mut game, Move::Reveal(4, 1)));
+    assert_eq!(tutorial.hint(), None);
+    assert_eq!(game.status(), Status::Playing);
+}
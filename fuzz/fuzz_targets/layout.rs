@@ -0,0 +1,15 @@
+//! Test layouts and move scripts, played through the simulation harness.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_core::sim::Sim;
+
+fuzz_target!(|input: &str| {
+    // Everything after the first blank line is the move script
+    let (layout, script) = input.split_once("\n\n").unwrap_or((input, ""));
+    if let Ok(mut sim) = Sim::new(layout) {
+        let _ = sim.run(script);
+        let _ = sim.view();
+    }
+});
@@ -0,0 +1,35 @@
+//! Every line a client or server might receive, including ones that are not
+//! UTF-8. Decoded states and deltas are applied the way the client does.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_core::protocol::{self, ClientMessage, ServerMessage};
+use minesweeper_core::{Board, FlagCapture};
+
+fuzz_target!(|data: &[u8]| {
+    let line = String::from_utf8_lossy(data);
+    let _ = ClientMessage::decode(&line);
+    match ServerMessage::decode(&line) {
+        Some(ServerMessage::State {
+            width,
+            height,
+            mines,
+            scores,
+            turn,
+            cells,
+        }) => {
+            protocol::restore(width, height, mines, scores, turn, &cells);
+        }
+        Some(ServerMessage::Delta {
+            scores,
+            turn,
+            changes,
+        }) => {
+            let mut board = Board::with_seed(8, 8, 10, 0).unwrap();
+            let mut game = FlagCapture::new(10);
+            protocol::apply_delta(&mut board, &mut game, scores, turn, &changes);
+        }
+        _ => {}
+    }
+});
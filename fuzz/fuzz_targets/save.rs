@@ -0,0 +1,14 @@
+//! Save files in any format, including ones cut short or edited by hand.
+//! Whatever loads must save again.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::Board;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(board) = save::load::<Board>(data) {
+        save::save(&board, Format::Binary);
+    }
+});
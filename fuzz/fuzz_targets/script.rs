@@ -0,0 +1,38 @@
+//! Bot and mod scripts: parsing must fail cleanly, whatever parses as a bot
+//! must play a game to the end, and whatever parses as a mod must run its
+//! hooks.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_core::agent::{Agent, View};
+use minesweeper_core::plugin::PluginRegistry;
+use minesweeper_core::script::{Script, ScriptAgent, ScriptMod};
+use minesweeper_core::Board;
+
+fuzz_target!(|source: &str| {
+    if let Ok(script) = ScriptMod::parse("fuzz", source) {
+        let mut registry = PluginRegistry::new();
+        registry.install(&script);
+        let mut board = Board::with_seed(8, 8, 10, 0).unwrap();
+        board.reveal_cells(0, 0);
+        for row in 0..8 {
+            registry.allow_reveal(&board, row, row);
+            registry.after_reveal(&mut board, row, row);
+        }
+        registry.widgets().for_each(|widget| drop(widget.text(&board)));
+        registry.analyze(&board, true);
+    }
+    let Ok(script) = Script::parse("fuzz", source) else {
+        return;
+    };
+    let mut agent = ScriptAgent::new(script);
+    agent.reset(0);
+    let mut board = Board::with_seed(8, 8, 10, 0).unwrap();
+    while !board.is_won() && !board.is_lost() {
+        let Some(step) = agent.next_move(&View::of(&board)) else {
+            break;
+        };
+        board.reveal_cells(step.row, step.col);
+    }
+});
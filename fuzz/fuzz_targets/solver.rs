@@ -0,0 +1,52 @@
+//! The solvers on random boards with a random set of safe cells already
+//! revealed. The first bytes pick the board, the rest are a bitmask over
+//! its cells.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_core::agent::{Agent, SinglePointAgent, View};
+use minesweeper_core::solver::Knowledge;
+use minesweeper_core::{generate, Board, CellType, Pos};
+
+fuzz_target!(|data: &[u8]| {
+    let [width, height, mines, seed, rest @ ..] = data else {
+        return;
+    };
+    let (width, height) = (*width as usize % 32 + 1, *height as usize % 32 + 1);
+    let mines = *mines as usize % (width * height);
+    let Ok(mut board) = Board::with_seed(width, height, mines, *seed as u64) else {
+        return;
+    };
+
+    for i in 0..width * height {
+        let shown = rest
+            .get(i / 8)
+            .is_some_and(|byte| byte & (1 << (i % 8)) != 0);
+        let (row, col) = (i / width, i % width);
+        if shown && board.cell(row, col).cell_type != CellType::Mine {
+            board.reveal_cells(row, col);
+        }
+    }
+
+    let start = Pos::new(rest.len() % height, *seed as usize % width);
+    let _ = generate::solvable(&board, start);
+
+    // Whatever the solver proves has to be true of the board
+    let knowledge = Knowledge::of(&View::of(&board));
+    for &(row, col) in &knowledge.mines {
+        assert_eq!(board.cell(row, col).cell_type, CellType::Mine);
+    }
+    for &(row, col) in &knowledge.safe {
+        assert_ne!(board.cell(row, col).cell_type, CellType::Mine);
+    }
+
+    let mut agent = SinglePointAgent::new();
+    agent.reset(*seed as u64);
+    while !board.is_won() && !board.is_lost() {
+        let Some(step) = agent.next_move(&View::of(&board)) else {
+            break;
+        };
+        board.reveal_cells(step.row, step.col);
+    }
+});
@@ -0,0 +1,278 @@
+language-name = Deutsch
+
+# Statuszeile
+mines-left = { $count } Minen übrig
+lives-left = Leben: { $count }
+mines-shifting = Eine Mine wandert in { $seconds } s
+scenario-bonus = Bonus: { $seconds } s, Uhr angehalten
+scenario-mines = { $count } weitere Minen aufgetaucht
+scenario-unlocked = Ein gesperrter Bereich ist offen
+time = { $seconds } s
+paused = Pausiert. Klicken oder eine Taste drücken zum Weiterspielen.
+coordinates = Spalte { $col }, Zeile { $row }
+hints-left = Tipps: { $count }
+streak = Serie: { $current } (beste { $best })
+streak-at-risk = Serie: { $count }
+hint-none = Kein Feld ist sicher beweisbar, du musst raten
+hint-exhausted = Keine Tipps mehr
+hint-unsupported = Keine Tipps bei eigenen Regeln
+progress = { $cleared }/{ $total } aufgedeckt
+board-cleared = Feld geräumt!
+hit-mine = Mine getroffen, { $percent } % aufgedeckt
+efficiency = Klicks: { $clicks } (ZiNi { $zini })
+result-speed = 3BV/s: { $speed }
+result-ioe = IOE: { $ioe }
+result-throughput = Durchsatz: { $throughput }
+result-rqp = RQP: { $rqp }
+rating = Schwierigkeit
+trainer-score = Wertung: { $ioe } IOE
+trainer-clicks = { $clicks } Klicks für { $bbbv } 3BV
+trainer-waste-none = Keine verschwendeten Klicks
+trainer-waste-no-effect = Zug { $step } ({ $row }, { $col }): nichts verändert
+trainer-waste-unflag = Zug { $step } ({ $row }, { $col }): Flagge entfernt
+trainer-waste-unused-flag = Zug { $step } ({ $row }, { $col }): Flagge nie genutzt
+trainer-waste-more = und { $count } weitere verschwendet
+trainer-progress = Letzte { $games } Siege: { $ioe } IOE
+trainer-progress-change = Letzte { $games } Siege: { $ioe } IOE ({ $change })
+player-score = Spieler { $player }: { $score }
+player-wins = Spieler { $player } gewinnt!
+draw = Unentschieden!
+
+# Einstellungen
+menu-title = Eigenes Spiel
+menu-preset = Vorgabe
+menu-preset-custom = Eigene
+menu-width = Breite
+menu-height = Höhe
+menu-mines = Minen
+menu-density = Dichte %
+menu-density-result = { $mines } Minen, { $difficulty }
+menu-density-ramp = { $mines } Minen, oben { $from } %, unten { $to } %
+menu-no-guess = Ohne Raten
+menu-trainer = Effizienztraining
+menu-moving = Wandernde Minen
+menu-placement = Minen
+menu-stars = Schwierigkeit
+menu-stars-any = Beliebig
+menu-stars-count = { $count }/5
+placement-uniform = Gleichmäßig
+placement-clustered = Gehäuft
+placement-dispersed = Verstreut
+placement-gradient = Verlauf
+placement-ramp = Rampe (5 % bis 35 %)
+menu-language = Sprache
+menu-mouse = Maus
+menu-mouse-right-handed = Rechtshändig
+menu-mouse-left-handed = Linkshändig
+menu-mouse-custom = Eigene (config.txt)
+menu-appearance = Erscheinungsbild
+menu-appearance-auto = Automatisch
+menu-appearance-light = Hell
+menu-appearance-dark = Dunkel
+menu-theme = Design
+menu-theme-default = Eingebaut
+menu-reduced-motion = Bewegung reduzieren
+menu-haptics = Auf Touchscreens vibrieren
+menu-haptic-strength = Vibration %
+theme-failed = Design nicht geladen: { $error }
+menu-new-game = Neues Spiel
+menu-achievements = Erfolge
+menu-stats = Statistik
+menu-reset-session = Sitzung zurücksetzen
+menu-play-seed = Seed spielen
+menu-copy-seed = Seed kopieren
+menu-custom-rules = Eigene Regeln
+menu-rules-in-use = Regeln: { $rules }
+
+# Absturz
+restore-title = Spiel wiederherstellen
+restore-crashed = Das letzte Spiel wurde durch einen Absturz beendet.
+restore-quit = Das letzte Spiel wurde nicht beendet.
+restore-summary = { $width }x{ $height } mit { $mines } Minen, { $revealed } Felder aufgedeckt.
+restore = Wiederherstellen
+discard = Verwerfen
+
+# Spiel verlassen
+confirm-title = Dieses Spiel verlassen?
+confirm-quit = Es wird gespeichert und beim nächsten Mal angeboten.
+confirm-restart = Das laufende Spiel geht verloren.
+confirm-leave = Verlassen
+confirm-stay = Weiterspielen
+
+# Online-Spiele
+connection-lost = Verbindung verloren
+disconnected = Getrennt: { $reason }
+server-needs-protocol = Server benötigt Protokoll { $min }-{ $max }
+you-are-player = Du bist Spieler { $player }
+spectating = Zuschauer
+your-turn = Du bist dran
+opponents-turn = Der Gegner ist dran
+
+# Leistungsanzeige
+overlay-fps = FPS: { $fps }
+overlay-frame = Frame: { $time }
+overlay-draw-calls = Draw-Calls: { $count }
+overlay-generation = Generierung: { $time }
+overlay-solver = Löser: { $time }
+overlay-openings = Öffnungen: { $count } ({ $sizes })
+overlay-benchmarks = 3BV { $bbbv }, ZiNi { $zini }, HZiNi { $hzini }
+
+# Barrierefreier Modus
+a11y-cell = Zeile { $row }, Spalte { $col }: { $cell }
+a11y-row = Zeile { $row }: { $cells }
+a11y-hidden = verdeckt
+a11y-flagged = markiert
+a11y-empty = leer
+a11y-unread = Zahl verborgen
+a11y-mine = Mine
+a11y-opened = { $count } Felder aufgedeckt
+a11y-flag-placed = Flagge gesetzt
+a11y-flag-removed = Flagge entfernt
+a11y-mine-hit = Mine! Spiel verloren.
+a11y-life-lost = Mine! Verbleibende Leben: { $lives }.
+a11y-blast = Große Mine! Sie hat { $count } Felder aufgesprengt. Verbleibende Leben: { $lives }.
+a11y-dud = Ein Blindgänger! Er zündet nicht, kostet aber Zeit.
+
+# Tutorial
+tutorial-step = Schritt { $step } von { $steps }:
+tutorial-welcome = Willkommen bei Minesweeper! Unter manchen Feldern liegen Minen. Decke alle anderen Felder auf, um zu gewinnen. Klicke auf das Spielfeld, um fortzufahren.
+tutorial-reveal = Klicke auf die markierte Ecke, um sie aufzudecken. Leere Felder decken auch ihre Nachbarn auf.
+tutorial-numbers = Jede Zahl zählt die Minen, die sie berührt. Die markierte 1 hat nur einen verdeckten Nachbarn, also muss dort die Mine liegen. Klicke, um fortzufahren.
+tutorial-flag = Markiere die Mine mit einem Rechtsklick.
+tutorial-chord = Diese 1 berührt schon ihre Flagge, also sind ihre übrigen verdeckten Nachbarn sicher. Klicke sie mit der mittleren Maustaste oder normal an, um sie alle auf einmal aufzudecken.
+tutorial-clear = Räume jetzt den Rest des Spielfelds selbst ab. Fehler werden blockiert, solange du lernst.
+tutorial-done = Tutorial abgeschlossen! Drücke Escape, um ein echtes Spiel zu starten.
+tutorial-not-that = Nicht dieses Feld: folge der Markierung.
+tutorial-mine = Dort liegt eine Mine! Sieh dir die Zahlen noch einmal an.
+tutorial-safe = Dieses Feld ist sicher und braucht keine Flagge.
+
+# Achievements
+achievements-title = Erfolge ({ $unlocked }/{ $total })
+achievement-unlocked = Erfolg freigeschaltet: { $name }
+personal-best = Neue Bestzeit: { $time } s (vorher { $was } s)
+close = Schließen
+achievement-first-win = Erster Sieg
+achievement-first-win-about = Gewinne ein Spiel.
+achievement-intermediate = Fortgeschritten
+achievement-intermediate-about = Gewinne ein 16x16-Spiel mit 40 Minen.
+achievement-expert = Experte
+achievement-expert-about = Gewinne ein 30x16-Spiel mit 99 Minen.
+achievement-expert-100 = Experte unter 100
+achievement-expert-100-about = Gewinne Experte in unter 100 Sekunden ohne Tipps.
+achievement-no-flags = Ohne Flaggen
+achievement-no-flags-about = Gewinne ein Spiel mit mindestens 10 Minen, ohne eine Flagge zu setzen.
+achievement-efficient = Effizient
+achievement-efficient-about = Gewinne ein Spiel ohne einen einzigen unnötigen Klick.
+achievement-daily-7 = Wochenserie
+achievement-daily-7-about = Gewinne an 7 Tagen in Folge mindestens ein Spiel.
+achievement-games-100 = Stammspieler
+achievement-games-100-about = Beende 100 Spiele.
+
+# Statistics
+stats-title = Statistik
+stats-empty = Noch keine Spiele beendet.
+stats-heatmap = Klicks auf
+stats-efficiency = Mittel IOE { $ioe }, Durchsatz { $throughput }, RQP { $rqp }
+stats-best-times = Bestzeiten
+stats-configuration = { $width }x{ $height }, { $mines } Minen ({ $density } %)
+stats-assisted = mit Hilfe
+variant-no-guess = ohne Raten
+variant-clustered = gehäuft
+variant-dispersed = verstreut
+variant-gradient = Verlauf
+variant-auto-chord = Auto-Akkord
+variant-trainer = Training
+variant-custom = eigene Regeln
+variant-moving = wandernde Minen
+stats-clicks = Aufgedeckt: { $openings } Öffnungen begonnen, { $numbers } auf Zahlen
+stats-row = { $games } Spiele, { $wins } gewonnen, Bestzeit { $best }, Serie { $current } (beste { $streak })
+stats-export-csv = Als CSV exportieren
+stats-export-json = Als JSON exportieren
+stats-exported = Alle Spiele exportiert nach { $path }
+stats-export-failed = Spiele konnten nicht exportiert werden: { $error }
+stats-import = Importieren
+stats-imported = { $games } neue Spiele aus imports importiert
+stats-import-failed = Import fehlgeschlagen: { $error }
+
+# Session widget
+session-games = Sitzung: { $wins }/{ $games } gewonnen
+session-time = Mittlere Zeit: { $time }
+session-speed = Mittlere 3BV/s: { $speed }
+
+# Post-game review
+review-open = A: Analyse
+review-move = Zug { $step }/{ $steps }:
+review-end = Spielende: { $forced } erzwungen, { $guesses } geraten, { $blunders } Patzer
+review-opening = Der erste Klick, freies Raten
+review-forced = Sicher und erzwungen
+review-unforced = Geraten mit { $risk } % Risiko, obwohl anderswo ein sicheres Feld bewiesen war
+review-guess = Geraten mit { $risk } % Risiko, kein Feld war beweisbar sicher
+review-blunder = Dieses Feld war beweisbar eine Mine
+review-flag = Flagge auf einer bewiesenen Mine
+review-flag-unproven = Flagge, die die Zahlen noch nicht bewiesen
+review-unflag = Flagge entfernt
+review-wasted = Hat nichts verändert
+review-unjudged = Nicht bewertet: der Löser kennt diese Regeln nicht
+
+# Export
+export-open = G: GIF, V: Video
+gif-saved = Spiel gespeichert als { $path }
+gif-failed = GIF konnte nicht gespeichert werden: { $error }
+video-saved = Einzelbilder für ffmpeg gespeichert in { $path }
+video-failed = Einzelbilder konnten nicht gespeichert werden: { $error }
+replay-saved = Wiederholung gespeichert als { $path }
+replay-failed = Wiederholung konnte nicht gespeichert werden: { $error }
+replay-unavailable = Das Brett hat sich in diesem Spiel verändert, daher gibt es keine Wiederholung zum Ansehen oder Exportieren
+screenshot-saved = Bildschirmfoto gespeichert als { $path }
+screenshot-failed = Bildschirmfoto konnte nicht gespeichert werden: { $error }
+
+# Seeds
+seed-title = Seed spielen
+seed-help = Teilen-Code einfügen oder Seed für das eingestellte Feld tippen.
+seed-code = Code
+seed-play = Spielen
+seed-paste = Einfügen
+seed-invalid = Nicht spielbar: { $error }
+seed-copied = { $code } kopiert
+seed-none = Dieses Spiel hat keinen Seed zum Kopieren
+
+# Eigene Regeln
+rules-title = Eigene Regeln
+rules-help = Beliebig kombinierbar für das eingestellte Feld.
+rules-adjacency = Zahlen zählen
+rules-adjacency-king = die acht Nachbarn
+rules-adjacency-cross = die vier angrenzenden
+rules-adjacency-knight = die Springerfelder
+rules-wrap = Ränder verbunden
+rules-lives = Leben
+rules-gradient = Dichteverlauf
+rules-liar = Lügende Zahlen (um eins daneben)
+rules-fog = Nebel (sichtbare Felder um aufgedeckte, 0 für keinen)
+rules-blind = Blinde Zahlen (erst sichtbar, wenn ein gezähltes Feld markiert ist)
+rules-duds = Blindgänger (Prozent der Minen, die nur Zeit kosten)
+rules-bigs = Große Minen (Prozent der Minen, die ihre Nachbarn aufsprengen)
+rules-code = Code: { $code }-...
+rules-start = Starten
+rules-reset = Klassische Regeln
+
+# Suche nach einem Brett der gewünschten Schwierigkeit
+search-title = Suche ein Brett mit Wertung { $stars }/5
+search-progress = { $tried } von höchstens { $attempts } Brettern bewertet
+search-cancel = Abbrechen
+search-no-guess-title = Erzeuge ein Brett ohne Raten
+search-no-guess-progress = { $tried } von höchstens { $attempts } Kandidaten geprüft
+game-failed = Das Spiel konnte nicht starten: { $error }
+
+# Difficulty
+difficulty-easy = leicht
+difficulty-medium = mittel
+difficulty-hard = schwer
+difficulty-extreme = extrem
+
+# Presets
+preset-beginner = Anfänger
+preset-intermediate = Fortgeschritten
+preset-expert = Experte
+preset-evil = Teuflisch
+preset-blitz = Blitz
+preset-dense = Dichte 25 %
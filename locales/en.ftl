@@ -0,0 +1,281 @@
+# English, the reference catalog. Every other language falls back to it for
+# messages it does not have.
+
+language-name = English
+
+# Status line
+mines-left = { $count } mines left
+lives-left = Lives: { $count }
+mines-shifting = A mine moves in { $seconds }s
+scenario-bonus = Bonus: { $seconds }s, clock stopped
+scenario-mines = { $count } more mines appeared
+scenario-unlocked = A locked area opened
+time = { $seconds }s
+paused = Paused. Click or press a key to go on.
+coordinates = Column { $col }, row { $row }
+hints-left = Hints: { $count }
+streak = Streak: { $current } (best { $best })
+streak-at-risk = Streak: { $count }
+hint-none = No safe cell can be proven, you have to guess
+hint-exhausted = No hints left
+hint-unsupported = No hints under custom rules
+progress = { $cleared }/{ $total } cleared
+board-cleared = Board cleared!
+hit-mine = Hit a mine with { $percent }% cleared
+efficiency = Clicks: { $clicks } (ZiNi { $zini })
+result-speed = 3BV/s: { $speed }
+result-ioe = IOE: { $ioe }
+result-throughput = Throughput: { $throughput }
+result-rqp = RQP: { $rqp }
+rating = Difficulty
+trainer-score = Score: { $ioe } IOE
+trainer-clicks = { $clicks } clicks for { $bbbv } 3BV
+trainer-waste-none = No wasted clicks
+trainer-waste-no-effect = Move { $step } ({ $row }, { $col }): changed nothing
+trainer-waste-unflag = Move { $step } ({ $row }, { $col }): flag removed
+trainer-waste-unused-flag = Move { $step } ({ $row }, { $col }): flag never chorded
+trainer-waste-more = and { $count } more wasted
+trainer-progress = Last { $games } wins: { $ioe } IOE
+trainer-progress-change = Last { $games } wins: { $ioe } IOE ({ $change })
+player-score = Player { $player }: { $score }
+player-wins = Player { $player } wins!
+draw = Draw!
+
+# Settings window
+menu-title = Custom game
+menu-preset = Preset
+menu-preset-custom = Custom
+menu-width = Width
+menu-height = Height
+menu-mines = Mines
+menu-density = Density %
+menu-density-result = { $mines } mines, { $difficulty }
+menu-density-ramp = { $mines } mines, { $from }% at the top to { $to }% at the bottom
+menu-no-guess = No guessing
+menu-trainer = Efficiency trainer
+menu-moving = Moving mines
+menu-placement = Mines
+menu-stars = Difficulty
+menu-stars-any = Any
+menu-stars-count = { $count }/5
+placement-uniform = Uniform
+placement-clustered = Clustered
+placement-dispersed = Dispersed
+placement-gradient = Gradient
+placement-ramp = Ramp (5% to 35%)
+menu-language = Language
+menu-mouse = Mouse
+menu-mouse-right-handed = Right-handed
+menu-mouse-left-handed = Left-handed
+menu-mouse-custom = Custom (config.txt)
+menu-appearance = Appearance
+menu-appearance-auto = Automatic
+menu-appearance-light = Light
+menu-appearance-dark = Dark
+menu-theme = Theme
+menu-theme-default = Built-in
+menu-reduced-motion = Reduce motion
+menu-haptics = Vibrate on touch screens
+menu-haptic-strength = Vibration %
+theme-failed = Theme not loaded: { $error }
+menu-new-game = New game
+menu-achievements = Achievements
+menu-stats = Statistics
+menu-reset-session = Reset session
+menu-play-seed = Play seed
+menu-copy-seed = Copy seed
+menu-custom-rules = Custom rules
+menu-rules-in-use = Rules: { $rules }
+
+# Crash recovery
+restore-title = Restore game
+restore-crashed = The last game ended in a crash.
+restore-quit = The last game was left unfinished.
+restore-summary = { $width }x{ $height } with { $mines } mines, { $revealed } cells revealed.
+restore = Restore
+discard = Discard
+
+# Leaving a game
+confirm-title = Leave this game?
+confirm-quit = It will be saved and offered again next time.
+confirm-restart = The game in progress will be lost.
+confirm-leave = Leave
+confirm-stay = Keep playing
+
+# Online games
+connection-lost = Connection lost
+disconnected = Disconnected: { $reason }
+server-needs-protocol = Server needs protocol { $min }-{ $max }
+you-are-player = You are Player { $player }
+spectating = Spectating
+your-turn = Your turn
+opponents-turn = Opponent's turn
+
+# Performance overlay
+overlay-fps = FPS: { $fps }
+overlay-frame = frame: { $time }
+overlay-draw-calls = draw calls: { $count }
+overlay-generation = generation: { $time }
+overlay-solver = solver: { $time }
+overlay-openings = openings: { $count } ({ $sizes })
+overlay-benchmarks = 3BV { $bbbv }, ZiNi { $zini }, HZiNi { $hzini }
+
+# Accessible mode
+a11y-cell = Row { $row }, column { $col }: { $cell }
+a11y-row = Row { $row }: { $cells }
+a11y-hidden = hidden
+a11y-flagged = flagged
+a11y-empty = empty
+a11y-unread = number not shown
+a11y-mine = mine
+a11y-opened = Opened { $count } cells
+a11y-flag-placed = Flag placed
+a11y-flag-removed = Flag removed
+a11y-mine-hit = Mine! Game over.
+a11y-life-lost = Mine! Lives left: { $lives }.
+a11y-blast = Big mine! It blew open { $count } cells. Lives left: { $lives }.
+a11y-dud = A dud! It fizzled, but costs time.
+
+# Tutorial
+tutorial-step = Step { $step } of { $steps }:
+tutorial-welcome = Welcome to Minesweeper! Some cells hide mines. Reveal every other cell to win. Click the board to continue.
+tutorial-reveal = Click the highlighted corner to reveal it. Empty cells open up their neighbours too.
+tutorial-numbers = Each number counts the mines touching it. The highlighted 1 has only one hidden neighbour, so that cell must be the mine. Click to continue.
+tutorial-flag = Right-click the mine to flag it.
+tutorial-chord = This 1 already touches its flag, so its other hidden neighbours are safe. Middle-click it, or click it, to chord and reveal them all at once.
+tutorial-clear = Now clear the rest of the board on your own. Mistakes are blocked while you learn.
+tutorial-done = Tutorial complete! Press Escape to start a real game.
+tutorial-not-that = Not that one: follow the highlighted cell.
+tutorial-mine = That cell is a mine! Look at the numbers again.
+tutorial-safe = That cell is safe, so it needs no flag.
+
+# Achievements
+achievements-title = Achievements ({ $unlocked }/{ $total })
+achievement-unlocked = Achievement unlocked: { $name }
+personal-best = New personal best: { $time }s (was { $was }s)
+close = Close
+achievement-first-win = First win
+achievement-first-win-about = Win a game.
+achievement-intermediate = Intermediate
+achievement-intermediate-about = Win a 16x16 game with 40 mines.
+achievement-expert = Expert
+achievement-expert-about = Win a 30x16 game with 99 mines.
+achievement-expert-100 = Sub-100 expert
+achievement-expert-100-about = Win expert in under 100 seconds without hints.
+achievement-no-flags = No flags
+achievement-no-flags-about = Win a game with at least 10 mines without placing a flag.
+achievement-efficient = Efficient
+achievement-efficient-about = Win a game without a single wasted click.
+achievement-daily-7 = Week streak
+achievement-daily-7-about = Win at least one game on 7 days in a row.
+achievement-games-100 = Regular
+achievement-games-100-about = Finish 100 games.
+
+# Statistics
+stats-title = Statistics
+stats-empty = No games finished yet.
+stats-heatmap = Clicks on
+stats-efficiency = average IOE { $ioe }, throughput { $throughput }, RQP { $rqp }
+stats-best-times = Best times
+stats-configuration = { $width }x{ $height }, { $mines } mines ({ $density }%)
+stats-assisted = assisted
+variant-no-guess = no guess
+variant-clustered = clustered
+variant-dispersed = dispersed
+variant-gradient = gradient
+variant-auto-chord = auto-chord
+variant-trainer = trainer
+variant-custom = custom rules
+variant-moving = moving mines
+stats-clicks = Reveals: { $openings } started openings, { $numbers } on numbers
+stats-row = { $games } games, { $wins } won, best { $best }, streak { $current } (best { $streak })
+stats-export-csv = Export CSV
+stats-export-json = Export JSON
+stats-exported = Exported every game to { $path }
+stats-export-failed = Could not export the games: { $error }
+stats-import = Import
+stats-imported = Imported { $games } new games from imports
+stats-import-failed = Could not import: { $error }
+
+# Session widget
+session-games = Session: { $wins }/{ $games } won
+session-time = Average time: { $time }
+session-speed = Average 3BV/s: { $speed }
+
+# Post-game review
+review-open = A: review
+review-move = Move { $step }/{ $steps }:
+review-end = End of game: { $forced } forced, { $guesses } guesses, { $blunders } blunders
+review-opening = The first click, a free guess
+review-forced = Safe and forced
+review-unforced = Safe but a guess at { $risk }% risk while a proven safe cell was elsewhere
+review-guess = A guess at { $risk }% risk, nothing was proven safe
+review-blunder = This cell was a proven mine
+review-flag = Flag on a proven mine
+review-flag-unproven = Flag the numbers didn't prove yet
+review-unflag = Flag removed
+review-wasted = Changed nothing
+review-unjudged = Not judged: the solver does not know these rules
+
+# Export
+export-open = G: GIF, V: video
+gif-saved = Saved the game as { $path }
+gif-failed = Could not save the GIF: { $error }
+video-saved = Saved the frames for ffmpeg in { $path }
+video-failed = Could not save the frames: { $error }
+replay-saved = Saved the replay as { $path }
+replay-failed = Could not save the replay: { $error }
+replay-unavailable = The board changed during this game, so it has no replay to review or export
+screenshot-saved = Saved a screenshot as { $path }
+screenshot-failed = Could not save the screenshot: { $error }
+
+# Seeds
+seed-title = Play seed
+seed-help = Paste a share code, or type a seed for the board in the settings.
+seed-code = Code
+seed-play = Play
+seed-paste = Paste
+seed-invalid = Can't play this: { $error }
+seed-copied = Copied { $code }
+seed-none = This game has no seed to copy
+
+# Custom rules
+rules-title = Custom rules
+rules-help = Combine any of these for the board in the settings.
+rules-adjacency = Numbers count
+rules-adjacency-king = the eight around
+rules-adjacency-cross = the four beside
+rules-adjacency-knight = a knight's move away
+rules-wrap = Edges wrap around
+rules-lives = Lives
+rules-gradient = Density gradient
+rules-liar = Lying numbers (off by one)
+rules-fog = Fog of war (cells seen around uncovered ones, 0 for none)
+rules-blind = Blind numbers (shown once a cell they count is flagged)
+rules-duds = Duds (percent of mines that only cost time)
+rules-bigs = Big mines (percent of mines that blow open their neighbours)
+rules-code = Code: { $code }-...
+rules-start = Start
+rules-reset = Classic rules
+
+# Searching for a board of a wanted difficulty
+search-title = Looking for a board rated { $stars }/5
+search-progress = Rated { $tried } of at most { $attempts } boards
+search-cancel = Cancel
+search-no-guess-title = Generating a board without guesses
+search-no-guess-progress = Checked { $tried } of at most { $attempts } candidates
+game-failed = Could not start the game: { $error }
+
+# Difficulty
+difficulty-easy = easy
+difficulty-medium = medium
+difficulty-hard = hard
+difficulty-extreme = extreme
+
+# Presets
+preset-beginner = Beginner
+preset-intermediate = Intermediate
+preset-expert = Expert
+preset-evil = Evil
+preset-blitz = Blitz
+preset-dense = Density 25%
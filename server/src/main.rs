@@ -0,0 +1,503 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use minesweeper_core::log::{self, Level};
+use minesweeper_core::protocol::{self, CellView, ClientMessage, RoomInfo, ServerMessage};
+use minesweeper_core::{debug, info, trace, warn, Board, FlagCapture};
+
+const DEFAULT_BIND: &str = "0.0.0.0:7878";
+const BOARD_SIZE: usize = 16;
+const MINES: usize = 51;
+/// How far behind the players spectators see the game, so a spectating
+/// player can't relay what is happening to their teammate in real time.
+const SPECTATOR_DELAY: Duration = Duration::from_secs(2);
+const CHAT_LIMIT: usize = 5;
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+const CHAT_MAX_LEN: usize = 200;
+/// Clients that send nothing, not even a heartbeat, for this long are dropped.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long one message may take to write before the client is taken to
+/// have stopped reading and is dropped, so it can't stall every room.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest line a client may send, in bytes; longer ones disconnect it.
+const MAX_LINE: usize = 4096;
+
+type ClientId = usize;
+
+enum Event {
+    Connected(ClientId, TcpStream),
+    Line(ClientId, String),
+    Disconnected(ClientId),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Player(usize),
+    Spectator,
+}
+
+struct Client {
+    stream: TcpStream,
+    version: Option<u32>,
+    room: Option<(String, Role)>,
+    recent_chat: VecDeque<Instant>,
+    last_seen: Instant,
+    /// Set once the connection has been shut down, for going idle or
+    /// falling behind, while its reader thread gets round to reporting the
+    /// disconnect.
+    closed: bool,
+}
+
+impl Client {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            version: None,
+            room: None,
+            recent_chat: VecDeque::new(),
+            last_seen: Instant::now(),
+            closed: false,
+        }
+    }
+
+    /// Records a chat message, returning false if the client is over the limit.
+    fn allow_chat(&mut self, now: Instant) -> bool {
+        while let Some(sent) = self.recent_chat.front() {
+            if now.duration_since(*sent) < CHAT_WINDOW {
+                break;
+            }
+            self.recent_chat.pop_front();
+        }
+        if self.recent_chat.len() >= CHAT_LIMIT {
+            return false;
+        }
+        self.recent_chat.push_back(now);
+        true
+    }
+
+    fn send(&mut self, message: &ServerMessage) {
+        if self.closed {
+            return;
+        }
+        // A client that doesn't take its messages in time is dropped; the
+        // reader thread then reports the disconnect
+        if let Err(err) = writeln!(self.stream, "{}", message.encode()) {
+            debug!("could not write to a client: {}", err);
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+        self.closed = true;
+    }
+}
+
+struct Room {
+    board: Board,
+    game: FlagCapture,
+    players: [Option<ClientId>; 2],
+    /// Spectators with when they started watching; messages queued before
+    /// then are already part of the snapshot they were sent.
+    spectators: Vec<(ClientId, Instant)>,
+    /// Messages on their way to spectators, and the one spectator each is
+    /// for, if not all of them.
+    delayed: VecDeque<(Instant, Option<ClientId>, ServerMessage)>,
+    /// The board as last sent to clients, which deltas are computed against.
+    view: Vec<CellView>,
+}
+
+impl Room {
+    fn new() -> Self {
+        let board = Board::new(BOARD_SIZE, BOARD_SIZE, MINES).expect("MINES fits the board");
+        Self {
+            view: protocol::view(&board),
+            board,
+            game: FlagCapture::new(MINES),
+            players: [None; 2],
+            spectators: Vec::new(),
+            delayed: VecDeque::new(),
+        }
+    }
+
+    fn info(&self, name: &str) -> RoomInfo {
+        RoomInfo {
+            name: name.to_owned(),
+            players: self.players.iter().flatten().count(),
+            spectators: self.spectators.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.players.iter().all(Option::is_none) && self.spectators.is_empty()
+    }
+}
+
+struct Server {
+    clients: HashMap<ClientId, Client>,
+    rooms: HashMap<String, Room>,
+}
+
+impl Server {
+    fn new() -> Self {
+        Self {
+            clients: HashMap::new(),
+            rooms: HashMap::new(),
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Connected(id, stream) => {
+                let peer = stream.peer_addr().map(|addr| addr.to_string());
+                info!(
+                    "client {} connected from {}",
+                    id,
+                    peer.as_deref().unwrap_or("?")
+                );
+                self.clients.insert(id, Client::new(stream));
+            }
+            Event::Line(id, line) => {
+                let Some(client) = self.clients.get_mut(&id) else {
+                    return;
+                };
+                client.last_seen = Instant::now();
+                trace!("client {} sent {}", id, line);
+
+                match ClientMessage::decode(&line) {
+                    Some(ClientMessage::Hello {
+                        min_version,
+                        max_version,
+                    }) => match protocol::negotiate(min_version, max_version) {
+                        Some(version) => {
+                            debug!("client {} speaks protocol {}", id, version);
+                            client.version = Some(version);
+                            client.send(&ServerMessage::Hello { version });
+                        }
+                        None => {
+                            info!(
+                                "client {} needs protocol {}-{}, which is not supported",
+                                id, min_version, max_version
+                            );
+                            client.send(&ServerMessage::Incompatible {
+                                min_version: protocol::MIN_VERSION,
+                                max_version: protocol::VERSION,
+                            });
+                        }
+                    },
+                    Some(_) if client.version.is_none() => {
+                        self.error(id, "send HELLO first");
+                    }
+                    Some(message) => self.handle_message(id, message),
+                    None => {
+                        warn!("client {} sent a malformed message: {:?}", id, line);
+                        self.error(id, "malformed message");
+                    }
+                }
+            }
+            Event::Disconnected(id) => {
+                info!("client {} disconnected", id);
+                self.leave(id);
+                self.clients.remove(&id);
+            }
+        }
+    }
+
+    fn handle_message(&mut self, id: ClientId, message: ClientMessage) {
+        match message {
+            ClientMessage::Hello { .. } => {}
+            ClientMessage::Ping { nonce } => {
+                if let Some(client) = self.clients.get_mut(&id) {
+                    client.send(&ServerMessage::Pong { nonce });
+                }
+            }
+            ClientMessage::ListRooms => {
+                let mut rooms: Vec<RoomInfo> = self
+                    .rooms
+                    .iter()
+                    .map(|(name, room)| room.info(name))
+                    .collect();
+                rooms.sort_by(|a, b| a.name.cmp(&b.name));
+                if let Some(client) = self.clients.get_mut(&id) {
+                    client.send(&ServerMessage::Rooms(rooms));
+                }
+            }
+            ClientMessage::Leave => self.leave(id),
+            ClientMessage::Join { room: name } => {
+                self.leave(id);
+
+                let room = self.rooms.entry(name.clone()).or_insert_with(Room::new);
+                let Some(player) = room.players.iter().position(Option::is_none) else {
+                    self.error(id, "room is full");
+                    return;
+                };
+                room.players[player] = Some(id);
+                info!("client {} joined {} as player {}", id, name, player + 1);
+                let snapshot = ServerMessage::state(&room.board, &room.game);
+
+                if let Some(client) = self.clients.get_mut(&id) {
+                    client.room = Some((name, Role::Player(player)));
+                    client.send(&ServerMessage::Welcome { player });
+                    client.send(&snapshot);
+                }
+            }
+            ClientMessage::Spectate { room: name } => {
+                self.leave(id);
+
+                let room = self.rooms.entry(name.clone()).or_insert_with(Room::new);
+                let now = Instant::now();
+                room.spectators.push((id, now));
+                info!("client {} is spectating {}", id, name);
+                // Spectators start from the delayed view like everyone else
+                let snapshot = ServerMessage::state(&room.board, &room.game);
+                room.delayed.push_back((now, Some(id), snapshot));
+
+                if let Some(client) = self.clients.get_mut(&id) {
+                    client.room = Some((name, Role::Spectator));
+                    client.send(&ServerMessage::Spectating);
+                }
+            }
+            ClientMessage::Reveal { row, col } => {
+                let Some((name, Role::Player(player))) =
+                    self.clients.get(&id).and_then(|c| c.room.clone())
+                else {
+                    self.error(id, "join a room as a player first");
+                    return;
+                };
+                let Some(room) = self.rooms.get_mut(&name) else {
+                    return;
+                };
+                if room.players.iter().any(Option::is_none) {
+                    self.error(id, "waiting for an opponent");
+                    return;
+                }
+
+                match room.game.play(&mut room.board, player, row, col) {
+                    Ok(()) => {
+                        debug!(
+                            "player {} revealed ({}, {}) in {}",
+                            player + 1,
+                            row,
+                            col,
+                            name
+                        );
+                        self.broadcast(&name);
+                    }
+                    Err(err) => self.error(id, &err.to_string()),
+                }
+            }
+            ClientMessage::Cursor { x, y } => {
+                let Some((name, Role::Player(player))) =
+                    self.clients.get(&id).and_then(|c| c.room.clone())
+                else {
+                    return;
+                };
+                if let Some(room) = self.rooms.get_mut(&name) {
+                    if room.spectators.is_empty() {
+                        return;
+                    }
+                    let cursor = ServerMessage::Cursor { player, x, y };
+                    room.delayed.push_back((Instant::now(), None, cursor));
+                }
+            }
+            ClientMessage::Chat { text } => {
+                let Some(client) = self.clients.get_mut(&id) else {
+                    return;
+                };
+                let Some((name, role)) = client.room.clone() else {
+                    self.error(id, "join a room first");
+                    return;
+                };
+                if !client.allow_chat(Instant::now()) {
+                    self.error(id, "you are sending messages too quickly");
+                    return;
+                }
+
+                let text: String = text.trim().chars().take(CHAT_MAX_LEN).collect();
+                if text.is_empty() {
+                    return;
+                }
+                let from = match role {
+                    Role::Player(player) => format!("Player{}", player + 1),
+                    Role::Spectator => "Spectator".to_owned(),
+                };
+                self.relay(&name, &ServerMessage::Chat { from, text });
+            }
+        }
+    }
+
+    fn leave(&mut self, id: ClientId) {
+        let Some(client) = self.clients.get_mut(&id) else {
+            // Reader threads report disconnects for clients already removed
+            return;
+        };
+        let Some((name, role)) = client.room.take() else {
+            return;
+        };
+
+        if let Some(room) = self.rooms.get_mut(&name) {
+            match role {
+                Role::Player(player) => room.players[player] = None,
+                Role::Spectator => room.spectators.retain(|&(spectator, _)| spectator != id),
+            }
+            if room.is_empty() {
+                debug!("closing empty room {}", name);
+                self.rooms.remove(&name);
+            }
+        }
+    }
+
+    fn broadcast(&mut self, name: &str) {
+        let Some(room) = self.rooms.get_mut(name) else {
+            return;
+        };
+        let message = ServerMessage::delta(&room.view, &room.board, &room.game);
+        room.view = protocol::view(&room.board);
+
+        for id in room.players.iter().flatten() {
+            if let Some(client) = self.clients.get_mut(id) {
+                client.send(&message);
+            }
+        }
+        if !room.spectators.is_empty() {
+            room.delayed.push_back((Instant::now(), None, message));
+        }
+    }
+
+    /// Sends a message to everyone in the room right away.
+    fn relay(&mut self, name: &str, message: &ServerMessage) {
+        let Some(room) = self.rooms.get(name) else {
+            return;
+        };
+        let spectators = room.spectators.iter().map(|(id, _)| id);
+        for id in room.players.iter().flatten().chain(spectators) {
+            if let Some(client) = self.clients.get_mut(id) {
+                client.send(message);
+            }
+        }
+    }
+
+    /// Sends spectators every queued message older than `SPECTATOR_DELAY`,
+    /// skipping those from before each one started watching.
+    fn flush_spectators(&mut self, now: Instant) {
+        for room in self.rooms.values_mut() {
+            while let Some((queued, _, _)) = room.delayed.front() {
+                if now.duration_since(*queued) < SPECTATOR_DELAY {
+                    break;
+                }
+                let Some((queued, to, message)) = room.delayed.pop_front() else {
+                    break;
+                };
+                for &(id, since) in &room.spectators {
+                    if queued < since || to.is_some_and(|to| to != id) {
+                        continue;
+                    }
+                    if let Some(client) = self.clients.get_mut(&id) {
+                        client.send(&message);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Closes connections that have gone quiet; their reader threads then
+    /// report the disconnect.
+    fn drop_idle(&mut self, now: Instant) {
+        for (id, client) in &mut self.clients {
+            if !client.closed && now.duration_since(client.last_seen) > IDLE_TIMEOUT {
+                info!("dropping client {} after {:?} of silence", id, IDLE_TIMEOUT);
+                client.close();
+            }
+        }
+    }
+
+    fn error(&mut self, id: ClientId, message: &str) {
+        debug!("rejected a message from client {}: {}", id, message);
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.send(&ServerMessage::Error(message.to_owned()));
+        }
+    }
+}
+
+fn read_client(id: ClientId, stream: TcpStream, events: Sender<Event>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        // One byte over the limit tells a line that is too long from one
+        // that just fits
+        match reader
+            .by_ref()
+            .take(MAX_LINE as u64 + 1)
+            .read_line(&mut line)
+        {
+            Ok(0) | Err(_) => break,
+            Ok(_) if !line.ends_with('\n') && line.len() > MAX_LINE => {
+                warn!("client {} sent a line over {} bytes", id, MAX_LINE);
+                break;
+            }
+            Ok(_) => {}
+        }
+        let text = line.trim_end_matches(['\r', '\n']).to_owned();
+        if events.send(Event::Line(id, text)).is_err() {
+            return;
+        }
+    }
+    let _ = events.send(Event::Disconnected(id));
+}
+
+fn accept_clients(listener: TcpListener, events: Sender<Event>) {
+    for (id, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("could not accept a connection: {}", err);
+                continue;
+            }
+        };
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        if let Err(err) = writer.set_write_timeout(Some(WRITE_TIMEOUT)) {
+            warn!("could not set a write timeout: {}", err);
+            continue;
+        }
+        if events.send(Event::Connected(id, writer)).is_err() {
+            return;
+        }
+
+        let events = events.clone();
+        thread::spawn(move || read_client(id, stream, events));
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    log::init_from_args(&args, Level::Info)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    let bind = match args.iter().position(|arg| arg == "--bind") {
+        Some(i) => args.get(i + 1).map(String::as_str).unwrap_or(DEFAULT_BIND),
+        None => DEFAULT_BIND,
+    };
+
+    let listener = TcpListener::bind(bind)?;
+    println!("minesweeper-server listening on {}", listener.local_addr()?);
+
+    let (events, incoming) = mpsc::channel();
+    thread::spawn(move || accept_clients(listener, events));
+
+    let mut server = Server::new();
+    loop {
+        match incoming.recv_timeout(Duration::from_millis(50)) {
+            Ok(event) => server.handle(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        server.flush_spectators(Instant::now());
+        server.drop_idle(Instant::now());
+    }
+}
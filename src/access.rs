@@ -0,0 +1,252 @@
+//! Accessible mode: a keyboard cursor, and announcements of what is under
+//! it and of every move, for players who can't see the board.
+//!
+//! Announcements are shown as a caption at the bottom of the window and
+//! printed to stdout, one per line, so a screen reader watching the
+//! terminal picks them up. With a speech command configured, each one is
+//! also passed to it as its last argument, e.g. `espeak` or `say`; a new
+//! announcement interrupts the one still being spoken.
+
+use std::cell::RefCell;
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+
+use macroquad::prelude::*;
+use minesweeper_core::{warn, Board, CellState, CellType, Event};
+
+use crate::classic::Classic;
+use crate::font;
+use crate::i18n::t;
+use crate::layout::Layout;
+use crate::scale;
+
+/// What a game reported since the last frame, boiled down to what an
+/// announcement needs so a huge opening doesn't queue a million events.
+#[derive(Default)]
+struct Heard {
+    revealed: usize,
+    last: Option<(usize, usize)>,
+    flagged: Option<bool>,
+    hit: bool,
+    /// Lives left after a mine went off without ending the game.
+    spared: Option<usize>,
+    /// Cells a big mine blew open.
+    blasted: Option<usize>,
+    dud: bool,
+    won: bool,
+}
+
+impl Heard {
+    fn record(&mut self, event: &Event) {
+        match *event {
+            Event::CellRevealed { row, col, .. } => {
+                self.revealed += 1;
+                self.last = Some((row, col));
+            }
+            Event::Flagged { flagged, .. } => self.flagged = Some(flagged),
+            Event::MineHit { .. } => self.hit = true,
+            Event::LifeLost { lives, .. } => self.spared = Some(lives),
+            Event::Blast { cells, .. } => {
+                *self.blasted.get_or_insert(0) += cells;
+            }
+            Event::DudHit { .. } => self.dud = true,
+            Event::GameWon => self.won = true,
+            Event::OpeningFlooded { .. } => {}
+        }
+    }
+}
+
+pub struct Access {
+    enabled: bool,
+    cursor: (usize, usize),
+    heard: Rc<RefCell<Heard>>,
+    caption: String,
+    speech: Option<Vec<String>>,
+    speaking: Option<Child>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            cursor: (0, 0),
+            heard: Rc::default(),
+            caption: String::new(),
+            speech: None,
+            speaking: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns the mode on or off and sets the speech command; an empty
+    /// command means captions and stdout only.
+    pub fn configure(&mut self, enabled: bool, speech: &str) {
+        self.enabled = enabled;
+        let words: Vec<String> = speech.split_whitespace().map(str::to_owned).collect();
+        self.speech = (!words.is_empty()).then_some(words);
+    }
+
+    /// Listens to a new game. Call whenever the classic game is replaced.
+    pub fn attach(&mut self, classic: &mut Classic) {
+        self.heard = Rc::default();
+        let heard = Rc::clone(&self.heard);
+        classic.subscribe(Box::new(move |event: &Event| {
+            heard.borrow_mut().record(event)
+        }));
+        let board = classic.board();
+        self.cursor = (board.height() / 2, board.width() / 2);
+    }
+
+    /// Moves the cursor and plays moves from the keyboard, then announces
+    /// whatever happened.
+    pub fn update(&mut self, classic: &mut Classic, layout: &mut Layout) {
+        if !self.enabled {
+            self.heard.take();
+            return;
+        }
+
+        let board = classic.board();
+        let (mut row, mut col) = self.cursor;
+        if is_key_pressed(KeyCode::Up) {
+            row = row.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            row = (row + 1).min(board.height() - 1);
+        }
+        if is_key_pressed(KeyCode::Left) {
+            col = col.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Right) {
+            col = (col + 1).min(board.width() - 1);
+        }
+        if (row, col) != self.cursor {
+            self.cursor = (row, col);
+            layout.scroll_to(row, col);
+            self.announce(t!(
+                "a11y-cell",
+                row = row + 1,
+                col = col + 1,
+                cell = describe(board, row, col)
+            ));
+        }
+        if is_key_pressed(KeyCode::R) {
+            let cells: Vec<String> = (0..board.width())
+                .map(|col| describe(board, row, col))
+                .collect();
+            self.announce(t!("a11y-row", row = row + 1, cells = cells.join(", ")));
+        }
+
+        if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
+            classic.click(row, col);
+        }
+        if is_key_pressed(KeyCode::F) {
+            classic.flag(row, col);
+        }
+        if is_key_pressed(KeyCode::C) {
+            classic.chord(row, col);
+        }
+
+        // Moves played with the mouse are announced too. A large opening
+        // is announced once it has finished uncovering.
+        if !classic.is_busy() {
+            if let Some(text) = summary(&self.heard.take(), classic) {
+                self.announce(text);
+            }
+        }
+    }
+
+    fn announce(&mut self, text: String) {
+        println!("{}", text);
+        if let Some(words) = &self.speech {
+            if let Some(mut child) = self.speaking.take() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            match Command::new(&words[0])
+                .args(&words[1..])
+                .arg(&text)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn()
+            {
+                Ok(child) => self.speaking = Some(child),
+                Err(err) => {
+                    warn!("could not run `{}`: {}", words.join(" "), err);
+                    self.speech = None;
+                }
+            }
+        }
+        self.caption = text;
+    }
+
+    /// Outlines the cursor and draws the last announcement along the bottom
+    /// of the window.
+    pub fn draw(&self, layout: &Layout) {
+        if !self.enabled {
+            return;
+        }
+        let (row, col) = self.cursor;
+        let tile = layout.tile(row, col);
+        draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 4.0, YELLOW);
+        let bar = Rect::new(
+            0.0,
+            screen_height() - scale::px(24.0),
+            screen_width(),
+            scale::px(24.0),
+        );
+        draw_rectangle(bar.x, bar.y, bar.w, bar.h, BLACK);
+        font::line(&self.caption, 8.0, bar.y + 4.0, 20.0, WHITE);
+    }
+}
+
+fn summary(heard: &Heard, classic: &Classic) -> Option<String> {
+    let board = classic.board();
+    let news = if heard.hit {
+        t!("a11y-mine-hit")
+    } else if let (Some(lives), Some(count)) = (heard.spared, heard.blasted) {
+        t!("a11y-blast", count = count, lives = lives)
+    } else if let Some(lives) = heard.spared {
+        t!("a11y-life-lost", lives = lives)
+    } else if heard.dud {
+        t!("a11y-dud")
+    } else if heard.won {
+        t!("board-cleared")
+    } else if heard.revealed > 1 {
+        t!("a11y-opened", count = heard.revealed)
+    } else if let Some((row, col)) = heard.last {
+        describe(board, row, col)
+    } else if let Some(flagged) = heard.flagged {
+        t!(if flagged {
+            "a11y-flag-placed"
+        } else {
+            "a11y-flag-removed"
+        })
+    } else {
+        return None;
+    };
+    if classic.is_over() {
+        return Some(news);
+    }
+    Some(format!(
+        "{}. {}",
+        news,
+        t!("mines-left", count = classic.mines_left())
+    ))
+}
+
+fn describe(board: &Board, row: usize, col: usize) -> String {
+    let cell = board.cell(row, col);
+    match (cell.cell_state, cell.cell_type) {
+        (CellState::Visible, CellType::Number(_)) if !board.shows_number(row, col) => {
+            t!("a11y-unread")
+        }
+        (CellState::Hidden, _) => t!("a11y-hidden"),
+        (CellState::Flagged, _) => t!("a11y-flagged"),
+        (CellState::Visible, CellType::Empty) => t!("a11y-empty"),
+        (CellState::Visible, CellType::Number(n)) => n.to_string(),
+        (CellState::Visible, CellType::Mine) => t!("a11y-mine"),
+    }
+}
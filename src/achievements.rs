@@ -0,0 +1,66 @@
+//! The achievements browser.
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::achievement::{self, Achievement};
+use minesweeper_core::stats::Stats;
+
+use crate::i18n::{t, tr};
+use crate::scale;
+use crate::stats;
+
+const WINDOW_SIZE: Vec2 = vec2(460.0, 400.0);
+
+pub fn name(achievement: &Achievement) -> String {
+    tr(&format!("achievement-{}", achievement.id), &[])
+}
+
+fn about(achievement: &Achievement) -> String {
+    tr(&format!("achievement-{}-about", achievement.id), &[])
+}
+
+/// Lists every achievement, unlocked or not.
+pub struct Browser {
+    stats: Stats,
+}
+
+impl Browser {
+    /// Reads the stats file, so it shows what is unlocked right now.
+    pub fn open() -> Self {
+        Self {
+            stats: stats::load().unwrap_or_default(),
+        }
+    }
+
+    /// Draws the window. Returns false once the player closes it.
+    pub fn update(&self) -> bool {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let unlocked = achievement::ALL
+            .iter()
+            .filter(|achievement| self.stats.is_unlocked(achievement.id))
+            .count();
+        let mut done = false;
+        let open = widgets::Window::new(hash!(), position, size)
+            .label(&t!(
+                "achievements-title",
+                unlocked = unlocked,
+                total = achievement::ALL.len()
+            ))
+            .movable(false)
+            .close_button(true)
+            .ui(&mut root_ui(), |ui| {
+                for achievement in achievement::ALL {
+                    let mark = if self.stats.is_unlocked(achievement.id) {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    };
+                    ui.label(None, &format!("{} {}", mark, name(achievement)));
+                    ui.label(None, &format!("     {}", about(achievement)));
+                }
+                ui.separator();
+                done = ui.button(None, t!("close").as_str());
+            });
+        open && !done && !is_key_pressed(KeyCode::Escape)
+    }
+}
@@ -0,0 +1,177 @@
+//! Light or dark. `appearance` in `config.txt`, also picked in the
+//! settings window, is `light`, `dark` or `auto`. Auto follows the
+//! system's dark mode where it can be read, and the clock otherwise: dark
+//! from `dark_from` until `dark_until` o'clock.
+//!
+//! The system setting is read by running a command (`gsettings` on Linux,
+//! `defaults` on macOS, `reg` on Windows), so a background thread checks it
+//! once a minute rather than the game asking every frame.
+
+use std::fmt;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+const CHECK_EVERY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Appearance {
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+impl Appearance {
+    pub const ALL: [Appearance; 3] = [Appearance::Auto, Appearance::Light, Appearance::Dark];
+}
+
+impl fmt::Display for Appearance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Appearance::Auto => "auto",
+            Appearance::Light => "light",
+            Appearance::Dark => "dark",
+        })
+    }
+}
+
+impl FromStr for Appearance {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "auto" => Ok(Appearance::Auto),
+            "light" => Ok(Appearance::Light),
+            "dark" => Ok(Appearance::Dark),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What the last check found.
+#[derive(Clone, Copy, Default)]
+struct Seen {
+    /// The system's dark mode, if it could be read.
+    dark: Option<bool>,
+    hour: u32,
+}
+
+impl Seen {
+    fn now() -> Self {
+        Self {
+            dark: system_dark(),
+            hour: local_hour(),
+        }
+    }
+}
+
+/// Keeps track of the system's dark mode and the time of day.
+pub struct System {
+    seen: Arc<Mutex<Seen>>,
+    /// Whether anyone asked since the last check, so the commands don't
+    /// run while the appearance is picked by hand.
+    wanted: Arc<AtomicBool>,
+}
+
+impl System {
+    /// Checks once right away, so the first frame is already right, and
+    /// then in the background.
+    pub fn watch() -> Self {
+        let system = Self {
+            seen: Arc::new(Mutex::new(Seen::now())),
+            wanted: Arc::new(AtomicBool::new(false)),
+        };
+        let seen = Arc::clone(&system.seen);
+        let wanted = Arc::clone(&system.wanted);
+        thread::spawn(move || loop {
+            thread::sleep(CHECK_EVERY);
+            if wanted.swap(false, Ordering::Relaxed) {
+                let now = Seen::now();
+                *seen.lock().unwrap_or_else(|err| err.into_inner()) = now;
+            }
+        });
+        system
+    }
+
+    /// Whether the dark theme should be in use.
+    pub fn is_dark(&self, config: &Config) -> bool {
+        match config.appearance {
+            Appearance::Light => false,
+            Appearance::Dark => true,
+            Appearance::Auto => {
+                self.wanted.store(true, Ordering::Relaxed);
+                let seen = *self.seen.lock().unwrap_or_else(|err| err.into_inner());
+                seen.dark
+                    .unwrap_or_else(|| is_night(seen.hour, config.dark_from, config.dark_until))
+            }
+        }
+    }
+}
+
+/// Whether `hour` falls in the dark hours, which may run past midnight.
+fn is_night(hour: u32, from: u32, until: u32) -> bool {
+    if from <= until {
+        (from..until).contains(&hour)
+    } else {
+        hour >= from || hour < until
+    }
+}
+
+/// The standard output of a command that succeeded.
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn system_dark() -> Option<bool> {
+    if cfg!(target_os = "macos") {
+        // The key only exists while dark mode is on
+        Some(output("defaults", &["read", "-g", "AppleInterfaceStyle"])?.contains("Dark"))
+    } else if cfg!(windows) {
+        let key = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
+        let found = output("reg", &["query", key, "/v", "AppsUseLightTheme"])?;
+        Some(found.trim_end().ends_with("0x0"))
+    } else {
+        let scheme = output(
+            "gsettings",
+            &["get", "org.gnome.desktop.interface", "color-scheme"],
+        )?;
+        Some(scheme.contains("prefer-dark"))
+    }
+}
+
+/// The hour on the local clock. The standard library has no time zones,
+/// so this asks the system, and falls back to UTC.
+fn local_hour() -> u32 {
+    let asked = if cfg!(windows) {
+        output(
+            "powershell",
+            &["-NoProfile", "-Command", "Get-Date -Format HH"],
+        )
+    } else {
+        output("date", &["+%H"])
+    };
+    asked
+        .and_then(|hour| hour.trim().parse().ok())
+        .filter(|hour| *hour < 24)
+        .unwrap_or_else(|| {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs());
+            (seconds / 3600 % 24) as u32
+        })
+}
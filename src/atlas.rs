@@ -0,0 +1,258 @@
+use macroquad::prelude::*;
+use minesweeper_core::{Cell, CellState, CellType};
+
+use crate::font;
+use crate::layout::PLAYER_COLORS;
+
+/// Side of one tile face in the atlas; tiles on screen scale it.
+const FACE: f32 = 64.0;
+/// Hidden, flagged, three mines, empty and the numbers 1 to 8.
+const FACES: usize = 14;
+
+/// Colours the atlas is painted with. Swapping skins only means building a
+/// new atlas from a different `Skin`.
+#[derive(Clone, Debug)]
+pub struct Skin {
+    pub hidden: Color,
+    pub empty: Color,
+    pub number: Color,
+    pub digit: Color,
+    pub mine: Color,
+    pub players: [Color; 2],
+    pub flag: Color,
+    pub pole: Color,
+    /// Behind the board, and the status lines drawn on it.
+    pub background: Color,
+    pub text: Color,
+    pub sprites: Sprites,
+}
+
+/// Images from a user theme drawn over some tile faces; the flag sprite
+/// replaces the drawn flag.
+#[derive(Clone, Debug, Default)]
+pub struct Sprites {
+    pub hidden: Option<Texture2D>,
+    pub flag: Option<Texture2D>,
+    pub mine: Option<Texture2D>,
+    pub empty: Option<Texture2D>,
+}
+
+impl Sprites {
+    /// Every sprite with its name in theme files.
+    pub fn all(&mut self) -> [(&'static str, &mut Option<Texture2D>); 4] {
+        [
+            ("hidden", &mut self.hidden),
+            ("flag", &mut self.flag),
+            ("mine", &mut self.mine),
+            ("empty", &mut self.empty),
+        ]
+    }
+}
+
+impl Default for Skin {
+    fn default() -> Self {
+        Self {
+            hidden: GRAY,
+            empty: GREEN,
+            number: PINK,
+            digit: BLACK,
+            mine: BLACK,
+            players: PLAYER_COLORS,
+            flag: RED,
+            pole: BLACK,
+            background: WHITE,
+            text: BLACK,
+            sprites: Sprites::default(),
+        }
+    }
+}
+
+impl Skin {
+    /// The colours of the dark appearance before `theme-dark.txt` changes
+    /// any.
+    pub fn dark() -> Self {
+        Self {
+            hidden: Color::from_rgba(0x4a, 0x4e, 0x57, 255),
+            empty: Color::from_rgba(0x2e, 0x6b, 0x3a, 255),
+            number: Color::from_rgba(0x8c, 0x4a, 0x72, 255),
+            digit: Color::from_rgba(0xf0, 0xf0, 0xf0, 255),
+            mine: Color::from_rgba(0x10, 0x10, 0x10, 255),
+            players: [
+                Color::from_rgba(0x4d, 0x9c, 0xff, 255),
+                Color::from_rgba(0xff, 0x5c, 0x6c, 255),
+            ],
+            flag: Color::from_rgba(0xff, 0x5c, 0x6c, 255),
+            pole: Color::from_rgba(0xd0, 0xd0, 0xd0, 255),
+            background: Color::from_rgba(0x1e, 0x1f, 0x24, 255),
+            text: Color::from_rgba(0xe6, 0xe6, 0xe6, 255),
+            sprites: Sprites::default(),
+        }
+    }
+
+    /// Every colour with its name in theme files.
+    pub fn colors(&mut self) -> [(&'static str, &mut Color); 11] {
+        let [player1, player2] = &mut self.players;
+        [
+            ("hidden", &mut self.hidden),
+            ("empty", &mut self.empty),
+            ("number", &mut self.number),
+            ("digit", &mut self.digit),
+            ("mine", &mut self.mine),
+            ("player1", player1),
+            ("player2", player2),
+            ("flag", &mut self.flag),
+            ("pole", &mut self.pole),
+            ("background", &mut self.background),
+            ("text", &mut self.text),
+        ]
+    }
+}
+
+/// Every tile face pre-rendered side by side into one texture, so the
+/// whole board draws as a single textured mesh.
+pub struct Atlas {
+    target: RenderTarget,
+}
+
+impl Atlas {
+    /// Paints the faces with corners rounded by `corner`, a fraction of a
+    /// tile's side.
+    pub fn new(skin: &Skin, corner: f32) -> Self {
+        let target = render_target((FACE * FACES as f32) as u32, FACE as u32);
+        target.texture.set_filter(FilterMode::Linear);
+        let mut camera =
+            Camera2D::from_display_rect(Rect::new(0.0, 0.0, FACE * FACES as f32, FACE));
+        camera.render_target = Some(target.clone());
+        push_camera_state();
+        set_camera(&camera);
+        clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+
+        let slot = |i: usize| i as f32 * FACE;
+        let radius = (corner * FACE).clamp(0.0, FACE / 2.0);
+        let face = |x: f32, color: Color| draw_face(x, radius, color);
+        let sprites = &skin.sprites;
+        face(slot(0), skin.hidden);
+        draw_sprite(slot(0), &sprites.hidden);
+
+        let x = slot(1);
+        face(x, skin.hidden);
+        draw_sprite(x, &sprites.hidden);
+        if sprites.flag.is_some() {
+            draw_sprite(x, &sprites.flag);
+        } else {
+            draw_flag(x, skin);
+        }
+
+        for (i, color) in [skin.mine, skin.players[0], skin.players[1]]
+            .into_iter()
+            .enumerate()
+        {
+            face(slot(2 + i), color);
+            draw_sprite(slot(2 + i), &sprites.mine);
+        }
+        face(slot(5), skin.empty);
+        draw_sprite(slot(5), &sprites.empty);
+
+        for n in 1..=8 {
+            let x = slot(5 + n);
+            face(x, skin.number);
+            let face = Rect::new(x, 0.0, FACE, FACE);
+            font::on_tile(&n.to_string(), face, FACE, skin.digit);
+        }
+
+        pop_camera_state();
+        Self { target }
+    }
+
+    pub fn texture(&self) -> Texture2D {
+        self.target.texture.clone()
+    }
+
+    /// The part of the texture showing `cell`.
+    pub fn uv(&self, cell: &Cell) -> Rect {
+        let slot = match (cell.cell_state, cell.cell_type) {
+            (CellState::Hidden, _) => 0,
+            (CellState::Flagged, _) => 1,
+            (CellState::Visible, CellType::Mine) => 2 + cell.owner.map_or(0, |player| player + 1),
+            (CellState::Visible, CellType::Empty) => 5,
+            (CellState::Visible, CellType::Number(n)) => 5 + n.clamp(1, 8),
+        };
+        // Half a texel in from each edge keeps linear filtering from
+        // sampling the neighbouring face
+        let inset = 0.5 / FACE;
+        let width = 1.0 / FACES as f32;
+        // Render targets come out upside down, so v runs from 1 to 0
+        Rect::new(
+            (slot as f32 + inset) * width,
+            1.0 - inset,
+            (1.0 - 2.0 * inset) * width,
+            2.0 * inset - 1.0,
+        )
+    }
+}
+
+/// The built-in flag on the face at `x`.
+fn draw_flag(x: f32, skin: &Skin) {
+    draw_line(
+        x + FACE * 0.35,
+        FACE * 0.2,
+        x + FACE * 0.35,
+        FACE * 0.8,
+        FACE / 16.0,
+        skin.pole,
+    );
+    draw_triangle(
+        vec2(x + FACE * 0.35, FACE * 0.2),
+        vec2(x + FACE * 0.75, FACE * 0.35),
+        vec2(x + FACE * 0.35, FACE * 0.5),
+        skin.flag,
+    );
+}
+
+/// A theme's sprite stretched over the face at `x`, if it has one.
+fn draw_sprite(x: f32, sprite: &Option<Texture2D>) {
+    if let Some(sprite) = sprite {
+        draw_texture_ex(
+            sprite,
+            x,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(FACE, FACE)),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Segments in each rounded corner.
+const CORNER_SEGMENTS: usize = 8;
+
+/// One face at `x`, with its corners rounded to `radius`. Drawn as a fan
+/// of triangles that don't overlap, so translucent colours stay even.
+fn draw_face(x: f32, radius: f32, color: Color) {
+    if radius <= 0.0 {
+        draw_rectangle(x, 0.0, FACE, FACE, color);
+        return;
+    }
+    let corners = [
+        (vec2(FACE - radius, FACE - radius), 0.0),
+        (vec2(radius, FACE - radius), 0.5),
+        (vec2(radius, radius), 1.0),
+        (vec2(FACE - radius, radius), 1.5),
+    ];
+    let outline: Vec<Vec2> = corners
+        .iter()
+        .flat_map(|&(centre, start)| {
+            (0..=CORNER_SEGMENTS).map(move |i| {
+                let angle =
+                    (start + 0.5 * i as f32 / CORNER_SEGMENTS as f32) * std::f32::consts::PI;
+                vec2(x, 0.0) + centre + radius * Vec2::from_angle(angle)
+            })
+        })
+        .collect();
+    let middle = vec2(x + FACE / 2.0, FACE / 2.0);
+    for (i, &point) in outline.iter().enumerate() {
+        draw_triangle(middle, point, outline[(i + 1) % outline.len()], color);
+    }
+}
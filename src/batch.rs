@@ -0,0 +1,76 @@
+use macroquad::prelude::*;
+
+/// Vertices macroquad accepts in one draw call; `window_conf` raises its
+/// limit to this. Mesh indices are `u16`, so it can't go past 65536.
+pub const MAX_VERTICES: usize = 1 << 15;
+/// Indices per draw call, enough for `MAX_VERTICES` worth of quads.
+pub const MAX_INDICES: usize = MAX_VERTICES / 4 * 6;
+
+/// Collects shapes into large meshes so thousands of tiles cost a handful of
+/// draw calls instead of one each. Everything in a batch shares one texture.
+pub struct Batch {
+    mesh: Mesh,
+    calls: usize,
+}
+
+impl Batch {
+    pub fn new(texture: Option<Texture2D>) -> Self {
+        Self {
+            mesh: Mesh {
+                vertices: Vec::new(),
+                indices: Vec::new(),
+                texture,
+            },
+            calls: 0,
+        }
+    }
+
+    /// Draws what has been collected if `vertices` and `indices` more would
+    /// overflow a draw call, and returns the index of the next vertex.
+    fn reserve(&mut self, vertices: usize, indices: usize) -> u16 {
+        if self.mesh.vertices.len() + vertices >= MAX_VERTICES
+            || self.mesh.indices.len() + indices >= MAX_INDICES
+        {
+            self.flush();
+        }
+        self.mesh.vertices.len() as u16
+    }
+
+    fn vertex(&mut self, position: Vec2, uv: Vec2, color: Color) {
+        self.mesh
+            .vertices
+            .push(Vertex::new(position.x, position.y, 0.0, uv.x, uv.y, color));
+    }
+
+    /// Adds `rect` showing the `uv` part of the texture, tinted by `color`.
+    pub fn quad(&mut self, rect: Rect, uv: Rect, color: Color) {
+        let first = self.reserve(4, 6);
+        self.vertex(rect.point(), uv.point(), color);
+        self.vertex(vec2(rect.right(), rect.y), vec2(uv.right(), uv.y), color);
+        self.vertex(
+            vec2(rect.right(), rect.bottom()),
+            vec2(uv.right(), uv.bottom()),
+            color,
+        );
+        self.vertex(vec2(rect.x, rect.bottom()), vec2(uv.x, uv.bottom()), color);
+        self.mesh
+            .indices
+            .extend([0, 1, 2, 0, 2, 3].into_iter().map(|offset| first + offset));
+    }
+
+    fn flush(&mut self) {
+        if self.mesh.vertices.is_empty() {
+            return;
+        }
+        draw_mesh(&self.mesh);
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+        self.calls += 1;
+    }
+
+    /// Draws whatever is left and returns how many draw calls the batch took.
+    pub fn finish(mut self) -> usize {
+        self.flush();
+        self.calls
+    }
+}
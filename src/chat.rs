@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::font;
+use crate::scale;
+
+const HISTORY: usize = 50;
+const VISIBLE_LINES: usize = 6;
+const MAX_INPUT: usize = 200;
+
+/// Chat panel drawn over the bottom-left of the board. Enter opens the input
+/// field and sends, Escape closes it. While closed it ignores every other key
+/// so game keybindings keep working.
+pub struct Chat {
+    open: bool,
+    input: String,
+    history: VecDeque<(String, String)>,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, from: String, text: String) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((from, text));
+    }
+
+    /// Handles keyboard input and returns a message once the player sends one.
+    pub fn update(&mut self) -> Option<String> {
+        if !self.open {
+            // Drain typed characters so they don't show up when chat opens
+            while get_char_pressed().is_some() {}
+            if is_key_pressed(KeyCode::Enter) {
+                self.open = true;
+            }
+            return None;
+        }
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && self.input.chars().count() < MAX_INPUT {
+                self.input.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+            self.input.clear();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            self.open = false;
+            let text = std::mem::take(&mut self.input);
+            if !text.trim().is_empty() {
+                return Some(text);
+            }
+        }
+        None
+    }
+
+    pub fn draw(&self, bottom: f32) {
+        let line_height = scale::px(22.0);
+        let lines = self.history.len().min(VISIBLE_LINES) + usize::from(self.open);
+        if lines == 0 {
+            return;
+        }
+
+        let top = bottom - line_height * lines as f32 - 8.0;
+        draw_rectangle(
+            4.0,
+            top,
+            scale::px(500.0),
+            bottom - top,
+            Color::new(0.0, 0.0, 0.0, 0.5),
+        );
+
+        let mut y = top + 4.0;
+        for (from, text) in self
+            .history
+            .iter()
+            .skip(self.history.len().saturating_sub(VISIBLE_LINES))
+        {
+            font::line(&format!("{}: {}", from, text), 10.0, y, 20.0, WHITE);
+            y += line_height;
+        }
+        if self.open {
+            font::line(&format!("> {}_", self.input), 10.0, y, 20.0, YELLOW);
+        }
+    }
+}
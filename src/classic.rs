@@ -0,0 +1,1225 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use minesweeper_core::agent::View;
+use minesweeper_core::console::{self, Command, Registry};
+use minesweeper_core::drift::Drift;
+use minesweeper_core::export::Screenshot;
+use minesweeper_core::hint::{HintError, Hints};
+use minesweeper_core::metrics::{self, Benchmarks, Openings, Waste};
+use minesweeper_core::plugin::{Builtin, Message, PluginRegistry};
+use minesweeper_core::rating::Rating;
+use minesweeper_core::replay::Replay;
+use minesweeper_core::review::Review;
+use minesweeper_core::scenario::{Happened, Run};
+use minesweeper_core::script::ScriptMod;
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::sim::Move;
+use minesweeper_core::solver::{Knowledge, Solver};
+use minesweeper_core::stats::{Configuration, Progress, Record, Streak, Variant};
+use minesweeper_core::tutorial::Tutorial;
+use minesweeper_core::{debug, Board, CellState, CellType, Game, Listener, MoveError, Status};
+
+use crate::console::{Context, Handler};
+use crate::font;
+use crate::i18n::{self, t};
+use crate::layout::{Layout, STATUS_MARGIN};
+use crate::menu;
+use crate::scale;
+use crate::trail::Trail;
+
+/// Frame time spent uncovering a large opening; the rest carries over to
+/// the next frame so the board keeps drawing.
+const FLOOD_BUDGET: Duration = Duration::from_millis(4);
+/// Space under the board for the status line and the tutorial's text.
+const TUTORIAL_HEIGHT: f32 = 200.0;
+const TUTORIAL_FONT: f32 = 24.0;
+const RESULTS_WIDTH: f32 = 180.0;
+/// Height of the results panel's copy seed button.
+const RESULTS_BUTTON: f32 = 30.0;
+/// Largest board the ZiNi benchmarks and the difficulty rating are worked
+/// out for when a game starts; their time grows faster than the board.
+const BENCHMARK_CELLS: usize = 4096;
+/// Wasted moves the trainer lists after a game before summing up the rest.
+const WASTE_LINES: usize = 8;
+/// Strips the ramp board's backdrop is drawn in.
+const RAMP_BANDS: usize = 24;
+/// Time added to the clock for every dud set off.
+const DUD_PENALTY: Duration = Duration::from_secs(10);
+
+/// Mods loaded from scripts at startup, installed into every game.
+static MODS: OnceLock<Vec<ScriptMod>> = OnceLock::new();
+
+/// Sets the script mods every game from now on plays with. Only the first
+/// call counts.
+pub fn install_mods(mods: Vec<ScriptMod>) {
+    let _ = MODS.set(mods);
+}
+
+/// Every plugin compiled into the game, then the script mods. Third-party
+/// plugins are added here.
+pub fn plugins() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry.install(&Builtin);
+    for script in MODS.get().into_iter().flatten() {
+        registry.install(script);
+    }
+    registry
+}
+
+/// Single-player game driven through the plugin registry.
+pub struct Classic {
+    game: Game,
+    /// Found once, when the game starts.
+    openings: Openings,
+    benchmarks: Option<Benchmarks>,
+    rating: Option<Rating>,
+    plugins: PluginRegistry,
+    analysis: Option<Vec<Message>>,
+    /// Click whose opening is still being uncovered.
+    flooding: Option<(usize, usize)>,
+    /// Uncover openings in one frame instead of spreading them out.
+    instant: bool,
+    /// The lesson being followed, which checks every move first.
+    tutorial: Option<Tutorial>,
+    hints: Hints,
+    /// Whether the console played a move, which counts as a hint so the
+    /// game stays out of personal bests.
+    assisted: bool,
+    /// Cell the last hint pointed at, or why there was none.
+    hint: Option<Result<(usize, usize), HintError>>,
+    /// When the first move was made, moved on by the time spent paused.
+    started: Option<Instant>,
+    /// When the game was paused, while it is.
+    paused: Option<Instant>,
+    /// Time from the first move to the last, once the game is over.
+    finished: Option<Duration>,
+    /// The finished game.
+    record: Option<Record>,
+    /// Whether `record` was taken to be saved.
+    recorded: bool,
+    /// Every move played and when, for the heatmap, the review and the
+    /// GIF export.
+    replay: Replay,
+    wasted: u32,
+    flags: u32,
+    opening_clicks: u32,
+    number_clicks: u32,
+    /// Wins in a row on this difficulty, counting this game once it is
+    /// over.
+    streak: Streak,
+    variant: Variant,
+    /// What the board was generated from, if it was from a seed.
+    seed: Option<u64>,
+    /// Whether the seed was asked for since the last `take_copy_seed`.
+    copy_seed: bool,
+    /// Flag every mine single numbers prove after each move.
+    auto_flag: bool,
+    /// Flags placed by `auto_flag`, which are in the replay but weren't
+    /// clicks.
+    auto_flags: u32,
+    /// Kept between moves, so `auto_flag` costs the same on every one.
+    solver: Solver,
+    /// Chord every number whose flags are all placed, after each move.
+    auto_chord: bool,
+    /// Chords played by `auto_chord`, likewise.
+    auto_chords: u32,
+    /// The IOE of the player's trainer wins, oldest first, this one
+    /// included once it is won.
+    trainer_ioes: Vec<f64>,
+    /// The finished trainer game's wasted moves, and the trend with it.
+    waste: Vec<(usize, Waste)>,
+    progress: Option<Progress>,
+    /// Hidden cells the player marked with a question mark. They are only
+    /// a note to the player: the game treats them as any hidden cell.
+    questions: HashSet<(usize, usize)>,
+    /// What the last move did, marked for `trail_time`.
+    trail: Option<Trail>,
+    trail_time: Duration,
+    /// The campaign level being played, whose events change the board.
+    scenario: Option<Run>,
+    /// What the level's last event did, for the status line.
+    happened: Option<Happened>,
+    /// Whether the board changed under the replay, through mines moved or
+    /// planted or cells unlocked. The replay has no record of that, so it would
+    /// play out differently, and the game can't be reviewed or exported.
+    reshaped: bool,
+    /// When the mines move, in a moving-mines game once it has started.
+    drift: Option<Drift>,
+}
+
+impl Classic {
+    pub fn new(mut board: Board) -> Self {
+        let plugins = plugins();
+        plugins.on_generate(&mut board);
+        Self::with_plugins(board, plugins)
+    }
+
+    /// Continues a saved game without running the generation hooks again.
+    pub fn resume(board: Board) -> Self {
+        Self::with_plugins(board, plugins())
+    }
+
+    /// The guided lesson on its fixed board.
+    pub fn tutorial() -> Self {
+        Self {
+            tutorial: Some(Tutorial::new()),
+            ..Self::with_plugins(Tutorial::board(), plugins())
+        }
+    }
+
+    fn with_plugins(board: Board, plugins: PluginRegistry) -> Self {
+        Self {
+            openings: Openings::of(&board),
+            benchmarks: (board.width() * board.height() <= BENCHMARK_CELLS)
+                .then(|| Benchmarks::of(&board)),
+            rating: (board.width() * board.height() <= BENCHMARK_CELLS).then(|| Rating::of(&board)),
+            replay: Replay::new(board.clone()),
+            game: Game::new(board),
+            plugins,
+            analysis: None,
+            flooding: None,
+            instant: false,
+            tutorial: None,
+            hints: Hints::none(),
+            assisted: false,
+            hint: None,
+            started: None,
+            paused: None,
+            finished: None,
+            record: None,
+            recorded: false,
+            wasted: 0,
+            flags: 0,
+            opening_clicks: 0,
+            number_clicks: 0,
+            streak: Streak::default(),
+            variant: Variant::CLASSIC,
+            seed: None,
+            copy_seed: false,
+            auto_flag: false,
+            auto_flags: 0,
+            solver: Solver::new(),
+            auto_chord: false,
+            auto_chords: 0,
+            trainer_ioes: Vec::new(),
+            waste: Vec::new(),
+            progress: None,
+            questions: HashSet::new(),
+            trail: None,
+            trail_time: Duration::ZERO,
+            scenario: None,
+            happened: None,
+            reshaped: false,
+            drift: None,
+        }
+    }
+
+    pub fn with_hints(mut self, hints: Hints) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    pub fn with_auto_flag(mut self, auto_flag: bool) -> Self {
+        self.auto_flag = auto_flag;
+        self
+    }
+
+    /// Games with it on are recorded under `Variant::AUTO_CHORD`.
+    pub fn with_auto_chord(mut self, auto_chord: bool) -> Self {
+        self.auto_chord = auto_chord;
+        self
+    }
+
+    /// Uncovers large openings at once rather than over several frames,
+    /// for players who find the spreading hard to watch.
+    pub fn with_instant_openings(mut self, instant: bool) -> Self {
+        self.instant = instant;
+        self
+    }
+
+    /// Marks each move's cells for `time`; zero turns the marks off.
+    pub fn with_trail(mut self, time: Duration) -> Self {
+        self.trail_time = time;
+        self
+    }
+
+    /// The streak this game continues or breaks.
+    pub fn with_streak(mut self, streak: Streak) -> Self {
+        self.streak = streak;
+        self
+    }
+
+    /// The rules the board was made under, for the records.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The player's earlier trainer wins, for the trend shown after one.
+    /// Plays a campaign level, whose board this must be.
+    pub fn with_scenario(mut self, run: Run) -> Self {
+        self.scenario = Some(run);
+        self
+    }
+
+    pub fn with_trainer_ioes(mut self, ioes: Vec<f64>) -> Self {
+        self.trainer_ioes = ioes;
+        self
+    }
+
+    pub fn is_tutorial(&self) -> bool {
+        self.tutorial.is_some()
+    }
+
+    /// Whether this is an efficiency trainer game, scored on its clicks.
+    pub fn is_trainer(&self) -> bool {
+        self.variant.contains(Variant::TRAINER)
+    }
+
+    /// Space the status line needs under the board.
+    pub fn status_height(&self) -> Option<f32> {
+        self.tutorial.as_ref().map(|_| scale::px(TUTORIAL_HEIGHT))
+    }
+
+    pub fn board(&self) -> &Board {
+        self.game.board()
+    }
+
+    pub fn openings(&self) -> &Openings {
+        &self.openings
+    }
+
+    /// 3BV and ZiNi of the board, unless it is too large to work out.
+    pub fn benchmarks(&self) -> Option<Benchmarks> {
+        self.benchmarks
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// Whether leaving now would throw away a game that counts: started,
+    /// not over, and not the lesson.
+    pub fn is_in_progress(&self) -> bool {
+        self.started.is_some() && !self.is_over() && !self.is_tutorial()
+    }
+
+    pub fn mines_left(&self) -> isize {
+        self.game.mines_left()
+    }
+
+    pub fn subscribe(&mut self, listener: Box<dyn Listener>) {
+        self.game.subscribe(listener);
+    }
+
+    /// Whether an opening is still being uncovered over several frames.
+    pub fn is_busy(&self) -> bool {
+        self.flooding.is_some()
+    }
+
+    /// Call once per frame to keep uncovering the current opening, and
+    /// for the results panel's copy seed button.
+    pub fn update(&mut self) {
+        self.run_timers();
+        if let Some((row, col)) = self.flooding {
+            if self.game.advance(FLOOD_BUDGET) {
+                self.flooding = None;
+                self.after_move(row, col);
+            }
+        }
+        if let Some(lines) = self.results().filter(|_| self.seed.is_some()) {
+            let panel = results_panel(&lines, true);
+            let at = vec2(
+                panel.x + 8.0,
+                panel.bottom() - scale::px(RESULTS_BUTTON) + 2.0,
+            );
+            if root_ui().button(at, t!("menu-copy-seed").as_str()) {
+                self.copy_seed = true;
+            }
+        }
+    }
+
+    /// Whether the player asked to copy the seed from the results panel
+    /// since the last call.
+    pub fn take_copy_seed(&mut self) -> bool {
+        std::mem::take(&mut self.copy_seed)
+    }
+
+    /// The code that deals this game again, if it was dealt from a seed.
+    pub fn share_code(&self) -> Option<ShareCode> {
+        let board = self.replay.start();
+        Some(ShareCode {
+            width: board.width(),
+            height: board.height(),
+            mines: board.mines(),
+            variant: self.variant,
+            seed: self.seed?,
+            rules: board.rules(),
+        })
+    }
+
+    /// Uncovers the rest of the current opening before another move.
+    fn finish(&mut self) {
+        if let Some((row, col)) = self.flooding.take() {
+            self.game.finish();
+            self.after_move(row, col);
+        }
+    }
+
+    pub fn click(&mut self, row: usize, col: usize) {
+        self.finish();
+        debug!("click at ({}, {})", row, col);
+        if !self.allowed(Move::Reveal(row, col)) {
+            return;
+        }
+        let before = self.begin_move(Move::Reveal(row, col));
+        if !self.plugins.allow_reveal(self.game.board(), row, col) {
+            debug!("a rule modifier vetoed the click");
+        } else {
+            let cell = self.game.board().cell(row, col);
+            if cell.cell_state == CellState::Hidden {
+                match cell.cell_type {
+                    CellType::Empty => self.opening_clicks += 1,
+                    CellType::Number(_) => self.number_clicks += 1,
+                    CellType::Mine => {}
+                }
+            }
+            match self.game.reveal_gradually(row, col) {
+                Ok(()) if self.game.is_flooding() && !self.instant => {
+                    self.flooding = Some((row, col))
+                }
+                Ok(()) if self.game.is_flooding() => {
+                    self.game.finish();
+                    self.after_move(row, col);
+                }
+                Ok(()) => self.after_move(row, col),
+                // Clicking a revealed number chords it
+                Err(MoveError::AlreadyRevealed) => self.play_chord(row, col),
+                Err(_) => {}
+            }
+        }
+        self.end_move(before);
+    }
+
+    pub fn flag(&mut self, row: usize, col: usize) {
+        self.finish();
+        debug!("flag at ({}, {})", row, col);
+        if self.allowed(Move::Flag(row, col)) {
+            let before = self.begin_move(Move::Flag(row, col));
+            let _ = self.game.toggle_flag(row, col);
+            if self.game.board().cell(row, col).cell_state == CellState::Flagged {
+                self.flags += 1;
+            }
+            // A flag can complete a number for the auto-chord
+            self.assist();
+            self.conclude();
+            self.end_move(before);
+        }
+    }
+
+    pub fn chord(&mut self, row: usize, col: usize) {
+        self.finish();
+        if self.allowed(Move::Chord(row, col)) {
+            let before = self.begin_move(Move::Chord(row, col));
+            self.play_chord(row, col);
+            self.end_move(before);
+        }
+    }
+
+    fn play_chord(&mut self, row: usize, col: usize) {
+        debug!("chord at ({}, {})", row, col);
+        if self.game.chord(row, col).is_ok() {
+            self.after_move(row, col);
+        }
+    }
+
+    /// Asks the tutorial, if there is one, whether to play a move.
+    /// Counts the game as helped, for moves made from the console.
+    pub fn mark_assisted(&mut self) {
+        self.assisted = true;
+    }
+
+    /// Moves a hidden mine to a hidden cell without one, for the debug
+    /// tools. The replay keeps the board the game started on, so the game
+    /// can't be replayed any more.
+    #[cfg(feature = "debug-tools")]
+    pub fn move_mine(&mut self, from: (usize, usize), to: (usize, usize)) -> bool {
+        self.finish();
+        self.assisted = true;
+        use minesweeper_core::Pos;
+        let (from, to) = (Pos::new(from.0, from.1), Pos::new(to.0, to.1));
+        let moved = self.game.board_mut().move_mine(from, to);
+        self.reshaped |= moved;
+        moved
+    }
+
+    fn allowed(&mut self, step: Move) -> bool {
+        match self.tutorial.as_mut() {
+            Some(tutorial) => tutorial.attempt(self.game.board(), step),
+            None => true,
+        }
+    }
+
+    /// Starts the clock on the first move and counts the click. Returns
+    /// the board's revision, for `end_move`.
+    fn begin_move(&mut self, step: Move) -> u64 {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        if self.variant.contains(Variant::MOVING) && self.drift.is_none() {
+            let seed = self.seed.unwrap_or_else(menu::seed);
+            self.drift = Some(Drift::new(Drift::EVERY, seed));
+        }
+        self.hint = None;
+        // Counted up front so the click that ends the game is in its record
+        self.replay.push(started.elapsed(), step);
+        if !self.trail_time.is_zero() {
+            self.trail = Some(Trail::new(self.game.board(), step.position()));
+        }
+        self.game.board().revision()
+    }
+
+    /// Counts a click that left the board as it was as wasted. Such a click
+    /// can't have ended the game, so the record is still right.
+    fn end_move(&mut self, before: u64) {
+        if self.game.board().revision() == before {
+            self.wasted += 1;
+        }
+        // Revealed and flagged cells lose their question marks
+        let board = self.game.board();
+        self.questions
+            .retain(|&(row, col)| board.cell(row, col).cell_state == CellState::Hidden);
+    }
+
+    /// Moves a cell's mark one step along none, flag, question mark, or
+    /// one step back. Revealed cells have no mark.
+    pub fn cycle_mark(&mut self, row: usize, col: usize, forward: bool) {
+        if self.is_over() {
+            return;
+        }
+        let questioned = self.questions.contains(&(row, col));
+        match (self.game.board().cell(row, col).cell_state, forward) {
+            (CellState::Visible, _) => {}
+            (CellState::Hidden, true) if !questioned => self.flag(row, col),
+            (CellState::Hidden, false) if questioned => {
+                self.questions.remove(&(row, col));
+                self.flag(row, col);
+            }
+            (CellState::Hidden, _) => {
+                if !self.questions.remove(&(row, col)) {
+                    self.questions.insert((row, col));
+                }
+            }
+            (CellState::Flagged, true) => {
+                self.flag(row, col);
+                if self.game.board().cell(row, col).cell_state == CellState::Hidden {
+                    self.questions.insert((row, col));
+                }
+            }
+            (CellState::Flagged, false) => self.flag(row, col),
+        }
+    }
+
+    /// Time since the first move, leaving out pauses, if it was made.
+    fn played(&self) -> Option<Duration> {
+        self.finished.or_else(|| {
+            let started = self.started?;
+            Some(
+                self.paused
+                    .map_or(started.elapsed(), |paused| paused - started),
+            )
+        })
+    }
+
+    /// Time played so far, with the hint penalties and without a level's
+    /// bonus time.
+    pub fn elapsed(&self) -> Duration {
+        let played = self.played().unwrap_or_default();
+        let bonus = self
+            .scenario
+            .as_ref()
+            .map_or(Duration::ZERO, |run| run.credit(played));
+        played - bonus + self.hints.penalty() + DUD_PENALTY * self.game.duds_hit() as u32
+    }
+
+    /// Stops the clock and hides the board, if a game is in progress.
+    pub fn pause(&mut self) {
+        if self.is_in_progress() && self.paused.is_none() {
+            self.paused = Some(Instant::now());
+        }
+    }
+
+    /// Starts the clock again where it stopped.
+    pub fn unpause(&mut self) {
+        if let (Some(paused), Some(started)) = (self.paused.take(), self.started.as_mut()) {
+            *started += paused.elapsed();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Points out a cell that is provably safe, if a hint is left.
+    pub fn hint(&mut self) {
+        self.finish();
+        if self.game.is_over() {
+            return;
+        }
+        self.hint = Some(self.hints.request(self.game.board()));
+        debug!("hint: {:?}", self.hint);
+    }
+
+    /// The game just finished and the cells clicked in it, once, for the
+    /// stats.
+    pub fn take_record(&mut self) -> Option<(Record, Vec<(usize, usize)>)> {
+        let record = self.record.filter(|_| !self.recorded)?;
+        self.recorded = true;
+        Some((
+            record,
+            self.replay
+                .steps()
+                .iter()
+                .map(|step| step.position())
+                .collect(),
+        ))
+    }
+
+    /// The finished game move by move, with the solver's notes.
+    pub fn review(&self) -> Option<Review> {
+        self.replay()
+            .map(|replay| Review::new(replay.start().clone(), &replay.steps()))
+    }
+
+    /// This game so far, to be stored with a screenshot of it.
+    pub fn screenshot(&self) -> Screenshot {
+        let board = self.replay.start();
+        Screenshot {
+            seed: self.seed,
+            configuration: Configuration {
+                variant: self.variant,
+                assisted: self.hints.used() > 0,
+                ..Configuration::classic(board.width(), board.height(), board.mines())
+            },
+            elapsed: self.elapsed(),
+            start: board.clone(),
+        }
+    }
+
+    /// The finished game, for exporting, unless the board changed in ways
+    /// the replay doesn't record.
+    pub fn replay(&self) -> Option<&Replay> {
+        (self.game.is_over() && !self.reshaped).then_some(&self.replay)
+    }
+
+    /// Whether the game is over but can't be replayed, see `reshaped`.
+    pub fn is_unreplayable(&self) -> bool {
+        self.game.is_over() && self.reshaped
+    }
+
+    /// Fires the level's events and moves the mines that are due. Nothing
+    /// happens before the first move, while paused, or once the game is
+    /// over.
+    fn run_timers(&mut self) {
+        if self.is_paused() || self.is_over() || self.is_busy() {
+            return;
+        }
+        let Some(played) = self.played() else {
+            return;
+        };
+        if let Some(run) = self.scenario.as_mut() {
+            let happened = run.update(self.game.board_mut(), played);
+            self.reshaped |= happened.iter().any(Happened::reshapes);
+            if let Some(&last) = happened.last() {
+                self.happened = Some(last);
+            }
+        }
+        if let Some(drift) = self.drift.as_mut() {
+            self.reshaped |= !drift.update(self.game.board_mut(), played).is_empty();
+        }
+    }
+
+    /// Time until a mine moves, once it is close enough to warn of.
+    fn shift_warning(&self) -> Option<Duration> {
+        let drift = self.drift.as_ref().filter(|_| !self.is_over())?;
+        let left = drift.until_next(self.played()?);
+        (left <= Drift::WARNING).then_some(left)
+    }
+
+    fn after_move(&mut self, row: usize, col: usize) {
+        self.run_timers();
+        self.plugins.after_reveal(self.game.board_mut(), row, col);
+        self.assist();
+        self.conclude();
+    }
+
+    /// Records the game once it is over.
+    fn conclude(&mut self) {
+        if self.game.is_over() && self.analysis.is_none() {
+            self.replay.finish(&self.game);
+            let won = self.game.status() == Status::Won;
+            self.analysis = Some(self.plugins.analyze(self.game.board(), won));
+            self.finished = Some(
+                self.started
+                    .map_or(Duration::ZERO, |started| started.elapsed()),
+            );
+            if self.tutorial.is_none() {
+                self.streak = self.streak.after(won);
+                let board = self.game.board();
+                self.record = Some(Record {
+                    width: board.width(),
+                    height: board.height(),
+                    mines: board.mines(),
+                    won,
+                    time: self.elapsed(),
+                    hints: self.hints.used() + u32::from(self.assisted),
+                    clicks: self.replay.len() as u32 - self.auto_flags - self.auto_chords,
+                    wasted: self.wasted,
+                    flags: self.flags,
+                    finished: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |time| time.as_secs()),
+                    // The layout the game started with, as the replay has
+                    // it, whatever the level did to it since
+                    bbbv: metrics::bbbv(self.replay.start()) as u32,
+                    opening_clicks: self.opening_clicks,
+                    number_clicks: self.number_clicks,
+                    variant: if self.auto_chord && !self.is_trainer() {
+                        self.variant.with(Variant::AUTO_CHORD)
+                    } else {
+                        self.variant
+                    },
+                    seed: self.seed,
+                });
+                if self.is_trainer() {
+                    self.score_training();
+                }
+            }
+        }
+    }
+
+    /// Finds the finished trainer game's wasted moves and adds a win to
+    /// the trend.
+    fn score_training(&mut self) {
+        self.waste = metrics::waste(self.replay.start(), &self.replay.steps());
+        if let Some(efficiency) = self.record.and_then(|record| record.efficiency()) {
+            self.trainer_ioes.push(efficiency.ioe);
+        }
+        self.progress = Progress::of(&self.trainer_ioes);
+    }
+
+    /// Plays the assists that are on until neither has anything left to
+    /// do, as each can give the other more. The tutorial has none, and
+    /// neither has the trainer, which counts every click.
+    fn assist(&mut self) {
+        if self.tutorial.is_some() || self.is_trainer() {
+            return;
+        }
+        while !self.game.is_over() {
+            let flagged = self.auto_flag && self.solvable() && self.flag_proven();
+            let chorded = self.auto_chord && self.chord_satisfied();
+            if !flagged && !chorded {
+                break;
+            }
+        }
+    }
+
+    /// Whether the solver's assists can be trusted on this board. It
+    /// doesn't model lying numbers, stacked mines and the other custom
+    /// rules, so under them it would prove cells wrong.
+    fn solvable(&self) -> bool {
+        self.game.board().rules().is_classic()
+    }
+
+    /// Flags the hidden cells single numbers prove are mines. The flags go
+    /// into the replay like the player's own, so chords replay the same.
+    /// Returns whether any were placed.
+    fn flag_proven(&mut self) -> bool {
+        let board = self.game.board();
+        let mut mines: Vec<_> = self
+            .solver
+            .single_point(&View::of(board))
+            .mines
+            .iter()
+            .copied()
+            .filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Hidden)
+            .collect();
+        mines.sort_unstable();
+        let started = self.started.unwrap_or_else(Instant::now);
+        let placed = self.auto_flags;
+        for (row, col) in mines {
+            if self.game.toggle_flag(row, col).is_ok() {
+                self.replay.push(started.elapsed(), Move::Flag(row, col));
+                self.auto_flags += 1;
+            }
+        }
+        self.auto_flags > placed
+    }
+
+    /// Chords every revealed number with as many flags around it as its
+    /// value and hidden cells left, wrong flags and all, as the player
+    /// would. Returns whether any were played.
+    fn chord_satisfied(&mut self) -> bool {
+        let board = self.game.board();
+        let mut numbers = Vec::new();
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                let cell = board.cell(row, col);
+                let (CellType::Number(n), CellState::Visible) = (cell.cell_type, cell.cell_state)
+                else {
+                    continue;
+                };
+                let around = board.adjacent(row, col);
+                let state = |state| {
+                    around
+                        .iter()
+                        .filter(|&&(r, c)| board.cell(r, c).cell_state == state)
+                        .count()
+                };
+                if state(CellState::Flagged) == n && state(CellState::Hidden) > 0 {
+                    numbers.push((row, col));
+                }
+            }
+        }
+        let started = self.started.unwrap_or_else(Instant::now);
+        let played = self.auto_chords;
+        for (row, col) in numbers {
+            if self.game.is_over() {
+                break;
+            }
+            if self.game.chord(row, col).is_ok() {
+                self.replay.push(started.elapsed(), Move::Chord(row, col));
+                self.auto_chords += 1;
+            }
+        }
+        self.auto_chords > played
+    }
+
+    /// Tints the window behind a ramp board, clear at the top and faintly
+    /// red at the bottom, so the way the mines thicken shows between the
+    /// tiles.
+    pub fn draw_backdrop(&self, layout: &Layout) {
+        if !self.variant.contains(Variant::RAMP) {
+            return;
+        }
+        let band = layout.height(self.game.board()) / RAMP_BANDS as f32;
+        for i in 0..RAMP_BANDS {
+            let alpha = 0.2 * (i as f32 + 0.5) / RAMP_BANDS as f32;
+            let color = Color::new(RED.r, RED.g, RED.b, alpha);
+            draw_rectangle(0.0, i as f32 * band, screen_width(), band + 1.0, color);
+        }
+    }
+
+    /// Draws the status line under the board, and the tutorial's text and
+    /// highlights if there is one.
+    pub fn draw(&self, layout: &Layout) {
+        let top = layout.height(self.game.board());
+        let time = t!("time", seconds = self.elapsed().as_secs());
+        let lines = match &self.analysis {
+            Some(analysis) => {
+                let mut texts: Vec<String> = analysis.iter().map(i18n::message).collect();
+                texts.push(time);
+                // How close the clicks came to the greedy benchmark
+                if let Some(benchmarks) = self.benchmarks {
+                    if self.game.status() == Status::Won {
+                        texts.push(t!(
+                            "efficiency",
+                            clicks = self.replay.len(),
+                            zini = benchmarks.zini
+                        ));
+                    }
+                }
+                if self.tutorial.is_none() {
+                    texts.push(t!("review-open"));
+                    texts.push(t!("export-open"));
+                }
+                if self.streak.best > 0 {
+                    texts.push(t!(
+                        "streak",
+                        current = self.streak.current,
+                        best = self.streak.best
+                    ));
+                }
+                texts.join("  ")
+            }
+            None => {
+                let mut texts = vec![t!("mines-left", count = self.game.mines_left()), time];
+                let board = self.game.board();
+                if board.rules().lives > 1 {
+                    texts.push(t!("lives-left", count = board.lives_left()));
+                }
+                let bonus = self
+                    .scenario
+                    .as_ref()
+                    .and_then(|run| run.bonus_left(self.played().unwrap_or_default()));
+                if let Some(left) = bonus {
+                    texts.push(t!("scenario-bonus", seconds = left.as_secs() + 1));
+                }
+                if let Some(left) = self.shift_warning() {
+                    texts.push(t!("mines-shifting", seconds = left.as_secs() + 1));
+                }
+                match self.happened {
+                    Some(Happened::Mines(count)) if count > 0 => {
+                        texts.push(t!("scenario-mines", count = count))
+                    }
+                    Some(Happened::Unlocked(_)) => texts.push(t!("scenario-unlocked")),
+                    _ => {}
+                }
+                match self.hint {
+                    Some(Err(HintError::Exhausted)) => texts.push(t!("hint-exhausted")),
+                    Some(Err(HintError::NoSafeCell)) => texts.push(t!("hint-none")),
+                    Some(Err(HintError::Unsupported)) => texts.push(t!("hint-unsupported")),
+                    _ if self.hints.remaining() > 0 && self.solvable() => {
+                        texts.push(t!("hints-left", count = self.hints.remaining()))
+                    }
+                    _ => {}
+                }
+                texts.extend(
+                    self.plugins
+                        .widgets()
+                        .filter_map(|widget| widget.text(self.game.board()))
+                        .map(|message| i18n::message(&message)),
+                );
+                texts.join("  ")
+            }
+        };
+        let text = layout.skin().text;
+        let status = font::line(&lines, 10.0, top + STATUS_MARGIN, 30.0, text);
+        if let Some(rating) = self.rating.filter(|_| self.tutorial.is_none()) {
+            let label = t!("rating");
+            let label = font::line(&label, status.right() + 20.0, status.y, 30.0, text);
+            draw_stars(label.right() + 8.0, label.center().y, rating.stars());
+        }
+        // A loss now would end the streak
+        if self.analysis.is_none() && self.streak.current > 0 {
+            let text = t!("streak-at-risk", count = self.streak.current);
+            let x = screen_width() - font::measure(&text, 30.0).width - 10.0;
+            font::line(&text, x, status.y, 30.0, ORANGE);
+        }
+        if let Some(trail) = &self.trail {
+            trail.draw(self.game.board(), layout, self.trail_time);
+        }
+        // Pulses around the board while a mine is about to move
+        if self.shift_warning().is_some() && !self.is_paused() {
+            let alpha = 0.5 + 0.4 * (get_time() as f32 * 8.0).sin();
+            let color = Color::new(ORANGE.r, ORANGE.g, ORANGE.b, alpha);
+            draw_rectangle_lines(0.0, 0.0, screen_width(), top, 8.0, color);
+        }
+        for &(row, col) in &self.questions {
+            font::on_tile("?", layout.tile(row, col), layout.tile(row, col).h, BLACK);
+        }
+        // The next move clears the hint
+        if let Some(Ok((row, col))) = self.hint {
+            let tile = layout.tile(row, col);
+            draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 6.0, GREEN);
+        }
+        // Hidden, so pausing can't buy time to think
+        if self.is_paused() {
+            draw_rectangle(0.0, 0.0, screen_width(), top, DARKGRAY);
+            let board = Rect::new(0.0, 0.0, screen_width(), top);
+            font::centred(&t!("paused"), board, 30.0, WHITE);
+        }
+        if let Some(tutorial) = &self.tutorial {
+            draw_tutorial(tutorial, self.game.is_over(), layout, top + scale::px(50.0));
+        }
+        if let Some(lines) = self.results() {
+            let panel = results_panel(&lines, self.seed.is_some());
+            draw_rectangle(
+                panel.x,
+                panel.y,
+                panel.w,
+                panel.h,
+                Color::new(0.0, 0.0, 0.0, 0.75),
+            );
+            for (i, line) in lines.iter().enumerate() {
+                let top = panel.y + 5.0 + i as f32 * scale::px(20.0);
+                font::line(line, panel.x + 8.0, top, 20.0, WHITE);
+            }
+        }
+    }
+
+    /// The lines of the results panel once a game is over: how efficient
+    /// a win was, nothing for a loss.
+    fn results(&self) -> Option<Vec<String>> {
+        let record = self.record.as_ref()?;
+        if self.is_trainer() {
+            return Some(self.training_results(record));
+        }
+        let Some(efficiency) = record.efficiency() else {
+            return Some(Vec::new()).filter(|_| self.seed.is_some());
+        };
+        Some(vec![
+            t!(
+                "result-speed",
+                speed = format!("{:.2}", record.bbbv_per_second())
+            ),
+            t!("result-ioe", ioe = format!("{:.2}", efficiency.ioe)),
+            t!(
+                "result-throughput",
+                throughput = format!("{:.2}", efficiency.throughput)
+            ),
+            t!("result-rqp", rqp = format!("{:.1}", efficiency.rqp)),
+        ])
+    }
+
+    /// The trainer's results panel: the game's score, its wasted moves
+    /// and how the player's efficiency is moving.
+    fn training_results(&self, record: &Record) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(efficiency) = record.efficiency() {
+            lines.push(t!("trainer-score", ioe = format!("{:.2}", efficiency.ioe)));
+            lines.push(t!(
+                "trainer-clicks",
+                clicks = record.clicks,
+                bbbv = record.bbbv
+            ));
+        }
+        if self.waste.is_empty() {
+            lines.push(t!("trainer-waste-none"));
+        }
+        for &(i, waste) in self.waste.iter().take(WASTE_LINES) {
+            let id = match waste {
+                Waste::NoEffect => "trainer-waste-no-effect",
+                Waste::Unflag => "trainer-waste-unflag",
+                Waste::UnusedFlag => "trainer-waste-unused-flag",
+            };
+            let (row, col) = self.replay.steps()[i].position();
+            lines.push(t!(id, step = i + 1, row = row, col = col));
+        }
+        if self.waste.len() > WASTE_LINES {
+            lines.push(t!(
+                "trainer-waste-more",
+                count = self.waste.len() - WASTE_LINES
+            ));
+        }
+        if let Some(progress) = self.progress {
+            let recent = format!("{:.2}", progress.recent);
+            lines.push(match progress.before {
+                Some(before) => t!(
+                    "trainer-progress-change",
+                    games = progress.games,
+                    ioe = recent,
+                    change = format!("{:+.2}", progress.recent - before)
+                ),
+                None => t!("trainer-progress", games = progress.games, ioe = recent),
+            });
+        }
+        lines
+    }
+}
+
+/// Where the results panel goes, on the right of the window, with room
+/// under the lines for the copy seed button if there is one. It widens
+/// for long lines.
+fn results_panel(lines: &[String], button: bool) -> Rect {
+    let button = if button { RESULTS_BUTTON } else { 0.0 };
+    let height = scale::px(lines.len() as f32 * 20.0 + 10.0 + button);
+    let width = lines
+        .iter()
+        .map(|line| font::measure(line, 20.0).width + 16.0)
+        .fold(scale::px(RESULTS_WIDTH), f32::max);
+    Rect::new(
+        screen_width() - width - 10.0,
+        (screen_height() - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+/// Five stars in a row from `x`, centred on `y`, the first `filled` of
+/// them in gold.
+fn draw_stars(x: f32, y: f32, filled: u8) {
+    let radius = scale::px(10.0);
+    for i in 0..5 {
+        let centre = vec2(x + radius + i as f32 * radius * 2.2, y);
+        let color = if i < filled { GOLD } else { LIGHTGRAY };
+        // Ten points round the centre, alternating between the tips and
+        // the notches between them
+        let point = |k: usize| {
+            let angle = std::f32::consts::PI * (k as f32 / 5.0 - 0.5);
+            let radius = if k.is_multiple_of(2) {
+                radius
+            } else {
+                radius * 0.45
+            };
+            centre + vec2(angle.cos(), angle.sin()) * radius
+        };
+        for k in 0..10 {
+            draw_triangle(centre, point(k), point(k + 1), color);
+        }
+    }
+}
+
+fn draw_tutorial(tutorial: &Tutorial, over: bool, layout: &Layout, top: f32) {
+    let step = tutorial.step();
+    if !over {
+        for &(row, col) in &step.highlight {
+            let tile = layout.tile(row, col);
+            draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 6.0, ORANGE);
+        }
+    }
+    let text = if over {
+        t!("tutorial-done")
+    } else {
+        format!(
+            "{} {}",
+            t!(
+                "tutorial-step",
+                step = tutorial.position() + 1,
+                steps = tutorial.len()
+            ),
+            i18n::message(&step.text)
+        )
+    };
+    let mut y = top;
+    for line in wrap(&text, screen_width() - 20.0) {
+        font::line(&line, 10.0, y, TUTORIAL_FONT, layout.skin().text);
+        y += scale::px(TUTORIAL_FONT) * 1.2;
+    }
+    if let Some(hint) = tutorial.hint().filter(|_| !over) {
+        y += scale::px(TUTORIAL_FONT) * 0.4;
+        font::line(&i18n::message(hint), 10.0, y, TUTORIAL_FONT, RED);
+    }
+}
+
+/// Breaks `text` into lines no wider than `width` at the tutorial's size.
+fn wrap(text: &str, width: f32) -> Vec<String> {
+    let fits = |line: &str| font::measure(line, TUTORIAL_FONT).width <= width;
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if fits(&format!("{} {}", line, word)) => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    lines
+}
+
+/// The console commands that play moves on the current game. Rows and
+/// columns count from 1, as the coordinates in the corner do.
+pub fn register(registry: &mut Registry<Handler>) {
+    for (name, about, run) in [
+        (
+            "reveal",
+            "Reveals a cell, or chords a number.",
+            reveal as Handler,
+        ),
+        ("flag", "Flags or unflags a hidden cell.", flag),
+        (
+            "chord",
+            "Reveals around a number whose flags are placed.",
+            chord,
+        ),
+    ] {
+        registry.register(Command {
+            name,
+            usage: "<row> <col>",
+            about,
+            run,
+        });
+    }
+    registry.register(Command {
+        name: "hint",
+        usage: "",
+        about: "Points out a safe cell, if a hint is left.",
+        run: |context, _| {
+            context.classic()?.hint();
+            Ok(String::new())
+        },
+    });
+    registry.register(Command {
+        name: "solve",
+        usage: "",
+        about: "Plays every move the numbers prove, until none is left.",
+        run: solve,
+    });
+}
+
+/// The cell `args` point at, counted from 0.
+fn cell(classic: &Classic, args: &[&str]) -> Result<(usize, usize), String> {
+    let board = classic.board();
+    let row: usize = console::arg(args, 0, "row")?.unwrap_or_default();
+    let col: usize = console::arg(args, 1, "col")?.unwrap_or_default();
+    if !(1..=board.height()).contains(&row) || !(1..=board.width()).contains(&col) {
+        return Err(format!(
+            "the board is {} rows by {} columns",
+            board.height(),
+            board.width()
+        ));
+    }
+    Ok((row - 1, col - 1))
+}
+
+fn reveal(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    let (row, col) = cell(classic, args)?;
+    classic.mark_assisted();
+    classic.click(row, col);
+    Ok(String::new())
+}
+
+fn flag(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    let (row, col) = cell(classic, args)?;
+    classic.mark_assisted();
+    classic.flag(row, col);
+    Ok(String::new())
+}
+
+fn chord(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    let (row, col) = cell(classic, args)?;
+    classic.mark_assisted();
+    classic.chord(row, col);
+    Ok(String::new())
+}
+
+fn solve(context: &mut Context, _: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    if !classic.solvable() {
+        return Err("the solver doesn't know these rules".to_owned());
+    }
+    let mut moves = 0;
+    while !classic.is_over() {
+        let knowledge = Knowledge::of(&View::of(classic.board()));
+        let hidden = |&(row, col): &(usize, usize)| {
+            classic.board().cell(row, col).cell_state == CellState::Hidden
+        };
+        let mines: Vec<_> = knowledge.mines.into_iter().filter(hidden).collect();
+        let safe: Vec<_> = knowledge.safe.into_iter().filter(hidden).collect();
+        if mines.is_empty() && safe.is_empty() {
+            break;
+        }
+        classic.mark_assisted();
+        let revision = classic.board().revision();
+        for (row, col) in mines {
+            classic.flag(row, col);
+            moves += 1;
+        }
+        for (row, col) in safe {
+            // An earlier opening may have uncovered it already
+            if classic.board().cell(row, col).cell_state == CellState::Hidden {
+                classic.click(row, col);
+                moves += 1;
+            }
+        }
+        // The tutorial may block the moves
+        if classic.board().revision() == revision {
+            break;
+        }
+    }
+    Ok(match moves {
+        0 if !classic.is_over() => "the numbers prove nothing, a guess is needed".to_owned(),
+        moves => format!("played {} moves", moves),
+    })
+}
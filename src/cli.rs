@@ -0,0 +1,234 @@
+//! Headless subcommands that run instead of opening the game window.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use minesweeper_core::agent::{self, Agent};
+use minesweeper_core::bench;
+use minesweeper_core::generate::{self, Verdict};
+use minesweeper_core::history::{self, Format as HistoryFormat};
+use minesweeper_core::rating::Rating;
+use minesweeper_core::replay;
+use minesweeper_core::save;
+use minesweeper_core::script::{Script, ScriptAgent, ScriptMod};
+use minesweeper_core::tournament::{self, TournamentConfig};
+use minesweeper_core::{Board, Pos};
+
+/// Returns the value following `flag`, e.g. `--seed 4`.
+pub fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+pub fn parsed<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> Result<T, String> {
+    match flag_value(args, flag) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid value for {}: {}", flag, value)),
+        None => Ok(default),
+    }
+}
+
+/// Runs the subcommand named by `args[1]`, or returns `None` if there is none
+/// and the game should start.
+pub fn run(args: &[String]) -> Option<Result<(), String>> {
+    match args.get(1).map(String::as_str) {
+        Some("tournament") => Some(run_tournament(&args[2..])),
+        Some("verify") => Some(run_verify(&args[2..])),
+        Some("verify-replay") => Some(run_verify_replay(&args[2..])),
+        Some("export-stats") => Some(run_export_stats(&args[2..])),
+        Some("import-stats") => Some(run_import_stats(&args[2..])),
+        Some("sync") => Some(run_sync()),
+        Some("--bench-report") => Some(run_bench_report(&args[2..])),
+        _ => None,
+    }
+}
+
+/// Parses every script in `dir` with the extension `ext`, in name order.
+/// A missing directory means no scripts; a script that fails to parse is an
+/// error.
+fn load_dir<T, E: std::fmt::Display>(
+    dir: &Path,
+    ext: &str,
+    parse: impl Fn(&str, &str) -> Result<T, E>,
+) -> Result<Vec<T>, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|found| found == ext))
+        .collect();
+    paths.sort();
+
+    let mut scripts = Vec::new();
+    for path in paths {
+        let source = fs::read_to_string(&path)
+            .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        scripts.push(parse(&name, &source).map_err(|err| format!("{}: {}", path.display(), err))?);
+    }
+    Ok(scripts)
+}
+
+/// Loads every `.msb` script bot in `dir`.
+fn load_scripts(dir: &Path) -> Result<Vec<Box<dyn Agent>>, String> {
+    let scripts = load_dir(dir, "msb", Script::parse)?;
+    Ok(scripts
+        .into_iter()
+        .map(|script| Box::new(ScriptAgent::new(script)) as Box<dyn Agent>)
+        .collect())
+}
+
+/// Loads every `.msm` mod in the directory `--mods` names, `mods` by
+/// default.
+pub fn load_mods(args: &[String]) -> Result<Vec<ScriptMod>, String> {
+    let dir = flag_value(args, "--mods").unwrap_or_else(|| "mods".to_owned());
+    load_dir(Path::new(&dir), "msm", ScriptMod::parse)
+}
+
+fn run_tournament(args: &[String]) -> Result<(), String> {
+    let config = TournamentConfig {
+        width: parsed(args, "--width", 9)?,
+        height: parsed(args, "--height", 9)?,
+        mines: parsed(args, "--mines", 10)?,
+        boards: parsed(args, "--boards", 100)?,
+        seed: parsed(args, "--seed", 0)?,
+    };
+    let out = flag_value(args, "--out").unwrap_or_else(|| "tournament.txt".to_owned());
+
+    let mut agents = agent::registered();
+    let scripts = flag_value(args, "--scripts").unwrap_or_else(|| "bots".to_owned());
+    agents.extend(load_scripts(Path::new(&scripts))?);
+    if let Some(names) = flag_value(args, "--agents") {
+        let names: Vec<&str> = names.split(',').collect();
+        agents.retain(|agent| names.contains(&agent.name()));
+        if agents.is_empty() {
+            return Err(format!("no registered agent matches {}", names.join(",")));
+        }
+    }
+
+    let standings = tournament::run(&mut agents, config).map_err(|err| err.to_string())?;
+    let table = tournament::table(&standings);
+    print!("{}", table);
+    fs::write(&out, table).map_err(|err| format!("could not write {}: {}", out, err))
+}
+
+/// Times the core's hot paths on every size in `bench::SIZES`, each for
+/// `--budget` milliseconds, and prints the table.
+fn run_bench_report(args: &[String]) -> Result<(), String> {
+    let budget = Duration::from_millis(parsed(args, "--budget", bench::BUDGET.as_millis() as u64)?);
+    print!("{}", bench::table(&bench::run(&bench::SIZES, budget)));
+    Ok(())
+}
+
+/// Checks whether a saved board can be cleared by logic from an opening.
+/// A board that needs a guess is reported as an error, so scripts can
+/// test the exit status.
+fn run_verify(args: &[String]) -> Result<(), String> {
+    let path = flag_value(args, "--board").ok_or("verify needs --board FILE")?;
+    let from = flag_value(args, "--from").ok_or("verify needs --from ROW,COL")?;
+    let start = from
+        .split_once(',')
+        .and_then(|(row, col)| Some(Pos::new(row.trim().parse().ok()?, col.trim().parse().ok()?)))
+        .ok_or_else(|| format!("invalid value for --from: {}", from))?;
+    let bytes = fs::read(&path).map_err(|err| format!("could not read {}: {}", path, err))?;
+    let board: Board = save::load(&bytes).map_err(|err| format!("{}: {}", path, err))?;
+
+    let verdict = generate::verify(&board, start);
+    if verdict != Verdict::BadStart {
+        let mut opened = board.clone();
+        opened.reveal(start.row, start.col);
+        let rating = Rating::of(&opened);
+        println!(
+            "difficulty {}/5: {:.0}% to survive {} guesses, complexity {:.2}, 3BV density {:.2}",
+            rating.stars(),
+            rating.survival * 100.0,
+            rating.guesses,
+            rating.complexity,
+            rating.bbbv_density
+        );
+    }
+    match verdict {
+        Verdict::Solvable => {
+            println!(
+                "{} is solvable without guessing from {},{}",
+                path, start.row, start.col
+            );
+            Ok(())
+        }
+        Verdict::BadStart => Err(format!(
+            "{},{} is a mine or off the {}x{} board",
+            start.row,
+            start.col,
+            board.width(),
+            board.height()
+        )),
+        Verdict::Guess { at, revealed } => Err(format!(
+            "{} needs a guess: logic reveals {} cells from {},{}, then stops at {},{}",
+            path, revealed, start.row, start.col, at.row, at.col
+        )),
+    }
+}
+
+/// Plays a saved replay through again and checks it ends as recorded. A
+/// replay that doesn't is reported as an error, so whatever accepts
+/// replays can test the exit status.
+fn run_verify_replay(args: &[String]) -> Result<(), String> {
+    let path = flag_value(args, "--replay").ok_or("verify-replay needs --replay FILE")?;
+    let bytes = fs::read(&path).map_err(|err| format!("could not read {}: {}", path, err))?;
+    let replay = replay::verify(&bytes).map_err(|err| format!("{}: {}", path, err))?;
+    let time = replay
+        .moves()
+        .last()
+        .map_or(0.0, |(at, _)| at.as_secs_f64());
+    println!(
+        "{} replays as recorded: {} moves in {:.3} s",
+        path,
+        replay.len(),
+        time
+    );
+    Ok(())
+}
+
+/// Writes every recorded game as CSV or JSON, to a file or to stdout.
+fn run_export_stats(args: &[String]) -> Result<(), String> {
+    let format: HistoryFormat = parsed(args, "--format", HistoryFormat::Csv)?;
+    let stats = crate::stats::load().ok_or("the stats file could not be read")?;
+    let table = history::export(&stats.records, format);
+    match flag_value(args, "--out") {
+        Some(out) => {
+            fs::write(&out, table).map_err(|err| format!("could not write {}: {}", out, err))
+        }
+        None => {
+            print!("{}", table);
+            Ok(())
+        }
+    }
+}
+
+/// Merges a history export or another stats file into the games
+/// recorded here, skipping games already recorded.
+fn run_import_stats(args: &[String]) -> Result<(), String> {
+    let path = flag_value(args, "--file").ok_or("import-stats needs --file FILE")?;
+    let bytes = fs::read(&path).map_err(|err| format!("could not read {}: {}", path, err))?;
+    let added = crate::stats::import(&bytes).map_err(|err| format!("{}: {}", path, err))?;
+    println!("imported {} new games from {}", added, path);
+    Ok(())
+}
+
+fn run_sync() -> Result<(), String> {
+    let report = crate::sync::run().ok_or("sync is not set up; see sync.txt in the README")??;
+    println!("uploaded: {}", report.uploaded.join(", "));
+    println!("downloaded: {}", report.downloaded.join(", "));
+    println!("{} games came from the server", report.games);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+use minesweeper_core::protocol::{ClientMessage, ServerMessage};
+use minesweeper_core::{info, trace};
+
+/// Non-blocking connection to a `minesweeper-server`, polled once per frame.
+pub struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl Client {
+    pub fn connect(addr: &str, room: &str, spectate: bool) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nonblocking(true)?;
+        info!("connected to {}", addr);
+
+        let mut client = Self {
+            stream,
+            buffer: Vec::new(),
+        };
+        client.send(&ClientMessage::hello())?;
+        let room = room.to_owned();
+        client.send(&if spectate {
+            ClientMessage::Spectate { room }
+        } else {
+            ClientMessage::Join { room }
+        })?;
+        Ok(client)
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        trace!("sending {}", message.encode());
+        writeln!(self.stream, "{}", message.encode())
+    }
+
+    /// Returns every complete message received since the last poll.
+    pub fn poll(&mut self) -> io::Result<Vec<ServerMessage>> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::ConnectionAborted.into()),
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(message) = ServerMessage::decode(line.trim_end()) {
+                messages.push(message);
+            }
+        }
+        Ok(messages)
+    }
+}
@@ -0,0 +1,640 @@
+//! Settings and colours read from `config.txt`, `theme.txt` and
+//! `theme-dark.txt` in the data directory. All are saves in the text
+//! format, so they start with a `minesweeper config 1` or
+//! `minesweeper theme 1` header; any field left out keeps its default.
+//! Edits are picked up while the game runs.
+
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+
+use macroquad::prelude::*;
+use minesweeper_core::export::{ReplayOptions, VideoFormat};
+use minesweeper_core::hint::Hints;
+use minesweeper_core::save::{self, Document, Format, SaveError, Saved};
+use minesweeper_core::{info, warn};
+
+use crate::appearance::Appearance;
+use crate::atlas::Skin;
+use crate::layout::Geometry;
+use crate::mouse::Buttons;
+use crate::preset::{self, Name, Preset};
+use crate::storage;
+use crate::theme::{self, Theme};
+use crate::watch::Watched;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Pixels between tiles.
+    pub gap: f32,
+    /// Pixels between the window edge and the board.
+    pub padding: f32,
+    /// Radius of the tiles' corners in pixels.
+    pub corner_radius: f32,
+    /// Largest tile side in pixels, or 0 to fill the window.
+    pub max_tile: f32,
+    /// Language code such as `de`, or empty to follow the system.
+    pub language: String,
+    /// TrueType file for numbers and HUD text, or empty for the built-in
+    /// font.
+    pub font: String,
+    /// Keyboard cursor and spoken announcements, see `access`.
+    pub accessible: bool,
+    /// Command that speaks announcements, e.g. `espeak`.
+    pub speech: String,
+    /// Flag the mines single numbers prove after every move.
+    pub auto_flag: bool,
+    /// Chord every number whose flags are all placed, after every move.
+    pub auto_chord: bool,
+    /// Samples drawn for each tangle of numbers too large to work out
+    /// exactly in the review's chance overlay.
+    pub samples: u32,
+    /// Hints allowed per game.
+    pub hints: u32,
+    /// Seconds added to a game's time for each hint.
+    pub hint_penalty: f32,
+    /// How much faster than played exported GIFs run.
+    pub export_speed: f32,
+    /// Pixels per cell in exported GIFs.
+    pub export_cell: u32,
+    /// `png` for a PNG per move with an ffconcat manifest, or `raw` for
+    /// raw RGBA frames at `video_fps`.
+    pub video_format: String,
+    pub video_fps: u32,
+    /// Boards of the player's own, from `preset.NAME` fields.
+    pub presets: Vec<Preset>,
+    pub buttons: Buttons,
+    /// Scrolling over a hidden cell cycles its mark instead of scrolling.
+    pub wheel_marks: bool,
+    /// Ask before quitting or starting over in the middle of a game.
+    pub confirm_leave: bool,
+    /// Pause classic games when the window loses focus.
+    pub auto_pause: bool,
+    /// Outline the cell under the pointer.
+    pub hover: bool,
+    /// Show the coordinates of the cell under the pointer in the corner.
+    pub coordinates: bool,
+    /// Seconds the last move's cells stay marked, or 0 for no marks.
+    pub trail: f32,
+    /// Confetti, a fanfare and the final time when a game is won.
+    pub celebrate: bool,
+    /// Command that plays the win fanfare's WAV file, e.g. `aplay -q`.
+    pub sound: String,
+    /// Turns off the loss ripple, confetti, spreading openings and the
+    /// CRT shader, and shows their end state at once.
+    pub reduced_motion: bool,
+    pub appearance: Appearance,
+    /// Hours on the local clock between which `auto` is dark when the
+    /// system's dark mode can't be read.
+    pub dark_from: u32,
+    pub dark_until: u32,
+    /// Scanlines, curvature and a vignette over the game, see `crt`.
+    pub crt: bool,
+    /// A theme from `themes/` used instead of the light and dark ones, or
+    /// empty for those.
+    pub theme: String,
+    /// Size of HUD text, panels and settings windows, 0.75 to 2.
+    pub ui_scale: f32,
+    /// Vibrate on touch devices, see `haptics`.
+    pub haptics: bool,
+    /// How strong vibrations are, 0.1 to 1.
+    pub haptic_strength: f32,
+    /// Command that vibrates for the milliseconds it is given, e.g.
+    /// `termux-vibrate -f -d`.
+    pub vibrate: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            gap: 1.0,
+            padding: 2.0,
+            corner_radius: 0.0,
+            max_tile: 0.0,
+            language: String::new(),
+            font: String::new(),
+            accessible: false,
+            speech: String::new(),
+            auto_flag: false,
+            auto_chord: false,
+            samples: 1000,
+            hints: 3,
+            hint_penalty: 10.0,
+            export_speed: 1.0,
+            export_cell: 16,
+            video_format: "png".to_owned(),
+            video_fps: 30,
+            presets: Vec::new(),
+            buttons: Buttons::default(),
+            wheel_marks: false,
+            confirm_leave: true,
+            auto_pause: true,
+            hover: true,
+            coordinates: false,
+            trail: 1.5,
+            celebrate: true,
+            sound: String::new(),
+            reduced_motion: false,
+            appearance: Appearance::Auto,
+            dark_from: 19,
+            dark_until: 7,
+            crt: false,
+            theme: String::new(),
+            ui_scale: 1.0,
+            haptics: true,
+            haptic_strength: 1.0,
+            vibrate: String::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn geometry(&self) -> Geometry {
+        Geometry {
+            gap: self.gap,
+            padding: self.padding,
+            corner: self.corner_radius,
+            max_tile: self.max_tile,
+        }
+    }
+
+    /// The hints a new game starts with.
+    pub fn hints(&self) -> Hints {
+        Hints::new(self.hints, Duration::from_secs_f32(self.hint_penalty))
+    }
+
+    /// How long the last move stays marked.
+    pub fn trail(&self) -> Duration {
+        Duration::from_secs_f32(self.trail)
+    }
+
+    pub fn replay_options(&self) -> ReplayOptions {
+        ReplayOptions {
+            cell: self.export_cell as usize,
+            speed: f64::from(self.export_speed),
+        }
+    }
+
+    /// The shipped presets, then the player's.
+    pub fn presets(&self) -> Vec<Preset> {
+        let mut presets = preset::builtin();
+        presets.extend(self.presets.iter().cloned());
+        presets
+    }
+
+    pub fn video_format(&self) -> VideoFormat {
+        match self.video_format.as_str() {
+            "raw" => VideoFormat::Raw {
+                fps: self.video_fps,
+            },
+            _ => VideoFormat::Png,
+        }
+    }
+}
+
+impl Saved for Config {
+    const KIND: &'static str = "config";
+
+    fn save(&self, document: &mut Document) {
+        document.set("gap", self.gap);
+        document.set("padding", self.padding);
+        document.set("corner_radius", self.corner_radius);
+        document.set("max_tile", self.max_tile);
+        document.set("language", &self.language);
+        document.set("font", &self.font);
+        document.set("accessible", self.accessible);
+        document.set("speech", &self.speech);
+        document.set("auto_flag", self.auto_flag);
+        document.set("auto_chord", self.auto_chord);
+        document.set("samples", self.samples);
+        document.set("hints", self.hints);
+        document.set("hint_penalty", self.hint_penalty);
+        document.set("export_speed", self.export_speed);
+        document.set("export_cell", self.export_cell);
+        document.set("video_format", &self.video_format);
+        document.set("video_fps", self.video_fps);
+        document.set("reveal_button", self.buttons.reveal);
+        document.set("flag_button", self.buttons.flag);
+        document.set("chord_button", self.buttons.chord);
+        document.set("wheel_marks", self.wheel_marks);
+        document.set("confirm_leave", self.confirm_leave);
+        document.set("auto_pause", self.auto_pause);
+        document.set("hover", self.hover);
+        document.set("coordinates", self.coordinates);
+        document.set("trail", self.trail);
+        document.set("celebrate", self.celebrate);
+        document.set("sound", &self.sound);
+        document.set("reduced_motion", self.reduced_motion);
+        document.set("appearance", self.appearance);
+        document.set("dark_from", self.dark_from);
+        document.set("dark_until", self.dark_until);
+        document.set("crt", self.crt);
+        document.set("theme", &self.theme);
+        document.set("ui_scale", self.ui_scale);
+        document.set("haptics", self.haptics);
+        document.set("haptic_strength", self.haptic_strength);
+        document.set("vibrate", &self.vibrate);
+        for preset in &self.presets {
+            document.set(
+                &format!("preset.{}", preset.name()),
+                format!("{}x{}x{}", preset.width, preset.height, preset.mines),
+            );
+        }
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        let default = Self::default();
+        let buttons = Buttons {
+            reveal: optional(document, "reveal_button", default.buttons.reveal)?,
+            flag: optional(document, "flag_button", default.buttons.flag)?,
+            chord: optional(document, "chord_button", default.buttons.chord)?,
+        };
+        // Two actions on one button would leave one of them unreachable
+        if !buttons.is_valid() {
+            return Err(invalid("chord_button", document));
+        }
+        let length = |key: &str, default: f32| -> Result<f32, SaveError> {
+            let value = optional(document, key, default)?;
+            if !(0.0..=100.0).contains(&value) {
+                return Err(invalid(key, document));
+            }
+            Ok(value)
+        };
+        Ok(Self {
+            gap: length("gap", default.gap)?,
+            padding: length("padding", default.padding)?,
+            corner_radius: length("corner_radius", default.corner_radius)?,
+            max_tile: match optional(document, "max_tile", default.max_tile)? {
+                side if side == 0.0 || (16.0..=512.0).contains(&side) => side,
+                _ => return Err(invalid("max_tile", document)),
+            },
+            language: document.get("language").unwrap_or_default().to_owned(),
+            font: document.get("font").unwrap_or_default().to_owned(),
+            accessible: optional(document, "accessible", default.accessible)?,
+            speech: document.get("speech").unwrap_or_default().to_owned(),
+            auto_flag: optional(document, "auto_flag", default.auto_flag)?,
+            auto_chord: optional(document, "auto_chord", default.auto_chord)?,
+            samples: match optional(document, "samples", default.samples)? {
+                samples if (10..=100_000).contains(&samples) => samples,
+                _ => return Err(invalid("samples", document)),
+            },
+            hints: optional(document, "hints", default.hints)?,
+            hint_penalty: match optional(document, "hint_penalty", default.hint_penalty)? {
+                penalty if (0.0..=3600.0).contains(&penalty) => penalty,
+                _ => return Err(invalid("hint_penalty", document)),
+            },
+            export_speed: match optional(document, "export_speed", default.export_speed)? {
+                speed if (0.1..=100.0).contains(&speed) => speed,
+                _ => return Err(invalid("export_speed", document)),
+            },
+            export_cell: match optional(document, "export_cell", default.export_cell)? {
+                cell if (4..=64).contains(&cell) => cell,
+                _ => return Err(invalid("export_cell", document)),
+            },
+            video_format: match optional(document, "video_format", default.video_format)? {
+                format if format == "png" || format == "raw" => format,
+                _ => return Err(invalid("video_format", document)),
+            },
+            video_fps: match optional(document, "video_fps", default.video_fps)? {
+                fps if (1..=240).contains(&fps) => fps,
+                _ => return Err(invalid("video_fps", document)),
+            },
+            presets: document
+                .fields()
+                .filter_map(|(key, value)| Some((key, key.strip_prefix("preset.")?, value)))
+                .map(|(key, name, value)| {
+                    Preset::parse(Name::Custom(name.to_owned()), value)
+                        .ok_or_else(|| invalid(key, document))
+                })
+                .collect::<Result<_, _>>()?,
+            buttons,
+            wheel_marks: optional(document, "wheel_marks", default.wheel_marks)?,
+            confirm_leave: optional(document, "confirm_leave", default.confirm_leave)?,
+            auto_pause: optional(document, "auto_pause", default.auto_pause)?,
+            hover: optional(document, "hover", default.hover)?,
+            coordinates: optional(document, "coordinates", default.coordinates)?,
+            trail: match optional(document, "trail", default.trail)? {
+                seconds if (0.0..=60.0).contains(&seconds) => seconds,
+                _ => return Err(invalid("trail", document)),
+            },
+            celebrate: optional(document, "celebrate", default.celebrate)?,
+            sound: document.get("sound").unwrap_or_default().to_owned(),
+            reduced_motion: optional(document, "reduced_motion", default.reduced_motion)?,
+            appearance: optional(document, "appearance", default.appearance)?,
+            dark_from: match optional(document, "dark_from", default.dark_from)? {
+                hour if hour < 24 => hour,
+                _ => return Err(invalid("dark_from", document)),
+            },
+            dark_until: match optional(document, "dark_until", default.dark_until)? {
+                hour if hour < 24 => hour,
+                _ => return Err(invalid("dark_until", document)),
+            },
+            crt: optional(document, "crt", default.crt)?,
+            theme: document.get("theme").unwrap_or_default().to_owned(),
+            ui_scale: match optional(document, "ui_scale", default.ui_scale)? {
+                scale if (0.75..=2.0).contains(&scale) => scale,
+                _ => return Err(invalid("ui_scale", document)),
+            },
+            haptics: optional(document, "haptics", default.haptics)?,
+            haptic_strength: match optional(document, "haptic_strength", default.haptic_strength)? {
+                strength if (0.1..=1.0).contains(&strength) => strength,
+                _ => return Err(invalid("haptic_strength", document)),
+            },
+            vibrate: document.get("vibrate").unwrap_or_default().to_owned(),
+        })
+    }
+}
+
+/// Colours are `#rrggbb` or `#rrggbbaa`.
+impl Saved for Skin {
+    const KIND: &'static str = "theme";
+
+    fn save(&self, document: &mut Document) {
+        let mut skin = self.clone();
+        for (key, color) in skin.colors() {
+            document.set(key, hex(*color));
+        }
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        load_colors(Skin::default(), document)
+    }
+}
+
+/// The skin of the dark appearance, whose missing colours come from
+/// `Skin::dark` instead of the light defaults.
+struct DarkSkin(Skin);
+
+impl Default for DarkSkin {
+    fn default() -> Self {
+        Self(Skin::dark())
+    }
+}
+
+impl Saved for DarkSkin {
+    const KIND: &'static str = "theme";
+
+    fn save(&self, document: &mut Document) {
+        self.0.save(document);
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        load_colors(Skin::dark(), document).map(Self)
+    }
+}
+
+fn load_colors(mut skin: Skin, document: &Document) -> Result<Skin, SaveError> {
+    for (key, color) in skin.colors() {
+        if let Ok(value) = document.get(key) {
+            *color = parse_hex(value).ok_or_else(|| invalid(key, document))?;
+        }
+    }
+    Ok(skin)
+}
+
+fn optional<T: FromStr>(document: &Document, key: &str, default: T) -> Result<T, SaveError> {
+    match document.get(key) {
+        Ok(_) => document.parse(key),
+        Err(_) => Ok(default),
+    }
+}
+
+fn invalid(key: &str, document: &Document) -> SaveError {
+    SaveError::Invalid {
+        field: key.to_owned(),
+        value: document.get(key).unwrap_or_default().to_owned(),
+    }
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, a]: [u8; 4] = color.into();
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+pub fn parse_hex(value: &str) -> Option<Color> {
+    let digits = value.strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+/// What `Live::poll` reloaded.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Reloaded {
+    pub config: bool,
+    pub theme: bool,
+}
+
+/// The config and theme in use, reloaded whenever their files change.
+pub struct Live {
+    pub config: Config,
+    /// The skin in use: the picked theme's, or the light or dark one.
+    pub skin: Skin,
+    light: Skin,
+    dark: Skin,
+    is_dark: bool,
+    /// The theme from `themes/` named in the config, if it loaded.
+    custom: Option<Theme>,
+    config_file: Watched,
+    theme_file: Watched,
+    dark_file: Watched,
+    custom_file: Option<Watched>,
+    /// Why the picked theme didn't load, until `take_problem`.
+    problem: Option<String>,
+}
+
+impl Live {
+    pub fn load() -> Self {
+        let mut live = Self {
+            config: Config::default(),
+            skin: Skin::default(),
+            light: Skin::default(),
+            dark: Skin::dark(),
+            is_dark: false,
+            custom: None,
+            config_file: Watched::new(storage::data_dir().join("config.txt")),
+            theme_file: Watched::new(storage::data_dir().join("theme.txt")),
+            dark_file: Watched::new(storage::data_dir().join("theme-dark.txt")),
+            custom_file: None,
+            problem: None,
+        };
+        live.config = read(&live.config_file).unwrap_or_default();
+        live.light = read(&live.theme_file).unwrap_or_default();
+        live.dark = read::<DarkSkin>(&live.dark_file).unwrap_or_default().0;
+        live.select_theme();
+        live
+    }
+
+    /// Switches between the light and dark skins. Returns whether the
+    /// skin in use changed, which it doesn't under a picked theme.
+    pub fn set_dark(&mut self, dark: bool) -> bool {
+        if dark == self.is_dark {
+            return false;
+        }
+        self.is_dark = dark;
+        self.pick_skin();
+        self.custom.is_none()
+    }
+
+    /// The font file to draw text in: the theme's, or the config's.
+    pub fn font(&self) -> &str {
+        self.custom
+            .as_ref()
+            .and_then(|custom| custom.font.as_deref())
+            .unwrap_or(&self.config.font)
+    }
+
+    /// Why the picked theme failed to load, once per failure.
+    pub fn take_problem(&mut self) -> Option<String> {
+        self.problem.take()
+    }
+
+    /// Remembers the theme picked in the settings window; empty for the
+    /// light and dark ones.
+    pub fn set_theme(&mut self, name: &str) {
+        self.config.theme = name.to_owned();
+        self.store();
+        self.select_theme();
+    }
+
+    /// Loads the theme the config names and starts watching its file.
+    fn select_theme(&mut self) {
+        self.custom_file =
+            (!self.config.theme.is_empty()).then(|| Watched::new(theme::path(&self.config.theme)));
+        self.load_theme();
+    }
+
+    fn load_theme(&mut self) {
+        self.custom = None;
+        if !self.config.theme.is_empty() {
+            match theme::load(&self.config.theme) {
+                Ok(custom) => {
+                    info!("loaded theme {}", self.config.theme);
+                    self.custom = Some(custom);
+                }
+                Err(problem) => {
+                    warn!("could not load a theme: {}", problem);
+                    self.problem = Some(problem);
+                }
+            }
+        }
+        self.pick_skin();
+    }
+
+    fn pick_skin(&mut self) {
+        self.skin = match (&self.custom, self.is_dark) {
+            (Some(custom), _) => custom.skin.clone(),
+            (None, true) => self.dark.clone(),
+            (None, false) => self.light.clone(),
+        };
+    }
+
+    /// Remembers the appearance picked in the settings window.
+    pub fn set_appearance(&mut self, appearance: Appearance) {
+        self.config.appearance = appearance;
+        self.store();
+    }
+
+    /// Remembers the language picked in the settings window.
+    pub fn set_language(&mut self, code: &str) {
+        self.config.language = code.to_owned();
+        self.store();
+    }
+
+    /// Remembers the reduced motion setting from the settings window.
+    pub fn set_reduced_motion(&mut self, reduced: bool) {
+        self.config.reduced_motion = reduced;
+        self.store();
+    }
+
+    /// Remembers the vibration settings picked in the settings window.
+    pub fn set_haptics(&mut self, enabled: bool, strength: f32) {
+        self.config.haptics = enabled;
+        self.config.haptic_strength = strength;
+        self.store();
+    }
+
+    /// Remembers the mouse buttons picked in the settings window.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.config.buttons = buttons;
+        self.store();
+    }
+
+    fn store(&self) {
+        let bytes = save::save(&self.config, Format::Text);
+        if let Err(err) = storage::write(self.config_file.path(), &bytes) {
+            warn!(
+                "could not save {}: {}",
+                self.config_file.path().display(),
+                err
+            );
+        }
+    }
+
+    /// Call once per frame. A file that fails to load is reported and the
+    /// previous values are kept, so a half-typed edit never breaks the game.
+    pub fn poll(&mut self) -> Reloaded {
+        let mut reloaded = Reloaded::default();
+        if self.config_file.changed() {
+            if let Some(config) = read::<Config>(&self.config_file) {
+                reloaded.config = config != self.config;
+                let theme = config.theme != self.config.theme;
+                self.config = config;
+                if theme {
+                    self.select_theme();
+                    reloaded.theme = true;
+                }
+            }
+        }
+        if self.theme_file.changed() {
+            if let Some(skin) = read(&self.theme_file) {
+                self.light = skin;
+                reloaded.theme |= !self.is_dark;
+            }
+        }
+        if self.dark_file.changed() {
+            if let Some(DarkSkin(skin)) = read(&self.dark_file) {
+                self.dark = skin;
+                reloaded.theme |= self.is_dark;
+            }
+        }
+        if self.custom_file.as_mut().is_some_and(Watched::changed) {
+            self.load_theme();
+            reloaded.theme = true;
+        }
+        if reloaded.theme {
+            self.pick_skin();
+        }
+        reloaded
+    }
+}
+
+/// Loads a watched file, with the defaults standing in for a missing one.
+/// `None` if the file is broken.
+fn read<T: Saved + Default>(file: &Watched) -> Option<T> {
+    let Ok(bytes) = fs::read(file.path()) else {
+        return Some(T::default());
+    };
+    match save::load(&bytes) {
+        Ok(value) => {
+            info!("loaded {}", file.path().display());
+            Some(value)
+        }
+        Err(err) => {
+            warn!("could not load {}: {}", file.path().display(), err);
+            None
+        }
+    }
+}
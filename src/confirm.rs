@@ -0,0 +1,69 @@
+//! Asks before a game in progress is thrown away by quitting or starting
+//! another one.
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::share::ShareCode;
+
+use crate::i18n::t;
+use crate::menu::Settings;
+use crate::scale;
+
+const WINDOW_SIZE: Vec2 = vec2(320.0, 110.0);
+
+/// What the player asked for that would end the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Leave {
+    /// Close the window.
+    Quit,
+    /// Start a new game from the settings window or with F2.
+    Start(Settings),
+    /// Play a shared seed.
+    Play(ShareCode),
+}
+
+/// The question, open until the player answers.
+pub struct Confirm {
+    leave: Leave,
+}
+
+impl Confirm {
+    pub fn new(leave: Leave) -> Self {
+        Self { leave }
+    }
+
+    /// Draws the question and returns the player's answer once they give
+    /// one: whether to leave the game after all.
+    pub fn update(&self) -> Option<bool> {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let mut answer = None;
+        widgets::Window::new(hash!(), position, size)
+            .label(&t!("confirm-title"))
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(
+                    None,
+                    &t!(match self.leave {
+                        Leave::Quit => "confirm-quit",
+                        Leave::Start(_) | Leave::Play(_) => "confirm-restart",
+                    }),
+                );
+                ui.separator();
+                if ui.button(None, t!("confirm-leave").as_str()) {
+                    answer = Some(true);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("confirm-stay").as_str()) {
+                    answer = Some(false);
+                }
+            });
+        if is_key_pressed(KeyCode::Escape) {
+            answer = Some(false);
+        }
+        answer
+    }
+
+    pub fn into_leave(self) -> Leave {
+        self.leave
+    }
+}
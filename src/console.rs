@@ -0,0 +1,256 @@
+//! The developer console, dropped down over the top of the window with
+//! the backtick key. Each line typed names a command from the registry,
+//! such as `newgame 30 16 99` or `reveal 4 7`; `help` lists them all and
+//! `clear` empties the scrollback. Up and Down go through earlier lines
+//! and Tab completes a command's name.
+//!
+//! Modules add their commands with a `register` function; each command
+//! runs with a `Context` holding what it may change. Commands that start
+//! a game only ask for it, and the game starts the way F2 starts one,
+//! asking first if a game is in progress.
+
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+use minesweeper_core::console::{self, Command, Registry};
+use minesweeper_core::share::ShareCode;
+
+use crate::appearance::System;
+use crate::classic::{self, Classic};
+use crate::config::Live;
+use crate::confirm::Leave;
+use crate::font;
+use crate::menu::Settings;
+use crate::scale;
+use crate::theme;
+
+const SCROLLBACK: usize = 200;
+const MAX_INPUT: usize = 200;
+const TEXT_SIZE: f32 = 20.0;
+/// Share of the window the console covers.
+const COVER: f32 = 0.4;
+const ERROR: Color = ORANGE;
+const ECHO: Color = GRAY;
+
+/// What a command may look at and change.
+pub struct Context<'a> {
+    pub classic: Option<&'a mut Classic>,
+    pub live: &'a mut Live,
+    pub system: &'a System,
+    /// The board in the settings window, for commands that start a game.
+    pub settings: Settings,
+    /// The game a command asked to start.
+    pub leave: Option<Leave>,
+}
+
+impl Context<'_> {
+    /// The classic game, for commands that play on it.
+    pub fn classic(&mut self) -> Result<&mut Classic, String> {
+        self.classic
+            .as_deref_mut()
+            .ok_or_else(|| "there is no classic game".to_owned())
+    }
+}
+
+/// Runs a command with its arguments and returns what to print.
+pub type Handler = fn(&mut Context, &[&str]) -> Result<String, String>;
+
+pub struct Console {
+    open: bool,
+    input: String,
+    /// Lines entered, oldest first.
+    entered: Vec<String>,
+    /// The entered line being shown by Up and Down, counting from the
+    /// oldest.
+    recalled: Option<usize>,
+    scrollback: VecDeque<(String, Color)>,
+    registry: Registry<Handler>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let mut registry = Registry::new();
+        register(&mut registry);
+        classic::register(&mut registry);
+        theme::register(&mut registry);
+        #[cfg(feature = "debug-tools")]
+        {
+            crate::debug::register(&mut registry);
+            crate::inspector::register(&mut registry);
+        }
+        Self {
+            open: false,
+            input: String::new(),
+            entered: Vec::new(),
+            recalled: None,
+            scrollback: VecDeque::new(),
+            registry,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handles the keyboard and returns a line once one is entered.
+    pub fn update(&mut self) -> Option<String> {
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            return None;
+        }
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && c != '`' && self.input.chars().count() < MAX_INPUT {
+                self.input.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.recall(self.recalled.unwrap_or(self.entered.len()).checked_sub(1));
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.recall(
+                self.recalled
+                    .map(|line| line + 1)
+                    .filter(|&line| line < self.entered.len()),
+            );
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            self.complete();
+        }
+        if !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+        self.recalled = None;
+        let line = std::mem::take(&mut self.input);
+        if line.trim().is_empty() {
+            return None;
+        }
+        if self.entered.last() != Some(&line) {
+            self.entered.push(line.clone());
+        }
+        Some(line)
+    }
+
+    /// Shows entered line `line`, or a blank input past the newest.
+    fn recall(&mut self, line: Option<usize>) {
+        match line {
+            Some(line) => {
+                self.input = self.entered[line].clone();
+                self.recalled = Some(line);
+            }
+            None if self.recalled.is_some_and(|line| line > 0) => {
+                self.input.clear();
+                self.recalled = None;
+            }
+            None => {}
+        }
+    }
+
+    fn complete(&mut self) {
+        if self.input.contains(' ') {
+            return;
+        }
+        match self.registry.complete(&self.input).as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            names => self.print(names.join("  "), ECHO),
+        }
+    }
+
+    /// Runs a line returned by `update`.
+    pub fn run(&mut self, context: &mut Context, line: &str) {
+        self.print(format!("> {}", line), ECHO);
+        match line.trim() {
+            "help" => {
+                for line in self.registry.help() {
+                    self.print(line, WHITE);
+                }
+                self.print("clear  Empties the console.".to_owned(), WHITE);
+            }
+            "clear" => self.scrollback.clear(),
+            line => {
+                let result = match self.registry.parse(line) {
+                    Ok(Some(call)) => (call.command.run)(context, &call.args),
+                    Ok(None) => Ok(String::new()),
+                    Err(err) => Err(err.to_string()),
+                };
+                match result {
+                    Ok(text) if text.is_empty() => {}
+                    Ok(text) => self.print(text, WHITE),
+                    Err(text) => self.print(text, ERROR),
+                }
+            }
+        }
+    }
+
+    fn print(&mut self, text: String, color: Color) {
+        if self.scrollback.len() == SCROLLBACK {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back((text, color));
+    }
+
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        let height = (screen_height() * COVER).round();
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        let line_height = scale::px(TEXT_SIZE + 2.0);
+        let mut y = height - line_height - 4.0;
+        font::line(&format!("> {}_", self.input), 8.0, y, TEXT_SIZE, YELLOW);
+        for (text, color) in self.scrollback.iter().rev() {
+            y -= line_height;
+            if y < 0.0 {
+                break;
+            }
+            font::line(text, 8.0, y, TEXT_SIZE, *color);
+        }
+    }
+}
+
+/// The commands that start games.
+fn register(registry: &mut Registry<Handler>) {
+    registry.register(Command {
+        name: "seed",
+        usage: "<seed>",
+        about: "Starts the board in the settings window from a seed, or plays a share code.",
+        run: seed,
+    });
+    registry.register(Command {
+        name: "newgame",
+        usage: "[width] [height] [mines]",
+        about: "Starts a new game, on the board in the settings window unless given.",
+        run: new_game,
+    });
+}
+
+fn seed(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let code = match args[0].parse::<u64>() {
+        Ok(seed) => context.settings.code(seed),
+        Err(_) => args[0]
+            .parse::<ShareCode>()
+            .map_err(|_| format!("`{}` is neither a seed nor a share code", args[0]))?,
+    };
+    context.leave = Some(Leave::Play(code));
+    Ok(String::new())
+}
+
+fn new_game(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let mut settings = context.settings;
+    settings.width = console::arg(args, 0, "width")?.unwrap_or(settings.width);
+    settings.height = console::arg(args, 1, "height")?.unwrap_or(settings.height);
+    settings.mines = console::arg(args, 2, "mines")?.unwrap_or(settings.mines);
+    context.leave = Some(Leave::Start(settings));
+    Ok(String::new())
+}
@@ -0,0 +1,174 @@
+//! The optional retro CRT look, `crt true` in `config.txt`: each frame is
+//! drawn into a render target, which is then drawn to the window through
+//! a shader adding scanlines, a slight barrel curvature and a vignette.
+//!
+//! The curvature moves what is shown under the pointer, so code that maps
+//! the pointer onto the board reads it through `mouse_position` here. The
+//! settings windows are drawn after the frame and keep their flat look.
+
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+use minesweeper_core::warn;
+
+/// How far the corners bulge; must match `CURVE` in the shader.
+const CURVE: f32 = 0.08;
+
+const VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+"#;
+
+const FRAGMENT: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+uniform vec2 Resolution;
+
+const float CURVE = 0.08;
+
+void main() {
+    vec2 centre = uv - 0.5;
+    vec2 warped = uv + centre * dot(centre, centre) * CURVE * 4.0;
+    if (warped.x < 0.0 || warped.x > 1.0 || warped.y < 0.0 || warped.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    vec3 rgb = texture2D(Texture, warped).rgb;
+    // One dark line every other pixel row
+    float scanline = 0.8 + 0.2 * sin(warped.y * Resolution.y * 3.14159);
+    float vignette = pow(16.0 * warped.x * warped.y * (1.0 - warped.x) * (1.0 - warped.y), 0.2);
+    gl_FragColor = vec4(rgb * scanline * vignette, 1.0) * color;
+}
+"#;
+
+struct Crt {
+    enabled: bool,
+    /// Loaded the first time the look is turned on; `None` after it failed
+    /// to compile, which turns the look off.
+    material: Option<Material>,
+    target: Option<RenderTarget>,
+}
+
+thread_local! {
+    static CRT: RefCell<Crt> = const {
+        RefCell::new(Crt {
+            enabled: false,
+            material: None,
+            target: None,
+        })
+    };
+}
+
+/// Turns the look on or off, from the next frame.
+pub fn set(enabled: bool) {
+    CRT.with_borrow_mut(|crt| {
+        crt.enabled = enabled;
+        if enabled && crt.material.is_none() {
+            let params = MaterialParams {
+                uniforms: vec![UniformDesc::new("Resolution", UniformType::Float2)],
+                ..Default::default()
+            };
+            let source = ShaderSource::Glsl {
+                vertex: VERTEX,
+                fragment: FRAGMENT,
+            };
+            match load_material(source, params) {
+                Ok(material) => crt.material = Some(material),
+                Err(err) => {
+                    warn!("could not load the CRT shader: {}", err);
+                    crt.enabled = false;
+                }
+            }
+        }
+        if !crt.enabled {
+            crt.target = None;
+        }
+    });
+}
+
+/// Starts a frame: with the look on, everything drawn until `finish` goes
+/// into the render target.
+pub fn begin() {
+    CRT.with_borrow_mut(|crt| {
+        if !crt.enabled {
+            return;
+        }
+        let size = (screen_width() as u32, screen_height() as u32);
+        let target = match crt.target.take() {
+            Some(target)
+                if (
+                    target.texture.width() as u32,
+                    target.texture.height() as u32,
+                ) == size =>
+            {
+                target
+            }
+            _ => {
+                let target = render_target(size.0.max(1), size.1.max(1));
+                target.texture.set_filter(FilterMode::Linear);
+                target
+            }
+        };
+        let mut camera =
+            Camera2D::from_display_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()));
+        camera.render_target = Some(target.clone());
+        set_camera(&camera);
+        crt.target = Some(target);
+    });
+}
+
+/// Draws the frame to the window through the shader.
+pub fn finish() {
+    CRT.with_borrow(|crt| {
+        let (Some(target), Some(material)) = (&crt.target, &crt.material) else {
+            return;
+        };
+        set_default_camera();
+        material.set_uniform("Resolution", (screen_width(), screen_height()));
+        gl_use_material(material);
+        draw_texture_ex(
+            &target.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(screen_width(), screen_height())),
+                // Render targets come out upside down
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    });
+}
+
+/// The pointer in frame coordinates: where the content shown under it was
+/// drawn, which the curvature moves away from the window position.
+pub fn mouse_position() -> (f32, f32) {
+    let (x, y) = macroquad::input::mouse_position();
+    if !CRT.with_borrow(|crt| crt.enabled) {
+        return (x, y);
+    }
+    let size = vec2(screen_width(), screen_height());
+    let uv = vec2(x, y) / size;
+    let centre = uv - 0.5;
+    let warped = uv + centre * centre.dot(centre) * CURVE * 4.0;
+    (warped.x * size.x, warped.y * size.y)
+}
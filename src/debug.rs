@@ -0,0 +1,157 @@
+//! Cheats for developers, built only with the `debug-tools` feature:
+//! X-ray to see the mines, moving a mine by clicking it and then where it
+//! should go, and winning or losing on the spot to reach the end of a
+//! game without playing it. Each is a console command, and any game they
+//! touch counts as hinted.
+
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+use minesweeper_core::console::{Command, Registry};
+use minesweeper_core::{CellState, CellType};
+
+use crate::classic::Classic;
+use crate::console::{Context, Handler};
+use crate::layout::Layout;
+use crate::mouse::Button;
+
+#[derive(Default)]
+struct Tools {
+    xray: bool,
+    teleport: bool,
+    /// The mine clicked first while teleporting, waiting for where it
+    /// goes.
+    picked: Option<(usize, usize)>,
+}
+
+thread_local! {
+    static TOOLS: RefCell<Tools> = RefCell::default();
+}
+
+pub fn register(registry: &mut Registry<Handler>) {
+    registry.register(Command {
+        name: "xray",
+        usage: "",
+        about: "Shows or hides the mines under hidden cells.",
+        run: |_, _| {
+            let on = TOOLS.with_borrow_mut(|tools| {
+                tools.xray = !tools.xray;
+                tools.xray
+            });
+            Ok(format!("x-ray {}", if on { "on" } else { "off" }))
+        },
+    });
+    registry.register(Command {
+        name: "teleport",
+        usage: "",
+        about: "Turns on or off moving mines: click a mine, then where it goes.",
+        run: |_, _| {
+            let on = TOOLS.with_borrow_mut(|tools| {
+                tools.teleport = !tools.teleport;
+                tools.picked = None;
+                tools.teleport
+            });
+            Ok(format!("teleport {}", if on { "on" } else { "off" }))
+        },
+    });
+    registry.register(Command {
+        name: "win",
+        usage: "",
+        about: "Reveals every safe cell.",
+        run: win,
+    });
+    registry.register(Command {
+        name: "lose",
+        usage: "",
+        about: "Reveals a mine.",
+        run: lose,
+    });
+}
+
+/// The cells not yet revealed that are mines, or that aren't.
+fn unrevealed(classic: &Classic, mines: bool) -> Vec<(usize, usize)> {
+    let board = classic.board();
+    (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+        .filter(|&(row, col)| {
+            let cell = board.cell(row, col);
+            cell.cell_state != CellState::Visible && (cell.cell_type == CellType::Mine) == mines
+        })
+        .collect()
+}
+
+fn win(context: &mut Context, _: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    classic.mark_assisted();
+    for (row, col) in unrevealed(classic, false) {
+        if classic.is_over() {
+            break;
+        }
+        // Flags would keep the cells from being revealed
+        if classic.board().cell(row, col).cell_state == CellState::Flagged {
+            classic.flag(row, col);
+        }
+        if classic.board().cell(row, col).cell_state == CellState::Hidden {
+            classic.click(row, col);
+        }
+    }
+    Ok(String::new())
+}
+
+fn lose(context: &mut Context, _: &[&str]) -> Result<String, String> {
+    let classic = context.classic()?;
+    let (row, col) = *unrevealed(classic, true)
+        .first()
+        .ok_or("every mine is revealed")?;
+    classic.mark_assisted();
+    if classic.board().cell(row, col).cell_state == CellState::Flagged {
+        classic.flag(row, col);
+    }
+    classic.click(row, col);
+    Ok(String::new())
+}
+
+/// Whether teleporting takes the clicks on the board, so the game doesn't
+/// play them. A press of `button` on `(row, col)` picks up the mine there,
+/// or drops the one picked up.
+pub fn intercept(classic: &mut Classic, (row, col): (usize, usize), button: Button) -> bool {
+    TOOLS.with_borrow_mut(|tools| {
+        if !tools.teleport || !button.is_pressed() {
+            return tools.teleport;
+        }
+        let cell = classic.board().cell(row, col);
+        match tools.picked.take() {
+            None if cell.cell_type == CellType::Mine && cell.cell_state != CellState::Visible => {
+                tools.picked = Some((row, col))
+            }
+            Some(from) => {
+                classic.move_mine(from, (row, col));
+            }
+            None => {}
+        }
+        true
+    })
+}
+
+/// Marks the mines under hidden cells with X-ray on, and the mine picked
+/// up while teleporting.
+pub fn draw(classic: &Classic, layout: &Layout) {
+    TOOLS.with_borrow(|tools| {
+        if tools.xray {
+            for (row, col) in unrevealed(classic, true) {
+                let tile = layout.tile(row, col);
+                let centre = tile.center();
+                draw_circle(
+                    centre.x,
+                    centre.y,
+                    tile.w / 4.0,
+                    Color::new(1.0, 0.0, 0.0, 0.6),
+                );
+            }
+        }
+        if let Some((row, col)) = tools.picked {
+            let tile = layout.tile(row, col);
+            draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 3.0, MAGENTA);
+        }
+    });
+}
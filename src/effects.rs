@@ -0,0 +1,336 @@
+//! What plays over the board when a game ends. A win is celebrated with
+//! confetti, a fanfare, the final time flashing, and the mines left hidden
+//! flipping to flags one by one. A loss uncovers the other mines in a
+//! ripple spreading from the one that went off. Both start on the game's
+//! events. With motion reduced there is no ripple or confetti, and the
+//! flags and the time appear at once.
+//!
+//! The fanfare is a WAV file written to the data directory and passed to
+//! the `sound` command from `config.txt`, e.g. `aplay -q`, `paplay` or
+//! `afplay`, as its last argument; without one the win is silent.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use minesweeper_core::{sound, warn, Cell, CellState, CellType, Event};
+
+use crate::classic::Classic;
+use crate::font;
+use crate::i18n::t;
+use crate::layout::Layout;
+use crate::storage;
+
+/// How long the celebration lasts.
+const LENGTH: Duration = Duration::from_secs(4);
+/// How long the final time flashes before it stays lit.
+const FLASHING: Duration = Duration::from_millis(2400);
+/// Time between two mines flipping, shortened on boards with so many
+/// mines that flipping them all would take longer than `FLIPPING`.
+const FLIP_EVERY: Duration = Duration::from_millis(60);
+const FLIPPING: Duration = Duration::from_secs(2);
+const CONFETTI: usize = 150;
+/// Pixels per second squared.
+const GRAVITY: f32 = 400.0;
+const COLORS: [Color; 6] = [RED, ORANGE, YELLOW, GREEN, SKYBLUE, VIOLET];
+/// How long the ripple takes to reach the mine farthest from the one that
+/// went off.
+const RIPPLE: Duration = Duration::from_millis(1500);
+
+/// What the listener has heard from the game.
+#[derive(Default)]
+struct Heard {
+    won: bool,
+    hit: Option<(usize, usize)>,
+    /// The flags on the board, so mines can be shown as they were before
+    /// the loss uncovered them.
+    flags: HashSet<(usize, usize)>,
+}
+
+impl Heard {
+    fn record(&mut self, event: &Event) {
+        match *event {
+            Event::GameWon => self.won = true,
+            Event::MineHit { row, col } => self.hit = Some((row, col)),
+            Event::Flagged { row, col, flagged } => {
+                if flagged {
+                    self.flags.insert((row, col));
+                } else {
+                    self.flags.remove(&(row, col));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Piece {
+    position: Vec2,
+    velocity: Vec2,
+    color: Color,
+    angle: f32,
+    spin: f32,
+}
+
+impl Piece {
+    /// A piece thrown up from somewhere along the bottom of the window.
+    fn thrown() -> Self {
+        Self {
+            position: vec2(gen_range(0.0, screen_width()), screen_height()),
+            velocity: vec2(gen_range(-150.0, 150.0), -gen_range(350.0, 700.0)),
+            color: COLORS[gen_range(0, COLORS.len())],
+            angle: gen_range(0.0, std::f32::consts::TAU),
+            spin: gen_range(-8.0, 8.0),
+        }
+    }
+}
+
+struct Celebration {
+    since: Instant,
+    confetti: Vec<Piece>,
+    /// The mines still hidden when the game was won, in reading order.
+    mines: Vec<(usize, usize)>,
+    time: Duration,
+}
+
+struct Ripple {
+    since: Instant,
+    /// Each mine uncovered by the loss, whether it was flagged, and when
+    /// the ripple reaches it.
+    mines: Vec<((usize, usize), bool, Duration)>,
+}
+
+impl Ripple {
+    fn new(classic: &Classic, hit: (usize, usize), flags: &HashSet<(usize, usize)>) -> Self {
+        let board = classic.board();
+        let distance = |(row, col): (usize, usize)| {
+            (row.abs_diff(hit.0) as f32).hypot(col.abs_diff(hit.1) as f32)
+        };
+        let mines: Vec<_> = (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .filter(|&pos| pos != hit && board.cell(pos.0, pos.1).cell_type == CellType::Mine)
+            .collect();
+        let farthest = mines.iter().map(|&pos| distance(pos)).fold(1.0, f32::max);
+        Self {
+            since: Instant::now(),
+            mines: mines
+                .into_iter()
+                .map(|pos| {
+                    (
+                        pos,
+                        flags.contains(&pos),
+                        RIPPLE.mul_f32(distance(pos) / farthest),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+pub struct Effects {
+    enabled: bool,
+    reduced_motion: bool,
+    sound: Option<Vec<String>>,
+    /// The last fanfare's player, reaped before the next one starts.
+    playing: Option<Child>,
+    heard: Rc<RefCell<Heard>>,
+    celebration: Option<Celebration>,
+    ripple: Option<Ripple>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            reduced_motion: false,
+            sound: None,
+            playing: None,
+            heard: Rc::default(),
+            celebration: None,
+            ripple: None,
+        }
+    }
+
+    /// Turns the celebration on or off, the loss ripple off with reduced
+    /// motion, and sets the command that plays the fanfare; an empty
+    /// command means no sound.
+    pub fn configure(&mut self, enabled: bool, reduced_motion: bool, sound: &str) {
+        self.enabled = enabled;
+        self.reduced_motion = reduced_motion;
+        let words: Vec<String> = sound.split_whitespace().map(str::to_owned).collect();
+        self.sound = (!words.is_empty()).then_some(words);
+    }
+
+    /// Listens to a new game, ending anything still playing for the last
+    /// one.
+    pub fn attach(&mut self, classic: &mut Classic) {
+        self.celebration = None;
+        self.ripple = None;
+        let board = classic.board();
+        let flags = (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .filter(|&(row, col)| board.cell(row, col).cell_state == CellState::Flagged)
+            .collect();
+        self.heard = Rc::new(RefCell::new(Heard {
+            flags,
+            ..Heard::default()
+        }));
+        let heard = Rc::clone(&self.heard);
+        classic.subscribe(Box::new(move |event: &Event| {
+            heard.borrow_mut().record(event)
+        }));
+    }
+
+    /// Starts an effect when the game ends, and moves it on.
+    pub fn update(&mut self, classic: &Classic) {
+        let mut heard = self.heard.borrow_mut();
+        let won = std::mem::take(&mut heard.won);
+        if let Some(hit) = heard.hit.take() {
+            if !self.reduced_motion {
+                self.ripple = Some(Ripple::new(classic, hit, &heard.flags));
+            }
+        }
+        drop(heard);
+        if won && self.enabled && !classic.is_tutorial() {
+            self.start(classic);
+        }
+        self.ripple
+            .take_if(|ripple| ripple.since.elapsed() >= RIPPLE);
+        let Some(celebration) = &mut self.celebration else {
+            return;
+        };
+        if celebration.since.elapsed() >= LENGTH {
+            self.celebration = None;
+            return;
+        }
+        let dt = get_frame_time();
+        for piece in &mut celebration.confetti {
+            piece.velocity.y += GRAVITY * dt;
+            piece.position += piece.velocity * dt;
+            piece.angle += piece.spin * dt;
+        }
+    }
+
+    fn start(&mut self, classic: &Classic) {
+        let board = classic.board();
+        let mines = (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                let cell = board.cell(row, col);
+                cell.cell_type == CellType::Mine && cell.cell_state == CellState::Hidden
+            })
+            .collect();
+        self.celebration = Some(Celebration {
+            since: Instant::now(),
+            confetti: if self.reduced_motion {
+                Vec::new()
+            } else {
+                (0..CONFETTI).map(|_| Piece::thrown()).collect()
+            },
+            mines,
+            time: classic.elapsed(),
+        });
+        self.play_fanfare();
+    }
+
+    fn play_fanfare(&mut self) {
+        let Some(words) = &self.sound else {
+            return;
+        };
+        let path = storage::data_dir().join("fanfare.wav");
+        if !path.exists() {
+            if let Err(err) = storage::write(&path, &sound::fanfare()) {
+                warn!("could not write {}: {}", path.display(), err);
+                return;
+            }
+        }
+        if let Some(mut child) = self.playing.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        match Command::new(&words[0])
+            .args(&words[1..])
+            .arg(&path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => self.playing = Some(child),
+            Err(err) => {
+                warn!("could not run `{}`: {}", words.join(" "), err);
+                self.sound = None;
+            }
+        }
+    }
+
+    /// Whether something is moving, so frames have to keep coming.
+    pub fn is_active(&self) -> bool {
+        self.celebration.is_some() || self.ripple.is_some()
+    }
+
+    /// Draws whatever is playing over the board.
+    pub fn draw(&self, layout: &Layout) {
+        if let Some(ripple) = &self.ripple {
+            let elapsed = ripple.since.elapsed();
+            for &((row, col), flagged, reached) in &ripple.mines {
+                if reached > elapsed {
+                    let state = if flagged {
+                        CellState::Flagged
+                    } else {
+                        CellState::Hidden
+                    };
+                    layout.draw_cell(&Cell::new(CellType::Mine, state), row, col);
+                }
+            }
+        }
+        let Some(celebration) = &self.celebration else {
+            return;
+        };
+        let elapsed = celebration.since.elapsed();
+
+        let every = FLIP_EVERY.min(FLIPPING / celebration.mines.len().max(1) as u32);
+        let flipped = if self.reduced_motion {
+            celebration.mines.len()
+        } else {
+            (elapsed.as_secs_f32() / every.as_secs_f32()) as usize + 1
+        };
+        let flag = Cell::new(CellType::Mine, CellState::Flagged);
+        for &(row, col) in celebration.mines.iter().take(flipped) {
+            layout.draw_cell(&flag, row, col);
+        }
+
+        for piece in &celebration.confetti {
+            let (sin, cos) = piece.angle.sin_cos();
+            let half = vec2(cos, sin) * 5.0;
+            draw_line(
+                piece.position.x - half.x,
+                piece.position.y - half.y,
+                piece.position.x + half.x,
+                piece.position.y + half.y,
+                4.0,
+                piece.color,
+            );
+        }
+
+        let lit =
+            self.reduced_motion || elapsed >= FLASHING || (elapsed.as_millis() / 200) % 2 == 0;
+        if lit {
+            let seconds = format!("{:.2}", celebration.time.as_secs_f32());
+            let text = t!("time", seconds = seconds);
+            let banner = Rect::new(0.0, screen_height() / 2.0 - 40.0, screen_width(), 80.0);
+            draw_rectangle(
+                banner.x,
+                banner.y,
+                banner.w,
+                banner.h,
+                Color::new(0.0, 0.0, 0.0, 0.5),
+            );
+            font::centred(&text, banner, 60.0, GOLD);
+        }
+    }
+}
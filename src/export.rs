@@ -0,0 +1,85 @@
+//! Files made from games: GIFs, video frames and replays of finished games
+//! and the history of every game in `exports`, and screenshots in
+//! `screenshots`, both in the data directory.
+
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::texture::Image;
+use minesweeper_core::export::{self, ReplayOptions, Screenshot, VideoFormat};
+use minesweeper_core::history::{self, Format as HistoryFormat};
+use minesweeper_core::info;
+use minesweeper_core::replay::Replay;
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::stats::Record;
+
+use crate::storage;
+
+/// A new name in `dir` in the data directory, unique to the second.
+/// `extension` starts with its dot, or is empty for a directory.
+fn path(dir: &str, extension: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    storage::data_dir()
+        .join(dir)
+        .join(format!("minesweeper-{}{}", now, extension))
+}
+
+/// Writes the game as an animated GIF and returns where it went.
+pub fn gif(replay: &Replay, options: ReplayOptions) -> io::Result<PathBuf> {
+    let path = path("exports", ".gif");
+    storage::write(&path, &export::gif(replay, options))?;
+    info!("exported the game to {}", path.display());
+    Ok(path)
+}
+
+/// Saves the game as a replay that `verify-replay` checks, and returns
+/// where it went.
+pub fn replay(replay: &Replay) -> io::Result<PathBuf> {
+    let path = path("exports", ".replay");
+    storage::write(&path, &save::save(replay, Format::Text))?;
+    info!("saved the replay to {}", path.display());
+    Ok(path)
+}
+
+/// Writes every recorded game as a table, see `history`, and returns
+/// where it went.
+pub fn history(records: &[Record], format: HistoryFormat) -> io::Result<PathBuf> {
+    let path = path("exports", &format!(".{}", format.extension()));
+    storage::write(&path, history::export(records, format).as_bytes())?;
+    info!("exported {} games to {}", records.len(), path.display());
+    Ok(path)
+}
+
+/// Writes the game's frames and their manifest for ffmpeg into a new
+/// directory and returns it.
+pub fn video(replay: &Replay, options: ReplayOptions, format: VideoFormat) -> io::Result<PathBuf> {
+    let dir = path("exports", "");
+    for (name, bytes) in export::video(replay, options, format) {
+        storage::write(&dir.join(name), &bytes)?;
+    }
+    info!("exported the game's frames to {}", dir.display());
+    Ok(dir)
+}
+
+/// Writes `image`, a grab of the window, as a PNG that records the game
+/// it shows, and returns where it went.
+pub fn screenshot(screenshot: &Screenshot, image: &Image) -> io::Result<PathBuf> {
+    // The window is read bottom row first
+    let stride = image.width as usize * 4;
+    let rgba: Vec<u8> = image
+        .bytes
+        .chunks(stride)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    let path = path("screenshots", ".png");
+    let png = screenshot.png(image.width.into(), image.height.into(), &rgba);
+    storage::write(&path, &png)?;
+    info!("saved a screenshot to {}", path.display());
+    Ok(path)
+}
@@ -0,0 +1,38 @@
+//! Notices the window losing focus, e.g. to alt-tab or a screen lock, so
+//! a game can pause itself.
+
+use macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use macroquad::miniquad::EventHandler;
+
+pub struct Focus {
+    subscriber: usize,
+    lost: bool,
+}
+
+impl Focus {
+    pub fn new() -> Self {
+        Self {
+            subscriber: register_input_subscriber(),
+            lost: false,
+        }
+    }
+
+    /// Whether the window lost focus since the last call. Call once per
+    /// frame, as the window events pile up until then.
+    pub fn lost(&mut self) -> bool {
+        repeat_all_miniquad_input(self, self.subscriber);
+        std::mem::take(&mut self.lost)
+    }
+}
+
+impl EventHandler for Focus {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    // The window library reports focus changes as the window being
+    // minimized and restored
+    fn window_minimized_event(&mut self) {
+        self.lost = true;
+    }
+}
@@ -0,0 +1,123 @@
+//! The font numbers and HUD text are drawn in: a TrueType file named by
+//! `font` in `config.txt`, or the built-in one.
+//!
+//! Text is placed by the font's own metrics instead of pixel offsets
+//! tuned to the built-in font, so a replacement with taller or shorter
+//! glyphs still sits where it should.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use minesweeper_core::info;
+
+use crate::scale;
+use crate::storage;
+
+/// Sizes drawn on every frame, whose digits are rasterised up front so
+/// the first frames don't stall on them.
+const WARM_SIZES: [u16; 3] = [20, 24, 30];
+
+thread_local! {
+    /// The file loaded, so an unchanged setting doesn't load it again, and
+    /// the font from it.
+    static CURRENT: RefCell<(String, Option<Font>)> = const { RefCell::new((String::new(), None)) };
+}
+
+/// Switches to the font at `path`, relative to the data directory unless
+/// absolute, or back to the built-in one for an empty path. On failure
+/// the built-in font is used and the error returned.
+pub fn load(path: &str) -> Result<(), String> {
+    if CURRENT.with_borrow(|(current, _)| current == path) {
+        return Ok(());
+    }
+    let font = if path.is_empty() {
+        Ok(None)
+    } else {
+        let file = resolve(path);
+        fs::read(&file)
+            .map_err(|err| format!("could not read {}: {}", file.display(), err))
+            .and_then(|bytes| {
+                load_ttf_font_from_bytes(&bytes)
+                    .map_err(|err| format!("could not load {}: {}", file.display(), err))
+            })
+            .map(|font| {
+                let digits: Vec<char> = "0123456789?".chars().collect();
+                for size in WARM_SIZES {
+                    font.populate_font_cache(&digits, scale::px(size.into()) as u16);
+                }
+                info!("loaded the font {}", file.display());
+                Some(font)
+            })
+    };
+    CURRENT.set((path.to_owned(), font.clone().unwrap_or_default()));
+    font.map(|_| ())
+}
+
+fn resolve(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_owned()
+    } else {
+        storage::data_dir().join(path)
+    }
+}
+
+fn with_font<T>(f: impl FnOnce(Option<&Font>) -> T) -> T {
+    CURRENT.with_borrow(|(_, font)| f(font.as_ref()))
+}
+
+/// Draws `text` with its baseline at `y`, like `draw_text`, at `size`
+/// as given; the public functions below scale their sizes by the UI
+/// scale, except `on_tile`.
+fn draw_at(text: &str, x: f32, y: f32, size: f32, color: Color) {
+    with_font(|font| {
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font,
+                font_size: size as u16,
+                color,
+                ..Default::default()
+            },
+        )
+    });
+}
+
+pub fn measure(text: &str, size: f32) -> TextDimensions {
+    measure_at(text, scale::px(size))
+}
+
+fn measure_at(text: &str, size: f32) -> TextDimensions {
+    with_font(|font| measure_text(text, font, size as u16, 1.0))
+}
+
+/// Draws a line of text whose top is at `top`, and returns the box from
+/// there down to the baseline.
+pub fn line(text: &str, x: f32, top: f32, size: f32, color: Color) -> Rect {
+    let size = scale::px(size);
+    let ascent = measure_at("Ay", size).offset_y;
+    draw_at(text, x, top + ascent, size, color);
+    Rect::new(x, top, measure_at(text, size).width, ascent)
+}
+
+/// Draws `text` centred in `area`.
+pub fn centred(text: &str, area: Rect, size: f32, color: Color) {
+    on_tile(text, area, scale::px(size), color);
+}
+
+/// Draws `text` centred in a tile, at a size that follows the tile rather
+/// than the UI scale.
+pub fn on_tile(text: &str, area: Rect, size: f32, color: Color) {
+    let dimensions = measure_at(text, size);
+    draw_at(
+        text,
+        area.x + (area.w - dimensions.width) / 2.0,
+        area.y + (area.h - dimensions.height) / 2.0 + dimensions.offset_y,
+        size,
+        color,
+    );
+}
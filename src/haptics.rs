@@ -0,0 +1,110 @@
+//! Short vibrations on touch devices: a tick when a long press is
+//! recognised, a tap when a flag goes down, and a long buzz when a mine
+//! goes off. Nothing vibrates until the screen has been touched, so
+//! desktops with a mouse never do.
+//!
+//! miniquad has no vibration API, so, like the speech and the fanfare,
+//! pulses go to the `vibrate` command from `config.txt` with their length
+//! in milliseconds as its last argument, e.g. `termux-vibrate -f -d`.
+//! `haptic_strength` shortens every pulse, since most motors only know on
+//! and off.
+
+use std::cell::RefCell;
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+use std::time::Duration;
+
+use minesweeper_core::{warn, Event};
+
+use crate::classic::Classic;
+
+const LONG_PRESS: Duration = Duration::from_millis(15);
+const FLAG: Duration = Duration::from_millis(30);
+const DETONATION: Duration = Duration::from_millis(300);
+
+pub struct Haptics {
+    enabled: bool,
+    strength: f32,
+    command: Option<Vec<String>>,
+    /// The last pulse's command, reaped before the next one starts.
+    vibrating: Option<Child>,
+    /// The longest pulse asked for since the last frame; a long press that
+    /// places a flag buzzes once, not twice.
+    pending: Rc<RefCell<Option<Duration>>>,
+}
+
+impl Haptics {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            strength: 1.0,
+            command: None,
+            vibrating: None,
+            pending: Rc::default(),
+        }
+    }
+
+    /// Turns vibration on or off, sets how strong it is from 0 to 1, and
+    /// the command that vibrates; an empty command turns it off.
+    pub fn configure(&mut self, enabled: bool, strength: f32, vibrate: &str) {
+        self.enabled = enabled;
+        self.strength = strength;
+        let words: Vec<String> = vibrate.split_whitespace().map(str::to_owned).collect();
+        self.command = (!words.is_empty()).then_some(words);
+    }
+
+    /// Listens to a new game for flags and mines.
+    pub fn attach(&mut self, classic: &mut Classic) {
+        let pending = Rc::clone(&self.pending);
+        classic.subscribe(Box::new(move |event: &Event| {
+            let pulse = match *event {
+                Event::Flagged { flagged: true, .. } => FLAG,
+                Event::MineHit { .. } | Event::LifeLost { .. } | Event::Blast { .. } => DETONATION,
+                Event::DudHit { .. } => FLAG,
+                _ => return,
+            };
+            ask(&pending, pulse);
+        }));
+    }
+
+    pub fn long_press(&mut self) {
+        ask(&self.pending, LONG_PRESS);
+    }
+
+    /// Sends the pulse asked for since the last frame, on touch devices.
+    pub fn update(&mut self, touch_device: bool) {
+        let Some(pulse) = self.pending.borrow_mut().take() else {
+            return;
+        };
+        if !self.enabled || !touch_device {
+            return;
+        }
+        let Some(words) = &self.command else {
+            return;
+        };
+        let millis = (pulse.as_millis() as f32 * self.strength).round().max(1.0);
+        if let Some(mut child) = self.vibrating.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        match Command::new(&words[0])
+            .args(&words[1..])
+            .arg(millis.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => self.vibrating = Some(child),
+            Err(err) => {
+                warn!("could not run `{}`: {}", words.join(" "), err);
+                self.command = None;
+            }
+        }
+    }
+}
+
+fn ask(pending: &RefCell<Option<Duration>>, pulse: Duration) {
+    let mut pending = pending.borrow_mut();
+    *pending = Some(pending.map_or(pulse, |asked| asked.max(pulse)));
+}
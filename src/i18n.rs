@@ -0,0 +1,166 @@
+//! Translations of every string the game shows.
+//!
+//! Each language is a catalog in `locales/`, compiled into the binary. A
+//! catalog holds one `id = text` message per line, in the style of Fluent:
+//! `{ $name }` is replaced by an argument and `#` starts a comment. A
+//! message missing from a catalog falls back to English, and one missing
+//! from English shows its id.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use minesweeper_core::plugin::Message;
+use minesweeper_core::warn;
+
+/// Language code and catalog of every shipped language, English first.
+const SOURCES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+struct Catalog {
+    code: &'static str,
+    messages: HashMap<&'static str, &'static str>,
+}
+
+static CATALOGS: OnceLock<Vec<Catalog>> = OnceLock::new();
+/// Index of the language in use.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+fn catalogs() -> &'static [Catalog] {
+    CATALOGS.get_or_init(|| {
+        SOURCES
+            .iter()
+            .map(|&(code, source)| Catalog {
+                code,
+                messages: parse(code, source),
+            })
+            .collect()
+    })
+}
+
+fn parse(code: &str, source: &'static str) -> HashMap<&'static str, &'static str> {
+    let mut messages = HashMap::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((id, text)) => {
+                messages.insert(id.trim(), text.trim());
+            }
+            None => warn!("{}.ftl line {}: expected `id = text`", code, i + 1),
+        }
+    }
+    messages
+}
+
+/// Code and native name of every shipped language.
+pub fn languages() -> Vec<(&'static str, String)> {
+    catalogs()
+        .iter()
+        .map(|catalog| {
+            let name = catalog.messages.get("language-name").copied();
+            (catalog.code, name.unwrap_or(catalog.code).to_owned())
+        })
+        .collect()
+}
+
+/// Switches to the language with `code`, e.g. `de`. Returns false if no
+/// catalog has it.
+pub fn set(code: &str) -> bool {
+    match catalogs().iter().position(|catalog| catalog.code == code) {
+        Some(i) => {
+            CURRENT.store(i, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn current() -> &'static str {
+    catalogs()[CURRENT.load(Ordering::Relaxed)].code
+}
+
+/// Picks the language at startup: `--lang`, then the config file, then
+/// the system locale, then English.
+pub fn init(flag: Option<&str>, config: &str) {
+    if let Some(code) = flag {
+        if !set(code) {
+            warn!("no translation for `{}`", code);
+        }
+        return;
+    }
+    if !config.is_empty() && set(config) {
+        return;
+    }
+    let system = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty());
+    // Locales look like `de_DE.UTF-8`
+    let code = system
+        .as_deref()
+        .and_then(|value| value.split(['_', '.', '-']).next());
+    if !code.is_some_and(set) {
+        set("en");
+    }
+}
+
+/// Looks `id` up in the current language and fills in `args`.
+pub fn tr(id: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let catalogs = catalogs();
+    let text = catalogs[CURRENT.load(Ordering::Relaxed)]
+        .messages
+        .get(id)
+        .or_else(|| catalogs[0].messages.get(id))
+        .copied()
+        .unwrap_or(id);
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = rest[1..end].trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Translates a message from a plugin.
+pub fn message(message: &Message) -> String {
+    match message {
+        Message::Plain(text) => text.clone(),
+        Message::Localized { id, args } => {
+            let args: Vec<(&str, &dyn fmt::Display)> = args
+                .iter()
+                .map(|(name, value)| (*name, value as &dyn fmt::Display))
+                .collect();
+            tr(id, &args)
+        }
+    }
+}
+
+/// `t!("id")` or `t!("id", name = value, ...)` translates a message.
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::tr($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr($id, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+])
+    };
+}
+pub(crate) use t;
@@ -0,0 +1,54 @@
+use std::thread;
+use std::time::Duration;
+
+use macroquad::prelude::*;
+
+/// Seconds without input before the frame rate drops.
+const IDLE_AFTER: f64 = 2.0;
+/// Frame time while idle, about four frames a second.
+const IDLE_FRAME: Duration = Duration::from_millis(250);
+
+/// Drops the frame rate while nobody is touching the game, so a forgotten
+/// window doesn't keep a laptop busy. Any input restores the full rate on the
+/// next frame.
+pub struct Idle {
+    last_input: f64,
+    last_mouse: (f32, f32),
+}
+
+impl Idle {
+    pub fn new() -> Self {
+        Self {
+            last_input: get_time(),
+            last_mouse: mouse_position(),
+        }
+    }
+
+    fn had_input(&mut self) -> bool {
+        let mouse = mouse_position();
+        let moved = mouse != self.last_mouse;
+        self.last_mouse = mouse;
+
+        // A click or key tap can start and end within one sleeping frame, so
+        // presses count as well as held buttons
+        let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+        moved
+            || mouse_wheel() != (0.0, 0.0)
+            || !get_keys_down().is_empty()
+            || !get_keys_pressed().is_empty()
+            || buttons
+                .into_iter()
+                .any(|button| is_mouse_button_down(button) || is_mouse_button_pressed(button))
+    }
+
+    /// Call once per frame. `animating` keeps the full frame rate while
+    /// something on screen moves on its own.
+    pub fn throttle(&mut self, animating: bool) {
+        let now = get_time();
+        if animating || self.had_input() {
+            self.last_input = now;
+        } else if now - self.last_input > IDLE_AFTER {
+            thread::sleep(IDLE_FRAME);
+        }
+    }
+}
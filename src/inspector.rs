@@ -0,0 +1,157 @@
+//! A developer overlay, built with the `debug-tools` feature and turned
+//! on and off with the `inspect` console command. Next to the pointer it
+//! lists what the game and the solver know about the cell under it: its
+//! type and state, its chance of a mine, whether the numbers prove it,
+//! and the constraints of the numbers around it, to make solver and
+//! generation bugs visible on the board they happen on.
+
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+use minesweeper_core::agent::View;
+use minesweeper_core::console::{Command, Registry};
+use minesweeper_core::solver::{Knowledge, Probabilities};
+use minesweeper_core::{Board, CellState, CellType};
+
+use crate::console::Handler;
+use crate::font;
+use crate::layout::Layout;
+use crate::scale;
+
+const TEXT_SIZE: f32 = 18.0;
+
+/// What the solver made of one position of the board, kept until the
+/// board changes since working it out can take a while.
+struct Solved {
+    revision: u64,
+    view: View,
+    knowledge: Knowledge,
+    chances: Probabilities,
+}
+
+#[derive(Default)]
+struct Inspector {
+    shown: bool,
+    solved: Option<Solved>,
+}
+
+thread_local! {
+    static INSPECTOR: RefCell<Inspector> = RefCell::default();
+}
+
+pub fn register(registry: &mut Registry<Handler>) {
+    registry.register(Command {
+        name: "inspect",
+        usage: "",
+        about: "Shows or hides the internals of the cell under the pointer.",
+        run: |_, _| {
+            let shown = INSPECTOR.with_borrow_mut(|inspector| {
+                inspector.shown = !inspector.shown;
+                inspector.solved = None;
+                inspector.shown
+            });
+            Ok(format!("inspector {}", if shown { "on" } else { "off" }))
+        },
+    });
+}
+
+/// Lists the internals of the cell at `mouse_pos`, if the inspector is on.
+pub fn draw(board: &Board, layout: &Layout, mouse_pos: (f32, f32)) {
+    INSPECTOR.with_borrow_mut(|inspector| {
+        if !inspector.shown {
+            return;
+        }
+        let Some((row, col)) = layout.cell_at(board, mouse_pos) else {
+            return;
+        };
+        let solved = match inspector.solved.take() {
+            Some(solved) if solved.revision == board.revision() => solved,
+            _ => {
+                let view = View::of(board);
+                Solved {
+                    revision: board.revision(),
+                    knowledge: Knowledge::of(&view),
+                    chances: Probabilities::of(&view),
+                    view,
+                }
+            }
+        };
+        let lines = describe(board, &solved, (row, col));
+        inspector.solved = Some(solved);
+        panel(&lines, mouse_pos);
+    });
+}
+
+fn describe(board: &Board, solved: &Solved, (row, col): (usize, usize)) -> Vec<String> {
+    let cell = board.cell(row, col);
+    let mut lines = vec![
+        format!("row {}, column {}", row + 1, col + 1),
+        format!(
+            "type: {}",
+            match cell.cell_type {
+                CellType::Empty => "empty".to_owned(),
+                CellType::Number(n) => format!("number {}", n),
+                CellType::Mine => "mine".to_owned(),
+            }
+        ),
+        format!("state: {:?}", cell.cell_state).to_lowercase(),
+    ];
+    if cell.cell_state == CellState::Visible {
+        return lines;
+    }
+    lines.push(match solved.chances.get(row, col) {
+        Some(chance) => {
+            let estimate = match (chance.exact, chance.margin) {
+                (true, _) => "exact".to_owned(),
+                (false, Some(margin)) => format!("sampled, ±{:.1}%", margin * 100.0),
+                (false, None) => "estimated".to_owned(),
+            };
+            format!("mine chance: {:.1}% ({})", chance.mine * 100.0, estimate)
+        }
+        None => "mine chance: unknown".to_owned(),
+    });
+    let knowledge = &solved.knowledge;
+    if knowledge.safe.contains(&(row, col)) {
+        lines.push("proven safe".to_owned());
+    } else if knowledge.mines.contains(&(row, col)) {
+        lines.push("proven mine".to_owned());
+    }
+    let memberships = knowledge.constraints_on(&solved.view, (row, col));
+    if memberships.is_empty() {
+        lines.push("in no constraint".to_owned());
+    }
+    for membership in memberships {
+        let (number_row, number_col) = membership.number;
+        lines.push(format!(
+            "number at {}, {}: {} of {} cells",
+            number_row + 1,
+            number_col + 1,
+            membership.mines,
+            membership.cells
+        ));
+    }
+    lines
+}
+
+/// Draws `lines` in a box beside the pointer, kept inside the window.
+fn panel(lines: &[String], (x, y): (f32, f32)) {
+    let line_height = scale::px(TEXT_SIZE + 2.0);
+    let width = lines
+        .iter()
+        .map(|line| font::measure(line, TEXT_SIZE).width)
+        .fold(0.0, f32::max)
+        + 16.0;
+    let height = line_height * lines.len() as f32 + 12.0;
+    let left = (x + 20.0).min(screen_width() - width).max(0.0);
+    let top = (y + 20.0).min(screen_height() - height).max(0.0);
+    draw_rectangle(left, top, width, height, Color::new(0.0, 0.0, 0.0, 0.85));
+    for (i, line) in lines.iter().enumerate() {
+        font::line(
+            line,
+            left + 8.0,
+            top + 6.0 + line_height * i as f32,
+            TEXT_SIZE,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use macroquad::prelude::*;
+use minesweeper_core::{Board, Cell, CellState, CellType, MineKind};
+
+use crate::atlas::{Atlas, Skin};
+use crate::batch::Batch;
+use crate::scale;
+
+pub const PLAYER_COLORS: [Color; 2] = [BLUE, RED];
+
+/// Space kept free under the board for the status line at 100% UI scale,
+/// unless the game asks for more.
+const STATUS_HEIGHT: f32 = 50.0;
+/// Space between the board and the top of the status line.
+pub const STATUS_MARGIN: f32 = 8.0;
+/// Boards that would need smaller tiles than this, at 100% UI scale, to
+/// fit the window scroll instead.
+const MIN_TILE: f32 = 16.0;
+/// Scroll speed for the arrow keys, in pixels per second.
+const SCROLL_SPEED: f32 = 900.0;
+/// Pixels scrolled per mouse wheel notch.
+const WHEEL_STEP: f32 = 60.0;
+/// Tint of cells a campaign level keeps locked.
+const LOCKED_TINT: Color = Color::new(0.55, 0.55, 0.7, 1.0);
+/// Tint of cells the fog of war hides.
+const FOG_TINT: Color = Color::new(0.3, 0.3, 0.35, 1.0);
+/// Tint of revealed numbers the blind rule doesn't show yet.
+const UNREAD_TINT: Color = Color::new(0.7, 0.8, 1.0, 1.0);
+/// Tint of uncovered duds, which only cost time.
+const DUD_TINT: Color = Color::new(0.6, 0.6, 0.6, 1.0);
+/// Tint of uncovered big mines, which blow open the cells around them.
+const BIG_TINT: Color = Color::new(1.0, 0.55, 0.45, 1.0);
+
+/// How the tiles are spaced and shaped, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    /// Between tiles.
+    pub gap: f32,
+    /// Kept free around the board.
+    pub padding: f32,
+    /// Rounds the tiles' corners; 0 keeps them square.
+    pub corner: f32,
+    /// Largest side a tile grows to on small boards, or 0 for no limit.
+    pub max_tile: f32,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            gap: 1.0,
+            padding: 2.0,
+            corner: 0.0,
+            max_tile: 0.0,
+        }
+    }
+}
+
+/// What the cached board image was drawn from.
+#[derive(Clone, Copy, PartialEq)]
+struct Frame {
+    revision: u64,
+    scroll: Vec2,
+    screen: Vec2,
+}
+
+/// Screen-space placement of the board's tiles. Boards smaller than the
+/// window are centred in it.
+pub struct Layout {
+    geometry: Geometry,
+    tile_width: f32,
+    /// Space kept free under the board.
+    status: f32,
+    /// Size of the whole board in pixels, padding included.
+    size: Vec2,
+    /// Board pixel shown at the top left of the window.
+    scroll: Vec2,
+    /// The visible tiles as last drawn, kept on the GPU so idle frames only
+    /// blit them.
+    cache: Option<(RenderTarget, Frame)>,
+    skin: Skin,
+    /// Tile faces painted with `skin`, built on the first draw.
+    atlas: Option<Atlas>,
+}
+
+impl Layout {
+    pub fn new(board: &Board, geometry: Geometry, skin: Skin) -> Self {
+        let mut layout = Self {
+            geometry,
+            tile_width: 0.0,
+            status: scale::px(STATUS_HEIGHT),
+            size: Vec2::ZERO,
+            scroll: Vec2::ZERO,
+            cache: None,
+            skin,
+            atlas: None,
+        };
+        layout.fit(board);
+        layout
+    }
+
+    /// Keeps `height` pixels free under the board instead of a single
+    /// status line, shrinking the tiles to make room.
+    pub fn with_status(mut self, board: &Board, height: f32) -> Self {
+        self.status = height;
+        self.fit(board);
+        self
+    }
+
+    fn fit(&mut self, board: &Board) {
+        let Geometry {
+            gap,
+            padding,
+            max_tile,
+            ..
+        } = self.geometry;
+        let cells = vec2(board.width() as f32, board.height() as f32);
+        let room = self.viewport() - 2.0 * padding + gap;
+        // Tall boards are limited by the window height instead of its width
+        let fit = room / cells - gap;
+        let fit = fit.x.min(fit.y);
+        let fit = if max_tile > 0.0 {
+            fit.min(max_tile)
+        } else {
+            fit
+        };
+        self.tile_width = fit.max(scale::px(MIN_TILE));
+        self.size = 2.0 * padding + cells * self.step() - gap;
+    }
+
+    /// Where board pixel 0 is when nothing is scrolled: the top left
+    /// corner of the window, or further in to centre a smaller board.
+    fn offset(&self) -> Vec2 {
+        ((self.viewport() - self.size) / 2.0).max(Vec2::ZERO)
+    }
+
+    pub fn skin(&self) -> &Skin {
+        &self.skin
+    }
+
+    /// Repaints the tiles with a different skin on the next draw.
+    pub fn set_skin(&mut self, skin: Skin) {
+        self.skin = skin;
+        self.atlas = None;
+        self.cache = None;
+    }
+
+    fn step(&self) -> f32 {
+        self.tile_width + self.geometry.gap
+    }
+
+    /// The part of the window the board is drawn in.
+    fn viewport(&self) -> Vec2 {
+        vec2(screen_width(), screen_height() - self.status)
+    }
+
+    /// Scrolls boards that don't fit the window with the mouse wheel (hold
+    /// Shift to scroll sideways) unless `wheel` is false because it marks
+    /// cells instead, and with the arrow keys unless `arrows` is false
+    /// because they move a keyboard cursor instead.
+    pub fn update(&mut self, arrows: bool, wheel: bool) {
+        let mut delta = Vec2::ZERO;
+        let speed = SCROLL_SPEED * get_frame_time();
+        if arrows {
+            if is_key_down(KeyCode::Left) {
+                delta.x -= speed;
+            }
+            if is_key_down(KeyCode::Right) {
+                delta.x += speed;
+            }
+            if is_key_down(KeyCode::Up) {
+                delta.y -= speed;
+            }
+            if is_key_down(KeyCode::Down) {
+                delta.y += speed;
+            }
+        }
+        let (_, scrolled) = mouse_wheel();
+        let wheel = -scrolled.signum() * WHEEL_STEP * f32::from(scrolled != 0.0 && wheel);
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            delta.x += wheel;
+        } else {
+            delta.y += wheel;
+        }
+
+        let max = (self.size - self.viewport()).max(Vec2::ZERO);
+        self.scroll = (self.scroll + delta).clamp(Vec2::ZERO, max);
+    }
+
+    /// Draws the board and returns how many draw calls that took. Only
+    /// tiles inside the window are drawn, and only when the board or the
+    /// scroll position changed since the last frame.
+    pub fn draw(&mut self, board: &Board) -> usize {
+        let viewport = self.viewport();
+        // Building the atlas switches cameras, so it can't happen while the
+        // board's render target is bound
+        if self.atlas.is_none() {
+            let corner = self.geometry.corner / self.tile_width;
+            self.atlas = Some(Atlas::new(&self.skin, corner));
+        }
+        let frame = Frame {
+            revision: board.revision(),
+            scroll: self.scroll,
+            screen: viewport,
+        };
+
+        let mut calls = 1;
+        if self.cache.as_ref().map(|(_, cached)| *cached) != Some(frame) {
+            let target = match self.cache.take() {
+                Some((target, cached)) if cached.screen == viewport => target,
+                _ => render_target(viewport.x as u32, viewport.y as u32),
+            };
+            let mut camera =
+                Camera2D::from_display_rect(Rect::new(0.0, 0.0, viewport.x, viewport.y));
+            camera.render_target = Some(target.clone());
+            push_camera_state();
+            set_camera(&camera);
+            clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+            calls += self.draw_tiles(board);
+            pop_camera_state();
+            self.cache = Some((target, frame));
+        }
+
+        if let Some((target, _)) = &self.cache {
+            draw_texture_ex(
+                &target.texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(viewport),
+                    // Render targets come out upside down
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+        }
+        calls
+    }
+
+    /// Range of rows or columns with at least one pixel between the board
+    /// coordinates `from` and `to`.
+    fn visible(from: f32, to: f32, cells: usize) -> std::ops::Range<usize> {
+        let first = from.floor().max(0.0) as usize;
+        let last = to.ceil().max(0.0) as usize;
+        first.min(cells)..last.min(cells)
+    }
+
+    /// Draws the visible tiles as one mesh textured with the atlas. Returns
+    /// the draw calls used.
+    fn draw_tiles(&self, board: &Board) -> usize {
+        let Some(atlas) = &self.atlas else {
+            return 0;
+        };
+        let viewport = self.viewport();
+        let w = self.tile_width;
+        let mut tiles = Batch::new(Some(atlas.texture()));
+        let (left, top) = self.to_board((0.0, 0.0));
+        let (right, bottom) = self.to_board((viewport.x, viewport.y));
+        for row in Self::visible(top, bottom, board.height()) {
+            for col in Self::visible(left, right, board.width()) {
+                let (x, y) = self.to_screen((col as f32, row as f32));
+                // Numbers the blind rule keeps back are drawn blank and tinted
+                let mut cell = board.cell(row, col);
+                let unread = cell.cell_state == CellState::Visible
+                    && matches!(cell.cell_type, CellType::Number(_))
+                    && !board.shows_number(row, col);
+                if unread {
+                    cell.cell_type = CellType::Empty;
+                }
+                // Locked cells are dimmed until the level opens them, cells
+                // out of sight are under fog, and uncovered mines show their kind
+                let kind = match cell.cell_state {
+                    CellState::Visible => board.mine_kind(row, col),
+                    _ => None,
+                };
+                let tint = if !board.in_sight(row, col) {
+                    FOG_TINT
+                } else if board.is_locked(row, col) {
+                    LOCKED_TINT
+                } else if unread {
+                    UNREAD_TINT
+                } else if kind == Some(MineKind::Dud) {
+                    DUD_TINT
+                } else if kind == Some(MineKind::Big) {
+                    BIG_TINT
+                } else {
+                    WHITE
+                };
+                tiles.quad(Rect::new(x, y, w, w), atlas.uv(&cell), tint);
+            }
+        }
+        tiles.finish()
+    }
+
+    /// Draws one tile face over the board at `(row, col)`, for effects
+    /// that show a cell other than it is.
+    pub fn draw_cell(&self, cell: &Cell, row: usize, col: usize) {
+        if let Some(atlas) = &self.atlas {
+            let mut tile = Batch::new(Some(atlas.texture()));
+            tile.quad(self.tile(row, col), atlas.uv(cell), WHITE);
+            tile.finish();
+        }
+    }
+
+    pub fn cell_at(&self, board: &Board, mouse_pos: (f32, f32)) -> Option<(usize, usize)> {
+        if mouse_pos.1 >= self.viewport().y {
+            return None;
+        }
+        let (x, y) = self.to_board(mouse_pos);
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (col, row) = (x.floor() as usize, y.floor() as usize);
+
+        // Check if the mouse position is on gap
+        let on_gap = |offset: f32| offset.fract() * self.step() > self.tile_width;
+        if !board.contains(row, col) || on_gap(x) || on_gap(y) {
+            return None;
+        }
+        // Nothing under the fog can be played
+        board.in_sight(row, col).then_some((row, col))
+    }
+
+    /// Converts a screen position to board coordinates measured in cells.
+    pub fn to_board(&self, pos: (f32, f32)) -> (f32, f32) {
+        let origin = self.offset() + self.geometry.padding - self.scroll;
+        let board = (Vec2::from(pos) - origin) / self.step();
+        (board.x, board.y)
+    }
+
+    pub fn to_screen(&self, pos: (f32, f32)) -> (f32, f32) {
+        let origin = self.offset() + self.geometry.padding - self.scroll;
+        let screen = origin + Vec2::from(pos) * self.step();
+        (screen.x, screen.y)
+    }
+
+    /// Where the tile at `(row, col)` is drawn.
+    pub fn tile(&self, row: usize, col: usize) -> Rect {
+        let (x, y) = self.to_screen((col as f32, row as f32));
+        Rect::new(x, y, self.tile_width, self.tile_width)
+    }
+
+    /// Scrolls just far enough to bring the tile at `(row, col)` into view.
+    pub fn scroll_to(&mut self, row: usize, col: usize) {
+        let viewport = self.viewport();
+        let start = vec2(col as f32, row as f32) * self.step() + self.geometry.padding;
+        let end = start + self.tile_width;
+        let max = (self.size - viewport).max(Vec2::ZERO);
+        self.scroll = self
+            .scroll
+            .min(start)
+            .max(end - viewport)
+            .clamp(Vec2::ZERO, max);
+    }
+
+    /// The part of the window the board covers, padding included.
+    pub fn bounds(&self) -> Rect {
+        let corner = self.offset() - self.scroll;
+        let end = (corner + self.size).min(self.viewport());
+        let corner = corner.max(Vec2::ZERO);
+        Rect::new(corner.x, corner.y, end.x - corner.x, end.y - corner.y)
+    }
+
+    /// Where the board ends on screen, which is where the status line goes.
+    pub fn height(&self, board: &Board) -> f32 {
+        let (_, bottom) = self.to_screen((0.0, board.height() as f32));
+        bottom.min(self.viewport().y)
+    }
+}
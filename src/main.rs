@@ -1,268 +1,1049 @@
-extern crate rand;
+mod access;
+mod achievements;
+mod appearance;
+mod atlas;
+mod batch;
+mod chat;
+mod classic;
+mod cli;
+mod client;
+mod config;
+mod confirm;
+mod console;
+mod crt;
+#[cfg(feature = "debug-tools")]
+mod debug;
+mod effects;
+mod export;
+mod focus;
+mod font;
+mod haptics;
+mod i18n;
+mod idle;
+#[cfg(feature = "debug-tools")]
+mod inspector;
+mod layout;
+mod menu;
+mod mouse;
+mod online;
+mod overlay;
+mod preset;
+mod recovery;
+mod review;
+mod rules;
+mod scale;
+mod search;
+mod seed;
+mod session;
+mod stats;
+mod storage;
+mod stroke;
+mod sync;
+mod theme;
+mod toast;
+mod touch;
+mod trail;
+mod watch;
 
-use std::collections::{HashSet, VecDeque};
+use std::time::Instant;
 
+use macroquad::conf::Conf;
 use macroquad::prelude::*;
-use macroquad::window::Conf;
-use rand::seq::index;
+use minesweeper_core::export::Screenshot;
+use minesweeper_core::log::{self, Level};
+use minesweeper_core::placement;
+use minesweeper_core::replay::Replay;
+use minesweeper_core::scenario::Scenario;
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::stats::Variant;
+use minesweeper_core::tutorial::Tutorial;
+use minesweeper_core::{warn, Board, CellState, FlagCapture, Outcome, Ruleset};
+
+use access::Access;
+use achievements::Browser;
+use appearance::System;
+use classic::Classic;
+use config::{Config, Live};
+use confirm::{Confirm, Leave};
+use console::{Console, Context};
+use effects::Effects;
+use focus::Focus;
+use haptics::Haptics;
+use i18n::t;
+use idle::Idle;
+use layout::{Layout, PLAYER_COLORS, STATUS_MARGIN};
+use menu::{Menu, Page, Settings};
+use online::Online;
+use overlay::Overlay;
+use recovery::{Answer, Prompt, Recorder};
+use review::Viewer;
+use search::Search;
+use session::SessionWidget;
+use stroke::Stroke;
+use toast::{Kind, Toasts};
+use touch::{Gesture, Touch};
 
 fn window_conf() -> Conf {
     Conf {
-        window_title: "Minesweeper".to_owned(),
-        window_width: 800,
-        window_height: 800,
+        miniquad_conf: miniquad::conf::Conf {
+            window_title: "Minesweeper".to_owned(),
+            window_width: 800,
+            window_height: 850,
+            ..Default::default()
+        },
+        // Large enough for the batched board meshes
+        draw_call_vertex_capacity: batch::MAX_VERTICES,
+        draw_call_index_capacity: batch::MAX_INDICES,
         ..Default::default()
     }
 }
 
-#[derive(Clone, Debug)]
-enum CellType {
-    Mine,
-    Number(usize),
-    Empty,
-}
-
-#[derive(Clone, Debug)]
-enum CellState {
-    Visible,
-    Hidden,
-}
-
-#[derive(Clone, Debug)]
-struct Cell {
-    cell_type: CellType,
-    cell_state: CellState,
+/// Command line options for the game window that aren't about the game.
+struct Options {
+    language: Option<String>,
+    accessible: bool,
+    speech: Option<String>,
 }
 
-impl Cell {
-    pub fn new(cell_type: CellType, cell_state: CellState) -> Self {
+impl Options {
+    fn from_args(args: &[String]) -> Self {
         Self {
-            cell_type,
-            cell_state,
+            language: cli::flag_value(args, "--lang"),
+            accessible: args.iter().any(|arg| arg == "--accessible"),
+            speech: cli::flag_value(args, "--speak"),
         }
     }
 
-    pub fn update_state(&mut self, cell_state: CellState) {
-        self.cell_state = cell_state;
+    /// Applies the accessibility options, which the command line can turn
+    /// on over the config file.
+    fn configure(&self, access: &mut Access, live: &Live) {
+        access.configure(
+            self.accessible || live.config.accessible,
+            self.speech.as_deref().unwrap_or(&live.config.speech),
+        );
     }
 }
 
-type State = Vec<Vec<Cell>>;
-
-struct Board {
-    x_cells: usize,
-    y_cells: usize,
-    gap: f32,
-    padding: f32,
-    tile_width: f32,
-    state: State,
+enum GameMode {
+    Classic(Settings),
+    /// The game recorded in a screenshot, from its first move.
+    Screenshot(Screenshot),
+    Tutorial,
+    /// A campaign level from a file, see `scenario`.
+    Scenario(Scenario),
+    FlagCapture,
+    Online {
+        addr: String,
+        room: String,
+        spectate: bool,
+    },
 }
 
-impl Board {
-    pub fn new(x_cells: usize, y_cells: usize, gap: f32, padding: f32, mines: usize) -> Self {
-        let mut state: State =
-            vec![vec![Cell::new(CellType::Empty, CellState::Hidden); x_cells]; y_cells];
-
-        let mut rng = rand::thread_rng();
-
-        let flattened_indexes = index::sample(&mut rng, x_cells * y_cells, mines);
-
-        // Convert flattened index to row and column indexes
-        let mine_positions = flattened_indexes
-            .iter()
-            .map(|i| (i.div_ceil(x_cells).saturating_sub(1), i % x_cells)); // div_floor does not work very weird
-
-        for pos in mine_positions {
-            let (x, y) = pos;
-            state[x][y].cell_type = CellType::Mine;
+impl GameMode {
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let value = |flag: &str| cli::flag_value(args, flag);
 
-            // Put in the numbers
-            let x_upper = if x + 1 >= y_cells { x } else { x + 1 };
-            let x_lower = x.saturating_sub(1);
-
-            let y_upper = if y + 1 >= y_cells { y } else { y + 1 };
-            let y_lower = y.saturating_sub(1);
-
-            for x in x_lower..=x_upper {
-                for y in y_lower..=y_upper {
-                    match state[x][y].cell_type {
-                        CellType::Mine => {}
-                        CellType::Empty => {
-                            state[x][y].cell_type = CellType::Number(1);
-                        }
-                        CellType::Number(n) => {
-                            state[x][y].cell_type = CellType::Number(n + 1);
-                        }
-                    }
-                }
-            }
+        if let Some(addr) = value("--connect") {
+            return Ok(GameMode::Online {
+                addr,
+                room: value("--room").unwrap_or_else(|| "lobby".to_owned()),
+                spectate: args.iter().any(|arg| arg == "--spectate"),
+            });
         }
-
-        Self {
-            x_cells,
-            y_cells,
-            gap,
-            padding,
-            tile_width: (screen_width() - padding - gap * x_cells as f32) / x_cells as f32,
-            state,
+        if let Some(path) = value("--screenshot") {
+            let bytes =
+                std::fs::read(&path).map_err(|err| format!("could not read {}: {}", path, err))?;
+            return Screenshot::read(&bytes)
+                .map(GameMode::Screenshot)
+                .map_err(|err| format!("{}: {}", path, err));
         }
-    }
-
-    pub fn draw(&self) {
-        for row in 0..self.y_cells {
-            for col in 0..self.x_cells {
-                let x = self.padding as f32 + col as f32 * (self.gap + self.tile_width);
-                let y = self.padding as f32 + row as f32 * (self.gap + self.tile_width);
-                let cell = &self.state[row][col];
-                match cell.cell_state {
-                    CellState::Hidden => {
-                        draw_rectangle(x, y, self.tile_width, self.tile_width, GRAY);
-                    }
-                    CellState::Visible => match cell.cell_type {
-                        CellType::Mine => {
-                            draw_rectangle(x, y, self.tile_width, self.tile_width, BLACK);
-                        }
-                        CellType::Empty => {
-                            draw_rectangle(x, y, self.tile_width, self.tile_width, GREEN);
-                        }
-                        CellType::Number(n) => {
-                            draw_rectangle(x, y, self.tile_width, self.tile_width, PINK);
-                            draw_text(
-                                n.to_string().as_str(),
-                                x + self.tile_width / 2.0 - 5.0,
-                                y + self.tile_width - 5.0,
-                                self.tile_width,
-                                BLACK,
-                            );
+        if let Some(path) = value("--scenario") {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|err| format!("could not read {}: {}", path, err))?;
+            return Scenario::parse(&source)
+                .map(GameMode::Scenario)
+                .map_err(|err| format!("{}: {}", path, err));
+        }
+        match value("--mode").as_deref() {
+            Some("flags") => Ok(GameMode::FlagCapture),
+            Some("tutorial") => Ok(GameMode::Tutorial),
+            _ => {
+                let default = Settings::default();
+                let settings = Settings {
+                    width: cli::parsed(args, "--width", default.width)?,
+                    height: cli::parsed(args, "--height", default.height)?,
+                    mines: cli::parsed(args, "--mines", default.mines)?,
+                    no_guess: args.iter().any(|arg| arg == "--no-guess"),
+                    trainer: args.iter().any(|arg| arg == "--trainer"),
+                    moving: args.iter().any(|arg| arg == "--moving-mines"),
+                    stars: match value("--stars") {
+                        Some(stars) => Some(
+                            stars
+                                .parse()
+                                .ok()
+                                .filter(|stars| (1..=5).contains(stars))
+                                .ok_or_else(|| format!("invalid value for --stars: {}", stars))?,
+                        ),
+                        None => None,
+                    },
+                    placement: match value("--placement") {
+                        Some(name) => placement::named(&name)
+                            .ok_or_else(|| format!("unknown placement `{}`", name))?
+                            .name(),
+                        None => default.placement,
+                    },
+                    rules: match value("--rules") {
+                        Some(names) => {
+                            names.split('+').try_fold(default.rules, |rules, name| {
+                                rules
+                                    .with_name(name)
+                                    .ok_or_else(|| format!("unknown rule `{}`", name))
+                            })?
                         }
+                        None => default.rules,
                     },
                 };
+                Board::validate(settings.width, settings.height, settings.mines)
+                    .map_err(|err| err.to_string())?;
+                Ok(GameMode::Classic(settings))
             }
         }
     }
+}
 
-    fn on_gap(&self, col: usize, row: usize, mouse_pos: (f32, f32)) -> bool {
-        // Check if the mouse position is on gap
-        !(mouse_pos.0 <= self.tile_width * (col as f32 + 1.0) + self.gap * col as f32)
-            || !(mouse_pos.1 <= self.tile_width * (row as f32 + 1.0) + self.gap * row as f32)
-    }
-
-    fn neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let indices = [
-            (row as i32, col as i32 - 1),
-            (row as i32, col as i32 + 1),
-            (row as i32 - 1, col as i32),
-            (row as i32 + 1, col as i32),
-        ];
-
-        let state = &self.state;
+/// A screen opened from the settings window. It takes the input while it
+/// is open.
+enum Screen {
+    Achievements(Browser),
+    Stats(stats::Page),
+    Seed(seed::Entry),
+    Rules(rules::Builder),
+}
 
-        let is_valid = |row: i32, col: i32| {
-            row >= 0
-                && col >= 0
-                && row < self.y_cells as i32
-                && col < self.x_cells as i32
-                && match state[row as usize][col as usize].cell_type {
-                    CellType::Mine => false,
-                    _ => true,
-                }
-        };
+impl Screen {
+    /// The settings window gives the presets to name boards by, and the
+    /// board a bare seed is played on.
+    fn open(page: Page, menu: &Menu) -> Self {
+        match page {
+            Page::Achievements => Screen::Achievements(Browser::open()),
+            Page::Stats => Screen::Stats(stats::Page::open(menu.presets().to_vec())),
+            Page::Seed => Screen::Seed(seed::Entry::open(menu.settings())),
+            Page::Rules => Screen::Rules(rules::Builder::open(menu.settings())),
+        }
+    }
 
-        indices
-            .into_iter()
-            .filter(|(row, col)| is_valid(*row, *col))
-            .map(|(row, col)| (row as usize, col as usize))
-            .collect()
+    /// Returns false once the player closes it.
+    fn update(&mut self) -> bool {
+        match self {
+            Screen::Achievements(browser) => browser.update(),
+            Screen::Stats(page) => page.update(),
+            Screen::Seed(entry) => entry.update(),
+            Screen::Rules(builder) => builder.update(),
+        }
     }
 
-    fn reveal_empty(&mut self, row: usize, col: usize) {
-        // Use BFS to reveal empty
-        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+    /// The game the player picked here, once.
+    fn take_code(&mut self) -> Option<ShareCode> {
+        match self {
+            Screen::Seed(entry) => entry.take_code(),
+            Screen::Rules(builder) => builder.take_code(),
+            _ => None,
+        }
+    }
+}
 
-        queue.push_back((row, col));
+/// Applies the settings every new classic game takes from outside.
+fn prepare(classic: Classic, live: &Live) -> Classic {
+    let streak = stats::streak(classic.board());
+    let classic = if classic.is_trainer() {
+        classic.with_trainer_ioes(stats::trainer_ioes())
+    } else {
+        classic
+    };
+    classic
+        .with_hints(live.config.hints())
+        .with_auto_flag(live.config.auto_flag)
+        .with_auto_chord(live.config.auto_chord)
+        .with_trail(live.config.trail())
+        .with_instant_openings(live.config.reduced_motion)
+        .with_streak(streak)
+}
 
-        let mut visited: HashSet<(usize, usize)> = HashSet::new();
+fn layout(board: &Board, live: &Live) -> Layout {
+    Layout::new(board, live.config.geometry(), live.skin.clone())
+}
 
-        while !queue.is_empty() {
-            let pos = queue.pop_front().unwrap();
+fn classic_layout(classic: &Classic, live: &Live) -> Layout {
+    let layout = layout(classic.board(), live);
+    match classic.status_height() {
+        Some(height) => layout.with_status(classic.board(), height),
+        None => layout,
+    }
+}
 
-            visited.insert(pos);
+fn draw_scores(game: &FlagCapture, top: f32, status: Option<&str>, text: Color) {
+    let y = top + STATUS_MARGIN;
+    for (player, score) in game.scores().iter().enumerate() {
+        let marker = if player == game.turn() { ">" } else { " " };
+        font::line(
+            &format!(
+                "{} {}",
+                marker,
+                t!("player-score", player = player + 1, score = score)
+            ),
+            10.0 + player as f32 * 200.0,
+            y,
+            30.0,
+            PLAYER_COLORS[player],
+        );
+    }
 
-            self.state[pos.0][pos.1].update_state(CellState::Visible);
+    let status = match (game.outcome(), status) {
+        (Some(Outcome::Winner(player)), _) => t!("player-wins", player = player + 1),
+        (Some(Outcome::Draw), _) => t!("draw"),
+        (None, Some(status)) => status.to_owned(),
+        (None, None) => t!("mines-left", count = game.mines_left()),
+    };
+    font::line(&status, 450.0, y, 30.0, text);
+}
 
-            for neighbor_coords in self.neighbors(pos.0, pos.1) {
-                let (row, col): (usize, usize) = neighbor_coords;
+/// Outlines the cell under the pointer and shows where it is in the
+/// bottom right corner, as far as the config asks for either.
+fn draw_hover(layout: &Layout, (row, col): (usize, usize), config: &Config, bottom: f32) {
+    if config.hover {
+        let tile = layout.tile(row, col);
+        draw_rectangle(
+            tile.x,
+            tile.y,
+            tile.w,
+            tile.h,
+            Color::new(1.0, 1.0, 1.0, 0.25),
+        );
+        draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 2.0, DARKBLUE);
+    }
+    if config.coordinates {
+        // Counted from 1, as players say them
+        let text = t!("coordinates", col = col + 1, row = row + 1);
+        let width = font::measure(&text, 20.0).width + 16.0;
+        let height = scale::px(24.0);
+        let corner = Rect::new(
+            screen_width() - width - 10.0,
+            bottom - height - 10.0,
+            width,
+            height,
+        );
+        draw_rectangle(
+            corner.x,
+            corner.y,
+            corner.w,
+            corner.h,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        font::centred(&text, corner, 20.0, WHITE);
+    }
+}
 
-                if !visited.contains(&neighbor_coords) {
-                    match self.state[row][col].cell_type {
-                        CellType::Empty => queue.push_back(neighbor_coords),
-                        CellType::Number(_) => {
-                            self.state[row][col].update_state(CellState::Visible);
-                        }
-                        CellType::Mine => {
-                            panic!("This should not be possible");
-                        }
-                    }
-                }
-            }
-        }
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Err(err) = log::init_from_args(&args, Level::Warn) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    recovery::install();
+    let result = match cli::run(&args) {
+        Some(result) => result,
+        None => GameMode::from_args(&args)
+            .and_then(|mode| {
+                classic::install_mods(cli::load_mods(&args)?);
+                Ok(mode)
+            })
+            .map(|mode| {
+                let options = Options::from_args(&args);
+                macroquad::Window::from_config(window_conf(), game(mode, options))
+            }),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
+}
 
-    fn reveal_all(&mut self) {
-        for row in 0..self.y_cells {
-            for col in 0..self.x_cells {
-                self.state[row][col].update_state(CellState::Visible);
+async fn game(mode: GameMode, options: Options) {
+    // An odd mine count means a flag-capture match can never end in a draw
+    let settings = match mode {
+        GameMode::Classic(settings) => settings,
+        GameMode::Screenshot(ref screenshot) => {
+            let configuration = screenshot.configuration;
+            Settings {
+                width: configuration.width,
+                height: configuration.height,
+                mines: configuration.mines,
+                no_guess: configuration.variant.contains(Variant::NO_GUESS),
+                placement: configuration.variant.generator().name(),
+                trainer: configuration.variant.contains(Variant::TRAINER),
+                moving: configuration.variant.contains(Variant::MOVING),
+                stars: None,
+                rules: Ruleset::CLASSIC,
             }
         }
+        // The settings window starts a normal game from the tutorial
+        GameMode::Tutorial => Settings::default(),
+        GameMode::Scenario(ref scenario) => Settings {
+            width: scenario.code.width,
+            height: scenario.code.height,
+            mines: scenario.code.mines,
+            rules: scenario.code.rules,
+            ..Settings::default()
+        },
+        GameMode::FlagCapture | GameMode::Online { .. } => Settings {
+            width: 16,
+            height: 16,
+            mines: 51,
+            ..Settings::default()
+        },
+    };
+    let mines = settings.mines;
+    let mut overlay = Overlay::new();
+    let mut idle = Idle::new();
+    let start = Instant::now();
+    let seed = menu::seed();
+    // Boards that take a search, rated or without guesses, are found in
+    // the background, with a plain board of the same size on show meanwhile
+    let searching =
+        matches!(mode, GameMode::Classic(_)) && (settings.stars.is_some() || settings.no_guess);
+    let mut level = None;
+    let generated = match &mode {
+        GameMode::Tutorial => Ok(Tutorial::board()),
+        GameMode::Scenario(scenario) => scenario.start().map(|(board, run)| {
+            level = Some(run);
+            board
+        }),
+        GameMode::Screenshot(screenshot) => Ok(screenshot.start.clone()),
+        _ if searching => Settings {
+            no_guess: false,
+            ..settings
+        }
+        .board(seed),
+        _ => settings.board(seed),
+    };
+    let mut board = match generated {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+    overlay.set_generation(start.elapsed());
+    let mut flag_capture = FlagCapture::new(mines);
+    // Before anything is loaded, so a config from another machine applies
+    // from the start
+    if let Some(Err(err)) = sync::run() {
+        eprintln!("Could not sync: {}", err);
     }
+    let mut live = Live::load();
+    scale::set(live.config.ui_scale);
+    if let Err(err) = font::load(live.font()) {
+        eprintln!("{}", err);
+    }
+    i18n::init(options.language.as_deref(), &live.config.language);
+    let mut layout = layout(&board, &live);
+    let mut menu = Menu::new(settings);
+    menu.set_presets(live.config.presets());
+    menu.set_buttons(live.config.buttons);
+    menu.set_appearance(live.config.appearance);
+    menu.set_theme(&live.config.theme);
+    menu.set_reduced_motion(live.config.reduced_motion);
+    menu.set_haptics(live.config.haptics, live.config.haptic_strength);
+    let system = System::watch();
+    crt::set(live.config.crt && !live.config.reduced_motion);
+    live.set_dark(system.is_dark(&live.config));
 
-    pub fn update(&mut self, mouse_pos: (f32, f32)) {
-        let col = (mouse_pos.0 / (self.tile_width + self.gap)).floor() as usize;
-        let row = (mouse_pos.1 / (self.tile_width + self.gap)).floor() as usize;
-
-        if !self.on_gap(col, row, mouse_pos) {
-            if let Some(clicked_cell) = self.state[row].get_mut(col) {
-                match clicked_cell.cell_state {
-                    CellState::Hidden => {
-                        clicked_cell.update_state(CellState::Visible);
-                        match clicked_cell.cell_type {
-                            CellType::Empty => {
-                                // If empty reveal all empty nearby
-                                self.reveal_empty(row, col);
-                            }
-                            CellType::Mine => {
-                                self.reveal_all();
-                            }
-                            CellType::Number(_) => {}
-                        }
-                    }
-                    CellState::Visible => {}
-                }
-            }
+    let mut classic = match mode {
+        GameMode::Classic(_) => Some(prepare(
+            Classic::new(board.clone())
+                .with_variant(settings.variant())
+                .with_seed(seed),
+            &live,
+        )),
+        GameMode::Screenshot(ref screenshot) => {
+            let classic =
+                Classic::resume(board.clone()).with_variant(screenshot.configuration.variant);
+            Some(prepare(
+                match screenshot.seed {
+                    Some(seed) => classic.with_seed(seed),
+                    None => classic,
+                },
+                &live,
+            ))
         }
+        GameMode::Tutorial => Some(Classic::tutorial()),
+        // Kept out of the records of plain games, as its mines change
+        GameMode::Scenario(ref scenario) => level.map(|run| {
+            prepare(
+                Classic::resume(board.clone())
+                    .with_variant(scenario.code.variant.with(Variant::CUSTOM))
+                    .with_seed(scenario.code.seed)
+                    .with_scenario(run),
+                &live,
+            )
+        }),
+        _ => None,
+    };
+    if let Some(classic) = &classic {
+        layout = classic_layout(classic, &live);
     }
-}
-
-#[macroquad::main(window_conf)]
-async fn main() {
-    let mines = 50;
-    // changing screen size
-    let mut board = Board::new(16, 16, 1.0, 2.0, mines);
+    let mut access = Access::new();
+    options.configure(&mut access, &live);
+    let mut effects = Effects::new();
+    effects.configure(
+        live.config.celebrate,
+        live.config.reduced_motion,
+        &live.config.sound,
+    );
+    let mut haptics = Haptics::new();
+    haptics.configure(
+        live.config.haptics,
+        live.config.haptic_strength,
+        &live.config.vibrate,
+    );
+    let mut touch = Touch::default();
+    if let Some(classic) = classic.as_mut() {
+        access.attach(classic);
+        effects.attach(classic);
+        haptics.attach(classic);
+    }
+    let mut recorder = Recorder::default();
+    let mut toasts = Toasts::default();
+    let mut console = Console::new();
+    let mut session = SessionWidget::new();
+    let mut screen: Option<Screen> = None;
+    let mut review: Option<Viewer> = None;
+    let mut stroke: Option<Stroke> = None;
+    // A game from the command line with a rating to meet, or without
+    // guesses, starts with the plain board until the search finds one
+    let mut search = match mode {
+        GameMode::Classic(settings) => match settings.stars {
+            Some(stars) => Some(Search::start(settings, stars, seed)),
+            None if settings.no_guess => Some(Search::deal(settings.code(seed))),
+            None => None,
+        },
+        _ => None,
+    };
+    // The board the next game of the menu's settings gets, dealt while
+    // this one is played so starting it is instant
+    let mut next: Option<(Settings, Search)> = None;
+    let mut prompt = match &classic {
+        Some(classic) if !classic.is_tutorial() => Prompt::pending(),
+        _ => None,
+    };
+    let mut online = match &mode {
+        GameMode::Online {
+            addr,
+            room,
+            spectate,
+        } => match Online::connect(addr, room, *spectate) {
+            Ok(online) => Some(online),
+            Err(err) => {
+                eprintln!("Could not connect to {}: {}", addr, err);
+                return;
+            }
+        },
+        _ => None,
+    };
 
     let mut mouse_pos: (f32, f32);
+    let mut confirm: Option<Confirm> = None;
+    let mut focus = Focus::new();
+    // Closing the window is a request the loop answers, so a game in
+    // progress can be kept
+    prevent_quit();
 
     loop {
-        clear_background(WHITE);
+        crt::begin();
+        clear_background(live.skin.background);
+        mouse_pos = crt::mouse_position();
+        let gesture = touch.update();
+
+        // Network
+        if let Some(online) = online.as_mut() {
+            online.update(&mut board, &mut flag_capture, &layout);
+            if let Some(trouble) = online.take_trouble() {
+                toasts.push(Kind::Warning, trouble);
+            }
+        }
 
         // Update
-        if is_mouse_button_pressed(MouseButton::Left) {
-            mouse_pos = mouse_position();
-            board.update(mouse_pos);
+        let reloaded = live.poll();
+        if reloaded.config {
+            if !live.config.language.is_empty() {
+                i18n::set(&live.config.language);
+            }
+            scale::set(live.config.ui_scale);
+            options.configure(&mut access, &live);
+            effects.configure(
+                live.config.celebrate,
+                live.config.reduced_motion,
+                &live.config.sound,
+            );
+            menu.set_presets(live.config.presets());
+            menu.set_buttons(live.config.buttons);
+            menu.set_appearance(live.config.appearance);
+            menu.set_theme(&live.config.theme);
+            menu.set_reduced_motion(live.config.reduced_motion);
+            menu.set_haptics(live.config.haptics, live.config.haptic_strength);
+            haptics.configure(
+                live.config.haptics,
+                live.config.haptic_strength,
+                &live.config.vibrate,
+            );
+            crt::set(live.config.crt && !live.config.reduced_motion);
+            live.set_dark(system.is_dark(&live.config));
+            layout = match &classic {
+                Some(classic) => classic_layout(classic, &live),
+                None => self::layout(&board, &live),
+            };
+        } else if reloaded.theme || live.set_dark(system.is_dark(&live.config)) {
+            layout.set_skin(live.skin.clone());
+        }
+        if reloaded.config || reloaded.theme {
+            if let Err(err) = font::load(live.font()) {
+                warn!("{}", err);
+            }
+        }
+        let mut asked = None;
+        if let Some(line) = console.update() {
+            let theme = live.config.theme.clone();
+            let mut context = Context {
+                classic: classic.as_mut(),
+                live: &mut live,
+                system: &system,
+                settings: menu.settings(),
+                leave: None,
+            };
+            console.run(&mut context, &line);
+            asked = context.leave;
+            layout.set_skin(live.skin.clone());
+            menu.set_appearance(live.config.appearance);
+            menu.set_theme(&live.config.theme);
+            if live.config.theme != theme {
+                if let Err(err) = font::load(live.font()) {
+                    warn!("{}", err);
+                }
+            }
+        }
+        if let Some(problem) = live.take_problem() {
+            toasts.notify(t!("theme-failed", error = problem));
+        }
+        // In accessible mode the arrow keys move the cursor instead, and
+        // in the review they step through the moves
+        // Scrolling over a hidden cell marks it instead, when asked to
+        let marking = classic
+            .as_ref()
+            .filter(|_| live.config.wheel_marks && review.is_none())
+            .and_then(|classic| {
+                let (row, col) = layout.cell_at(classic.board(), mouse_pos)?;
+                (classic.board().cell(row, col).cell_state != CellState::Visible)
+                    .then_some((row, col))
+            });
+        layout.update(
+            (classic.is_none() || !access.is_enabled()) && review.is_none() && !console.is_open(),
+            marking.is_none(),
+        );
+        let focus_lost = focus.lost();
+        if let Some(classic) = classic.as_mut() {
+            classic.update();
+            if focus_lost && live.config.auto_pause {
+                classic.pause();
+            }
+            if let Some(answer) = prompt.as_ref().and_then(Prompt::update) {
+                prompt = None;
+                if let Answer::Restore(board) = answer {
+                    *classic = prepare(Classic::resume(board), &live);
+                    access.attach(classic);
+                    effects.attach(classic);
+                    haptics.attach(classic);
+                    layout = classic_layout(classic, &live);
+                }
+            }
+            let start = Instant::now();
+            let request = if prompt.is_some() || confirm.is_some() {
+                None
+            } else if let Some(looking) = &search {
+                if !looking.update() {
+                    search = None;
+                }
+                None
+            } else if let Some(open) = &mut screen {
+                let still_open = open.update();
+                let code = open.take_code();
+                if let (Screen::Rules(_), Some(code)) = (&open, &code) {
+                    // Later games keep the rules until they are changed
+                    menu.set_rules(code.rules);
+                }
+                if !still_open {
+                    screen = None;
+                }
+                code.map(Leave::Play)
+            } else if let Some(viewer) = &mut review {
+                if !viewer.update(&layout) {
+                    review = None;
+                }
+                None
+            } else {
+                let settings = menu.update();
+                let restart = !menu.is_open() && is_key_pressed(KeyCode::F2);
+                settings
+                    .or_else(|| restart.then(|| menu.settings()))
+                    .map(Leave::Start)
+            };
+            let request = if is_quit_requested() {
+                Some(Leave::Quit)
+            } else {
+                request.or(asked)
+            };
+            let agreed = match confirm.as_ref().and_then(Confirm::update) {
+                Some(true) => confirm.take().map(Confirm::into_leave),
+                Some(false) => {
+                    confirm = None;
+                    None
+                }
+                None => None,
+            };
+            // Asked first when it would throw away a game in progress
+            let leave = match request {
+                Some(leave) if live.config.confirm_leave && classic.is_in_progress() => {
+                    confirm = Some(Confirm::new(leave));
+                    None
+                }
+                request => request.or(agreed),
+            };
+            let code = match leave {
+                Some(Leave::Quit) => {
+                    if classic.is_in_progress() {
+                        recovery::save(classic.board());
+                    }
+                    break;
+                }
+                Some(Leave::Start(settings)) => {
+                    match next.take_if(|(dealing, _)| *dealing == settings) {
+                        // Taken up below once it is done, which it usually is
+                        Some((_, ahead)) => {
+                            search = Some(ahead);
+                            None
+                        }
+                        None => match settings.stars.filter(|_| settings.rules.is_classic()) {
+                            Some(stars) => {
+                                search = Some(Search::start(settings, stars, menu::seed()));
+                                None
+                            }
+                            None => Some(settings.code(menu::seed())),
+                        },
+                    }
+                }
+                Some(Leave::Play(code)) => Some(code),
+                None => None,
+            };
+            if menu.take_reset_session() {
+                session.reset();
+            }
+            if let Some(page) = menu.take_page() {
+                screen = Some(Screen::open(page, &menu));
+            }
+            if let Some(code) = menu.take_language() {
+                live.set_language(code);
+            }
+            if let Some(buttons) = menu.take_buttons() {
+                live.set_buttons(buttons);
+            }
+            if let Some(appearance) = menu.take_appearance() {
+                live.set_appearance(appearance);
+                if live.set_dark(system.is_dark(&live.config)) {
+                    layout.set_skin(live.skin.clone());
+                }
+            }
+            if let Some(reduced) = menu.take_reduced_motion() {
+                live.set_reduced_motion(reduced);
+            }
+            if let Some((enabled, strength)) = menu.take_haptics() {
+                live.set_haptics(enabled, strength);
+                haptics.configure(enabled, strength, &live.config.vibrate);
+            }
+            if let Some(name) = menu.take_theme() {
+                live.set_theme(&name);
+                layout.set_skin(live.skin.clone());
+                if let Err(err) = font::load(live.font()) {
+                    warn!("{}", err);
+                }
+            }
+            if menu.take_copy_seed() || classic.take_copy_seed() {
+                match classic.share_code() {
+                    Some(code) => {
+                        seed::copy(&code);
+                        toasts.notify(t!("seed-copied", code = code.to_string()));
+                    }
+                    None => toasts.notify(t!("seed-none")),
+                }
+            }
+            let dealt = match code {
+                // These can take thousands of candidates, so they are dealt
+                // in the background like a rated board
+                Some(code) if code.variant.contains(Variant::NO_GUESS) => {
+                    search = Some(Search::deal(code));
+                    None
+                }
+                Some(code) => Some((start, code.board().map(|board| (code, board)))),
+                None => search
+                    .take_if(|looking| looking.is_finished())
+                    .map(|looking| (looking.started(), looking.finish())),
+            };
+            match dealt {
+                Some((start, Ok((code, board)))) => {
+                    overlay.set_generation(start.elapsed());
+                    review = None;
+                    *classic = prepare(
+                        Classic::new(board)
+                            .with_variant(code.variant)
+                            .with_seed(code.seed),
+                        &live,
+                    );
+                    access.attach(classic);
+                    effects.attach(classic);
+                    haptics.attach(classic);
+                    layout = classic_layout(classic, &live);
+                }
+                Some((_, Err(err))) => {
+                    eprintln!("Could not start a custom game: {}", err);
+                    toasts.notify(t!("game-failed", error = err.to_string()));
+                }
+                None => {}
+            }
+            let wanted = menu.settings();
+            if classic.is_in_progress()
+                && search.is_none()
+                && next.as_ref().is_none_or(|(dealing, _)| *dealing != wanted)
+            {
+                // Replacing one for other settings stops it
+                next = Some((wanted, Search::ahead(wanted, menu::seed())));
+            }
+            if let Some((record, clicks)) = classic.take_record() {
+                session.add(&record);
+                let time = format!("{:.2}", record.time.as_secs_f32());
+                let recorded = stats::record(record, &clicks);
+                if let Some(beaten) = recorded.beaten {
+                    let was = format!("{:.2}", beaten.as_secs_f32());
+                    toasts.push(Kind::Best, t!("personal-best", time = time, was = was));
+                }
+                toasts.achievements(recorded.unlocked);
+                sync::spawn();
+            }
+            effects.update(classic);
+            haptics.update(touch.is_touch_device());
+            if !classic.is_busy() {
+                let playing = !classic.is_over() && !classic.is_tutorial();
+                recorder.record(Some(classic.board()).filter(|_| playing));
+            }
+            // The settings window and the review take the clicks while they
+            // are open
+            let blocked = menu.is_open()
+                || prompt.is_some()
+                || confirm.is_some()
+                || classic.is_paused()
+                || screen.is_some()
+                || search.is_some()
+                || console.is_open();
+            let target = if blocked || review.is_some() {
+                None
+            } else {
+                access.update(classic, &mut layout);
+                if is_key_pressed(KeyCode::H) {
+                    classic.hint();
+                }
+                if is_key_pressed(KeyCode::A) && !classic.is_tutorial() {
+                    let samples = live.config.samples as usize;
+                    let timeline = classic.replay().map(Replay::marks).unwrap_or_default();
+                    let trail = live.config.trail();
+                    review = classic.review().map(|review| {
+                        Viewer::new(review, samples)
+                            .with_timeline(timeline)
+                            .with_trail(trail)
+                    });
+                }
+                let exports = [KeyCode::A, KeyCode::G, KeyCode::S, KeyCode::V];
+                if classic.is_unreplayable() && exports.into_iter().any(is_key_pressed) {
+                    toasts.report(Err(t!("replay-unavailable")));
+                }
+                if let Some(replay) = classic.replay().filter(|_| !classic.is_tutorial()) {
+                    let options = live.config.replay_options();
+                    if is_key_pressed(KeyCode::G) {
+                        toasts.report(match export::gif(replay, options) {
+                            Ok(path) => Ok(t!("gif-saved", path = path.display().to_string())),
+                            Err(err) => Err(t!("gif-failed", error = err.to_string())),
+                        });
+                    }
+                    if is_key_pressed(KeyCode::S) {
+                        toasts.report(match export::replay(replay) {
+                            Ok(path) => Ok(t!("replay-saved", path = path.display().to_string())),
+                            Err(err) => Err(t!("replay-failed", error = err.to_string())),
+                        });
+                    }
+                    if is_key_pressed(KeyCode::V) {
+                        let format = live.config.video_format();
+                        toasts.report(match export::video(replay, options, format) {
+                            Ok(path) => Ok(t!("video-saved", path = path.display().to_string())),
+                            Err(err) => Err(t!("video-failed", error = err.to_string())),
+                        });
+                    }
+                }
+                layout.cell_at(classic.board(), mouse_pos)
+            };
+            // A drag paints from the press that started it until the button
+            // is let go
+            stroke.take_if(|drag| {
+                blocked || review.is_some() || !drag.update(classic, &layout, mouse_pos)
+            });
+            #[cfg(feature = "debug-tools")]
+            let target =
+                target.filter(|&cell| !debug::intercept(classic, cell, live.config.buttons.reveal));
+            if let Some((row, col)) = target {
+                match gesture {
+                    Some(Gesture::Tap) => classic.click(row, col),
+                    Some(Gesture::LongPress) => {
+                        haptics.long_press();
+                        classic.flag(row, col);
+                    }
+                    None => {}
+                }
+            }
+            // A finger on the screen also presses the left button, which
+            // would reveal before a long press could flag
+            if let Some((row, col)) = target.filter(|_| !touch.is_down()) {
+                let buttons = live.config.buttons;
+                if buttons.reveal.is_pressed() {
+                    classic.click(row, col);
+                    stroke = Some(Stroke::chord(buttons.reveal, mouse_pos, (row, col)));
+                }
+                if buttons.flag.is_pressed() {
+                    classic.flag(row, col);
+                    stroke = Some(Stroke::flag(
+                        classic.board(),
+                        buttons.flag,
+                        mouse_pos,
+                        (row, col),
+                    ));
+                }
+                if buttons.chord.is_pressed() {
+                    classic.chord(row, col);
+                }
+            }
+            if let Some((row, col)) = marking.filter(|_| target.is_some()) {
+                let (_, wheel) = mouse_wheel();
+                if wheel != 0.0 {
+                    classic.cycle_mark(row, col, wheel > 0.0);
+                }
+            }
+            // After the clicks were blocked, so the click that resumes
+            // isn't also a move
+            let pressed = [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+                .into_iter()
+                .any(is_mouse_button_pressed);
+            if classic.is_paused() && (pressed || get_last_key_pressed().is_some()) {
+                classic.unpause();
+            }
+        } else if is_quit_requested() {
+            break;
+        } else if live.config.buttons.reveal.is_pressed() {
+            if let Some((row, col)) = layout.cell_at(&board, mouse_pos) {
+                match online.as_mut() {
+                    Some(online) => online.reveal(row, col),
+                    None => {
+                        let player = flag_capture.turn();
+                        // Both local players share the mouse, so the only
+                        // rejected moves are clicks on revealed cells
+                        let _ = flag_capture.play(&mut board, player, row, col);
+                    }
+                }
+            }
         }
 
         // Draw
-        board.draw();
+        let draw_calls = match (&classic, &online) {
+            (Some(classic), _) => match &review {
+                Some(viewer) => {
+                    let calls = layout.draw(viewer.board());
+                    viewer.draw(&layout);
+                    calls
+                }
+                None => {
+                    classic.draw_backdrop(&layout);
+                    let calls = layout.draw(classic.board());
+                    classic.draw(&layout);
+                    #[cfg(feature = "debug-tools")]
+                    {
+                        debug::draw(classic, &layout);
+                        inspector::draw(classic.board(), &layout, mouse_pos);
+                    }
+                    access.draw(&layout);
+                    effects.draw(&layout);
+                    calls
+                }
+            },
+            (None, Some(online)) => {
+                let calls = layout.draw(&board);
+                draw_scores(
+                    &flag_capture,
+                    layout.height(&board),
+                    online.status(),
+                    live.skin.text,
+                );
+                online.draw(&layout, &board);
+                calls
+            }
+            (None, None) => {
+                let calls = layout.draw(&board);
+                draw_scores(&flag_capture, layout.height(&board), None, live.skin.text);
+                calls
+            }
+        };
+        // Grabbed before the overlays and toasts are drawn over the board
+        if let Some(classic) = classic.as_ref().filter(|_| is_key_pressed(KeyCode::F12)) {
+            toasts.report(
+                match export::screenshot(&classic.screenshot(), &get_screen_data()) {
+                    Ok(path) => Ok(t!("screenshot-saved", path = path.display().to_string())),
+                    Err(err) => Err(t!("screenshot-failed", error = err.to_string())),
+                },
+            );
+        }
+        let shown = classic.as_ref().map_or(&board, Classic::board);
+        let paused = classic.as_ref().is_some_and(Classic::is_paused);
+        if let Some(cell) = layout.cell_at(shown, mouse_pos).filter(|_| !paused) {
+            // Kept clear of the accessible mode's caption bar
+            let bottom = screen_height()
+                - if access.is_enabled() {
+                    scale::px(24.0)
+                } else {
+                    0.0
+                };
+            draw_hover(&layout, cell, &live.config, bottom);
+        }
+        overlay.update(shown, draw_calls);
+        overlay.draw(classic.as_ref());
+        session.update();
+        session.draw();
+        toasts.draw(layout.bounds());
+        console.draw();
+        crt::finish();
 
+        // Online games change without local input, and the overlay graph
+        // and large openings move on their own
+        let flooding = classic.as_ref().is_some_and(Classic::is_busy);
+        let busy = flooding || search.is_some();
+        idle.throttle(
+            online.is_some()
+                || overlay.is_visible()
+                || busy
+                || toasts.is_active()
+                || effects.is_active(),
+        );
         next_frame().await
     }
 }
@@ -0,0 +1,585 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::placement::{self, Generator, Ramp, Uniform};
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::stats::Variant;
+use minesweeper_core::{Board, BoardError, Ruleset};
+
+use crate::appearance::Appearance;
+use crate::i18n::{self, t};
+use crate::mouse::Buttons;
+use crate::preset::Preset;
+use crate::scale;
+use crate::theme;
+
+const WINDOW_SIZE: Vec2 = vec2(400.0, 640.0);
+/// Highest mine density the slider offers, in percent.
+const MAX_DENSITY: f32 = 50.0;
+
+/// The mouse layouts offered in the settings window; any other mapping
+/// from `config.txt` shows as custom.
+const MOUSE_LAYOUTS: [(Buttons, &str); 2] = [
+    (Buttons::RIGHT_HANDED, "menu-mouse-right-handed"),
+    (Buttons::LEFT_HANDED, "menu-mouse-left-handed"),
+];
+
+/// A rough difficulty for a density in percent, as a message id. The
+/// classic beginner, intermediate and expert boards are about 12%, 16%
+/// and 21%.
+fn difficulty(density: f32) -> &'static str {
+    match density {
+        d if d < 13.0 => "difficulty-easy",
+        d if d < 18.0 => "difficulty-medium",
+        d if d < 23.0 => "difficulty-hard",
+        _ => "difficulty-extreme",
+    }
+}
+
+/// A screen opened from the settings window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Page {
+    Achievements,
+    Stats,
+    Seed,
+    Rules,
+}
+
+/// Board size chosen in the custom game dialog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    /// Generate a board that never needs a guess, with its centre opening
+    /// already revealed.
+    pub no_guess: bool,
+    /// Name of the generator that places the mines.
+    pub placement: &'static str,
+    /// Score the game on clicks against 3BV, see `Variant::TRAINER`.
+    pub trainer: bool,
+    /// Move a mine every few seconds, see `Variant::MOVING`.
+    pub moving: bool,
+    /// Search for a board the difficulty rating gives this many stars,
+    /// see `rating::find`.
+    pub stars: Option<u8>,
+    /// The rules picked on the Custom rules screen, see `Variant::CUSTOM`.
+    pub rules: Ruleset,
+}
+
+/// The board a classic game starts with when no size is given.
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 16,
+            height: 16,
+            mines: 50,
+            no_guess: false,
+            placement: Uniform.name(),
+            trainer: false,
+            moving: false,
+            stars: None,
+            rules: Ruleset::CLASSIC,
+        }
+    }
+}
+
+impl Settings {
+    pub fn variant(&self) -> Variant {
+        let mut variant = Variant::placement(self.placement);
+        // No-guess boards are only found for the classic rules
+        if self.no_guess && self.rules.is_classic() {
+            variant = variant.with(Variant::NO_GUESS);
+        }
+        if self.trainer {
+            variant = variant.with(Variant::TRAINER);
+        }
+        if self.moving {
+            variant = variant.with(Variant::MOVING);
+        }
+        if !self.rules.is_classic() {
+            variant = variant.with(Variant::CUSTOM);
+        }
+        variant
+    }
+
+    pub fn code(&self, seed: u64) -> ShareCode {
+        ShareCode {
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            variant: self.variant(),
+            seed,
+            rules: self.rules,
+        }
+    }
+
+    /// Generates the board for `seed`, so the same seed always gives the
+    /// same game.
+    pub fn board(&self, seed: u64) -> Result<Board, BoardError> {
+        self.code(seed).board()
+    }
+}
+
+/// A seed for a new game, from the clock.
+pub fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
+
+/// In-game settings window drawn with macroquad's widget layer. Escape
+/// toggles it, and the board ignores clicks while it is open.
+pub struct Menu {
+    open: bool,
+    width: f32,
+    height: f32,
+    mines: f32,
+    /// Mines as a percentage of the cells. Moving it or resizing the board
+    /// sets the mine count; moving the mine count sets it.
+    density: f32,
+    no_guess: bool,
+    trainer: bool,
+    moving: bool,
+    /// Stars the board should rate, or 0 for any board.
+    stars: usize,
+    /// Index into `placement::ALL` of the generator picked.
+    placement: usize,
+    /// Largest width or height the sliders offer; grows to fit a board
+    /// started from the command line or a preset.
+    max_size: f32,
+    presets: Vec<Preset>,
+    /// The preset picked, counting from 1, or 0 once the sliders leave
+    /// it.
+    preset: usize,
+    /// Index into `i18n::languages()` of the language in use.
+    language: usize,
+    /// Language picked since the last `take_language`.
+    picked: Option<&'static str>,
+    /// The mouse buttons in use.
+    buttons: Buttons,
+    /// Mouse layout picked since the last `take_buttons`.
+    picked_buttons: Option<Buttons>,
+    /// Index into `Appearance::ALL` of the appearance in use.
+    appearance: usize,
+    /// Appearance picked since the last `take_appearance`.
+    picked_appearance: Option<Appearance>,
+    /// The themes in `themes/`, listed again whenever the window opens.
+    themes: Vec<String>,
+    /// The theme in use, or empty for the light and dark ones.
+    theme: String,
+    /// Theme picked since the last `take_theme`.
+    picked_theme: Option<String>,
+    reduced_motion: bool,
+    /// Whether the reduced motion box was toggled since the last
+    /// `take_reduced_motion`.
+    toggled_motion: bool,
+    haptics: bool,
+    /// Vibration strength in percent.
+    haptic_strength: f32,
+    /// Whether the vibration settings changed since the last
+    /// `take_haptics`.
+    changed_haptics: bool,
+    /// Screen asked for since the last `take_page`.
+    page: Option<Page>,
+    /// Whether the session was reset since the last `take_reset_session`.
+    reset_session: bool,
+    /// Whether the seed was asked for since the last `take_copy_seed`.
+    copy_seed: bool,
+    /// Set on the Custom rules screen, see `set_rules`.
+    rules: Ruleset,
+}
+
+impl Menu {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            open: false,
+            max_size: settings.width.max(settings.height).max(30) as f32,
+            width: settings.width as f32,
+            height: settings.height as f32,
+            mines: settings.mines as f32,
+            density: (settings.mines * 100) as f32 / (settings.width * settings.height) as f32,
+            no_guess: settings.no_guess,
+            trainer: settings.trainer,
+            moving: settings.moving,
+            stars: settings.stars.map_or(0, usize::from),
+            placement: placement::ALL
+                .iter()
+                .position(|generator| generator.name() == settings.placement)
+                .unwrap_or(0),
+            presets: Vec::new(),
+            preset: 0,
+            language: i18n::languages()
+                .iter()
+                .position(|(code, _)| *code == i18n::current())
+                .unwrap_or(0),
+            picked: None,
+            buttons: Buttons::default(),
+            picked_buttons: None,
+            appearance: 0,
+            picked_appearance: None,
+            themes: Vec::new(),
+            theme: String::new(),
+            picked_theme: None,
+            reduced_motion: false,
+            toggled_motion: false,
+            haptics: true,
+            haptic_strength: 100.0,
+            changed_haptics: false,
+            page: None,
+            reset_session: false,
+            copy_seed: false,
+            rules: settings.rules,
+        }
+    }
+
+    /// Deals new games by `rules`, as picked on the Custom rules screen.
+    pub fn set_rules(&mut self, rules: Ruleset) {
+        self.rules = rules;
+    }
+
+    /// The language the player switched to, if they did since the last
+    /// call.
+    pub fn take_language(&mut self) -> Option<&'static str> {
+        self.picked.take()
+    }
+
+    /// The mouse layout the player switched to, if they did since the last
+    /// call.
+    pub fn take_buttons(&mut self) -> Option<Buttons> {
+        self.picked_buttons.take()
+    }
+
+    /// Shows the buttons in use, e.g. after the config file changed.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.buttons = buttons;
+    }
+
+    /// The appearance the player switched to, if they did since the last
+    /// call.
+    pub fn take_appearance(&mut self) -> Option<Appearance> {
+        self.picked_appearance.take()
+    }
+
+    /// Shows the appearance in use, e.g. after the config file changed.
+    pub fn set_appearance(&mut self, appearance: Appearance) {
+        self.appearance = Appearance::ALL
+            .iter()
+            .position(|&known| known == appearance)
+            .unwrap_or(0);
+    }
+
+    /// The theme the player switched to, if they did since the last call;
+    /// empty for the light and dark ones.
+    pub fn take_theme(&mut self) -> Option<String> {
+        self.picked_theme.take()
+    }
+
+    /// Shows the theme in use, e.g. after the config file changed.
+    pub fn set_theme(&mut self, name: &str) {
+        self.theme = name.to_owned();
+    }
+
+    /// The reduced motion setting, if the player changed it since the last
+    /// call.
+    pub fn take_reduced_motion(&mut self) -> Option<bool> {
+        std::mem::take(&mut self.toggled_motion).then_some(self.reduced_motion)
+    }
+
+    /// Shows the reduced motion setting in use, e.g. after the config file
+    /// changed.
+    pub fn set_reduced_motion(&mut self, reduced: bool) {
+        self.reduced_motion = reduced;
+    }
+
+    /// The vibration switch and strength, from 0.1 to 1, if the player
+    /// changed them since the last call. A strength still being dragged
+    /// waits until the slider is let go.
+    pub fn take_haptics(&mut self) -> Option<(bool, f32)> {
+        if !self.changed_haptics || is_mouse_button_down(MouseButton::Left) {
+            return None;
+        }
+        self.changed_haptics = false;
+        Some((self.haptics, self.haptic_strength.round() / 100.0))
+    }
+
+    /// Shows the vibration settings in use, e.g. after the config file
+    /// changed.
+    pub fn set_haptics(&mut self, enabled: bool, strength: f32) {
+        self.haptics = enabled;
+        self.haptic_strength = strength * 100.0;
+    }
+
+    /// The screen the player asked for, if they did since the last call.
+    pub fn take_page(&mut self) -> Option<Page> {
+        self.page.take()
+    }
+
+    /// Whether the player reset the session statistics since the last
+    /// call.
+    pub fn take_reset_session(&mut self) -> bool {
+        std::mem::take(&mut self.reset_session)
+    }
+
+    /// Replaces the presets offered, e.g. after the config file changed.
+    pub fn set_presets(&mut self, presets: Vec<Preset>) {
+        self.presets = presets;
+        self.preset = 0;
+    }
+
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    /// Whether the player asked to copy the current game's seed since the
+    /// last call.
+    pub fn take_copy_seed(&mut self) -> bool {
+        std::mem::take(&mut self.copy_seed)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handles the toggle key and draws the window. Returns the new settings
+    /// when the player starts a custom game.
+    pub fn update(&mut self) -> Option<Settings> {
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = !self.open;
+            if self.open {
+                self.themes = theme::names();
+            }
+        }
+        if !self.open {
+            return None;
+        }
+
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let mut start = false;
+        let languages = i18n::languages();
+        let names: Vec<&str> = languages.iter().map(|(_, name)| name.as_str()).collect();
+        let language = self.language;
+        let mines = self.mines;
+        let preset = self.preset;
+        let mut presets = vec![t!("menu-preset-custom")];
+        presets.extend(self.presets.iter().map(Preset::name));
+        let presets: Vec<&str> = presets.iter().map(String::as_str).collect();
+        let placements: Vec<String> = placement::ALL
+            .iter()
+            .map(|generator| i18n::tr(&format!("placement-{}", generator.name()), &[]))
+            .collect();
+        let placements: Vec<&str> = placements.iter().map(String::as_str).collect();
+        let mut stars = vec![t!("menu-stars-any")];
+        stars.extend((1..=5).map(|count| t!("menu-stars-count", count = count)));
+        let stars: Vec<&str> = stars.iter().map(String::as_str).collect();
+        let mut layouts: Vec<String> = MOUSE_LAYOUTS.iter().map(|(_, id)| t!(id)).collect();
+        let mut layout = match MOUSE_LAYOUTS.iter().position(|(b, _)| *b == self.buttons) {
+            Some(layout) => layout,
+            None => {
+                layouts.push(t!("menu-mouse-custom"));
+                MOUSE_LAYOUTS.len()
+            }
+        };
+        let picked_layout = layout;
+        let layouts: Vec<&str> = layouts.iter().map(String::as_str).collect();
+        let appearances: Vec<String> = Appearance::ALL
+            .iter()
+            .map(|appearance| i18n::tr(&format!("menu-appearance-{}", appearance), &[]))
+            .collect();
+        let appearances: Vec<&str> = appearances.iter().map(String::as_str).collect();
+        let appearance = self.appearance;
+        let reduced_motion = self.reduced_motion;
+        let haptics = (self.haptics, self.haptic_strength);
+        let mut themes = vec![t!("menu-theme-default")];
+        themes.extend(self.themes.iter().cloned());
+        let mut theme = match self.themes.iter().position(|name| *name == self.theme) {
+            Some(theme) => theme + 1,
+            None if self.theme.is_empty() => 0,
+            // Named in the config but gone from the directory
+            None => {
+                themes.push(self.theme.clone());
+                themes.len() - 1
+            }
+        };
+        let picked_theme = theme;
+        let themes: Vec<&str> = themes.iter().map(String::as_str).collect();
+        self.open = widgets::Window::new(hash!(), position, size)
+            .label(&t!("menu-title"))
+            .movable(false)
+            .close_button(true)
+            .ui(&mut root_ui(), |ui| {
+                widgets::ComboBox::new(hash!(), &presets)
+                    .label(&t!("menu-preset"))
+                    .ui(ui, &mut self.preset);
+                widgets::Slider::new(hash!(), 5.0..self.max_size)
+                    .label(&t!("menu-width"))
+                    .ui(ui, &mut self.width);
+                widgets::Slider::new(hash!(), 5.0..self.max_size)
+                    .label(&t!("menu-height"))
+                    .ui(ui, &mut self.height);
+                let cells = self.width.round() * self.height.round();
+                widgets::Slider::new(hash!(), 1.0..cells - 1.0)
+                    .label(&t!("menu-mines"))
+                    .ui(ui, &mut self.mines);
+                widgets::Slider::new(hash!(), 1.0..MAX_DENSITY)
+                    .label(&t!("menu-density"))
+                    .ui(ui, &mut self.density);
+                // A ramp's ends say more than the average
+                let result = if placement::ALL[self.placement].name() == Ramp::STEEP.name() {
+                    let (width, height) = (self.width.round(), self.height.round());
+                    let (from, to) = Ramp::STEEP.ends(
+                        width as usize,
+                        height as usize,
+                        self.mines.round() as usize,
+                    );
+                    t!(
+                        "menu-density-ramp",
+                        mines = self.mines.round(),
+                        from = from.round(),
+                        to = to.round()
+                    )
+                } else {
+                    t!(
+                        "menu-density-result",
+                        mines = self.mines.round(),
+                        difficulty = t!(difficulty(self.density))
+                    )
+                };
+                ui.label(None, &result);
+                ui.checkbox(hash!(), &t!("menu-no-guess"), &mut self.no_guess);
+                ui.checkbox(hash!(), &t!("menu-trainer"), &mut self.trainer);
+                ui.checkbox(hash!(), &t!("menu-moving"), &mut self.moving);
+                widgets::ComboBox::new(hash!(), &placements)
+                    .label(&t!("menu-placement"))
+                    .ui(ui, &mut self.placement);
+                widgets::ComboBox::new(hash!(), &stars)
+                    .label(&t!("menu-stars"))
+                    .ui(ui, &mut self.stars);
+                widgets::ComboBox::new(hash!(), &names)
+                    .label(&t!("menu-language"))
+                    .ui(ui, &mut self.language);
+                widgets::ComboBox::new(hash!(), &layouts)
+                    .label(&t!("menu-mouse"))
+                    .ui(ui, &mut layout);
+                widgets::ComboBox::new(hash!(), &appearances)
+                    .label(&t!("menu-appearance"))
+                    .ui(ui, &mut self.appearance);
+                widgets::ComboBox::new(hash!(), &themes)
+                    .label(&t!("menu-theme"))
+                    .ui(ui, &mut theme);
+                ui.checkbox(
+                    hash!(),
+                    &t!("menu-reduced-motion"),
+                    &mut self.reduced_motion,
+                );
+                ui.checkbox(hash!(), &t!("menu-haptics"), &mut self.haptics);
+                widgets::Slider::new(hash!(), 10.0..100.0)
+                    .label(&t!("menu-haptic-strength"))
+                    .ui(ui, &mut self.haptic_strength);
+                ui.separator();
+                start = ui.button(None, t!("menu-new-game").as_str());
+                ui.same_line(0.0);
+                if ui.button(None, t!("menu-achievements").as_str()) {
+                    self.page = Some(Page::Achievements);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("menu-stats").as_str()) {
+                    self.page = Some(Page::Stats);
+                }
+                if ui.button(None, t!("menu-reset-session").as_str()) {
+                    self.reset_session = true;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("menu-play-seed").as_str()) {
+                    self.page = Some(Page::Seed);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("menu-copy-seed").as_str()) {
+                    self.copy_seed = true;
+                }
+                if ui.button(None, t!("menu-custom-rules").as_str()) {
+                    self.page = Some(Page::Rules);
+                }
+                if !self.rules.is_classic() {
+                    ui.same_line(0.0);
+                    ui.label(
+                        None,
+                        &t!("menu-rules-in-use", rules = self.rules.names().join("+")),
+                    );
+                }
+            });
+        if self.page.is_some() {
+            self.open = false;
+        }
+
+        if let Some((buttons, _)) = MOUSE_LAYOUTS
+            .get(layout)
+            .filter(|_| layout != picked_layout)
+        {
+            self.buttons = *buttons;
+            self.picked_buttons = Some(*buttons);
+        }
+        if self.reduced_motion != reduced_motion {
+            self.toggled_motion = true;
+        }
+        if (self.haptics, self.haptic_strength) != haptics {
+            self.changed_haptics = true;
+        }
+        if theme != picked_theme {
+            self.theme = match theme {
+                0 => String::new(),
+                theme => themes[theme].to_owned(),
+            };
+            self.picked_theme = Some(self.theme.clone());
+        }
+        if self.appearance != appearance {
+            self.picked_appearance = Some(Appearance::ALL[self.appearance]);
+        }
+        if self.language != language {
+            let (code, _) = languages[self.language];
+            i18n::set(code);
+            self.picked = Some(code);
+        }
+        if let Some(picked) = self.preset.checked_sub(1).filter(|_| self.preset != preset) {
+            let picked = &self.presets[picked];
+            self.max_size = self.max_size.max(picked.width.max(picked.height) as f32);
+            self.width = picked.width as f32;
+            self.height = picked.height as f32;
+            self.mines = picked.mines as f32;
+        }
+        let width = self.width.round() as usize;
+        let height = self.height.round() as usize;
+        let cells = (width * height) as f32;
+        if self.mines == mines {
+            self.mines = (self.density * cells / 100.0).round();
+        }
+        // Shrinking the board can leave the old mine count out of range
+        self.mines = self.mines.clamp(1.0, cells - 1.0);
+        self.density = self.mines * 100.0 / cells;
+        let size = (width, height, self.mines.round() as usize);
+        if self.preset > 0 && self.presets[self.preset - 1].size() != size {
+            self.preset = 0;
+        }
+        if !start {
+            return None;
+        }
+        self.open = false;
+        Some(self.settings())
+    }
+
+    /// The board the sliders are set to.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            width: self.width.round() as usize,
+            height: self.height.round() as usize,
+            mines: self.mines.round() as usize,
+            no_guess: self.no_guess,
+            placement: placement::ALL[self.placement].name(),
+            trainer: self.trainer,
+            moving: self.moving,
+            stars: (self.stars > 0).then_some(self.stars as u8),
+            rules: self.rules,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Which mouse button reveals, flags and chords, so left-handed players
+//! and players with unusual mice can play without fighting the defaults.
+
+use std::fmt;
+use std::str::FromStr;
+
+use macroquad::prelude::*;
+
+/// A mouse button the game can see. Side buttons never reach the game,
+/// so they have to be mapped to one of these in the mouse's own software.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    /// The action has no button.
+    None,
+}
+
+impl Button {
+    pub fn is_pressed(self) -> bool {
+        match self {
+            Button::Left => is_mouse_button_pressed(MouseButton::Left),
+            Button::Right => is_mouse_button_pressed(MouseButton::Right),
+            Button::Middle => is_mouse_button_pressed(MouseButton::Middle),
+            Button::None => false,
+        }
+    }
+
+    pub fn is_down(self) -> bool {
+        match self {
+            Button::Left => is_mouse_button_down(MouseButton::Left),
+            Button::Right => is_mouse_button_down(MouseButton::Right),
+            Button::Middle => is_mouse_button_down(MouseButton::Middle),
+            Button::None => false,
+        }
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Button::Left => "left",
+            Button::Right => "right",
+            Button::Middle => "middle",
+            Button::None => "none",
+        })
+    }
+}
+
+impl FromStr for Button {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "left" => Ok(Button::Left),
+            "right" => Ok(Button::Right),
+            "middle" => Ok(Button::Middle),
+            "none" => Ok(Button::None),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The button for each action on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Buttons {
+    pub reveal: Button,
+    pub flag: Button,
+    pub chord: Button,
+}
+
+impl Buttons {
+    pub const RIGHT_HANDED: Buttons = Buttons {
+        reveal: Button::Left,
+        flag: Button::Right,
+        chord: Button::Middle,
+    };
+    pub const LEFT_HANDED: Buttons = Buttons {
+        reveal: Button::Right,
+        flag: Button::Left,
+        chord: Button::Middle,
+    };
+
+    /// Whether no button does two things.
+    pub fn is_valid(&self) -> bool {
+        let buttons = [self.reveal, self.flag, self.chord];
+        buttons
+            .iter()
+            .enumerate()
+            .all(|(i, button)| *button == Button::None || !buttons[i + 1..].contains(button))
+    }
+}
+
+impl Default for Buttons {
+    fn default() -> Self {
+        Self::RIGHT_HANDED
+    }
+}
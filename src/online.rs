@@ -0,0 +1,215 @@
+use std::io;
+
+use macroquad::prelude::*;
+use minesweeper_core::protocol::{self, ClientMessage, ServerMessage};
+use minesweeper_core::{debug, trace, warn, Board, FlagCapture};
+
+use crate::chat::Chat;
+use crate::client::Client;
+use crate::crt;
+use crate::i18n::t;
+use crate::layout::{Layout, PLAYER_COLORS};
+
+/// Seconds between heartbeats sent to the server.
+const PING_INTERVAL: f64 = 5.0;
+/// Seconds without hearing from the server before the connection is
+/// reported as lost.
+const SILENCE_TIMEOUT: f64 = 15.0;
+
+/// Client side of a networked flag-capture game.
+pub struct Online {
+    client: Option<Client>,
+    local_player: Option<usize>,
+    status: Option<String>,
+    /// Why the connection failed, since the last `take_trouble`.
+    trouble: Option<String>,
+    cursors: [Option<(f32, f32)>; 2],
+    last_cursor: (f32, f32),
+    last_cursor_sent: f64,
+    last_ping: f64,
+    last_heard: f64,
+    next_nonce: u64,
+    chat: Chat,
+}
+
+impl Online {
+    pub fn connect(addr: &str, room: &str, spectate: bool) -> io::Result<Self> {
+        Ok(Self {
+            client: Some(Client::connect(addr, room, spectate)?),
+            local_player: None,
+            status: None,
+            trouble: None,
+            cursors: [None; 2],
+            last_cursor: (f32::NAN, f32::NAN),
+            last_cursor_sent: 0.0,
+            last_ping: get_time(),
+            last_heard: get_time(),
+            next_nonce: 0,
+            chat: Chat::new(),
+        })
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// What went wrong with the connection, if anything did since the
+    /// last call.
+    pub fn take_trouble(&mut self) -> Option<String> {
+        self.trouble.take()
+    }
+
+    fn lose(&mut self, text: String) {
+        self.trouble = Some(text.clone());
+        self.status = Some(text);
+    }
+
+    fn send(&mut self, message: &ClientMessage) {
+        if let Some(client) = self.client.as_mut() {
+            if let Err(err) = client.send(message) {
+                warn!("could not send to the server: {}", err);
+                self.lose(t!("disconnected", reason = err));
+                self.client = None;
+            }
+        }
+    }
+
+    pub fn reveal(&mut self, row: usize, col: usize) {
+        self.send(&ClientMessage::Reveal { row, col });
+    }
+
+    /// Applies everything the server sent since the last frame and sends
+    /// heartbeats, cursor updates and chat messages.
+    pub fn update(&mut self, board: &mut Board, game: &mut FlagCapture, layout: &Layout) {
+        let Some(client) = self.client.as_mut() else {
+            return;
+        };
+        let messages = match client.poll() {
+            Ok(messages) => messages,
+            Err(err) => {
+                warn!("lost the connection to the server: {}", err);
+                self.lose(t!("disconnected", reason = err));
+                self.client = None;
+                return;
+            }
+        };
+
+        let now = get_time();
+        if !messages.is_empty() {
+            self.last_heard = now;
+        }
+        for message in messages {
+            self.handle(message, board, game);
+        }
+
+        let lost = t!("connection-lost");
+        if now - self.last_heard > SILENCE_TIMEOUT && self.status.as_deref() != Some(&lost) {
+            warn!(
+                "no message from the server for {:.0}s",
+                now - self.last_heard
+            );
+            self.lose(lost);
+        }
+        if now - self.last_ping > PING_INTERVAL {
+            self.last_ping = now;
+            self.next_nonce += 1;
+            self.send(&ClientMessage::Ping {
+                nonce: self.next_nonce,
+            });
+        }
+
+        // Share the pointer with spectators, throttled to ten updates a second
+        if self.local_player.is_some() {
+            let cursor = layout.to_board(crt::mouse_position());
+            if cursor != self.last_cursor && now - self.last_cursor_sent > 0.1 {
+                self.send(&ClientMessage::Cursor {
+                    x: cursor.0,
+                    y: cursor.1,
+                });
+                self.last_cursor = cursor;
+                self.last_cursor_sent = now;
+            }
+        }
+
+        if let Some(text) = self.chat.update() {
+            self.send(&ClientMessage::Chat { text });
+        }
+    }
+
+    fn handle(&mut self, message: ServerMessage, board: &mut Board, game: &mut FlagCapture) {
+        trace!("received {}", message.encode());
+        match message {
+            ServerMessage::Hello { .. } | ServerMessage::Pong { .. } | ServerMessage::Rooms(_) => {}
+            ServerMessage::Incompatible {
+                min_version,
+                max_version,
+            } => {
+                self.status = Some(t!(
+                    "server-needs-protocol",
+                    min = min_version,
+                    max = max_version
+                ));
+            }
+            ServerMessage::Welcome { player } => {
+                self.local_player = Some(player);
+                self.status = Some(t!("you-are-player", player = player + 1));
+            }
+            ServerMessage::Spectating => {
+                self.status = Some(t!("spectating"));
+            }
+            ServerMessage::State {
+                width,
+                height,
+                mines,
+                scores,
+                turn,
+                cells,
+            } => {
+                (*board, *game) = protocol::restore(width, height, mines, scores, turn, &cells);
+                self.update_turn_status(turn);
+            }
+            ServerMessage::Delta {
+                scores,
+                turn,
+                changes,
+            } => {
+                if !protocol::apply_delta(board, game, scores, turn, &changes) {
+                    warn!("server sent scores past the mines on the board");
+                    return;
+                }
+                self.update_turn_status(turn);
+            }
+            ServerMessage::Cursor { player, x, y } => {
+                if let Some(cursor) = self.cursors.get_mut(player) {
+                    *cursor = Some((x, y));
+                }
+            }
+            ServerMessage::Chat { from, text } => self.chat.push(from, text),
+            ServerMessage::Error(message) => {
+                debug!("server rejected a message: {}", message);
+                self.status = Some(message);
+            }
+        }
+    }
+
+    fn update_turn_status(&mut self, turn: usize) {
+        if let Some(player) = self.local_player {
+            self.status = Some(if player == turn {
+                t!("your-turn")
+            } else {
+                t!("opponents-turn")
+            });
+        }
+    }
+
+    pub fn draw(&self, layout: &Layout, board: &Board) {
+        for (player, cursor) in self.cursors.iter().enumerate() {
+            if let Some(cursor) = cursor {
+                let (x, y) = layout.to_screen(*cursor);
+                draw_circle(x, y, 6.0, PLAYER_COLORS[player]);
+                draw_circle_lines(x, y, 6.0, 2.0, WHITE);
+            }
+        }
+        self.chat.draw(layout.height(board) - 4.0);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+use minesweeper_core::agent::View;
+use minesweeper_core::solver::Solver;
+use minesweeper_core::Board;
+
+use crate::classic::Classic;
+use crate::font;
+use crate::i18n::t;
+use crate::scale;
+
+/// Frames kept for the frame time graph.
+const HISTORY: usize = 120;
+const WIDTH: f32 = 260.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+/// Largest openings listed by size.
+const OPENINGS_LISTED: usize = 5;
+/// Frame time drawn as the top of the graph, in seconds.
+const GRAPH_SCALE: f32 = 1.0 / 30.0;
+
+/// Performance overlay toggled with F3.
+pub struct Overlay {
+    visible: bool,
+    frame_times: VecDeque<f32>,
+    draw_calls: usize,
+    generation: Option<Duration>,
+    solver: Option<Duration>,
+    /// Kept between frames, so the timing is of the solving alone.
+    scratch: Solver,
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(HISTORY),
+            draw_calls: 0,
+            generation: None,
+            solver: None,
+            scratch: Solver::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Records how long the current board took to generate.
+    pub fn set_generation(&mut self, time: Duration) {
+        self.generation = Some(time);
+    }
+
+    /// Samples this frame. `draw_calls` is the number of draw calls the
+    /// board renderer issued.
+    pub fn update(&mut self, board: &Board, draw_calls: usize) {
+        if is_key_pressed(KeyCode::F3) {
+            self.visible = !self.visible;
+        }
+        if self.frame_times.len() == HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(get_frame_time());
+        self.draw_calls = draw_calls;
+
+        // Only pay for a solver pass while someone is looking at the result
+        if self.visible {
+            let start = Instant::now();
+            self.scratch.single_point(&View::of(board));
+            self.solver = Some(start.elapsed());
+        }
+    }
+
+    /// Draws the overlay, with what is known about the board if it is a
+    /// classic game.
+    pub fn draw(&self, classic: Option<&Classic>) {
+        if !self.visible {
+            return;
+        }
+
+        let width = scale::px(WIDTH);
+        let x = screen_width() - width - 10.0;
+        let y = 10.0;
+        let millis = |time: Option<Duration>| {
+            time.map_or("-".to_owned(), |time| {
+                format!("{:.2}ms", time.as_secs_f64() * 1000.0)
+            })
+        };
+        let mut lines = vec![
+            t!("overlay-fps", fps = get_fps()),
+            t!(
+                "overlay-frame",
+                time = format!("{:.2}ms", get_frame_time() * 1000.0)
+            ),
+            t!("overlay-draw-calls", count = self.draw_calls),
+            t!("overlay-generation", time = millis(self.generation)),
+            t!("overlay-solver", time = millis(self.solver)),
+        ];
+        if let Some(classic) = classic {
+            let openings = classic.openings();
+            let mut sizes = openings.sizes().to_vec();
+            sizes.sort_unstable_by(|a, b| b.cmp(a));
+            let mut listed: Vec<String> = sizes
+                .iter()
+                .take(OPENINGS_LISTED)
+                .map(usize::to_string)
+                .collect();
+            if sizes.len() > OPENINGS_LISTED {
+                listed.push("...".to_owned());
+            }
+            lines.push(t!(
+                "overlay-openings",
+                count = openings.len(),
+                sizes = listed.join(", ")
+            ));
+            if let Some(benchmarks) = classic.benchmarks() {
+                lines.push(t!(
+                    "overlay-benchmarks",
+                    bbbv = benchmarks.bbbv,
+                    zini = benchmarks.zini,
+                    hzini = benchmarks.hzini
+                ));
+            }
+        }
+
+        let step = scale::px(20.0);
+        let height = lines.len() as f32 * step + GRAPH_HEIGHT + 20.0;
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+        for (i, line) in lines.iter().enumerate() {
+            font::line(line, x + 8.0, y + 5.0 + i as f32 * step, 20.0, WHITE);
+        }
+
+        // Frame time graph, with a line at 60 FPS
+        let bottom = y + height - 8.0;
+        let bar = (width - 16.0) / HISTORY as f32;
+        for (i, time) in self.frame_times.iter().enumerate() {
+            let h = (time / GRAPH_SCALE).min(1.0) * GRAPH_HEIGHT;
+            let color = if *time > 1.0 / 55.0 { RED } else { GREEN };
+            draw_rectangle(x + 8.0 + i as f32 * bar, bottom - h, bar, h, color);
+        }
+        let target = bottom - (1.0 / 60.0) / GRAPH_SCALE * GRAPH_HEIGHT;
+        draw_line(x + 8.0, target, x + width - 8.0, target, 1.0, YELLOW);
+    }
+}
@@ -0,0 +1,82 @@
+//! Named boards offered in the settings window: the classic three, a few
+//! extras, and any defined in `config.txt` as `preset.NAME` fields.
+
+use crate::i18n;
+
+/// Board sizes with their names, written `WIDTHxHEIGHTxMINES` or with a
+/// density as `WIDTHxHEIGHTxPERCENT%`.
+const BUILTIN: &[(&str, &str)] = &[
+    ("beginner", "9x9x10"),
+    ("intermediate", "16x16x40"),
+    ("expert", "30x16x99"),
+    ("evil", "30x20x130"),
+    ("blitz", "8x8x12"),
+    ("dense", "20x20x25%"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Name {
+    /// Shipped with the game, with a `preset-ID` message.
+    Builtin(&'static str),
+    /// From the config file, shown as written.
+    Custom(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preset {
+    pub name: Name,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+impl Preset {
+    /// Reads a board written as in `BUILTIN`. Densities are rounded to
+    /// whole mines.
+    pub fn parse(name: Name, board: &str) -> Option<Self> {
+        let mut parts = board.trim().splitn(3, 'x');
+        let width: usize = parts.next()?.parse().ok()?;
+        let height: usize = parts.next()?.parse().ok()?;
+        let mines = parts.next()?;
+        let mines = match mines.strip_suffix('%') {
+            Some(percent) => {
+                let percent: f64 = percent.parse().ok()?;
+                (percent * (width * height) as f64 / 100.0).round() as usize
+            }
+            None => mines.parse().ok()?,
+        };
+        (width * height > mines && mines > 0).then_some(Self {
+            name,
+            width,
+            height,
+            mines,
+        })
+    }
+
+    /// The name to show, in the current language for shipped presets.
+    pub fn name(&self) -> String {
+        match &self.name {
+            Name::Builtin(id) => i18n::tr(&format!("preset-{}", id), &[]),
+            Name::Custom(name) => name.clone(),
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.mines)
+    }
+}
+
+pub fn builtin() -> Vec<Preset> {
+    BUILTIN
+        .iter()
+        .filter_map(|(id, board)| Preset::parse(Name::Builtin(id), board))
+        .collect()
+}
+
+/// The name of the first preset with this board, if any.
+pub fn name_of(presets: &[Preset], size: (usize, usize, usize)) -> Option<String> {
+    presets
+        .iter()
+        .find(|preset| preset.size() == size)
+        .map(Preset::name)
+}
@@ -0,0 +1,171 @@
+//! Emergency saves. A panic hook writes the classic game in progress to
+//! disk before the process dies, and the next launch offers to restore it.
+//! A game the player quits in the middle of is kept the same way.
+
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::{error, info, warn, Board};
+
+use crate::i18n::t;
+use crate::scale;
+use crate::storage;
+
+const WINDOW_SIZE: Vec2 = vec2(300.0, 110.0);
+
+/// The last settled position of the game in progress, copied out of the
+/// main loop so the panic hook never reads a board halfway through a move.
+static SNAPSHOT: Mutex<Option<Board>> = Mutex::new(None);
+
+/// Why a game was left unfinished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Reason {
+    Crash,
+    Quit,
+}
+
+impl Reason {
+    fn path(self) -> PathBuf {
+        storage::data_dir().join(match self {
+            Reason::Crash => "emergency.save",
+            Reason::Quit => "quit.save",
+        })
+    }
+}
+
+/// Keeps a game the player is quitting, for the next launch to offer.
+pub fn save(board: &Board) {
+    let path = Reason::Quit.path();
+    match storage::write(&path, &save::save(board, Format::Binary)) {
+        Ok(()) => info!("saved the game in progress to {}", path.display()),
+        Err(err) => warn!("could not save the game in progress: {}", err),
+    }
+}
+
+/// Installs the panic hook. The default hook still runs afterwards to print
+/// the panic message.
+pub fn install() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // The panicking thread may be the one holding the lock
+        if let Ok(snapshot) = SNAPSHOT.try_lock() {
+            if let Some(board) = snapshot.as_ref() {
+                let path = Reason::Crash.path();
+                match storage::write(&path, &save::save(board, Format::Binary)) {
+                    Ok(()) => eprintln!("Saved the game in progress to {}", path.display()),
+                    Err(err) => eprintln!("Could not save the game in progress: {}", err),
+                }
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Keeps the emergency snapshot in step with the game. Cheap when nothing
+/// changed since the last call.
+#[derive(Default)]
+pub struct Recorder {
+    revision: Option<u64>,
+}
+
+impl Recorder {
+    /// Call once per frame with the current board, or `None` when there is
+    /// no game worth restoring.
+    pub fn record(&mut self, board: Option<&Board>) {
+        let revision = board.map(Board::revision);
+        if revision == self.revision {
+            return;
+        }
+        self.revision = revision;
+        if let Ok(mut snapshot) = SNAPSHOT.lock() {
+            *snapshot = board.cloned();
+        }
+    }
+}
+
+pub enum Answer {
+    Restore(Board),
+    Discard,
+}
+
+/// A game left behind by a crash or a quit, waiting for the player to
+/// restore or discard it.
+pub struct Prompt {
+    board: Board,
+    reason: Reason,
+}
+
+impl Prompt {
+    /// Looks for a game saved by an earlier run, the crashed one first.
+    pub fn pending() -> Option<Self> {
+        [Reason::Crash, Reason::Quit]
+            .into_iter()
+            .find_map(|reason| {
+                let path = reason.path();
+                let bytes = fs::read(&path).ok()?;
+                match save::load(&bytes) {
+                    Ok(board) => {
+                        info!("found a saved game at {}", path.display());
+                        Some(Self { board, reason })
+                    }
+                    Err(err) => {
+                        warn!("ignoring unreadable saved game: {}", err);
+                        discard(reason);
+                        None
+                    }
+                }
+            })
+    }
+
+    /// Draws the question and returns the player's answer once they give
+    /// one. The save is deleted either way.
+    pub fn update(&self) -> Option<Answer> {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let mut answer = None;
+        widgets::Window::new(hash!(), position, size)
+            .label(&t!("restore-title"))
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(
+                    None,
+                    &t!(match self.reason {
+                        Reason::Crash => "restore-crashed",
+                        Reason::Quit => "restore-quit",
+                    }),
+                );
+                ui.label(
+                    None,
+                    &t!(
+                        "restore-summary",
+                        width = self.board.width(),
+                        height = self.board.height(),
+                        mines = self.board.mines(),
+                        revealed = self.board.revealed()
+                    ),
+                );
+                ui.separator();
+                if ui.button(None, t!("restore").as_str()) {
+                    answer = Some(Answer::Restore(self.board.clone()));
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("discard").as_str()) {
+                    answer = Some(Answer::Discard);
+                }
+            });
+        if answer.is_some() {
+            discard(self.reason);
+        }
+        answer
+    }
+}
+
+fn discard(reason: Reason) {
+    if let Err(err) = fs::remove_file(reason.path()) {
+        error!("could not remove {}: {}", reason.path().display(), err);
+    }
+}
@@ -0,0 +1,296 @@
+//! The post-game review screen: the board is shown as it was before each
+//! move, with the move outlined and the review's note on it underneath.
+//! P lays each hidden cell's chance of a mine over the board. A strip
+//! under the note lays the moves out in time, and clicking it jumps to the
+//! move made closest to that moment.
+
+use std::time::Duration;
+
+use macroquad::prelude::*;
+use minesweeper_core::agent::View;
+use minesweeper_core::replay::Mark;
+use minesweeper_core::review::{Review, Verdict};
+use minesweeper_core::solver::{Analysis, Probabilities};
+use minesweeper_core::Board;
+
+use crate::crt;
+use crate::font;
+use crate::i18n::{self, t};
+use crate::layout::{Layout, STATUS_MARGIN};
+use crate::trail::Trail;
+
+/// Height of the timeline strip, which fits under the note in the status
+/// line.
+const STRIP_HEIGHT: f32 = 10.0;
+/// Width of the stretches of time the strip's shading counts moves in.
+const BIN_WIDTH: f32 = 4.0;
+
+pub struct Viewer {
+    review: Review,
+    /// The move shown, or `review.len()` for the end of the game.
+    position: usize,
+    /// The chances on the board shown, as far as `analysis` got.
+    chances: Option<Probabilities>,
+    /// Works out the chances on the board shown, while the overlay is on.
+    analysis: Option<Analysis>,
+    /// The sample budget for the chances, see `Probabilities::with_budget`.
+    samples: usize,
+    /// When each move was made and what it did, one for each note.
+    timeline: Vec<(Duration, Mark)>,
+    /// The move that led to the position shown, marked for `trail_time`.
+    trail: Option<Trail>,
+    trail_time: Duration,
+}
+
+impl Viewer {
+    pub fn new(review: Review, samples: usize) -> Self {
+        Self {
+            review,
+            position: 0,
+            chances: None,
+            analysis: None,
+            samples,
+            timeline: Vec::new(),
+            trail: None,
+            trail_time: Duration::ZERO,
+        }
+    }
+
+    /// Shows `timeline`, the game's `Replay::marks`, under the note.
+    pub fn with_timeline(mut self, timeline: Vec<(Duration, Mark)>) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Marks the move that led to each position for `time`, see `Trail`.
+    pub fn with_trail(mut self, time: Duration) -> Self {
+        self.trail_time = time;
+        self
+    }
+
+    /// Left and Right step through the moves, Home and End jump to either
+    /// end, and a click on the timeline jumps to the move made nearest
+    /// that moment. Returns false once the player closes it with Escape.
+    pub fn update(&mut self, layout: &Layout) -> bool {
+        let (position, overlay) = (self.position, self.analysis.is_some());
+        if is_key_pressed(KeyCode::Left) {
+            self.position = self.position.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Right) {
+            self.position = (self.position + 1).min(self.review.len());
+        }
+        if is_key_pressed(KeyCode::Home) {
+            self.position = 0;
+        }
+        if is_key_pressed(KeyCode::End) {
+            self.position = self.review.len();
+        }
+        let strip = self.strip(layout);
+        let (x, y) = crt::mouse_position();
+        if is_mouse_button_pressed(MouseButton::Left) && strip.contains(vec2(x, y)) {
+            let at = self.length().mul_f32((x - strip.x) / strip.w);
+            let nearest = self
+                .timeline
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (when, _))| when.abs_diff(at))
+                .map(|(i, _)| i);
+            if let Some(i) = nearest {
+                self.position = i.min(self.review.len());
+            }
+        }
+        if self.position != position {
+            self.trail = self
+                .position
+                .checked_sub(1)
+                .filter(|_| !self.trail_time.is_zero())
+                .map(|last| {
+                    let step = self.review.notes()[last].step;
+                    Trail::new(self.review.position(last), step.position())
+                });
+        }
+        // The chances are only worked out for the classic rules, which are
+        // all the solver models
+        let overlay = overlay != is_key_pressed(KeyCode::P) && self.board().rules().is_classic();
+        if !overlay {
+            self.analysis = None;
+            self.chances = None;
+        } else if self.analysis.is_none() || self.position != position {
+            // Dropping the last analysis stops it
+            self.chances = None;
+            self.analysis = Some(Analysis::start(View::of(self.board()), self.samples));
+        }
+        if let Some(chances) = self.analysis.as_ref().and_then(Analysis::poll) {
+            self.chances = Some(chances);
+        }
+        !is_key_pressed(KeyCode::Escape)
+    }
+
+    /// The board to draw.
+    pub fn board(&self) -> &Board {
+        self.review.position(self.position)
+    }
+
+    /// Draws the note in place of the status line, and outlines the move.
+    pub fn draw(&self, layout: &Layout) {
+        if let Some(chances) = &self.chances {
+            draw_chances(chances, self.board(), layout);
+        }
+        if let Some(trail) = &self.trail {
+            trail.draw(self.board(), layout, self.trail_time);
+        }
+        let top = layout.height(self.board());
+        let text = match self.review.notes().get(self.position) {
+            Some(note) => {
+                let (row, col) = note.step.position();
+                let tile = layout.tile(row, col);
+                draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 6.0, color(note.verdict));
+                format!(
+                    "{} {}",
+                    t!(
+                        "review-move",
+                        step = self.position + 1,
+                        steps = self.review.len()
+                    ),
+                    i18n::message(&note.message())
+                )
+            }
+            None => {
+                let count = |matches: fn(&Verdict) -> bool| {
+                    self.review
+                        .notes()
+                        .iter()
+                        .filter(|note| matches(&note.verdict))
+                        .count()
+                };
+                t!(
+                    "review-end",
+                    forced = count(|verdict| *verdict == Verdict::Forced),
+                    guesses = count(|verdict| matches!(
+                        verdict,
+                        Verdict::Guess { .. } | Verdict::Unforced { .. }
+                    )),
+                    blunders = count(|verdict| *verdict == Verdict::Blunder)
+                )
+            }
+        };
+        font::line(&text, 10.0, top + STATUS_MARGIN, 30.0, layout.skin().text);
+        self.draw_timeline(layout);
+    }
+
+    /// How long the game took, up to its last move.
+    fn length(&self) -> Duration {
+        self.timeline.last().map_or(Duration::ZERO, |&(at, _)| at)
+    }
+
+    /// Where the timeline goes: across the window, under the note.
+    fn strip(&self, layout: &Layout) -> Rect {
+        let top = layout.height(self.board());
+        Rect::new(10.0, top + 37.0, screen_width() - 20.0, STRIP_HEIGHT)
+    }
+
+    /// Shades the lower half of the strip by how many moves fell in each
+    /// stretch of it, ticks each move in the upper half in its kind's
+    /// colour, and marks the move shown.
+    fn draw_timeline(&self, layout: &Layout) {
+        if self.timeline.is_empty() {
+            return;
+        }
+        let strip = self.strip(layout);
+        let length = self.length().as_secs_f32();
+        let x = |at: Duration| {
+            let share = if length > 0.0 {
+                at.as_secs_f32() / length
+            } else {
+                0.0
+            };
+            strip.x + strip.w * share
+        };
+        draw_rectangle(strip.x, strip.y, strip.w, strip.h, LIGHTGRAY);
+
+        let bins = (strip.w / BIN_WIDTH).max(1.0) as usize;
+        let mut counts = vec![0usize; bins];
+        for &(at, _) in &self.timeline {
+            let bin = ((x(at) - strip.x) / BIN_WIDTH) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+        let most = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let half = strip.h / 2.0;
+        for (bin, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let shade = Color::new(0.1, 0.2, 0.6, 0.2 + 0.8 * count as f32 / most);
+            let left = strip.x + bin as f32 * BIN_WIDTH;
+            draw_rectangle(left, strip.y + half, BIN_WIDTH, half, shade);
+        }
+        for &(at, mark) in &self.timeline {
+            let x = x(at);
+            draw_line(x, strip.y, x, strip.y + half, 1.0, mark_color(mark));
+        }
+
+        let shown = match self.timeline.get(self.position) {
+            Some(&(at, _)) => x(at),
+            None => strip.x + strip.w,
+        };
+        draw_line(
+            shown,
+            strip.y - 2.0,
+            shown,
+            strip.y + strip.h + 2.0,
+            2.0,
+            BLACK,
+        );
+    }
+}
+
+/// Tints each hidden cell by its chance of a mine, with the chance in
+/// percent on it; estimates are marked with a `~`, and sampled ones
+/// carry their margin underneath.
+fn draw_chances(chances: &Probabilities, board: &Board, layout: &Layout) {
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let Some(chance) = chances.get(row, col) else {
+                continue;
+            };
+            let tile = layout.tile(row, col);
+            let tint = Color::new(1.0, 0.0, 0.0, 0.6 * chance.mine as f32);
+            draw_rectangle(tile.x, tile.y, tile.w, tile.h, tint);
+            let percent = format!(
+                "{}{:.0}",
+                if chance.exact { "" } else { "~" },
+                chance.mine * 100.0
+            );
+            let size = (tile.h * 0.45).max(8.0);
+            // The percentage in the upper part, the margin under it
+            let (upper, lower) = (
+                Rect::new(tile.x, tile.y, tile.w, tile.h * 0.65),
+                Rect::new(tile.x, tile.y + tile.h * 0.6, tile.w, tile.h * 0.4),
+            );
+            match chance.margin {
+                Some(margin) => {
+                    font::on_tile(&percent, upper, size, BLACK);
+                    let margin = format!("±{:.0}", margin * 100.0);
+                    font::on_tile(&margin, lower, size * 0.7, BLACK);
+                }
+                None => font::on_tile(&percent, tile, size, BLACK),
+            }
+        }
+    }
+}
+
+fn mark_color(mark: Mark) -> Color {
+    match mark {
+        Mark::Click => BLUE,
+        Mark::Opening => GREEN,
+        Mark::Flag => RED,
+        Mark::Chord => PURPLE,
+    }
+}
+
+fn color(verdict: Verdict) -> Color {
+    match verdict {
+        Verdict::Forced | Verdict::Flag { proven: true } => GREEN,
+        Verdict::Unforced { .. } => ORANGE,
+        Verdict::Guess { .. } => GOLD,
+        Verdict::Blunder => RED,
+        _ => GRAY,
+    }
+}
@@ -0,0 +1,160 @@
+//! The Custom rules screen, where the variant options are combined before
+//! a game starts.
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::placement::{Generator, Gradient, Uniform};
+use minesweeper_core::rules::{Adjacency, Ruleset};
+use minesweeper_core::share::ShareCode;
+
+use crate::i18n::{self, t};
+use crate::menu::{self, Settings};
+use crate::scale;
+
+const WINDOW_SIZE: Vec2 = vec2(420.0, 390.0);
+/// Most of the mines, in percent, the sliders make duds or big.
+const MAX_KIND_SHARE: f32 = 50.0;
+
+/// Edits the rules of the board the settings window is set to. The game
+/// it starts goes into the share code, so it can be dealt again.
+pub struct Builder {
+    settings: Settings,
+    wrap: bool,
+    /// Index into `Adjacency::ALL`.
+    adjacency: usize,
+    lives: f32,
+    gradient: bool,
+    liar: bool,
+    /// Cells seen around uncovered ones, 0 for no fog.
+    fog: f32,
+    blind: bool,
+    /// Percent of the mines that are duds, and that are big.
+    duds: f32,
+    bigs: f32,
+    /// The game asked for, until `take_code`.
+    chosen: Option<ShareCode>,
+}
+
+impl Builder {
+    pub fn open(settings: Settings) -> Self {
+        let rules = settings.rules;
+        Self {
+            wrap: rules.wrap,
+            adjacency: Adjacency::ALL
+                .iter()
+                .position(|&adjacency| adjacency == rules.adjacency)
+                .unwrap_or(0),
+            lives: rules.lives as f32,
+            gradient: settings.placement == Gradient.name(),
+            liar: rules.liar,
+            fog: rules.fog as f32,
+            blind: rules.blind,
+            duds: rules.duds as f32,
+            bigs: rules.bigs as f32,
+            settings,
+            chosen: None,
+        }
+    }
+
+    /// The game the player started, once.
+    pub fn take_code(&mut self) -> Option<ShareCode> {
+        self.chosen.take()
+    }
+
+    /// The rules as the boxes are set, keeping whatever else the settings
+    /// had.
+    fn rules(&self) -> Ruleset {
+        Ruleset {
+            adjacency: Adjacency::ALL[self.adjacency],
+            wrap: self.wrap,
+            lives: self.lives.round() as u8,
+            liar: self.liar,
+            fog: self.fog.round() as u8,
+            blind: self.blind,
+            duds: self.duds.round() as u8,
+            bigs: self.bigs.round() as u8,
+            ..self.settings.rules
+        }
+    }
+
+    /// The settings a game started here is dealt from. Rated and no-guess
+    /// boards are only found for the classic rules.
+    pub fn settings(&self) -> Settings {
+        let rules = self.rules();
+        let custom = !rules.is_classic();
+        let placement = match (self.gradient, self.settings.placement == Gradient.name()) {
+            (true, _) => Gradient.name(),
+            (false, true) => Uniform.name(),
+            (false, false) => self.settings.placement,
+        };
+        Settings {
+            rules,
+            placement,
+            no_guess: self.settings.no_guess && !custom,
+            stars: self.settings.stars.filter(|_| !custom),
+            ..self.settings
+        }
+    }
+
+    /// Draws the window. Returns false once the player closes it or starts
+    /// a game.
+    pub fn update(&mut self) -> bool {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let kernels: Vec<String> = Adjacency::ALL
+            .iter()
+            .map(|adjacency| i18n::tr(&format!("rules-adjacency-{}", adjacency.name()), &[]))
+            .collect();
+        let kernels: Vec<&str> = kernels.iter().map(String::as_str).collect();
+        let (mut start, mut done) = (false, false);
+        let open = widgets::Window::new(hash!(), position, size)
+            .label(&t!("rules-title"))
+            .movable(false)
+            .close_button(true)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(None, &t!("rules-help"));
+                widgets::ComboBox::new(hash!(), &kernels)
+                    .label(&t!("rules-adjacency"))
+                    .ui(ui, &mut self.adjacency);
+                ui.checkbox(hash!(), &t!("rules-wrap"), &mut self.wrap);
+                widgets::Slider::new(hash!(), 1.0..Ruleset::MAX_LIVES as f32)
+                    .label(&t!("rules-lives"))
+                    .ui(ui, &mut self.lives);
+                ui.checkbox(hash!(), &t!("rules-gradient"), &mut self.gradient);
+                ui.checkbox(hash!(), &t!("rules-liar"), &mut self.liar);
+                widgets::Slider::new(hash!(), 0.0..Ruleset::MAX_FOG as f32)
+                    .label(&t!("rules-fog"))
+                    .ui(ui, &mut self.fog);
+                ui.checkbox(hash!(), &t!("rules-blind"), &mut self.blind);
+                widgets::Slider::new(hash!(), 0.0..MAX_KIND_SHARE)
+                    .label(&t!("rules-duds"))
+                    .ui(ui, &mut self.duds);
+                widgets::Slider::new(hash!(), 0.0..MAX_KIND_SHARE)
+                    .label(&t!("rules-bigs"))
+                    .ui(ui, &mut self.bigs);
+                // The code without the seed, which is drawn when the game starts
+                let code = self.code(0).to_string();
+                let (deal, _) = code.rsplit_once('-').unwrap_or((&code, ""));
+                ui.label(None, &t!("rules-code", code = deal));
+                ui.separator();
+                start = ui.button(None, t!("rules-start").as_str());
+                ui.same_line(0.0);
+                if ui.button(None, t!("rules-reset").as_str()) {
+                    *self = Self::open(Settings {
+                        rules: Ruleset::CLASSIC,
+                        ..self.settings
+                    });
+                }
+                ui.same_line(0.0);
+                done = ui.button(None, t!("close").as_str());
+            });
+        if start || is_key_pressed(KeyCode::Enter) {
+            self.chosen = Some(self.code(menu::seed()));
+            return false;
+        }
+        open && !done && !is_key_pressed(KeyCode::Escape)
+    }
+
+    fn code(&self, seed: u64) -> ShareCode {
+        self.settings().code(seed)
+    }
+}
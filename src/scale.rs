@@ -0,0 +1,93 @@
+//! The UI scale, `ui_scale` in `config.txt` from 0.75 to 2: HUD text and
+//! panels, the settings windows and the smallest tile grow or shrink with
+//! it, while the board still zooms to fit the window on its own.
+//!
+//! HUD code works in pixels at 100% and passes lengths through `px`; the
+//! `font` functions scale text sizes themselves.
+
+use std::cell::Cell;
+
+use macroquad::math::RectOffset;
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, Skin, Ui};
+
+/// macroquad's own UI text size, which the settings windows use.
+const UI_FONT: f32 = 16.0;
+const UI_MARGIN: f32 = 2.0;
+const UI_TITLE: f32 = 14.0;
+
+thread_local! {
+    static SCALE: Cell<f32> = const { Cell::new(1.0) };
+}
+
+pub fn get() -> f32 {
+    SCALE.get()
+}
+
+/// A length in pixels at 100%, at the scale in use.
+pub fn px(length: f32) -> f32 {
+    length * get()
+}
+
+/// Where to put a settings window of `size` at 100%, centred, and its size
+/// at the scale in use.
+pub fn window(size: Vec2) -> (Vec2, Vec2) {
+    let size = size * get();
+    let position = (vec2(screen_width(), screen_height()) - size) / 2.0;
+    (position.max(Vec2::ZERO), size)
+}
+
+/// Switches to `scale`, restyling macroquad's UI to match.
+pub fn set(scale: f32) {
+    let old = SCALE.replace(scale);
+    if old == scale {
+        return;
+    }
+    let mut ui = root_ui();
+    if old != 1.0 {
+        ui.pop_skin();
+    }
+    if scale != 1.0 {
+        let skin = skin(&ui, scale);
+        ui.push_skin(&skin);
+    }
+}
+
+/// The default UI skin with its text and the space around it scaled. The
+/// text styles are rebuilt with the default colours, as macroquad offers no
+/// way to change the size of an existing style.
+fn skin(ui: &Ui, scale: f32) -> Skin {
+    let size = (UI_FONT * scale).round() as u16;
+    let margin = UI_MARGIN * scale;
+    let margins = RectOffset::new(margin, margin, margin, margin);
+    let text = || ui.style_builder().font_size(size).text_color(BLACK);
+    Skin {
+        label_style: text()
+            .margin(margins)
+            .color_inactive(Color::from_rgba(0, 0, 0, 128))
+            .build(),
+        button_style: text()
+            .margin(margins)
+            .color(Color::from_rgba(204, 204, 204, 235))
+            .color_hovered(Color::from_rgba(170, 170, 170, 235))
+            .color_clicked(Color::from_rgba(187, 187, 187, 255))
+            .build(),
+        editbox_style: text()
+            .color_selected(Color::from_rgba(200, 200, 200, 255))
+            .build(),
+        checkbox_style: text()
+            .color(Color::from_rgba(200, 200, 200, 255))
+            .color_hovered(Color::from_rgba(210, 210, 210, 255))
+            .color_clicked(Color::from_rgba(150, 150, 150, 255))
+            .color_selected(Color::from_rgba(128, 128, 128, 255))
+            .color_selected_hovered(Color::from_rgba(140, 140, 140, 255))
+            .build(),
+        window_titlebar_style: text()
+            .color(Color::from_rgba(68, 68, 68, 255))
+            .color_inactive(Color::from_rgba(102, 102, 102, 127))
+            .build(),
+        margin,
+        title_height: UI_TITLE * scale,
+        ..ui.default_skin()
+    }
+}
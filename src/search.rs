@@ -0,0 +1,144 @@
+//! Boards of a wanted difficulty, and boards that never need a guess,
+//! searched for on a thread of their own so the window keeps drawing. A
+//! small window shows how far the search got and lets the player give up
+//! on it, which stops the thread too.
+
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::generate::{self, Progress};
+use minesweeper_core::rating;
+use minesweeper_core::share::ShareCode;
+use minesweeper_core::{Board, BoardError};
+
+use crate::i18n::t;
+use crate::menu::Settings;
+use crate::scale;
+
+const WINDOW_SIZE: Vec2 = vec2(300.0, 90.0);
+
+type Found = Result<(ShareCode, Board), BoardError>;
+
+/// What the search is after.
+#[derive(Clone, Copy, Debug)]
+enum Goal {
+    Stars(u8),
+    NoGuess,
+}
+
+pub struct Search {
+    goal: Goal,
+    started: Instant,
+    /// Candidates tried so far, counted by the searching thread.
+    progress: Arc<Progress>,
+    /// Taken by `finish`.
+    handle: Option<JoinHandle<Found>>,
+}
+
+impl Search {
+    /// Starts looking for a board of `settings` rated `stars` stars, from
+    /// `seed` on.
+    pub fn start(settings: Settings, stars: u8, seed: u64) -> Self {
+        Self::spawn(Goal::Stars(stars), move |progress| {
+            let deal = |seed| settings.board(seed);
+            let (seed, board) = rating::find(deal, stars, seed, progress)?;
+            Ok((settings.code(seed), board))
+        })
+    }
+
+    /// Starts dealing the no-guess board `code` stands for, which can take
+    /// thousands of candidates on a large board.
+    pub fn deal(code: ShareCode) -> Self {
+        Self::spawn(Goal::NoGuess, move |progress| {
+            Ok((code, code.board_with(progress)?))
+        })
+    }
+
+    /// Starts dealing the board a new game of `settings` would get from
+    /// `seed`: a rated one if it asks for stars, otherwise the board the
+    /// seed deals.
+    pub fn ahead(settings: Settings, seed: u64) -> Self {
+        match settings.stars {
+            Some(stars) => Self::start(settings, stars, seed),
+            None => Self::deal(settings.code(seed)),
+        }
+    }
+
+    fn spawn(goal: Goal, find: impl FnOnce(&Progress) -> Found + Send + 'static) -> Self {
+        let progress = Arc::new(Progress::default());
+        let shared = Arc::clone(&progress);
+        Self {
+            goal,
+            started: Instant::now(),
+            progress,
+            handle: Some(thread::spawn(move || find(&shared))),
+        }
+    }
+
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// The board found and the code that deals it again. Blocks until the
+    /// search is over.
+    pub fn finish(mut self) -> Found {
+        self.handle
+            .take()
+            .expect("a search is only finished once")
+            .join()
+            .expect("the board search panicked")
+    }
+
+    /// Draws the progress window. Returns false when the player gave up on
+    /// the search; the thread then stops at its next candidate and is
+    /// ignored.
+    pub fn update(&self) -> bool {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let tried = self.progress.tried();
+        let (title, progress) = match self.goal {
+            Goal::Stars(stars) => (
+                t!("search-title", stars = stars),
+                t!(
+                    "search-progress",
+                    tried = tried,
+                    attempts = rating::MAX_ATTEMPTS
+                ),
+            ),
+            Goal::NoGuess => (
+                t!("search-no-guess-title"),
+                t!(
+                    "search-no-guess-progress",
+                    tried = tried,
+                    attempts = generate::MAX_ATTEMPTS
+                ),
+            ),
+        };
+        let mut cancelled = false;
+        widgets::Window::new(hash!(), position, size)
+            .label(&title)
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(None, &progress);
+                ui.separator();
+                cancelled = ui.button(None, t!("search-cancel").as_str());
+            });
+        if cancelled {
+            self.progress.cancel();
+        }
+        !cancelled
+    }
+}
+
+/// A search nobody waits for any more stops at its next candidate.
+impl Drop for Search {
+    fn drop(&mut self) {
+        self.progress.cancel();
+    }
+}
@@ -0,0 +1,92 @@
+//! The Play seed screen, and copying the current game's share code.
+
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use minesweeper_core::info;
+use minesweeper_core::share::ShareCode;
+
+use crate::i18n::t;
+use crate::menu::Settings;
+use crate::scale;
+
+const WINDOW_SIZE: Vec2 = vec2(460.0, 170.0);
+
+/// Puts `code` on the clipboard.
+pub fn copy(code: &ShareCode) {
+    clipboard_set(&code.to_string());
+    info!("copied share code {}", code);
+}
+
+/// Takes a share code, or a bare seed for a board like the current one,
+/// typed or pasted.
+pub struct Entry {
+    /// The board a bare seed is played on.
+    settings: Settings,
+    text: String,
+    /// Why the text can't be played, after the player tried.
+    error: Option<String>,
+    /// The game asked for, until `take_code`.
+    chosen: Option<ShareCode>,
+}
+
+impl Entry {
+    pub fn open(settings: Settings) -> Self {
+        Self {
+            settings,
+            text: String::new(),
+            error: None,
+            chosen: None,
+        }
+    }
+
+    /// The game the player asked for, once.
+    pub fn take_code(&mut self) -> Option<ShareCode> {
+        self.chosen.take()
+    }
+
+    /// Draws the window. Returns false once the player closes it or picks
+    /// a game.
+    pub fn update(&mut self) -> bool {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let (mut play, mut done) = (false, false);
+        let open = widgets::Window::new(hash!(), position, size)
+            .label(&t!("seed-title"))
+            .movable(false)
+            .close_button(true)
+            .ui(&mut root_ui(), |ui| {
+                ui.label(None, &t!("seed-help"));
+                ui.input_text(hash!(), &t!("seed-code"), &mut self.text);
+                if let Some(error) = &self.error {
+                    ui.label(None, &t!("seed-invalid", error = error.as_str()));
+                }
+                ui.separator();
+                play = ui.button(None, t!("seed-play").as_str());
+                ui.same_line(0.0);
+                if ui.button(None, t!("seed-paste").as_str()) {
+                    if let Some(text) = clipboard_get() {
+                        self.text = text.trim().to_owned();
+                    }
+                }
+                ui.same_line(0.0);
+                done = ui.button(None, t!("close").as_str());
+            });
+        if play || is_key_pressed(KeyCode::Enter) {
+            match self.parse() {
+                Ok(code) => {
+                    self.chosen = Some(code);
+                    return false;
+                }
+                Err(error) => self.error = Some(error),
+            }
+        }
+        open && !done && !is_key_pressed(KeyCode::Escape)
+    }
+
+    fn parse(&self) -> Result<ShareCode, String> {
+        match self.text.trim().parse::<u64>() {
+            Ok(seed) => Ok(self.settings.code(seed)),
+            Err(_) => self.text.parse(),
+        }
+    }
+}
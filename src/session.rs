@@ -0,0 +1,79 @@
+use macroquad::prelude::*;
+use minesweeper_core::stats::{Record, Session};
+
+use crate::font;
+use crate::i18n::t;
+use crate::scale;
+
+const WIDTH: f32 = 200.0;
+
+/// Corner widget with the games finished since launch, toggled with F4
+/// and reset from the settings window.
+pub struct SessionWidget {
+    visible: bool,
+    session: Session,
+}
+
+impl SessionWidget {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            session: Session::default(),
+        }
+    }
+
+    pub fn add(&mut self, record: &Record) {
+        self.session.add(record);
+    }
+
+    pub fn reset(&mut self) {
+        self.session = Session::default();
+    }
+
+    pub fn update(&mut self) {
+        if is_key_pressed(KeyCode::F4) {
+            self.visible = !self.visible;
+        }
+    }
+
+    /// Draws in the top left corner once a game has finished.
+    pub fn draw(&self) {
+        if !self.visible || self.session.games == 0 {
+            return;
+        }
+        let lines = [
+            t!(
+                "session-games",
+                games = self.session.games,
+                wins = self.session.wins
+            ),
+            t!(
+                "session-time",
+                time = self
+                    .session
+                    .average_time()
+                    .map_or("-".to_owned(), |time| format!("{:.1}s", time.as_secs_f64()))
+            ),
+            t!(
+                "session-speed",
+                speed = self
+                    .session
+                    .average_speed()
+                    .map_or("-".to_owned(), |speed| format!("{:.2}", speed))
+            ),
+        ];
+        let (x, y) = (10.0, 10.0);
+        let step = scale::px(20.0);
+        let height = lines.len() as f32 * step + 10.0;
+        draw_rectangle(
+            x,
+            y,
+            scale::px(WIDTH),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            font::line(line, x + 8.0, y + 5.0 + i as f32 * step, 20.0, WHITE);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! The record of finished games, kept in `stats.txt` in the data
+//! directory.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+
+use minesweeper_core::achievement::Achievement;
+use minesweeper_core::history::{self, Format as HistoryFormat};
+use minesweeper_core::save::{self, Format};
+use minesweeper_core::stats::{Configuration, Heatmap, Record, Stats, Streak};
+use minesweeper_core::Board;
+use minesweeper_core::{info, warn};
+
+use crate::export;
+use crate::i18n::{self, t};
+use crate::preset::{self, Preset};
+use crate::scale;
+use crate::storage;
+
+const WINDOW_SIZE: Vec2 = vec2(460.0, 520.0);
+/// The largest the heatmap is drawn, in either direction.
+const HEATMAP_SIZE: f32 = 200.0;
+
+fn path() -> PathBuf {
+    storage::data_dir().join("stats.txt")
+}
+
+/// Every game recorded so far. `None` if the file exists but can't be
+/// read, so callers don't overwrite it.
+pub fn load() -> Option<Stats> {
+    let path = path();
+    let Ok(bytes) = fs::read(&path) else {
+        return Some(Stats::default());
+    };
+    match save::load(&bytes) {
+        Ok(stats) => Some(stats),
+        Err(err) => {
+            warn!("could not load {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// What a finished game brought.
+#[derive(Default)]
+pub struct Recorded {
+    pub unlocked: Vec<&'static Achievement>,
+    /// The personal best the game beat, if it beat one.
+    pub beaten: Option<Duration>,
+}
+
+/// Adds a finished game and the cells clicked in it to the file.
+pub fn record(record: Record, clicks: &[(usize, usize)]) -> Recorded {
+    let Some(mut stats) = load() else {
+        warn!("not recording the game, so the broken stats file is kept");
+        return Recorded::default();
+    };
+    stats.add_clicks(record.width, record.height, clicks);
+    let beaten = stats
+        .best_in(record.configuration())
+        .map(|best| best.time)
+        .filter(|&best| record.won && record.time < best);
+    let unlocked = stats.push(record);
+    match store(&stats) {
+        Ok(()) => info!("recorded the game in {}", path().display()),
+        Err(err) => warn!("{}", err),
+    }
+    Recorded { unlocked, beaten }
+}
+
+/// Replaces the file with `stats`.
+pub fn store(stats: &Stats) -> Result<(), String> {
+    let path = path();
+    storage::write(&path, &save::save(stats, Format::Text))
+        .map_err(|err| format!("could not save {}: {}", path.display(), err))
+}
+
+/// Merges the games in `bytes`, a history export or another stats file,
+/// into the file, and returns how many of them were new.
+pub fn import(bytes: &[u8]) -> Result<usize, String> {
+    let mut stats = load().ok_or("the stats file could not be read")?;
+    let imported = history::import(bytes).map_err(|err| err.to_string())?;
+    let added = stats.merge(imported);
+    store(&stats)?;
+    info!("imported {} new games into {}", added, path().display());
+    Ok(added)
+}
+
+/// Imports every file in `imports` in the data directory, and returns
+/// how many games were new.
+fn import_folder() -> Result<usize, String> {
+    let dir = storage::data_dir().join("imports");
+    let entries =
+        fs::read_dir(&dir).map_err(|err| format!("could not read {}: {}", dir.display(), err))?;
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    let mut added = 0;
+    for path in paths.iter().filter(|path| path.is_file()) {
+        let bytes =
+            fs::read(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        added += import(&bytes).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    Ok(added)
+}
+
+/// The win streak a new game on `board` continues.
+pub fn streak(board: &Board) -> Streak {
+    load()
+        .unwrap_or_default()
+        .streak(board.width(), board.height(), board.mines())
+}
+
+/// The IOE of every trainer win so far, oldest first.
+pub fn trainer_ioes() -> Vec<f64> {
+    load().unwrap_or_default().trainer_ioes()
+}
+
+/// The statistics screen: games, wins, best time and streaks for every
+/// difficulty played, and where the player clicks on each board size.
+pub struct Page {
+    stats: Stats,
+    /// To name the boards that have a preset.
+    presets: Vec<Preset>,
+    /// Index into the heatmaps of the one shown.
+    selected: usize,
+    /// The shown heatmap as a texture, and which one it is.
+    texture: Option<(usize, Texture2D)>,
+    /// Where the history was last exported to or how many games were
+    /// imported, or why that failed.
+    exported: Option<String>,
+}
+
+impl Page {
+    pub fn open(presets: Vec<Preset>) -> Self {
+        Self {
+            stats: load().unwrap_or_default(),
+            presets,
+            selected: 0,
+            texture: None,
+            exported: None,
+        }
+    }
+
+    /// Draws the window. Returns false once the player closes it.
+    pub fn update(&mut self) -> bool {
+        let (position, size) = scale::window(WINDOW_SIZE);
+        let mut done = false;
+        let open = widgets::Window::new(hash!(), position, size)
+            .label(&t!("stats-title"))
+            .movable(false)
+            .close_button(true)
+            .ui(&mut root_ui(), |ui| {
+                let sizes = self.stats.sizes();
+                if sizes.is_empty() {
+                    ui.label(None, &t!("stats-empty"));
+                }
+                for (width, height, mines) in sizes {
+                    let games = self.stats.games(width, height, mines).count();
+                    let wins = self
+                        .stats
+                        .games(width, height, mines)
+                        .filter(|record| record.won)
+                        .count();
+                    let best = self.stats.best(width, height, mines).map_or_else(
+                        || "-".to_owned(),
+                        |record| format!("{:.1}s", record.time.as_secs_f64()),
+                    );
+                    let streak = self.stats.streak(width, height, mines);
+                    let efficiency = self.stats.efficiency(width, height, mines);
+                    let size = format!("{}x{} / {}", width, height, mines);
+                    match preset::name_of(&self.presets, (width, height, mines)) {
+                        Some(name) => ui.label(None, &format!("{}: {}", name, size)),
+                        None => ui.label(None, &size),
+                    }
+                    ui.label(
+                        None,
+                        &format!(
+                            "    {}",
+                            t!(
+                                "stats-row",
+                                games = games,
+                                wins = wins,
+                                best = best,
+                                current = streak.current,
+                                streak = streak.best
+                            )
+                        ),
+                    );
+                    if let Some(efficiency) = efficiency {
+                        ui.label(
+                            None,
+                            &format!(
+                                "    {}",
+                                t!(
+                                    "stats-efficiency",
+                                    ioe = format!("{:.2}", efficiency.ioe),
+                                    throughput = format!("{:.2}", efficiency.throughput),
+                                    rqp = format!("{:.1}", efficiency.rqp)
+                                )
+                            ),
+                        );
+                    }
+                }
+                let configurations = self.stats.configurations();
+                if !configurations.is_empty() {
+                    ui.separator();
+                    ui.label(None, &t!("stats-best-times"));
+                }
+                for configuration in configurations {
+                    let best = self.stats.best_in(configuration).map_or_else(
+                        || "-".to_owned(),
+                        |record| format!("{:.1}s", record.time.as_secs_f64()),
+                    );
+                    let described = describe(&configuration, &self.presets);
+                    ui.label(None, &format!("    {}: {}", described, best));
+                }
+                if !self.stats.records.is_empty() {
+                    let (openings, numbers) =
+                        self.stats.records.iter().fold((0, 0), |(o, n), record| {
+                            (o + record.opening_clicks, n + record.number_clicks)
+                        });
+                    ui.separator();
+                    ui.label(
+                        None,
+                        &t!("stats-clicks", openings = openings, numbers = numbers),
+                    );
+                }
+                if !self.stats.heatmaps.is_empty() {
+                    ui.separator();
+                    let names: Vec<String> = self
+                        .stats
+                        .heatmaps
+                        .iter()
+                        .map(|map| format!("{}x{}", map.width(), map.height()))
+                        .collect();
+                    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                    widgets::ComboBox::new(hash!(), &names)
+                        .label(&t!("stats-heatmap"))
+                        .ui(ui, &mut self.selected);
+                    let map = &self.stats.heatmaps[self.selected];
+                    if self.texture.as_ref().map(|(i, _)| *i) != Some(self.selected) {
+                        self.texture = Some((self.selected, texture(map)));
+                    }
+                    let scale = HEATMAP_SIZE / map.width().max(map.height()) as f32;
+                    let size = vec2(map.width() as f32, map.height() as f32) * scale;
+                    let mut canvas = ui.canvas();
+                    let position = canvas.request_space(size);
+                    if let Some((_, texture)) = &self.texture {
+                        canvas.image(Rect::new(position.x, position.y, size.x, size.y), texture);
+                    }
+                }
+                ui.separator();
+                let mut export = None;
+                if ui.button(None, t!("stats-export-csv").as_str()) {
+                    export = Some(HistoryFormat::Csv);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("stats-export-json").as_str()) {
+                    export = Some(HistoryFormat::Json);
+                }
+                if let Some(format) = export {
+                    self.exported = Some(match export::history(&self.stats.records, format) {
+                        Ok(path) => t!("stats-exported", path = path.display().to_string()),
+                        Err(err) => t!("stats-export-failed", error = err.to_string()),
+                    });
+                }
+                ui.same_line(0.0);
+                if ui.button(None, t!("stats-import").as_str()) {
+                    self.exported = Some(match import_folder() {
+                        Ok(games) => {
+                            self.stats = load().unwrap_or_default();
+                            self.texture = None;
+                            self.selected = 0;
+                            t!("stats-imported", games = games)
+                        }
+                        Err(error) => t!("stats-import-failed", error = error),
+                    });
+                }
+                if let Some(exported) = &self.exported {
+                    ui.label(None, exported);
+                }
+                done = ui.button(None, t!("close").as_str());
+            });
+        open && !done && !is_key_pressed(KeyCode::Escape)
+    }
+}
+
+/// A configuration in words: size, mines and density, then its variant
+/// flags and whether it was assisted.
+fn describe(configuration: &Configuration, presets: &[Preset]) -> String {
+    let size = (
+        configuration.width,
+        configuration.height,
+        configuration.mines,
+    );
+    let mut parts: Vec<String> = preset::name_of(presets, size).into_iter().collect();
+    parts.push(t!(
+        "stats-configuration",
+        width = configuration.width,
+        height = configuration.height,
+        mines = configuration.mines,
+        density = format!("{:.1}", configuration.density())
+    ));
+    parts.extend(
+        configuration
+            .variant
+            .names()
+            .map(|name| i18n::tr(&format!("variant-{}", name), &[])),
+    );
+    if configuration.assisted {
+        parts.push(t!("stats-assisted"));
+    }
+    parts.join(", ")
+}
+
+/// One pixel per cell: dark grey where nobody clicked, otherwise blue for
+/// rarely clicked cells through to red for the most clicked one. The
+/// square root keeps a few hot cells from washing out the rest.
+fn texture(map: &Heatmap) -> Texture2D {
+    let mut image = Image::gen_image_color(map.width() as u16, map.height() as u16, DARKGRAY);
+    let max = map.max().max(1) as f32;
+    for row in 0..map.height() {
+        for col in 0..map.width() {
+            let count = map.get(row, col);
+            if count > 0 {
+                let heat = (count as f32 / max).sqrt();
+                let color = Color::new(heat, 0.2, 1.0 - heat, 1.0);
+                image.set_pixel(col as u32, row as u32, color);
+            }
+        }
+    }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    texture
+}
@@ -0,0 +1,28 @@
+//! Where the game keeps files between runs.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `$XDG_DATA_HOME/minesweeper`, falling back to `%APPDATA%` on Windows and
+/// `~/.local/share` elsewhere.
+pub fn data_dir() -> PathBuf {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .unwrap_or_default()
+        .join("minesweeper")
+}
+
+/// Writes `bytes` next to `path` and renames it into place, so a crash
+/// halfway through never leaves a truncated file behind.
+pub fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes)?;
+    fs::rename(&partial, path)
+}
@@ -0,0 +1,124 @@
+//! Painting moves by dragging: holding the flag button over hidden cells
+//! flags each one, and holding the reveal button over numbers whose flags
+//! are all placed chords each one.
+
+use std::collections::HashSet;
+
+use minesweeper_core::{Board, CellState, CellType};
+
+use crate::classic::Classic;
+use crate::layout::Layout;
+use crate::mouse::Button;
+
+/// Points sampled per cell crossed between two frames, so a fast drag
+/// can't skip a cell.
+const SAMPLES_PER_CELL: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Paint {
+    /// Flags hidden cells, or unflags flagged ones when the stroke started
+    /// by removing a flag.
+    Flag {
+        flagged: bool,
+    },
+    Chord,
+}
+
+/// A drag in progress, from the press that started it until the button is
+/// let go.
+pub struct Stroke {
+    paint: Paint,
+    button: Button,
+    /// Where the pointer was on the last frame.
+    last: (f32, f32),
+    /// Cells already painted, each of which is only painted once.
+    done: HashSet<(usize, usize)>,
+}
+
+impl Stroke {
+    /// A stroke of flags started by the flag press on `cell`, which has
+    /// already been played.
+    pub fn flag(board: &Board, button: Button, pos: (f32, f32), cell: (usize, usize)) -> Self {
+        let flagged = board.cell(cell.0, cell.1).cell_state == CellState::Flagged;
+        Self::new(Paint::Flag { flagged }, button, pos, cell)
+    }
+
+    /// A stroke of chords started by the reveal press on `cell`, which has
+    /// already been played.
+    pub fn chord(button: Button, pos: (f32, f32), cell: (usize, usize)) -> Self {
+        Self::new(Paint::Chord, button, pos, cell)
+    }
+
+    fn new(paint: Paint, button: Button, last: (f32, f32), cell: (usize, usize)) -> Self {
+        Self {
+            paint,
+            button,
+            last,
+            done: HashSet::from([cell]),
+        }
+    }
+
+    /// Paints every new cell the pointer crossed since the last frame.
+    /// Returns false once the button is let go and the stroke is over.
+    pub fn update(&mut self, classic: &mut Classic, layout: &Layout, pos: (f32, f32)) -> bool {
+        if !self.button.is_down() {
+            return false;
+        }
+        let from = layout.to_board(self.last);
+        let to = layout.to_board(pos);
+        let cells = (to.0 - from.0).abs().max((to.1 - from.1).abs());
+        let samples = (cells * SAMPLES_PER_CELL).ceil() as usize;
+        for i in 1..=samples {
+            let t = i as f32 / samples as f32;
+            let point = (
+                self.last.0 + (pos.0 - self.last.0) * t,
+                self.last.1 + (pos.1 - self.last.1) * t,
+            );
+            let Some(cell) = layout.cell_at(classic.board(), point) else {
+                continue;
+            };
+            if self.done.insert(cell) {
+                self.paint(classic, cell);
+            }
+        }
+        self.last = pos;
+        !classic.is_over()
+    }
+
+    fn paint(&self, classic: &mut Classic, (row, col): (usize, usize)) {
+        let board = classic.board();
+        let cell = board.cell(row, col);
+        match self.paint {
+            Paint::Flag { flagged } => {
+                let wanted = if flagged {
+                    CellState::Hidden
+                } else {
+                    CellState::Flagged
+                };
+                if cell.cell_state == wanted {
+                    classic.flag(row, col);
+                }
+            }
+            Paint::Chord => {
+                if cell.cell_state == CellState::Visible && opens_anything(board, row, col) {
+                    classic.chord(row, col);
+                }
+            }
+        }
+    }
+}
+
+/// Whether chording the number at the cell would reveal something: its
+/// flags are all placed and a hidden neighbour is left.
+fn opens_anything(board: &Board, row: usize, col: usize) -> bool {
+    let CellType::Number(number) = board.cell(row, col).cell_type else {
+        return false;
+    };
+    let states: Vec<CellState> = board
+        .adjacent(row, col)
+        .into_iter()
+        .map(|(r, c)| board.cell(r, c).cell_state)
+        .collect();
+    let flags = states.iter().filter(|&&s| s == CellState::Flagged).count();
+    flags == number && states.contains(&CellState::Hidden)
+}
@@ -0,0 +1,260 @@
+//! Keeps the config, themes and statistics the same on every machine the
+//! game is played on, through a WebDAV folder the player provides.
+//!
+//! Sync is off until `sync.txt` in the data directory names the folder:
+//!
+//! ```text
+//! minesweeper sync 1
+//! url http://example.com/dav/minesweeper/
+//! user me
+//! password secret
+//! ```
+//!
+//! `sync.txt` itself is never uploaded. The statistics are merged both
+//! ways, so no game is lost whichever machine played it; the config and
+//! themes, including every file in `themes/`, can't be merged, and the
+//! copy changed last wins, see `minesweeper_core::sync`. A deleted theme
+//! comes back from the server on the next sync.
+//!
+//! Saved games stay on the machine that saved them: the one kept on quit
+//! or after a crash is deleted once it has been offered, and a deletion
+//! can't be told from a file this machine never had, so a synced save
+//! would be offered again after every sync.
+//!
+//! There is no TLS here, so only `http://` folders work; a WebDAV server
+//! on the local network, or a local proxy such as stunnel in front of a
+//! remote one, keeps the password off the open internet.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+use minesweeper_core::history;
+use minesweeper_core::save::{self, Document, Format, SaveError, Saved};
+use minesweeper_core::sync::{self, Action, Folder, Manifest};
+use minesweeper_core::{info, warn};
+
+use crate::stats;
+use crate::storage;
+use crate::theme;
+
+/// Files whose newer copy replaces the older one.
+const NEWEST_WINS: [&str; 3] = ["config.txt", "theme.txt", "theme-dark.txt"];
+const MANIFEST: &str = "manifest.txt";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Only one sync runs at a time, so a sync after a game can't race one
+/// still running from the start.
+static RUNNING: Mutex<()> = Mutex::new(());
+
+/// Where to sync to, from `sync.txt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// An `http://` URL of the folder, ending with `/`.
+    pub url: String,
+    pub user: String,
+    pub password: String,
+}
+
+impl Saved for Settings {
+    const KIND: &'static str = "sync";
+
+    fn save(&self, document: &mut Document) {
+        document.set("url", &self.url);
+        document.set("user", &self.user);
+        document.set("password", &self.password);
+    }
+
+    fn load(document: &Document) -> Result<Self, SaveError> {
+        Ok(Self {
+            url: document.get("url")?.to_owned(),
+            user: document.get("user").unwrap_or_default().to_owned(),
+            password: document.get("password").unwrap_or_default().to_owned(),
+        })
+    }
+}
+
+/// What a sync did.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    /// Games that came from the server.
+    pub games: usize,
+}
+
+/// Syncs now. `None` when sync isn't set up.
+pub fn run() -> Option<Result<Report, String>> {
+    let path = storage::data_dir().join("sync.txt");
+    let bytes = fs::read(&path).ok()?;
+    let settings: Settings = match save::load(&bytes) {
+        Ok(settings) => settings,
+        Err(err) => return Some(Err(format!("{}: {}", path.display(), err))),
+    };
+    let _running = RUNNING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    Some(
+        Folder::new(&settings.url, &settings.user, &settings.password)
+            .and_then(|folder| sync_with(&folder)),
+    )
+}
+
+/// Syncs on another thread, reporting the outcome in the log.
+pub fn spawn() {
+    thread::spawn(|| match run() {
+        Some(Ok(report)) => info!(
+            "synced: uploaded {:?}, downloaded {:?}, {} games from the server",
+            report.uploaded, report.downloaded, report.games
+        ),
+        Some(Err(err)) => warn!("could not sync: {}", err),
+        None => {}
+    });
+}
+
+fn sync_with(folder: &Folder) -> Result<Report, String> {
+    let mut report = Report::default();
+    create(folder, "")?;
+    create(folder, "themes/")?;
+    let mut manifest: Manifest = match get(folder, MANIFEST)? {
+        Some(bytes) => save::load(&bytes).map_err(|err| format!("{}: {}", MANIFEST, err))?,
+        None => Manifest::default(),
+    };
+    let mut names: Vec<String> = NEWEST_WINS.map(str::to_owned).to_vec();
+    let themes = theme::names()
+        .into_iter()
+        .map(|name| format!("themes/{}.txt", name));
+    let remote = manifest.names().map(str::to_owned);
+    for name in themes.chain(remote).filter(|name| sync::is_theme(name)) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let dir = storage::data_dir();
+    for name in names {
+        let name = name.as_str();
+        let path = dir.join(name);
+        let local = modified(&path);
+        match sync::resolve(local, manifest.get(name)) {
+            Action::Upload => {
+                let bytes = fs::read(&path)
+                    .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+                put(folder, name, &bytes)?;
+                manifest.set(name, local.unwrap_or_default());
+                report.uploaded.push(name.to_owned());
+            }
+            Action::Download => {
+                let Some(bytes) = get(folder, name)? else {
+                    continue;
+                };
+                let at = manifest.get(name).unwrap_or_default();
+                storage::write(&path, &bytes)
+                    .and_then(|()| set_modified(&path, at))
+                    .map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+                report.downloaded.push(name.to_owned());
+            }
+            Action::Keep => {}
+        }
+    }
+    put(folder, MANIFEST, &save::save(&manifest, Format::Text))?;
+
+    let mut local = stats::load().ok_or("the stats file could not be read")?;
+    let remote = match get(folder, "stats.txt")? {
+        Some(bytes) => history::import(&bytes).map_err(|err| format!("stats.txt: {}", err))?,
+        None => Default::default(),
+    };
+    let merged = {
+        let mut merged = local.clone();
+        report.games = merged.merge(remote.clone());
+        merged
+    };
+    if merged != local {
+        local = merged;
+        stats::store(&local)?;
+        report.downloaded.push("stats.txt".to_owned());
+    }
+    if local != remote {
+        put(folder, "stats.txt", &save::save(&local, Format::Text))?;
+        report.uploaded.push("stats.txt".to_owned());
+    }
+    Ok(report)
+}
+
+/// When the file at `path` last changed, in seconds since the epoch.
+fn modified(path: &Path) -> Option<u64> {
+    let time = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|time| time.as_secs())
+}
+
+/// Dates a downloaded file as the copy it replaced the local one with,
+/// so it isn't taken for a newer edit on the next sync.
+fn set_modified(path: &Path, at: u64) -> io::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(UNIX_EPOCH + Duration::from_secs(at))
+}
+
+/// Makes the folder, or the one called `name` inside it, either of which
+/// may already be there.
+fn create(folder: &Folder, name: &str) -> Result<(), String> {
+    let (status, _) = request(folder, "MKCOL", name, &[])?;
+    match status {
+        // Created, or already there
+        201 | 405 => Ok(()),
+        status => Err(format!(
+            "could not create {}{}: HTTP {}",
+            folder.path(),
+            name,
+            status
+        )),
+    }
+}
+
+/// The file's contents, or `None` if the server has no such file.
+fn get(folder: &Folder, name: &str) -> Result<Option<Vec<u8>>, String> {
+    match request(folder, "GET", name, &[])? {
+        (200, body) => Ok(Some(body)),
+        (404, _) => Ok(None),
+        (status, _) => Err(format!("could not download {}: HTTP {}", name, status)),
+    }
+}
+
+fn put(folder: &Folder, name: &str, body: &[u8]) -> Result<(), String> {
+    match request(folder, "PUT", name, body)? {
+        (200..=299, _) => Ok(()),
+        (status, _) => Err(format!("could not upload {}: HTTP {}", name, status)),
+    }
+}
+
+/// Sends one request on a fresh connection and returns the status and
+/// body of the reply.
+fn request(
+    folder: &Folder,
+    method: &str,
+    name: &str,
+    body: &[u8],
+) -> Result<(u16, Vec<u8>), String> {
+    let failed = |err: io::Error| format!("{}:{}: {}", folder.host(), folder.port(), err);
+    let address = (folder.host(), folder.port())
+        .to_socket_addrs()
+        .map_err(failed)?
+        .next()
+        .ok_or_else(|| format!("{} has no address", folder.host()))?;
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT).map_err(failed)?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(failed)?;
+    stream.set_write_timeout(Some(TIMEOUT)).map_err(failed)?;
+
+    let head = folder.head(method, name, body.len());
+    stream.write_all(head.as_bytes()).map_err(failed)?;
+    stream.write_all(body).map_err(failed)?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).map_err(failed)?;
+    sync::parse_reply(&reply).ok_or_else(|| format!("{} sent a malformed reply", folder.host()))
+}
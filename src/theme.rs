@@ -0,0 +1,173 @@
+//! Themes the player drops into `themes/` in the data directory. Each
+//! `NAME.txt` there shows up as NAME in the settings window's theme list.
+//!
+//! A theme file is a theme save like `theme.txt`, so it starts with a
+//! `minesweeper theme 1` header and sets any of the same colours. On top
+//! of those it takes:
+//!
+//! ```text
+//! base dark
+//! font fonts/pixel.ttf
+//! sprite.mine mine.png
+//! ```
+//!
+//! `base` is `light` or `dark`: which built-in colours the ones left out
+//! come from. `font` replaces the font from `config.txt`, and a
+//! `sprite.FACE` image is drawn over the hidden, flag, mine or empty
+//! tiles; paths are relative to `themes/`. Every mistake in the file is
+//! reported at once, so a theme can be fixed in one go.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use minesweeper_core::console::{Command, Registry};
+use minesweeper_core::save::Document;
+
+use crate::appearance::Appearance;
+use crate::atlas::Skin;
+use crate::config::parse_hex;
+use crate::console::{Context, Handler};
+use crate::storage;
+
+pub struct Theme {
+    pub skin: Skin,
+    /// Absolute path of the theme's font, if it has one.
+    pub font: Option<String>,
+}
+
+pub fn dir() -> PathBuf {
+    storage::data_dir().join("themes")
+}
+
+/// The file of the theme called `name`.
+pub fn path(name: &str) -> PathBuf {
+    dir().join(format!("{}.txt", name))
+}
+
+/// The themes in `themes/`, by name in alphabetical order.
+pub fn names() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "txt").then_some(())?;
+            Some(path.file_stem()?.to_str()?.to_owned())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Loads the theme called `name`, or says everything wrong with its file.
+pub fn load(name: &str) -> Result<Theme, String> {
+    let file = path(name);
+    let shown = Path::new("themes").join(format!("{}.txt", name));
+    let bytes = fs::read(&file).map_err(|err| format!("{}: {}", shown.display(), err))?;
+    let document =
+        Document::decode(&bytes).map_err(|err| format!("{}: {}", shown.display(), err))?;
+    if document.kind() != "theme" {
+        return Err(format!(
+            "{}: starts as a {} file; a theme starts with `minesweeper theme 1`",
+            shown.display(),
+            document.kind()
+        ));
+    }
+    let mut problems = Vec::new();
+    let mut skin = match document.get("base") {
+        Ok("dark") => Skin::dark(),
+        Ok("light") | Err(_) => Skin::default(),
+        Ok(other) => {
+            problems.push(format!("`base {}`: expected light or dark", other));
+            Skin::default()
+        }
+    };
+    let mut font = None;
+    for (key, value) in document.fields() {
+        if key == "base" {
+            continue;
+        }
+        if key == "font" {
+            let path = dir().join(value);
+            if path.is_file() {
+                font = Some(path.to_string_lossy().into_owned());
+            } else {
+                problems.push(format!("`font {}`: no such file in themes/", value));
+            }
+        } else if let Some(face) = key.strip_prefix("sprite.") {
+            let sprite = skin
+                .sprites
+                .all()
+                .into_iter()
+                .find(|(known, _)| *known == face)
+                .map(|(_, sprite)| sprite);
+            match sprite {
+                Some(sprite) => match load_sprite(&dir().join(value)) {
+                    Ok(texture) => *sprite = Some(texture),
+                    Err(err) => problems.push(format!("`{} {}`: {}", key, value, err)),
+                },
+                None => problems.push(format!(
+                    "`{}`: no such sprite; sprites are hidden, flag, mine and empty",
+                    key
+                )),
+            }
+        } else {
+            let color = skin
+                .colors()
+                .into_iter()
+                .find(|(known, _)| *known == key)
+                .map(|(_, color)| color);
+            match color {
+                Some(color) => match parse_hex(value) {
+                    Some(parsed) => *color = parsed,
+                    None => problems.push(format!(
+                        "`{} {}`: not a colour; write #rrggbb or #rrggbbaa",
+                        key, value
+                    )),
+                },
+                None => problems.push(format!("`{}`: unknown setting", key)),
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(Theme { skin, font })
+    } else {
+        Err(format!("{}: {}", shown.display(), problems.join("; ")))
+    }
+}
+
+/// The `theme` console command.
+pub fn register(registry: &mut Registry<Handler>) {
+    registry.register(Command {
+        name: "theme",
+        usage: "<name>",
+        about: "Switches to `light`, `dark`, `auto` or a theme from themes/.",
+        run: switch,
+    });
+}
+
+fn switch(context: &mut Context, args: &[&str]) -> Result<String, String> {
+    let name = args[0];
+    match name.parse::<Appearance>() {
+        Ok(appearance) => {
+            context.live.set_theme("");
+            context.live.set_appearance(appearance);
+            context
+                .live
+                .set_dark(context.system.is_dark(&context.live.config));
+        }
+        Err(_) if names().iter().any(|known| known == name) => context.live.set_theme(name),
+        Err(_) => return Err(format!("no theme `{}` in {}", name, dir().display())),
+    }
+    Ok(String::new())
+}
+
+fn load_sprite(path: &Path) -> Result<Texture2D, String> {
+    let bytes = fs::read(path).map_err(|err| err.to_string())?;
+    let image = Image::from_file_with_format(&bytes, None).map_err(|err| err.to_string())?;
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Linear);
+    Ok(texture)
+}
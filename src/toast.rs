@@ -0,0 +1,159 @@
+//! Short notices that come and go on their own: unlocked achievements,
+//! personal bests, saved files and trouble with the connection.
+//!
+//! A few are up at once, stacked, and the rest wait their turn. They go
+//! where the board isn't, above or below it, or beside it on wide
+//! windows, and only cover it when it fills the whole window.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+use minesweeper_core::achievement::Achievement;
+
+use crate::achievements;
+use crate::font;
+use crate::i18n::t;
+use crate::scale;
+
+/// How many toasts are up at once.
+const SHOWN: usize = 3;
+const TEXT_SIZE: f32 = 30.0;
+const HEIGHT: f32 = 50.0;
+/// Space around and between toasts.
+const MARGIN: f32 = 10.0;
+
+/// What a toast is about, which sets its colour and how long it stays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Info,
+    Achievement,
+    Best,
+    Saved,
+    /// Something went wrong, such as the connection; stays up longer.
+    Warning,
+}
+
+impl Kind {
+    fn color(self) -> Color {
+        match self {
+            Kind::Info => WHITE,
+            Kind::Achievement => GOLD,
+            Kind::Best => SKYBLUE,
+            Kind::Saved => LIGHTGRAY,
+            Kind::Warning => ORANGE,
+        }
+    }
+
+    fn lasts(self) -> Duration {
+        match self {
+            Kind::Warning => Duration::from_secs(6),
+            _ => Duration::from_secs(4),
+        }
+    }
+}
+
+struct Toast {
+    kind: Kind,
+    text: String,
+    /// When it went up, once it has.
+    shown: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, kind: Kind, text: String) {
+        self.queue.push_back(Toast {
+            kind,
+            text,
+            shown: None,
+        });
+    }
+
+    /// Queues a plain message, such as a copied seed.
+    pub fn notify(&mut self, text: String) {
+        self.push(Kind::Info, text);
+    }
+
+    /// Queues a file saved, or why it couldn't be.
+    pub fn report(&mut self, saved: Result<String, String>) {
+        match saved {
+            Ok(text) => self.push(Kind::Saved, text),
+            Err(text) => self.push(Kind::Warning, text),
+        }
+    }
+
+    pub fn achievements(&mut self, unlocked: Vec<&'static Achievement>) {
+        for achievement in unlocked {
+            let text = t!(
+                "achievement-unlocked",
+                name = achievements::name(achievement)
+            );
+            self.push(Kind::Achievement, text);
+        }
+    }
+
+    /// Whether a toast is up, so the frame rate must stay up to take it
+    /// down on time.
+    pub fn is_active(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Takes down the toasts whose time is up and draws the rest clear of
+    /// `board`, the part of the window the board covers.
+    pub fn draw(&mut self, board: Rect) {
+        self.queue.retain(|toast| {
+            toast
+                .shown
+                .is_none_or(|shown| shown.elapsed() < toast.kind.lasts())
+        });
+        let shown = self.queue.len().min(SHOWN);
+        if shown == 0 {
+            return;
+        }
+        let (height, margin) = (scale::px(HEIGHT), scale::px(MARGIN));
+        let widths: Vec<f32> = self
+            .queue
+            .iter()
+            .take(shown)
+            .map(|toast| font::measure(&toast.text, TEXT_SIZE).width + scale::px(40.0))
+            .collect();
+        let widest = widths.iter().copied().fold(0.0, f32::max);
+        let stack = vec2(widest, shown as f32 * (height + margin)) + margin;
+        let area = place(board, stack);
+        let mut y = area.y + margin;
+        for (toast, width) in self.queue.iter_mut().zip(widths) {
+            toast.shown.get_or_insert_with(Instant::now);
+            let rect = Rect::new(area.x + (area.w - width) / 2.0, y, width, height);
+            draw_rectangle(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h,
+                Color::new(0.0, 0.0, 0.0, 0.8),
+            );
+            font::centred(&toast.text, rect, TEXT_SIZE, toast.kind.color());
+            y += height + margin;
+        }
+    }
+}
+
+/// The first part of the window outside `board` that fits a stack of
+/// toasts this size: above the board, below it, right of it or left of
+/// it. When none does, the top of the window.
+fn place(board: Rect, stack: Vec2) -> Rect {
+    let (width, height) = (screen_width(), screen_height());
+    [
+        Rect::new(0.0, 0.0, width, board.top()),
+        Rect::new(0.0, board.bottom(), width, height - board.bottom()),
+        Rect::new(board.right(), 0.0, width - board.right(), height),
+        Rect::new(0.0, 0.0, board.left(), height),
+    ]
+    .into_iter()
+    .find(|area| area.w >= stack.x && area.h >= stack.y)
+    .unwrap_or(Rect::new(0.0, 0.0, width, height))
+}
@@ -0,0 +1,87 @@
+//! Taps and long presses on touch screens. A tap reveals like a click and
+//! a finger held still on a cell flags it, since there is no right button
+//! to reach for.
+//!
+//! macroquad also turns each touch into left button presses, which would
+//! reveal the cell the moment a finger lands; the board ignores those
+//! while `is_down` and waits for the gesture instead.
+
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+
+/// How long a finger has to stay on a cell to flag it.
+const LONG_PRESS: Duration = Duration::from_millis(400);
+/// How far a finger can wander, in pixels, and still be holding still.
+const SLOP: f32 = 12.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    Tap,
+    LongPress,
+}
+
+struct Down {
+    id: u64,
+    since: Instant,
+    start: Vec2,
+    /// Moved too far or already recognised as a long press, so letting go
+    /// is not a tap.
+    spent: bool,
+}
+
+#[derive(Default)]
+pub struct Touch {
+    down: Option<Down>,
+    seen: bool,
+}
+
+impl Touch {
+    /// Follows the first finger on the screen and returns the gesture it
+    /// just finished, if any.
+    pub fn update(&mut self) -> Option<Gesture> {
+        for touch in touches() {
+            match touch.phase {
+                TouchPhase::Started if self.down.is_none() => {
+                    self.seen = true;
+                    self.down = Some(Down {
+                        id: touch.id,
+                        since: Instant::now(),
+                        start: touch.position,
+                        spent: false,
+                    });
+                }
+                TouchPhase::Moved => {
+                    if let Some(down) = self.down.as_mut().filter(|down| down.id == touch.id) {
+                        down.spent |= down.start.distance(touch.position) > SLOP;
+                    }
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    if let Some(down) = self.down.take_if(|down| down.id == touch.id) {
+                        if touch.phase == TouchPhase::Ended && !down.spent {
+                            return Some(Gesture::Tap);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let down = self.down.as_mut()?;
+        if !down.spent && down.since.elapsed() >= LONG_PRESS {
+            down.spent = true;
+            return Some(Gesture::LongPress);
+        }
+        None
+    }
+
+    /// Whether a finger is on the screen.
+    pub fn is_down(&self) -> bool {
+        self.down.is_some()
+    }
+
+    /// Whether the screen has been touched since the game started, i.e.
+    /// this is a touch device.
+    pub fn is_touch_device(&self) -> bool {
+        self.seen
+    }
+}
@@ -0,0 +1,60 @@
+//! Briefly marks what the last move did: the cell it was made on, and
+//! every cell it changed, which shows the whole of an opening.
+
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+use minesweeper_core::{Board, CellState};
+
+use crate::layout::Layout;
+
+const TINT: Color = Color::new(1.0, 0.85, 0.0, 0.35);
+const OUTLINE: Color = ORANGE;
+
+pub struct Trail {
+    since: Instant,
+    cell: (usize, usize),
+    /// Every cell's state before the move, row by row.
+    before: Vec<CellState>,
+}
+
+impl Trail {
+    /// Starts marking a move on `cell`, given the board before it.
+    pub fn new(before: &Board, cell: (usize, usize)) -> Self {
+        Self {
+            since: Instant::now(),
+            cell,
+            before: states(before),
+        }
+    }
+
+    /// Marks the move on `board`, fading out over `duration`. Cells that
+    /// change later, as an opening spreads, are marked as they do.
+    pub fn draw(&self, board: &Board, layout: &Layout, duration: Duration) {
+        let fade = 1.0 - self.since.elapsed().as_secs_f32() / duration.as_secs_f32();
+        if fade <= 0.0 {
+            return;
+        }
+        let with_fade = |color: Color| Color {
+            a: color.a * fade,
+            ..color
+        };
+        let changed = states(board)
+            .into_iter()
+            .zip(&self.before)
+            .enumerate()
+            .filter(|(_, (now, before))| now != *before);
+        for (i, _) in changed {
+            let tile = layout.tile(i / board.width(), i % board.width());
+            draw_rectangle(tile.x, tile.y, tile.w, tile.h, with_fade(TINT));
+        }
+        let tile = layout.tile(self.cell.0, self.cell.1);
+        draw_rectangle_lines(tile.x, tile.y, tile.w, tile.h, 4.0, with_fade(OUTLINE));
+    }
+}
+
+fn states(board: &Board) -> Vec<CellState> {
+    (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| board.cell(row, col).cell_state))
+        .collect()
+}
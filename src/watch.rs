@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often a watched file's modification time is checked.
+const POLL_EVERY: Duration = Duration::from_millis(500);
+
+/// Notices when a file is created, edited or deleted by polling its
+/// modification time, which is cheap enough to do from the frame loop.
+pub struct Watched {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl Watched {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            modified: modified(&path),
+            path,
+            checked: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Whether the file changed since the last call that returned true.
+    pub fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < POLL_EVERY {
+            return false;
+        }
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,111 @@
+mod terminal;
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use minesweeper_core::log::{self, Level};
+use minesweeper_core::{Board, CellState, CellType, Game, MoveError, Status};
+
+use terminal::{Key, Terminal};
+
+const HELP: &str = "arrows/hjkl move  space reveal  f flag  c chord  r restart  q quit";
+
+fn flag_value(args: &[String], flag: &str) -> Option<usize> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|value| value.parse().ok())
+}
+
+/// Foreground colour for each number, matching the classic palette.
+fn number_color(n: usize) -> &'static str {
+    match n {
+        1 => "\x1b[94m",
+        2 => "\x1b[32m",
+        3 => "\x1b[91m",
+        4 => "\x1b[34m",
+        5 => "\x1b[31m",
+        6 => "\x1b[36m",
+        7 => "\x1b[35m",
+        _ => "\x1b[90m",
+    }
+}
+
+fn render(game: &Game, cursor: (usize, usize)) -> String {
+    let board = game.board();
+    let mut out = String::from("\x1b[H\x1b[2J");
+
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            let cell = board.cell(row, col);
+            let glyph = match (&cell.cell_state, &cell.cell_type) {
+                (CellState::Hidden, _) => "\x1b[37m■".to_owned(),
+                (CellState::Flagged, _) => "\x1b[91m⚑".to_owned(),
+                (CellState::Visible, CellType::Empty) => "\x1b[90m·".to_owned(),
+                (CellState::Visible, CellType::Number(n)) => format!("{}{}", number_color(*n), n),
+                (CellState::Visible, CellType::Mine) => "\x1b[1;31m✹".to_owned(),
+            };
+            if (row, col) == cursor {
+                let _ = write!(out, "\x1b[7m{} \x1b[0m", glyph);
+            } else {
+                let _ = write!(out, "{} \x1b[0m", glyph);
+            }
+        }
+        out.push_str("\r\n");
+    }
+
+    let status = match game.status() {
+        Status::Playing => format!("{} mines left", game.mines_left()),
+        Status::Won => "You win! Press r to play again".to_owned(),
+        Status::Lost => "Boom! Press r to try again".to_owned(),
+    };
+    let _ = write!(out, "\r\n{}\r\n\x1b[90m{}\x1b[0m", status, HELP);
+    out
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    // Records on stderr would be drawn over the board, so nothing is logged
+    // unless a level is asked for
+    log::init_from_args(&args, Level::Off)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let width = flag_value(&args, "--width").unwrap_or(9);
+    let height = flag_value(&args, "--height").unwrap_or(9);
+    let mines = flag_value(&args, "--mines").unwrap_or(10);
+    let new_board = || {
+        Board::new(width, height, mines)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+    };
+
+    let mut game = Game::new(new_board()?);
+    let mut terminal = Terminal::enter()?;
+    let mut cursor = (0, 0);
+
+    loop {
+        print!("{}", render(&game, cursor));
+        io::stdout().flush()?;
+
+        let (row, col) = cursor;
+        match terminal.read_key()? {
+            Key::Up | Key::Char('k') => cursor.0 = row.saturating_sub(1),
+            Key::Down | Key::Char('j') => cursor.0 = (row + 1).min(height - 1),
+            Key::Left | Key::Char('h') => cursor.1 = col.saturating_sub(1),
+            Key::Right | Key::Char('l') => cursor.1 = (col + 1).min(width - 1),
+            Key::Enter | Key::Char(' ') => {
+                // Revealing an uncovered number chords it instead
+                if let Err(MoveError::AlreadyRevealed) = game.reveal(row, col) {
+                    let _ = game.chord(row, col);
+                }
+            }
+            Key::Char('f') => {
+                let _ = game.toggle_flag(row, col);
+            }
+            Key::Char('c') => {
+                let _ = game.chord(row, col);
+            }
+            Key::Char('r') => game = Game::new(new_board()?),
+            Key::Char('q') | Key::Escape => return Ok(()),
+            Key::Char(_) => {}
+        }
+    }
+}
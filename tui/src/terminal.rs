@@ -0,0 +1,80 @@
+//! Raw-mode terminal handling with plain ANSI escape sequences.
+
+use std::io::{self, Read, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Char(char),
+    Escape,
+}
+
+/// Puts the terminal in raw mode on the alternate screen and restores it on
+/// drop, even if the game panics.
+pub struct Terminal {
+    original: libc::termios,
+}
+
+impl Terminal {
+    pub fn enter() -> io::Result<Self> {
+        // SAFETY: termios is plain old data and tcgetattr fills it in.
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Alternate screen, hidden cursor
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(Self { original })
+    }
+
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        let mut stdin = io::stdin().lock();
+        loop {
+            let mut byte = [0; 1];
+            stdin.read_exact(&mut byte)?;
+            match byte[0] {
+                b'\r' | b'\n' => return Ok(Key::Enter),
+                0x1b => {
+                    // Arrow keys arrive as ESC [ A..D; a lone ESC is Escape
+                    let mut seq = [0; 2];
+                    if stdin.read_exact(&mut seq).is_err() || seq[0] != b'[' {
+                        return Ok(Key::Escape);
+                    }
+                    match seq[1] {
+                        b'A' => return Ok(Key::Up),
+                        b'B' => return Ok(Key::Down),
+                        b'C' => return Ok(Key::Right),
+                        b'D' => return Ok(Key::Left),
+                        _ => continue,
+                    }
+                }
+                b if b.is_ascii() => return Ok(Key::Char(b as char)),
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}